@@ -0,0 +1,70 @@
+//! Error type for vault operations.
+
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use thiserror::Error;
+
+use crate::domain::{ParseLinkCommentError, ParseLinkError, ParseNoteError};
+use crate::index::{IndexError, IndexedNote};
+use crate::infra::FsError;
+
+/// Errors returned by [`Vault`](super::Vault) operations.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The notes directory does not exist.
+    #[error("notes directory does not exist: {}", .0.display())]
+    MissingDirectory(PathBuf),
+
+    /// No note matches the reference.
+    #[error("note not found: '{0}'")]
+    NotFound(String),
+
+    /// More than one note matches the reference.
+    #[error("ambiguous note identifier: '{query}' matches {} notes", .matches.len())]
+    Ambiguous {
+        query: String,
+        matches: Vec<IndexedNote>,
+    },
+
+    /// A new note's `created` time is later than its `modified` time.
+    #[error(
+        "created ({}) is after modified ({})",
+        .created.to_rfc3339(),
+        .modified.to_rfc3339()
+    )]
+    CreatedAfterModified {
+        created: DateTime<Utc>,
+        modified: DateTime<Utc>,
+    },
+
+    /// Every generated note ID gave a filename that was already taken.
+    #[error("could not generate an unused note ID after {0} attempts")]
+    IdsExhausted(usize),
+
+    /// The note's metadata is invalid, e.g. an empty title.
+    #[error(transparent)]
+    InvalidNote(#[from] ParseNoteError),
+
+    /// The link is invalid, e.g. it has no rels.
+    #[error(transparent)]
+    InvalidLink(#[from] ParseLinkError),
+
+    /// A `<!-- den:link ... -->` comment is malformed.
+    #[error(transparent)]
+    InvalidLinkComment(#[from] ParseLinkCommentError),
+
+    /// The index keeps no body for the note, as it doesn't store bodies.
+    #[error("the index holds no body for '{0}'")]
+    NotStored(String),
+
+    /// Reading or writing a note file failed.
+    #[error(transparent)]
+    Fs(#[from] FsError),
+
+    /// The index could not be opened, queried or updated.
+    #[error(transparent)]
+    Index(#[from] IndexError),
+}
+
+/// Result type for vault operations.
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,43 @@
+//! High-level API for embedding den in other programs.
+//!
+//! A [`Vault`] wraps a notes directory and its index, and covers the common
+//! operations without going through the CLI:
+//!
+//! ```no_run
+//! use den::api::{ListFilter, NewNote, Vault};
+//! use den::domain::{Tag, Topic};
+//!
+//! # fn main() -> den::api::Result<()> {
+//! let mut vault = Vault::open("/home/me/notes")?;
+//! vault.refresh()?;
+//!
+//! let created = vault.create(NewNote {
+//!     title: "Reading list".to_string(),
+//!     topics: vec![Topic::new("books").unwrap()],
+//!     ..NewNote::default()
+//! })?;
+//! vault.tag(&created.note.id().to_string(), &Tag::new("todo").unwrap())?;
+//!
+//! for note in vault.list(&ListFilter::default())? {
+//!     println!("{} {}", note.id().prefix(), note.title());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The CLI handlers are built on this module, so it behaves the same as the
+//! corresponding `notes` commands.
+
+mod error;
+mod resolve;
+mod vault;
+
+#[cfg(test)]
+mod tests;
+
+pub use error::{Error, Result};
+pub use resolve::{ResolveResult, resolve_note};
+pub use vault::{
+    InboundLink, InboundMention, InboundReport, LinkCommentReport, ListFilter, NewNote, NoteUpdate,
+    SharedAttachment, SkippedLinkComment, Vault, VaultNote, VaultOptions, merge_or_add_link,
+};
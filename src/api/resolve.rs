@@ -0,0 +1,91 @@
+//! Resolving note references (ID prefix, title, alias or short ref).
+
+use crate::domain::Topic;
+use crate::index::{IndexRepository, IndexResult, IndexedNote};
+use crate::infra::{SlugOptions, slugify_with};
+
+/// Result of resolving a note identifier.
+// Short-lived and almost always `Unique`, so boxing it would only add an
+// allocation per lookup
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum ResolveResult {
+    /// Exactly one note matched.
+    Unique(IndexedNote),
+    /// Multiple notes matched (ambiguous).
+    Ambiguous(Vec<IndexedNote>),
+    /// No notes matched.
+    NotFound,
+}
+
+/// Resolves a note identifier to a unique note.
+///
+/// Resolution order:
+/// 1. ID prefix match (if input looks like a ULID prefix)
+/// 2. Exact title match
+/// 3. Alias match
+/// 4. Short ref match (`topic/slug`: a topic of the note and its title
+///    slugged with `slug`, as the filename is)
+///
+/// Returns `Unique` if exactly one note matches across all methods,
+/// `Ambiguous` if multiple notes match, or `NotFound` if no match.
+pub fn resolve_note<R: IndexRepository>(
+    index: &R,
+    identifier: &str,
+    slug: &SlugOptions,
+) -> IndexResult<ResolveResult> {
+    let identifier = identifier.trim();
+
+    // Check if it looks like a ULID prefix (alphanumeric, typically 8+ chars)
+    let looks_like_id =
+        identifier.len() >= 4 && identifier.chars().all(|c| c.is_ascii_alphanumeric());
+
+    let mut candidates: Vec<IndexedNote> = Vec::new();
+
+    // 1. Try ID prefix match if it looks like one
+    if looks_like_id {
+        let id_matches = index.find_by_id_prefix(identifier)?;
+
+        // If we get exactly one ID match, return it immediately
+        // ID matches are the most precise
+        if id_matches.len() == 1 {
+            return Ok(ResolveResult::Unique(
+                id_matches.into_iter().next().unwrap(),
+            ));
+        }
+
+        candidates.extend(id_matches);
+    }
+
+    // 2. Try exact title match
+    let title_matches = index.find_by_title(identifier)?;
+    candidates.extend(title_matches);
+
+    // 3. Try alias match
+    let alias_matches = index.find_by_alias(identifier)?;
+    candidates.extend(alias_matches);
+
+    // 4. Try short ref match (topic/slug)
+    if let Some((topic_str, short_slug)) = identifier.rsplit_once('/')
+        && let Ok(topic) = Topic::new(topic_str)
+    {
+        let ref_matches = index.list_by_topic(&topic, false)?;
+        candidates.extend(
+            ref_matches
+                .into_iter()
+                .filter(|n| slugify_with(n.title(), slug) == short_slug),
+        );
+    }
+
+    // Deduplicate by ID
+    candidates.sort_by_key(|a| a.id().to_string());
+    candidates.dedup_by(|a, b| a.id() == b.id());
+
+    match candidates.len() {
+        0 => Ok(ResolveResult::NotFound),
+        1 => Ok(ResolveResult::Unique(
+            candidates.into_iter().next().unwrap(),
+        )),
+        _ => Ok(ResolveResult::Ambiguous(candidates)),
+    }
+}
@@ -0,0 +1,658 @@
+use super::*;
+use crate::domain::{Link, NoteId, Rel, Tag, Topic};
+use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
+use crate::infra::read_note;
+use chrono::{TimeZone, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const PARENT_ID: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9A";
+const CHILD_ID: &str = "01HQ4A2R9PXJK4QZPW8V2R6T9B";
+
+fn write_note(dir: &Path, filename: &str, frontmatter: &str, body: &str) {
+    fs::write(
+        dir.join(filename),
+        format!("---\n{}\n---\n\n{}", frontmatter.trim(), body),
+    )
+    .unwrap();
+}
+
+/// A vault with two notes: "Parent" (topic `projects`) and "Child"
+/// (topic `projects/den`, tagged `draft`), modified in that order.
+fn sample_vault() -> (TempDir, Vault) {
+    let dir = TempDir::new().unwrap();
+    write_note(
+        dir.path(),
+        "01HQ3K5M7N-parent.md",
+        &format!(
+            "id: {}\ntitle: Parent\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\ntopics:\n  - projects",
+            PARENT_ID
+        ),
+        "Parent body.",
+    );
+    write_note(
+        dir.path(),
+        "01HQ4A2R9P-child.md",
+        &format!(
+            "id: {}\ntitle: Child\ncreated: 2024-01-16T10:30:00Z\nmodified: 2024-01-16T10:30:00Z\ntopics:\n  - projects/den\ntags:\n  - draft",
+            CHILD_ID
+        ),
+        "Child body.",
+    );
+    let mut vault = Vault::open(dir.path()).unwrap();
+    vault.refresh().unwrap();
+    (dir, vault)
+}
+
+fn id(s: &str) -> NoteId {
+    s.parse().unwrap()
+}
+
+fn tag(s: &str) -> Tag {
+    Tag::new(s).unwrap()
+}
+
+fn titles(notes: &[crate::index::IndexedNote]) -> Vec<&str> {
+    notes.iter().map(|n| n.title()).collect()
+}
+
+// ===========================================
+// Opening
+// ===========================================
+
+#[test]
+fn open_missing_directory_fails() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("nope");
+
+    let err = Vault::open(&missing).err().unwrap();
+
+    assert!(matches!(err, Error::MissingDirectory(path) if path == missing));
+}
+
+#[test]
+fn open_creates_index() {
+    let dir = TempDir::new().unwrap();
+
+    let vault = Vault::open(dir.path()).unwrap();
+
+    assert_eq!(vault.dir(), dir.path());
+    assert!(Vault::index_path(dir.path()).exists());
+}
+
+#[test]
+fn contains_finds_indexed_note() {
+    let (_dir, vault) = sample_vault();
+
+    assert!(vault.contains(&id(PARENT_ID)).unwrap());
+    assert!(!vault.contains(&NoteId::new()).unwrap());
+}
+
+// ===========================================
+// Resolving and reading
+// ===========================================
+
+#[test]
+fn resolve_by_title_and_prefix() {
+    let (_dir, vault) = sample_vault();
+
+    assert_eq!(vault.resolve("parent").unwrap().id(), &id(PARENT_ID));
+    assert_eq!(vault.resolve("01HQ4A2R").unwrap().id(), &id(CHILD_ID));
+}
+
+#[test]
+fn resolve_unknown_is_not_found() {
+    let (_dir, vault) = sample_vault();
+
+    let err = vault.resolve("Nope").unwrap_err();
+
+    assert!(matches!(&err, Error::NotFound(query) if query == "Nope"));
+    assert_eq!(err.to_string(), "note not found: 'Nope'");
+}
+
+#[test]
+fn resolve_shared_prefix_is_ambiguous() {
+    let (_dir, vault) = sample_vault();
+
+    let err = vault.resolve("01HQ").unwrap_err();
+
+    match err {
+        Error::Ambiguous { query, matches } => {
+            assert_eq!(query, "01HQ");
+            assert_eq!(matches.len(), 2);
+        }
+        other => panic!("expected Ambiguous, got {:?}", other),
+    }
+}
+
+#[test]
+fn get_reads_note_and_body() {
+    let (dir, vault) = sample_vault();
+
+    let note = vault.get("Child").unwrap();
+
+    assert_eq!(note.note.id(), &id(CHILD_ID));
+    assert_eq!(note.body.trim(), "Child body.");
+    assert_eq!(note.path, dir.path().join("01HQ4A2R9P-child.md"));
+}
+
+#[test]
+fn read_stored_needs_stored_bodies() {
+    let (dir, vault) = sample_vault();
+    let child = vault.resolve("Child").unwrap();
+
+    assert!(matches!(
+        vault.read_stored(&child),
+        Err(Error::NotStored(_))
+    ));
+
+    let mut index = SqliteIndex::open(&Vault::index_path(dir.path())).unwrap();
+    IndexBuilder::new(dir.path().to_path_buf())
+        .with_store_bodies(true)
+        .incremental_update(&mut index)
+        .unwrap();
+    fs::remove_file(dir.path().join("01HQ4A2R9P-child.md")).unwrap();
+
+    let note = vault.read_stored(&child).unwrap();
+    assert_eq!(note.note.title(), "Child");
+    assert_eq!(note.note.tags(), &[tag("draft")]);
+    assert_eq!(note.body.trim(), "Child body.");
+}
+
+// ===========================================
+// Listing and searching
+// ===========================================
+
+#[test]
+fn list_returns_newest_first() {
+    let (_dir, vault) = sample_vault();
+
+    let notes = vault.list(&ListFilter::default()).unwrap();
+
+    assert_eq!(titles(&notes), vec!["Child", "Parent"]);
+}
+
+#[test]
+fn list_filters_by_topic() {
+    let (_dir, vault) = sample_vault();
+    let mut filter = ListFilter {
+        topic: Some(Topic::new("projects").unwrap()),
+        ..ListFilter::default()
+    };
+
+    assert_eq!(titles(&vault.list(&filter).unwrap()), vec!["Parent"]);
+
+    filter.include_descendants = true;
+    assert_eq!(
+        titles(&vault.list(&filter).unwrap()),
+        vec!["Child", "Parent"]
+    );
+}
+
+#[test]
+fn list_filters_by_tags() {
+    let (_dir, vault) = sample_vault();
+    let filter = ListFilter {
+        tags: vec![tag("draft")],
+        ..ListFilter::default()
+    };
+
+    assert_eq!(titles(&vault.list(&filter).unwrap()), vec!["Child"]);
+}
+
+#[test]
+fn list_hides_archived_unless_asked() {
+    let (_dir, mut vault) = sample_vault();
+    vault.tag("Parent", &tag("archived")).unwrap();
+
+    let notes = vault.list(&ListFilter::default()).unwrap();
+    assert_eq!(titles(&notes), vec!["Child"]);
+
+    let filter = ListFilter {
+        include_archived: true,
+        ..ListFilter::default()
+    };
+    assert_eq!(vault.list(&filter).unwrap().len(), 2);
+}
+
+#[test]
+fn search_matches_title() {
+    let (_dir, vault) = sample_vault();
+
+    let results = vault.search("parent").unwrap();
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].note().id(), &id(PARENT_ID));
+}
+
+// ===========================================
+// Creating
+// ===========================================
+
+#[test]
+fn create_writes_and_indexes_note() {
+    let (_dir, mut vault) = sample_vault();
+
+    let created = vault
+        .create(NewNote {
+            title: "  Fresh Idea  ".to_string(),
+            topics: vec![Topic::new("ideas").unwrap()],
+            tags: vec![tag("todo")],
+            body: "First line.\n".to_string(),
+            ..NewNote::default()
+        })
+        .unwrap();
+
+    assert_eq!(created.note.title(), "Fresh Idea");
+    let filename = created.path.file_name().unwrap().to_string_lossy();
+    assert!(filename.ends_with("-fresh-idea.md"), "{}", filename);
+
+    let parsed = read_note(&created.path).unwrap();
+    assert_eq!(parsed.note.tags(), &[tag("todo")]);
+    assert_eq!(parsed.body.trim(), "First line.");
+    assert!(vault.contains(created.note.id()).unwrap());
+}
+
+#[test]
+fn create_never_overwrites_a_note_with_the_same_filename() {
+    let (dir, mut vault) = sample_vault();
+    let before = fs::read_dir(dir.path()).unwrap().count();
+
+    // Notes created in the same millisecond share an ID prefix and so a
+    // filename; each must still get its own file
+    let created: Vec<VaultNote> = (0..20)
+        .map(|i| {
+            vault
+                .create(NewNote {
+                    title: "Same Title".to_string(),
+                    body: format!("Note {}\n", i),
+                    ..NewNote::default()
+                })
+                .unwrap()
+        })
+        .collect();
+
+    let paths: HashSet<&PathBuf> = created.iter().map(|c| &c.path).collect();
+    assert_eq!(paths.len(), created.len());
+    assert_eq!(
+        fs::read_dir(dir.path()).unwrap().count(),
+        before + created.len()
+    );
+    for (i, note) in created.iter().enumerate() {
+        let parsed = read_note(&note.path).unwrap();
+        assert_eq!(parsed.body.trim(), format!("Note {}", i));
+    }
+}
+
+#[test]
+fn create_applies_frontmatter_defaults() {
+    let dir = TempDir::new().unwrap();
+    let mut options = VaultOptions::default();
+    options
+        .frontmatter
+        .defaults
+        .insert("status".to_string(), "seed".into());
+    let mut vault = Vault::open_with(dir.path(), options).unwrap();
+
+    let created = vault
+        .create(NewNote {
+            title: "Seeded".to_string(),
+            ..NewNote::default()
+        })
+        .unwrap();
+
+    let contents = fs::read_to_string(&created.path).unwrap();
+    assert!(contents.contains("status: seed"), "{}", contents);
+}
+
+#[test]
+fn create_writes_note_type() {
+    let (_dir, mut vault) = sample_vault();
+
+    let created = vault
+        .create(NewNote {
+            title: "Ada Lovelace".to_string(),
+            note_type: Some("person".to_string()),
+            ..NewNote::default()
+        })
+        .unwrap();
+
+    assert_eq!(created.note.note_type(), Some("person"));
+    let indexed = vault.resolve(&created.note.id().to_string()).unwrap();
+    assert_eq!(indexed.note_type(), Some("person"));
+}
+
+#[test]
+fn create_rejects_empty_title() {
+    let (_dir, mut vault) = sample_vault();
+
+    let err = vault
+        .create(NewNote {
+            title: "   ".to_string(),
+            ..NewNote::default()
+        })
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidNote(_)));
+}
+
+#[test]
+fn create_uses_given_timestamps() {
+    let (_dir, mut vault) = sample_vault();
+    let created_at = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+
+    let created = vault
+        .create(NewNote {
+            title: "Old Notes".to_string(),
+            created: Some(created_at),
+            ..NewNote::default()
+        })
+        .unwrap();
+    assert_eq!(created.note.created(), created_at);
+    assert!(created.note.modified() > created_at);
+
+    let err = vault
+        .create(NewNote {
+            title: "Backwards".to_string(),
+            created: Some(created_at),
+            modified: Some(created_at - chrono::Duration::days(1)),
+            ..NewNote::default()
+        })
+        .unwrap_err();
+    assert!(matches!(err, Error::CreatedAfterModified { .. }));
+}
+
+// ===========================================
+// Tagging
+// ===========================================
+
+#[test]
+fn tag_adds_tag_once() {
+    let (_dir, mut vault) = sample_vault();
+
+    let update = vault.tag("Parent", &tag("review")).unwrap();
+    assert!(update.changed);
+    assert_eq!(
+        read_note(&update.path).unwrap().note.tags(),
+        &[tag("review")]
+    );
+
+    let again = vault.tag("Parent", &tag("review")).unwrap();
+    assert!(!again.changed);
+
+    let tagged = vault.index().list_by_tag(&tag("review")).unwrap();
+    assert_eq!(titles(&tagged), vec!["Parent"]);
+}
+
+#[test]
+fn dated_tag_replaces_tag_of_same_name() {
+    let (_dir, mut vault) = sample_vault();
+
+    vault.tag("Parent", &tag("review-by:2024-06-01")).unwrap();
+    let update = vault.tag("Parent", &tag("review-by:2024-07-01")).unwrap();
+    assert!(update.changed);
+    assert_eq!(update.note.tags(), &[tag("review-by:2024-07-01")]);
+
+    // Untagging by name removes the dated tag
+    let update = vault.untag("Parent", &tag("review-by")).unwrap();
+    assert!(update.changed);
+    assert!(update.note.tags().is_empty());
+}
+
+#[test]
+fn untag_removes_tag() {
+    let (_dir, mut vault) = sample_vault();
+
+    let update = vault.untag("Child", &tag("draft")).unwrap();
+    assert!(update.changed);
+    assert!(update.note.tags().is_empty());
+
+    let again = vault.untag("Child", &tag("draft")).unwrap();
+    assert!(!again.changed);
+}
+
+#[test]
+fn describe_sets_and_clears_description() {
+    let (_dir, mut vault) = sample_vault();
+
+    let update = vault.describe("Parent", Some("The parent note")).unwrap();
+    assert!(update.changed);
+    assert_eq!(
+        read_note(&update.path).unwrap().note.description(),
+        Some("The parent note")
+    );
+    let indexed = vault.index().get_note(update.note.id()).unwrap().unwrap();
+    assert_eq!(indexed.description(), Some("The parent note"));
+
+    let again = vault.describe("Parent", Some("The parent note")).unwrap();
+    assert!(!again.changed);
+
+    let cleared = vault.describe("Parent", None).unwrap();
+    assert!(cleared.changed);
+    assert_eq!(cleared.note.description(), None);
+}
+
+#[test]
+fn tag_unknown_note_fails() {
+    let (_dir, mut vault) = sample_vault();
+
+    let err = vault.tag("Nope", &tag("review")).unwrap_err();
+
+    assert!(matches!(err, Error::NotFound(_)));
+}
+
+// ===========================================
+// Linking
+// ===========================================
+
+#[test]
+fn link_adds_link_to_resolved_target() {
+    let (_dir, mut vault) = sample_vault();
+    let parent = [Rel::new("parent").unwrap()];
+
+    let update = vault.link("Child", "Parent", &parent, None).unwrap();
+
+    assert!(update.changed);
+    assert_eq!(update.note.links().len(), 1);
+    assert_eq!(update.note.links()[0].target(), &id(PARENT_ID));
+    let backlinks = vault.index().backlinks(&id(PARENT_ID), None).unwrap();
+    assert_eq!(titles(&backlinks), vec!["Child"]);
+}
+
+#[test]
+fn link_merges_rels_and_is_idempotent() {
+    let (_dir, mut vault) = sample_vault();
+    let parent = [Rel::new("parent").unwrap()];
+    let see_also = [Rel::new("see-also").unwrap()];
+
+    vault.link("Child", "Parent", &parent, None).unwrap();
+    let merged = vault.link("Child", "Parent", &see_also, None).unwrap();
+    let again = vault.link("Child", "Parent", &parent, None).unwrap();
+
+    assert!(merged.changed);
+    assert_eq!(merged.note.links()[0].rel().len(), 2);
+    assert!(!again.changed);
+}
+
+#[test]
+fn link_accepts_unindexed_note_id() {
+    let (_dir, mut vault) = sample_vault();
+    let target = NoteId::new();
+    let rels = [Rel::new("see-also").unwrap()];
+
+    let update = vault
+        .link("Parent", &target.to_string(), &rels, Some("later"))
+        .unwrap();
+
+    assert_eq!(update.note.links()[0].target(), &target);
+    assert_eq!(update.note.links()[0].context(), Some("later"));
+}
+
+#[test]
+fn link_unknown_target_fails() {
+    let (_dir, mut vault) = sample_vault();
+    let rels = [Rel::new("see-also").unwrap()];
+
+    let err = vault.link("Parent", "Nope", &rels, None).unwrap_err();
+
+    assert!(matches!(&err, Error::NotFound(query) if query == "Nope"));
+}
+
+/// [`sample_vault`] with link titles enabled.
+fn titled_vault() -> (TempDir, Vault) {
+    let (dir, _) = sample_vault();
+    let mut options = VaultOptions::default();
+    options.frontmatter.link_titles = true;
+    let vault = Vault::open_with(dir.path(), options).unwrap();
+    (dir, vault)
+}
+
+#[test]
+fn link_records_target_title_when_enabled() {
+    let (_dir, mut vault) = titled_vault();
+    let parent = [Rel::new("parent").unwrap()];
+
+    let update = vault.link("Child", "Parent", &parent, None).unwrap();
+
+    assert_eq!(update.note.links()[0].title(), Some("Parent"));
+    let contents = fs::read_to_string(&update.path).unwrap();
+    assert!(
+        contents.contains(&format!("- id: {}\n  title: Parent\n", PARENT_ID)),
+        "{}",
+        contents
+    );
+}
+
+#[test]
+fn sync_link_titles_follows_renamed_targets() {
+    let (dir, mut vault) = titled_vault();
+    let parent = [Rel::new("parent").unwrap()];
+    let child = vault.link("Child", "Parent", &parent, None).unwrap();
+    let modified = child.note.modified();
+
+    let parent_path = dir.path().join("01HQ3K5M7N-parent.md");
+    let contents = fs::read_to_string(&parent_path).unwrap();
+    fs::write(
+        &parent_path,
+        contents.replace("title: Parent", "title: Renamed"),
+    )
+    .unwrap();
+    vault.refresh().unwrap();
+
+    assert_eq!(vault.sync_link_titles().unwrap(), 1);
+    let synced = read_note(&child.path).unwrap().note;
+    assert_eq!(synced.links()[0].title(), Some("Renamed"));
+    assert_eq!(synced.modified(), modified);
+    assert_eq!(vault.sync_link_titles().unwrap(), 0);
+}
+
+#[test]
+fn sync_link_titles_does_nothing_when_disabled() {
+    let (_dir, mut vault) = sample_vault();
+    let parent = [Rel::new("parent").unwrap()];
+    let update = vault.link("Child", "Parent", &parent, None).unwrap();
+
+    assert_eq!(vault.sync_link_titles().unwrap(), 0);
+    assert_eq!(update.note.links()[0].title(), None);
+}
+
+#[test]
+fn merge_or_add_link_replaces_context() {
+    let existing = vec![Link::new(id(PARENT_ID), vec!["parent"]).unwrap()];
+    let new = Link::with_context(id(PARENT_ID), vec!["parent"], "because").unwrap();
+
+    let (links, changed) = merge_or_add_link(&existing, &new);
+
+    assert!(changed);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].context(), Some("because"));
+}
+
+#[test]
+fn inbound_references_reports_links_mentions_and_shared_files() {
+    let (dir, mut vault) = sample_vault();
+    fs::write(dir.path().join("flow.png"), "png").unwrap();
+    let parent_path = dir.path().join("01HQ3K5M7N-parent.md");
+    let contents = fs::read_to_string(&parent_path).unwrap();
+    fs::write(&parent_path, contents + "\n![flow](flow.png)\n").unwrap();
+    let child_path = dir.path().join("01HQ4A2R9P-child.md");
+    let contents = fs::read_to_string(&child_path).unwrap().replace(
+        "Child body.",
+        "Child body, see [[parent]].\n![flow](/flow.png)",
+    );
+    fs::write(&child_path, contents).unwrap();
+    vault
+        .link("Child", "Parent", &[Rel::new("parent").unwrap()], None)
+        .unwrap();
+
+    let parent = vault.resolve("Parent").unwrap();
+    let report = vault.inbound_references(&parent).unwrap();
+
+    assert_eq!(report.links.len(), 1);
+    assert_eq!(report.links[0].source.title(), "Child");
+    assert_eq!(report.mentions.len(), 1);
+    assert_eq!(report.mentions[0].reference.text, "[[parent]]");
+    let line = fs::read_to_string(&child_path)
+        .unwrap()
+        .lines()
+        .position(|l| l.contains("[[parent]]"))
+        .unwrap();
+    assert_eq!(report.mentions[0].reference.line, line + 1);
+    assert_eq!(report.attachments.len(), 1);
+    assert_eq!(report.attachments[0].path, Path::new("flow.png"));
+
+    let child = vault.resolve("Child").unwrap();
+    let report = vault.inbound_references(&child).unwrap();
+    assert!(report.links.is_empty() && report.mentions.is_empty());
+    assert!(!report.is_empty());
+}
+
+// ===========================================
+// Link comments
+// ===========================================
+
+#[test]
+fn convert_link_comments_adds_links_and_removes_comments() {
+    let (dir, mut vault) = sample_vault();
+    let path = dir.path().join("01HQ4A2R9P-child.md");
+    let contents = fs::read_to_string(&path).unwrap().replace(
+        "Child body.",
+        "Child body.\n<!-- den:link \"Parent\" rel=parent context=\"up\" -->\nMore.",
+    );
+    fs::write(&path, contents).unwrap();
+    vault.refresh().unwrap();
+
+    let report = vault.convert_link_comments().unwrap();
+
+    assert_eq!(report.converted, 1);
+    assert_eq!(report.notes, 1);
+    assert!(report.skipped.is_empty());
+    let parsed = read_note(&path).unwrap();
+    assert_eq!(parsed.body.trim(), "Child body.\nMore.");
+    assert_eq!(parsed.note.links()[0].target(), &id(PARENT_ID));
+    assert_eq!(parsed.note.links()[0].context(), Some("up"));
+    let backlinks = vault.index().backlinks(&id(PARENT_ID), None).unwrap();
+    assert_eq!(titles(&backlinks), vec!["Child"]);
+}
+
+#[test]
+fn convert_link_comments_keeps_unresolved_comments() {
+    let (dir, mut vault) = sample_vault();
+    let path = dir.path().join("01HQ3K5M7N-parent.md");
+    let contents = fs::read_to_string(&path).unwrap().replace(
+        "Parent body.",
+        "<!-- den:link Nope rel=see-also -->\n<!-- den:link Child -->",
+    );
+    fs::write(&path, &contents).unwrap();
+    vault.refresh().unwrap();
+
+    let report = vault.convert_link_comments().unwrap();
+
+    assert_eq!(report.converted, 0);
+    let reasons: Vec<&str> = report.skipped.iter().map(|s| s.reason.as_str()).collect();
+    assert_eq!(reasons, vec!["note not found: 'Nope'", "missing rel="]);
+    assert_eq!(report.skipped[1].text, "<!-- den:link Child -->");
+    assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+}
@@ -0,0 +1,764 @@
+//! The [`Vault`] facade.
+
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::error::{Error, Result};
+use super::resolve::{ResolveResult, resolve_note};
+use crate::domain::{
+    ARCHIVED_TAG, BodyReference, LANG_FIELD, Link, LinkComment, Note, NoteId,
+    ParseLinkCommentError, Rel, TYPE_FIELD, Tag, Topic, find_link_comments, find_local_files,
+    find_note_references, remove_link_comments,
+};
+use crate::index::{
+    IndexBuilder, IndexRepository, IndexedNote, SearchResult, SqliteIndex, UpdateResult,
+};
+use crate::infra::{
+    FrontmatterOptions, FsError, INDEX_DIR, ScanOptions, SlugOptions, create_note_with,
+    generate_filename_with, read_note, write_note_with,
+};
+
+/// How many IDs [`Vault::create`] tries before giving up on finding an
+/// unused filename.
+const MAX_ID_ATTEMPTS: usize = 5;
+
+/// Settings for how a vault writes notes.
+#[derive(Debug, Clone, Default)]
+pub struct VaultOptions {
+    /// How filenames are derived from titles.
+    pub slug: SlugOptions,
+    /// Frontmatter key order and defaults for written notes.
+    pub frontmatter: FrontmatterOptions,
+    /// Directories left out of the index.
+    pub scan: ScanOptions,
+}
+
+/// A note read from disk.
+#[derive(Debug, Clone)]
+pub struct VaultNote {
+    pub note: Note,
+    pub body: String,
+    /// Absolute path of the note file.
+    pub path: PathBuf,
+}
+
+/// The outcome of an operation that may leave a note unchanged.
+#[derive(Debug, Clone)]
+pub struct NoteUpdate {
+    /// The note after the operation.
+    pub note: Note,
+    /// Absolute path of the note file.
+    pub path: PathBuf,
+    /// False when the note already had the requested state and was not written.
+    pub changed: bool,
+}
+
+/// A note to create with [`Vault::create`].
+#[derive(Debug, Clone, Default)]
+pub struct NewNote {
+    pub title: String,
+    pub description: Option<String>,
+    pub topics: Vec<Topic>,
+    pub tags: Vec<Tag>,
+    pub links: Vec<Link>,
+    /// Written as the `type` frontmatter field.
+    pub note_type: Option<String>,
+    /// Written as the `lang` frontmatter field; also picks the slug's
+    /// transliteration rules.
+    pub lang: Option<String>,
+    pub body: String,
+    /// Creation time, for notes written earlier; defaults to now, or to
+    /// `modified` if that is earlier.
+    pub created: Option<DateTime<Utc>>,
+    /// Last modification time; defaults to now, or to `created` if that is
+    /// later.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Which notes [`Vault::list`] returns.
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// Only notes in this topic.
+    pub topic: Option<Topic>,
+    /// With `topic`, also include notes in its descendants.
+    pub include_descendants: bool,
+    /// Only notes with all of these tags.
+    pub tags: Vec<Tag>,
+    /// Include notes tagged `archived`.
+    pub include_archived: bool,
+}
+
+/// A link comment that [`Vault::convert_link_comments`] left in place.
+#[derive(Debug, Clone)]
+pub struct SkippedLinkComment {
+    /// Absolute path of the note file.
+    pub path: PathBuf,
+    /// The comment's text.
+    pub text: String,
+    /// Why it wasn't converted.
+    pub reason: String,
+}
+
+/// The outcome of [`Vault::convert_link_comments`].
+#[derive(Debug, Clone, Default)]
+pub struct LinkCommentReport {
+    /// Comments turned into frontmatter links.
+    pub converted: usize,
+    /// Notes rewritten.
+    pub notes: usize,
+    pub skipped: Vec<SkippedLinkComment>,
+}
+
+/// A frontmatter link found by [`Vault::inbound_references`].
+#[derive(Debug, Clone)]
+pub struct InboundLink {
+    /// The linking note.
+    pub source: IndexedNote,
+    pub rels: Vec<Rel>,
+}
+
+/// A body reference found by [`Vault::inbound_references`].
+#[derive(Debug, Clone)]
+pub struct InboundMention {
+    /// The referencing note.
+    pub source: IndexedNote,
+    /// The reference, with its line counted from the start of the file.
+    pub reference: BodyReference,
+}
+
+/// A local file the audited note references that other notes reference
+/// too.
+#[derive(Debug, Clone)]
+pub struct SharedAttachment {
+    /// Path of the file relative to the notes directory.
+    pub path: PathBuf,
+    /// The other notes referencing it.
+    pub shared_with: Vec<IndexedNote>,
+}
+
+/// Everything referencing a note, from [`Vault::inbound_references`].
+#[derive(Debug, Clone, Default)]
+pub struct InboundReport {
+    pub links: Vec<InboundLink>,
+    pub mentions: Vec<InboundMention>,
+    pub attachments: Vec<SharedAttachment>,
+}
+
+impl InboundReport {
+    /// True when nothing references the note, so removing it breaks nothing.
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty() && self.mentions.is_empty() && self.attachments.is_empty()
+    }
+}
+
+/// A notes directory and its index.
+///
+/// Writes go to the note files first and are then applied to the index. If
+/// updating the index fails the file is still written, and the next
+/// [`refresh`](Vault::refresh) picks up the change.
+pub struct Vault {
+    dir: PathBuf,
+    index: SqliteIndex,
+    options: VaultOptions,
+}
+
+impl Vault {
+    /// Returns the index database path for a notes directory.
+    pub fn index_path(dir: &Path) -> PathBuf {
+        dir.join(INDEX_DIR).join("notes.db")
+    }
+
+    /// Opens the vault at `dir` with default options, creating the index if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        Self::open_with(dir, VaultOptions::default())
+    }
+
+    /// Opens the vault at `dir`, creating the index if needed.
+    pub fn open_with(dir: impl Into<PathBuf>, options: VaultOptions) -> Result<Self> {
+        let dir = dir.into();
+        if !dir.is_dir() {
+            return Err(Error::MissingDirectory(dir));
+        }
+        let index = SqliteIndex::open(&Self::index_path(&dir))?;
+        Ok(Self {
+            dir,
+            index,
+            options,
+        })
+    }
+
+    /// The notes directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The underlying index, for queries the facade doesn't cover.
+    pub fn index(&self) -> &SqliteIndex {
+        &self.index
+    }
+
+    /// Brings the index up to date with the note files.
+    pub fn refresh(&mut self) -> Result<UpdateResult> {
+        Ok(IndexBuilder::new(self.dir.clone())
+            .with_scan_options(self.options.scan.clone())
+            .incremental_update(&mut self.index)?)
+    }
+
+    /// Returns true if a note with `id` is in the index.
+    pub fn contains(&self, id: &NoteId) -> Result<bool> {
+        Ok(self.index.get_note(id)?.is_some())
+    }
+
+    /// Finds the note a reference points to: an ID prefix, title, alias or
+    /// `topic/slug` short ref.
+    pub fn resolve(&self, query: &str) -> Result<IndexedNote> {
+        match resolve_note(&self.index, query, &self.options.slug)? {
+            ResolveResult::Unique(note) => Ok(note),
+            ResolveResult::Ambiguous(matches) => Err(Error::Ambiguous {
+                query: query.to_string(),
+                matches,
+            }),
+            ResolveResult::NotFound => Err(Error::NotFound(query.to_string())),
+        }
+    }
+
+    /// Resolves a reference and reads the note from disk.
+    pub fn get(&self, query: &str) -> Result<VaultNote> {
+        let note = self.resolve(query)?;
+        self.read(&note)
+    }
+
+    /// Reads an indexed note from disk.
+    pub fn read(&self, note: &IndexedNote) -> Result<VaultNote> {
+        let path = self.dir.join(note.path());
+        let parsed = read_note(&path)?;
+        Ok(VaultNote {
+            note: parsed.note,
+            body: parsed.body,
+            path,
+        })
+    }
+
+    /// Reads an indexed note from the index alone, without its file.
+    ///
+    /// Needs an index that stores bodies (see
+    /// [`IndexBuilder::with_store_bodies`]). The note carries its metadata
+    /// and frontmatter links, but no other extra fields.
+    pub fn read_stored(&self, note: &IndexedNote) -> Result<VaultNote> {
+        let (stored, body) = self
+            .index
+            .stored_note(note.id())?
+            .ok_or_else(|| Error::NotStored(note.title().to_string()))?;
+        Ok(VaultNote {
+            note: stored,
+            body,
+            path: self.dir.join(note.path()),
+        })
+    }
+
+    /// Lists notes matching the filter, most recently modified first.
+    pub fn list(&self, filter: &ListFilter) -> Result<Vec<IndexedNote>> {
+        let mut notes = match &filter.topic {
+            Some(topic) => self
+                .index
+                .list_by_topic(topic, filter.include_descendants)?,
+            None => self.index.list_all()?,
+        };
+
+        for tag in &filter.tags {
+            let ids: HashSet<NoteId> = self
+                .index
+                .list_by_tag(tag)?
+                .into_iter()
+                .map(|n| n.id().clone())
+                .collect();
+            notes.retain(|n| ids.contains(n.id()));
+        }
+
+        if !filter.include_archived {
+            let archived = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+            notes.retain(|n| !n.tags().contains(&archived));
+        }
+
+        notes.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+        Ok(notes)
+    }
+
+    /// Full-text search, most relevant first.
+    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>> {
+        Ok(self.index.search(query)?)
+    }
+
+    /// Creates a note with an unused ID and filename and adds it to the
+    /// index.
+    ///
+    /// The configured frontmatter defaults are applied, and the filename is
+    /// derived from the ID prefix and title. A note created in the same
+    /// millisecond with the same title would get the same filename, so the
+    /// file is never overwritten; the note gets an ID from a later
+    /// millisecond instead. If the index can't be updated the
+    /// error is returned, though the note file has been written.
+    pub fn create(&mut self, new: NewNote) -> Result<VaultNote> {
+        let links = new
+            .links
+            .into_iter()
+            .map(|link| self.titled(link))
+            .collect::<Result<Vec<_>>>()?;
+        let title = new.title.trim();
+        let now = Utc::now();
+        let created = new
+            .created
+            .unwrap_or_else(|| new.modified.map_or(now, |modified| modified.min(now)));
+        let modified = new.modified.unwrap_or_else(|| created.max(now));
+        if created > modified {
+            return Err(Error::CreatedAfterModified { created, modified });
+        }
+        let extra: BTreeMap<_, _> = new
+            .note_type
+            .map(|t| (TYPE_FIELD.to_string(), t.into()))
+            .into_iter()
+            .chain(new.lang.map(|l| (LANG_FIELD.to_string(), l.into())))
+            .collect();
+
+        let mut attempts = 0;
+        let (note, path) = loop {
+            let id = NoteId::new();
+            let note = Note::builder(id.clone(), title, created, modified)
+                .description(new.description.clone())
+                .topics(new.topics.clone())
+                .tags(new.tags.clone())
+                .links(links.clone())
+                .extra(extra.clone())
+                .build()?;
+            let note = self.options.frontmatter.apply_defaults(note);
+
+            let path = self.dir.join(generate_filename_with(
+                &id,
+                title,
+                &self.options.slug.for_lang(note.lang().as_deref()),
+            ));
+            match create_note_with(&path, &note, &new.body, &self.options.frontmatter) {
+                Ok(()) => break (note, path),
+                Err(FsError::AlreadyExists { .. }) => {}
+                Err(e) => return Err(e.into()),
+            }
+            attempts += 1;
+            if attempts == MAX_ID_ATTEMPTS {
+                return Err(Error::IdsExhausted(attempts));
+            }
+            // The filename prefix is the ID's millisecond
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        self.refresh()?;
+
+        Ok(VaultNote {
+            note,
+            body: new.body,
+            path,
+        })
+    }
+
+    /// Adds a tag to a note.
+    pub fn tag(&mut self, query: &str, tag: &Tag) -> Result<NoteUpdate> {
+        let indexed = self.resolve(query)?;
+        self.update(&indexed, |note| {
+            if note.tags().contains(tag) {
+                return Ok(None);
+            }
+            // A dated tag replaces the tag of that name, moving its expiry
+            let mut tags: Vec<Tag> = note
+                .tags()
+                .iter()
+                .filter(|t| tag.expires().is_none() || t.name() != tag.name())
+                .cloned()
+                .collect();
+            tags.push(tag.clone());
+            rebuild(note, Utc::now(), |builder| builder.tags(tags)).map(Some)
+        })
+    }
+
+    /// Removes a tag from a note.
+    pub fn untag(&mut self, query: &str, tag: &Tag) -> Result<NoteUpdate> {
+        let indexed = self.resolve(query)?;
+        self.update(&indexed, |note| {
+            // A tag given without a date also removes its dated forms
+            let removed = |t: &Tag| t == tag || (tag.expires().is_none() && t.name() == tag.name());
+            if !note.tags().iter().any(removed) {
+                return Ok(None);
+            }
+            let tags: Vec<Tag> = note
+                .tags()
+                .iter()
+                .filter(|t| !removed(t))
+                .cloned()
+                .collect();
+            rebuild(note, Utc::now(), |builder| builder.tags(tags)).map(Some)
+        })
+    }
+
+    /// Sets a note's description, or removes it when `description` is `None`.
+    pub fn describe(&mut self, query: &str, description: Option<&str>) -> Result<NoteUpdate> {
+        let indexed = self.resolve(query)?;
+        self.update(&indexed, |note| {
+            if note.description() == description {
+                return Ok(None);
+            }
+            rebuild(note, Utc::now(), |builder| builder.description(description)).map(Some)
+        })
+    }
+
+    /// Links `source` to `target` with the given rels.
+    ///
+    /// The target may be a reference to an indexed note or the full ID of a
+    /// note that doesn't exist yet. An existing link to the same target gains
+    /// any new rels, and its context is replaced if one is given.
+    pub fn link(
+        &mut self,
+        source: &str,
+        target: &str,
+        rels: &[Rel],
+        context: Option<&str>,
+    ) -> Result<NoteUpdate> {
+        let source = self.resolve(source)?;
+        let target_id = self.resolve_target(target)?;
+        let rels: Vec<&str> = rels.iter().map(Rel::as_str).collect();
+        let link = match context {
+            Some(context) => Link::with_context(target_id, rels, context)?,
+            None => Link::new(target_id, rels)?,
+        };
+        self.add_link(&source, &link)
+    }
+
+    /// Resolves a link target, which may also be the full ID of a note that
+    /// isn't indexed.
+    fn resolve_target(&self, target: &str) -> Result<NoteId> {
+        match self.resolve(target) {
+            Ok(note) => Ok(note.id().clone()),
+            Err(Error::NotFound(query)) => query.parse().map_err(|_| Error::NotFound(query)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Adds a link to an indexed note, merging it into an existing link to
+    /// the same target.
+    pub fn add_link(&mut self, source: &IndexedNote, link: &Link) -> Result<NoteUpdate> {
+        let link = self.titled(link.clone())?;
+        self.update(source, |note| {
+            let (links, changed) = merge_or_add_link(note.links(), &link);
+            if !changed {
+                return Ok(None);
+            }
+            rebuild(note, Utc::now(), |builder| builder.links(links)).map(Some)
+        })
+    }
+
+    /// Turns `<!-- den:link "Target" rel=see-also -->` comments in note
+    /// bodies into frontmatter links, removing the comments.
+    ///
+    /// Targets are resolved like [`link`](Vault::link). Comments that are
+    /// malformed or whose target can't be resolved stay in the body and are
+    /// reported as skipped.
+    pub fn convert_link_comments(&mut self) -> Result<LinkCommentReport> {
+        let mut report = LinkCommentReport::default();
+        let all = ListFilter {
+            include_archived: true,
+            ..ListFilter::default()
+        };
+
+        for indexed in self.list(&all)? {
+            let current = self.read(&indexed)?;
+            let mut links = current.note.links().to_vec();
+            let mut converted = Vec::new();
+
+            for found in find_link_comments(&current.body) {
+                match self.comment_link(found.comment) {
+                    Ok(link) => {
+                        links = merge_or_add_link(&links, &link).0;
+                        converted.push(found.span);
+                    }
+                    Err(
+                        e @ (Error::NotFound(_)
+                        | Error::Ambiguous { .. }
+                        | Error::InvalidLink(_)
+                        | Error::InvalidLinkComment(_)),
+                    ) => report.skipped.push(SkippedLinkComment {
+                        path: current.path.clone(),
+                        text: found.text,
+                        reason: e.to_string(),
+                    }),
+                    Err(e) => return Err(e),
+                }
+            }
+            if converted.is_empty() {
+                continue;
+            }
+
+            let body = remove_link_comments(&current.body, &converted);
+            let note = rebuild(&current.note, Utc::now(), |builder| builder.links(links))?;
+            write_note_with(&current.path, &note, &body, &self.options.frontmatter)?;
+            report.converted += converted.len();
+            report.notes += 1;
+        }
+
+        if report.notes > 0 {
+            let _ = self.refresh();
+        }
+        Ok(report)
+    }
+
+    /// Sets the title of every frontmatter link to its target's current
+    /// title, when link titles are enabled, and returns how many notes were
+    /// rewritten.
+    ///
+    /// Only links are changed, so `modified` is left alone. Links to notes
+    /// that aren't indexed keep the title they have.
+    pub fn sync_link_titles(&mut self) -> Result<usize> {
+        if !self.options.frontmatter.link_titles {
+            return Ok(0);
+        }
+        let all = ListFilter {
+            include_archived: true,
+            ..ListFilter::default()
+        };
+
+        let mut rewritten = 0;
+        for indexed in self.list(&all)? {
+            let current = self.read(&indexed)?;
+            let links = current
+                .note
+                .links()
+                .iter()
+                .map(|link| self.titled(link.clone()))
+                .collect::<Result<Vec<_>>>()?;
+            let stale = links
+                .iter()
+                .zip(current.note.links())
+                .any(|(new, old)| new.title() != old.title());
+            if !stale {
+                continue;
+            }
+
+            let note = rebuild(&current.note, current.note.modified(), |builder| {
+                builder.links(links)
+            })?;
+            write_note_with(
+                &current.path,
+                &note,
+                &current.body,
+                &self.options.frontmatter,
+            )?;
+            rewritten += 1;
+        }
+
+        if rewritten > 0 {
+            let _ = self.refresh();
+        }
+        Ok(rewritten)
+    }
+
+    /// Finds everything referencing a note, to judge what removing or
+    /// archiving it would break: frontmatter links to it, references in
+    /// other notes' bodies, and local files it shares with other notes.
+    ///
+    /// Note files are read rather than the index, so the report includes
+    /// edits made since the last refresh. Sources are in title order.
+    pub fn inbound_references(&self, target: &IndexedNote) -> Result<InboundReport> {
+        let all = ListFilter {
+            include_archived: true,
+            ..ListFilter::default()
+        };
+        let names: Vec<&str> = std::iter::once(target.title())
+            .chain(target.aliases().iter().map(String::as_str))
+            .collect();
+        let root = self.dir.canonicalize().unwrap_or_else(|_| self.dir.clone());
+
+        let body = self.read(target)?.body;
+        let mut shared: BTreeMap<PathBuf, Vec<IndexedNote>> = self
+            .local_files(&root, target, &body)
+            .into_iter()
+            .map(|path| (path, Vec::new()))
+            .collect();
+
+        let mut sources = self.list(&all)?;
+        sources.retain(|n| n.id() != target.id());
+        sources.sort_by_key(|n| n.title().to_lowercase());
+
+        let mut report = InboundReport::default();
+        for indexed in sources {
+            let current = read_note(&self.dir.join(indexed.path()))?;
+            if let Some(link) = current
+                .note
+                .links()
+                .iter()
+                .find(|l| l.target() == target.id())
+            {
+                report.links.push(InboundLink {
+                    source: indexed.clone(),
+                    rels: link.rel().to_vec(),
+                });
+            }
+            for mut reference in find_note_references(&current.body, target.id(), &names) {
+                reference.line += current.body_line - 1;
+                report.mentions.push(InboundMention {
+                    source: indexed.clone(),
+                    reference,
+                });
+            }
+            if !shared.is_empty() {
+                for path in self.local_files(&root, &indexed, &current.body) {
+                    if let Some(users) = shared.get_mut(&path) {
+                        users.push(indexed.clone());
+                    }
+                }
+            }
+        }
+
+        report.attachments = shared
+            .into_iter()
+            .filter(|(_, users)| !users.is_empty())
+            .map(|(path, shared_with)| SharedAttachment { path, shared_with })
+            .collect();
+        Ok(report)
+    }
+
+    /// The existing files in the vault a note's body references, relative to
+    /// the (canonical) notes directory `root`. Paths starting with `/` are
+    /// relative to the notes directory, others to the note's directory.
+    fn local_files(&self, root: &Path, note: &IndexedNote, body: &str) -> Vec<PathBuf> {
+        let note_dir = note.path().parent().unwrap_or(Path::new(""));
+        find_local_files(body)
+            .into_iter()
+            .filter_map(|file| {
+                let path = match file.strip_prefix('/') {
+                    Some(rooted) => root.join(rooted),
+                    None => root.join(note_dir).join(&file),
+                };
+                let path = path.canonicalize().ok().filter(|p| p.is_file())?;
+                path.strip_prefix(root).ok().map(Path::to_path_buf)
+            })
+            .collect()
+    }
+
+    /// Records the target's title in a link when link titles are enabled
+    /// and the target is indexed.
+    fn titled(&self, link: Link) -> Result<Link> {
+        if !self.options.frontmatter.link_titles {
+            return Ok(link);
+        }
+        Ok(match self.index.get_note(link.target())? {
+            Some(target) => link.with_title(Some(target.title())),
+            None => link,
+        })
+    }
+
+    /// Builds the link a parsed link comment asks for.
+    fn comment_link(
+        &self,
+        comment: std::result::Result<LinkComment, ParseLinkCommentError>,
+    ) -> Result<Link> {
+        let comment = comment?;
+        let target_id = self.resolve_target(&comment.target)?;
+        let rels: Vec<&str> = comment.rels.iter().map(Rel::as_str).collect();
+        let link = match comment.context {
+            Some(context) => Link::with_context(target_id, rels, context)?,
+            None => Link::new(target_id, rels)?,
+        };
+        self.titled(link)
+    }
+
+    /// Reads a note, lets `change` produce an updated copy (or `None` to
+    /// leave it alone), then writes it and updates the index.
+    fn update(
+        &mut self,
+        indexed: &IndexedNote,
+        change: impl FnOnce(&Note) -> Result<Option<Note>>,
+    ) -> Result<NoteUpdate> {
+        let current = self.read(indexed)?;
+        let Some(updated) = change(&current.note)? else {
+            return Ok(NoteUpdate {
+                note: current.note,
+                path: current.path,
+                changed: false,
+            });
+        };
+
+        write_note_with(
+            &current.path,
+            &updated,
+            &current.body,
+            &self.options.frontmatter,
+        )?;
+        let _ = self.refresh();
+
+        Ok(NoteUpdate {
+            note: updated,
+            path: current.path,
+            changed: true,
+        })
+    }
+}
+
+/// Returns a copy of `note` with a new modified time and the fields set by `edit`.
+fn rebuild(
+    note: &Note,
+    modified: DateTime<Utc>,
+    edit: impl FnOnce(crate::domain::NoteBuilder) -> crate::domain::NoteBuilder,
+) -> Result<Note> {
+    let builder = Note::builder(note.id().clone(), note.title(), note.created(), modified)
+        .description(note.description().map(String::from))
+        .topics(note.topics().to_vec())
+        .aliases(note.aliases().to_vec())
+        .tags(note.tags().to_vec())
+        .links(note.links().to_vec())
+        .extra(note.extra().clone());
+    Ok(edit(builder).build()?)
+}
+
+/// Merge new link into existing links, or add if not present.
+/// Returns (updated_links, changed).
+pub fn merge_or_add_link(existing: &[Link], new: &Link) -> (Vec<Link>, bool) {
+    let mut links = existing.to_vec();
+
+    if let Some(pos) = links.iter().position(|l| l.target() == new.target()) {
+        let existing_link = &links[pos];
+
+        // Check if rels need merging
+        let mut merged_rels: Vec<Rel> = existing_link.rel().to_vec();
+        let mut added_rels = false;
+        for rel in new.rel() {
+            if !merged_rels.contains(rel) {
+                merged_rels.push(rel.clone());
+                added_rels = true;
+            }
+        }
+
+        // Check if context changed
+        let context_changed = new.context().is_some() && new.context() != existing_link.context();
+
+        if !added_rels && !context_changed {
+            return (links, false); // No change
+        }
+
+        // Build merged link
+        let context = new.context().or(existing_link.context());
+        let merged = match context {
+            Some(ctx) => Link::with_context(
+                new.target().clone(),
+                merged_rels.iter().map(|r| r.as_str()).collect(),
+                ctx,
+            )
+            .unwrap(),
+            None => Link::new(
+                new.target().clone(),
+                merged_rels.iter().map(|r| r.as_str()).collect(),
+            )
+            .unwrap(),
+        };
+        links[pos] = merged.with_title(new.title().or(existing_link.title()));
+        (links, true)
+    } else {
+        links.push(new.clone());
+        (links, true)
+    }
+}
@@ -0,0 +1,237 @@
+//! Command aliases (the `[alias]` section), expanded before parsing.
+//!
+//! An alias names a command line, e.g. `inbox = "ls inbox/ --untagged"`.
+//! When the command word isn't a built-in command but an alias, it is
+//! replaced by the alias's words, so `notes inbox -f json` runs
+//! `notes ls inbox/ --untagged -f json`. Aliases may use other aliases;
+//! built-in commands always win.
+
+use anyhow::{Result, anyhow, bail};
+use clap::CommandFactory;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+use crate::cli::Cli;
+
+/// Returns true if `name` is a built-in command or a clap alias of one.
+pub fn is_builtin(name: &str) -> bool {
+    name == "help" || Cli::command().find_subcommand(name).is_some()
+}
+
+/// Splits an alias definition into arguments the way a shell would:
+/// at whitespace, except inside single or double quotes, with `\` escaping
+/// the next character outside single quotes.
+///
+/// # Errors
+///
+/// Returns a description of the problem if a quote is left open or the
+/// definition ends in a lone `\`.
+pub fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("unclosed single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => word.push(c),
+                            None => return Err("unclosed double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("unclosed double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some(c) => word.get_or_insert_with(String::new).push(c),
+                None => return Err("trailing backslash".to_string()),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Finds the command word in `args` (program name first), skipping global
+/// options and their values. Returns `None` if there is none or it isn't
+/// valid UTF-8.
+fn command_position(args: &[OsString]) -> Option<usize> {
+    let cli = Cli::command();
+    let takes_value = |matches: &dyn Fn(&clap::Arg) -> bool| {
+        cli.get_arguments()
+            .any(|arg| matches(arg) && arg.get_action().takes_values())
+    };
+
+    let mut i = 1;
+    while i < args.len() {
+        let arg = args[i].to_str()?;
+        if arg == "--" {
+            return None;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=') && takes_value(&|a| a.get_long() == Some(long)) {
+                i += 1;
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // The rest of a cluster after an option taking a value is the
+            // value; if the option ends the cluster, the value follows
+            for (pos, c) in shorts.char_indices() {
+                if takes_value(&|a| a.get_short() == Some(c)) {
+                    if pos + c.len_utf8() == shorts.len() {
+                        i += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Replaces the command word in `args` (program name first) with its
+/// definition if it is an alias, repeatedly for aliases of aliases.
+///
+/// # Errors
+///
+/// Fails if an alias is empty or can't be split into words, or if aliases
+/// refer to each other in a loop.
+pub fn expand_aliases(
+    mut args: Vec<OsString>,
+    aliases: &BTreeMap<String, String>,
+) -> Result<Vec<OsString>> {
+    let mut expanded: Vec<String> = Vec::new();
+    while let Some(i) = command_position(&args) {
+        let name = args[i].to_string_lossy().into_owned();
+        if is_builtin(&name) {
+            break;
+        }
+        let Some(definition) = aliases.get(&name) else {
+            break;
+        };
+        if expanded.contains(&name) {
+            expanded.push(name);
+            bail!("alias loop: {}", expanded.join(" -> "));
+        }
+        let words =
+            split_words(definition).map_err(|e| anyhow!("invalid alias '{}': {}", name, e))?;
+        if words.is_empty() {
+            bail!("alias '{}' is empty", name);
+        }
+        args.splice(i..=i, words.into_iter().map(OsString::from));
+        expanded.push(name);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn expand(args: &[&str], aliases: &BTreeMap<String, String>) -> Result<Vec<String>> {
+        let args = args.iter().map(OsString::from).collect();
+        Ok(expand_aliases(args, aliases)?
+            .into_iter()
+            .map(|a| a.into_string().unwrap())
+            .collect())
+    }
+
+    #[test]
+    fn split_words_handles_quotes_and_escapes() {
+        assert_eq!(
+            split_words(r#"search "error handling" -t 'a b' x\ y "say \"hi\"""#).unwrap(),
+            vec!["search", "error handling", "-t", "a b", "x y", "say \"hi\""]
+        );
+        assert_eq!(split_words(r#"ls '' """#).unwrap(), vec!["ls", "", ""]);
+        assert!(split_words("ls 'open").is_err());
+        assert!(split_words("ls \\").is_err());
+    }
+
+    #[test]
+    fn expands_alias_after_global_options_keeping_extra_arguments() {
+        let aliases = aliases(&[("inbox", "ls -t inbox --untagged")]);
+
+        assert_eq!(
+            expand(
+                &["notes", "--dir", "inbox", "-v", "inbox", "--limit", "5"],
+                &aliases
+            )
+            .unwrap(),
+            vec![
+                "notes",
+                "--dir",
+                "inbox",
+                "-v",
+                "ls",
+                "-t",
+                "inbox",
+                "--untagged",
+                "--limit",
+                "5"
+            ]
+        );
+        assert_eq!(
+            expand(&["notes", "-vd", "x", "inbox"], &aliases).unwrap(),
+            vec!["notes", "-vd", "x", "ls", "-t", "inbox", "--untagged"]
+        );
+    }
+
+    #[test]
+    fn builtin_commands_win_and_unknown_words_are_left_alone() {
+        let aliases = aliases(&[("ls", "search x"), ("tdy", "recent")]);
+
+        assert_eq!(
+            expand(&["notes", "ls"], &aliases).unwrap(),
+            vec!["notes", "ls"]
+        );
+        assert_eq!(
+            expand(&["notes", "nope"], &aliases).unwrap(),
+            vec!["notes", "nope"]
+        );
+        assert_eq!(
+            expand(&["notes", "--", "tdy"], &aliases).unwrap(),
+            vec!["notes", "--", "tdy"]
+        );
+    }
+
+    #[test]
+    fn aliases_can_chain_but_not_loop() {
+        let chained = aliases(&[("w", "wk --limit 3"), ("wk", "recent --since 7d")]);
+        assert_eq!(
+            expand(&["notes", "w"], &chained).unwrap(),
+            vec!["notes", "recent", "--since", "7d", "--limit", "3"]
+        );
+
+        let looping = aliases(&[("a", "b -x"), ("b", "a")]);
+        let err = expand(&["notes", "a"], &looping).unwrap_err();
+        assert_eq!(err.to_string(), "alias loop: a -> b -> a");
+
+        let empty = aliases(&[("e", "  ")]);
+        assert!(expand(&["notes", "e"], &empty).is_err());
+    }
+}
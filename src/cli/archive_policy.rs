@@ -0,0 +1,97 @@
+//! Thresholds for `archive --suggest`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::domain::Topic;
+
+const DEFAULT_ARCHIVE_MONTHS: u32 = 12;
+
+/// When untouched notes become archive candidates (the `[archive]` section).
+///
+/// Topic thresholds also apply to descendant topics, with the most specific
+/// configured topic winning. A note in several topics uses the longest of
+/// their thresholds.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ArchivePolicy {
+    /// Months without modification before a note is suggested.
+    pub months: u32,
+    /// Per-topic overrides, e.g. `journal = 3`.
+    pub topics: BTreeMap<String, u32>,
+}
+
+impl Default for ArchivePolicy {
+    fn default() -> Self {
+        Self {
+            months: DEFAULT_ARCHIVE_MONTHS,
+            topics: BTreeMap::new(),
+        }
+    }
+}
+
+impl ArchivePolicy {
+    /// Returns the threshold in months for a note with the given topics.
+    pub fn months_for(&self, topics: &[Topic]) -> u32 {
+        topics
+            .iter()
+            .filter_map(|topic| self.topic_months(topic))
+            .max()
+            .unwrap_or(self.months)
+    }
+
+    fn topic_months(&self, topic: &Topic) -> Option<u32> {
+        std::iter::once(topic.clone())
+            .chain(topic.ancestors().into_iter().rev())
+            .find_map(|t| self.topics.get(&t.to_string()).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ArchivePolicy {
+        toml::from_str(
+            r#"
+            months = 6
+
+            [topics]
+            journal = 2
+            "work/reference" = 24
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn topics(paths: &[&str]) -> Vec<Topic> {
+        paths.iter().map(|p| Topic::new(p).unwrap()).collect()
+    }
+
+    #[test]
+    fn default_threshold_is_one_year() {
+        assert_eq!(ArchivePolicy::default().months_for(&[]), 12);
+    }
+
+    #[test]
+    fn unconfigured_topics_use_default() {
+        assert_eq!(policy().months_for(&topics(&["rust"])), 6);
+    }
+
+    #[test]
+    fn topic_threshold_applies_to_descendants() {
+        let policy = policy();
+        assert_eq!(policy.months_for(&topics(&["journal"])), 2);
+        assert_eq!(policy.months_for(&topics(&["journal/2024"])), 2);
+        assert_eq!(policy.months_for(&topics(&["work/reference/api"])), 24);
+        assert_eq!(policy.months_for(&topics(&["work"])), 6);
+    }
+
+    #[test]
+    fn multiple_topics_use_longest_threshold() {
+        assert_eq!(
+            policy().months_for(&topics(&["journal", "work/reference"])),
+            24
+        );
+    }
+}
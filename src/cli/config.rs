@@ -2,9 +2,19 @@
 
 use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
+use crate::cli::archive_policy::ArchivePolicy;
+use crate::cli::note_type::NoteTypes;
+use crate::cli::output::OutputOptions;
+use crate::cli::tag_style::TagStyles;
+use crate::cli::time_format::TimeFormat;
+use crate::domain::{Rel, SizeLimits};
+use crate::export::SiteLabels;
+use crate::index::{DEFAULT_BUSY_TIMEOUT, Tokenizer};
+use crate::infra::{FrontmatterOptions, ScanOptions, SlugOptions, find_program};
+
 /// Application configuration loaded from config file.
 #[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
@@ -20,6 +30,274 @@ pub struct Config {
     /// Named vault mappings
     #[serde(default)]
     pub vaults: HashMap<String, PathBuf>,
+
+    /// How notes are identified in human-readable listings
+    #[serde(default)]
+    pub note_refs: NoteRefStyle,
+
+    /// Prompt for a choice when a note reference matches several notes,
+    /// instead of failing
+    #[serde(default)]
+    pub interactive: bool,
+
+    /// Slug generation options for note filenames
+    #[serde(default)]
+    pub slug: SlugOptions,
+
+    /// How timestamps are shown in human-readable output
+    #[serde(default)]
+    pub time: TimeFormat,
+
+    /// Tag colors, emojis and groups for human-readable output
+    #[serde(default)]
+    pub tags: TagStyles,
+
+    /// Theme and color settings for human-readable output
+    #[serde(default)]
+    pub output: OutputOptions,
+
+    /// Thresholds for `archive --suggest`
+    #[serde(default)]
+    pub archive: ArchivePolicy,
+
+    /// Default fields and key order for note frontmatter
+    #[serde(default)]
+    pub frontmatter: FrontmatterOptions,
+
+    /// Relationship type settings
+    #[serde(default)]
+    pub rels: RelOptions,
+
+    /// Field mapping for `import`
+    #[serde(default)]
+    pub import: ImportOptions,
+
+    /// Frontmatter key renames for `migrate`
+    #[serde(default)]
+    pub migrate: MigrateOptions,
+
+    /// Index locking settings
+    #[serde(default)]
+    pub index: IndexOptions,
+
+    /// Directories left out of the index and scans
+    #[serde(default)]
+    pub scan: ScanOptions,
+
+    /// Note types: templates, required fields and styling
+    #[serde(default)]
+    pub types: NoteTypes,
+
+    /// Book metadata for `export --format epub` and page settings for
+    /// `export --format site`
+    #[serde(default)]
+    pub export: ExportOptions,
+
+    /// Whether notes must be filed under a topic
+    #[serde(default)]
+    pub topics: TopicOptions,
+
+    /// Note sizes above which `check` suggests a split
+    #[serde(default)]
+    pub check: SizeLimits,
+
+    /// How long notes stay in the trash
+    #[serde(default)]
+    pub trash: TrashOptions,
+
+    /// Titles, topics and tags of `daily` notes
+    #[serde(default)]
+    pub daily: DailyOptions,
+
+    /// Command aliases: name to command line, e.g.
+    /// `inbox = "ls inbox/ --sort modified --limit 20"`
+    #[serde(default)]
+    pub alias: BTreeMap<String, String>,
+}
+
+/// Daily note settings (the `[daily]` section).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DailyOptions {
+    /// strftime format of the note's title; defaults to "%Y-%m-%d"
+    pub title: String,
+
+    /// strftime format of the note's topic, e.g. "journal/%Y/%m"; empty for
+    /// no topic
+    pub topic: String,
+
+    /// Tags of daily notes; defaults to `["journal"]`
+    pub tags: Vec<String>,
+}
+
+impl Default for DailyOptions {
+    fn default() -> Self {
+        Self {
+            title: "%Y-%m-%d".to_string(),
+            topic: "journal/%Y/%m".to_string(),
+            tags: vec!["journal".to_string()],
+        }
+    }
+}
+
+/// Trash settings (the `[trash]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TrashOptions {
+    /// Days a note stays in the trash before `index` deletes it; unset keeps
+    /// trashed notes until `trash purge`
+    pub retention_days: Option<u32>,
+}
+
+/// Topic settings (the `[topics]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TopicOptions {
+    /// Notes that must have a topic: `true` for all notes, or a list of note
+    /// types. `check` reports them as errors and `new` refuses to create
+    /// them without `--allow-untopiced`
+    pub required: RequiredTopics,
+}
+
+impl TopicOptions {
+    /// Whether a note of the given type must have a topic.
+    pub fn required_for(&self, note_type: Option<&str>) -> bool {
+        match &self.required {
+            RequiredTopics::All(required) => *required,
+            RequiredTopics::Types(types) => {
+                note_type.is_some_and(|t| types.iter().any(|required| required == t))
+            }
+        }
+    }
+}
+
+/// Which notes `topics.required` applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum RequiredTopics {
+    /// Every note, or none
+    All(bool),
+    /// Notes of these types
+    Types(Vec<String>),
+}
+
+impl Default for RequiredTopics {
+    fn default() -> Self {
+        Self::All(false)
+    }
+}
+
+/// Export settings (the `[export]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExportOptions {
+    /// Title of exported books; defaults to "Notes"
+    pub title: Option<String>,
+
+    /// Author of exported books
+    pub author: Option<String>,
+
+    /// Language of exported books and sites as a BCP 47 tag; defaults to
+    /// "en"
+    pub language: Option<String>,
+
+    /// strftime format of dates on exported site pages; defaults to
+    /// "%Y-%m-%d"
+    pub date_format: Option<String>,
+
+    /// Lay exported sites out right to left; defaults to whether `language`
+    /// is written right to left
+    pub rtl: Option<bool>,
+
+    /// Navigation labels and headings of exported sites
+    pub labels: SiteLabels,
+}
+
+/// Relationship type settings (the `[rels]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RelOptions {
+    /// Rel pairs that should link in both directions, e.g. `["parent", "child"]`,
+    /// checked by `check`
+    pub reciprocal: Vec<(Rel, Rel)>,
+}
+
+impl RelOptions {
+    /// Returns the rel a link with `rel` should be answered with, if any.
+    pub fn reciprocal_of(&self, rel: &Rel) -> Option<&Rel> {
+        self.reciprocal.iter().find_map(|(a, b)| {
+            if a == rel {
+                Some(b)
+            } else if b == rel {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Import settings (the `[import]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    /// Frontmatter field mapping, source key to den field, e.g.
+    /// `keywords = "tags"`; `--map` flags take precedence
+    pub map: BTreeMap<String, String>,
+}
+
+/// Migration settings (the `[migrate]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MigrateOptions {
+    /// Frontmatter keys to rename, old key to new, e.g.
+    /// `category = "topics"`; an empty name removes the key
+    pub rename: BTreeMap<String, String>,
+}
+
+/// Index settings (the `[index]` section).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IndexOptions {
+    /// How long to wait for another process's lock on the index, in
+    /// milliseconds, before retrying
+    pub busy_timeout_ms: u64,
+
+    /// How note text is split into search terms; applied by `index --full`
+    pub tokenizer: Tokenizer,
+
+    /// Tokenizers for notes in particular languages (by their `lang`
+    /// field), e.g. `ja = "trigram"`; applied by any `index` run
+    pub languages: BTreeMap<String, Tokenizer>,
+
+    /// Keep a copy of each note's body in the index, for `show
+    /// --from-index`; applied by any `index` run
+    pub store_bodies: bool,
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: DEFAULT_BUSY_TIMEOUT.as_millis() as u64,
+            tokenizer: Tokenizer::default(),
+            languages: BTreeMap::new(),
+            store_bodies: false,
+        }
+    }
+}
+
+/// Style of note identifier shown in `ls` and `search` output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteRefStyle {
+    /// ULID prefix, e.g. `01HQ3K5M7N` (default)
+    #[default]
+    Id,
+    /// Primary topic plus title slug, e.g. `rust/api-design`, or the ULID
+    /// prefix for a note without topics
+    Ref,
+    /// ULID prefix followed by the short ref
+    Both,
 }
 
 /// Result of resolving the notes directory.
@@ -227,14 +505,32 @@ impl Config {
     /// 1. Config file `editor` setting
     /// 2. $EDITOR environment variable
     /// 3. $VISUAL environment variable
-    /// 4. "vi" as fallback
+    /// 4. The platform default (see [`default_editor`])
     pub fn editor(&self) -> String {
         self.editor
             .clone()
             .filter(|s| !s.is_empty())
             .or_else(|| std::env::var("EDITOR").ok().filter(|s| !s.is_empty()))
             .or_else(|| std::env::var("VISUAL").ok().filter(|s| !s.is_empty()))
-            .unwrap_or_else(|| "vi".to_string())
+            .unwrap_or_else(default_editor)
+    }
+}
+
+/// The editor used when none is configured: "vi", or on Windows VS Code
+/// ("code --wait") if it is on `PATH` and "notepad" otherwise.
+fn default_editor() -> String {
+    platform_editor(cfg!(windows), || find_program("code").is_some()).to_string()
+}
+
+/// Picks the default editor for a platform; `has_code` is only asked on
+/// Windows.
+fn platform_editor(windows: bool, has_code: impl FnOnce() -> bool) -> &'static str {
+    if !windows {
+        "vi"
+    } else if has_code() {
+        "code --wait"
+    } else {
+        "notepad"
     }
 }
 
@@ -251,6 +547,7 @@ mod tests {
             editor: None,
             default_vault: Some("personal".to_string()),
             vaults,
+            ..Default::default()
         }
     }
 
@@ -268,6 +565,7 @@ mod tests {
             editor: None,
             default_vault: None,
             vaults: HashMap::new(),
+            ..Default::default()
         };
         let cli_dir = PathBuf::from("/cli/notes");
         assert_eq!(
@@ -284,6 +582,7 @@ mod tests {
             editor: None,
             default_vault: None,
             vaults: HashMap::new(),
+            ..Default::default()
         };
         assert_eq!(config.notes_dir(None), PathBuf::from("/config/notes"));
     }
@@ -301,6 +600,13 @@ mod tests {
         assert!(path.ends_with("notes/config.toml"));
     }
 
+    #[test]
+    fn platform_editor_falls_back_to_vs_code_then_notepad_on_windows() {
+        assert_eq!(platform_editor(false, || unreachable!()), "vi");
+        assert_eq!(platform_editor(true, || true), "code --wait");
+        assert_eq!(platform_editor(true, || false), "notepad");
+    }
+
     #[test]
     fn editor_uses_config_setting() {
         let config = Config {
@@ -308,6 +614,7 @@ mod tests {
             editor: Some("nvim".to_string()),
             default_vault: None,
             vaults: HashMap::new(),
+            ..Default::default()
         };
         assert_eq!(config.editor(), "nvim");
     }
@@ -319,6 +626,7 @@ mod tests {
             editor: Some("".to_string()),
             default_vault: None,
             vaults: HashMap::new(),
+            ..Default::default()
         };
         // Should fall through to env vars or default, not use empty string
         // This test verifies empty config editor is skipped
@@ -371,6 +679,7 @@ mod tests {
             editor: None,
             default_vault: None,
             vaults: HashMap::new(),
+            ..Default::default()
         };
         let resolved = config.resolve_notes_dir(None, None).unwrap();
         assert_eq!(resolved.path, PathBuf::from("/legacy/notes"));
@@ -426,4 +735,18 @@ mod tests {
         assert_eq!(vaults[0].0, "personal");
         assert_eq!(vaults[1].0, "work");
     }
+
+    #[test]
+    fn topics_required_for_all_notes_or_listed_types() {
+        let config: Config = toml::from_str("[topics]\nrequired = true\n").unwrap();
+        assert!(config.topics.required_for(None));
+        assert!(config.topics.required_for(Some("person")));
+
+        let config: Config = toml::from_str("[topics]\nrequired = [\"meeting\"]\n").unwrap();
+        assert!(!config.topics.required_for(None));
+        assert!(!config.topics.required_for(Some("person")));
+        assert!(config.topics.required_for(Some("meeting")));
+
+        assert!(!Config::default().topics.required_for(None));
+    }
 }
@@ -1,6 +1,6 @@
 //! Date filter parsing for CLI commands.
 
-use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 
 /// A date filter that matches timestamps on or after a threshold.
 #[derive(Debug, Clone)]
@@ -52,6 +52,29 @@ impl DateFilter {
     }
 }
 
+/// Parses a point in time given on the command line.
+///
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM[:SS]` and `YYYY-MM-DD` (midnight).
+/// Times without an offset are taken as UTC.
+pub fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, String> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(Utc.from_utc_datetime(&dt));
+        }
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| {
+        format!(
+            "invalid date (expected YYYY-MM-DD, \"YYYY-MM-DD HH:MM\" or RFC 3339): {}",
+            s
+        )
+    })?;
+    Ok(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,4 +124,19 @@ mod tests {
         let before = Utc.with_ymd_and_hms(2024, 1, 14, 23, 59, 59).unwrap();
         assert!(!filter.matches(before));
     }
+
+    #[test]
+    fn parse_timestamp_formats() {
+        let midnight = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+        assert_eq!(parse_timestamp("2023-05-01").unwrap(), midnight);
+        assert_eq!(
+            parse_timestamp("2023-05-01 09:30").unwrap(),
+            Utc.with_ymd_and_hms(2023, 5, 1, 9, 30, 0).unwrap()
+        );
+        assert_eq!(
+            parse_timestamp("2023-05-01T02:00:00+02:00").unwrap(),
+            midnight
+        );
+        assert!(parse_timestamp("May 1st").is_err());
+    }
 }
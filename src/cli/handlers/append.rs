@@ -0,0 +1,249 @@
+//! Append and prepend command handlers.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+
+use super::index_db_path;
+use super::resolve::require_note;
+use crate::cli::AppendArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{Note, parse_heading};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{read_note, write_note_with};
+
+/// Where new text goes in the body or section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placement {
+    Append,
+    Prepend,
+}
+
+/// Result of append/prepend for JSON output.
+#[derive(Debug, Serialize)]
+pub struct AppendResult {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    /// Heading the text was added under, if any.
+    pub heading: Option<String>,
+}
+
+/// Finds the section under `heading` as a range of line indices: the
+/// heading line itself and the end of its content (exclusive). Headings
+/// inside fenced code blocks are ignored.
+fn find_section(lines: &[&str], heading: &str) -> Option<(usize, usize)> {
+    let mut in_fence = false;
+    let mut section: Option<(usize, u8)> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((level, title)) = parse_heading(line) else {
+            continue;
+        };
+        match section {
+            None if title.eq_ignore_ascii_case(heading) => section = Some((i, level)),
+            Some((start, section_level)) if level <= section_level => return Some((start, i)),
+            _ => {}
+        }
+    }
+
+    section.map(|(start, _)| (start, lines.len()))
+}
+
+/// Removes blank lines from both ends.
+fn trim_blank_lines<'a>(mut lines: &'a [&'a str]) -> &'a [&'a str] {
+    while let [first, rest @ ..] = lines
+        && first.trim().is_empty()
+    {
+        lines = rest;
+    }
+    while let [rest @ .., last] = lines
+        && last.trim().is_empty()
+    {
+        lines = rest;
+    }
+    lines
+}
+
+/// Adds a block of text at the start or end of `body`.
+fn add_block(body: &str, text: &str, placement: Placement) -> String {
+    let body = body.trim_matches('\n');
+    if body.trim().is_empty() {
+        return format!("{}\n", text);
+    }
+    match placement {
+        Placement::Append => format!("{}\n\n{}\n", body.trim_end(), text),
+        Placement::Prepend => format!("{}\n\n{}\n", text, body),
+    }
+}
+
+/// Adds `text` to a note body, optionally under a heading.
+///
+/// Without a heading the text goes at the start or end of the body. With
+/// one it goes at the start or end of that heading's section; a missing
+/// heading is created as a level-2 heading at the end of the body. Blocks
+/// are separated by a single blank line.
+pub(crate) fn add_to_body(
+    body: &str,
+    text: &str,
+    heading: Option<&str>,
+    placement: Placement,
+) -> String {
+    let Some(heading) = heading else {
+        return add_block(body, text, placement);
+    };
+
+    let lines: Vec<&str> = body.lines().collect();
+    let Some((start, end)) = find_section(&lines, heading) else {
+        let section = format!("## {}\n\n{}", heading, text);
+        return add_block(body, &section, Placement::Append);
+    };
+
+    let content = trim_blank_lines(&lines[start + 1..end]);
+    let text_lines: Vec<&str> = text.lines().collect();
+    let (first, second) = match placement {
+        Placement::Append => (content, text_lines.as_slice()),
+        Placement::Prepend => (text_lines.as_slice(), content),
+    };
+
+    let mut out: Vec<&str> = lines[..=start].to_vec();
+    out.push("");
+    out.extend_from_slice(first);
+    if !first.is_empty() && !second.is_empty() {
+        out.push("");
+    }
+    out.extend_from_slice(second);
+    if end < lines.len() {
+        out.push("");
+        out.extend_from_slice(&lines[end..]);
+    }
+
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Cleans up the heading argument, accepting `Log` as well as `## Log`.
+fn normalize_heading(heading: &str) -> Result<String> {
+    let heading = heading.trim().trim_start_matches('#').trim();
+    if heading.is_empty() {
+        bail!("heading cannot be empty");
+    }
+    Ok(heading.to_string())
+}
+
+fn read_text(args: &AppendArgs) -> Result<String> {
+    let text = match &args.text {
+        Some(text) => text.clone(),
+        None => {
+            let mut text = String::new();
+            std::io::stdin()
+                .read_to_string(&mut text)
+                .with_context(|| "failed to read text from stdin")?;
+            text
+        }
+    };
+
+    let text = text.trim_start_matches(['\r', '\n']).trim_end();
+    if text.is_empty() {
+        bail!("no text to add");
+    }
+    Ok(text.to_string())
+}
+
+fn add_to_note(
+    args: &AppendArgs,
+    notes_dir: &Path,
+    config: &Config,
+    placement: Placement,
+) -> Result<()> {
+    let heading = args.heading.as_deref().map(normalize_heading).transpose()?;
+    let text = read_text(args)?;
+
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let indexed_note = require_note(&index, &args.note, "note", config)?;
+
+    let file_path = notes_dir.join(indexed_note.path());
+    let parsed = read_note(&file_path)
+        .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+
+    let body = add_to_body(&parsed.body, &text, heading.as_deref(), placement);
+
+    let updated_note = Note::builder(
+        parsed.note.id().clone(),
+        parsed.note.title(),
+        parsed.note.created(),
+        Utc::now(),
+    )
+    .description(parsed.note.description().map(|s| s.to_string()))
+    .topics(parsed.note.topics().to_vec())
+    .aliases(parsed.note.aliases().to_vec())
+    .tags(parsed.note.tags().to_vec())
+    .links(parsed.note.links().to_vec())
+    .extra(parsed.note.extra().clone())
+    .build()
+    .with_context(|| "failed to rebuild note")?;
+
+    write_note_with(&file_path, &updated_note, &body, &config.frontmatter)
+        .with_context(|| "failed to write updated note")?;
+
+    // Update index
+    if let Ok(mut idx) = SqliteIndex::open(&db_path) {
+        let builder = IndexBuilder::new(notes_dir.to_path_buf());
+        let _ = builder.incremental_update(&mut idx);
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            let verb = match placement {
+                Placement::Append => "Appended to",
+                Placement::Prepend => "Prepended to",
+            };
+            let section = heading
+                .as_deref()
+                .map(|h| format!(" under '{}'", h))
+                .unwrap_or_default();
+            println!(
+                "{} '{}' [{}]{}",
+                verb,
+                updated_note.title(),
+                updated_note.id().prefix(),
+                section
+            );
+        }
+        OutputFormat::Json => {
+            let result = AppendResult {
+                id: updated_note.id().to_string(),
+                title: updated_note.title().to_string(),
+                path: file_path.to_string_lossy().to_string(),
+                heading,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", file_path.display()),
+    }
+
+    Ok(())
+}
+
+pub fn handle_append(args: &AppendArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    add_to_note(args, notes_dir, config, Placement::Append)
+}
+
+pub fn handle_prepend(args: &AppendArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    add_to_note(args, notes_dir, config, Placement::Prepend)
+}
@@ -1,20 +1,26 @@
-//! Archive command handlers (archive, unarchive).
+//! Archive command handlers (archive, archive --suggest, unarchive).
 
 use anyhow::{Context, Result, bail};
-use chrono::Utc;
+use chrono::{DateTime, Months, Utc};
 use serde::Serialize;
-use std::path::Path;
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 
+use super::batch::{NoteOutcome, STDIN_NOTES, read_stdin_notes, run_batch};
 use super::index_db_path;
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
-use crate::cli::output::{Output, OutputFormat};
+use super::resolve::require_note;
+use crate::cli::archive_policy::ArchivePolicy;
+use crate::cli::config::Config;
+use crate::cli::output::{ArchiveCandidateListing, Output, OutputFormat};
 use crate::cli::{ArchiveArgs, UnarchiveArgs};
-use crate::domain::{Note, Tag};
+use crate::domain::{Note, NoteId, Tag};
 use crate::index::{IndexBuilder, SqliteIndex};
-use crate::infra::{read_note, write_note};
+use crate::infra::{
+    FrontmatterOptions, ParsedNote, read_note, scan_notes_directory_with, write_note_with,
+};
 
-/// The canonical tag used to mark archived notes.
-pub const ARCHIVED_TAG: &str = "archived";
+pub use crate::domain::ARCHIVED_TAG;
 
 /// Result type for archive/unarchive operations.
 #[derive(Debug, Serialize)]
@@ -25,203 +31,376 @@ pub struct ArchiveResult {
     pub path: String,
 }
 
-/// Archive a note by adding the 'archived' tag.
-pub fn handle_archive(args: &ArchiveArgs, notes_dir: &Path) -> Result<()> {
+/// Adds the 'archived' tag to a parsed note and writes it back to disk.
+fn archive_file(
+    file_path: &Path,
+    parsed: &ParsedNote,
+    frontmatter: &FrontmatterOptions,
+) -> Result<Note> {
+    let mut tags = parsed.note.tags().to_vec();
+    tags.push(Tag::new(ARCHIVED_TAG).expect("archived is a valid tag name"));
+
+    let updated_note = Note::builder(
+        parsed.note.id().clone(),
+        parsed.note.title(),
+        parsed.note.created(),
+        Utc::now(),
+    )
+    .description(parsed.note.description().map(|s| s.to_string()))
+    .topics(parsed.note.topics().to_vec())
+    .aliases(parsed.note.aliases().to_vec())
+    .tags(tags)
+    .links(parsed.note.links().to_vec())
+    .extra(parsed.note.extra().clone())
+    .build()
+    .with_context(|| "failed to rebuild note")?;
+
+    write_note_with(file_path, &updated_note, &parsed.body, frontmatter)
+        .with_context(|| "failed to write updated note")?;
+
+    Ok(updated_note)
+}
+
+/// Removes the 'archived' tag from a parsed note and writes it back to disk.
+fn unarchive_file(
+    file_path: &Path,
+    parsed: &ParsedNote,
+    frontmatter: &FrontmatterOptions,
+) -> Result<Note> {
+    let tags: Vec<Tag> = parsed
+        .note
+        .tags()
+        .iter()
+        .filter(|t| t.as_str() != ARCHIVED_TAG)
+        .cloned()
+        .collect();
+
+    let updated_note = Note::builder(
+        parsed.note.id().clone(),
+        parsed.note.title(),
+        parsed.note.created(),
+        Utc::now(),
+    )
+    .description(parsed.note.description().map(|s| s.to_string()))
+    .topics(parsed.note.topics().to_vec())
+    .aliases(parsed.note.aliases().to_vec())
+    .tags(tags)
+    .links(parsed.note.links().to_vec())
+    .extra(parsed.note.extra().clone())
+    .build()
+    .with_context(|| "failed to rebuild note")?;
+
+    write_note_with(file_path, &updated_note, &parsed.body, frontmatter)
+        .with_context(|| "failed to write updated note")?;
+
+    Ok(updated_note)
+}
+
+/// Archives or unarchives the note `query` refers to, leaving notes already
+/// in that state untouched. The index is not updated.
+fn set_archived(
+    index: &SqliteIndex,
+    query: &str,
+    archived: bool,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<NoteOutcome<ArchiveResult>> {
     let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag name");
 
+    let indexed_note = require_note(index, query, "note", config)?;
+
+    let file_path = notes_dir.join(indexed_note.path());
+    let parsed = read_note(&file_path)
+        .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+
+    // Idempotency: already archived (or not archived)
+    let changed = parsed.note.tags().contains(&archived_tag) != archived;
+    let note = if !changed {
+        parsed.note
+    } else if archived {
+        archive_file(&file_path, &parsed, &config.frontmatter)?
+    } else {
+        unarchive_file(&file_path, &parsed, &config.frontmatter)?
+    };
+
+    let message = match (archived, changed) {
+        (true, true) => format!("Archived '{}' [{}]", note.title(), note.id().prefix()),
+        (true, false) => format!("'{}' is already archived", note.title()),
+        (false, true) => format!("Unarchived '{}' [{}]", note.title(), note.id().prefix()),
+        (false, false) => format!("'{}' is not archived", note.title()),
+    };
+    Ok(NoteOutcome {
+        result: ArchiveResult {
+            id: note.id().to_string(),
+            title: note.title().to_string(),
+            archived,
+            path: file_path.to_string_lossy().to_string(),
+        },
+        message,
+        path: file_path,
+        changed,
+    })
+}
+
+/// Archives or unarchives one note, or each note read from stdin for `-`.
+fn handle_set_archived(
+    query: &str,
+    archived: bool,
+    format: OutputFormat,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(indexed_note) => {
-            let file_path = notes_dir.join(indexed_note.path());
-            let parsed = read_note(&file_path)
-                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
-
-            // Idempotency: already archived
-            if parsed.note.tags().contains(&archived_tag) {
-                match args.format {
-                    OutputFormat::Human => {
-                        println!("'{}' is already archived", parsed.note.title());
-                    }
-                    OutputFormat::Json => {
-                        let result = ArchiveResult {
-                            id: parsed.note.id().to_string(),
-                            title: parsed.note.title().to_string(),
-                            archived: true,
-                            path: file_path.to_string_lossy().to_string(),
-                        };
-                        let out = Output::new(result);
-                        println!("{}", serde_json::to_string_pretty(&out)?);
-                    }
-                    OutputFormat::Paths => {
-                        println!("{}", file_path.display());
-                    }
-                }
-                return Ok(());
-            }
+    if query == STDIN_NOTES {
+        let notes = read_stdin_notes(notes_dir)?;
+        let mut changed = false;
+        let result = run_batch(&notes, format, |note| {
+            let outcome = set_archived(&index, note, archived, notes_dir, config)?;
+            changed |= outcome.changed;
+            Ok(outcome)
+        });
+        if changed {
+            update_index(notes_dir, config);
+        }
+        return result;
+    }
 
-            // Add archived tag
-            let mut tags = parsed.note.tags().to_vec();
-            tags.push(archived_tag);
-
-            let updated_note = Note::builder(
-                parsed.note.id().clone(),
-                parsed.note.title(),
-                parsed.note.created(),
-                Utc::now(),
-            )
-            .description(parsed.note.description().map(|s| s.to_string()))
-            .topics(parsed.note.topics().to_vec())
-            .aliases(parsed.note.aliases().to_vec())
-            .tags(tags)
-            .links(parsed.note.links().to_vec())
-            .build()
-            .with_context(|| "failed to rebuild note")?;
-
-            write_note(&file_path, &updated_note, &parsed.body)
-                .with_context(|| "failed to write updated note")?;
-
-            // Update index (ignore failures)
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
-            }
+    let outcome = set_archived(&index, query, archived, notes_dir, config)?;
+    if outcome.changed {
+        update_index(notes_dir, config);
+    }
+    outcome.print(format)
+}
 
-            match args.format {
-                OutputFormat::Human => {
-                    println!(
-                        "Archived '{}' [{}]",
-                        updated_note.title(),
-                        updated_note.id().prefix()
-                    );
-                }
-                OutputFormat::Json => {
-                    let result = ArchiveResult {
-                        id: updated_note.id().to_string(),
-                        title: updated_note.title().to_string(),
-                        archived: true,
-                        path: file_path.to_string_lossy().to_string(),
-                    };
-                    let out = Output::new(result);
-                    println!("{}", serde_json::to_string_pretty(&out)?);
-                }
-                OutputFormat::Paths => {
-                    println!("{}", file_path.display());
-                }
-            }
-            Ok(())
-        }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
-        }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
-        }
+/// Brings the index up to date after notes were rewritten (ignoring failures).
+fn update_index(notes_dir: &Path, config: &Config) {
+    if let Ok(mut idx) = SqliteIndex::open(&index_db_path(notes_dir)) {
+        let builder =
+            IndexBuilder::new(notes_dir.to_path_buf()).with_scan_options(config.scan.clone());
+        let _ = builder.incremental_update(&mut idx);
     }
 }
 
-/// Unarchive a note by removing the 'archived' tag.
-pub fn handle_unarchive(args: &UnarchiveArgs, notes_dir: &Path) -> Result<()> {
-    let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag name");
+/// Archive a note by adding the 'archived' tag, or suggest notes to archive.
+pub fn handle_archive(args: &ArchiveArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if args.suggest {
+        return handle_suggest(args, notes_dir, config);
+    }
+    let Some(query) = args.note.as_deref() else {
+        bail!("a note is required unless --suggest is given");
+    };
+    handle_set_archived(query, true, args.format, notes_dir, config)
+}
 
-    let db_path = index_db_path(notes_dir);
-    let index = SqliteIndex::open(&db_path)
-        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+/// A note that looks ready to archive.
+#[derive(Debug)]
+pub(crate) struct ArchiveCandidate {
+    /// Path relative to the notes directory.
+    pub path: PathBuf,
+    pub parsed: ParsedNote,
+    /// Threshold that applied to the note.
+    pub months: u32,
+}
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(indexed_note) => {
-            let file_path = notes_dir.join(indexed_note.path());
-            let parsed = read_note(&file_path)
-                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
-
-            // Idempotency: not archived
-            if !parsed.note.tags().contains(&archived_tag) {
-                match args.format {
-                    OutputFormat::Human => {
-                        println!("'{}' is not archived", parsed.note.title());
-                    }
-                    OutputFormat::Json => {
-                        let result = ArchiveResult {
-                            id: parsed.note.id().to_string(),
-                            title: parsed.note.title().to_string(),
-                            archived: false,
-                            path: file_path.to_string_lossy().to_string(),
-                        };
-                        let out = Output::new(result);
-                        println!("{}", serde_json::to_string_pretty(&out)?);
-                    }
-                    OutputFormat::Paths => {
-                        println!("{}", file_path.display());
-                    }
-                }
-                return Ok(());
+/// Returns true if the body contains an unchecked task list item.
+pub(crate) fn has_open_tasks(body: &str) -> bool {
+    body.lines().any(|line| {
+        let line = line.trim_start();
+        let rest = line.strip_prefix(['-', '*', '+']).or_else(|| {
+            let after_digits = line.trim_start_matches(|c: char| c.is_ascii_digit());
+            if after_digits.len() == line.len() {
+                return None;
             }
+            after_digits.strip_prefix(['.', ')'])
+        });
+        rest.is_some_and(|r| r.starts_with(" [ ]"))
+    })
+}
 
-            // Remove archived tag
-            let tags: Vec<Tag> = parsed
+/// Selects notes untouched for longer than their threshold, with no
+/// backlinks from other notes and no open tasks, oldest first.
+///
+/// `months_override` replaces the configured thresholds. Notes that are
+/// already archived are never suggested.
+pub(crate) fn archive_candidates(
+    notes: Vec<(PathBuf, ParsedNote)>,
+    policy: &ArchivePolicy,
+    months_override: Option<u32>,
+    now: DateTime<Utc>,
+) -> Vec<ArchiveCandidate> {
+    let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag name");
+
+    let linked: HashSet<NoteId> = notes
+        .iter()
+        .flat_map(|(_, parsed)| {
+            parsed
                 .note
-                .tags()
+                .links()
                 .iter()
-                .filter(|t| *t != &archived_tag)
+                .map(|link| link.target())
+                .filter(|target| *target != parsed.note.id())
                 .cloned()
+        })
+        .collect();
+
+    let mut candidates: Vec<ArchiveCandidate> = notes
+        .into_iter()
+        .filter_map(|(path, parsed)| {
+            let note = &parsed.note;
+            if note.tags().contains(&archived_tag)
+                || linked.contains(note.id())
+                || has_open_tasks(&parsed.body)
+            {
+                return None;
+            }
+
+            let months = months_override.unwrap_or_else(|| policy.months_for(note.topics()));
+            let cutoff = now.checked_sub_months(Months::new(months))?;
+            (note.modified() < cutoff).then_some(ArchiveCandidate {
+                path,
+                parsed,
+                months,
+            })
+        })
+        .collect();
+
+    candidates.sort_by_key(|c| c.parsed.note.modified());
+    candidates
+}
+
+fn handle_suggest(args: &ArchiveArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    // 1. Load notes from disk; unparseable files are left to `check`
+    let notes: Vec<_> = scan_notes_directory_with(notes_dir, &config.scan)?
+        .filter_map(|path| {
+            let parsed = read_note(&notes_dir.join(&path)).ok()?;
+            Some((path, parsed))
+        })
+        .collect();
+
+    // 2. Pick candidates
+    let candidates = archive_candidates(notes, &config.archive, args.months, Utc::now());
+
+    if args.apply {
+        return apply_suggestions(args, notes_dir, config, &candidates);
+    }
+
+    // 3. Display results
+    match args.format {
+        OutputFormat::Human => {
+            if candidates.is_empty() {
+                println!("No archive candidates.");
+                return Ok(());
+            }
+            println!("{} archive candidate(s):", candidates.len());
+            println!();
+            for c in &candidates {
+                let note = &c.parsed.note;
+                println!(
+                    "  {}  {}  {}",
+                    note.id().prefix(),
+                    config.time.date(note.modified()),
+                    note.title()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let listings: Vec<_> = candidates
+                .iter()
+                .map(|c| ArchiveCandidateListing {
+                    id: c.parsed.note.id().to_string(),
+                    title: c.parsed.note.title().to_string(),
+                    path: notes_dir.join(&c.path).to_string_lossy().to_string(),
+                    modified: c.parsed.note.modified().to_rfc3339(),
+                    months: c.months,
+                })
                 .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for c in &candidates {
+                println!("{}", notes_dir.join(&c.path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
 
-            let updated_note = Note::builder(
-                parsed.note.id().clone(),
-                parsed.note.title(),
-                parsed.note.created(),
-                Utc::now(),
-            )
-            .description(parsed.note.description().map(|s| s.to_string()))
-            .topics(parsed.note.topics().to_vec())
-            .aliases(parsed.note.aliases().to_vec())
-            .tags(tags)
-            .links(parsed.note.links().to_vec())
-            .build()
-            .with_context(|| "failed to rebuild note")?;
-
-            write_note(&file_path, &updated_note, &parsed.body)
-                .with_context(|| "failed to write updated note")?;
-
-            // Update index
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
+/// Archives suggested notes, prompting on stderr for each one unless `--yes`.
+fn apply_suggestions(
+    args: &ArchiveArgs,
+    notes_dir: &Path,
+    config: &Config,
+    candidates: &[ArchiveCandidate],
+) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut answers = stdin.lock().lines();
+
+    let mut archived = Vec::new();
+    for c in candidates {
+        let note = &c.parsed.note;
+        if !args.yes {
+            eprint!(
+                "Archive '{}' [{}]? [y/N] ",
+                note.title(),
+                note.id().prefix()
+            );
+            std::io::stderr().flush()?;
+            let Some(answer) = answers.next() else {
+                break;
+            };
+            let answer = answer.with_context(|| "failed to read answer from stdin")?;
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                continue;
             }
+        }
+
+        let file_path = notes_dir.join(&c.path);
+        let updated_note = archive_file(&file_path, &c.parsed, &config.frontmatter)?;
+        archived.push((updated_note, file_path));
+    }
 
-            match args.format {
-                OutputFormat::Human => {
-                    println!(
-                        "Unarchived '{}' [{}]",
-                        updated_note.title(),
-                        updated_note.id().prefix()
-                    );
-                }
-                OutputFormat::Json => {
-                    let result = ArchiveResult {
-                        id: updated_note.id().to_string(),
-                        title: updated_note.title().to_string(),
-                        archived: false,
-                        path: file_path.to_string_lossy().to_string(),
-                    };
-                    let out = Output::new(result);
-                    println!("{}", serde_json::to_string_pretty(&out)?);
-                }
-                OutputFormat::Paths => {
-                    println!("{}", file_path.display());
-                }
+    if !archived.is_empty() {
+        update_index(notes_dir, config);
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            for (note, _) in &archived {
+                println!("Archived '{}' [{}]", note.title(), note.id().prefix());
             }
-            Ok(())
+            println!(
+                "Archived {} of {} candidate(s)",
+                archived.len(),
+                candidates.len()
+            );
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        OutputFormat::Json => {
+            let results: Vec<_> = archived
+                .iter()
+                .map(|(note, path)| ArchiveResult {
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    archived: true,
+                    path: path.to_string_lossy().to_string(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(results))?);
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+        OutputFormat::Paths => {
+            for (_, path) in &archived {
+                println!("{}", path.display());
+            }
         }
     }
+
+    Ok(())
+}
+
+/// Unarchive a note by removing the 'archived' tag.
+pub fn handle_unarchive(args: &UnarchiveArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    handle_set_archived(&args.note, false, args.format, notes_dir, config)
 }
@@ -0,0 +1,167 @@
+//! Applying a command to notes read from stdin.
+//!
+//! Commands that take a single note also accept `-`, reading one note per
+//! line instead: an ID, prefix, title or alias, or the path of a note file
+//! as printed by `ls -f paths`. Each note is handled on its own, so one bad
+//! line doesn't stop the rest.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::cli::output::{Output, OutputFormat};
+use crate::infra::read_note;
+
+/// Note argument that reads the notes from stdin.
+pub(crate) const STDIN_NOTES: &str = "-";
+
+/// The outcome of a command on one note.
+#[derive(Debug)]
+pub(crate) struct NoteOutcome<T> {
+    /// Result printed as JSON.
+    pub result: T,
+    /// Line printed in human output.
+    pub message: String,
+    pub path: PathBuf,
+    /// False when the note was already in the requested state.
+    pub changed: bool,
+}
+
+impl<T: Serialize> NoteOutcome<T> {
+    /// Prints the outcome of a single-note command.
+    pub(crate) fn print(&self, format: OutputFormat) -> Result<()> {
+        match format {
+            OutputFormat::Human => println!("{}", self.message),
+            OutputFormat::Json => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&Output::new(&self.result))?
+                )
+            }
+            OutputFormat::Paths => println!("{}", self.path.display()),
+        }
+        Ok(())
+    }
+}
+
+/// Reads note references from stdin, one per line.
+pub(crate) fn read_stdin_notes(notes_dir: &Path) -> Result<Vec<String>> {
+    note_refs(std::io::stdin().lock(), notes_dir)
+}
+
+/// Reads note references, one per line, skipping blank lines.
+///
+/// A line naming an existing note file (absolute, or relative to the current
+/// or notes directory) becomes that note's ID; any other line is kept as is.
+pub(crate) fn note_refs<R: BufRead>(input: R, notes_dir: &Path) -> Result<Vec<String>> {
+    let mut refs = Vec::new();
+    for line in input.lines() {
+        let line = line.with_context(|| "failed to read notes from stdin")?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        refs.push(path_note_id(line, notes_dir).unwrap_or_else(|| line.to_string()));
+    }
+    Ok(refs)
+}
+
+/// Returns the ID of the note file `line` names, if it names one.
+fn path_note_id(line: &str, notes_dir: &Path) -> Option<String> {
+    if !line.ends_with(".md") {
+        return None;
+    }
+    let path = Path::new(line);
+    [path.to_path_buf(), notes_dir.join(path)]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+        .and_then(|file| read_note(&file).ok())
+        .map(|parsed| parsed.note.id().to_string())
+}
+
+/// Applies `op` to each note, then prints every outcome and a summary.
+///
+/// Failures are reported on stderr as they happen and don't stop the batch;
+/// the command fails at the end if any note did.
+pub(crate) fn run_batch<T: Serialize>(
+    notes: &[String],
+    format: OutputFormat,
+    mut op: impl FnMut(&str) -> Result<NoteOutcome<T>>,
+) -> Result<()> {
+    let mut outcomes = Vec::with_capacity(notes.len());
+    let mut failed = 0;
+    for note in notes {
+        match op(note) {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => {
+                eprintln!("error: '{}': {:#}", note, e);
+                failed += 1;
+            }
+        }
+    }
+
+    match format {
+        OutputFormat::Human => {
+            for outcome in &outcomes {
+                println!("{}", outcome.message);
+            }
+            let changed = outcomes.iter().filter(|o| o.changed).count();
+            println!(
+                "{} note(s): {} changed, {} unchanged, {} failed",
+                notes.len(),
+                changed,
+                outcomes.len() - changed,
+                failed
+            );
+        }
+        OutputFormat::Json => {
+            let results: Vec<&T> = outcomes.iter().map(|o| &o.result).collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(results))?);
+        }
+        OutputFormat::Paths => {
+            for outcome in &outcomes {
+                println!("{}", outcome.path.display());
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} note(s) failed", failed, notes.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    #[test]
+    fn note_refs_reads_ids_and_note_paths() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("api.md"),
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: API\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\n",
+        )
+        .unwrap();
+        let absolute = dir.path().join("api.md");
+        let input = format!(
+            "01HQ4A\n\n  API Design  \napi.md\n{}\nmissing.md\n",
+            absolute.display()
+        );
+
+        let refs = note_refs(Cursor::new(input), dir.path()).unwrap();
+        assert_eq!(
+            refs,
+            vec![
+                "01HQ4A",
+                "API Design",
+                "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+                "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+                "missing.md",
+            ]
+        );
+    }
+}
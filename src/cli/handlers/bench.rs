@@ -0,0 +1,484 @@
+//! Bench command handler.
+//!
+//! Generates a synthetic vault in a temporary directory and times the
+//! operations whose speed users notice: a full rebuild, incremental updates
+//! with and without changes, searches and listings. Each measurement is
+//! repeated and its median reported, so reports from different machines or
+//! versions can be compared, and `--baseline` flags the regressions.
+
+use anyhow::{Context, Result, bail};
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::index_db_path;
+use super::list::notes_matching;
+use crate::cli::BenchArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Cell, Column, Output, OutputFormat, Painter, Role, Table};
+use crate::domain::{Link, Note, NoteId, Tag, Topic};
+use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
+use crate::infra::{generate_filename, write_note};
+
+/// Topics spread over the generated notes.
+const TOPICS: &[&str] = &[
+    "software/architecture",
+    "software/patterns",
+    "software/testing",
+    "projects/alpha",
+    "projects/beta",
+    "reference",
+    "personal/journal",
+    "personal/ideas",
+];
+
+/// Tags spread over the generated notes.
+const TAGS: &[&str] = &[
+    "draft",
+    "review",
+    "published",
+    "important",
+    "rust",
+    "cli",
+    "async",
+    "database",
+];
+
+/// Words the generated titles and bodies are made of.
+const WORDS: &[&str] = &[
+    "architecture",
+    "design",
+    "pattern",
+    "system",
+    "component",
+    "interface",
+    "module",
+    "function",
+    "method",
+    "struct",
+    "implementation",
+    "abstraction",
+    "dependency",
+    "injection",
+    "testing",
+    "integration",
+    "performance",
+    "optimization",
+    "latency",
+    "throughput",
+    "cache",
+    "index",
+    "query",
+    "schema",
+];
+
+/// Queries timed by the search benchmark.
+const QUERIES: &[&str] = &[
+    "architecture",
+    "cache latency",
+    "dependency injection",
+    "schema",
+    "optimization throughput",
+];
+
+/// One timed operation in a [`BenchReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    pub name: String,
+    pub median_ms: f64,
+    pub min_ms: f64,
+    /// Notes (or queries) handled per second at the median time.
+    pub per_second: f64,
+}
+
+/// Timings of one `bench` run, as written by `bench -f json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// den version that produced the report.
+    pub version: String,
+    pub notes: usize,
+    pub words: usize,
+    pub runs: usize,
+    pub results: Vec<BenchResult>,
+}
+
+/// A result next to the same result in a baseline report.
+#[derive(Debug, Serialize)]
+pub struct BenchComparison {
+    pub name: String,
+    pub median_ms: f64,
+    pub baseline_ms: f64,
+    /// Percent change of the median; positive is slower.
+    pub change_percent: f64,
+    pub regression: bool,
+}
+
+/// `bench` JSON output when comparing with a baseline.
+#[derive(Debug, Serialize)]
+struct BenchComparisonReport<'a> {
+    #[serde(flatten)]
+    report: &'a BenchReport,
+    comparison: &'a [BenchComparison],
+}
+
+/// Deterministic pseudo-random numbers, so every run generates the same
+/// vault.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % bound as u64) as usize
+    }
+}
+
+/// Writes `count` notes of about `words` words each into `dir`. Notes have
+/// one or two topics and tags, and link to up to two earlier notes.
+pub(crate) fn generate_vault(dir: &Path, count: usize, words: usize) -> Result<()> {
+    let mut rng = Lcg(count as u64);
+    let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut ids: Vec<NoteId> = Vec::with_capacity(count);
+
+    for i in 0..count {
+        let id = NoteId::from_timestamp_ms(base.timestamp_millis() as u64 + i as u64 * 60_000);
+        let title = format!(
+            "{} {} {}",
+            capitalize(WORDS[rng.next(WORDS.len())]),
+            WORDS[rng.next(WORDS.len())],
+            i
+        );
+        let mut topics = (0..1 + rng.next(2))
+            .map(|_| Topic::new(TOPICS[rng.next(TOPICS.len())]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut tags = (0..1 + rng.next(2))
+            .map(|_| Tag::new(TAGS[rng.next(TAGS.len())]))
+            .collect::<Result<Vec<_>, _>>()?;
+        topics.dedup();
+        tags.dedup();
+        let mut links = Vec::new();
+        if i > 0 {
+            links.push(Link::new(ids[i - 1].clone(), vec!["see-also"])?);
+            let other = rng.next(i);
+            if other != i - 1 {
+                links.push(Link::new(ids[other].clone(), vec!["parent"])?);
+            }
+        }
+
+        let created = base + chrono::Duration::minutes(i as i64);
+        let note = Note::builder(id.clone(), &title, created, created)
+            .description(Some(format!("Generated note {} for benchmarking", i)))
+            .topics(topics)
+            .tags(tags)
+            .links(links)
+            .build()?;
+        let body = generate_body(&mut rng, &title, words);
+        write_note(&dir.join(generate_filename(&id, &title)), &note, &body)?;
+        ids.push(id);
+    }
+    Ok(())
+}
+
+/// A body of `words` words in paragraphs of about 50 under a few headings.
+fn generate_body(rng: &mut Lcg, title: &str, words: usize) -> String {
+    let mut body = format!("# {}\n", title);
+    for (i, start) in (0..words).step_by(50).enumerate() {
+        if i % 3 == 0 {
+            body.push_str(&format!(
+                "\n## {}\n",
+                capitalize(WORDS[rng.next(WORDS.len())])
+            ));
+        }
+        let paragraph: Vec<&str> = (start..words.min(start + 50))
+            .map(|_| WORDS[rng.next(WORDS.len())])
+            .collect();
+        body.push('\n');
+        body.push_str(&paragraph.join(" "));
+        body.push_str(".\n");
+    }
+    body
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Times `op` `runs` times and summarizes the timings, with `items`
+/// handled per run.
+fn measure(
+    name: &str,
+    runs: usize,
+    items: usize,
+    mut op: impl FnMut(usize) -> Result<()>,
+) -> Result<BenchResult> {
+    let mut times: Vec<Duration> = Vec::with_capacity(runs);
+    for run in 0..runs {
+        let start = Instant::now();
+        op(run).with_context(|| format!("benchmark '{}' failed", name))?;
+        times.push(start.elapsed());
+    }
+    times.sort();
+    let median = times[times.len() / 2];
+    let secs = median.as_secs_f64();
+    Ok(BenchResult {
+        name: name.to_string(),
+        median_ms: secs * 1000.0,
+        min_ms: times[0].as_secs_f64() * 1000.0,
+        per_second: if secs > 0.0 { items as f64 / secs } else { 0.0 },
+    })
+}
+
+/// Generates a vault and runs every benchmark on it. Progress goes to
+/// stderr when `progress` is set.
+pub(crate) fn run_benchmarks(
+    args: &BenchArgs,
+    config: &Config,
+    progress: bool,
+) -> Result<BenchReport> {
+    if args.notes == 0 || args.runs == 0 {
+        bail!("--notes and --runs must be at least 1");
+    }
+    let step = |name: &str| {
+        if progress {
+            eprint!("  {}...", name);
+            let _ = std::io::stderr().flush();
+        }
+    };
+    let done = |result: &BenchResult| {
+        if progress {
+            eprintln!(" {:.1} ms", result.median_ms);
+        }
+    };
+
+    let dir = tempfile::tempdir().with_context(|| "failed to create a temporary vault")?;
+    if progress {
+        eprintln!("Generating {} notes...", args.notes);
+    }
+    generate_vault(dir.path(), args.notes, args.words)?;
+
+    let db_path = index_db_path(dir.path());
+    std::fs::create_dir_all(db_path.parent().expect("index path has a parent"))?;
+    let mut index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let builder = IndexBuilder::new(dir.path().to_path_buf())
+        .with_tokenizer(config.index.tokenizer)
+        .with_languages(config.index.languages.clone())
+        .with_store_bodies(config.index.store_bodies);
+    let mut results = Vec::new();
+
+    step("full rebuild");
+    let result = measure("full_rebuild", args.runs, args.notes, |_| {
+        let built = builder.full_rebuild(&mut index)?;
+        if !built.errors.is_empty() {
+            bail!("{} generated note(s) failed to index", built.errors.len());
+        }
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    step("incremental update (no changes)");
+    let result = measure("incremental_unchanged", args.runs, args.notes, |_| {
+        builder.incremental_update(&mut index)?;
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    // Each run edits a different 1% of the notes
+    let mut paths: Vec<_> = index
+        .list_all()?
+        .iter()
+        .map(|n| n.path().to_path_buf())
+        .collect();
+    paths.sort();
+    let changed = (args.notes / 100).max(1);
+    step("incremental update (1% changed)");
+    let result = measure("incremental_changed", args.runs, changed, |run| {
+        for path in paths.iter().cycle().skip(run * changed).take(changed) {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(dir.path().join(path))?;
+            writeln!(file, "\nEdited in run {}.", run)?;
+        }
+        builder.incremental_update(&mut index)?;
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    step("search");
+    let result = measure("search", args.runs, QUERIES.len(), |_| {
+        for query in QUERIES {
+            index.search(query)?;
+        }
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    step("ls");
+    let result = measure("ls", args.runs, args.notes, |_| {
+        notes_matching(&index, None, &[])?;
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    step("ls by topic and tag");
+    let tags = vec![TAGS[0].to_string()];
+    let result = measure("ls_filtered", args.runs, args.notes, |_| {
+        notes_matching(&index, Some("software/"), &tags)?;
+        Ok(())
+    })?;
+    done(&result);
+    results.push(result);
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        notes: args.notes,
+        words: args.words,
+        runs: args.runs,
+        results,
+    })
+}
+
+/// Reads a report saved with `bench -f json`.
+fn load_baseline(path: &Path) -> Result<BenchReport> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read baseline {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("failed to parse baseline {}", path.display()))?;
+    let data = value.get("data").cloned().unwrap_or(value);
+    serde_json::from_value(data)
+        .with_context(|| format!("{} is not a bench report", path.display()))
+}
+
+/// Compares each result with the baseline result of the same name; results
+/// missing from the baseline are skipped.
+pub(crate) fn compare(
+    report: &BenchReport,
+    baseline: &BenchReport,
+    max_regression: f64,
+) -> Vec<BenchComparison> {
+    report
+        .results
+        .iter()
+        .filter_map(|result| {
+            let base = baseline.results.iter().find(|b| b.name == result.name)?;
+            let change_percent = if base.median_ms > 0.0 {
+                (result.median_ms - base.median_ms) / base.median_ms * 100.0
+            } else {
+                0.0
+            };
+            Some(BenchComparison {
+                name: result.name.clone(),
+                median_ms: result.median_ms,
+                baseline_ms: base.median_ms,
+                change_percent,
+                regression: change_percent > max_regression,
+            })
+        })
+        .collect()
+}
+
+pub fn handle_bench(args: &BenchArgs, config: &Config) -> Result<()> {
+    let baseline = args.baseline.as_deref().map(load_baseline).transpose()?;
+    if let Some(baseline) = &baseline
+        && (baseline.notes != args.notes || baseline.words != args.words)
+    {
+        eprintln!(
+            "warning: the baseline used {} notes of {} words; timings may not be comparable",
+            baseline.notes, baseline.words
+        );
+    }
+
+    let report = run_benchmarks(args, config, matches!(args.format, OutputFormat::Human))?;
+    let comparison = baseline
+        .as_ref()
+        .map(|baseline| compare(&report, baseline, args.max_regression));
+
+    match args.format {
+        OutputFormat::Json => match &comparison {
+            Some(comparison) => {
+                let output = Output::new(BenchComparisonReport {
+                    report: &report,
+                    comparison,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            }
+            None => println!("{}", serde_json::to_string_pretty(&Output::new(&report))?),
+        },
+        OutputFormat::Human | OutputFormat::Paths => {
+            print_report(&report, comparison.as_deref(), config)
+        }
+    }
+
+    let regressions = comparison.iter().flatten().filter(|c| c.regression).count();
+    if regressions > 0 {
+        bail!(
+            "{} benchmark(s) more than {}% slower than the baseline",
+            regressions,
+            args.max_regression
+        );
+    }
+    Ok(())
+}
+
+fn print_report(report: &BenchReport, comparison: Option<&[BenchComparison]>, config: &Config) {
+    println!();
+    println!(
+        "den {}: {} notes of {} words, median of {} run(s)",
+        report.version, report.notes, report.words, report.runs
+    );
+    let mut columns = vec![
+        Column::left("Benchmark"),
+        Column::right("Median"),
+        Column::right("Min").role(Role::Muted),
+        Column::right("Rate").role(Role::Muted),
+    ];
+    if comparison.is_some() {
+        columns.push(Column::right("Baseline").role(Role::Muted));
+        columns.push(Column::right("Change"));
+    }
+    let mut table = Table::new(columns);
+    for result in &report.results {
+        let unit = if result.name == "search" {
+            "queries/s"
+        } else {
+            "notes/s"
+        };
+        let mut row: Vec<Cell> = vec![
+            result.name.as_str().into(),
+            format!("{:.1} ms", result.median_ms).into(),
+            format!("{:.1} ms", result.min_ms).into(),
+            format!("{:.0} {}", result.per_second, unit).into(),
+        ];
+        if let Some(comparison) = comparison {
+            match comparison.iter().find(|c| c.name == result.name) {
+                Some(c) => {
+                    let role = if c.regression {
+                        Role::Error
+                    } else if c.change_percent < 0.0 {
+                        Role::Success
+                    } else {
+                        Role::Muted
+                    };
+                    row.push(format!("{:.1} ms", c.baseline_ms).into());
+                    row.push(Cell::styled(format!("{:+.1}%", c.change_percent), role));
+                }
+                None => row.push("-".into()),
+            }
+        }
+        table.row(row);
+    }
+    table.print(&Painter::new(&config.output));
+}
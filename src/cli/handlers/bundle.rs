@@ -0,0 +1,151 @@
+//! Bundle command handlers (bundle create, bundle open).
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::Path;
+
+use super::{ARCHIVED_TAG, index_db_path};
+use crate::cli::config::Config;
+use crate::cli::output::{Column, Output, OutputFormat, Painter, Role, Table, page};
+use crate::cli::{BundleArgs, BundleCommand, BundleCreateArgs, BundleOpenArgs};
+use crate::domain::Tag;
+use crate::export::{ExportExclusions, MetadataBundle};
+use crate::index::{IndexRepository, SqliteIndex};
+
+/// Environment variable the passphrase is read from without --passphrase-file.
+const PASSPHRASE_ENV: &str = "DEN_BUNDLE_PASSPHRASE";
+
+/// Result of `bundle create`.
+#[derive(Debug, Serialize)]
+struct BundleResult {
+    path: String,
+    notes: usize,
+    topics: usize,
+    tags: usize,
+    links: usize,
+}
+
+pub fn handle_bundle(args: &BundleArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        BundleCommand::Create(args) => handle_bundle_create(args, notes_dir),
+        BundleCommand::Open(args) => handle_bundle_open(args, config),
+    }
+}
+
+fn handle_bundle_create(args: &BundleCreateArgs, notes_dir: &Path) -> Result<()> {
+    let passphrase = read_passphrase(args.passphrase_file.as_deref())?;
+
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let mut notes = index
+        .list_all()
+        .with_context(|| "failed to list all notes")?;
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+    let exclusions = ExportExclusions::new(
+        &args.exclude_topics,
+        &args.exclude_tags,
+        &args.exclude_paths,
+    )?;
+    notes.retain(|n| !exclusions.excludes(n));
+    let links = index.all_links().with_context(|| "failed to query links")?;
+
+    let bundle = MetadataBundle::new(&notes, &links, Utc::now());
+    let sealed = bundle.seal(&passphrase)?;
+    if let Some(parent) = args.output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&args.output, sealed)
+        .with_context(|| format!("failed to write {}", args.output.display()))?;
+
+    let result = BundleResult {
+        path: args.output.display().to_string(),
+        notes: bundle.notes.len(),
+        topics: bundle.topic_count(),
+        tags: bundle.tag_count(),
+        links: bundle.link_count(),
+    };
+    match args.format {
+        OutputFormat::Human => println!(
+            "Wrote {} note(s), {} topic(s), {} tag(s) and {} link(s) to {}",
+            result.notes, result.topics, result.tags, result.links, result.path
+        ),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", result.path),
+    }
+    Ok(())
+}
+
+fn handle_bundle_open(args: &BundleOpenArgs, config: &Config) -> Result<()> {
+    let passphrase = read_passphrase(args.passphrase_file.as_deref())?;
+    let bytes = std::fs::read(&args.bundle)
+        .with_context(|| format!("failed to read {}", args.bundle.display()))?;
+    let bundle = MetadataBundle::open(&bytes, &passphrase)
+        .with_context(|| format!("failed to open {}", args.bundle.display()))?;
+
+    match args.format {
+        // Bundles don't record where notes live
+        OutputFormat::Human | OutputFormat::Paths => {
+            let mut out = format!(
+                "{} note(s), {} topic(s), {} tag(s) and {} link(s), bundled {}\n",
+                bundle.notes.len(),
+                bundle.topic_count(),
+                bundle.tag_count(),
+                bundle.link_count(),
+                config.time.date(bundle.created)
+            );
+            if !bundle.notes.is_empty() {
+                let mut table = Table::new(vec![
+                    Column::left("Title").max_width(40).shrink(),
+                    Column::left("Topics").shrink(),
+                    Column::left("Tags").shrink(),
+                    Column::right("Links").role(Role::Muted),
+                ]);
+                for note in &bundle.notes {
+                    table.row([
+                        note.title.clone(),
+                        note.topics.join(", "),
+                        note.tags.join(", "),
+                        note.links.len().to_string(),
+                    ]);
+                }
+                out.push('\n');
+                out.push_str(&table.render(&Painter::new(&config.output)));
+            }
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&Output::new(bundle))?);
+        }
+    }
+    Ok(())
+}
+
+/// Reads the bundle passphrase from the first line of `file`, or from
+/// `$DEN_BUNDLE_PASSPHRASE`.
+fn read_passphrase(file: Option<&Path>) -> Result<String> {
+    let passphrase = match file {
+        Some(file) => {
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("failed to read passphrase from {}", file.display()))?;
+            content.lines().next().unwrap_or_default().to_string()
+        }
+        None => match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => passphrase,
+            Err(_) => bail!(
+                "no passphrase given: set {} or use --passphrase-file",
+                PASSPHRASE_ENV
+            ),
+        },
+    };
+    if passphrase.is_empty() {
+        bail!("passphrase is empty");
+    }
+    Ok(passphrase)
+}
@@ -1,29 +1,127 @@
 //! Check command handler.
 
 use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
+use super::index_db_path;
+use crate::api::merge_or_add_link;
 use crate::cli::CheckArgs;
-use crate::domain::{Note, NoteId, Severity, ValidationIssue, ValidationKind, validate_notes};
-use crate::infra::{FsError, read_note, scan_notes_directory, write_note};
+use crate::cli::config::{Config, RequiredTopics};
+use crate::cli::output::{Output, OutputFormat, Painter, Role};
+use crate::domain::{
+    Link, Note, NoteId, Severity, TimestampField, ValidationIssue, ValidationKind,
+    find_broken_wikilinks, find_expired_tags, find_markdown_issues, find_missing_reciprocals,
+    find_missing_topics, find_size_issues, find_timestamp_anomalies, find_type_issues,
+    find_wikilinks, reconcile_modified, validate_notes_with_trash,
+};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{
+    ContentHash, FrontmatterOptions, FsError, read_note, scan_notes_directory_with,
+    scan_trash_directory, write_note_with,
+};
 
-pub fn handle_check(args: &CheckArgs, notes_dir: &Path) -> Result<()> {
-    // 1. Scan directory for notes
-    let paths: Vec<_> = scan_notes_directory(notes_dir)?.collect();
-    if paths.is_empty() {
+/// A single validation issue in check JSON output.
+#[derive(Debug, Serialize)]
+pub struct CheckIssue {
+    pub severity: &'static str,
+    pub kind: &'static str,
+    pub path: String,
+    /// Line of the file, for issues in the note body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// A note found in the trash directory.
+#[derive(Debug, Serialize)]
+pub struct TrashedNote {
+    /// Note ID, if the file could be parsed.
+    pub id: Option<String>,
+    /// Note title, if the file could be parsed.
+    pub title: Option<String>,
+    pub path: String,
+}
+
+/// Result of the check command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub ok: bool,
+    pub notes: usize,
+    /// Notes read and checked on their own, present only with `--incremental`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked: Option<usize>,
+    pub errors: usize,
+    pub warnings: usize,
+    pub fixed: usize,
+    pub issues: Vec<CheckIssue>,
+    /// Trashed notes, present only with `--include-trash`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trash: Option<Vec<TrashedNote>>,
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+pub fn handle_check(args: &CheckArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    // 1. Scan directory for notes (the trash is skipped here and loaded separately)
+    let paths: Vec<_> = scan_notes_directory_with(notes_dir, &config.scan)?.collect();
+    let trash = load_trash(notes_dir)?;
+    if paths.is_empty() && !matches!(args.format, OutputFormat::Json) {
         println!("No notes found.");
+        if args.include_trash {
+            print_trash(&trash, notes_dir);
+        }
         return Ok(());
     }
 
-    // 2. Load notes, collecting parse errors
+    // 2. Load notes and their file mtimes, collecting parse errors. With
+    //    --incremental, files unchanged since they last passed their checks
+    //    are taken from the index instead of being parsed
+    let mut cache = if args.incremental {
+        Some(CheckCache::load(notes_dir, config)?)
+    } else {
+        None
+    };
     let mut notes = Vec::new();
+    let mut unchanged = Vec::new();
+    let mut hashes = Vec::new();
+    let mut mtimes = HashMap::new();
     let mut parse_issues = Vec::new();
+    let mut markdown_issues = Vec::new();
+    let mut wikilinks = HashMap::new();
     for path in &paths {
         let full_path = notes_dir.join(path);
-        match read_note(&full_path) {
-            Ok(parsed) => notes.push((path.clone(), parsed.note)),
+        if let Some((note, hash)) = cache.as_mut().and_then(|c| c.take_unchanged(path)) {
+            unchanged.push((path.clone(), note));
+            hashes.push((path.clone(), hash));
+            continue;
+        }
+        let result = read_note(&full_path);
+        match result {
+            Ok(parsed) => {
+                if let Ok(mtime) = fs::metadata(&full_path).and_then(|m| m.modified()) {
+                    mtimes.insert(path.clone(), DateTime::<Utc>::from(mtime));
+                }
+                markdown_issues.extend(find_markdown_issues(path, &parsed.body, parsed.body_line));
+                markdown_issues.extend(find_size_issues(
+                    path,
+                    &parsed.note,
+                    &parsed.body,
+                    &config.check,
+                ));
+                wikilinks.insert(parsed.note.id().clone(), find_wikilinks(&parsed.body));
+                hashes.push((path.clone(), parsed.content_hash));
+                notes.push((path.clone(), parsed.note));
+            }
             Err(FsError::Parse { source, .. }) => {
                 parse_issues.push(ValidationIssue::parse_error(path, source));
             }
@@ -36,41 +134,225 @@ pub fn handle_check(args: &CheckArgs, notes_dir: &Path) -> Result<()> {
         }
     }
 
-    // 3. Validate the successfully loaded notes
+    // 3. Validate the successfully loaded notes; links into the trash are
+    //    reported separately from broken links. Checks across notes cover
+    //    unchanged notes too, checks of a single file only the files read
+    let trashed_ids: HashSet<NoteId> = trash.iter().filter_map(|t| t.id.clone()).collect();
     let note_refs: Vec<_> = notes.iter().map(|(p, n)| (p.clone(), n)).collect();
-    let mut summary = validate_notes(&note_refs);
+    let all_refs: Vec<_> = note_refs
+        .iter()
+        .cloned()
+        .chain(unchanged.iter().map(|(p, n)| (p.clone(), n)))
+        .collect();
+    let mut summary = validate_notes_with_trash(&all_refs, &trashed_ids);
+    for issue in find_missing_reciprocals(&all_refs, &config.rels.reciprocal) {
+        summary.add(issue);
+    }
+    // Wikilinks of unchanged notes come from the index
+    if let Some(cache) = &cache {
+        for (id, targets) in cache.wikilinks()? {
+            wikilinks.entry(id).or_insert(targets);
+        }
+    }
+    for issue in find_broken_wikilinks(&all_refs, &wikilinks) {
+        summary.add(issue);
+    }
+    // Notes that must have a topic get an error instead of the orphan warning
+    let missing_topics = match &config.topics.required {
+        RequiredTopics::All(all) => find_missing_topics(&all_refs, *all, &[]),
+        RequiredTopics::Types(types) => find_missing_topics(&all_refs, false, types),
+    };
+    let untopiced: HashSet<PathBuf> = missing_topics.iter().map(|i| i.path.clone()).collect();
+    summary
+        .issues
+        .retain(|issue| !(issue.is_orphaned() && untopiced.contains(&issue.path)));
+    for issue in missing_topics {
+        summary.add(issue);
+    }
+    let now = Utc::now();
+    // Expiry depends on the day, so unchanged notes are checked too
+    let today = config.time.today(now);
+    for issue in find_expired_tags(&all_refs, &config.tags.ttl, today) {
+        summary.add(issue);
+    }
+    let mut file_issues = find_timestamp_anomalies(&note_refs, &mtimes, now);
+    file_issues.extend(find_type_issues(
+        &note_refs,
+        &config.types.required_fields(),
+    ));
+    file_issues.extend(parse_issues);
+    file_issues.extend(markdown_issues);
 
-    // Add parse errors to the summary
-    for issue in parse_issues {
+    // Files with issues of their own are checked again next time
+    if let Some(cache) = &mut cache {
+        let failing: HashSet<&PathBuf> = file_issues.iter().map(|issue| &issue.path).collect();
+        hashes.retain(|(path, _)| !failing.contains(path));
+        cache.record(&hashes)?;
+    }
+    for issue in file_issues {
         summary.add(issue);
     }
 
-    // 4. If --fix is set, attempt to fix broken links
+    // With --drift, compare the notes with the index as it is, without
+    // bringing it up to date first
+    let mut drift_index = None;
+    if args.drift {
+        let db_path = index_db_path(notes_dir);
+        let index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let note_hashes: HashMap<&PathBuf, &ContentHash> =
+            hashes.iter().map(|(path, hash)| (path, hash)).collect();
+        let files: Vec<_> = notes
+            .iter()
+            .filter_map(|(path, note)| Some((path.as_path(), note, *note_hashes.get(path)?)))
+            .collect();
+        let drift = index
+            .find_drift(&files, &paths)
+            .with_context(|| "failed to compare notes with the index")?;
+        for issue in drift {
+            summary.add(issue);
+        }
+        drift_index = Some(index);
+    }
+
+    // 4. If --fix is set, attempt to fix broken links, add missing
+    //    reciprocal links, reconcile modified times with file mtimes and
+    //    re-index notes that drifted from the index
     let mut fixed_count = 0;
+    let mut added_count = 0;
+    let mut reconciled_count = 0;
+    let mut reindexed_count = 0;
     if args.fix {
-        fixed_count = fix_broken_links(&summary, notes_dir)?;
+        fixed_count = fix_broken_links(&summary, notes_dir, &config.frontmatter)?;
         if fixed_count > 0 {
             // Remove fixed broken link issues from summary
             summary.issues.retain(|issue| !issue.is_broken_link());
         }
+        added_count = fix_missing_reciprocals(&summary, notes_dir, &config.frontmatter)?;
+        summary
+            .issues
+            .retain(|issue| !issue.is_missing_reciprocal());
+        let reconciled = fix_timestamps(&notes, &mtimes, now, notes_dir, &config.frontmatter)?;
+        reconciled_count = reconciled.len();
+        // A future created time can't be fixed from the mtime
+        summary.issues.retain(|issue| {
+            !(issue.is_timestamp_anomaly()
+                && reconciled.contains(&issue.path)
+                && !matches!(
+                    issue.kind,
+                    ValidationKind::FutureTimestamp {
+                        field: TimestampField::Created,
+                        ..
+                    }
+                ))
+        });
+        if let Some(index) = &mut drift_index {
+            reindexed_count = fix_index_drift(&summary, index, notes_dir, config)?;
+            summary.issues.retain(|issue| !issue.is_index_drift());
+        }
     }
 
     // 5. Display results
+    match args.format {
+        OutputFormat::Human => {}
+        OutputFormat::Json => {
+            let result = CheckResult {
+                ok: !summary.has_errors(),
+                notes: paths.len(),
+                checked: args.incremental.then_some(paths.len() - unchanged.len()),
+                errors: summary.error_count(),
+                warnings: summary.warning_count(),
+                fixed: fixed_count + added_count + reconciled_count + reindexed_count,
+                issues: summary
+                    .issues_by_severity()
+                    .map(|issue| CheckIssue {
+                        severity: severity_label(issue.severity()),
+                        kind: issue.kind.code(),
+                        path: notes_dir.join(&issue.path).to_string_lossy().to_string(),
+                        line: issue.line,
+                        message: issue.kind.to_string(),
+                    })
+                    .collect(),
+                trash: args.include_trash.then(|| {
+                    trash
+                        .iter()
+                        .map(|t| TrashedNote {
+                            id: t.id.as_ref().map(|id| id.to_string()),
+                            title: t.title.clone(),
+                            path: notes_dir.join(&t.path).to_string_lossy().to_string(),
+                        })
+                        .collect()
+                }),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+            if summary.has_errors() {
+                bail!("check failed");
+            }
+            return Ok(());
+        }
+        OutputFormat::Paths => {
+            let mut seen = HashSet::new();
+            for issue in summary.issues_by_severity() {
+                if seen.insert(&issue.path) {
+                    println!("{}", notes_dir.join(&issue.path).display());
+                }
+            }
+            if args.include_trash {
+                for entry in &trash {
+                    println!("{}", notes_dir.join(&entry.path).display());
+                }
+            }
+            if summary.has_errors() {
+                bail!("check failed");
+            }
+            return Ok(());
+        }
+    }
+
+    if args.incremental {
+        println!(
+            "Checked {} changed note(s); {} unchanged since the last check.",
+            paths.len() - unchanged.len(),
+            unchanged.len()
+        );
+    }
+    if added_count > 0 {
+        println!("Added {} reciprocal link(s).", added_count);
+    }
+    if reconciled_count > 0 {
+        println!(
+            "Reconciled {} modified time(s) with file mtimes.",
+            reconciled_count
+        );
+    }
+    if reindexed_count > 0 {
+        println!(
+            "Re-indexed {} note(s) that drifted from the index.",
+            reindexed_count
+        );
+    }
+
+    let painter = Painter::new(&config.output);
     if summary.is_ok() {
+        let ok = painter.paint(Role::Success, "All notes OK.");
         if fixed_count > 0 {
-            println!("Fixed {} broken link(s). All notes OK.", fixed_count);
+            println!("Fixed {} broken link(s). {}", fixed_count, ok);
         } else {
-            println!("All notes OK.");
+            println!("{}", ok);
+        }
+        if args.include_trash {
+            print_trash(&trash, notes_dir);
         }
         return Ok(());
     }
 
     for issue in summary.issues_by_severity() {
-        let prefix = match issue.severity() {
-            Severity::Error => "error",
-            Severity::Warning => "warning",
+        let role = match issue.severity() {
+            Severity::Error => Role::Error,
+            Severity::Warning => Role::Warning,
         };
-        println!("{}: {}", prefix, issue);
+        let label = painter.paint(role, severity_label(issue.severity()));
+        println!("{}: {}", label, issue);
     }
 
     if fixed_count > 0 {
@@ -83,6 +365,13 @@ pub fn handle_check(args: &CheckArgs, notes_dir: &Path) -> Result<()> {
         summary.error_count(),
         summary.warning_count()
     );
+    if summary.issues.iter().any(ValidationIssue::is_duplicate_id) {
+        println!("Run `notes fix-ids` to give duplicate notes new IDs.");
+    }
+
+    if args.include_trash {
+        print_trash(&trash, notes_dir);
+    }
 
     // 6. Exit code: fail only if there are errors
     if summary.has_errors() {
@@ -91,10 +380,123 @@ pub fn handle_check(args: &CheckArgs, notes_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// What `check --incremental` learns from the index.
+struct CheckCache {
+    index: SqliteIndex,
+    /// Notes unchanged since they last passed their checks, by path.
+    unchanged: HashMap<PathBuf, (Note, ContentHash)>,
+}
+
+impl CheckCache {
+    /// Brings the index up to date and loads the notes unchanged since
+    /// their last check.
+    fn load(notes_dir: &Path, config: &Config) -> Result<Self> {
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .incremental_update(&mut index)
+            .with_context(|| "failed to update index")?;
+
+        let unchanged = index
+            .checked_notes()
+            .with_context(|| "failed to load checked notes")?;
+        Ok(Self { index, unchanged })
+    }
+
+    /// Returns the wikilink targets of every indexed note.
+    fn wikilinks(&self) -> Result<HashMap<NoteId, Vec<String>>> {
+        self.index
+            .all_wikilinks()
+            .with_context(|| "failed to load wikilinks")
+    }
+
+    /// Takes the note at `path` if it is unchanged since its last check.
+    fn take_unchanged(&mut self, path: &Path) -> Option<(Note, ContentHash)> {
+        self.unchanged.remove(path)
+    }
+
+    /// Records the files that passed their checks, replacing the last record.
+    fn record(&mut self, files: &[(PathBuf, ContentHash)]) -> Result<()> {
+        self.index
+            .record_checked_files(files)
+            .with_context(|| "failed to record checked files")
+    }
+}
+
+/// Re-indexes the files `--drift` found out of step with the index and
+/// removes index rows whose file is gone, returning how many were fixed.
+fn fix_index_drift(
+    summary: &crate::domain::ValidationSummary,
+    index: &mut SqliteIndex,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<usize> {
+    let paths: Vec<PathBuf> = summary
+        .issues
+        .iter()
+        .filter(|issue| issue.is_index_drift())
+        .map(|issue| issue.path.clone())
+        .collect();
+    if paths.is_empty() {
+        return Ok(0);
+    }
+    let result = IndexBuilder::new(notes_dir.to_path_buf())
+        .with_scan_options(config.scan.clone())
+        .with_tokenizer(config.index.tokenizer)
+        .with_languages(config.index.languages.clone())
+        .with_store_bodies(config.index.store_bodies)
+        .reindex_paths(index, &paths)
+        .with_context(|| "failed to re-index notes")?;
+    Ok(result.added + result.modified + result.removed)
+}
+
+/// A file in the trash directory, with its metadata if it could be parsed.
+pub(super) struct TrashEntry {
+    pub path: PathBuf,
+    pub id: Option<NoteId>,
+    pub title: Option<String>,
+}
+
+/// Loads the trash directory. Unparseable files are kept without metadata.
+pub(super) fn load_trash(notes_dir: &Path) -> Result<Vec<TrashEntry>> {
+    Ok(scan_trash_directory(notes_dir)?
+        .into_iter()
+        .map(|path| {
+            let parsed = read_note(&notes_dir.join(&path)).ok();
+            TrashEntry {
+                id: parsed.as_ref().map(|p| p.note.id().clone()),
+                title: parsed.as_ref().map(|p| p.note.title().to_string()),
+                path,
+            }
+        })
+        .collect())
+}
+
+fn print_trash(trash: &[TrashEntry], notes_dir: &Path) {
+    println!("\nTrash: {} note(s)", trash.len());
+    for entry in trash {
+        match (&entry.title, &entry.id) {
+            (Some(title), Some(id)) => println!(
+                "  {} [{}] {}",
+                title,
+                id.prefix(),
+                notes_dir.join(&entry.path).display()
+            ),
+            _ => println!("  {}", notes_dir.join(&entry.path).display()),
+        }
+    }
+}
+
 /// Fixes broken links by removing them from affected notes.
 ///
 /// Returns the number of broken links that were fixed.
-fn fix_broken_links(summary: &crate::domain::ValidationSummary, notes_dir: &Path) -> Result<usize> {
+fn fix_broken_links(
+    summary: &crate::domain::ValidationSummary,
+    notes_dir: &Path,
+    frontmatter: &FrontmatterOptions,
+) -> Result<usize> {
     // Group broken links by file path
     let mut broken_by_file: HashMap<&PathBuf, HashSet<&NoteId>> = HashMap::new();
     for issue in summary.broken_links() {
@@ -135,10 +537,10 @@ fn fix_broken_links(summary: &crate::domain::ValidationSummary, notes_dir: &Path
         }
 
         // Build a new note with the fixed links
-        let fixed_note = rebuild_note_with_links(note, fixed_links)?;
+        let fixed_note = rebuild_note(note, note.modified(), fixed_links)?;
 
         // Write the fixed note back
-        write_note(&full_path, &fixed_note, body)?;
+        write_note_with(&full_path, &fixed_note, body, frontmatter)?;
 
         total_fixed += links_removed;
     }
@@ -146,21 +548,105 @@ fn fix_broken_links(summary: &crate::domain::ValidationSummary, notes_dir: &Path
     Ok(total_fixed)
 }
 
-/// Creates a new Note with the same fields as the original but with different links.
-fn rebuild_note_with_links(note: &Note, links: Vec<crate::domain::Link>) -> Result<Note> {
-    let fixed_note = Note::builder(
-        note.id().clone(),
-        note.title(),
-        note.created(),
-        note.modified(),
-    )
-    .description(note.description().map(|s| s.to_string()))
-    .topics(note.topics().to_vec())
-    .aliases(note.aliases().to_vec())
-    .tags(note.tags().to_vec())
-    .links(links)
-    .build()
-    .map_err(|e| anyhow::anyhow!("Failed to rebuild note: {}", e))?;
+/// Adds the missing side of reciprocal links to the affected notes.
+///
+/// Returns the number of links added.
+fn fix_missing_reciprocals(
+    summary: &crate::domain::ValidationSummary,
+    notes_dir: &Path,
+    frontmatter: &FrontmatterOptions,
+) -> Result<usize> {
+    // Group the links to add by file path
+    let mut missing_by_file: HashMap<&PathBuf, Vec<Link>> = HashMap::new();
+    for issue in summary.missing_reciprocals() {
+        if let ValidationKind::MissingReciprocal {
+            source_id,
+            expected,
+            ..
+        } = &issue.kind
+        {
+            let link = Link::new(source_id.clone(), vec![expected.as_str()])?;
+            missing_by_file.entry(&issue.path).or_default().push(link);
+        }
+    }
+
+    let mut total_added = 0;
+
+    for (rel_path, new_links) in missing_by_file {
+        let full_path = notes_dir.join(rel_path);
+        let parsed = read_note(&full_path)?;
+
+        let mut links = parsed.note.links().to_vec();
+        let mut added = 0;
+        for link in &new_links {
+            let (updated, changed) = merge_or_add_link(&links, link);
+            links = updated;
+            if changed {
+                added += 1;
+            }
+        }
+        if added == 0 {
+            continue;
+        }
+
+        let fixed_note = rebuild_note(&parsed.note, parsed.note.modified(), links)?;
+        write_note_with(&full_path, &fixed_note, &parsed.body, frontmatter)?;
+
+        total_added += added;
+    }
+
+    Ok(total_added)
+}
+
+/// Sets each note's modified time from its file mtime where the two disagree.
+///
+/// The mtime is restored after writing, so the rewrite itself doesn't look
+/// like an outside edit. Returns the paths of the notes that were changed.
+fn fix_timestamps(
+    notes: &[(PathBuf, Note)],
+    mtimes: &HashMap<PathBuf, DateTime<Utc>>,
+    now: DateTime<Utc>,
+    notes_dir: &Path,
+    frontmatter: &FrontmatterOptions,
+) -> Result<HashSet<PathBuf>> {
+    let mut reconciled = HashSet::new();
+
+    for (rel_path, note) in notes {
+        let Some(&mtime) = mtimes.get(rel_path) else {
+            continue;
+        };
+        let Some(modified) = reconcile_modified(note, mtime, now) else {
+            continue;
+        };
+
+        let full_path = notes_dir.join(rel_path);
+        let parsed = read_note(&full_path)?;
+        let fixed_note = rebuild_note(&parsed.note, modified, parsed.note.links().to_vec())?;
+        write_note_with(&full_path, &fixed_note, &parsed.body, frontmatter)?;
+        fs::File::options()
+            .write(true)
+            .open(&full_path)
+            .and_then(|file| file.set_modified(modified.into()))
+            .with_context(|| format!("failed to set mtime of {}", full_path.display()))?;
+
+        reconciled.insert(rel_path.clone());
+    }
+
+    Ok(reconciled)
+}
+
+/// Creates a new Note with the same fields as the original but with a
+/// different modified time and links.
+fn rebuild_note(note: &Note, modified: DateTime<Utc>, links: Vec<Link>) -> Result<Note> {
+    let fixed_note = Note::builder(note.id().clone(), note.title(), note.created(), modified)
+        .description(note.description().map(|s| s.to_string()))
+        .topics(note.topics().to_vec())
+        .aliases(note.aliases().to_vec())
+        .tags(note.tags().to_vec())
+        .links(links)
+        .extra(note.extra().clone())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to rebuild note: {}", e))?;
 
     Ok(fixed_note)
 }
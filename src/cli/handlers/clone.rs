@@ -0,0 +1,83 @@
+//! Clone command handler.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use std::path::Path;
+
+use super::index_db_path;
+use super::new::{NewNoteResult, open_in_editor, print_created, update_modified_timestamp};
+use super::resolve::require_note;
+use crate::cli::CloneArgs;
+use crate::cli::config::Config;
+use crate::domain::{Note, NoteId};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{SlugOptions, generate_filename_with, read_note, write_note_with};
+
+/// Builds a copy of a note under a new ID (pure function, no I/O).
+///
+/// The copy keeps the description, topics, tags and extra frontmatter fields
+/// but not links or aliases, which would otherwise point at or shadow the
+/// original. The title
+/// defaults to "Copy of <title>".
+pub fn clone_note(source: &Note, title: Option<&str>, slug: &SlugOptions) -> Result<NewNoteResult> {
+    let title = match title {
+        Some(title) => title.trim().to_string(),
+        None => format!("Copy of {}", source.title()),
+    };
+    if title.is_empty() {
+        bail!("--title cannot be empty");
+    }
+
+    let id = NoteId::new();
+    let now = Utc::now();
+
+    let note = Note::builder(id.clone(), &title, now, now)
+        .description(source.description().map(|s| s.to_string()))
+        .topics(source.topics().to_vec())
+        .tags(source.tags().to_vec())
+        .extra(source.extra().clone())
+        .build()
+        .with_context(|| "failed to create note")?;
+
+    let filename = generate_filename_with(&id, &title, &slug.for_lang(note.lang().as_deref()));
+
+    Ok(NewNoteResult { note, filename })
+}
+
+pub fn handle_clone(args: &CloneArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let source = require_note(&index, &args.note, "note", config)?;
+    let source_path = notes_dir.join(source.path());
+
+    let parsed = read_note(&source_path)
+        .with_context(|| format!("failed to read note: {}", source_path.display()))?;
+
+    let result = clone_note(&parsed.note, args.title.as_deref(), &config.slug)?;
+    let note = config.frontmatter.apply_defaults(result.note);
+    let file_path = notes_dir.join(&result.filename);
+
+    write_note_with(&file_path, &note, &parsed.body, &config.frontmatter)
+        .with_context(|| format!("failed to write note to {}", file_path.display()))?;
+
+    if let Ok(mut index) = SqliteIndex::open(&db_path) {
+        let builder = IndexBuilder::new(notes_dir.to_path_buf());
+        let _ = builder.incremental_update(&mut index);
+    }
+
+    print_created(&note, &file_path, args.format)?;
+
+    if args.edit {
+        open_in_editor(&file_path, config)?;
+        update_modified_timestamp(&file_path, &config.frontmatter)?;
+
+        if let Ok(mut index) = SqliteIndex::open(&db_path) {
+            let builder = IndexBuilder::new(notes_dir.to_path_buf());
+            let _ = builder.incremental_update(&mut index);
+        }
+    }
+
+    Ok(())
+}
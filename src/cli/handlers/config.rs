@@ -0,0 +1,523 @@
+//! Config command handler: validating the config file.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, Key, TableLike};
+
+use super::import::FieldMap;
+use super::migrate::rename_map;
+use crate::cli::alias::{is_builtin, split_words};
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::cli::time_format::is_valid_format;
+use crate::cli::{ConfigArgs, ConfigCommand, ConfigValidateArgs};
+use crate::domain::Severity;
+
+/// Keys a config table may contain.
+enum Schema {
+    /// A single value; its type is checked when the config is deserialized
+    Value,
+    /// A table with user-chosen keys, e.g. vault names
+    Map,
+    /// A table with a fixed set of keys
+    Table(&'static [(&'static str, Schema)]),
+}
+
+/// Every key [`Config`] reads. Keep in sync when adding a config section.
+const CONFIG_SCHEMA: Schema = Schema::Table(&[
+    ("dir", Schema::Value),
+    ("editor", Schema::Value),
+    ("default_vault", Schema::Value),
+    ("vaults", Schema::Map),
+    ("note_refs", Schema::Value),
+    ("interactive", Schema::Value),
+    (
+        "slug",
+        Schema::Table(&[
+            ("transliterate", Schema::Value),
+            ("max_length", Schema::Value),
+            ("stop_words", Schema::Value),
+            ("separator", Schema::Value),
+        ]),
+    ),
+    (
+        "time",
+        Schema::Table(&[
+            ("date_format", Schema::Value),
+            ("datetime_format", Schema::Value),
+            ("utc", Schema::Value),
+            ("iso", Schema::Value),
+        ]),
+    ),
+    (
+        "tags",
+        Schema::Table(&[
+            ("colors", Schema::Map),
+            ("emojis", Schema::Map),
+            ("groups", Schema::Map),
+            ("ttl", Schema::Map),
+            ("expired", Schema::Map),
+        ]),
+    ),
+    (
+        "output",
+        Schema::Table(&[("theme", Schema::Value), ("color", Schema::Value)]),
+    ),
+    (
+        "archive",
+        Schema::Table(&[("months", Schema::Value), ("topics", Schema::Map)]),
+    ),
+    (
+        "frontmatter",
+        Schema::Table(&[
+            ("defaults", Schema::Map),
+            ("order", Schema::Value),
+            ("link_titles", Schema::Value),
+            ("forbid_backdating", Schema::Value),
+        ]),
+    ),
+    ("rels", Schema::Table(&[("reciprocal", Schema::Value)])),
+    ("import", Schema::Table(&[("map", Schema::Map)])),
+    ("migrate", Schema::Table(&[("rename", Schema::Map)])),
+    (
+        "index",
+        Schema::Table(&[
+            ("busy_timeout_ms", Schema::Value),
+            ("tokenizer", Schema::Value),
+            ("languages", Schema::Map),
+            ("store_bodies", Schema::Value),
+        ]),
+    ),
+    (
+        "scan",
+        Schema::Table(&[("exclude", Schema::Value), ("ignore", Schema::Value)]),
+    ),
+    ("types", Schema::Map),
+    (
+        "export",
+        Schema::Table(&[
+            ("title", Schema::Value),
+            ("author", Schema::Value),
+            ("language", Schema::Value),
+            ("date_format", Schema::Value),
+            ("rtl", Schema::Value),
+            (
+                "labels",
+                Schema::Table(&[
+                    ("home", Schema::Value),
+                    ("notes", Schema::Value),
+                    ("all_notes", Schema::Value),
+                    ("note_count_one", Schema::Value),
+                    ("note_count_other", Schema::Value),
+                    ("topics", Schema::Value),
+                    ("subtopics", Schema::Value),
+                    ("tags", Schema::Value),
+                    ("search_placeholder", Schema::Value),
+                    ("filters", Schema::Value),
+                    ("active_filters", Schema::Value),
+                    ("clear_filters", Schema::Value),
+                    ("search", Schema::Value),
+                    ("topic", Schema::Value),
+                    ("tag", Schema::Value),
+                    ("no_results", Schema::Value),
+                    ("updated", Schema::Value),
+                    ("top_tags", Schema::Value),
+                    ("back_to_index", Schema::Value),
+                    ("view_source", Schema::Value),
+                    ("page_moved", Schema::Value),
+                    ("generated_by", Schema::Value),
+                ]),
+            ),
+        ]),
+    ),
+    ("topics", Schema::Table(&[("required", Schema::Value)])),
+    (
+        "check",
+        Schema::Table(&[
+            ("max_words", Schema::Value),
+            ("max_headings", Schema::Value),
+            ("max_links", Schema::Value),
+        ]),
+    ),
+    ("trash", Schema::Table(&[("retention_days", Schema::Value)])),
+    ("alias", Schema::Map),
+    (
+        "daily",
+        Schema::Table(&[
+            ("title", Schema::Value),
+            ("topic", Schema::Value),
+            ("tags", Schema::Value),
+        ]),
+    ),
+]);
+
+/// A problem found in the config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigIssue {
+    pub severity: Severity,
+    /// 1-based line in the config file, if known
+    pub line: Option<usize>,
+    /// Dotted key path, e.g. `time.date_format`; empty if not tied to a key
+    pub key: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(line: Option<usize>, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(line: Option<usize>, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A single issue in `config validate` JSON output.
+#[derive(Debug, Serialize)]
+pub struct ConfigIssueListing {
+    pub severity: &'static str,
+    pub line: Option<usize>,
+    pub key: String,
+    pub message: String,
+}
+
+/// Result of `config validate` for JSON output.
+#[derive(Debug, Serialize)]
+pub struct ConfigValidateResult {
+    pub ok: bool,
+    pub path: String,
+    /// False if there is no config file and the defaults are used.
+    pub exists: bool,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<ConfigIssueListing>,
+}
+
+/// Returns the 1-based line containing byte `offset`.
+fn line_at(contents: &str, offset: usize) -> usize {
+    let offset = offset.min(contents.len());
+    contents.as_bytes()[..offset]
+        .iter()
+        .filter(|&&b| b == b'\n')
+        .count()
+        + 1
+}
+
+/// A key in the config file with the location of its value.
+struct Entry {
+    path: String,
+    line: Option<usize>,
+    value_span: Option<Range<usize>>,
+}
+
+/// Collects every key in `table`, reporting keys the schema doesn't know.
+fn walk(
+    contents: &str,
+    table: &dyn TableLike,
+    schema: &Schema,
+    prefix: &str,
+    entries: &mut Vec<Entry>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (key, item) in table.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let key_span = table
+            .get_key_value(key)
+            .and_then(|(k, _): (&Key, _)| k.span());
+        let line = key_span.as_ref().map(|span| line_at(contents, span.start));
+        entries.push(Entry {
+            path: path.clone(),
+            line,
+            value_span: item.span(),
+        });
+
+        let Schema::Table(fields) = schema else {
+            continue;
+        };
+        match fields.iter().find(|(name, _)| *name == key) {
+            None => issues.push(ConfigIssue::warning(line, path, "unknown key (ignored)")),
+            Some((_, child @ Schema::Table(_))) => {
+                if let Some(child_table) = item.as_table_like() {
+                    walk(contents, child_table, child, &path, entries, issues);
+                }
+            }
+            Some(_) => {
+                if let Item::Table(child_table) = item {
+                    // Record map keys (e.g. vault names) for line lookups
+                    walk(contents, child_table, &Schema::Map, &path, entries, issues);
+                } else if let Some(child_table) = item.as_inline_table() {
+                    walk(contents, child_table, &Schema::Map, &path, entries, issues);
+                }
+            }
+        }
+    }
+}
+
+fn line_of(entries: &[Entry], path: &str) -> Option<usize> {
+    entries.iter().find(|e| e.path == path).and_then(|e| e.line)
+}
+
+/// Checks values that parse but can't work, e.g. paths that don't exist.
+fn check_values(config: &Config, entries: &[Entry], issues: &mut Vec<ConfigIssue>) {
+    if let Some(dir) = &config.dir
+        && !dir.is_dir()
+    {
+        issues.push(ConfigIssue::error(
+            line_of(entries, "dir"),
+            "dir",
+            format!("directory does not exist: {}", dir.display()),
+        ));
+    }
+
+    for (name, path) in config.list_vaults() {
+        if !path.is_dir() {
+            let key = format!("vaults.{}", name);
+            issues.push(ConfigIssue::error(
+                line_of(entries, &key),
+                key,
+                format!("vault directory does not exist: {}", path.display()),
+            ));
+        }
+    }
+
+    if let Some(vault) = &config.default_vault
+        && !config.vaults.contains_key(vault)
+    {
+        issues.push(ConfigIssue::error(
+            line_of(entries, "default_vault"),
+            "default_vault",
+            format!("no vault named '{}' in [vaults]", vault),
+        ));
+    }
+
+    for (key, format) in [
+        ("time.date_format", Some(&config.time.date_format)),
+        ("time.datetime_format", Some(&config.time.datetime_format)),
+        ("daily.title", Some(&config.daily.title)),
+        ("daily.topic", Some(&config.daily.topic)),
+        ("export.date_format", config.export.date_format.as_ref()),
+    ] {
+        let Some(format) = format else {
+            continue;
+        };
+        if !is_valid_format(format) {
+            issues.push(ConfigIssue::error(
+                line_of(entries, key),
+                key,
+                format!("invalid strftime format '{}'", format),
+            ));
+        }
+    }
+
+    for (name, definition) in &config.alias {
+        let key = format!("alias.{}", name);
+        let line = line_of(entries, &key);
+        if is_builtin(name) {
+            issues.push(ConfigIssue::warning(
+                line,
+                key,
+                format!(
+                    "'{}' is a built-in command, which takes precedence (ignored)",
+                    name
+                ),
+            ));
+            continue;
+        }
+        match split_words(definition) {
+            Ok(words) if words.is_empty() => {
+                issues.push(ConfigIssue::error(line, key, "alias is empty"))
+            }
+            Ok(_) => {}
+            Err(e) => issues.push(ConfigIssue::error(line, key, e)),
+        }
+    }
+
+    for (src, dst) in &config.import.map {
+        let single = BTreeMap::from([(src.clone(), dst.clone())]);
+        if let Err(e) = FieldMap::new(&single, &[]) {
+            let key = format!("import.map.{}", src);
+            let message = e.root_cause().to_string();
+            issues.push(ConfigIssue::error(line_of(entries, &key), key, message));
+        }
+    }
+
+    for (src, dst) in &config.migrate.rename {
+        let single = BTreeMap::from([(src.clone(), dst.clone())]);
+        if let Err(e) = rename_map(&single, &[]) {
+            let key = format!("migrate.rename.{}", src);
+            let message = e.root_cause().to_string();
+            issues.push(ConfigIssue::error(line_of(entries, &key), key, message));
+        }
+    }
+}
+
+/// Validates config file contents.
+///
+/// Reports syntax errors, unknown keys (as warnings), values of the wrong
+/// type, and values that parse but can't work, such as missing vault
+/// directories or invalid time formats.
+pub(crate) fn validate_config(contents: &str) -> Vec<ConfigIssue> {
+    let document = match ImDocument::parse(contents) {
+        Ok(document) => document,
+        Err(e) => {
+            let line = e.span().map(|span| line_at(contents, span.start));
+            return vec![ConfigIssue::error(line, "", e.message().trim().to_string())];
+        }
+    };
+
+    let mut entries = Vec::new();
+    let mut issues = Vec::new();
+    walk(
+        contents,
+        document.as_table(),
+        &CONFIG_SCHEMA,
+        "",
+        &mut entries,
+        &mut issues,
+    );
+
+    match toml::from_str::<Config>(contents) {
+        Ok(config) => check_values(&config, &entries, &mut issues),
+        Err(e) => {
+            let span = e.span();
+            let line = span.as_ref().map(|span| line_at(contents, span.start));
+            // The innermost key whose value contains the error
+            let key = span
+                .and_then(|span| {
+                    entries
+                        .iter()
+                        .filter(|entry| {
+                            entry.value_span.as_ref().is_some_and(|value| {
+                                value.start <= span.start
+                                    && span.start < value.end.max(value.start + 1)
+                            })
+                        })
+                        .max_by_key(|entry| entry.path.len())
+                })
+                .map(|entry| entry.path.clone())
+                .unwrap_or_default();
+            issues.push(ConfigIssue::error(
+                line,
+                key,
+                e.message().trim().to_string(),
+            ));
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.line.unwrap_or(usize::MAX));
+    issues
+}
+
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+fn handle_config_validate(args: &ConfigValidateArgs) -> Result<()> {
+    let path: PathBuf = args.file.clone().unwrap_or_else(Config::config_path);
+    let exists = path.exists();
+    let issues = if exists {
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config file: {}", path.display()))?;
+        validate_config(&contents)
+    } else {
+        Vec::new()
+    };
+
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+
+    match args.format {
+        OutputFormat::Json => {
+            let result = ConfigValidateResult {
+                ok: errors == 0,
+                path: path.display().to_string(),
+                exists,
+                errors,
+                warnings,
+                issues: issues
+                    .iter()
+                    .map(|issue| ConfigIssueListing {
+                        severity: severity_label(issue.severity),
+                        line: issue.line,
+                        key: issue.key.clone(),
+                        message: issue.message.clone(),
+                    })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => print_issues(&path, exists, &issues, errors),
+    }
+
+    if errors > 0 {
+        bail!("config validation failed");
+    }
+    Ok(())
+}
+
+fn print_issues(path: &Path, exists: bool, issues: &[ConfigIssue], errors: usize) {
+    if !exists {
+        println!(
+            "No config file at {} (using defaults). Config OK.",
+            path.display()
+        );
+        return;
+    }
+    if issues.is_empty() {
+        println!("{}: Config OK.", path.display());
+        return;
+    }
+
+    println!("{}:", path.display());
+    for issue in issues {
+        println!("{}: {}", severity_label(issue.severity), issue);
+    }
+    println!(
+        "\nFound {} issue(s): {} error(s), {} warning(s)",
+        issues.len(),
+        errors,
+        issues.len() - errors
+    );
+}
+
+pub fn handle_config(args: &ConfigArgs) -> Result<()> {
+    match &args.command {
+        ConfigCommand::Validate(validate_args) => handle_config_validate(validate_args),
+    }
+}
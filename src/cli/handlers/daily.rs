@@ -0,0 +1,147 @@
+//! Daily command handler: one journal note per day.
+
+use anyhow::{Context, Result, bail};
+use chrono::{Days, Local, NaiveDate, Utc};
+use serde::Serialize;
+use std::path::Path;
+
+use super::new::{
+    CreateResult, open_in_editor, parse_tags, parse_topics, print_created,
+    update_modified_timestamp,
+};
+use super::open_vault;
+use crate::api::NewNote;
+use crate::cli::DailyArgs;
+use crate::cli::config::{Config, DailyOptions};
+use crate::cli::output::{Output, OutputFormat};
+use crate::cli::time_format::is_valid_format;
+use crate::index::IndexRepository;
+use crate::infra::read_note;
+
+/// Result of the daily command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct DailyResult {
+    #[serde(flatten)]
+    pub note: CreateResult,
+    /// False if the note already existed.
+    pub created: bool,
+}
+
+/// Works out the day of the note from `--date`, `--yesterday` and
+/// `--tomorrow`, relative to `today`.
+fn daily_date(args: &DailyArgs, today: NaiveDate) -> Result<NaiveDate> {
+    if let Some(date) = &args.date {
+        return NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .with_context(|| format!("invalid --date '{}': expected YYYY-MM-DD", date));
+    }
+    let date = if args.yesterday {
+        today.checked_sub_days(Days::new(1))
+    } else if args.tomorrow {
+        today.checked_add_days(Days::new(1))
+    } else {
+        Some(today)
+    };
+    date.context("date out of range")
+}
+
+/// Formats `date` with a strftime `format` from the `[daily]` section.
+fn format_date(date: NaiveDate, format: &str, key: &str) -> Result<String> {
+    if !is_valid_format(format) {
+        bail!("invalid strftime format '{}' for daily.{}", format, key);
+    }
+    Ok(date.format(format).to_string())
+}
+
+/// Returns the title and topic of the daily note for `date`.
+fn daily_title_and_topic(
+    date: NaiveDate,
+    options: &DailyOptions,
+) -> Result<(String, Option<String>)> {
+    let title = format_date(date, &options.title, "title")?;
+    if title.trim().is_empty() {
+        bail!("daily.title gives an empty title");
+    }
+    let topic = format_date(date, &options.topic, "topic")?;
+    let topic = Some(topic).filter(|t| !t.trim().is_empty());
+    Ok((title, topic))
+}
+
+pub fn handle_daily(args: &DailyArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let today = if config.time.utc {
+        Utc::now().date_naive()
+    } else {
+        Local::now().date_naive()
+    };
+    let date = daily_date(args, today)?;
+    let (title, topic) = daily_title_and_topic(date, &config.daily)?;
+    let topics = parse_topics(topic.as_slice())?;
+    let tags = parse_tags(&config.daily.tags)?;
+
+    // The day's note is found by its title, whatever its tags are now; the
+    // index is brought up to date first so a note added since is found
+    let mut vault = open_vault(notes_dir, config)?;
+    vault
+        .refresh()
+        .with_context(|| "failed to update the index")?;
+    let mut existing = vault
+        .index()
+        .find_by_title(&title)
+        .with_context(|| format!("failed to look up '{}'", title))?;
+    // Of several notes with the title, prefer one filed under the day's topic
+    existing.sort_by_key(|note| !topics.iter().all(|t| note.topics().contains(t)));
+    let existing = existing.into_iter().next();
+
+    let (note, file_path, created) = match existing {
+        Some(indexed) => {
+            let file_path = notes_dir.join(indexed.path());
+            let parsed = read_note(&file_path)
+                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+            (parsed.note, file_path, false)
+        }
+        None => {
+            let created = vault
+                .create(NewNote {
+                    title,
+                    description: None,
+                    topics,
+                    tags,
+                    links: Vec::new(),
+                    note_type: None,
+                    lang: None,
+                    body: String::new(),
+                    created: None,
+                    modified: None,
+                })
+                .with_context(|| "failed to create daily note")?;
+            (created.note, created.path, true)
+        }
+    };
+
+    match args.format {
+        OutputFormat::Human if created => print_created(&note, &file_path, args.format)?,
+        OutputFormat::Human => {
+            println!("Exists: {} [{}]", note.title(), note.id().prefix());
+            println!("  {}", file_path.display());
+        }
+        OutputFormat::Json => {
+            let result = DailyResult {
+                note: CreateResult::new(&note, &file_path),
+                created,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", file_path.display()),
+    }
+
+    if !args.no_edit {
+        open_in_editor(&file_path, config)?;
+        update_modified_timestamp(&file_path, &config.frontmatter)?;
+        let _ = vault.refresh();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Digest command handler.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::{ARCHIVED_TAG, index_db_path};
+use crate::cli::date_filter::DateFilter;
+use crate::cli::{DigestArgs, DigestFormat};
+use crate::domain::Tag;
+use crate::export::{Digest, DigestOptions, TextOptions, markdown_to_text, page_slugs};
+use crate::index::{IndexRepository, SqliteIndex};
+
+pub fn handle_digest(args: &DigestArgs, notes_dir: &Path) -> Result<()> {
+    let since =
+        DateFilter::parse(&args.since).map_err(|e| anyhow::anyhow!("invalid --since: {}", e))?;
+
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let mut notes = index
+        .list_all()
+        .with_context(|| "failed to list all notes")?;
+
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+
+    // Site pages are named by `export_slug`, and unexported notes have none
+    let page_slugs = if args.site_url.is_some() {
+        page_slugs(&notes, notes_dir)?
+    } else {
+        HashMap::new()
+    };
+    let digest = Digest::build(
+        &notes,
+        &DigestOptions {
+            since: since.threshold(),
+            site_url: args.site_url.as_deref(),
+            page_slugs: &page_slugs,
+        },
+    );
+    let content = match args.format {
+        DigestFormat::Markdown => digest.to_markdown(),
+        DigestFormat::Html => digest.to_html(args.theme.as_deref())?,
+        DigestFormat::Text => markdown_to_text(&digest.to_markdown(), &TextOptions::default()),
+    };
+
+    match &args.output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, &content)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "Wrote digest of {} new and {} updated note(s) to {}",
+                digest.created.len(),
+                digest.modified.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
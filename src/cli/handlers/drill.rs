@@ -0,0 +1,493 @@
+//! Drill command handler.
+//!
+//! Quizzes the spaced-repetition items that are due, notes drilled whole and
+//! the `question :: answer` cards in them, and writes their new schedules
+//! back to the notes' `srs` frontmatter.
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use super::resolve::require_note;
+use super::{index_db_path, open_vault};
+use crate::cli::DrillArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{ARCHIVED_TAG, Card, Grade, Note, SRS_FIELD, Srs, find_cards};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{FrontmatterOptions, read_note, scan_notes_directory_with, write_note_with};
+
+/// A note added to or removed from the deck, for JSON output.
+#[derive(Debug, Serialize)]
+pub struct DeckChange {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub in_deck: bool,
+    /// False if the note was already in (or out of) the deck.
+    pub changed: bool,
+    pub cards: usize,
+}
+
+/// A due item, for JSON output of `drill --list`.
+#[derive(Debug, Serialize)]
+pub struct DueItem {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    /// The card's question, or `None` for a note drilled whole.
+    pub card: Option<String>,
+    /// `None` for an item never reviewed.
+    pub due: Option<NaiveDate>,
+}
+
+/// A note in the deck.
+struct DeckNote {
+    /// Path relative to the notes directory.
+    path: PathBuf,
+    note: Note,
+    body: String,
+    srs: Srs,
+    cards: Vec<Card>,
+}
+
+/// An item to review: a card of a deck note, or the note itself.
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    note: usize,
+    card: Option<usize>,
+}
+
+impl Item {
+    fn card<'a>(&self, deck: &'a [DeckNote]) -> Option<&'a Card> {
+        self.card.map(|i| &deck[self.note].cards[i])
+    }
+
+    fn due(&self, deck: &[DeckNote]) -> Option<NaiveDate> {
+        let entry = &deck[self.note];
+        let key = self.card(deck).map(|c| c.key.as_str());
+        entry.srs.schedule(key).due
+    }
+}
+
+pub fn handle_drill(args: &DrillArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if let Some(query) = &args.add {
+        return change_deck(args, query, true, notes_dir, config);
+    }
+    if let Some(query) = &args.remove {
+        return change_deck(args, query, false, notes_dir, config);
+    }
+
+    let today = Utc::now().date_naive();
+    let mut deck = load_deck(notes_dir, config)?;
+    let mut items = due_items(&deck, today);
+    if let Some(limit) = args.limit {
+        items.truncate(limit);
+    }
+
+    if args.list {
+        return print_due(args.format, &deck, &items, notes_dir);
+    }
+    if items.is_empty() {
+        println!("Nothing to drill today.");
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    let reviewed = drill(
+        &mut deck,
+        &items,
+        &mut stdin.lock(),
+        &mut std::io::stdout(),
+        today,
+        notes_dir,
+        &config.frontmatter,
+    )?;
+
+    if reviewed > 0 {
+        update_index(notes_dir, config);
+    }
+    println!("Reviewed {} of {} due item(s)", reviewed, items.len());
+    Ok(())
+}
+
+/// Adds a note to the deck or removes it.
+fn change_deck(
+    args: &DrillArgs,
+    query: &str,
+    add: bool,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let vault = open_vault(notes_dir, config)?;
+    let indexed = require_note(vault.index(), query, "note", config)?;
+    let path = notes_dir.join(indexed.path());
+    let parsed =
+        read_note(&path).with_context(|| format!("failed to read note: {}", path.display()))?;
+    let current = Srs::from_note(&parsed.note)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let cards = find_cards(&parsed.body).len();
+
+    let changed = current.is_some() != add;
+    if changed {
+        let srs = add.then(Srs::default);
+        let note = with_srs(&parsed.note, srs.as_ref())?;
+        write_note_with(&path, &note, &parsed.body, &config.frontmatter)
+            .with_context(|| "failed to write updated note")?;
+        update_index(notes_dir, config);
+    }
+
+    let note = &parsed.note;
+    match args.format {
+        OutputFormat::Human => {
+            let message = match (add, changed) {
+                (true, true) => "Added to the drill deck",
+                (true, false) => "Already in the drill deck",
+                (false, true) => "Removed from the drill deck",
+                (false, false) => "Not in the drill deck",
+            };
+            println!("{}: '{}' [{}]", message, note.title(), note.id().prefix());
+            if add && changed && cards > 0 {
+                println!("  {} card(s)", cards);
+            }
+        }
+        OutputFormat::Json => {
+            let result = DeckChange {
+                id: note.id().to_string(),
+                title: note.title().to_string(),
+                path: path.to_string_lossy().to_string(),
+                in_deck: add,
+                changed,
+                cards,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", path.display()),
+    }
+    Ok(())
+}
+
+/// Reads the notes in the deck, skipping archived ones and warning about
+/// unreadable `srs` fields.
+fn load_deck(notes_dir: &Path, config: &Config) -> Result<Vec<DeckNote>> {
+    let mut deck = Vec::new();
+    for path in scan_notes_directory_with(notes_dir, &config.scan)? {
+        let Ok(parsed) = read_note(&notes_dir.join(&path)) else {
+            continue;
+        };
+        if parsed
+            .note
+            .tags()
+            .iter()
+            .any(|t| t.as_str() == ARCHIVED_TAG)
+        {
+            continue;
+        }
+        let srs = match Srs::from_note(&parsed.note) {
+            Ok(Some(srs)) => srs,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("warning: {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        let cards = find_cards(&parsed.body);
+        deck.push(DeckNote {
+            path,
+            note: parsed.note,
+            body: parsed.body,
+            srs,
+            cards,
+        });
+    }
+    Ok(deck)
+}
+
+/// Returns the items due on `today`, the longest overdue first.
+fn due_items(deck: &[DeckNote], today: NaiveDate) -> Vec<Item> {
+    let mut items = Vec::new();
+    for (i, entry) in deck.iter().enumerate() {
+        if entry.cards.is_empty() {
+            if entry.srs.schedule.is_due(today) {
+                items.push(Item {
+                    note: i,
+                    card: None,
+                });
+            }
+            continue;
+        }
+        for (c, card) in entry.cards.iter().enumerate() {
+            if entry.srs.schedule(Some(&card.key)).is_due(today) {
+                items.push(Item {
+                    note: i,
+                    card: Some(c),
+                });
+            }
+        }
+    }
+    items.sort_by_key(|item| {
+        (
+            item.due(deck).unwrap_or(today),
+            deck[item.note].path.clone(),
+        )
+    });
+    items
+}
+
+fn print_due(
+    format: OutputFormat,
+    deck: &[DeckNote],
+    items: &[Item],
+    notes_dir: &Path,
+) -> Result<()> {
+    match format {
+        OutputFormat::Human => {
+            for item in items {
+                let note = &deck[item.note].note;
+                let due = item
+                    .due(deck)
+                    .map_or_else(|| "new".to_string(), |d| d.to_string());
+                match item.card(deck) {
+                    Some(card) => println!(
+                        "{}  {} [{}]: {}",
+                        due,
+                        note.title(),
+                        note.id().prefix(),
+                        card.question
+                    ),
+                    None => println!("{}  {} [{}]", due, note.title(), note.id().prefix()),
+                }
+            }
+            println!("{} item(s) due", items.len());
+        }
+        OutputFormat::Json => {
+            let listings: Vec<DueItem> = items
+                .iter()
+                .map(|item| {
+                    let entry = &deck[item.note];
+                    DueItem {
+                        id: entry.note.id().to_string(),
+                        title: entry.note.title().to_string(),
+                        path: notes_dir.join(&entry.path).to_string_lossy().to_string(),
+                        card: item.card(deck).map(|c| c.question.clone()),
+                        due: item.due(deck),
+                    }
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            let mut seen = Vec::new();
+            for item in items {
+                if !seen.contains(&item.note) {
+                    seen.push(item.note);
+                    println!("{}", notes_dir.join(&deck[item.note].path).display());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Quizzes each item: shows the prompt, waits for Enter, shows the answer
+/// and reads a grade. Each note is written as soon as one of its items is
+/// graded, so quitting keeps earlier reviews. Returns how many items were
+/// graded.
+fn drill<R: BufRead, W: Write>(
+    deck: &mut [DeckNote],
+    items: &[Item],
+    input: &mut R,
+    out: &mut W,
+    today: NaiveDate,
+    notes_dir: &Path,
+    frontmatter: &FrontmatterOptions,
+) -> Result<usize> {
+    let mut reviewed = 0;
+    for (n, item) in items.iter().enumerate() {
+        let entry = &deck[item.note];
+        let (prompt, answer) = match item.card(deck) {
+            Some(card) => (card.question.clone(), card.answer.clone()),
+            None => (
+                entry.note.title().to_string(),
+                entry.body.trim().to_string(),
+            ),
+        };
+
+        writeln!(out, "\n[{}/{}] {}", n + 1, items.len(), prompt)?;
+        write!(out, "(Enter to show the answer, q to quit) ")?;
+        out.flush()?;
+        let Some(line) = read_line(input)? else {
+            break;
+        };
+        if line.trim() == "q" {
+            break;
+        }
+
+        writeln!(out, "{}", answer)?;
+        let grade = loop {
+            write!(out, "Grade: 1 again, 2 hard, 3 good, 4 easy (q to quit) ")?;
+            out.flush()?;
+            let Some(line) = read_line(input)? else {
+                break None;
+            };
+            if line.trim() == "q" {
+                break None;
+            }
+            if let Some(grade) = Grade::from_key(&line) {
+                break Some(grade);
+            }
+        };
+        let Some(grade) = grade else {
+            break;
+        };
+
+        let key = item.card(deck).map(|c| c.key.clone());
+        let entry = &mut deck[item.note];
+        let schedule = entry.srs.schedule(key.as_deref()).review(grade, today);
+        entry.srs.set_schedule(key.as_deref(), schedule);
+        entry.srs.retain_cards(&entry.cards);
+        writeln!(out, "Next review in {} day(s)", schedule.interval)?;
+
+        let note = with_srs(&entry.note, Some(&entry.srs))?;
+        let path = notes_dir.join(&entry.path);
+        write_note_with(&path, &note, &entry.body, frontmatter)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        entry.note = note;
+        reviewed += 1;
+    }
+    Ok(reviewed)
+}
+
+fn read_line<R: BufRead>(input: &mut R) -> Result<Option<String>> {
+    let mut line = String::new();
+    let read = input
+        .read_line(&mut line)
+        .with_context(|| "failed to read answer from stdin")?;
+    Ok((read > 0).then_some(line))
+}
+
+/// Returns a copy of the note with its `srs` field set, or removed for
+/// `None`. Scheduling isn't an edit, so `modified` is kept.
+fn with_srs(note: &Note, srs: Option<&Srs>) -> Result<Note> {
+    let mut extra = note.extra().clone();
+    match srs {
+        Some(srs) => extra.insert(SRS_FIELD.to_string(), srs.to_value()),
+        None => extra.remove(SRS_FIELD),
+    };
+
+    Note::builder(
+        note.id().clone(),
+        note.title(),
+        note.created(),
+        note.modified(),
+    )
+    .description(note.description().map(|s| s.to_string()))
+    .topics(note.topics().to_vec())
+    .aliases(note.aliases().to_vec())
+    .tags(note.tags().to_vec())
+    .links(note.links().to_vec())
+    .extra(extra)
+    .build()
+    .with_context(|| "failed to rebuild note")
+}
+
+/// Updates the index after notes were written, ignoring failures.
+fn update_index(notes_dir: &Path, config: &Config) {
+    if let Ok(mut idx) = SqliteIndex::open(&index_db_path(notes_dir)) {
+        let builder =
+            IndexBuilder::new(notes_dir.to_path_buf()).with_scan_options(config.scan.clone());
+        let _ = builder.incremental_update(&mut idx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::NoteId;
+    use chrono::TimeZone;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    fn deck_note(dir: &Path, name: &str, srs: &str, body: &str) -> DeckNote {
+        let content = format!(
+            "---\nid: {}\ntitle: {}\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\nsrs: {}\n---\n{}",
+            NoteId::new(),
+            name,
+            srs,
+            body
+        );
+        let path = PathBuf::from(format!("{}.md", name));
+        std::fs::write(dir.join(&path), content).unwrap();
+        let parsed = read_note(&dir.join(&path)).unwrap();
+        DeckNote {
+            path,
+            srs: Srs::from_note(&parsed.note).unwrap().unwrap(),
+            cards: find_cards(&parsed.body),
+            note: parsed.note,
+            body: parsed.body,
+        }
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    #[test]
+    fn due_items_prefer_cards_and_sort_by_due_date() {
+        let dir = TempDir::new().unwrap();
+        let deck = vec![
+            deck_note(dir.path(), "whole", "{due: 2024-03-02}", "Body"),
+            deck_note(dir.path(), "cards", "{due: 2024-03-20}", "a :: 1\nb :: 2\n"),
+            deck_note(dir.path(), "later", "{due: 2024-03-09}", "Body"),
+        ];
+
+        let items = due_items(&deck, day(5));
+
+        let labels: Vec<String> = items
+            .iter()
+            .map(|i| match i.card(&deck) {
+                Some(card) => card.question.clone(),
+                None => deck[i.note].note.title().to_string(),
+            })
+            .collect();
+        assert_eq!(labels, vec!["whole", "a", "b"]);
+    }
+
+    #[test]
+    fn drill_grades_items_and_writes_schedules() {
+        let dir = TempDir::new().unwrap();
+        let mut deck = vec![deck_note(dir.path(), "cards", "{}", "a :: 1\nb :: 2\n")];
+        let items = due_items(&deck, day(5));
+        let mut input = Cursor::new("\n3\n\nq\n");
+        let mut out = Vec::new();
+
+        let reviewed = drill(
+            &mut deck,
+            &items,
+            &mut input,
+            &mut out,
+            day(5),
+            dir.path(),
+            &FrontmatterOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(reviewed, 1);
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("[1/2] a"), "{}", output);
+        assert!(output.contains("Next review in 1 day(s)"), "{}", output);
+
+        let parsed = read_note(&dir.path().join("cards.md")).unwrap();
+        let srs = Srs::from_note(&parsed.note).unwrap().unwrap();
+        let key = &deck[0].cards[0].key;
+        assert_eq!(srs.schedule(Some(key)).due, Some(day(6)));
+        assert!(srs.schedule(Some(&deck[0].cards[1].key)).due.is_none());
+        assert_eq!(
+            parsed.note.modified(),
+            Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap()
+        );
+    }
+}
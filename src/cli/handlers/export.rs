@@ -5,17 +5,20 @@ use std::path::Path;
 use anyhow::{bail, Result};
 use serde::Serialize;
 
+use crate::cli::config::Config;
 use crate::cli::{ExportArgs, ExportFormat, output::OutputFormat};
 use crate::domain::{Tag, Topic};
 use crate::export::{
-    LinkResolver, LinkResolverOptions, SiteConfig, generate_site, render_note_html,
-    template::RenderOptions,
+    ASSETS_DIR, AssetCopier, AssetOptions, EpubConfig, ExportExclusions, LinkResolver,
+    LinkResolverOptions, MarkdownOptions, SiteConfig, SiteStatsOptions, TextOptions, TocOptions,
+    generate_epub, generate_site, is_rtl_language, page_slug, page_template, render_note_html,
+    render_note_text, retain_exported, template::RenderOptions,
 };
 use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
 use crate::infra::read_note;
 
 use super::index_db_path;
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
+use super::resolve::require_note;
 
 /// Result of an export operation.
 #[derive(Debug, Serialize)]
@@ -28,16 +31,18 @@ pub struct ExportResult {
     pub id: Option<String>,
     /// Note title (for single note export)
     pub title: Option<String>,
+    /// Number of images copied into the assets directory
+    pub images: usize,
 }
 
 /// Handle the `export` command.
-pub fn handle_export(args: &ExportArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_export(args: &ExportArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)?;
 
     match (&args.note, args.all) {
-        (Some(query), false) => handle_single_export(args, &index, notes_dir, query),
-        (None, true) => handle_bulk_export(args, &index, notes_dir),
+        (Some(query), false) => handle_single_export(args, &index, notes_dir, query, config),
+        (None, true) => handle_bulk_export(args, &index, notes_dir, config),
         _ => unreachable!(),
     }
 }
@@ -48,95 +53,122 @@ fn handle_single_export(
     index: &SqliteIndex,
     notes_dir: &Path,
     query: &str,
+    config: &Config,
 ) -> Result<()> {
-    let indexed_note = match resolve_note(index, query)? {
-        ResolveResult::Unique(note) => note,
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(query, &notes);
-            bail!("Ambiguous note reference");
-        }
-        ResolveResult::NotFound => {
-            bail!("Note not found: {}", query);
-        }
-    };
+    let indexed_note = require_note(index, query, "note", config)?;
 
     let file_path = notes_dir.join(indexed_note.path());
     let parsed = read_note(&file_path)?;
+    if !parsed.note.is_exported() {
+        bail!(
+            "'{}' is not exported: its frontmatter has `export: false`",
+            parsed.note.title()
+        );
+    }
 
-    match args.export_format {
+    let mut images = 0;
+    let (content, extension) = match args.export_format {
         ExportFormat::Html => {
             // Create link resolver if requested
             let link_options = LinkResolverOptions::default();
             let resolver = if args.resolve_links {
-                Some(LinkResolver::from_index(index, &link_options))
+                Some(LinkResolver::from_index(index, notes_dir, &link_options)?)
             } else {
                 None
             };
 
+            let template = page_template(&parsed.note, notes_dir);
             let options = RenderOptions {
-                template_path: args.template.as_deref(),
+                template_path: template.as_deref().or(args.template.as_deref()),
                 theme: args.theme.as_deref(),
                 link_resolver: resolver.as_ref(),
+                markdown: markdown_options(args),
+                toc: toc_options(args),
             };
 
-            let html = render_note_html(&parsed.note, &parsed.body, &options)?;
-
-            match &args.output {
-                Some(output_path) => {
-                    // Determine if output is a directory or file
-                    // Treat as directory if: already a dir, ends with /, or has no extension
-                    let is_dir = output_path.is_dir()
-                        || output_path.to_string_lossy().ends_with('/')
-                        || output_path.extension().is_none();
-
-                    let output_file = if is_dir {
-                        std::fs::create_dir_all(output_path)?;
-                        let slug = crate::infra::slugify(parsed.note.title());
-                        output_path.join(format!("{}.html", slug))
-                    } else {
-                        // It's a file path - ensure parent directory exists
-                        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
-                            std::fs::create_dir_all(parent)?;
-                        }
-                        output_path.clone()
-                    };
-
-                    std::fs::write(&output_file, &html)?;
-
-                    print_result(
-                        &args.cli_format,
-                        ExportResult {
-                            notes_exported: 1,
-                            path: Some(output_file.display().to_string()),
-                            id: Some(parsed.note.id().to_string()),
-                            title: Some(parsed.note.title().to_string()),
-                        },
-                        &format!(
-                            "Exported '{}' to {}",
-                            parsed.note.title(),
-                            output_file.display()
-                        ),
-                    );
+            // Images can only be copied next to an output file
+            let output_dir = args.output.as_deref().map(|output| {
+                if is_output_dir(output) {
+                    output
+                } else {
+                    output.parent().unwrap_or(Path::new(""))
                 }
-                None => {
-                    // Output to stdout
-                    print!("{}", html);
+            });
+            let body = match (output_dir, asset_options(args)) {
+                (Some(output_dir), Some(asset_options)) => {
+                    let mut assets = AssetCopier::new(notes_dir, output_dir, asset_options);
+                    let body = assets.rewrite(&parsed.body, indexed_note.path(), "")?;
+                    images = assets.copied();
+                    body
                 }
-            }
+                _ => parsed.body.clone(),
+            };
+
+            (render_note_html(&parsed.note, &body, &options)?, "html")
         }
+        ExportFormat::Man => (
+            render_note_text(&parsed.note, &parsed.body, &text_options(args)),
+            "txt",
+        ),
         ExportFormat::Pdf => {
             bail!("PDF export is not yet implemented. Use --format html and convert manually.");
         }
         ExportFormat::Site => {
             bail!("Site export requires --all flag to export all notes.");
         }
+        ExportFormat::Epub => {
+            bail!("EPUB export requires --all flag to export all notes.");
+        }
+    };
+
+    match &args.output {
+        Some(output_path) => {
+            let output_file = if is_output_dir(output_path) {
+                std::fs::create_dir_all(output_path)?;
+                output_path.join(format!("{}.{}", page_slug(&parsed.note), extension))
+            } else {
+                // It's a file path - ensure parent directory exists
+                if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent)?;
+                }
+                output_path.clone()
+            };
+
+            std::fs::write(&output_file, &content)?;
+
+            print_result(
+                &args.cli_format,
+                ExportResult {
+                    notes_exported: 1,
+                    path: Some(output_file.display().to_string()),
+                    id: Some(parsed.note.id().to_string()),
+                    title: Some(parsed.note.title().to_string()),
+                    images,
+                },
+                &format!(
+                    "Exported '{}' to {}{}",
+                    parsed.note.title(),
+                    output_file.display(),
+                    copied_images(images, output_file.parent().unwrap_or(Path::new("")))
+                ),
+            );
+        }
+        None => {
+            // Output to stdout
+            print!("{}", content);
+        }
     }
 
     Ok(())
 }
 
 /// Export multiple notes (bulk export).
-fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path) -> Result<()> {
+fn handle_bulk_export(
+    args: &ExportArgs,
+    index: &SqliteIndex,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
     let output_dir = match &args.output {
         Some(p) => p.clone(),
         None => bail!("Bulk export requires --output directory"),
@@ -151,6 +183,15 @@ fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path)
         notes.retain(|n| !n.tags().contains(&archived_tag));
     }
 
+    // Never publish excluded notes
+    let exclusions = ExportExclusions::new(
+        &args.exclude_topics,
+        &args.exclude_tags,
+        &args.exclude_paths,
+    )?;
+    notes.retain(|n| !exclusions.excludes(n));
+    retain_exported(&mut notes, notes_dir)?;
+
     if notes.is_empty() {
         bail!("No notes match the specified filters");
     }
@@ -160,28 +201,79 @@ fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path)
             // Bulk HTML export - each note as a separate file
             std::fs::create_dir_all(&output_dir)?;
 
-            // Create link resolver if requested
+            let parsed_notes = notes
+                .iter()
+                .map(|n| Ok((n, read_note(&notes_dir.join(n.path()))?)))
+                .collect::<Result<Vec<_>>>()?;
+
+            // Create link resolver if requested, linking to custom slugs
             let link_options = LinkResolverOptions::default();
-            let resolver = if args.resolve_links {
-                Some(LinkResolver::from_notes(&notes, &link_options))
-            } else {
-                None
-            };
+            let resolver = args.resolve_links.then(|| {
+                let mut resolver = LinkResolver::from_notes(&notes, &link_options);
+                let slugs = parsed_notes
+                    .iter()
+                    .map(|(indexed_note, parsed)| {
+                        (indexed_note.id().prefix(), page_slug(&parsed.note))
+                    })
+                    .collect();
+                resolver.set_page_slugs(&slugs);
+                resolver
+            });
 
-            let render_options = RenderOptions {
-                template_path: args.template.as_deref(),
-                theme: args.theme.as_deref(),
-                link_resolver: resolver.as_ref(),
-            };
+            let mut assets = asset_options(args)
+                .map(|options| AssetCopier::new(notes_dir, &output_dir, options));
+            let mut exported = 0;
+            for (indexed_note, parsed) in parsed_notes {
+                let template = page_template(&parsed.note, notes_dir);
+                let render_options = RenderOptions {
+                    template_path: template.as_deref().or(args.template.as_deref()),
+                    theme: args.theme.as_deref(),
+                    link_resolver: resolver.as_ref(),
+                    markdown: markdown_options(args),
+                    toc: toc_options(args),
+                };
+
+                let body = match &mut assets {
+                    Some(assets) => assets.rewrite(&parsed.body, indexed_note.path(), "")?,
+                    None => parsed.body,
+                };
+                let html = render_note_html(&parsed.note, &body, &render_options)?;
+                let slug = page_slug(&parsed.note);
+                std::fs::write(output_dir.join(format!("{}.html", slug)), html)?;
+                exported += 1;
+            }
+            let images = assets.map_or(0, |a| a.copied());
+
+            print_result(
+                &args.cli_format,
+                ExportResult {
+                    notes_exported: exported,
+                    path: Some(output_dir.display().to_string()),
+                    id: None,
+                    title: None,
+                    images,
+                },
+                &format!(
+                    "Exported {} notes to {}{}",
+                    exported,
+                    output_dir.display(),
+                    copied_images(images, &output_dir)
+                ),
+            );
+        }
+        ExportFormat::Man => {
+            // Bulk plain-text export - each note as a separate file
+            std::fs::create_dir_all(&output_dir)?;
 
+            let text_options = text_options(args);
             let mut exported = 0;
             for indexed_note in &notes {
                 let file_path = notes_dir.join(indexed_note.path());
                 let parsed = read_note(&file_path)?;
 
-                let html = render_note_html(&parsed.note, &parsed.body, &render_options)?;
-                let slug = crate::infra::slugify(parsed.note.title());
-                std::fs::write(output_dir.join(format!("{}.html", slug)), html)?;
+                let text = render_note_text(&parsed.note, &parsed.body, &text_options);
+                let slug = page_slug(&parsed.note);
+                std::fs::write(output_dir.join(format!("{}.txt", slug)), text)?;
                 exported += 1;
             }
 
@@ -192,16 +284,28 @@ fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path)
                     path: Some(output_dir.display().to_string()),
                     id: None,
                     title: None,
+                    images: 0,
                 },
                 &format!("Exported {} notes to {}", exported, output_dir.display()),
             );
         }
         ExportFormat::Site => {
             // Static site generation
+            let lang = config.export.language.as_deref().unwrap_or("en");
             let site_config = SiteConfig {
                 site_title: "Notes",
                 theme: args.theme.as_deref(),
                 note_template: args.template.as_deref(),
+                markdown: markdown_options(args),
+                stats: args.stats.then(SiteStatsOptions::default),
+                exclude: exclusions,
+                include_source: args.include_source,
+                assets: asset_options(args),
+                toc: toc_options(args),
+                lang,
+                rtl: config.export.rtl.unwrap_or_else(|| is_rtl_language(lang)),
+                date_format: config.export.date_format.as_deref().unwrap_or("%Y-%m-%d"),
+                labels: config.export.labels.clone(),
             };
 
             let result = generate_site(&notes, &output_dir, notes_dir, &site_config)?;
@@ -213,12 +317,55 @@ fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path)
                     path: Some(output_dir.display().to_string()),
                     id: None,
                     title: None,
+                    images: result.assets,
                 },
                 &format!(
-                    "Generated site with {} notes and {} topic pages at {}",
+                    "Generated site with {} notes, {} topic pages and {} redirects at {}{}",
                     result.notes_exported,
                     result.topic_pages,
-                    output_dir.display()
+                    result.redirects,
+                    output_dir.display(),
+                    copied_images(result.assets, &output_dir)
+                ),
+            );
+        }
+        ExportFormat::Epub => {
+            let title = config.export.title.as_deref().unwrap_or("Notes");
+            let output_file = if is_output_dir(&output_dir) {
+                output_dir.join(format!("{}.epub", crate::infra::slugify(title)))
+            } else {
+                output_dir.clone()
+            };
+            let root_topic = args
+                .topic
+                .as_deref()
+                .map(|t| Topic::new(t.trim_end_matches('/')))
+                .transpose()?;
+            let epub_config = EpubConfig {
+                title,
+                author: config.export.author.as_deref(),
+                language: config.export.language.as_deref().unwrap_or("en"),
+                cover: args.cover.as_deref(),
+                root_topic: root_topic.as_ref(),
+                markdown: markdown_options(args),
+            };
+
+            let result = generate_epub(&notes, &output_file, notes_dir, &epub_config)?;
+
+            print_result(
+                &args.cli_format,
+                ExportResult {
+                    notes_exported: result.notes_exported,
+                    path: Some(output_file.display().to_string()),
+                    id: None,
+                    title: None,
+                    images: 0,
+                },
+                &format!(
+                    "Exported {} notes in {} chapters to {}",
+                    result.notes_exported,
+                    result.chapters,
+                    output_file.display()
                 ),
             );
         }
@@ -230,6 +377,59 @@ fn handle_bulk_export(args: &ExportArgs, index: &SqliteIndex, notes_dir: &Path)
     Ok(())
 }
 
+/// Build the markdown extension settings from the command-line flags.
+fn markdown_options(args: &ExportArgs) -> MarkdownOptions {
+    MarkdownOptions {
+        footnotes: !args.no_footnotes,
+        task_lists: !args.no_task_lists,
+        callouts: !args.no_callouts,
+        math: args.math,
+    }
+}
+
+/// Build the table of contents settings from the command-line flags, or
+/// `None` without `--toc`.
+fn toc_options(args: &ExportArgs) -> Option<TocOptions> {
+    args.toc.then_some(TocOptions {
+        depth: args.toc_depth,
+        after_first_heading: args.toc_after_heading,
+    })
+}
+
+/// Build the image copying settings from the command-line flags, or `None`
+/// if images aren't copied.
+fn asset_options(args: &ExportArgs) -> Option<AssetOptions> {
+    (!args.no_copy_images).then_some(AssetOptions {
+        max_width: args.max_image_width,
+    })
+}
+
+/// Whether an output path names a directory rather than a file: it already
+/// is one, ends with /, or has no extension.
+fn is_output_dir(path: &Path) -> bool {
+    path.is_dir() || path.to_string_lossy().ends_with('/') || path.extension().is_none()
+}
+
+/// Line appended to the human message when images were copied.
+fn copied_images(count: usize, output_dir: &Path) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    format!(
+        "\nCopied {} image(s) into {}",
+        count,
+        output_dir.join(ASSETS_DIR).display()
+    )
+}
+
+/// Build the plain-text rendering settings from the command-line flags.
+fn text_options(args: &ExportArgs) -> TextOptions {
+    TextOptions {
+        width: args.width,
+        markdown: markdown_options(args),
+    }
+}
+
 /// Get notes filtered by topic and tags.
 fn get_filtered_notes(index: &SqliteIndex, args: &ExportArgs) -> Result<Vec<IndexedNote>> {
     let notes = match &args.topic {
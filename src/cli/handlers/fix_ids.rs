@@ -0,0 +1,731 @@
+//! Fix-ids command handler.
+//!
+//! Gives new IDs to notes whose ID is duplicated, invalid or missing, renames
+//! files named after the old ID prefix, and rewrites links to the old ID
+//! where that can be done without guessing.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use super::index_db_path;
+use crate::cli::FixIdsArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{Link, Note, NoteId, NoteNames, WikilinkTarget};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{
+    Journal, read_note, scan_notes_directory_with, serialize_with, split_frontmatter,
+};
+
+/// Why a note is given a new ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixReason {
+    /// Another note has the same ID.
+    Duplicate,
+    /// The `id` field is not a valid ULID.
+    Invalid,
+    /// There is no `id` field.
+    Missing,
+    /// The file was named on the command line.
+    Requested,
+}
+
+impl FixReason {
+    fn describe(self) -> &'static str {
+        match self {
+            FixReason::Duplicate => "duplicate ID",
+            FixReason::Invalid => "invalid ID",
+            FixReason::Missing => "missing ID",
+            FixReason::Requested => "new ID",
+        }
+    }
+}
+
+/// A note given a new ID, for JSON output.
+#[derive(Debug, Serialize)]
+pub struct FixedId {
+    pub path: String,
+    /// The file's new path, if it was renamed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_path: Option<String>,
+    pub reason: FixReason,
+    pub old_id: Option<String>,
+    pub new_id: String,
+    /// Links in other notes that were pointed at the new ID or path.
+    pub links_rewritten: usize,
+    /// Notes that still link to the old ID, which another note keeps.
+    pub links_kept: usize,
+}
+
+/// A note given a new ID by `mv --id`, for JSON output.
+#[derive(Debug, Serialize)]
+pub struct IdChange {
+    pub title: String,
+    pub old_id: String,
+    pub new_id: String,
+    pub path: String,
+    /// The file's new path, if it was renamed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_path: Option<String>,
+    /// Links in other notes that were pointed at the new ID or path.
+    pub links_rewritten: usize,
+    /// Notes whose links were rewritten.
+    pub notes_relinked: usize,
+}
+
+/// A note file loaded for repair.
+struct NoteFile {
+    path: PathBuf,
+    note: Note,
+    body: String,
+}
+
+/// A file whose note cannot be loaded only because of its ID.
+struct BadIdFile {
+    path: PathBuf,
+    old_id: Option<String>,
+    frontmatter: Mapping,
+    body: String,
+}
+
+/// A note whose links were rewritten: its path, note and body.
+type RelinkedNote = (PathBuf, Note, String);
+
+/// A planned ID change.
+struct Fix {
+    path: PathBuf,
+    new_path: PathBuf,
+    reason: FixReason,
+    old_id: Option<String>,
+    note: Note,
+    body: String,
+}
+
+pub fn handle_fix_ids(args: &FixIdsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    // 1. Load every note
+    let (notes, bad) = load_notes(notes_dir, config)?;
+
+    // 2. Decide which notes get new IDs
+    let chosen = if args.files.is_empty() {
+        default_choice(&notes, &bad)
+    } else {
+        requested_choice(&args.files, notes_dir, &notes, &bad)?
+    };
+
+    let mut fixes = Vec::new();
+    for (path, reason) in chosen {
+        let fix = match notes.iter().find(|n| n.path == path) {
+            Some(file) => plan_fix(file, reason, NoteId::new())?,
+            None => {
+                let file = bad.iter().find(|b| b.path == path).expect("chosen file");
+                plan_bad_id_fix(file)?
+            }
+        };
+        if fix.new_path != fix.path && notes_dir.join(&fix.new_path).exists() {
+            bail!(
+                "cannot rename {} to {}: file exists",
+                fix.path.display(),
+                fix.new_path.display()
+            );
+        }
+        fixes.push(fix);
+    }
+
+    // 3. Point links at the new IDs and paths
+    let (rewrites, rewritten, changed) = rewrite_links(&notes, &mut fixes)?;
+
+    // 4. Write the changes
+    if !args.dry_run {
+        write_fixes(notes_dir, config, "fix-ids", &changed, &fixes)?;
+
+        if !fixes.is_empty()
+            && let Ok(mut index) = SqliteIndex::open(&index_db_path(notes_dir))
+        {
+            let builder = IndexBuilder::new(notes_dir.to_path_buf());
+            let _ = builder.incremental_update(&mut index);
+        }
+    }
+
+    // 5. Report
+    let results: Vec<FixedId> = fixes
+        .iter()
+        .enumerate()
+        .map(|(i, fix)| {
+            // Links to an ID another note keeps are left alone
+            let links_kept = match old_note_id(fix) {
+                Some(old) if !rewrites.ids.contains_key(&old) => notes
+                    .iter()
+                    .filter(|n| n.note.links().iter().any(|l| l.target() == &old))
+                    .count(),
+                _ => 0,
+            };
+            FixedId {
+                path: path_str(&fix.path),
+                new_path: (fix.new_path != fix.path).then(|| path_str(&fix.new_path)),
+                reason: fix.reason,
+                old_id: fix.old_id.clone(),
+                new_id: fix.note.id().to_string(),
+                links_rewritten: rewritten[i],
+                links_kept,
+            }
+        })
+        .collect();
+
+    match args.format {
+        OutputFormat::Human => print_fixes(&results, args.dry_run),
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&Output::new(results))?);
+        }
+        OutputFormat::Paths => {
+            for result in &results {
+                let path = result.new_path.as_ref().unwrap_or(&result.path);
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gives the note at `path` (relative to the notes directory) the ID
+/// `new_id`.
+///
+/// The file is renamed if it is named after the old ID, every link to the
+/// note is pointed at the new ID, and the old ID is kept as an alias so
+/// references to it still resolve. The changed notes are re-indexed in one
+/// transaction. Refuses an ID another note has, and a note sharing its ID,
+/// whose links can't be told apart.
+pub(crate) fn change_note_id(
+    notes_dir: &Path,
+    config: &Config,
+    path: &Path,
+    new_id: NoteId,
+    dry_run: bool,
+) -> Result<IdChange> {
+    let (notes, _) = load_notes(notes_dir, config)?;
+    let Some(file) = notes.iter().find(|n| n.path == path) else {
+        bail!("not a note in the notes directory: {}", path.display());
+    };
+    let old_id = file.note.id();
+    if old_id == &new_id {
+        bail!("'{}' already has ID {}", file.note.title(), new_id);
+    }
+    if let Some(other) = notes.iter().find(|n| n.note.id() == &new_id) {
+        bail!(
+            "ID {} is already used by '{}' ({})",
+            new_id,
+            other.note.title(),
+            other.path.display()
+        );
+    }
+    if notes.iter().filter(|n| n.note.id() == old_id).count() > 1 {
+        bail!(
+            "'{}' shares ID {} with another note; run `notes fix-ids` first",
+            file.note.title(),
+            old_id
+        );
+    }
+
+    let mut fix = plan_fix(file, FixReason::Requested, new_id)?;
+    if fix.new_path != fix.path && notes_dir.join(&fix.new_path).exists() {
+        bail!(
+            "cannot rename {} to {}: file exists",
+            fix.path.display(),
+            fix.new_path.display()
+        );
+    }
+    let mut aliases = fix.note.aliases().to_vec();
+    aliases.push(old_id.to_string());
+    fix.note = Note::builder(
+        fix.note.id().clone(),
+        fix.note.title(),
+        fix.note.created(),
+        fix.note.modified(),
+    )
+    .description(fix.note.description().map(String::from))
+    .topics(fix.note.topics().to_vec())
+    .aliases(aliases)
+    .tags(fix.note.tags().to_vec())
+    .links(fix.note.links().to_vec())
+    .extra(fix.note.extra().clone())
+    .build()?;
+
+    let mut fixes = [fix];
+    let (_, rewritten, changed) = rewrite_links(&notes, &mut fixes)?;
+    let [fix] = fixes;
+
+    if !dry_run {
+        write_fixes(
+            notes_dir,
+            config,
+            "mv --id",
+            &changed,
+            std::slice::from_ref(&fix),
+        )?;
+
+        let mut paths: Vec<PathBuf> = changed.iter().map(|(path, _, _)| path.clone()).collect();
+        paths.push(fix.path.clone());
+        paths.push(fix.new_path.clone());
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &paths)
+            .with_context(|| "failed to update index")?;
+    }
+
+    Ok(IdChange {
+        title: fix.note.title().to_string(),
+        old_id: old_id.to_string(),
+        new_id: fix.note.id().to_string(),
+        path: path_str(&fix.path),
+        new_path: (fix.new_path != fix.path).then(|| path_str(&fix.new_path)),
+        links_rewritten: rewritten[0],
+        notes_relinked: changed.len(),
+    })
+}
+
+/// Loads every note, keeping the files that only fail to load because of
+/// their ID.
+fn load_notes(notes_dir: &Path, config: &Config) -> Result<(Vec<NoteFile>, Vec<BadIdFile>)> {
+    let mut notes = Vec::new();
+    let mut bad = Vec::new();
+    for path in scan_notes_directory_with(notes_dir, &config.scan)? {
+        let full_path = notes_dir.join(&path);
+        match read_note(&full_path) {
+            Ok(parsed) => notes.push(NoteFile {
+                path,
+                note: parsed.note,
+                body: parsed.body,
+            }),
+            Err(_) => {
+                let content = fs::read_to_string(&full_path).unwrap_or_default();
+                if let Some(file) = bad_id_file(path, &content) {
+                    bad.push(file);
+                }
+            }
+        }
+    }
+    Ok((notes, bad))
+}
+
+/// Points links in every note, including the fixed ones, at the fixes' new
+/// IDs and paths.
+///
+/// Returns the rewrites, the number of links rewritten per fix and the other
+/// notes that changed.
+fn rewrite_links(
+    notes: &[NoteFile],
+    fixes: &mut [Fix],
+) -> Result<(Rewrites, Vec<usize>, Vec<RelinkedNote>)> {
+    // Links can be rewritten when they point to an ID only one note had, or
+    // to a renamed file's path. Each maps to the fix it belongs to, for
+    // counting.
+    let mut holders: HashMap<&NoteId, usize> = HashMap::new();
+    let mut names = NoteNames::new();
+    for file in notes {
+        *holders.entry(file.note.id()).or_default() += 1;
+        names.add(file.note.id(), file.note.title(), file.note.aliases());
+    }
+    let mut ids = HashMap::new();
+    let mut targets = HashMap::new();
+    for (i, fix) in fixes.iter().enumerate() {
+        if let Some(old) = old_note_id(fix)
+            && holders.get(&old) == Some(&1)
+        {
+            targets.insert(old.to_string(), (fix.note.id().to_string(), i));
+            targets.insert(old.prefix(), (fix.note.id().prefix(), i));
+            ids.insert(old, (fix.note.id().clone(), i));
+        }
+        if fix.new_path != fix.path {
+            targets.insert(path_str(&fix.path), (path_str(&fix.new_path), i));
+            if let (Some(old), Some(new)) = (fix.path.file_name(), fix.new_path.file_name()) {
+                targets.insert(
+                    old.to_string_lossy().to_string(),
+                    (new.to_string_lossy().to_string(), i),
+                );
+            }
+        }
+    }
+    let rewrites = Rewrites {
+        ids,
+        targets,
+        link_target: Regex::new(r"\]\(([^)\s]+)\)").expect("valid regex"),
+        names,
+        wikilink_target: Regex::new(r"(\[\[\s*)([0-9A-Za-z]+)(\s*[\]|#])").expect("valid regex"),
+    };
+
+    let mut rewritten = vec![0; fixes.len()];
+    let mut changed = Vec::new();
+    for file in notes {
+        if fixes.iter().any(|f| f.path == file.path) {
+            continue;
+        }
+        if let Some((note, body)) = rewrites.apply(&file.note, &file.body, &mut rewritten)? {
+            changed.push((file.path.clone(), note, body));
+        }
+    }
+    for fix in fixes.iter_mut() {
+        if let Some((note, body)) = rewrites.apply(&fix.note, &fix.body, &mut rewritten)? {
+            fix.note = note;
+            fix.body = body;
+        }
+    }
+    Ok((rewrites, rewritten, changed))
+}
+
+/// Writes the notes whose links changed and the fixed notes, removing the
+/// files fixed notes were renamed from.
+///
+/// The changes go through a journal, so an interrupted run can be finished
+/// or undone with `recover` instead of leaving links half rewritten.
+fn write_fixes(
+    notes_dir: &Path,
+    config: &Config,
+    operation: &str,
+    changed: &[RelinkedNote],
+    fixes: &[Fix],
+) -> Result<()> {
+    let mut journal = Journal::new(operation, Utc::now());
+    for (path, note, body) in changed {
+        let contents = serialize_with(note, body, &config.frontmatter);
+        journal.record(notes_dir, path, Some(contents))?;
+    }
+    for fix in fixes {
+        let contents = serialize_with(&fix.note, &fix.body, &config.frontmatter);
+        journal.record(notes_dir, &fix.new_path, Some(contents))?;
+        if fix.new_path != fix.path {
+            journal.record(notes_dir, &fix.path, None)?;
+        }
+    }
+    journal
+        .run(notes_dir)
+        .with_context(|| format!("failed to write changes for {}", operation))
+}
+
+/// Returns the file as a [`BadIdFile`] if its frontmatter would load as a
+/// note with a valid ID.
+fn bad_id_file(path: PathBuf, content: &str) -> Option<BadIdFile> {
+    let (yaml, body) = split_frontmatter(content).ok()?;
+    let mut frontmatter: Mapping = serde_yaml::from_str(yaml).ok()?;
+    let old_id = frontmatter.get("id").map(|id| match id {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    });
+
+    let mut with_id = frontmatter.clone();
+    with_id.insert("id".into(), NoteId::new().to_string().into());
+    serde_yaml::from_value::<Note>(Value::Mapping(with_id)).ok()?;
+
+    frontmatter.remove("id");
+    Some(BadIdFile {
+        path,
+        old_id,
+        frontmatter,
+        body: body.to_string(),
+    })
+}
+
+/// Chooses the files `check` would complain about: every file with an
+/// invalid or missing ID, and all but one of the notes sharing an ID.
+///
+/// Of the notes sharing an ID, the one whose file name starts with the ID's
+/// prefix keeps it, then the oldest.
+fn default_choice(notes: &[NoteFile], bad: &[BadIdFile]) -> Vec<(PathBuf, FixReason)> {
+    let mut chosen: Vec<_> = bad
+        .iter()
+        .map(|file| {
+            let reason = if file.old_id.is_some() {
+                FixReason::Invalid
+            } else {
+                FixReason::Missing
+            };
+            (file.path.clone(), reason)
+        })
+        .collect();
+
+    let mut by_id: HashMap<&NoteId, Vec<&NoteFile>> = HashMap::new();
+    for file in notes {
+        by_id.entry(file.note.id()).or_default().push(file);
+    }
+    let mut duplicates = Vec::new();
+    for (id, mut files) in by_id {
+        if files.len() < 2 {
+            continue;
+        }
+        files.sort_by_key(|f| {
+            (
+                !has_prefix(&f.path, &id.prefix()),
+                f.note.created(),
+                &f.path,
+            )
+        });
+        for file in &files[1..] {
+            duplicates.push((file.path.clone(), FixReason::Duplicate));
+        }
+    }
+    duplicates.sort_by(|a, b| a.0.cmp(&b.0));
+    chosen.extend(duplicates);
+    chosen
+}
+
+/// Resolves the files named on the command line.
+fn requested_choice(
+    files: &[PathBuf],
+    notes_dir: &Path,
+    notes: &[NoteFile],
+    bad: &[BadIdFile],
+) -> Result<Vec<(PathBuf, FixReason)>> {
+    let canonical_dir = notes_dir
+        .canonicalize()
+        .unwrap_or_else(|_| notes_dir.into());
+    let mut chosen = Vec::new();
+    for file in files {
+        // Accept paths relative to the working directory or the notes directory
+        let path = file
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.strip_prefix(&canonical_dir).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| file.clone());
+
+        let reason = if let Some(file) = bad.iter().find(|b| b.path == path) {
+            if file.old_id.is_some() {
+                FixReason::Invalid
+            } else {
+                FixReason::Missing
+            }
+        } else if notes.iter().any(|n| n.path == path) {
+            FixReason::Requested
+        } else {
+            bail!("not a note in the notes directory: {}", file.display());
+        };
+        if !chosen.iter().any(|(p, _)| p == &path) {
+            chosen.push((path, reason));
+        }
+    }
+    Ok(chosen)
+}
+
+fn plan_fix(file: &NoteFile, reason: FixReason, id: NoteId) -> Result<Fix> {
+    let note = &file.note;
+    let new_note = Note::builder(id.clone(), note.title(), note.created(), note.modified())
+        .description(note.description().map(String::from))
+        .topics(note.topics().to_vec())
+        .aliases(note.aliases().to_vec())
+        .tags(note.tags().to_vec())
+        .links(note.links().to_vec())
+        .extra(note.extra().clone())
+        .build()?;
+
+    Ok(Fix {
+        path: file.path.clone(),
+        new_path: renamed(&file.path, &note.id().prefix(), &id),
+        reason,
+        old_id: Some(note.id().to_string()),
+        note: new_note,
+        body: file.body.clone(),
+    })
+}
+
+fn plan_bad_id_fix(file: &BadIdFile) -> Result<Fix> {
+    let id = NoteId::new();
+    let mut frontmatter = file.frontmatter.clone();
+    frontmatter.insert("id".into(), id.to_string().into());
+    let note: Note = serde_yaml::from_value(Value::Mapping(frontmatter))?;
+
+    let new_path = match file.old_id.as_deref().and_then(|old| old.get(..10)) {
+        Some(prefix) => renamed(&file.path, prefix, &id),
+        None => file.path.clone(),
+    };
+    Ok(Fix {
+        path: file.path.clone(),
+        new_path,
+        reason: if file.old_id.is_some() {
+            FixReason::Invalid
+        } else {
+            FixReason::Missing
+        },
+        old_id: file.old_id.clone(),
+        note,
+        body: file.body.clone(),
+    })
+}
+
+fn has_prefix(path: &Path, prefix: &str) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with(prefix))
+}
+
+/// Returns the path with a file name starting with `old_prefix` renamed to
+/// start with the new ID's prefix instead.
+fn renamed(path: &Path, old_prefix: &str, id: &NoteId) -> PathBuf {
+    match path.file_name().map(|n| n.to_string_lossy().to_string()) {
+        Some(name) if name.starts_with(old_prefix) => {
+            path.with_file_name(format!("{}{}", id.prefix(), &name[old_prefix.len()..]))
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Link rewrites for the notes given new IDs.
+struct Rewrites {
+    /// Old ID to new ID and the index of its fix.
+    ids: HashMap<NoteId, (NoteId, usize)>,
+    /// Old body link target (ID, ID prefix or path) to new target and the
+    /// index of its fix.
+    targets: HashMap<String, (String, usize)>,
+    /// Matches the target of a markdown link.
+    link_target: Regex,
+    /// The notes' titles, aliases and old IDs, for resolving wikilinks.
+    names: NoteNames,
+    /// Matches an ID-like wikilink target, with what comes before and after.
+    wikilink_target: Regex,
+}
+
+impl Rewrites {
+    /// Points a note's frontmatter links at new IDs, its body links at new
+    /// IDs, prefixes or paths and its `[[wikilinks]]` by ID at new IDs,
+    /// counting the rewrites per fix.
+    ///
+    /// Returns the updated note and body, or `None` if nothing changed.
+    fn apply(
+        &self,
+        note: &Note,
+        body: &str,
+        counts: &mut [usize],
+    ) -> Result<Option<(Note, String)>> {
+        let mut changed = false;
+
+        let mut links = Vec::with_capacity(note.links().len());
+        for link in note.links() {
+            let Some((new, i)) = self.ids.get(link.target()) else {
+                links.push(link.clone());
+                continue;
+            };
+            counts[*i] += 1;
+            changed = true;
+            let rels: Vec<&str> = link.rel().iter().map(|r| r.as_str()).collect();
+            let relinked = match link.context() {
+                Some(context) => Link::with_context(new.clone(), rels, context)?,
+                None => Link::new(new.clone(), rels)?,
+            };
+            links.push(relinked.with_title(link.title()));
+        }
+
+        let body = self
+            .link_target
+            .replace_all(body, |caps: &Captures| match self.targets.get(&caps[1]) {
+                Some((new, i)) => {
+                    counts[*i] += 1;
+                    changed = true;
+                    format!("]({})", new)
+                }
+                None => caps[0].to_string(),
+            })
+            .into_owned();
+
+        let body = self
+            .wikilink_target
+            .replace_all(&body, |caps: &Captures| match self.wikilink_id(&caps[2]) {
+                Some((new, i)) => {
+                    counts[i] += 1;
+                    changed = true;
+                    format!("{}{}{}", &caps[1], new, &caps[3])
+                }
+                None => caps[0].to_string(),
+            })
+            .into_owned();
+
+        if !changed {
+            return Ok(None);
+        }
+        let note = Note::builder(
+            note.id().clone(),
+            note.title(),
+            note.created(),
+            note.modified(),
+        )
+        .description(note.description().map(String::from))
+        .topics(note.topics().to_vec())
+        .aliases(note.aliases().to_vec())
+        .tags(note.tags().to_vec())
+        .links(links)
+        .extra(note.extra().clone())
+        .build()?;
+        Ok(Some((note, body)))
+    }
+
+    /// Returns the new ID for a wikilink target that is the old ID of a
+    /// fixed note, or the new prefix for one that is a prefix of it, with
+    /// the index of its fix. Targets resolved by title or alias are left
+    /// alone.
+    fn wikilink_id(&self, target: &str) -> Option<(String, usize)> {
+        let WikilinkTarget::Note(old) = self.names.resolve(target) else {
+            return None;
+        };
+        let (new, i) = self.ids.get(&old)?;
+        let old = old.to_string();
+        if !old.starts_with(&target.to_ascii_uppercase()) {
+            return None;
+        }
+        let new = if target.len() == old.len() {
+            new.to_string()
+        } else {
+            new.prefix()
+        };
+        Some((new, *i))
+    }
+}
+
+/// Returns the ID a fixed note had, if it was a valid one.
+fn old_note_id(fix: &Fix) -> Option<NoteId> {
+    fix.old_id.as_deref().and_then(|id| id.parse().ok())
+}
+
+/// Prints the fixes for humans.
+fn print_fixes(results: &[FixedId], dry_run: bool) {
+    if results.is_empty() {
+        println!("No duplicate, invalid or missing IDs found.");
+        return;
+    }
+
+    for result in results {
+        let reason = match &result.old_id {
+            Some(old) => format!("{} {}", result.reason.describe(), old),
+            None => result.reason.describe().to_string(),
+        };
+        println!("{}: {} -> {}", result.path, reason, result.new_id);
+        if let Some(new_path) = &result.new_path {
+            println!("  renamed to {}", new_path);
+        }
+        if result.links_rewritten > 0 {
+            println!("  {} link(s) rewritten", result.links_rewritten);
+        }
+        if result.links_kept > 0 {
+            println!(
+                "  {} note(s) still link to {}, which another note keeps",
+                result.links_kept,
+                result.old_id.as_deref().unwrap_or_default()
+            );
+        }
+    }
+
+    let verb = if dry_run { "Would give" } else { "Gave" };
+    println!();
+    println!("{} {} note(s) new IDs", verb, results.len());
+}
@@ -0,0 +1,228 @@
+//! Graph command handler: the link graph as DOT, GraphML or JSON.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::list::notes_matching;
+use super::resolve::require_note;
+use super::{ARCHIVED_TAG, index_db_path};
+use crate::cli::config::Config;
+use crate::cli::output::{GraphEdgeListing, GraphListing, GraphNodeListing, Output};
+use crate::cli::{GraphArgs, GraphFormat};
+use crate::domain::{Link, NoteId, Tag};
+use crate::index::{IndexedNote, SqliteIndex};
+
+/// Notes and the links between them.
+struct Graph<'a> {
+    /// Ordered by title, then ID.
+    nodes: Vec<&'a IndexedNote>,
+    /// Source and target positions in `nodes`, with the rels of every link
+    /// between them. One edge per pair, ordered by source then target.
+    edges: Vec<(usize, usize, BTreeSet<String>)>,
+}
+
+impl<'a> Graph<'a> {
+    /// Builds the graph of `notes`, keeping the links between them and
+    /// leaving out links to notes outside it.
+    fn build(notes: &'a [IndexedNote], links: &HashMap<NoteId, Vec<Link>>) -> Self {
+        let mut nodes: Vec<&IndexedNote> = notes.iter().collect();
+        nodes.sort_by_key(|note| (note.title().to_string(), note.id().to_string()));
+        let positions: HashMap<&NoteId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, note)| (note.id(), i))
+            .collect();
+
+        let mut edges: BTreeMap<(usize, usize), BTreeSet<String>> = BTreeMap::new();
+        for (source, note) in nodes.iter().enumerate() {
+            for link in links.get(note.id()).into_iter().flatten() {
+                if let Some(&target) = positions.get(link.target()) {
+                    edges
+                        .entry((source, target))
+                        .or_default()
+                        .extend(link.rel().iter().map(ToString::to_string));
+                }
+            }
+        }
+
+        Self {
+            nodes,
+            edges: edges
+                .into_iter()
+                .map(|((source, target), rels)| (source, target, rels))
+                .collect(),
+        }
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph notes {\n");
+        for note in &self.nodes {
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{}\"];",
+                note.id(),
+                escape_dot(note.title())
+            );
+        }
+        for (source, target, rels) in &self.edges {
+            let rels = rels.iter().cloned().collect::<Vec<_>>().join(", ");
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                self.nodes[*source].id(),
+                self.nodes[*target].id(),
+                escape_dot(&rels)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n",
+            "  <key id=\"rels\" for=\"edge\" attr.name=\"rels\" attr.type=\"string\"/>\n",
+            "  <graph id=\"notes\" edgedefault=\"directed\">\n",
+        ));
+        for note in &self.nodes {
+            let _ = writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"title\">{}</data><data key=\"path\">{}</data></node>",
+                note.id(),
+                escape_xml(note.title()),
+                escape_xml(&note.path().display().to_string())
+            );
+        }
+        for (source, target, rels) in &self.edges {
+            let rels = rels.iter().cloned().collect::<Vec<_>>().join(",");
+            let _ = writeln!(
+                out,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"rels\">{}</data></edge>",
+                self.nodes[*source].id(),
+                self.nodes[*target].id(),
+                escape_xml(&rels)
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    fn to_listing(&self) -> GraphListing {
+        GraphListing {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|note| GraphNodeListing {
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    path: note.path().display().to_string(),
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|(source, target, rels)| GraphEdgeListing {
+                    source: self.nodes[*source].id().to_string(),
+                    target: self.nodes[*target].id().to_string(),
+                    rels: rels.iter().cloned().collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Finds the notes among `notes` within `hops` links of `center`, following
+/// links in either direction but only through notes in `notes`.
+fn within_hops(
+    center: &NoteId,
+    hops: usize,
+    notes: &[IndexedNote],
+    links: &HashMap<NoteId, Vec<Link>>,
+) -> HashSet<NoteId> {
+    let ids: HashSet<&NoteId> = notes.iter().map(IndexedNote::id).collect();
+    let mut neighbors: HashMap<&NoteId, Vec<&NoteId>> = HashMap::new();
+    for (source, targets) in links.iter().filter(|(source, _)| ids.contains(source)) {
+        for target in targets.iter().map(Link::target).filter(|t| ids.contains(t)) {
+            neighbors.entry(source).or_default().push(target);
+            neighbors.entry(target).or_default().push(source);
+        }
+    }
+
+    let mut reached: HashSet<&NoteId> = HashSet::from([center]);
+    let mut frontier = vec![center];
+    for _ in 0..hops {
+        frontier = frontier
+            .iter()
+            .flat_map(|id| neighbors.get(id).into_iter().flatten().copied())
+            .filter(|id| reached.insert(id))
+            .collect();
+        if frontier.is_empty() {
+            break;
+        }
+    }
+    reached.into_iter().cloned().collect()
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+pub(super) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn handle_graph(args: &GraphArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let mut notes = notes_matching(&index, args.topic.as_deref(), &args.tags)?;
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+    let links = index.all_links().with_context(|| "failed to query links")?;
+
+    if let Some(from) = &args.from {
+        let center = require_note(&index, from, "note", config)?;
+        if !notes.iter().any(|n| n.id() == center.id()) {
+            notes.push(center.clone());
+        }
+        let reached = within_hops(center.id(), args.hops, &notes, &links);
+        notes.retain(|n| reached.contains(n.id()));
+    }
+
+    let graph = Graph::build(&notes, &links);
+    let content = match args.format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Graphml => graph.to_graphml(),
+        GraphFormat::Json => serde_json::to_string_pretty(&Output::new(graph.to_listing()))? + "\n",
+    };
+
+    match &args.output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, &content)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "Wrote graph of {} note(s) and {} link(s) to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
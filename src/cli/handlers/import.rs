@@ -0,0 +1,479 @@
+//! Import command handler.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::cli::{ImportArgs, ImportCommand, ImportGenericArgs};
+use crate::domain::{NOTE_FIELDS, Note, NoteId, Tag, Topic};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{
+    SlugOptions, generate_filename_with, scan_notes_directory, split_frontmatter, write_note_with,
+};
+
+/// Den field an imported frontmatter key is mapped to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FieldTarget {
+    Title,
+    Description,
+    Topics,
+    Tags,
+    Aliases,
+    Created,
+    Modified,
+    /// Kept as an extra frontmatter field under this name
+    Extra(String),
+    /// Not imported
+    Drop,
+}
+
+impl FieldTarget {
+    /// Returns the den field with the given name, if it can be imported into.
+    fn den_field(name: &str) -> Option<Self> {
+        match name {
+            "title" => Some(Self::Title),
+            "description" => Some(Self::Description),
+            "topics" => Some(Self::Topics),
+            "tags" => Some(Self::Tags),
+            "aliases" => Some(Self::Aliases),
+            "created" => Some(Self::Created),
+            "modified" => Some(Self::Modified),
+            _ => None,
+        }
+    }
+
+    /// Parses the DST side of a `SRC=DST` mapping.
+    pub(super) fn parse(dst: &str) -> Result<Self> {
+        let dst = dst.trim();
+        if dst.is_empty() {
+            return Ok(Self::Drop);
+        }
+        if let Some(name) = dst.strip_prefix(':') {
+            return Self::den_field(name).with_context(|| {
+                format!(
+                    "unknown den field ':{}' (expected title, description, topics, tags, aliases, created or modified)",
+                    name
+                )
+            });
+        }
+        if let Some(target) = Self::den_field(dst) {
+            return Ok(target);
+        }
+        if NOTE_FIELDS.contains(&dst) {
+            bail!(
+                "cannot import into '{}': den manages that field itself",
+                dst
+            );
+        }
+        Ok(Self::Extra(dst.to_string()))
+    }
+}
+
+/// Mapping from source frontmatter keys to den fields.
+///
+/// Keys without an explicit mapping go to the den field of the same name, or
+/// are kept as extra fields. Source `id` and `links` are dropped, since den
+/// assigns its own IDs.
+#[derive(Debug, Default)]
+pub(crate) struct FieldMap {
+    fields: HashMap<String, FieldTarget>,
+}
+
+impl FieldMap {
+    /// Builds the mapping from the `[import]` config section and `--map`
+    /// flags, with flags taking precedence.
+    pub(crate) fn new(configured: &BTreeMap<String, String>, flags: &[String]) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for (src, dst) in configured {
+            let target = FieldTarget::parse(dst)
+                .with_context(|| format!("invalid [import] mapping '{}'", src))?;
+            fields.insert(src.clone(), target);
+        }
+        for flag in flags {
+            let Some((src, dst)) = flag.split_once('=') else {
+                bail!("invalid --map '{}': expected SRC=DST", flag);
+            };
+            let src = src.trim();
+            if src.is_empty() {
+                bail!("invalid --map '{}': source field is empty", flag);
+            }
+            let target =
+                FieldTarget::parse(dst).with_context(|| format!("invalid --map '{}'", flag))?;
+            fields.insert(src.to_string(), target);
+        }
+        Ok(Self { fields })
+    }
+
+    /// Returns where the given source key is imported to.
+    pub(crate) fn target(&self, key: &str) -> FieldTarget {
+        if let Some(target) = self.fields.get(key) {
+            return target.clone();
+        }
+        if let Some(target) = FieldTarget::den_field(key) {
+            return target;
+        }
+        if NOTE_FIELDS.contains(&key) {
+            return FieldTarget::Drop;
+        }
+        FieldTarget::Extra(key.to_string())
+    }
+}
+
+/// A source file converted to a den note, ready to be written.
+#[derive(Debug)]
+pub(crate) struct ImportedNote {
+    /// Path of the source file, relative to the import directory
+    pub source: PathBuf,
+    pub note: Note,
+    pub body: String,
+    pub filename: String,
+    /// Values that could not be imported
+    pub warnings: Vec<String>,
+}
+
+/// Converts a markdown file from another tool into a den note (pure function,
+/// no I/O).
+///
+/// The title falls back to the first `# ` heading, then the file name.
+/// Timestamps that are missing or unreadable fall back to `fallback_time`.
+/// Notes without topics get one from their directory, if it is a valid topic.
+pub(crate) fn convert_note(
+    source: &Path,
+    content: &str,
+    map: &FieldMap,
+    fallback_time: DateTime<Utc>,
+    slug: &SlugOptions,
+) -> Result<ImportedNote> {
+    // A leading `---` without a closing delimiter is a horizontal rule
+    let (yaml, body) = split_frontmatter(content).unwrap_or(("", content));
+    let frontmatter: Value = serde_yaml::from_str(yaml).context("invalid YAML in frontmatter")?;
+    let fields = match frontmatter {
+        Value::Null => Mapping::new(),
+        Value::Mapping(fields) => fields,
+        _ => bail!("frontmatter is not a mapping"),
+    };
+
+    let mut warnings = Vec::new();
+    let mut title = None;
+    let mut description = None;
+    let mut created = None;
+    let mut modified = None;
+    let mut topics: Vec<Topic> = Vec::new();
+    let mut tags: Vec<Tag> = Vec::new();
+    let mut aliases: Vec<String> = Vec::new();
+    let mut extra = BTreeMap::new();
+
+    for (key, value) in fields {
+        let Some(key) = scalar_string(&key) else {
+            continue;
+        };
+        match map.target(&key) {
+            FieldTarget::Title => {
+                if title.is_none() {
+                    title = scalar_string(&value).filter(|t| !t.trim().is_empty());
+                }
+            }
+            FieldTarget::Description => {
+                if description.is_none() {
+                    description = scalar_string(&value);
+                }
+            }
+            FieldTarget::Created => {
+                if created.is_none() {
+                    created = parse_timestamp(&value);
+                    if created.is_none() {
+                        warnings.push(format!("unreadable timestamp in '{}'", key));
+                    }
+                }
+            }
+            FieldTarget::Modified => {
+                if modified.is_none() {
+                    modified = parse_timestamp(&value);
+                    if modified.is_none() {
+                        warnings.push(format!("unreadable timestamp in '{}'", key));
+                    }
+                }
+            }
+            FieldTarget::Topics => {
+                for value in list_strings(&value, true) {
+                    match Topic::new(&value.replace(' ', "-")) {
+                        Ok(topic) if !topics.contains(&topic) => topics.push(topic),
+                        Ok(_) => {}
+                        Err(_) => warnings.push(format!("skipping invalid topic '{}'", value)),
+                    }
+                }
+            }
+            FieldTarget::Tags => {
+                for value in list_strings(&value, true) {
+                    let value = value.trim_start_matches('#');
+                    match Tag::new(&value.replace(' ', "-")) {
+                        Ok(tag) if !tags.contains(&tag) => tags.push(tag),
+                        Ok(_) => {}
+                        Err(_) => warnings.push(format!("skipping invalid tag '{}'", value)),
+                    }
+                }
+            }
+            FieldTarget::Aliases => {
+                for alias in list_strings(&value, false) {
+                    if !aliases.contains(&alias) {
+                        aliases.push(alias);
+                    }
+                }
+            }
+            FieldTarget::Extra(name) => {
+                extra.entry(name).or_insert(value);
+            }
+            FieldTarget::Drop => {}
+        }
+    }
+
+    if topics.is_empty() {
+        topics.extend(directory_topic(source));
+    }
+
+    let title = title
+        .or_else(|| first_heading(body))
+        .or_else(|| {
+            source
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "Untitled".to_string());
+    let title = title.trim();
+    let modified = modified.or(created).unwrap_or(fallback_time);
+    let created = created.unwrap_or(modified);
+
+    let id = NoteId::new();
+    let note = Note::builder(id.clone(), title, created, modified)
+        .description(description)
+        .topics(topics)
+        .aliases(aliases)
+        .tags(tags)
+        .extra(extra)
+        .build()
+        .with_context(|| "failed to create note")?;
+
+    let filename = generate_filename_with(&id, title, &slug.for_lang(note.lang().as_deref()));
+
+    Ok(ImportedNote {
+        source: source.to_path_buf(),
+        note,
+        body: body.to_string(),
+        filename,
+        warnings,
+    })
+}
+
+/// Returns a scalar YAML value as a string.
+pub(super) fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the strings in a YAML list, or in a single string value.
+///
+/// With `split`, a single string is split on commas and whitespace, as tools
+/// that store tags as `tags: rust, cli` expect.
+pub(super) fn list_strings(value: &Value, split: bool) -> Vec<String> {
+    let values: Vec<String> = match value {
+        Value::Sequence(items) => items.iter().filter_map(scalar_string).collect(),
+        Value::String(s) if split => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect(),
+        other => scalar_string(other).into_iter().collect(),
+    };
+    values
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Parses a timestamp written by another tool.
+///
+/// Accepts RFC 3339, `YYYY-MM-DD HH:MM:SS`, `YYYY-MM-DD` and Unix
+/// timestamps in seconds or milliseconds (as Dendron writes them). Times
+/// without an offset are taken as UTC.
+pub(super) fn parse_timestamp(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::Number(n) => {
+            let n = n.as_i64()?;
+            // Anything past the year 5138 in seconds is really milliseconds
+            if n.abs() >= 100_000_000_000 {
+                Utc.timestamp_millis_opt(n).single()
+            } else {
+                Utc.timestamp_opt(n, 0).single()
+            }
+        }
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                return Some(dt.with_timezone(&Utc));
+            }
+            for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+                if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+                    return Some(Utc.from_utc_datetime(&dt));
+                }
+            }
+            let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+            Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the text of the first level-one ATX heading in a body.
+fn first_heading(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|heading| heading.trim().to_string())
+        .filter(|heading| !heading.is_empty())
+}
+
+/// Returns the topic matching a file's directory, with spaces as hyphens.
+fn directory_topic(source: &Path) -> Option<Topic> {
+    let dir = source.parent()?.to_string_lossy().replace('\\', "/");
+    if dir.is_empty() {
+        return None;
+    }
+    Topic::new(&dir.replace(' ', "-")).ok()
+}
+
+/// An imported note, for output.
+#[derive(Debug, Serialize)]
+struct ImportedNoteListing {
+    source: String,
+    id: String,
+    title: String,
+    path: String,
+    topics: Vec<String>,
+    tags: Vec<String>,
+}
+
+pub fn handle_import(args: &ImportArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        ImportCommand::Generic(generic) => handle_import_generic(generic, notes_dir, config),
+    }
+}
+
+fn handle_import_generic(
+    args: &ImportGenericArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+    if !args.source.is_dir() {
+        bail!("source directory does not exist: {}", args.source.display());
+    }
+    if args.source.canonicalize()? == notes_dir.canonicalize()? {
+        bail!("cannot import the notes directory into itself");
+    }
+
+    let map = FieldMap::new(&config.import.map, &args.map)?;
+
+    let mut sources: Vec<PathBuf> = scan_notes_directory(&args.source)?.collect();
+    sources.sort();
+
+    // Convert everything before writing, so a bad file leaves the vault untouched
+    let mut imported = Vec::with_capacity(sources.len());
+    for source in sources {
+        let path = args.source.join(&source);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mtime = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_else(|_| Utc::now());
+        let note = convert_note(&source, &content, &map, mtime, &config.slug)
+            .with_context(|| format!("failed to import {}", path.display()))?;
+        imported.push(note);
+    }
+
+    for entry in &imported {
+        for warning in &entry.warnings {
+            eprintln!("warning: {}: {}", entry.source.display(), warning);
+        }
+    }
+
+    if !args.dry_run {
+        for entry in &mut imported {
+            let note = config.frontmatter.apply_defaults(entry.note.clone());
+            let file_path = notes_dir.join(&entry.filename);
+            write_note_with(&file_path, &note, &entry.body, &config.frontmatter)
+                .with_context(|| format!("failed to write note to {}", file_path.display()))?;
+            entry.note = note;
+        }
+
+        let db_path = index_db_path(notes_dir);
+        if let Ok(mut index) = SqliteIndex::open(&db_path) {
+            let builder = IndexBuilder::new(notes_dir.to_path_buf());
+            // Ignore index errors - notes were imported successfully
+            let _ = builder.incremental_update(&mut index);
+        }
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            if imported.is_empty() {
+                println!("No markdown files found in {}", args.source.display());
+                return Ok(());
+            }
+            for entry in &imported {
+                println!(
+                    "{} -> {} [{}]",
+                    entry.source.display(),
+                    entry.note.title(),
+                    entry.note.id().prefix()
+                );
+            }
+            let verb = if args.dry_run {
+                "Would import"
+            } else {
+                "Imported"
+            };
+            println!(
+                "{} {} note(s) from {}",
+                verb,
+                imported.len(),
+                args.source.display()
+            );
+        }
+        OutputFormat::Json => {
+            let listing: Vec<ImportedNoteListing> = imported
+                .iter()
+                .map(|entry| ImportedNoteListing {
+                    source: entry.source.to_string_lossy().to_string(),
+                    id: entry.note.id().to_string(),
+                    title: entry.note.title().to_string(),
+                    path: notes_dir
+                        .join(&entry.filename)
+                        .to_string_lossy()
+                        .to_string(),
+                    topics: entry.note.topics().iter().map(|t| t.to_string()).collect(),
+                    tags: entry.note.tags().iter().map(|t| t.to_string()).collect(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            for entry in &imported {
+                println!("{}", notes_dir.join(&entry.filename).display());
+            }
+        }
+    }
+
+    Ok(())
+}
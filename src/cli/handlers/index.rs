@@ -1,18 +1,68 @@
 //! Index command handler.
 
-use anyhow::{Context, Result};
-use std::path::Path;
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
 
-use super::{ConsoleReporter, index_db_path};
+use super::trash::sweep_trash;
+use super::{ConsoleReporter, format_bytes, index_db_path, open_vault, sync_link_titles};
+use crate::api::LinkCommentReport;
 use crate::cli::IndexArgs;
-use crate::index::{IndexBuilder, SqliteIndex};
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, IndexErrorListing, IndexStatsListing, IndexUpdateListing, Output, OutputFormat,
+    Painter, SkippedLinkCommentListing, Table, TableStatsListing, VacuumListing,
+};
+use crate::index::{BuildError, IndexBuilder, NoopReporter, SqliteIndex};
+use crate::infra::{IGNORE_FILE, IgnoreRules, ScanOptions, is_note_path};
 
-pub fn handle_index(args: &IndexArgs, notes_dir: &Path, verbose: bool) -> Result<()> {
+/// How long the vault must be quiet before `--watch` indexes what changed,
+/// so an editor's burst of writes for one save is indexed once.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub fn handle_index(
+    args: &IndexArgs,
+    notes_dir: &Path,
+    verbose: bool,
+    config: &Config,
+) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let mut index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    let builder = IndexBuilder::new(notes_dir.to_path_buf());
+    if args.stats {
+        return print_stats(args, &index, &db_path, config);
+    }
+
+    if args.vacuum {
+        return vacuum(args, &mut index);
+    }
+
+    if args.watch && matches!(args.format, OutputFormat::Json) {
+        bail!("--watch does not support JSON output");
+    }
+    // Watch before the first update, so no change made during it is missed
+    let watcher = if args.watch {
+        Some(start_watcher(notes_dir)?)
+    } else {
+        None
+    };
+
+    let builder = IndexBuilder::new(notes_dir.to_path_buf())
+        .with_scan_options(config.scan.clone())
+        .with_tokenizer(config.index.tokenizer)
+        .with_languages(config.index.languages.clone())
+        .with_store_bodies(config.index.store_bodies);
+
+    if matches!(args.format, OutputFormat::Json) {
+        return update_json(args, &builder, &mut index, notes_dir, config);
+    }
+
     let mut reporter = ConsoleReporter::new(verbose);
 
     if args.full {
@@ -40,6 +90,417 @@ pub fn handle_index(args: &IndexArgs, notes_dir: &Path, verbose: bool) -> Result
         for error in &result.errors {
             eprintln!("  {}", error);
         }
+        warn_tokenizer_mismatch(&index, config)?;
+    }
+
+    let report = convert_link_comments(notes_dir, config)?;
+    if report.converted > 0 {
+        println!(
+            "Converted {} link comment(s) in {} note(s)",
+            report.converted, report.notes
+        );
+    }
+    for skipped in &report.skipped {
+        eprintln!(
+            "  {}: skipped {}: {}",
+            relative_display(&skipped.path, notes_dir),
+            skipped.text,
+            skipped.reason
+        );
+    }
+
+    let retitled = sync_link_titles(notes_dir, config)?;
+    if retitled > 0 {
+        println!("Updated link titles in {} note(s)", retitled);
+    }
+
+    let purged = sweep_trash(notes_dir, &mut index, config, Utc::now())?;
+    if !purged.is_empty() {
+        println!(
+            "Purged {} note(s) trashed more than {} days ago",
+            purged.len(),
+            config.trash.retention_days.unwrap_or_default()
+        );
+    }
+
+    if let Some((_watcher, events)) = watcher {
+        watch(&builder, &mut index, notes_dir, config, events)?;
+    }
+
+    Ok(())
+}
+
+type WatchEvents = Receiver<notify::Result<Event>>;
+
+/// Starts watching the notes directory, returning the watcher (which stops
+/// when dropped) and its events.
+fn start_watcher(notes_dir: &Path) -> Result<(notify::RecommendedWatcher, WatchEvents)> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(tx).with_context(|| "failed to start watching for changes")?;
+    // Events name paths under the watched path, which `watch` strips again
+    watcher
+        .watch(&watch_root(notes_dir), RecursiveMode::Recursive)
+        .with_context(|| format!("failed to watch {}", notes_dir.display()))?;
+    Ok((watcher, rx))
+}
+
+fn watch_root(notes_dir: &Path) -> PathBuf {
+    notes_dir
+        .canonicalize()
+        .unwrap_or_else(|_| notes_dir.to_path_buf())
+}
+
+/// Keeps the index up to date with changes to the notes directory until the
+/// watcher stops.
+///
+/// Changes are collected until the vault has been quiet for
+/// [`WATCH_DEBOUNCE`], then only the notes they touched are re-indexed; a
+/// changed `.denignore` rescans the whole vault.
+fn watch(
+    builder: &IndexBuilder,
+    index: &mut SqliteIndex,
+    notes_dir: &Path,
+    config: &Config,
+    events: WatchEvents,
+) -> Result<()> {
+    println!(
+        "Watching {} for changes (Ctrl-C to stop)...",
+        notes_dir.display()
+    );
+    let root = watch_root(notes_dir);
+    let mut rules = config.scan.ignore_rules(notes_dir)?;
+
+    while let Ok(event) = events.recv() {
+        let mut changed = BTreeSet::new();
+        if !collect_event(event, &root, &mut changed) {
+            continue;
+        }
+        // Events that change nothing, like other commands using the index,
+        // don't hold the update back
+        let mut quiet_at = Instant::now() + WATCH_DEBOUNCE;
+        while let Ok(event) =
+            events.recv_timeout(quiet_at.saturating_duration_since(Instant::now()))
+        {
+            if collect_event(event, &root, &mut changed) {
+                quiet_at = Instant::now() + WATCH_DEBOUNCE;
+            }
+        }
+
+        let result = if changed.contains(Path::new(IGNORE_FILE)) {
+            rules = config.scan.ignore_rules(notes_dir)?;
+            builder.incremental_update(index)
+        } else {
+            let indexed: Vec<PathBuf> = index
+                .all_indexed_paths()
+                .with_context(|| "failed to read the index")?
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            let paths = changed_note_paths(&changed, notes_dir, &indexed, &config.scan, &rules);
+            if paths.is_empty() {
+                continue;
+            }
+            builder.update_paths(index, &paths)
+        };
+
+        match result {
+            Ok(result) => {
+                if result.added > 0 || result.modified > 0 || result.removed > 0 {
+                    println!(
+                        "{}: {} added, {} modified, {} removed",
+                        config.time.datetime(Utc::now()),
+                        result.added,
+                        result.modified,
+                        result.removed
+                    );
+                }
+                for error in &result.errors {
+                    eprintln!("  {}", error);
+                }
+            }
+            Err(e) => eprintln!("warning: failed to update index: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds the paths an event changed, relative to the notes directory, and
+/// returns whether there were any.
+fn collect_event(
+    event: notify::Result<Event>,
+    root: &Path,
+    changed: &mut BTreeSet<PathBuf>,
+) -> bool {
+    let event = match event {
+        Ok(event) => event,
+        Err(e) => {
+            eprintln!("warning: {}", e);
+            return false;
+        }
+    };
+    // Reading a file changes nothing, and the indexer reads every note it updates
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    let mut any = false;
+    for path in event.paths {
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        // Hidden paths hold no notes, and include the index's own writes
+        let hidden = relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        if relative == Path::new(IGNORE_FILE) || !(hidden || relative.as_os_str().is_empty()) {
+            changed.insert(relative.to_path_buf());
+            any = true;
+        }
+    }
+    any
+}
+
+/// Returns the note files that changes at `changed` (relative to the notes
+/// directory) may have touched.
+///
+/// A changed path that isn't a note may be a directory that was created,
+/// renamed or removed, so the indexed notes under it and the notes now in
+/// it are included too.
+pub(crate) fn changed_note_paths(
+    changed: &BTreeSet<PathBuf>,
+    notes_dir: &Path,
+    indexed: &[PathBuf],
+    options: &ScanOptions,
+    rules: &IgnoreRules,
+) -> Vec<PathBuf> {
+    let mut paths = BTreeSet::new();
+    for path in changed {
+        if is_note_path(path, options, rules) {
+            paths.insert(path.clone());
+            continue;
+        }
+        paths.extend(indexed.iter().filter(|p| p.starts_with(path)).cloned());
+        let dir = notes_dir.join(path);
+        if dir.is_dir() {
+            paths.extend(
+                WalkDir::new(&dir)
+                    .follow_links(true)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|e| e.file_type().is_file())
+                    .filter_map(|e| e.path().strip_prefix(notes_dir).ok().map(Path::to_path_buf))
+                    .filter(|p| is_note_path(p, options, rules)),
+            );
+        }
+    }
+    paths.into_iter().collect()
+}
+
+/// Points out a configured tokenizer the index doesn't use yet.
+fn warn_tokenizer_mismatch(index: &SqliteIndex, config: &Config) -> Result<()> {
+    let current = index
+        .tokenizer()
+        .with_context(|| "failed to read index tokenizer")?;
+    if current != config.index.tokenizer {
+        eprintln!(
+            "warning: the index uses the {} tokenizer; run `notes index --full` to switch to {}",
+            current, config.index.tokenizer
+        );
+    }
+    Ok(())
+}
+
+/// Turns `<!-- den:link -->` comments into frontmatter links once the index
+/// is current, so their targets can be resolved.
+fn convert_link_comments(notes_dir: &Path, config: &Config) -> Result<LinkCommentReport> {
+    let mut vault = open_vault(notes_dir, config)?;
+    vault
+        .convert_link_comments()
+        .with_context(|| "failed to convert link comments")
+}
+
+fn relative_display(path: &Path, notes_dir: &Path) -> String {
+    path.strip_prefix(notes_dir)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Runs the update without progress output and prints the result as JSON.
+fn update_json(
+    args: &IndexArgs,
+    builder: &IndexBuilder,
+    index: &mut SqliteIndex,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut listing = if args.full {
+        let result = builder
+            .full_rebuild_with_progress(index, &mut NoopReporter)
+            .with_context(|| "failed to rebuild index")?;
+        IndexUpdateListing {
+            full: true,
+            added: result.indexed,
+            modified: 0,
+            removed: 0,
+            errors: error_listings(&result.errors),
+            converted_links: 0,
+            skipped_link_comments: Vec::new(),
+            retitled_notes: 0,
+            purged_trash: 0,
+        }
+    } else {
+        let result = builder
+            .incremental_update_with_progress(index, &mut NoopReporter)
+            .with_context(|| "failed to update index")?;
+        warn_tokenizer_mismatch(index, config)?;
+        IndexUpdateListing {
+            full: false,
+            added: result.added,
+            modified: result.modified,
+            removed: result.removed,
+            errors: error_listings(&result.errors),
+            converted_links: 0,
+            skipped_link_comments: Vec::new(),
+            retitled_notes: 0,
+            purged_trash: 0,
+        }
+    };
+
+    let report = convert_link_comments(notes_dir, config)?;
+    listing.converted_links = report.converted;
+    listing.skipped_link_comments = report
+        .skipped
+        .iter()
+        .map(|s| SkippedLinkCommentListing {
+            path: relative_display(&s.path, notes_dir),
+            comment: s.text.clone(),
+            reason: s.reason.clone(),
+        })
+        .collect();
+    listing.retitled_notes = sync_link_titles(notes_dir, config)?;
+    listing.purged_trash = sweep_trash(notes_dir, index, config, Utc::now())?.len();
+
+    println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+    Ok(())
+}
+
+fn error_listings(errors: &[BuildError]) -> Vec<IndexErrorListing> {
+    errors
+        .iter()
+        .map(|e| IndexErrorListing {
+            path: e.path().display().to_string(),
+            message: e.message().to_string(),
+        })
+        .collect()
+}
+
+fn print_stats(
+    args: &IndexArgs,
+    index: &SqliteIndex,
+    db_path: &Path,
+    config: &Config,
+) -> Result<()> {
+    let stats = index
+        .stats()
+        .with_context(|| "failed to collect index statistics")?;
+
+    // Prefer the on-disk size, which includes any WAL/journal overhead
+    let file_size = std::fs::metadata(db_path)
+        .map(|m| m.len())
+        .unwrap_or(stats.db_size_bytes);
+
+    match args.format {
+        OutputFormat::Json => {
+            let listing = IndexStatsListing {
+                schema_version: stats.schema_version,
+                db_size_bytes: file_size,
+                fts_size_bytes: stats.fts_size_bytes,
+                free_pages: stats.free_pages,
+                page_count: stats.page_count,
+                last_full_rebuild: stats.last_full_rebuild.map(|dt| dt.to_rfc3339()),
+                tokenizer: stats.tokenizer.to_string(),
+                tables: stats
+                    .tables
+                    .iter()
+                    .map(|t| TableStatsListing {
+                        name: t.name.clone(),
+                        rows: t.rows,
+                    })
+                    .collect(),
+                suggestions: stats.suggestions,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            println!("Index: {}", db_path.display());
+            println!("Schema version: {}", stats.schema_version);
+            println!("Database size: {}", format_bytes(file_size));
+            println!("FTS index size: {}", format_bytes(stats.fts_size_bytes));
+            println!("Free pages: {} of {}", stats.free_pages, stats.page_count);
+            match stats.last_full_rebuild {
+                Some(dt) => println!("Last full rebuild: {}", config.time.datetime(dt)),
+                None => println!("Last full rebuild: never"),
+            }
+            if stats.tokenizer == config.index.tokenizer {
+                println!("Tokenizer: {}", stats.tokenizer);
+            } else {
+                println!(
+                    "Tokenizer: {} ({} configured; run `notes index --full` to switch)",
+                    stats.tokenizer, config.index.tokenizer
+                );
+            }
+
+            println!();
+            println!("Tables:");
+            let mut tables = Table::new(vec![Column::left(""), Column::left("")])
+                .indent(2)
+                .without_header();
+            for table in &stats.tables {
+                tables.row([table.name.clone(), table.rows.to_string()]);
+            }
+            tables.print(&Painter::new(&config.output));
+
+            if !stats.suggestions.is_empty() {
+                println!();
+                println!("Suggestions:");
+                for suggestion in &stats.suggestions {
+                    println!("  {}", suggestion);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn vacuum(args: &IndexArgs, index: &mut SqliteIndex) -> Result<()> {
+    let result = index.vacuum().with_context(|| "failed to vacuum index")?;
+
+    match args.format {
+        OutputFormat::Json => {
+            let listing = VacuumListing {
+                size_before: result.size_before,
+                size_after: result.size_after,
+                topics_removed: result.topics_removed,
+                tags_removed: result.tags_removed,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            println!(
+                "Vacuumed index: {} -> {}",
+                format_bytes(result.size_before),
+                format_bytes(result.size_after)
+            );
+            if result.topics_removed > 0 || result.tags_removed > 0 {
+                println!(
+                    "  removed {} unused topic(s), {} unused tag(s)",
+                    result.topics_removed, result.tags_removed
+                );
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,109 @@
+//! Keywords command handler.
+
+use anyhow::{Context, Result, bail};
+use std::path::Path;
+
+use super::open_vault;
+use super::resolve::require_note;
+use crate::api::ListFilter;
+use crate::cli::KeywordsArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Cell, Column, KeywordListing, Output, OutputFormat, Painter, Role, Table,
+};
+use crate::domain::{Corpus, Note, extract_terms};
+
+/// Returns the terms of a note's title, description and body.
+fn note_terms(note: &Note, body: &str) -> Vec<String> {
+    let text = format!(
+        "{}\n{}\n{}",
+        note.title(),
+        note.description().unwrap_or_default(),
+        body
+    );
+    extract_terms(&text)
+}
+
+pub fn handle_keywords(args: &KeywordsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if args.limit == 0 {
+        bail!("--limit must be at least 1");
+    }
+
+    let vault = open_vault(notes_dir, config)?;
+    let indexed = require_note(vault.index(), &args.note, "note", config)?;
+    let target = vault
+        .read(&indexed)
+        .with_context(|| format!("failed to read note: {}", indexed.path().display()))?;
+    let terms = note_terms(&target.note, &target.body);
+
+    // Score against every note, archived ones included; notes that can't be
+    // read are left out
+    let mut corpus = Corpus::new();
+    corpus.add(&terms);
+    let all = ListFilter {
+        include_archived: true,
+        ..ListFilter::default()
+    };
+    for other in vault.list(&all)? {
+        if other.id() == indexed.id() {
+            continue;
+        }
+        if let Ok(note) = vault.read(&other) {
+            corpus.add(&note_terms(&note.note, &note.body));
+        }
+    }
+
+    let keywords = corpus.keywords(&terms, args.limit);
+    let is_tag = |term: &str| target.note.tags().iter().any(|t| t.as_str() == term);
+
+    match args.format {
+        OutputFormat::Human => {
+            if keywords.is_empty() {
+                println!("No keywords found.");
+                return Ok(());
+            }
+            let painter = Painter::new(&config.output);
+            println!(
+                "Keywords for '{}' [{}]",
+                target.note.title(),
+                painter.paint(Role::Id, &target.note.id().prefix())
+            );
+            println!();
+            let mut table = Table::new(vec![
+                Column::left("Term").max_width(30).shrink(),
+                Column::right("Score"),
+                Column::right("Count").role(Role::Muted),
+            ]);
+            for keyword in &keywords {
+                let term = if is_tag(&keyword.term) {
+                    Cell::styled(format!("{} (tag)", keyword.term), Role::Accent)
+                } else {
+                    Cell::from(keyword.term.clone())
+                };
+                table.row([
+                    term,
+                    format!("{:.3}", keyword.score).into(),
+                    keyword.count.to_string().into(),
+                ]);
+            }
+            table.print(&painter);
+            println!();
+            println!("Compared against {} note(s)", corpus.len());
+        }
+        OutputFormat::Json => {
+            let listings: Vec<KeywordListing> = keywords
+                .iter()
+                .map(|k| KeywordListing {
+                    term: k.term.clone(),
+                    score: (k.score * 1000.0).round() / 1000.0,
+                    count: k.count,
+                    tagged: is_tag(&k.term),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => println!("{}", target.path.display()),
+    }
+
+    Ok(())
+}
@@ -1,94 +1,248 @@
-//! Link-related command handlers (backlinks, link, unlink, rels).
+//! Link-related command handlers (backlinks, link, link check, unlink, rels).
 
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::path::Path;
 
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
-use super::{index_db_path, truncate_str};
-use crate::cli::output::{NoteListing, Output, OutputFormat, RelListing};
-use crate::cli::{BacklinksArgs, LinkArgs, RelsArgs, UnlinkArgs};
+use super::batch::{NoteOutcome, STDIN_NOTES, read_stdin_notes, run_batch};
+use super::rel_export::handle_rels_export;
+use super::rel_graph::{build_backlink_tree, collect_paths, handle_rels_graph, render_rel_forest};
+use super::resolve::{choose_note, find_note, require_note, require_or_pick_note};
+use super::{index_db_path, open_vault};
+use crate::api::{InboundReport, Vault};
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, NoteListing, Output, OutputFormat, Painter, RelListing, Role, Table,
+};
+use crate::cli::{
+    BacklinksArgs, LinkArgs, LinkCheckArgs, LinkCommand, RelsArgs, RelsCommand, UnlinkArgs,
+};
 use crate::domain::{Link, Note, NoteId, Rel};
-use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
-use crate::infra::{read_note, write_note};
+use crate::index::{IndexBuilder, IndexRepository, IndexedNote, SqliteIndex};
+use crate::infra::{read_note, slugify, write_note_with};
 
-pub fn handle_backlinks(args: &BacklinksArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_backlinks(args: &BacklinksArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(note) => {
-            // Parse optional rel filter
-            let rel = match &args.rel {
-                Some(rel_str) => Some(crate::domain::Rel::new(rel_str).map_err(|e| {
-                    anyhow::anyhow!("invalid relationship type '{}': {}", rel_str, e)
-                })?),
-                None => None,
-            };
+    let note = match find_note(&index, &args.note, "note", config)? {
+        Some(note) => note,
+        None if args.mentions => resolve_handle(&index, &args.note, config.interactive)?,
+        None => bail!("note not found: '{}'", args.note),
+    };
 
-            let mut backlinks = index
-                .backlinks(note.id(), rel.as_ref())
-                .with_context(|| "failed to query backlinks")?;
+    // Parse optional rel filter
+    let rel = match &args.rel {
+        Some(rel_str) => Some(
+            crate::domain::Rel::new(rel_str)
+                .map_err(|e| anyhow::anyhow!("invalid relationship type '{}': {}", rel_str, e))?,
+        ),
+        None => None,
+    };
 
-            // Sort by modified date, most recent first
-            backlinks.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+    if args.recursive {
+        return print_backlink_tree(&index, &note, rel.as_ref(), args, notes_dir);
+    }
 
-            match args.format {
-                OutputFormat::Human => {
-                    if backlinks.is_empty() {
-                        println!("No backlinks found.");
-                    } else {
-                        println!("{:<10}  {:<50}  {:>10}", "ID", "Title", "Modified");
-                        println!(
-                            "{:<10}  {:<50}  {:>10}",
-                            "----------",
-                            "--------------------------------------------------",
-                            "----------"
-                        );
-
-                        for backlink in &backlinks {
-                            let id_short = backlink.id().prefix();
-                            let title = truncate_str(backlink.title(), 50);
-                            let modified = backlink.modified().format("%Y-%m-%d").to_string();
-                            println!("{:<10}  {:<50}  {:>10}", id_short, title, modified);
-                        }
-
-                        println!();
-                        println!("{} backlink(s)", backlinks.len());
-                    }
-                }
-                OutputFormat::Json => {
-                    let listings: Vec<NoteListing> = backlinks
-                        .iter()
-                        .map(|n| NoteListing {
-                            id: n.id().to_string(),
-                            title: n.title().to_string(),
-                            path: n.path().to_string_lossy().to_string(),
-                        })
-                        .collect();
-                    let output = Output::new(listings);
-                    println!("{}", serde_json::to_string_pretty(&output)?);
-                }
-                OutputFormat::Paths => {
-                    for backlink in &backlinks {
-                        println!("{}", notes_dir.join(backlink.path()).display());
-                    }
+    let mut backlinks = index
+        .backlinks(note.id(), rel.as_ref())
+        .with_context(|| "failed to query backlinks")?;
+
+    if args.mentions {
+        let mentioning = index
+            .notes_mentioning(&mention_handles(&note))
+            .with_context(|| "failed to query mentions")?;
+        for mentioner in mentioning {
+            if mentioner.id() != note.id() && !backlinks.iter().any(|b| b.id() == mentioner.id()) {
+                backlinks.push(mentioner);
+            }
+        }
+    }
+
+    // Sort by modified date, most recent first
+    backlinks.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+
+    match args.format {
+        OutputFormat::Human => {
+            if backlinks.is_empty() {
+                println!("No backlinks found.");
+            } else {
+                let mut table = Table::new(vec![
+                    Column::left("ID").role(Role::Id),
+                    Column::left("Title").max_width(50).shrink(),
+                    Column::right("Modified").role(Role::Muted),
+                ]);
+                for backlink in &backlinks {
+                    table.row([
+                        backlink.id().prefix(),
+                        backlink.title().to_string(),
+                        config.time.date(backlink.modified()),
+                    ]);
                 }
+                table.print(&Painter::new(&config.output));
+
+                println!();
+                println!("{} backlink(s)", backlinks.len());
             }
-            Ok(())
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        OutputFormat::Json => {
+            let listings: Vec<NoteListing> = backlinks
+                .iter()
+                .map(|n| NoteListing {
+                    id: n.id().to_string(),
+                    title: n.title().to_string(),
+                    path: n.path().to_string_lossy().to_string(),
+                    note_type: n.note_type().map(String::from),
+                    description: None,
+                    words: None,
+                })
+                .collect();
+            let output = Output::new(listings);
+            println!("{}", serde_json::to_string_pretty(&output)?);
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+        OutputFormat::Paths => {
+            for backlink in &backlinks {
+                println!("{}", notes_dir.join(backlink.path()).display());
+            }
         }
     }
+    Ok(())
+}
+
+/// Returns the `@handles` that mention a note: the slugs of its title and aliases.
+fn mention_handles(note: &IndexedNote) -> Vec<String> {
+    let mut handles: Vec<String> = std::iter::once(note.title())
+        .chain(note.aliases().iter().map(String::as_str))
+        .map(slugify)
+        .collect();
+    handles.sort();
+    handles.dedup();
+    handles
 }
 
-pub fn handle_link(args: &LinkArgs, notes_dir: &Path) -> Result<()> {
+/// Resolves a mention handle such as `jane-doe` or `@jane-doe` to the note
+/// it mentions.
+fn resolve_handle(index: &SqliteIndex, handle: &str, interactive: bool) -> Result<IndexedNote> {
+    let slug = handle.strip_prefix('@').unwrap_or(handle).to_lowercase();
+    let mut matches: Vec<IndexedNote> = index
+        .list_all()
+        .with_context(|| "failed to list notes")?
+        .into_iter()
+        .filter(|n| mention_handles(n).contains(&slug))
+        .collect();
+
+    match matches.len() {
+        0 => bail!("note not found: '{}'", handle),
+        1 => Ok(matches.remove(0)),
+        _ => choose_note(handle, matches, "note", interactive),
+    }
+}
+
+/// Prints the tree of transitive backlinks for `backlinks --recursive`.
+fn print_backlink_tree(
+    index: &SqliteIndex,
+    note: &IndexedNote,
+    rel: Option<&Rel>,
+    args: &BacklinksArgs,
+    notes_dir: &Path,
+) -> Result<()> {
+    if args.depth == 0 {
+        bail!("--depth must be at least 1");
+    }
+
+    let tree = build_backlink_tree(note, args.depth, |id| {
+        let mut linkers = index
+            .backlinks(id, rel)
+            .with_context(|| "failed to query backlinks")?;
+        linkers.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+        Ok(linkers)
+    })?;
+
+    // Every note in the tree except the root, once each
+    let mut paths = Vec::new();
+    let mut seen = HashSet::from([note.id().clone()]);
+    collect_paths(&tree.children, &mut seen, &mut paths);
+
+    match args.format {
+        OutputFormat::Human => {
+            if tree.children.is_empty() {
+                println!("No backlinks found.");
+                return Ok(());
+            }
+            for line in render_rel_forest(std::slice::from_ref(&tree)) {
+                println!("{}", line);
+            }
+            println!();
+            println!(
+                "{} note(s) link here within {} level(s)",
+                paths.len(),
+                args.depth
+            );
+        }
+        OutputFormat::Json => {
+            let output = Output::new(tree.to_listing());
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Paths => {
+            for path in paths {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Result of link/unlink operations for JSON output.
+#[derive(Debug, Serialize)]
+pub struct LinkResult {
+    pub source_id: String,
+    pub source_title: String,
+    pub path: String,
+    pub target_id: String,
+    /// Relationship types on the link after the operation (empty after unlink).
+    pub rels: Vec<String>,
+    /// False when the link already existed (or was already absent).
+    pub changed: bool,
+}
+
+/// Builds the outcome of a link/unlink operation.
+fn link_outcome(
+    source: &Note,
+    file_path: &Path,
+    target_id: &NoteId,
+    link: Option<&Link>,
+    changed: bool,
+    message: String,
+) -> NoteOutcome<LinkResult> {
+    NoteOutcome {
+        result: LinkResult {
+            source_id: source.id().to_string(),
+            source_title: source.title().to_string(),
+            path: file_path.to_string_lossy().to_string(),
+            target_id: target_id.to_string(),
+            rels: link
+                .map(|l| l.rel().iter().map(|r| r.to_string()).collect())
+                .unwrap_or_default(),
+            changed,
+        },
+        message,
+        path: file_path.to_path_buf(),
+        changed,
+    }
+}
+
+/// Finds the link to the given target, if any.
+fn link_to<'a>(note: &'a Note, target_id: &NoteId) -> Option<&'a Link> {
+    note.links().iter().find(|l| l.target() == target_id)
+}
+
+pub fn handle_link(args: &LinkArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if let Some(LinkCommand::Check(check_args)) = &args.command {
+        return handle_link_check(check_args, notes_dir, config);
+    }
     // 1. Validate rels
     if args.rels.is_empty() {
         bail!("link requires at least one --rel");
@@ -99,44 +253,39 @@ pub fn handle_link(args: &LinkArgs, notes_dir: &Path) -> Result<()> {
         .map(|r| Rel::new(r).map_err(|e| anyhow::anyhow!("invalid rel '{}': {}", r, e)))
         .collect::<Result<Vec<_>>>()?;
 
-    // 2. Open index
-    let db_path = index_db_path(notes_dir);
-    let index = SqliteIndex::open(&db_path)
-        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    // 2. Open vault
+    let mut vault = open_vault(notes_dir, config)?;
 
-    // 3. Resolve source (must exist)
-    let source_note = match resolve_note(&index, &args.source)? {
-        ResolveResult::Unique(note) => note,
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.source, &notes);
-            bail!("ambiguous source note identifier");
-        }
-        ResolveResult::NotFound => {
-            bail!("source note not found: '{}'", args.source);
-        }
+    // 3. Resolve source (must exist), unless the sources come from stdin
+    let from_stdin = args.source.as_deref() == Some(STDIN_NOTES);
+    if from_stdin && args.target.is_none() {
+        bail!("a target note is required when reading source notes from stdin");
+    }
+    let source_note = if from_stdin {
+        None
+    } else {
+        Some(require_or_pick_note(
+            vault.index(),
+            args.source.as_deref(),
+            "source note",
+            config,
+        )?)
     };
 
     // 4. Resolve target (may not exist - broken links allowed)
-    let target_id: NoteId = match resolve_note(&index, &args.target)? {
-        ResolveResult::Unique(note) => note.id().clone(),
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.target, &notes);
-            bail!("ambiguous target note identifier");
-        }
-        ResolveResult::NotFound => args.target.parse::<NoteId>().map_err(|_| {
-            anyhow::anyhow!(
-                "target not found and not a valid note ID: '{}'",
-                args.target
-            )
-        })?,
+    let target_id: NoteId = match &args.target {
+        Some(target) => match find_note(vault.index(), target, "target note", config)? {
+            Some(note) => note.id().clone(),
+            None => target.parse::<NoteId>().map_err(|_| {
+                anyhow::anyhow!("target not found and not a valid note ID: '{}'", target)
+            })?,
+        },
+        None => require_or_pick_note(vault.index(), None, "target note", config)?
+            .id()
+            .clone(),
     };
 
-    // 5. Read source note from disk
-    let file_path = notes_dir.join(source_note.path());
-    let parsed = read_note(&file_path)
-        .with_context(|| format!("failed to read note: {}", file_path.display()))?;
-
-    // 6. Build new link
+    // 5. Build new link
     let new_link = match &args.note {
         Some(ctx) => Link::with_context(
             target_id.clone(),
@@ -146,104 +295,242 @@ pub fn handle_link(args: &LinkArgs, notes_dir: &Path) -> Result<()> {
         None => Link::new(target_id.clone(), rels.iter().map(|r| r.as_str()).collect())?,
     };
 
-    // 7. Check for existing link, merge if needed
-    let (updated_links, changed) = merge_or_add_link(parsed.note.links(), &new_link);
+    // 6. Merge into an existing link or add it, then write and reindex
+    match source_note {
+        Some(source_note) => add_link(&mut vault, &source_note, &new_link)?.print(args.format),
+        None => {
+            let notes = read_stdin_notes(notes_dir)?;
+            run_batch(&notes, args.format, |note| {
+                let source_note = require_note(vault.index(), note, "source note", config)?;
+                add_link(&mut vault, &source_note, &new_link)
+            })
+        }
+    }
+}
 
-    if !changed {
-        println!(
+/// Adds `new_link` to the source note, merging it into an existing link to
+/// the same target.
+fn add_link(
+    vault: &mut Vault,
+    source_note: &IndexedNote,
+    new_link: &Link,
+) -> Result<NoteOutcome<LinkResult>> {
+    let target_id = new_link.target();
+    let update = vault.add_link(source_note, new_link)?;
+
+    let message = if update.changed {
+        format!(
+            "Added link: '{}' [{}] -> [{}] ({})",
+            update.note.title(),
+            update.note.id().prefix(),
+            target_id.prefix(),
+            new_link
+                .rel()
+                .iter()
+                .map(|r| r.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    } else {
+        format!(
             "Link already exists: '{}' -> {}",
-            parsed.note.title(),
+            update.note.title(),
             target_id.prefix()
-        );
-        return Ok(());
-    }
+        )
+    };
+    Ok(link_outcome(
+        &update.note,
+        &update.path,
+        target_id,
+        link_to(&update.note, target_id),
+        update.changed,
+        message,
+    ))
+}
 
-    // 8. Rebuild note with updated links
-    let now = Utc::now();
-    let updated_note = Note::builder(
-        parsed.note.id().clone(),
-        parsed.note.title(),
-        parsed.note.created(),
-        now,
-    )
-    .description(parsed.note.description().map(String::from))
-    .topics(parsed.note.topics().to_vec())
-    .aliases(parsed.note.aliases().to_vec())
-    .tags(parsed.note.tags().to_vec())
-    .links(updated_links)
-    .build()
-    .with_context(|| "failed to rebuild note")?;
+/// A note referencing the audited note with a frontmatter link, in
+/// `link check` JSON output.
+#[derive(Debug, Serialize)]
+struct InboundLinkListing {
+    source: NoteListing,
+    rels: Vec<String>,
+}
 
-    // 9. Write atomically
-    write_note(&file_path, &updated_note, &parsed.body)
-        .with_context(|| "failed to write updated note")?;
+/// A body reference to the audited note, in `link check` JSON output.
+#[derive(Debug, Serialize)]
+struct InboundMentionListing {
+    source: NoteListing,
+    line: usize,
+    kind: &'static str,
+    text: String,
+}
 
-    // 10. Update index
-    if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-        let builder = IndexBuilder::new(notes_dir.to_path_buf());
-        let _ = builder.incremental_update(&mut idx);
-    }
+/// A file the audited note shares with other notes, in `link check` JSON
+/// output.
+#[derive(Debug, Serialize)]
+struct SharedAttachmentListing {
+    path: String,
+    shared_with: Vec<NoteListing>,
+}
 
-    println!(
-        "Added link: '{}' [{}] -> [{}] ({})",
-        updated_note.title(),
-        updated_note.id().prefix(),
-        target_id.prefix(),
-        new_link
-            .rel()
-            .iter()
-            .map(|r| r.as_str())
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
-    Ok(())
+/// `link check` JSON output.
+#[derive(Debug, Serialize)]
+struct InboundListing {
+    target: NoteListing,
+    links: Vec<InboundLinkListing>,
+    mentions: Vec<InboundMentionListing>,
+    attachments: Vec<SharedAttachmentListing>,
 }
 
-/// Merge new link into existing links, or add if not present.
-/// Returns (updated_links, changed).
-fn merge_or_add_link(existing: &[Link], new: &Link) -> (Vec<Link>, bool) {
-    let mut links = existing.to_vec();
+fn note_listing(note: &IndexedNote) -> NoteListing {
+    NoteListing {
+        id: note.id().to_string(),
+        title: note.title().to_string(),
+        path: note.path().to_string_lossy().to_string(),
+        note_type: note.note_type().map(String::from),
+        description: None,
+        words: None,
+    }
+}
 
-    if let Some(pos) = links.iter().position(|l| l.target() == new.target()) {
-        let existing_link = &links[pos];
+/// Handle `link check --target`: report the frontmatter links, body
+/// references and shared attachments that removing or archiving the note
+/// would affect.
+fn handle_link_check(args: &LinkCheckArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let vault = open_vault(notes_dir, config)?;
+    let target = require_note(vault.index(), &args.target, "note", config)?;
+    let report = vault
+        .inbound_references(&target)
+        .with_context(|| "failed to scan notes for references")?;
 
-        // Check if rels need merging
-        let mut merged_rels: Vec<Rel> = existing_link.rel().to_vec();
-        let mut added_rels = false;
-        for rel in new.rel() {
-            if !merged_rels.contains(rel) {
-                merged_rels.push(rel.clone());
-                added_rels = true;
+    match args.format {
+        OutputFormat::Human => print_inbound_report(&target, &report, config),
+        OutputFormat::Json => {
+            let listing = InboundListing {
+                target: note_listing(&target),
+                links: report
+                    .links
+                    .iter()
+                    .map(|link| InboundLinkListing {
+                        source: note_listing(&link.source),
+                        rels: link.rels.iter().map(|r| r.as_str().to_string()).collect(),
+                    })
+                    .collect(),
+                mentions: report
+                    .mentions
+                    .iter()
+                    .map(|mention| InboundMentionListing {
+                        source: note_listing(&mention.source),
+                        line: mention.reference.line,
+                        kind: mention.reference.kind.as_str(),
+                        text: mention.reference.text.clone(),
+                    })
+                    .collect(),
+                attachments: report
+                    .attachments
+                    .iter()
+                    .map(|attachment| SharedAttachmentListing {
+                        path: attachment.path.to_string_lossy().to_string(),
+                        shared_with: attachment.shared_with.iter().map(note_listing).collect(),
+                    })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            // The notes that would be affected
+            let mut seen = HashSet::new();
+            let sources = report
+                .links
+                .iter()
+                .map(|link| &link.source)
+                .chain(report.mentions.iter().map(|mention| &mention.source))
+                .chain(report.attachments.iter().flat_map(|a| &a.shared_with));
+            for source in sources {
+                if seen.insert(source.id().clone()) {
+                    println!("{}", notes_dir.join(source.path()).display());
+                }
             }
         }
+    }
+    Ok(())
+}
 
-        // Check if context changed
-        let context_changed = new.context().is_some() && new.context() != existing_link.context();
+/// Prints the `link check` report as tables, one per kind of reference.
+fn print_inbound_report(target: &IndexedNote, report: &InboundReport, config: &Config) {
+    if report.is_empty() {
+        println!(
+            "Nothing references '{}' ({}).",
+            target.title(),
+            target.id().prefix()
+        );
+        return;
+    }
+    println!(
+        "References to '{}' ({}):",
+        target.title(),
+        target.id().prefix()
+    );
+    let painter = Painter::new(&config.output);
+
+    if !report.links.is_empty() {
+        println!("\nFrontmatter links ({}):", report.links.len());
+        let mut table = Table::new(vec![
+            Column::left("ID").role(Role::Id),
+            Column::left("Title").max_width(50).shrink(),
+            Column::left("Rels").role(Role::Muted),
+        ]);
+        for link in &report.links {
+            table.row([
+                link.source.id().prefix(),
+                link.source.title().to_string(),
+                link.rels
+                    .iter()
+                    .map(Rel::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        table.print(&painter);
+    }
 
-        if !added_rels && !context_changed {
-            return (links, false); // No change
+    if !report.mentions.is_empty() {
+        println!("\nBody references ({}):", report.mentions.len());
+        let mut table = Table::new(vec![
+            Column::left("ID").role(Role::Id),
+            Column::left("Title").max_width(40).shrink(),
+            Column::right("Line").role(Role::Muted),
+            Column::left("Reference").max_width(50).shrink(),
+        ]);
+        for mention in &report.mentions {
+            table.row([
+                mention.source.id().prefix(),
+                mention.source.title().to_string(),
+                mention.reference.line.to_string(),
+                format!("{} {}", mention.reference.kind, mention.reference.text),
+            ]);
         }
+        table.print(&painter);
+    }
 
-        // Build merged link
-        let context = new.context().or(existing_link.context());
-        let merged = match context {
-            Some(ctx) => Link::with_context(
-                new.target().clone(),
-                merged_rels.iter().map(|r| r.as_str()).collect(),
-                ctx,
-            )
-            .unwrap(),
-            None => Link::new(
-                new.target().clone(),
-                merged_rels.iter().map(|r| r.as_str()).collect(),
-            )
-            .unwrap(),
-        };
-        links[pos] = merged;
-        (links, true)
-    } else {
-        links.push(new.clone());
-        (links, true)
+    if !report.attachments.is_empty() {
+        println!("\nShared attachments ({}):", report.attachments.len());
+        let mut table = Table::new(vec![
+            Column::left("File").shrink(),
+            Column::left("Also used by").max_width(60).shrink(),
+        ]);
+        for attachment in &report.attachments {
+            table.row([
+                attachment.path.display().to_string(),
+                attachment
+                    .shared_with
+                    .iter()
+                    .map(|n| format!("{} {}", n.id().prefix(), n.title()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        table.print(&painter);
     }
 }
 
@@ -260,32 +547,19 @@ fn remove_link(existing: &[Link], target_id: &NoteId) -> (Vec<Link>, bool) {
     }
 }
 
-pub fn handle_unlink(args: &UnlinkArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_unlink(args: &UnlinkArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     // 1. Open index
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
     // 2. Resolve source note (must exist)
-    let source_note = match resolve_note(&index, &args.source)? {
-        ResolveResult::Unique(note) => note,
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.source, &notes);
-            bail!("ambiguous source note identifier");
-        }
-        ResolveResult::NotFound => {
-            bail!("source note not found: '{}'", args.source);
-        }
-    };
+    let source_note = require_note(&index, &args.source, "source note", config)?;
 
     // 3. Resolve target (allow ID-only for broken links)
-    let target_id: NoteId = match resolve_note(&index, &args.target)? {
-        ResolveResult::Unique(note) => note.id().clone(),
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.target, &notes);
-            bail!("ambiguous target note identifier");
-        }
-        ResolveResult::NotFound => args.target.parse::<NoteId>().map_err(|_| {
+    let target_id: NoteId = match find_note(&index, &args.target, "target note", config)? {
+        Some(note) => note.id().clone(),
+        None => args.target.parse::<NoteId>().map_err(|_| {
             anyhow::anyhow!(
                 "target note not found and not a valid note ID: '{}'",
                 args.target
@@ -302,13 +576,14 @@ pub fn handle_unlink(args: &UnlinkArgs, notes_dir: &Path) -> Result<()> {
     let (updated_links, changed) = remove_link(parsed.note.links(), &target_id);
 
     if !changed {
-        println!(
+        let message = format!(
             "No link found: '{}' [{}] -> [{}]",
             parsed.note.title(),
             parsed.note.id().prefix(),
             target_id.prefix()
         );
-        return Ok(());
+        return link_outcome(&parsed.note, &file_path, &target_id, None, false, message)
+            .print(args.format);
     }
 
     // 6. Rebuild note with updated links
@@ -324,11 +599,12 @@ pub fn handle_unlink(args: &UnlinkArgs, notes_dir: &Path) -> Result<()> {
     .aliases(parsed.note.aliases().to_vec())
     .tags(parsed.note.tags().to_vec())
     .links(updated_links)
+    .extra(parsed.note.extra().clone())
     .build()
     .with_context(|| "failed to rebuild note")?;
 
     // 7. Write atomically
-    write_note(&file_path, &updated_note, &parsed.body)
+    write_note_with(&file_path, &updated_note, &parsed.body, &config.frontmatter)
         .with_context(|| "failed to write updated note")?;
 
     // 8. Update index
@@ -338,21 +614,28 @@ pub fn handle_unlink(args: &UnlinkArgs, notes_dir: &Path) -> Result<()> {
     }
 
     // 9. Print success
-    println!(
+    let message = format!(
         "Removed link: '{}' [{}] -> [{}]",
         updated_note.title(),
         updated_note.id().prefix(),
         target_id.prefix()
     );
-
-    Ok(())
+    link_outcome(&updated_note, &file_path, &target_id, None, true, message).print(args.format)
 }
 
-pub fn handle_rels(args: &RelsArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_rels(args: &RelsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
+    match &args.command {
+        Some(RelsCommand::Graph(graph_args)) => return handle_rels_graph(graph_args, &index),
+        Some(RelsCommand::Export(export_args)) => {
+            return handle_rels_export(export_args, &index, config);
+        }
+        None => {}
+    }
+
     let rels = index.all_rels().with_context(|| "failed to list rels")?;
 
     match args.format {
@@ -1,60 +1,60 @@
 //! List command handler.
 
 use anyhow::{Context, Result};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
 use std::path::Path;
 
-use super::{ARCHIVED_TAG, index_db_path, truncate_str};
-use crate::cli::ListArgs;
+use super::resolve::note_label;
+use super::{ARCHIVED_TAG, index_db_path};
+use crate::cli::config::{Config, NoteRefStyle};
 use crate::cli::date_filter::DateFilter;
-use crate::cli::output::{NoteListing, Output, OutputFormat};
-use crate::domain::{Tag, Topic};
+use crate::cli::output::{Column, NoteListing, Output, OutputFormat, Painter, Role, Table, page};
+use crate::cli::{ListArgs, ListColumn};
+use crate::domain::{NoteId, Tag, Topic};
 use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
 
-pub fn handle_list(args: &ListArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_list(args: &ListArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    // 1. Fetch initial set based on topic argument
-    let mut notes: Vec<IndexedNote> = if let Some(topic_arg) = &args.topic {
-        let (topic_str, include_descendants) = if topic_arg.ends_with('/') {
-            (topic_arg.trim_end_matches('/'), true)
-        } else {
-            (topic_arg.as_str(), false)
-        };
+    // 1-2. Fetch notes under the topic with every tag
+    let mut notes = notes_matching(&index, args.topic.as_deref(), &args.tags)?;
 
-        let topic =
-            Topic::new(topic_str).with_context(|| format!("invalid topic: {}", topic_str))?;
-
-        index
-            .list_by_topic(&topic, include_descendants)
-            .with_context(|| "failed to list notes by topic")?
-    } else {
-        index
-            .list_all()
-            .with_context(|| "failed to list all notes")?
-    };
+    // 3. Hygiene filters
+    if args.orphans {
+        let orphans = index
+            .list_without_topics()
+            .with_context(|| "failed to list notes without topics")?;
+        retain_ids(&mut notes, &orphans);
+    }
 
-    // 2. Filter by tags (AND logic)
-    for tag_str in &args.tags {
-        let tag = Tag::new(tag_str).with_context(|| format!("invalid tag: {}", tag_str))?;
+    if args.untagged {
+        let untagged = index
+            .list_without_tags()
+            .with_context(|| "failed to list notes without tags")?;
+        retain_ids(&mut notes, &untagged);
+    }
 
-        let notes_with_tag = index
-            .list_by_tag(&tag)
-            .with_context(|| format!("failed to list notes with tag: {}", tag_str))?;
+    if args.no_links {
+        let unlinked = index
+            .list_unlinked()
+            .with_context(|| "failed to list unlinked notes")?;
+        retain_ids(&mut notes, &unlinked);
+    }
 
-        let tag_ids: HashSet<_> = notes_with_tag.iter().map(|n| n.id().clone()).collect();
-        notes.retain(|n| tag_ids.contains(n.id()));
+    if let Some(note_type) = &args.note_type {
+        notes.retain(|n| n.note_type() == Some(note_type.as_str()));
     }
 
-    // 3. Exclude archived unless --include-archived
+    // 4. Exclude archived unless --include-archived
     if !args.include_archived {
         let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
         notes.retain(|n| !n.tags().contains(&archived_tag));
     }
 
-    // 4. Filter by dates
+    // 5. Filter by dates
     if let Some(created_str) = &args.created {
         let filter = DateFilter::parse(created_str)
             .map_err(|e| anyhow::anyhow!("invalid --created filter: {}", e))?;
@@ -67,41 +67,118 @@ pub fn handle_list(args: &ListArgs, notes_dir: &Path) -> Result<()> {
         notes.retain(|n| filter.matches(n.modified()));
     }
 
-    // 5. Sort by modified date, most recent first
+    // 6. Sort by modified date, most recent first, or with --largest by
+    //    word count. Notes indexed before word counts were kept have none
+    //    and sort last
     notes.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+    let mut word_counts: HashMap<NoteId, usize> = HashMap::new();
+    if args.largest {
+        word_counts = index
+            .word_counts()
+            .with_context(|| "failed to read word counts")?;
+        notes.sort_by_key(|n| std::cmp::Reverse(word_counts.get(n.id()).copied()));
+    }
 
-    // 6. Output based on format
+    // 7. Output based on format
     match args.format {
         OutputFormat::Human => {
             if notes.is_empty() {
                 println!("No notes found.");
             } else {
-                println!("{:<10}  {:<50}  {:>10}", "ID", "Title", "Modified");
-                println!(
-                    "{:<10}  {:<50}  {:>10}",
-                    "----------",
-                    "--------------------------------------------------",
-                    "----------"
-                );
+                let header = match config.note_refs {
+                    NoteRefStyle::Id => "ID",
+                    NoteRefStyle::Ref | NoteRefStyle::Both => "Ref",
+                };
+                let painter = Painter::new(&config.output);
+
+                // The Tags column only appears once tag styles are configured,
+                // and the Type column once a listed note has a type, unless
+                // asked for with --columns
+                let show_created = args.columns.contains(&ListColumn::Created);
+                let show_tags = config.tags.is_styled() || args.columns.contains(&ListColumn::Tags);
+                let show_types = notes.iter().any(|n| n.note_type().is_some())
+                    || args.columns.contains(&ListColumn::Type);
+                let show_descriptions = args.columns.contains(&ListColumn::Description);
+                let mut columns = vec![
+                    Column::left(header).role(Role::Id),
+                    Column::left("Title").max_width(50).shrink(),
+                    Column::right("Modified").role(Role::Muted),
+                ];
+                if show_created {
+                    columns.push(Column::right("Created").role(Role::Muted));
+                }
+                if show_types {
+                    columns.push(Column::left("Type"));
+                }
+                if show_tags {
+                    columns.push(Column::left("Tags"));
+                }
+                if show_descriptions {
+                    columns.push(Column::left("Description").max_width(60).shrink());
+                }
+                if args.largest {
+                    columns.push(Column::right("Words"));
+                }
 
+                let mut table = Table::new(columns);
                 for note in &notes {
-                    let id_short = note.id().prefix();
-                    let title = truncate_str(note.title(), 50);
-                    let modified = note.modified().format("%Y-%m-%d").to_string();
-                    println!("{:<10}  {:<50}  {:>10}", id_short, title, modified);
+                    let mut row = vec![
+                        note_label(note, config.note_refs, &config.slug),
+                        note.title().to_string(),
+                        config.time.date(note.modified()),
+                    ];
+                    if show_created {
+                        row.push(config.time.date(note.created()));
+                    }
+                    if show_types {
+                        row.push(
+                            note.note_type()
+                                .map(|t| config.types.render(t, painter.color()))
+                                .unwrap_or_default(),
+                        );
+                    }
+                    if show_tags {
+                        row.push(
+                            config.tags.render_list(
+                                note.tags().iter().map(|t| t.as_str()),
+                                painter.color(),
+                            ),
+                        );
+                    }
+                    if show_descriptions {
+                        row.push(note.description().unwrap_or_default().to_string());
+                    }
+                    if args.largest {
+                        row.push(
+                            word_counts
+                                .get(note.id())
+                                .map(|w| w.to_string())
+                                .unwrap_or_default(),
+                        );
+                    }
+                    table.row(row);
                 }
 
-                println!();
-                println!("{} note(s)", notes.len());
+                let mut out = table.render(&painter);
+                writeln!(out)?;
+                writeln!(out, "{} note(s)", notes.len())?;
+                page(&out, &config.output)?;
             }
         }
         OutputFormat::Json => {
+            let with_descriptions = args.columns.contains(&ListColumn::Description);
             let listings: Vec<NoteListing> = notes
                 .iter()
                 .map(|n| NoteListing {
                     id: n.id().to_string(),
                     title: n.title().to_string(),
                     path: n.path().to_string_lossy().to_string(),
+                    note_type: n.note_type().map(String::from),
+                    description: n
+                        .description()
+                        .filter(|_| with_descriptions)
+                        .map(String::from),
+                    words: word_counts.get(n.id()).copied(),
                 })
                 .collect();
             let output = Output::new(listings);
@@ -117,6 +194,52 @@ pub fn handle_list(args: &ListArgs, notes_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Lists the notes under `topic` (a trailing `/` includes descendants, none
+/// lists every note) that have all of `tags`.
+pub(crate) fn notes_matching(
+    index: &SqliteIndex,
+    topic: Option<&str>,
+    tags: &[String],
+) -> Result<Vec<IndexedNote>> {
+    let mut notes: Vec<IndexedNote> = if let Some(topic_arg) = topic {
+        let (topic_str, include_descendants) = if topic_arg.ends_with('/') {
+            (topic_arg.trim_end_matches('/'), true)
+        } else {
+            (topic_arg, false)
+        };
+
+        let topic =
+            Topic::new(topic_str).with_context(|| format!("invalid topic: {}", topic_str))?;
+
+        index
+            .list_by_topic(&topic, include_descendants)
+            .with_context(|| "failed to list notes by topic")?
+    } else {
+        index
+            .list_all()
+            .with_context(|| "failed to list all notes")?
+    };
+
+    // Filter by tags (AND logic)
+    for tag_str in tags {
+        let tag = Tag::new(tag_str).with_context(|| format!("invalid tag: {}", tag_str))?;
+
+        let notes_with_tag = index
+            .list_by_tag(&tag)
+            .with_context(|| format!("failed to list notes with tag: {}", tag_str))?;
+
+        let tag_ids: HashSet<_> = notes_with_tag.iter().map(|n| n.id().clone()).collect();
+        notes.retain(|n| tag_ids.contains(n.id()));
+    }
+    Ok(notes)
+}
+
+/// Keep only notes that also appear in `matching`.
+fn retain_ids(notes: &mut Vec<IndexedNote>, matching: &[IndexedNote]) {
+    let ids: HashSet<_> = matching.iter().map(|n| n.id().clone()).collect();
+    notes.retain(|n| ids.contains(n.id()));
+}
+
 /// Parse topic filter string, extracting path and whether to include descendants.
 pub(crate) fn parse_topic_filter(s: &str) -> (String, bool) {
     if s.ends_with('/') {
@@ -0,0 +1,226 @@
+//! List-broken command handler.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::check::load_trash;
+use super::index_db_path;
+use super::list::{note_matches_topic, parse_topic_filter};
+use super::resolve::note_label;
+use crate::cli::ListBrokenArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Cell, Column, NoteListing, Output, OutputFormat, Painter, Role, Table, page,
+};
+use crate::domain::{Link, NoteId, NoteNames, Rel, Topic, WIKILINK_REL, unresolved_wikilinks};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// A frontmatter link whose target note doesn't exist, or a body wikilink
+/// that resolves to no single note.
+#[derive(Debug, Clone)]
+pub(crate) struct BrokenLink<'a> {
+    pub source: &'a IndexedNote,
+    pub target: BrokenTarget,
+    pub rels: Vec<String>,
+}
+
+/// What a [`BrokenLink`] points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BrokenTarget {
+    /// A note ID that isn't indexed, which may be in the trash
+    Note { id: NoteId, trashed: bool },
+    /// A `[[wikilink]]` matching no note, or `matches` notes
+    Wikilink { target: String, matches: usize },
+}
+
+/// Returns the links from `notes` to notes that aren't indexed and the
+/// wikilinks that `names` can't resolve, as `check` reports them, ordered by
+/// source path, optionally only those with rel `rel`. Wikilinks have the
+/// `mentions` rel.
+pub(crate) fn broken_links<'a>(
+    notes: &'a [IndexedNote],
+    links: &HashMap<NoteId, Vec<Link>>,
+    wikilinks: &HashMap<NoteId, Vec<String>>,
+    names: &NoteNames,
+    trashed_ids: &HashSet<NoteId>,
+    rel: Option<&Rel>,
+) -> Vec<BrokenLink<'a>> {
+    let known: HashSet<&NoteId> = notes.iter().map(|n| n.id()).collect();
+    let mut sources: Vec<&IndexedNote> = notes.iter().collect();
+    sources.sort_by(|a, b| a.path().cmp(b.path()));
+
+    let mut broken = Vec::new();
+    for source in sources {
+        for link in links.get(source.id()).into_iter().flatten() {
+            if known.contains(link.target()) || rel.is_some_and(|r| !link.rel().contains(r)) {
+                continue;
+            }
+            broken.push(BrokenLink {
+                source,
+                target: BrokenTarget::Note {
+                    id: link.target().clone(),
+                    trashed: trashed_ids.contains(link.target()),
+                },
+                rels: link.rel().iter().map(|r| r.to_string()).collect(),
+            });
+        }
+        if rel.is_some_and(|r| r.to_string() != WIKILINK_REL) {
+            continue;
+        }
+        let targets = wikilinks.get(source.id()).map_or(&[][..], Vec::as_slice);
+        for (target, matches) in unresolved_wikilinks(names, targets) {
+            broken.push(BrokenLink {
+                source,
+                target: BrokenTarget::Wikilink {
+                    target: target.clone(),
+                    matches,
+                },
+                rels: vec![WIKILINK_REL.to_string()],
+            });
+        }
+    }
+    broken
+}
+
+/// A broken link in `list-broken` JSON output.
+#[derive(Debug, Serialize)]
+struct BrokenLinkListing {
+    source: NoteListing,
+    /// `None` for a wikilink
+    target_id: Option<String>,
+    rels: Vec<String>,
+    trashed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wikilink: Option<String>,
+    /// How many notes an ambiguous wikilink matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matches: Option<usize>,
+}
+
+pub fn handle_list_broken(args: &ListBrokenArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let rel = match &args.rel {
+        Some(rel) => {
+            Some(Rel::new(rel).map_err(|e| anyhow::anyhow!("invalid rel '{}': {}", rel, e))?)
+        }
+        None => None,
+    };
+
+    let all_notes = index.list_all().with_context(|| "failed to list notes")?;
+    let links = index
+        .frontmatter_links()
+        .with_context(|| "failed to query links")?;
+    let wikilinks = index
+        .all_wikilinks()
+        .with_context(|| "failed to load wikilinks")?;
+    let names = index
+        .note_names()
+        .with_context(|| "failed to load note names")?;
+    let trashed_ids: HashSet<NoteId> = load_trash(notes_dir)?
+        .into_iter()
+        .filter_map(|entry| entry.id)
+        .collect();
+
+    let mut broken = broken_links(
+        &all_notes,
+        &links,
+        &wikilinks,
+        &names,
+        &trashed_ids,
+        rel.as_ref(),
+    );
+    if let Some(topic) = &args.topic {
+        let (topic_str, include_descendants) = parse_topic_filter(topic);
+        let topic =
+            Topic::new(&topic_str).with_context(|| format!("invalid topic: {}", topic_str))?;
+        broken.retain(|b| note_matches_topic(b.source, &topic, include_descendants));
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            if broken.is_empty() {
+                println!("No broken links found.");
+                return Ok(());
+            }
+
+            let mut table = Table::new(vec![
+                Column::left("Note").role(Role::Id),
+                Column::left("Title").shrink(),
+                Column::left("Target"),
+                Column::left("Rels"),
+            ]);
+            for link in &broken {
+                let target = match &link.target {
+                    BrokenTarget::Note { id, trashed: true } => {
+                        Cell::styled(format!("{} (in trash)", id), Role::Warning)
+                    }
+                    BrokenTarget::Note { id, .. } => Cell::styled(id.to_string(), Role::Error),
+                    BrokenTarget::Wikilink { target, matches } if *matches > 1 => Cell::styled(
+                        format!("[[{}]] ({} matches)", target, matches),
+                        Role::Warning,
+                    ),
+                    BrokenTarget::Wikilink { target, .. } => {
+                        Cell::styled(format!("[[{}]]", target), Role::Warning)
+                    }
+                };
+                table.row([
+                    Cell::from(note_label(link.source, config.note_refs, &config.slug)),
+                    Cell::from(link.source.title()),
+                    target,
+                    Cell::from(link.rels.join(", ")),
+                ]);
+            }
+
+            let mut out = table.render(&Painter::new(&config.output));
+            writeln!(out)?;
+            writeln!(out, "{} broken link(s)", broken.len())?;
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            let listings: Vec<BrokenLinkListing> = broken
+                .into_iter()
+                .map(|link| {
+                    let (target_id, trashed, wikilink, matches) = match link.target {
+                        BrokenTarget::Note { id, trashed } => {
+                            (Some(id.to_string()), trashed, None, None)
+                        }
+                        BrokenTarget::Wikilink { target, matches } => {
+                            (None, false, Some(target), Some(matches))
+                        }
+                    };
+                    BrokenLinkListing {
+                        source: NoteListing {
+                            id: link.source.id().to_string(),
+                            title: link.source.title().to_string(),
+                            path: link.source.path().to_string_lossy().to_string(),
+                            note_type: link.source.note_type().map(String::from),
+                            description: None,
+                            words: None,
+                        },
+                        target_id,
+                        rels: link.rels,
+                        trashed,
+                        wikilink,
+                        matches,
+                    }
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            // Each linking note once
+            let paths: BTreeSet<_> = broken.iter().map(|link| link.source.path()).collect();
+            for path in paths {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
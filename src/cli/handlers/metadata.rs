@@ -1,22 +1,197 @@
-//! Metadata command handlers (topics, tags, tag, untag).
+//! Metadata command handlers (topics, tags, tag, untag, desc).
 
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
-use super::index_db_path;
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
-use crate::cli::output::{Output, OutputFormat, TagListing, TopicListing};
-use crate::cli::{TagArgs, TagsArgs, TopicsArgs, UntagArgs};
-use crate::domain::{Note, Tag};
-use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
-use crate::infra::{read_note, write_note};
+use super::batch::{NoteOutcome, STDIN_NOTES, read_stdin_notes, run_batch};
+use super::resolve::require_note;
+use super::tag_expire::handle_tag_expire;
+use super::tag_mv::{handle_tag_merge, handle_tag_mv};
+use super::topic_mv::handle_topic_mv;
+use super::topic_stats::handle_topic_stats;
+use super::{index_db_path, open_vault};
+use crate::api::{NoteUpdate, Vault};
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Output, OutputFormat, Painter, TagGroupListing, TagListing, TopicListing, TopicTreeListing,
+};
+use crate::cli::tag_style::TagStyles;
+use crate::cli::{DescArgs, TagArgs, TagsArgs, TagsCommand, TopicsArgs, TopicsCommand, UntagArgs};
+use crate::domain::{ARCHIVED_TAG, NoteId, Tag};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex, TagWithCount};
+
+/// A topic in the `topics --tree` hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TopicTreeNode {
+    /// Last segment of the path
+    pub name: String,
+    pub path: String,
+    /// Notes filed directly under this topic
+    pub exact: usize,
+    /// Distinct notes filed under this topic or any descendant
+    pub total: usize,
+    pub children: Vec<TopicTreeNode>,
+}
+
+impl TopicTreeNode {
+    fn to_listing(&self) -> TopicTreeListing {
+        TopicTreeListing {
+            name: self.name.clone(),
+            path: self.path.clone(),
+            count: self.exact,
+            total: self.total,
+            children: self.children.iter().map(Self::to_listing).collect(),
+        }
+    }
+}
+
+/// Builds the topic hierarchy from the notes' topics, sorted by name.
+///
+/// Parent topics that no note is filed under directly still appear, with an
+/// exact count of zero. With `max_depth`, deeper topics are left out but
+/// still count towards their ancestors' totals.
+pub(crate) fn build_topic_tree(
+    notes: &[IndexedNote],
+    max_depth: Option<usize>,
+) -> Vec<TopicTreeNode> {
+    // path -> (notes filed exactly here, notes filed here or below)
+    let mut counts: BTreeMap<String, (HashSet<&NoteId>, HashSet<&NoteId>)> = BTreeMap::new();
+    for note in notes {
+        for topic in note.topics() {
+            counts
+                .entry(topic.to_string())
+                .or_default()
+                .0
+                .insert(note.id());
+            for ancestor in topic.ancestors().iter().chain([topic]) {
+                counts
+                    .entry(ancestor.to_string())
+                    .or_default()
+                    .1
+                    .insert(note.id());
+            }
+        }
+    }
+
+    fn children_of(
+        parent: Option<&str>,
+        depth: usize,
+        counts: &BTreeMap<String, (HashSet<&NoteId>, HashSet<&NoteId>)>,
+        max_depth: Option<usize>,
+    ) -> Vec<TopicTreeNode> {
+        if max_depth.is_some_and(|max| depth > max) {
+            return Vec::new();
+        }
+        counts
+            .iter()
+            .filter(|(path, _)| match parent {
+                Some(parent) => path
+                    .strip_prefix(parent)
+                    .and_then(|rest| rest.strip_prefix('/'))
+                    .is_some_and(|rest| !rest.contains('/')),
+                None => !path.contains('/'),
+            })
+            .map(|(path, (exact, total))| TopicTreeNode {
+                name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                path: path.clone(),
+                exact: exact.len(),
+                total: total.len(),
+                children: children_of(Some(path), depth + 1, counts, max_depth),
+            })
+            .collect()
+    }
+
+    children_of(None, 1, &counts, max_depth)
+}
+
+/// Renders the topic tree one line per topic, with `(exact/total)` counts.
+pub(crate) fn render_topic_tree(nodes: &[TopicTreeNode]) -> Vec<String> {
+    fn render(nodes: &[TopicTreeNode], prefix: &str, root: bool, lines: &mut Vec<String>) {
+        for (i, node) in nodes.iter().enumerate() {
+            let last = i + 1 == nodes.len();
+            let (branch, indent) = match (root, last) {
+                (true, _) => ("", ""),
+                (false, true) => ("└── ", "    "),
+                (false, false) => ("├── ", "│   "),
+            };
+            lines.push(format!(
+                "{}{}{} ({}/{})",
+                prefix, branch, node.name, node.exact, node.total
+            ));
+            render(
+                &node.children,
+                &format!("{}{}", prefix, indent),
+                false,
+                lines,
+            );
+        }
+    }
+
+    let mut lines = Vec::new();
+    render(nodes, "", true, &mut lines);
+    lines
+}
+
+fn collect_tree_paths(nodes: &[TopicTreeNode], out: &mut Vec<String>) {
+    for node in nodes {
+        out.push(node.path.clone());
+        collect_tree_paths(&node.children, out);
+    }
+}
+
+fn handle_topic_tree(args: &TopicsArgs, index: &SqliteIndex) -> Result<()> {
+    if args.depth == Some(0) {
+        bail!("--depth must be at least 1");
+    }
+
+    let notes = index.list_all().with_context(|| "failed to list notes")?;
+    let tree = build_topic_tree(&notes, args.depth);
+
+    match args.format {
+        OutputFormat::Human => {
+            if tree.is_empty() {
+                println!("No topics found.");
+            }
+            for line in render_topic_tree(&tree) {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Json => {
+            let listings: Vec<TopicTreeListing> =
+                tree.iter().map(TopicTreeNode::to_listing).collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            let mut paths = Vec::new();
+            collect_tree_paths(&tree, &mut paths);
+            for path in paths {
+                println!("{}", path);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_topics(args: &TopicsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        Some(TopicsCommand::Stats(stats_args)) => {
+            return handle_topic_stats(stats_args, notes_dir, config);
+        }
+        Some(TopicsCommand::Mv(mv_args)) => return handle_topic_mv(mv_args, notes_dir, config),
+        None => {}
+    }
 
-pub fn handle_topics(args: &TopicsArgs, notes_dir: &Path) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
+    if args.tree {
+        return handle_topic_tree(args, &index);
+    }
+
     let topics = index
         .all_topics()
         .with_context(|| "failed to list topics")?;
@@ -59,41 +234,121 @@ pub fn handle_topics(args: &TopicsArgs, notes_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn handle_tags(args: &TagsArgs, notes_dir: &Path) -> Result<()> {
+/// Groups tags by the configured tag groups.
+///
+/// Groups come in name order with their tags in configured order; groups
+/// with no tags in use are omitted. Tags outside every group are collected
+/// last under `None`.
+pub(crate) fn group_tags<'a>(
+    tags: &'a [TagWithCount],
+    styles: &TagStyles,
+) -> Vec<(Option<String>, Vec<&'a TagWithCount>)> {
+    let mut groups = Vec::new();
+
+    for (name, members) in &styles.groups {
+        let in_group: Vec<&TagWithCount> = members
+            .iter()
+            .filter_map(|m| tags.iter().find(|t| t.tag().as_str() == m))
+            .collect();
+        if !in_group.is_empty() {
+            groups.push((Some(name.clone()), in_group));
+        }
+    }
+
+    let ungrouped: Vec<&TagWithCount> = tags
+        .iter()
+        .filter(|t| styles.group_of(t.tag().as_str()).is_none())
+        .collect();
+    if !ungrouped.is_empty() {
+        groups.push((None, ungrouped));
+    }
+
+    groups
+}
+
+fn tag_listing(t: &TagWithCount, counts: bool) -> TagListing {
+    TagListing {
+        name: t.tag().to_string(),
+        count: if counts {
+            Some(t.count() as usize)
+        } else {
+            None
+        },
+    }
+}
+
+pub fn handle_tags(args: &TagsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        Some(TagsCommand::Expire(expire_args)) => {
+            return handle_tag_expire(expire_args, notes_dir, config);
+        }
+        Some(TagsCommand::Mv(mv_args)) => return handle_tag_mv(mv_args, notes_dir, config),
+        Some(TagsCommand::Merge(merge_args)) => {
+            return handle_tag_merge(merge_args, notes_dir, config);
+        }
+        None => {}
+    }
+
+    if args.groups && config.tags.groups.is_empty() {
+        bail!("no tag groups configured; add a [tags.groups] section to the config file");
+    }
+
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
     let tags = index.all_tags().with_context(|| "failed to list tags")?;
+    let styles = &config.tags;
 
     match args.format {
         OutputFormat::Human => {
+            let color = Painter::new(&config.output).color();
+            let line = |t: &TagWithCount| {
+                let name = styles.render(t.tag().as_str(), color);
+                if args.counts {
+                    format!("{} ({})", name, t.count())
+                } else {
+                    name
+                }
+            };
+
             if tags.is_empty() {
                 println!("No tags found.");
+            } else if args.groups {
+                for (i, (group, members)) in group_tags(&tags, styles).iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    println!("{}:", group.as_deref().unwrap_or("other"));
+                    for t in members {
+                        println!("  {}", line(t));
+                    }
+                }
             } else {
                 for t in &tags {
-                    if args.counts {
-                        println!("{} ({})", t.tag(), t.count());
-                    } else {
-                        println!("{}", t.tag());
-                    }
+                    println!("{}", line(t));
                 }
             }
         }
         OutputFormat::Json => {
-            let listings: Vec<TagListing> = tags
-                .iter()
-                .map(|t| TagListing {
-                    name: t.tag().to_string(),
-                    count: if args.counts {
-                        Some(t.count() as usize)
-                    } else {
-                        None
-                    },
-                })
-                .collect();
-            let out = Output::new(listings);
-            println!("{}", serde_json::to_string_pretty(&out)?);
+            if args.groups {
+                let listings: Vec<TagGroupListing> = group_tags(&tags, styles)
+                    .into_iter()
+                    .map(|(group, members)| TagGroupListing {
+                        group,
+                        tags: members
+                            .into_iter()
+                            .map(|t| tag_listing(t, args.counts))
+                            .collect(),
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+            } else {
+                let listings: Vec<TagListing> =
+                    tags.iter().map(|t| tag_listing(t, args.counts)).collect();
+                let out = Output::new(listings);
+                println!("{}", serde_json::to_string_pretty(&out)?);
+            }
         }
         OutputFormat::Paths => {
             for t in &tags {
@@ -104,143 +359,259 @@ pub fn handle_tags(args: &TagsArgs, notes_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-pub fn handle_tag(args: &TagArgs, notes_dir: &Path) -> Result<()> {
+/// Result of tag/untag operations for JSON output.
+#[derive(Debug, Serialize)]
+pub struct TagResult {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub tag: String,
+    /// False when the note already had (or lacked) the tag and was left untouched.
+    pub changed: bool,
+}
+
+/// Builds the outcome of a tag/untag operation.
+fn tag_outcome(update: NoteUpdate, tag: &Tag, message: String) -> NoteOutcome<TagResult> {
+    NoteOutcome {
+        result: TagResult {
+            id: update.note.id().to_string(),
+            title: update.note.title().to_string(),
+            path: update.path.to_string_lossy().to_string(),
+            tag: tag.to_string(),
+            changed: update.changed,
+        },
+        message,
+        path: update.path,
+        changed: update.changed,
+    }
+}
+
+/// Adds a tag to the note `query` refers to.
+fn tag_note(
+    vault: &mut Vault,
+    query: &str,
+    tag: &Tag,
+    config: &Config,
+) -> Result<NoteOutcome<TagResult>> {
+    let note = require_note(vault.index(), query, "note", config)?;
+    let update = vault.tag(&note.id().to_string(), tag)?;
+    let message = if update.changed {
+        format!(
+            "Added tag '{}' to '{}' [{}]",
+            tag,
+            update.note.title(),
+            update.note.id().prefix()
+        )
+    } else {
+        format!("Tag '{}' already present on '{}'", tag, update.note.title())
+    };
+    Ok(tag_outcome(update, tag, message))
+}
+
+/// Removes a tag from the note `query` refers to.
+fn untag_note(
+    vault: &mut Vault,
+    query: &str,
+    tag: &Tag,
+    config: &Config,
+) -> Result<NoteOutcome<TagResult>> {
+    let note = require_note(vault.index(), query, "note", config)?;
+    let update = vault.untag(&note.id().to_string(), tag)?;
+    let message = if update.changed {
+        format!(
+            "Removed tag '{}' from '{}' [{}]",
+            tag,
+            update.note.title(),
+            update.note.id().prefix()
+        )
+    } else {
+        format!("Tag '{}' not present on '{}'", tag, update.note.title())
+    };
+    Ok(tag_outcome(update, tag, message))
+}
+
+pub fn handle_tag(args: &TagArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if let Some(query) = &args.query {
+        return handle_tag_query(args, query, notes_dir, config);
+    }
+    let Some(tag) = &args.tag else {
+        bail!("missing tag to add");
+    };
+
     // Validate tag first (before any I/O)
+    let tag = Tag::new(tag).map_err(|e| anyhow::anyhow!("invalid tag '{}': {}", tag, e))?;
+    let tag = config.tags.with_ttl(tag, config.time.today(Utc::now()));
+
+    let mut vault = open_vault(notes_dir, config)?;
+    if args.note == STDIN_NOTES {
+        let notes = read_stdin_notes(notes_dir)?;
+        return run_batch(&notes, args.format, |note| {
+            tag_note(&mut vault, note, &tag, config)
+        });
+    }
+    tag_note(&mut vault, &args.note, &tag, config)?.print(args.format)
+}
+
+/// Tags every note matching a full-text query (`tag --query`).
+fn handle_tag_query(args: &TagArgs, query: &str, notes_dir: &Path, config: &Config) -> Result<()> {
+    // With --query the only positional argument is the tag
     let tag =
-        Tag::new(&args.tag).map_err(|e| anyhow::anyhow!("invalid tag '{}': {}", args.tag, e))?;
+        Tag::new(&args.note).map_err(|e| anyhow::anyhow!("invalid tag '{}': {}", args.note, e))?;
+    let tag = config.tags.with_ttl(tag, config.time.today(Utc::now()));
+    let archived = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
 
-    let db_path = index_db_path(notes_dir);
-    let index = SqliteIndex::open(&db_path)
-        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let mut vault = open_vault(notes_dir, config)?;
+    let matches: Vec<IndexedNote> = vault
+        .search(query)
+        .with_context(|| format!("search failed for query: {}", query))?
+        .into_iter()
+        .map(|r| r.note().clone())
+        .filter(|n| args.include_archived || !n.tags().contains(&archived))
+        .collect();
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(indexed_note) => {
-            let file_path = notes_dir.join(indexed_note.path());
-            let parsed = read_note(&file_path)
-                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+    let mut results = Vec::with_capacity(matches.len());
+    for indexed in &matches {
+        let result = if args.dry_run {
+            TagResult {
+                id: indexed.id().to_string(),
+                title: indexed.title().to_string(),
+                path: notes_dir.join(indexed.path()).to_string_lossy().to_string(),
+                tag: tag.to_string(),
+                changed: !indexed.tags().contains(&tag),
+            }
+        } else {
+            let update = vault.tag(&indexed.id().to_string(), &tag)?;
+            TagResult {
+                id: update.note.id().to_string(),
+                title: update.note.title().to_string(),
+                path: update.path.to_string_lossy().to_string(),
+                tag: tag.to_string(),
+                changed: update.changed,
+            }
+        };
+        results.push(result);
+    }
 
-            // Idempotency check: if tag already exists, no-op
-            if parsed.note.tags().contains(&tag) {
-                println!("Tag '{}' already present on '{}'", tag, parsed.note.title());
+    match args.format {
+        OutputFormat::Human => {
+            if results.is_empty() {
+                println!("No matching notes found.");
                 return Ok(());
             }
-
-            // Build updated note with new tag
-            let mut tags = parsed.note.tags().to_vec();
-            tags.push(tag.clone());
-
-            let now = Utc::now();
-            let updated_note = Note::builder(
-                parsed.note.id().clone(),
-                parsed.note.title(),
-                parsed.note.created(),
-                now,
-            )
-            .description(parsed.note.description().map(|s| s.to_string()))
-            .topics(parsed.note.topics().to_vec())
-            .aliases(parsed.note.aliases().to_vec())
-            .tags(tags)
-            .links(parsed.note.links().to_vec())
-            .build()
-            .with_context(|| "failed to rebuild note")?;
-
-            // Write updated note
-            write_note(&file_path, &updated_note, &parsed.body)
-                .with_context(|| "failed to write updated note")?;
-
-            // Update index
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
-            }
-
-            println!(
-                "Added tag '{}' to '{}' [{}]",
-                tag,
-                updated_note.title(),
-                updated_note.id().prefix()
-            );
-            Ok(())
+            let changed: Vec<&TagResult> = results.iter().filter(|r| r.changed).collect();
+            if !changed.is_empty() {
+                let verb = if args.dry_run { "Would tag" } else { "Tagged" };
+                println!("{} {} note(s) with '{}':", verb, changed.len(), tag);
+                for result in &changed {
+                    let id: NoteId = result.id.parse()?;
+                    println!("  {} [{}]", result.title, id.prefix());
+                }
+            }
+            let unchanged = results.len() - changed.len();
+            if unchanged > 0 {
+                println!("{} matching note(s) already tagged '{}'", unchanged, tag);
+            }
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&Output::new(results))?);
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+        OutputFormat::Paths => {
+            for result in results.iter().filter(|r| r.changed) {
+                println!("{}", result.path);
+            }
         }
     }
+
+    Ok(())
 }
 
-pub fn handle_untag(args: &UntagArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_untag(args: &UntagArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     // Validate tag first (before any I/O)
     let tag =
         Tag::new(&args.tag).map_err(|e| anyhow::anyhow!("invalid tag '{}': {}", args.tag, e))?;
 
-    let db_path = index_db_path(notes_dir);
-    let index = SqliteIndex::open(&db_path)
-        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
-
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(indexed_note) => {
-            let file_path = notes_dir.join(indexed_note.path());
-            let parsed = read_note(&file_path)
-                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+    let mut vault = open_vault(notes_dir, config)?;
+    if args.note == STDIN_NOTES {
+        let notes = read_stdin_notes(notes_dir)?;
+        return run_batch(&notes, args.format, |note| {
+            untag_note(&mut vault, note, &tag, config)
+        });
+    }
+    untag_note(&mut vault, &args.note, &tag, config)?.print(args.format)
+}
 
-            // Idempotency check: if tag doesn't exist, no-op
-            if !parsed.note.tags().contains(&tag) {
-                println!("Tag '{}' not present on '{}'", tag, parsed.note.title());
-                return Ok(());
-            }
+/// Result of a desc operation for JSON output.
+#[derive(Debug, Serialize)]
+pub struct DescResult {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub description: Option<String>,
+    /// False when the note already had the description and was left untouched.
+    pub changed: bool,
+}
 
-            // Build updated note without the tag
-            let tags: Vec<Tag> = parsed
-                .note
-                .tags()
-                .iter()
-                .filter(|t| *t != &tag)
-                .cloned()
-                .collect();
+/// Sets or clears the description of the note `query` refers to.
+fn describe_note(
+    vault: &mut Vault,
+    query: &str,
+    description: Option<&str>,
+    config: &Config,
+) -> Result<NoteOutcome<DescResult>> {
+    let note = require_note(vault.index(), query, "note", config)?;
+    let update = vault.describe(&note.id().to_string(), description)?;
+    let title = update.note.title();
+    let message = match (description, update.changed) {
+        (Some(_), true) => format!(
+            "Set description of '{}' [{}]",
+            title,
+            update.note.id().prefix()
+        ),
+        (None, true) => format!(
+            "Cleared description of '{}' [{}]",
+            title,
+            update.note.id().prefix()
+        ),
+        (Some(_), false) => format!("Description of '{}' already set", title),
+        (None, false) => format!("'{}' has no description", title),
+    };
+    Ok(NoteOutcome {
+        result: DescResult {
+            id: update.note.id().to_string(),
+            title: title.to_string(),
+            path: update.path.to_string_lossy().to_string(),
+            description: update.note.description().map(String::from),
+            changed: update.changed,
+        },
+        message,
+        path: update.path,
+        changed: update.changed,
+    })
+}
 
-            let now = Utc::now();
-            let updated_note = Note::builder(
-                parsed.note.id().clone(),
-                parsed.note.title(),
-                parsed.note.created(),
-                now,
-            )
-            .description(parsed.note.description().map(|s| s.to_string()))
-            .topics(parsed.note.topics().to_vec())
-            .aliases(parsed.note.aliases().to_vec())
-            .tags(tags)
-            .links(parsed.note.links().to_vec())
-            .build()
-            .with_context(|| "failed to rebuild note")?;
-
-            // Write updated note
-            write_note(&file_path, &updated_note, &parsed.body)
-                .with_context(|| "failed to write updated note")?;
-
-            // Update index
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
-            }
-
-            println!(
-                "Removed tag '{}' from '{}' [{}]",
-                tag,
-                updated_note.title(),
-                updated_note.id().prefix()
-            );
-            Ok(())
-        }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
-        }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+pub fn handle_desc(args: &DescArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    // Validate the description first (before any I/O)
+    let description = match &args.description {
+        Some(_) if args.clear => bail!("cannot set and clear a description at once"),
+        Some(text) => {
+            let text = text.trim();
+            if text.is_empty() {
+                bail!("description is empty; use --clear to remove it");
+            }
+            if text.contains('\n') {
+                bail!("description must be a single line");
+            }
+            Some(text)
         }
+        None => None,
+    };
+
+    let mut vault = open_vault(notes_dir, config)?;
+    if args.note == STDIN_NOTES {
+        let notes = read_stdin_notes(notes_dir)?;
+        return run_batch(&notes, args.format, |note| {
+            describe_note(&mut vault, note, description, config)
+        });
     }
+    describe_note(&mut vault, &args.note, description, config)?.print(args.format)
 }
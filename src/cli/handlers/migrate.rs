@@ -0,0 +1,298 @@
+//! Migrate command handler: renaming frontmatter keys across the vault.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::import::{FieldTarget, list_strings, parse_timestamp, scalar_string};
+use super::index_db_path;
+use crate::cli::MigrateArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{NOTE_FIELDS, Note, Tag, Topic};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{Journal, read_note, scan_notes_directory_with, serialize_with};
+
+/// Builds the key renames from the `[migrate.rename]` config section and
+/// `--rename` flags, with flags taking precedence.
+///
+/// Targets are parsed as for `import --map`. Only extra fields can be
+/// renamed, since den reads its own fields under their own names.
+pub(crate) fn rename_map(
+    configured: &BTreeMap<String, String>,
+    flags: &[String],
+) -> Result<BTreeMap<String, FieldTarget>> {
+    let mut renames = BTreeMap::new();
+    for (src, dst) in configured {
+        let target = parse_rename(src, dst)
+            .with_context(|| format!("invalid [migrate.rename] entry '{}'", src))?;
+        renames.insert(src.clone(), target);
+    }
+    for flag in flags {
+        let Some((src, dst)) = flag.split_once('=') else {
+            bail!("invalid --rename '{}': expected SRC=DST", flag);
+        };
+        let src = src.trim();
+        if src.is_empty() {
+            bail!("invalid --rename '{}': source key is empty", flag);
+        }
+        let target =
+            parse_rename(src, dst).with_context(|| format!("invalid --rename '{}'", flag))?;
+        renames.insert(src.to_string(), target);
+    }
+    Ok(renames)
+}
+
+fn parse_rename(src: &str, dst: &str) -> Result<FieldTarget> {
+    if NOTE_FIELDS.contains(&src) {
+        bail!("cannot rename '{}': den manages that field itself", src);
+    }
+    let target = FieldTarget::parse(dst)?;
+    if target == FieldTarget::Extra(src.to_string()) {
+        bail!("'{}' is renamed to itself", src);
+    }
+    Ok(target)
+}
+
+/// A frontmatter key a migration renames, or removes if `to` is `None`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct KeyChange {
+    pub from: String,
+    pub to: Option<String>,
+}
+
+/// A note with its renames applied.
+#[derive(Debug)]
+pub(crate) struct MigratedNote {
+    pub note: Note,
+    pub changes: Vec<KeyChange>,
+    /// Values that could not be moved; the note must not be written
+    pub problems: Vec<String>,
+}
+
+/// Applies key renames to a note (pure function, no I/O).
+///
+/// Values renamed into topics, tags or aliases are merged with the note's
+/// own; into the title, timestamps or an extra field they replace it,
+/// unless the description or extra field already holds a different value.
+pub(crate) fn migrate_note(note: &Note, renames: &BTreeMap<String, FieldTarget>) -> MigratedNote {
+    let mut title = note.title().to_string();
+    let mut description = note.description().map(str::to_string);
+    let mut created = note.created();
+    let mut modified = note.modified();
+    let mut topics = note.topics().to_vec();
+    let mut tags = note.tags().to_vec();
+    let mut aliases = note.aliases().to_vec();
+    let mut extra = note.extra().clone();
+    let mut changes = Vec::new();
+    let mut problems = Vec::new();
+
+    for (key, target) in renames {
+        let Some(value) = extra.remove(key) else {
+            continue;
+        };
+        let to = match target {
+            FieldTarget::Title => match scalar_string(&value).filter(|t| !t.trim().is_empty()) {
+                Some(value) => {
+                    title = value;
+                    Some("title")
+                }
+                None => {
+                    problems.push(format!("'{}' is not a usable title", key));
+                    None
+                }
+            },
+            FieldTarget::Description => match scalar_string(&value) {
+                Some(value) if description.as_ref().is_none_or(|d| *d == value.trim()) => {
+                    description = Some(value);
+                    Some("description")
+                }
+                Some(_) => {
+                    problems.push(format!("'{}' would overwrite a different description", key));
+                    None
+                }
+                None => {
+                    problems.push(format!("'{}' is not a usable description", key));
+                    None
+                }
+            },
+            FieldTarget::Created | FieldTarget::Modified => match parse_timestamp(&value) {
+                Some(timestamp) if *target == FieldTarget::Created => {
+                    created = timestamp;
+                    Some("created")
+                }
+                Some(timestamp) => {
+                    modified = timestamp;
+                    Some("modified")
+                }
+                None => {
+                    problems.push(format!("unreadable timestamp in '{}'", key));
+                    None
+                }
+            },
+            FieldTarget::Topics => {
+                for value in list_strings(&value, true) {
+                    match Topic::new(&value.replace(' ', "-")) {
+                        Ok(topic) => topics.push(topic),
+                        Err(_) => problems.push(format!("invalid topic '{}' in '{}'", value, key)),
+                    }
+                }
+                Some("topics")
+            }
+            FieldTarget::Tags => {
+                for value in list_strings(&value, true) {
+                    let value = value.trim_start_matches('#');
+                    match Tag::new(&value.replace(' ', "-")) {
+                        Ok(tag) => tags.push(tag),
+                        Err(_) => problems.push(format!("invalid tag '{}' in '{}'", value, key)),
+                    }
+                }
+                Some("tags")
+            }
+            FieldTarget::Aliases => {
+                aliases.extend(list_strings(&value, false));
+                Some("aliases")
+            }
+            FieldTarget::Extra(name) => match extra.get(name) {
+                Some(existing) if *existing != value => {
+                    problems.push(format!("'{}' would overwrite a different '{}'", key, name));
+                    None
+                }
+                _ => {
+                    extra.insert(name.clone(), value);
+                    Some(name.as_str())
+                }
+            },
+            FieldTarget::Drop => None,
+        };
+        changes.push(KeyChange {
+            from: key.clone(),
+            to: to.map(str::to_string),
+        });
+    }
+
+    let migrated = Note::builder(note.id().clone(), title, created, modified)
+        .description(description)
+        .topics(topics)
+        .aliases(aliases)
+        .tags(tags)
+        .links(note.links().to_vec())
+        .extra(extra)
+        .build()
+        .unwrap_or_else(|_| note.clone());
+
+    MigratedNote {
+        note: migrated,
+        changes,
+        problems,
+    }
+}
+
+/// A migrated note, for JSON output.
+#[derive(Debug, Serialize)]
+struct MigratedNoteListing {
+    id: String,
+    title: String,
+    path: String,
+    changes: Vec<KeyChange>,
+}
+
+pub fn handle_migrate(args: &MigrateArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let renames = rename_map(&config.migrate.rename, &args.rename)?;
+    if renames.is_empty() {
+        bail!("no renames given: pass --rename SRC=DST or set [migrate.rename] in the config");
+    }
+
+    let mut paths: Vec<PathBuf> = scan_notes_directory_with(notes_dir, &config.scan)?.collect();
+    paths.sort();
+
+    // Migrate everything before writing, so a problem leaves the vault untouched
+    let mut migrated: Vec<(PathBuf, MigratedNote, String)> = Vec::new();
+    for path in paths {
+        let parsed = match read_note(&notes_dir.join(&path)) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("warning: skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let result = migrate_note(&parsed.note, &renames);
+        if !result.changes.is_empty() {
+            migrated.push((path, result, parsed.body));
+        }
+    }
+
+    let problems: usize = migrated.iter().map(|(_, m, _)| m.problems.len()).sum();
+    if problems > 0 {
+        for (path, result, _) in &migrated {
+            for problem in &result.problems {
+                eprintln!("error: {}: {}", path.display(), problem);
+            }
+        }
+        bail!("{} problem(s) found; no notes were changed", problems);
+    }
+
+    if !args.dry_run && !migrated.is_empty() {
+        let mut journal = Journal::new("migrate", Utc::now());
+        for (path, result, body) in &migrated {
+            let contents = serialize_with(&result.note, body, &config.frontmatter);
+            journal.record(notes_dir, path, Some(contents))?;
+        }
+        journal
+            .run(notes_dir)
+            .with_context(|| "failed to write migrated notes")?;
+
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let changed: Vec<PathBuf> = migrated.iter().map(|(path, _, _)| path.clone()).collect();
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &changed)
+            .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            for (path, result, _) in &migrated {
+                for change in &result.changes {
+                    match &change.to {
+                        Some(to) => println!("{}: {} -> {}", path.display(), change.from, to),
+                        None => println!("{}: {} removed", path.display(), change.from),
+                    }
+                }
+            }
+            let verb = if args.dry_run {
+                "Would migrate"
+            } else {
+                "Migrated"
+            };
+            println!("{} {} note(s)", verb, migrated.len());
+        }
+        OutputFormat::Json => {
+            let listing: Vec<MigratedNoteListing> = migrated
+                .iter()
+                .map(|(path, result, _)| MigratedNoteListing {
+                    id: result.note.id().to_string(),
+                    title: result.note.title().to_string(),
+                    path: notes_dir.join(path).to_string_lossy().to_string(),
+                    changes: result.changes.clone(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            for (path, _, _) in &migrated {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,17 +1,50 @@
 //! Command handlers for the CLI.
 
+mod append;
 mod archive;
+mod batch;
+mod bench;
+mod bundle;
 mod check;
+mod clone;
+mod config;
+mod daily;
+mod digest;
+mod drill;
 mod export;
+mod fix_ids;
+mod graph;
+mod import;
 mod index;
+mod keywords;
 mod links;
 mod list;
+mod list_broken;
 mod metadata;
+mod migrate;
 mod mv;
 mod new;
+mod person;
+mod quick_search;
+mod recent;
+mod recover;
+mod rel_export;
+mod rel_graph;
 mod resolve;
+mod rm;
 mod search;
 mod show_edit;
+mod snippet;
+mod stats;
+mod stats_overview;
+mod sync_config;
+mod tag_expire;
+mod tag_mv;
+mod top;
+mod topic_mv;
+mod topic_stats;
+mod trash;
+mod urls;
 mod vaults;
 
 #[cfg(test)]
@@ -19,26 +52,52 @@ pub(crate) mod tests;
 
 use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::CommandFactory;
 use clap_complete::{Shell, generate};
 
+use crate::api::{Vault, VaultOptions};
+use crate::cli::config::Config;
 use crate::cli::{Cli, CompletionsArgs};
 use crate::index::{FileResult, ProgressReporter};
 
 // Re-export public items
+pub use append::{handle_append, handle_prepend};
 pub use archive::{ARCHIVED_TAG, handle_archive, handle_unarchive};
+pub use bench::handle_bench;
+pub use bundle::handle_bundle;
 pub use check::handle_check;
+pub use clone::{clone_note, handle_clone};
+pub use config::handle_config;
+pub use daily::handle_daily;
+pub use digest::handle_digest;
+pub use drill::handle_drill;
 pub use export::handle_export;
+pub use fix_ids::handle_fix_ids;
+pub use graph::handle_graph;
+pub use import::handle_import;
 pub use index::handle_index;
+pub use keywords::handle_keywords;
 pub use links::{handle_backlinks, handle_link, handle_rels, handle_unlink};
 pub use list::handle_list;
-pub use metadata::{handle_tag, handle_tags, handle_topics, handle_untag};
+pub use list_broken::handle_list_broken;
+pub use metadata::{handle_desc, handle_tag, handle_tags, handle_topics, handle_untag};
+pub use migrate::handle_migrate;
 pub use mv::handle_mv;
 pub use new::{NewNoteResult, create_new_note, handle_new};
-pub use resolve::{ResolveResult, resolve_note};
+pub use person::handle_person;
+pub use quick_search::handle_quick_search;
+pub use recent::{SESSION_TAG, handle_recent};
+pub use recover::handle_recover;
+pub use resolve::{ResolveResult, resolve_note, short_ref};
+pub use rm::handle_rm;
 pub use search::handle_search;
 pub use show_edit::{handle_edit, handle_show};
+pub use snippet::handle_snippet;
+pub use stats::handle_stats;
+pub use top::handle_top;
+pub use trash::handle_trash;
+pub use urls::handle_urls;
 pub use vaults::handle_vaults;
 
 /// Generate shell completions script for the given shell.
@@ -55,11 +114,57 @@ pub fn handle_completions(args: &CompletionsArgs) -> Result<()> {
 
 // Re-export for tests
 #[cfg(test)]
+pub(crate) use append::{Placement, add_to_body};
+#[cfg(test)]
+pub(crate) use archive::{archive_candidates, has_open_tasks};
+#[cfg(test)]
+pub(crate) use bench::{BenchReport, BenchResult, compare, generate_vault};
+#[cfg(test)]
+pub(crate) use config::{ConfigIssue, validate_config};
+#[cfg(test)]
+pub(crate) use import::{FieldMap, FieldTarget, ImportedNote, convert_note};
+#[cfg(test)]
+pub(crate) use index::changed_note_paths;
+#[cfg(test)]
 pub(crate) use list::{note_matches_topic, parse_topic_filter};
 #[cfg(test)]
-pub(crate) use search::strip_html_tags;
+pub(crate) use list_broken::{BrokenTarget, broken_links};
+#[cfg(test)]
+pub(crate) use metadata::{build_topic_tree, group_tags, render_topic_tree};
+#[cfg(test)]
+pub(crate) use migrate::{KeyChange, migrate_note, rename_map};
+#[cfg(test)]
+pub(crate) use new::{body_title, split_command};
+#[cfg(test)]
+pub(crate) use recent::group_sessions;
+#[cfg(test)]
+pub(crate) use rel_export::RelOntology;
+#[cfg(test)]
+pub(crate) use rel_graph::{build_backlink_tree, build_rel_forest, render_rel_forest};
+#[cfg(test)]
+pub(crate) use rm::confirmed;
+#[cfg(test)]
+pub(crate) use search::{find_in_body, handle_search_impl, highlight_matches, strip_html_tags};
+#[cfg(test)]
+pub(crate) use show_edit::{EditorLauncher, edit_listed_notes, handle_edit_impl};
 #[cfg(test)]
-pub(crate) use show_edit::{EditorLauncher, handle_edit_impl};
+pub(crate) use snippet::{create_snippet, detect_language, fence_code, snippet_title};
+#[cfg(test)]
+pub(crate) use stats::{trend_summary, usage_by_month};
+#[cfg(test)]
+pub(crate) use stats_overview::vault_overview;
+#[cfg(test)]
+pub(crate) use sync_config::{export_profile, import_profile};
+#[cfg(test)]
+pub(crate) use tag_expire::{expire_tags, expiry_replacements};
+#[cfg(test)]
+pub(crate) use tag_mv::replace_tags;
+#[cfg(test)]
+pub(crate) use topic_mv::move_topics;
+#[cfg(test)]
+pub(crate) use topic_stats::topic_report;
+#[cfg(test)]
+pub(crate) use trash::{move_to_trash, purge_trash, sweep_trash};
 
 // ===========================================
 // Shared Utilities
@@ -98,15 +203,47 @@ impl ProgressReporter for ConsoleReporter {
 
 /// Returns the default index database path for a notes directory.
 pub(crate) fn index_db_path(notes_dir: &Path) -> PathBuf {
-    notes_dir.join(".index").join("notes.db")
+    Vault::index_path(notes_dir)
+}
+
+/// Opens the vault at `notes_dir` with the config's slug and frontmatter settings.
+pub(crate) fn open_vault(notes_dir: &Path, config: &Config) -> Result<Vault> {
+    let options = VaultOptions {
+        slug: config.slug.clone(),
+        frontmatter: config.frontmatter.clone(),
+        scan: config.scan.clone(),
+    };
+    Vault::open_with(notes_dir, options).with_context(|| {
+        format!(
+            "failed to open index at {}",
+            index_db_path(notes_dir).display()
+        )
+    })
 }
 
-/// Truncates a string to a maximum display width, adding ellipsis if needed.
-pub(crate) fn truncate_str(s: &str, max_width: usize) -> String {
-    if s.chars().count() <= max_width {
-        s.to_string()
+/// Brings the titles written next to link IDs up to date, if enabled, and
+/// returns how many notes were rewritten.
+pub(crate) fn sync_link_titles(notes_dir: &Path, config: &Config) -> Result<usize> {
+    if !config.frontmatter.link_titles {
+        return Ok(0);
+    }
+    open_vault(notes_dir, config)?
+        .sync_link_titles()
+        .with_context(|| "failed to update link titles")
+}
+
+/// Formats a byte count with a binary unit suffix (B, KiB, MiB, GiB).
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
     } else {
-        let truncated: String = s.chars().take(max_width.saturating_sub(1)).collect();
-        format!("{}…", truncated)
+        format!("{:.1} {}", value, UNITS[unit])
     }
 }
@@ -3,15 +3,18 @@
 use anyhow::{Context, Result, bail};
 use chrono::Utc;
 use serde::Serialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use super::index_db_path;
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
+use super::batch::{NoteOutcome, STDIN_NOTES, read_stdin_notes, run_batch};
+use super::fix_ids::{IdChange, change_note_id};
+use super::resolve::require_note;
+use super::{index_db_path, sync_link_titles};
 use crate::cli::MvArgs;
-use crate::cli::output::{Output, OutputFormat};
-use crate::domain::{Note, Topic};
+use crate::cli::config::Config;
+use crate::cli::output::OutputFormat;
+use crate::domain::{Note, NoteId, Topic};
 use crate::index::{IndexBuilder, SqliteIndex};
-use crate::infra::{generate_filename, read_note, write_note};
+use crate::infra::{Journal, generate_filename_with, is_case_change, read_note, serialize_with};
 
 /// Result of a move operation for JSON output.
 #[derive(Debug, Serialize)]
@@ -21,18 +24,20 @@ pub struct MvResult {
     pub old_path: String,
     pub new_path: String,
     pub topics: Vec<String>,
+    /// Old title kept as an alias, if the rename added one.
+    pub alias: Option<String>,
 }
 
 /// Validates the mv command arguments.
 ///
 /// Returns an error if:
-/// - No change is specified (no --title, --topic, or --clear-topics)
+/// - No change is specified (no --title, --topic, --clear-topics, or --id)
 /// - Both --clear-topics and --topic are specified
 /// - --title is empty
 pub fn validate_mv_args(args: &MvArgs) -> Result<()> {
     // At least one change must be specified
-    if args.title.is_none() && args.topics.is_empty() && !args.clear_topics {
-        bail!("at least one of --title, --topic, or --clear-topics must be specified");
+    if args.title.is_none() && args.topics.is_empty() && !args.clear_topics && args.id.is_none() {
+        bail!("at least one of --title, --topic, --clear-topics, or --id must be specified");
     }
 
     // --clear-topics and --topic are mutually exclusive
@@ -65,167 +70,402 @@ fn parse_topics(topic_strs: &[String]) -> Result<Vec<Topic>> {
     Ok(topics)
 }
 
-pub fn handle_mv(args: &MvArgs, notes_dir: &Path) -> Result<()> {
-    validate_mv_args(args)?;
+/// Words [`title_case`] keeps lowercase unless they start or end the title.
+const MINOR_WORDS: &[&str] = &[
+    "a", "an", "and", "as", "at", "but", "by", "for", "if", "in", "nor", "of", "on", "or", "per",
+    "the", "to", "via", "vs", "with",
+];
 
-    let db_path = index_db_path(notes_dir);
-    let index = SqliteIndex::open(&db_path)
-        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+/// Title-cases `title` the way headlines are usually written.
+///
+/// Short words such as "a", "of" and "the" stay lowercase unless they start
+/// or end the title or follow a colon. Words with capitals after the first
+/// letter (`iPhone`, `API`) or an inner dot (`example.com`) are kept as
+/// written, and hyphenated words are cased part by part.
+pub(crate) fn title_case(title: &str) -> String {
+    let words: Vec<&str> = title.split_whitespace().collect();
+    let last = words.len().saturating_sub(1);
+    let mut starts_phrase = true;
+
+    let mut cased = Vec::with_capacity(words.len());
+    for (i, word) in words.iter().enumerate() {
+        cased.push(case_word(word, starts_phrase || i == last));
+        starts_phrase = word.ends_with(':');
+    }
+    cased.join(" ")
+}
+
+fn case_word(word: &str, capitalize_minor: bool) -> String {
+    let trimmed = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+    let keep = word.chars().skip(1).any(char::is_uppercase) || trimmed.contains('.');
+    if keep {
+        return word.to_string();
+    }
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(indexed_note) => {
-            let old_path = notes_dir.join(indexed_note.path());
-            let parsed = read_note(&old_path)
-                .with_context(|| format!("failed to read note: {}", old_path.display()))?;
-
-            // Determine new title
-            let new_title = args
-                .title
-                .as_ref()
-                .map(|t| t.trim())
-                .unwrap_or(parsed.note.title());
-
-            // Determine new topics
-            let new_topics = if args.clear_topics {
-                vec![]
-            } else if !args.topics.is_empty() {
-                parse_topics(&args.topics)?
+    word.split('-')
+        .enumerate()
+        .map(|(i, part)| {
+            let bare: String = part
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase();
+            if (i > 0 || !capitalize_minor) && MINOR_WORDS.contains(&bare.as_str()) {
+                part.to_lowercase()
             } else {
-                parsed.note.topics().to_vec()
-            };
-
-            // Check for idempotency (no actual changes)
-            let title_unchanged = new_title == parsed.note.title();
-            let topics_unchanged = new_topics == parsed.note.topics();
-
-            if title_unchanged && topics_unchanged {
-                // No actual change needed
-                let path_str = indexed_note.path().to_string_lossy();
-                match args.format {
-                    OutputFormat::Human => {
-                        println!(
-                            "No changes needed for '{}' [{}]",
-                            new_title,
-                            indexed_note.id()
-                        );
-                    }
-                    OutputFormat::Json => {
-                        let result = MvResult {
-                            id: indexed_note.id().to_string(),
-                            title: new_title.to_string(),
-                            old_path: path_str.to_string(),
-                            new_path: path_str.to_string(),
-                            topics: new_topics.iter().map(|t| t.to_string()).collect(),
-                        };
-                        let out = Output::new(result);
-                        println!("{}", serde_json::to_string_pretty(&out)?);
-                    }
-                    OutputFormat::Paths => {
-                        println!("{}", path_str);
-                    }
-                }
-                return Ok(());
+                capitalize(part)
             }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
 
-            // Build updated note
-            let now = Utc::now();
-            let updated_note = Note::builder(
-                parsed.note.id().clone(),
-                new_title,
-                parsed.note.created(),
-                now,
-            )
-            .description(parsed.note.description().map(|s| s.to_string()))
-            .topics(new_topics.clone())
-            .aliases(parsed.note.aliases().to_vec())
-            .tags(parsed.note.tags().to_vec())
-            .links(parsed.note.links().to_vec())
-            .build()
-            .with_context(|| "failed to rebuild note")?;
-
-            // Determine new filename
-            let new_filename = generate_filename(updated_note.id(), updated_note.title());
-            let new_path = notes_dir.join(&new_filename);
-
-            // Write to new path
-            write_note(&new_path, &updated_note, &parsed.body)
-                .with_context(|| format!("failed to write note to {}", new_path.display()))?;
-
-            // Delete old file if renamed (different path)
-            if old_path != new_path {
-                std::fs::remove_file(&old_path).with_context(|| {
-                    format!("failed to remove old file: {}", old_path.display())
-                })?;
-            }
+/// Uppercases the first letter of `word` unless it starts with a digit ("2nd").
+fn capitalize(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphanumeric()) {
+        Some((i, c)) if c.is_alphabetic() => {
+            let mut out = String::with_capacity(word.len());
+            out.push_str(&word[..i]);
+            out.extend(c.to_uppercase());
+            out.push_str(&word[i + c.len_utf8()..]);
+            out
+        }
+        _ => word.to_string(),
+    }
+}
 
-            // Update index
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
-            }
+/// Returns a filename for the moved note that no other file uses.
+///
+/// If `filename` belongs to another note, `-2`, `-3`, ... is appended to the
+/// slug until a free name is found. The note's own file at `current` counts
+/// as free, including under another spelling on a case-insensitive file
+/// system.
+fn available_filename(notes_dir: &Path, filename: &str, current: &Path) -> String {
+    let stem = filename.strip_suffix(".md").unwrap_or(filename);
+    let mut candidate = filename.to_string();
+    let mut n = 2;
+    loop {
+        let path = notes_dir.join(&candidate);
+        if path == current || !path.exists() || is_case_change(current, &path) {
+            return candidate;
+        }
+        candidate = format!("{}-{}.md", stem, n);
+        n += 1;
+    }
+}
 
-            // Output result
-            match args.format {
-                OutputFormat::Human => {
-                    if !title_unchanged {
-                        println!(
-                            "Renamed '{}' to '{}' [{}]",
-                            parsed.note.title(),
-                            new_title,
-                            updated_note.id().prefix()
-                        );
-                    }
-                    if !topics_unchanged {
-                        if new_topics.is_empty() {
-                            println!(
-                                "Cleared topics from '{}' [{}]",
-                                new_title,
-                                updated_note.id().prefix()
-                            );
-                        } else {
-                            let topic_strs: Vec<_> =
-                                new_topics.iter().map(|t| t.to_string()).collect();
-                            println!(
-                                "Moved '{}' to {} [{}]",
-                                new_title,
-                                topic_strs.join(", "),
-                                updated_note.id().prefix()
-                            );
-                        }
-                    }
-                    if old_path != new_path {
-                        println!("  {} -> {}", indexed_note.path().display(), new_filename);
-                    }
-                }
-                OutputFormat::Json => {
-                    let result = MvResult {
-                        id: updated_note.id().to_string(),
-                        title: new_title.to_string(),
-                        old_path: indexed_note.path().to_string_lossy().to_string(),
-                        new_path: new_filename,
-                        topics: new_topics.iter().map(|t| t.to_string()).collect(),
-                    };
-                    let out = Output::new(result);
-                    println!("{}", serde_json::to_string_pretty(&out)?);
-                }
-                OutputFormat::Paths => {
-                    println!("{}", old_path.display());
-                    if old_path != new_path {
-                        println!("{}", new_path.display());
-                    }
-                }
-            }
+/// Returns the aliases for a note renamed from `old_title` to `new_title`,
+/// and whether the old title was added.
+///
+/// Keeping the old title means references to it still resolve and the
+/// exported site redirects the old page. An alias equal to the new title is
+/// dropped, and a change of case only adds nothing.
+fn aliases_after_rename(
+    aliases: &[String],
+    old_title: &str,
+    new_title: &str,
+) -> (Vec<String>, bool) {
+    let same = |a: &str, b: &str| a.to_lowercase() == b.to_lowercase();
+    let mut updated: Vec<String> = aliases
+        .iter()
+        .filter(|alias| !same(alias, new_title))
+        .cloned()
+        .collect();
+
+    let add = !same(old_title, new_title) && !updated.iter().any(|alias| same(alias, old_title));
+    if add {
+        updated.push(old_title.to_string());
+    }
+    (updated, add)
+}
+
+pub fn handle_mv(args: &MvArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    validate_mv_args(args)?;
 
-            Ok(())
+    if args.note == STDIN_NOTES {
+        if args.title.is_some() {
+            bail!("--title renames a single note and can't be used with '-'");
+        }
+        if matches!(args.id, Some(Some(_))) {
+            bail!("an ID can't be given to several notes; use --id alone for new IDs");
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        let notes = read_stdin_notes(notes_dir)?;
+        if args.id.is_some() {
+            return run_batch(&notes, args.format, |note| {
+                change_id(note, NoteId::new(), args, notes_dir, config)
+            });
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+        return run_batch(&notes, args.format, |note| {
+            move_note(note, args, notes_dir, config)
+        });
+    }
+
+    if let Some(id) = &args.id {
+        let new_id = match id.as_deref() {
+            Some(id) => id
+                .parse::<NoteId>()
+                .with_context(|| format!("invalid ID '{}'", id))?,
+            None => NoteId::new(),
+        };
+        return change_id(&args.note, new_id, args, notes_dir, config)?.print(args.format);
+    }
+
+    let outcome = move_note(&args.note, args, notes_dir, config)?;
+    if matches!(args.format, OutputFormat::Paths) && outcome.changed {
+        let old_path = notes_dir.join(&outcome.result.old_path);
+        if old_path != outcome.path {
+            println!("{}", old_path.display());
         }
     }
+    outcome.print(args.format)
+}
+
+/// Retitles or re-topics the note `query` refers to, renaming its file.
+fn move_note(
+    query: &str,
+    args: &MvArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<NoteOutcome<MvResult>> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let indexed_note = require_note(&index, query, "note", config)?;
+    drop(index);
+
+    let old_path = notes_dir.join(indexed_note.path());
+    let parsed = read_note(&old_path)
+        .with_context(|| format!("failed to read note: {}", old_path.display()))?;
+
+    // Determine new title
+    let new_title = match &args.title {
+        Some(title) if args.title_case => title_case(title),
+        Some(title) => title.trim().to_string(),
+        None => parsed.note.title().to_string(),
+    };
+    let new_title = new_title.as_str();
+
+    // Determine new topics
+    let new_topics = if args.clear_topics {
+        vec![]
+    } else if !args.topics.is_empty() {
+        parse_topics(&args.topics)?
+    } else {
+        parsed.note.topics().to_vec()
+    };
+
+    // Check for idempotency (no actual changes)
+    let title_unchanged = new_title == parsed.note.title();
+    let topics_unchanged = new_topics == parsed.note.topics();
+
+    if title_unchanged && topics_unchanged {
+        // No actual change needed
+        let path_str = indexed_note.path().to_string_lossy().to_string();
+        return Ok(NoteOutcome {
+            result: MvResult {
+                id: indexed_note.id().to_string(),
+                title: new_title.to_string(),
+                old_path: path_str.clone(),
+                new_path: path_str,
+                topics: new_topics.iter().map(|t| t.to_string()).collect(),
+                alias: None,
+            },
+            message: format!(
+                "No changes needed for '{}' [{}]",
+                new_title,
+                indexed_note.id()
+            ),
+            path: old_path,
+            changed: false,
+        });
+    }
+
+    // Keep the old title as an alias
+    let (aliases, alias_added) = if title_unchanged || args.no_alias {
+        (parsed.note.aliases().to_vec(), false)
+    } else {
+        aliases_after_rename(parsed.note.aliases(), parsed.note.title(), new_title)
+    };
+
+    // Build updated note
+    let now = Utc::now();
+    let updated_note = Note::builder(
+        parsed.note.id().clone(),
+        new_title,
+        parsed.note.created(),
+        now,
+    )
+    .description(parsed.note.description().map(|s| s.to_string()))
+    .topics(new_topics.clone())
+    .aliases(aliases)
+    .tags(parsed.note.tags().to_vec())
+    .links(parsed.note.links().to_vec())
+    .extra(parsed.note.extra().clone())
+    .build()
+    .with_context(|| "failed to rebuild note")?;
+
+    // Determine new filename, avoiding other notes' files
+    let new_filename = available_filename(
+        notes_dir,
+        &generate_filename_with(
+            updated_note.id(),
+            updated_note.title(),
+            &config.slug.for_lang(updated_note.lang().as_deref()),
+        ),
+        &old_path,
+    );
+    let new_path = notes_dir.join(&new_filename);
+
+    // A new name differing only in case is the same file on a
+    // case-insensitive file system, so deleting the old name would delete
+    // the note; it is written in place and renamed instead
+    let case_change = is_case_change(&old_path, &new_path);
+
+    // Write to the new path and delete the old file if renamed, through a
+    // journal so a crash in between can't leave two copies of the note
+    let mut journal = Journal::new("mv", now);
+    let written = if case_change {
+        indexed_note.path()
+    } else {
+        Path::new(&new_filename)
+    };
+    journal.record(
+        notes_dir,
+        written,
+        Some(serialize_with(
+            &updated_note,
+            &parsed.body,
+            &config.frontmatter,
+        )),
+    )?;
+    if old_path != new_path && !case_change {
+        journal.record(notes_dir, indexed_note.path(), None)?;
+    }
+    journal
+        .run(notes_dir)
+        .with_context(|| format!("failed to write note to {}", new_path.display()))?;
+    if case_change {
+        std::fs::rename(&old_path, &new_path)
+            .with_context(|| format!("failed to rename note to {}", new_path.display()))?;
+    }
+
+    // Update the index entry and its path in one transaction
+    let mut paths = vec![PathBuf::from(&new_filename)];
+    // The old name still reaches the file after a change of case
+    if !case_change {
+        paths.push(indexed_note.path().to_path_buf());
+    }
+    let mut index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    IndexBuilder::new(notes_dir.to_path_buf())
+        .with_scan_options(config.scan.clone())
+        .update_paths(&mut index, &paths)
+        .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+
+    // Links to the note record its title
+    let retitled = if title_unchanged {
+        0
+    } else {
+        sync_link_titles(notes_dir, config)?
+    };
+
+    let alias = alias_added.then(|| parsed.note.title().to_string());
+
+    let mut lines = Vec::new();
+    let prefix = updated_note.id().prefix();
+    if !title_unchanged {
+        lines.push(format!(
+            "Renamed '{}' to '{}' [{}]",
+            parsed.note.title(),
+            new_title,
+            prefix
+        ));
+    }
+    if !topics_unchanged {
+        if new_topics.is_empty() {
+            lines.push(format!("Cleared topics from '{}' [{}]", new_title, prefix));
+        } else {
+            let topic_strs: Vec<_> = new_topics.iter().map(|t| t.to_string()).collect();
+            lines.push(format!(
+                "Moved '{}' to {} [{}]",
+                new_title,
+                topic_strs.join(", "),
+                prefix
+            ));
+        }
+    }
+    if old_path != new_path {
+        lines.push(format!(
+            "  {} -> {}",
+            indexed_note.path().display(),
+            new_filename
+        ));
+    }
+    if let Some(alias) = &alias {
+        lines.push(format!("  Kept '{}' as an alias", alias));
+    }
+    if retitled > 0 {
+        lines.push(format!("  Updated link titles in {} note(s)", retitled));
+    }
+
+    Ok(NoteOutcome {
+        result: MvResult {
+            id: updated_note.id().to_string(),
+            title: new_title.to_string(),
+            old_path: indexed_note.path().to_string_lossy().to_string(),
+            new_path: new_filename,
+            topics: new_topics.iter().map(|t| t.to_string()).collect(),
+            alias,
+        },
+        message: lines.join("\n"),
+        path: new_path,
+        changed: true,
+    })
+}
+
+/// Gives the note `query` refers to the ID `new_id` (`mv --id`).
+fn change_id(
+    query: &str,
+    new_id: NoteId,
+    args: &MvArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<NoteOutcome<IdChange>> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let indexed_note = require_note(&index, query, "note", config)?;
+    drop(index);
+    let change = change_note_id(notes_dir, config, indexed_note.path(), new_id, args.dry_run)?;
+
+    let verb = if args.dry_run {
+        "Would change"
+    } else {
+        "Changed"
+    };
+    let mut lines = vec![format!(
+        "{} ID of '{}' from {} to {}",
+        verb, change.title, change.old_id, change.new_id
+    )];
+    if let Some(new_path) = &change.new_path {
+        lines.push(format!("  {} -> {}", change.path, new_path));
+    }
+    if change.links_rewritten > 0 {
+        lines.push(format!(
+            "  Rewrote {} link(s) in {} note(s)",
+            change.links_rewritten, change.notes_relinked
+        ));
+    }
+    lines.push(format!("  Kept {} as an alias", change.old_id));
+
+    let path = notes_dir.join(change.new_path.as_ref().unwrap_or(&change.path));
+    Ok(NoteOutcome {
+        result: change,
+        message: lines.join("\n"),
+        path,
+        changed: !args.dry_run,
+    })
 }
 
 #[cfg(test)]
@@ -237,8 +477,12 @@ mod tests {
         MvArgs {
             note: note.to_string(),
             title: title.map(|s| s.to_string()),
+            title_case: false,
+            no_alias: false,
             topics: topics.into_iter().map(|s| s.to_string()).collect(),
             clear_topics,
+            id: None,
+            dry_run: false,
             format: OutputFormat::Human,
         }
     }
@@ -256,7 +500,7 @@ mod tests {
             result
                 .unwrap_err()
                 .to_string()
-                .contains("at least one of --title, --topic, or --clear-topics")
+                .contains("at least one of --title, --topic, --clear-topics, or --id")
         );
     }
 
@@ -343,4 +587,144 @@ mod tests {
         let topics = parse_topics(&["invalid topic with spaces".to_string()]);
         assert!(topics.is_err());
     }
+
+    // ===========================================
+    // Title Casing Tests
+    // ===========================================
+
+    #[test]
+    fn title_case_capitalizes_words() {
+        assert_eq!(
+            title_case("rust  ownership basics"),
+            "Rust Ownership Basics"
+        );
+    }
+
+    #[test]
+    fn title_case_keeps_minor_words_lowercase() {
+        assert_eq!(
+            title_case("the art of war in the modern age"),
+            "The Art of War in the Modern Age"
+        );
+    }
+
+    #[test]
+    fn title_case_capitalizes_minor_words_at_edges_and_after_colon() {
+        assert_eq!(
+            title_case("notes: a guide to what it is for"),
+            "Notes: A Guide to What It Is For"
+        );
+    }
+
+    #[test]
+    fn title_case_keeps_acronyms_and_mixed_case() {
+        assert_eq!(
+            title_case("using the API on an iPhone via example.com"),
+            "Using the API on an iPhone via example.com"
+        );
+    }
+
+    #[test]
+    fn title_case_handles_hyphens_digits_and_punctuation() {
+        assert_eq!(
+            title_case("up-to-date 2nd edition (draft)"),
+            "Up-to-Date 2nd Edition (Draft)"
+        );
+    }
+
+    // ===========================================
+    // Filename Collision Tests
+    // ===========================================
+
+    #[test]
+    fn available_filename_keeps_free_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let current = dir.path().join("01HQ3K5M7N-old.md");
+
+        let name = available_filename(dir.path(), "01HQ3K5M7N-new.md", &current);
+
+        assert_eq!(name, "01HQ3K5M7N-new.md");
+    }
+
+    #[test]
+    fn available_filename_skips_taken_names() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("01HQ3K5M7N-new.md"), "").unwrap();
+        std::fs::write(dir.path().join("01HQ3K5M7N-new-2.md"), "").unwrap();
+        let current = dir.path().join("01HQ3K5M7N-old.md");
+
+        let name = available_filename(dir.path(), "01HQ3K5M7N-new.md", &current);
+
+        assert_eq!(name, "01HQ3K5M7N-new-3.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn available_filename_reuses_own_file_under_another_spelling() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let current = dir.path().join("01HQ3K5M7N-Rust.md");
+        std::fs::write(&current, "").unwrap();
+        // As a case-insensitive file system would, the new name reaches the note
+        std::os::unix::fs::symlink(&current, dir.path().join("01HQ3K5M7N-rust.md")).unwrap();
+
+        let name = available_filename(dir.path(), "01HQ3K5M7N-rust.md", &current);
+
+        assert_eq!(name, "01HQ3K5M7N-rust.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn available_filename_skips_links_to_own_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let current = dir.path().join("01HQ3K5M7N-rust.md");
+        std::fs::write(&current, "").unwrap();
+        std::os::unix::fs::symlink(&current, dir.path().join("01HQ3K5M7N-go.md")).unwrap();
+
+        let name = available_filename(dir.path(), "01HQ3K5M7N-go.md", &current);
+
+        assert_eq!(name, "01HQ3K5M7N-go-2.md");
+    }
+
+    #[test]
+    fn available_filename_reuses_own_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("01HQ3K5M7N-new.md"), "").unwrap();
+        let current = dir.path().join("01HQ3K5M7N-new-2.md");
+        std::fs::write(&current, "").unwrap();
+
+        let name = available_filename(dir.path(), "01HQ3K5M7N-new.md", &current);
+
+        assert_eq!(name, "01HQ3K5M7N-new-2.md");
+    }
+
+    // ===========================================
+    // Alias Tests
+    // ===========================================
+
+    #[test]
+    fn aliases_after_rename_adds_old_title() {
+        let (aliases, added) = aliases_after_rename(&["Ownership".to_string()], "Old", "New");
+
+        assert!(added);
+        assert_eq!(aliases, vec!["Ownership", "Old"]);
+    }
+
+    #[test]
+    fn aliases_after_rename_drops_alias_matching_new_title() {
+        let (aliases, added) = aliases_after_rename(&["new".to_string()], "Old", "New");
+
+        assert!(added);
+        assert_eq!(aliases, vec!["Old"]);
+    }
+
+    #[test]
+    fn aliases_after_rename_skips_existing_alias_and_case_changes() {
+        let (aliases, added) = aliases_after_rename(&["old".to_string()], "Old", "New");
+        assert!(!added);
+        assert_eq!(aliases, vec!["old"]);
+
+        let (aliases, added) = aliases_after_rename(&[], "rust notes", "Rust Notes");
+        assert!(!added);
+        assert!(aliases.is_empty());
+    }
 }
@@ -1,16 +1,26 @@
 //! New note command handler.
 
 use anyhow::{Context, Result, bail};
-use chrono::Utc;
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use minijinja::{Environment, context};
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use super::index_db_path;
+use super::open_vault;
+use super::resolve::require_note;
+use crate::api::{NewNote, Vault};
 use crate::cli::NewArgs;
 use crate::cli::config::Config;
-use crate::domain::{Note, NoteId, Tag, Topic};
-use crate::index::{IndexBuilder, SqliteIndex};
-use crate::infra::{generate_filename, read_note, write_note};
+use crate::cli::date_filter::parse_timestamp;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{Link, Note, NoteId, Rel, Tag, Topic, find_sections};
+use crate::index::IndexedNote;
+use crate::infra::{
+    FrontmatterOptions, SlugOptions, find_program, generate_filename_with, read_note,
+    write_note_with,
+};
 
 /// Result of creating a new note (for testability).
 #[derive(Debug)]
@@ -19,10 +29,75 @@ pub struct NewNoteResult {
     pub filename: String,
 }
 
+/// Result of a create operation (`new`, `snippet`) for JSON output.
+#[derive(Debug, Serialize)]
+pub struct CreateResult {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub topics: Vec<String>,
+    pub tags: Vec<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub note_type: Option<String>,
+}
+
+impl CreateResult {
+    pub(crate) fn new(note: &Note, file_path: &Path) -> Self {
+        Self {
+            id: note.id().to_string(),
+            title: note.title().to_string(),
+            path: file_path.to_string_lossy().to_string(),
+            topics: note.topics().iter().map(|t| t.to_string()).collect(),
+            tags: note.tags().iter().map(|t| t.to_string()).collect(),
+            note_type: note.note_type().map(String::from),
+        }
+    }
+}
+
+/// Prints the outcome of creating a note in the requested format.
+pub(crate) fn print_created(note: &Note, file_path: &Path, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Human => {
+            println!("Created: {} [{}]", note.title(), note.id().prefix());
+            println!("  {}", file_path.display());
+        }
+        OutputFormat::Json => {
+            let result = CreateResult::new(note, file_path);
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => {
+            println!("{}", file_path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Parses and validates `--topic` values.
+pub(crate) fn parse_topics(topic_strs: &[String]) -> Result<Vec<Topic>> {
+    topic_strs
+        .iter()
+        .map(|topic_str| {
+            Topic::new(topic_str)
+                .with_context(|| format!("invalid topic '{}': topics must contain only alphanumeric characters, hyphens, underscores, and forward slashes", topic_str))
+        })
+        .collect()
+}
+
+/// Parses and validates `--tag` values.
+pub(crate) fn parse_tags(tag_strs: &[String]) -> Result<Vec<Tag>> {
+    tag_strs
+        .iter()
+        .map(|tag_str| {
+            Tag::new(tag_str)
+                .with_context(|| format!("invalid tag '{}': tags must contain only alphanumeric characters, hyphens, and underscores (no spaces)", tag_str))
+        })
+        .collect()
+}
+
 /// Creates a new note from the given arguments (pure function, no I/O).
 ///
 /// Validates the title, topics, and tags, then constructs a Note.
-/// Returns the Note and the generated filename.
+/// Returns the Note and the filename generated with the given slug options.
 ///
 /// # Errors
 ///
@@ -35,6 +110,7 @@ pub fn create_new_note(
     description: Option<&str>,
     topic_strs: &[String],
     tag_strs: &[String],
+    slug: &SlugOptions,
 ) -> Result<NewNoteResult> {
     // Validate title
     let trimmed_title = title.trim();
@@ -42,21 +118,8 @@ pub fn create_new_note(
         bail!("title cannot be empty");
     }
 
-    // Parse and validate topics
-    let mut topics = Vec::new();
-    for topic_str in topic_strs {
-        let topic = Topic::new(topic_str)
-            .with_context(|| format!("invalid topic '{}': topics must contain only alphanumeric characters, hyphens, underscores, and forward slashes", topic_str))?;
-        topics.push(topic);
-    }
-
-    // Parse and validate tags
-    let mut tags = Vec::new();
-    for tag_str in tag_strs {
-        let tag = Tag::new(tag_str)
-            .with_context(|| format!("invalid tag '{}': tags must contain only alphanumeric characters, hyphens, and underscores (no spaces)", tag_str))?;
-        tags.push(tag);
-    }
+    let topics = parse_topics(topic_strs)?;
+    let tags = parse_tags(tag_strs)?;
 
     // Generate ID and timestamps
     let id = NoteId::new();
@@ -71,26 +134,73 @@ pub fn create_new_note(
         .with_context(|| "failed to create note")?;
 
     // Generate filename
-    let filename = generate_filename(&id, trimmed_title);
+    let filename = generate_filename_with(&id, trimmed_title, slug);
 
     Ok(NewNoteResult { note, filename })
 }
 
 /// Opens a file in the user's configured editor.
 pub(crate) fn open_in_editor(path: &Path, config: &Config) -> Result<()> {
+    open_paths_in_editor(&[path], config)
+}
+
+/// Splits a command line into the program and its arguments at whitespace,
+/// keeping double-quoted parts together, as in
+/// `"C:\Program Files\Microsoft VS Code\bin\code.cmd" --wait`.
+///
+/// Backslashes are kept as they are, since they separate Windows paths.
+pub(crate) fn split_command(command: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    parts.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            c => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Opens several files in a single editor invocation.
+///
+/// The paths are appended to the editor command, so editors that accept an
+/// argument list (e.g. `vim`, `code -r --wait`) open them all in one session.
+pub(crate) fn open_paths_in_editor<P: AsRef<Path>>(paths: &[P], config: &Config) -> Result<()> {
     let editor = config.editor();
 
     // Parse editor command (may include args like "code --wait")
-    let parts: Vec<&str> = editor.split_whitespace().collect();
+    let parts = split_command(&editor);
     if parts.is_empty() {
         bail!("editor command is empty");
     }
 
     let (cmd, args) = parts.split_first().unwrap();
+    // Windows only finds `.exe` files by itself, not `code.cmd` and the like
+    let program = match find_program(cmd) {
+        Some(program) if cfg!(windows) => program,
+        _ => PathBuf::from(cmd),
+    };
 
-    let status = Command::new(cmd)
+    let status = Command::new(program)
         .args(args)
-        .arg(path)
+        .args(paths.iter().map(AsRef::as_ref))
         .status()
         .with_context(|| format!("failed to launch editor '{}'", editor))?;
 
@@ -102,7 +212,10 @@ pub(crate) fn open_in_editor(path: &Path, config: &Config) -> Result<()> {
 }
 
 /// Updates the modified timestamp of a note after editing.
-pub(crate) fn update_modified_timestamp(path: &Path) -> Result<()> {
+pub(crate) fn update_modified_timestamp(
+    path: &Path,
+    frontmatter: &FrontmatterOptions,
+) -> Result<()> {
     let parsed = read_note(path).with_context(|| "failed to read note after editing")?;
 
     let now = Utc::now();
@@ -117,58 +230,253 @@ pub(crate) fn update_modified_timestamp(path: &Path) -> Result<()> {
     .aliases(parsed.note.aliases().to_vec())
     .tags(parsed.note.tags().to_vec())
     .links(parsed.note.links().to_vec())
+    .extra(parsed.note.extra().clone())
     .build()
     .with_context(|| "failed to rebuild note")?;
 
-    write_note(path, &updated_note, &parsed.body)
+    write_note_with(path, &updated_note, &parsed.body, frontmatter)
         .with_context(|| "failed to write updated note")?;
 
     Ok(())
 }
 
+/// Returns the body for a new note of the given type: its rendered
+/// template, or an empty body if it has none.
+fn type_body(note_type: &str, title: &str, notes_dir: &Path, config: &Config) -> Result<String> {
+    let Some(options) = config.types.get(note_type) else {
+        bail!(
+            "unknown note type '{}' (configured types: {})",
+            note_type,
+            config.types.names().join(", ")
+        );
+    };
+    let Some(template) = &options.template else {
+        return Ok(String::new());
+    };
+
+    let path = notes_dir.join(template);
+    let source = std::fs::read_to_string(&path).with_context(|| {
+        format!(
+            "failed to read template for type '{}': {}",
+            note_type,
+            path.display()
+        )
+    })?;
+    let date = Utc::now().format("%Y-%m-%d").to_string();
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.render_str(&source, context! { title, type => note_type, date })
+        .with_context(|| format!("failed to render template: {}", path.display()))
+}
+
+/// Parses a `new --created` or `--modified` time, which can't be in the
+/// future, nor in the past when backdating is forbidden.
+fn parse_time_arg(
+    flag: &str,
+    value: Option<&str>,
+    config: &Config,
+) -> Result<Option<DateTime<Utc>>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let time = parse_timestamp(value).map_err(|e| anyhow::anyhow!("invalid {}: {}", flag, e))?;
+    let now = Utc::now();
+    if time > now {
+        bail!("{} is in the future: {}", flag, time.to_rfc3339());
+    }
+    if config.frontmatter.forbid_backdating && time < now {
+        bail!(
+            "{} is in the past, and backdating is forbidden by frontmatter.forbid_backdating",
+            flag
+        );
+    }
+    Ok(Some(time))
+}
+
+/// The existing note a new note links to (`new --link`).
+struct LinkTarget {
+    note: IndexedNote,
+    rels: Vec<Rel>,
+    /// Rels for the link back from `note`; empty unless `--reciprocal`
+    reciprocal: Vec<Rel>,
+}
+
+fn resolve_link_target(
+    args: &NewArgs,
+    target: &str,
+    vault: &Vault,
+    config: &Config,
+) -> Result<LinkTarget> {
+    let rels: Vec<Rel> = args
+        .rels
+        .iter()
+        .map(|r| Rel::new(r).map_err(|e| anyhow::anyhow!("invalid rel '{}': {}", r, e)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut reciprocal = Vec::new();
+    if args.reciprocal {
+        for rel in &rels {
+            let Some(back) = config.rels.reciprocal_of(rel) else {
+                bail!(
+                    "no reciprocal configured for rel '{}' (see [rels] reciprocal in the config)",
+                    rel
+                );
+            };
+            if !reciprocal.contains(back) {
+                reciprocal.push(back.clone());
+            }
+        }
+    }
+
+    let note = require_note(vault.index(), target, "link target", config)?;
+
+    Ok(LinkTarget {
+        note,
+        rels,
+        reciprocal,
+    })
+}
+
+fn print_linked(note: &Note, target: &LinkTarget) {
+    let rels = |rels: &[Rel]| rels.iter().map(Rel::as_str).collect::<Vec<_>>().join(", ");
+    println!(
+        "Linked: '{}' -> '{}' [{}] ({})",
+        note.title(),
+        target.note.title(),
+        target.note.id().prefix(),
+        rels(&target.rels)
+    );
+    if !target.reciprocal.is_empty() {
+        println!(
+            "Linked: '{}' -> '{}' [{}] ({})",
+            target.note.title(),
+            note.title(),
+            note.id().prefix(),
+            rels(&target.reciprocal)
+        );
+    }
+}
+
+/// Longest title taken from a note's body.
+const MAX_BODY_TITLE_LEN: usize = 80;
+
+/// Derives a title from a note body: its first heading, or else its first
+/// non-blank line, cut to a readable length; `None` for a blank body.
+pub(crate) fn body_title(body: &str) -> Option<String> {
+    let title = match find_sections(body).into_iter().find(|s| s.level > 0) {
+        Some(section) => section.title,
+        None => body
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty())?
+            .to_string(),
+    };
+    let title: String = title.chars().take(MAX_BODY_TITLE_LEN).collect();
+    let title = title.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
 pub fn handle_new(args: &NewArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     // Validate that the notes directory exists
     if !notes_dir.exists() {
         bail!("notes directory does not exist: {}", notes_dir.display());
     }
 
-    // Create the note (validates inputs)
-    let result = create_new_note(&args.title, args.desc.as_deref(), &args.topics, &args.tags)?;
+    let stdin_body = if args.stdin {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .with_context(|| "failed to read body from stdin")?;
+        Some(text)
+    } else {
+        None
+    };
+
+    // Validate inputs before any I/O
+    let title = match (&args.title, &stdin_body) {
+        (Some(title), _) => title.trim().to_string(),
+        (None, Some(body)) => body_title(body)
+            .unwrap_or_else(|| format!("Note {}", Utc::now().format("%Y-%m-%d %H:%M"))),
+        (None, None) => String::new(),
+    };
+    let title = title.as_str();
+    if title.is_empty() {
+        bail!("title cannot be empty");
+    }
+    let topics = parse_topics(&args.topics)?;
+    if topics.is_empty()
+        && !args.allow_untopiced
+        && config.topics.required_for(args.note_type.as_deref())
+    {
+        bail!(
+            "notes need a topic (`topics.required` in config); add --topic or pass --allow-untopiced"
+        );
+    }
+    let tags = parse_tags(&args.tags)?;
+    let created = parse_time_arg("--created", args.created.as_deref(), config)?;
+    let modified = parse_time_arg("--modified", args.modified.as_deref(), config)?;
+    let body = match (stdin_body, &args.note_type) {
+        (Some(body), _) => body,
+        (None, Some(note_type)) => type_body(note_type, title, notes_dir, config)?,
+        (None, None) => String::new(),
+    };
+
+    let mut vault = open_vault(notes_dir, config)?;
 
-    // Construct file path
-    let file_path = notes_dir.join(&result.filename);
+    // Resolve the note to link to before writing anything
+    let link_target = match &args.link {
+        Some(target) => Some(resolve_link_target(args, target, &vault, config)?),
+        None => None,
+    };
+    let links = match &link_target {
+        Some(target) => vec![Link::new(
+            target.note.id().clone(),
+            target.rels.iter().map(Rel::as_str).collect(),
+        )?],
+        None => Vec::new(),
+    };
 
-    // Write the note file
-    write_note(&file_path, &result.note, "")
-        .with_context(|| format!("failed to write note to {}", file_path.display()))?;
+    let created = vault
+        .create(NewNote {
+            title: title.to_string(),
+            description: args.desc.clone(),
+            topics,
+            tags,
+            links,
+            note_type: args.note_type.clone(),
+            lang: args.lang.clone(),
+            body,
+            created,
+            modified,
+        })
+        .with_context(|| "failed to create note")?;
+    let note = created.note;
+    let file_path = created.path;
 
-    // Update index (create if needed)
-    let db_path = index_db_path(notes_dir);
-    if let Ok(mut index) = SqliteIndex::open(&db_path) {
-        let builder = IndexBuilder::new(notes_dir.to_path_buf());
-        // Ignore index errors - note was created successfully
-        let _ = builder.incremental_update(&mut index);
+    // Add the reciprocal link from the existing note back to the new one
+    if let Some(target) = &link_target
+        && !target.reciprocal.is_empty()
+    {
+        let link = Link::new(
+            note.id().clone(),
+            target.reciprocal.iter().map(Rel::as_str).collect(),
+        )?;
+        vault.add_link(&target.note, &link)?;
     }
 
-    // Print success message
-    println!(
-        "Created: {} [{}]",
-        result.note.title(),
-        result.note.id().prefix()
-    );
-    println!("  {}", file_path.display());
+    print_created(&note, &file_path, args.format)?;
+    if let (Some(target), OutputFormat::Human) = (&link_target, args.format) {
+        print_linked(&note, target);
+    }
 
     // Open in editor if requested
     if args.edit {
         open_in_editor(&file_path, config)?;
         // Update modified timestamp after editing
-        update_modified_timestamp(&file_path)?;
+        update_modified_timestamp(&file_path, &config.frontmatter)?;
 
         // Update index again after editing to capture content changes
-        if let Ok(mut index) = SqliteIndex::open(&db_path) {
-            let builder = IndexBuilder::new(notes_dir.to_path_buf());
-            let _ = builder.incremental_update(&mut index);
-        }
+        let _ = vault.refresh();
     }
 
     Ok(())
@@ -0,0 +1,37 @@
+//! Handler for the `person` command.
+
+use anyhow::Result;
+use std::path::Path;
+
+use super::new::handle_new;
+use crate::cli::config::Config;
+use crate::cli::note_type::PERSON_TYPE;
+use crate::cli::{NewArgs, PersonArgs, PersonCommand, PersonNewArgs};
+
+pub fn handle_person(args: &PersonArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        PersonCommand::New(new_args) => handle_person_new(new_args, notes_dir, config),
+    }
+}
+
+/// Creates a person note, i.e. `new --type person`.
+fn handle_person_new(args: &PersonNewArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let new_args = NewArgs {
+        title: Some(args.name.clone()),
+        stdin: false,
+        topics: args.topics.clone(),
+        tags: args.tags.clone(),
+        desc: args.desc.clone(),
+        link: None,
+        rels: Vec::new(),
+        reciprocal: false,
+        note_type: Some(PERSON_TYPE.to_string()),
+        lang: None,
+        created: None,
+        modified: None,
+        allow_untopiced: args.allow_untopiced,
+        edit: args.edit,
+        format: args.format,
+    };
+    handle_new(&new_args, notes_dir, config)
+}
@@ -0,0 +1,45 @@
+//! Quick-search command handler.
+//!
+//! Prints matches as one line of JSON, a bare array rather than the usual
+//! `{"data": ...}` envelope, so launcher scripts can pass it straight on.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use super::index_db_path;
+use crate::cli::QuickSearchArgs;
+use crate::index::SqliteIndex;
+
+/// A search hit as printed by `quick-search`.
+#[derive(Debug, Serialize)]
+pub struct QuickSearchHit {
+    pub id: String,
+    pub title: String,
+    /// Absolute path of the note file.
+    pub path: String,
+    pub snippet: Option<String>,
+    pub score: f64,
+}
+
+pub fn handle_quick_search(args: &QuickSearchArgs, notes_dir: &Path) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let hits: Vec<QuickSearchHit> = index
+        .quick_search(&args.query, args.limit, args.include_archived)
+        .with_context(|| "search failed")?
+        .into_iter()
+        .map(|hit| QuickSearchHit {
+            id: hit.id.to_string(),
+            title: hit.title,
+            path: notes_dir.join(&hit.path).to_string_lossy().to_string(),
+            snippet: hit.snippet,
+            score: hit.score,
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string(&hits)?);
+    Ok(())
+}
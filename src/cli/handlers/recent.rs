@@ -0,0 +1,269 @@
+//! Recent command handler.
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::resolve::note_label;
+use super::{ARCHIVED_TAG, index_db_path, open_vault};
+use crate::api::NewNote;
+use crate::cli::RecentArgs;
+use crate::cli::config::{Config, NoteRefStyle};
+use crate::cli::date_filter::DateFilter;
+use crate::cli::output::{
+    Column, NoteListing, Output, OutputFormat, Painter, Role, SessionListing, Table, page,
+};
+use crate::domain::{Link, Tag};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// Tag applied to every session summary note.
+pub const SESSION_TAG: &str = "session";
+
+/// Groups notes into editing sessions, most recent first.
+///
+/// A session is a run of edits with no gap longer than `gap` between
+/// consecutive ones. Notes within a session are ordered most recent first.
+pub(crate) fn group_sessions(notes: &[IndexedNote], gap: Duration) -> Vec<Vec<&IndexedNote>> {
+    let mut sorted: Vec<&IndexedNote> = notes.iter().collect();
+    sorted.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+
+    let mut sessions: Vec<Vec<&IndexedNote>> = Vec::new();
+    for note in sorted {
+        match sessions.last_mut() {
+            Some(session)
+                if session
+                    .last()
+                    .is_some_and(|prev| prev.modified() - note.modified() <= gap) =>
+            {
+                session.push(note);
+            }
+            _ => sessions.push(vec![note]),
+        }
+    }
+    sessions
+}
+
+/// Title of the summary note for a session, from its first edit.
+fn session_title(session: &[&IndexedNote], config: &Config) -> String {
+    let start = session.last().map(|n| n.modified()).unwrap_or_default();
+    format!("Session {}", config.time.datetime(start))
+}
+
+/// Writes a summary note for a session, linking the notes edited in it,
+/// and returns its path.
+fn summarize_session(
+    session: &[&IndexedNote],
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<std::path::PathBuf> {
+    let mut body = String::from("Notes edited in this session:\n\n");
+    for note in session.iter().rev() {
+        writeln!(
+            body,
+            "- {} [{}]({})",
+            config.time.datetime(note.modified()),
+            note.title(),
+            note.id().prefix()
+        )?;
+    }
+    let links = session
+        .iter()
+        .map(|n| Link::new(n.id().clone(), vec!["see-also"]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut vault = open_vault(notes_dir, config)?;
+    let created = vault
+        .create(NewNote {
+            title: session_title(session, config),
+            tags: vec![Tag::new(SESSION_TAG).expect("session is a valid tag")],
+            links,
+            body,
+            ..NewNote::default()
+        })
+        .with_context(|| "failed to create session summary")?;
+    Ok(created.path)
+}
+
+fn listing(note: &IndexedNote) -> NoteListing {
+    NoteListing {
+        id: note.id().to_string(),
+        title: note.title().to_string(),
+        path: note.path().to_string_lossy().to_string(),
+        note_type: note.note_type().map(String::from),
+        description: None,
+        words: None,
+    }
+}
+
+pub fn handle_recent(args: &RecentArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let since =
+        DateFilter::parse(&args.since).map_err(|e| anyhow::anyhow!("invalid --since: {}", e))?;
+
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let mut notes = index
+        .list_all()
+        .with_context(|| "failed to list all notes")?;
+    notes.retain(|n| since.matches(n.modified()));
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+
+    if !args.sessions {
+        notes.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+        if let Some(limit) = args.limit {
+            notes.truncate(limit);
+        }
+        return print_notes(&notes, notes_dir, config, args.format);
+    }
+
+    // Summaries are written after their session, so they would otherwise
+    // make up sessions of their own
+    let session_tag = Tag::new(SESSION_TAG).expect("session is a valid tag");
+    let summaries: HashSet<String> = notes
+        .iter()
+        .filter(|n| n.tags().contains(&session_tag))
+        .map(|n| n.title().to_string())
+        .collect();
+    notes.retain(|n| !n.tags().contains(&session_tag));
+
+    let mut sessions = group_sessions(&notes, Duration::minutes(i64::from(args.gap)));
+    if let Some(limit) = args.limit {
+        sessions.truncate(limit);
+    }
+
+    let mut summary_paths = Vec::with_capacity(sessions.len());
+    for session in &sessions {
+        let path = if args.summarize && !summaries.contains(&session_title(session, config)) {
+            Some(summarize_session(session, notes_dir, config)?)
+        } else {
+            None
+        };
+        summary_paths.push(path);
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            if sessions.is_empty() {
+                println!(
+                    "No notes modified since {}.",
+                    config.time.date(since.threshold())
+                );
+                return Ok(());
+            }
+            let painter = Painter::new(&config.output);
+            let header = note_label_header(config);
+            let mut out = String::new();
+            for (session, summary) in sessions.iter().zip(&summary_paths) {
+                let (start, end) = (session[session.len() - 1].modified(), session[0].modified());
+                writeln!(
+                    out,
+                    "{} ({} note(s))",
+                    painter.paint(
+                        Role::Header,
+                        &format!(
+                            "{} - {}",
+                            config.time.datetime(start),
+                            config.time.datetime(end)
+                        )
+                    ),
+                    session.len()
+                )?;
+                let mut table = Table::new(vec![
+                    Column::left(header).role(Role::Id),
+                    Column::left("Title").max_width(50).shrink(),
+                    Column::right("Modified").role(Role::Muted),
+                ]);
+                for note in session {
+                    table.row(vec![
+                        note_label(note, config.note_refs, &config.slug),
+                        note.title().to_string(),
+                        config.time.datetime(note.modified()),
+                    ]);
+                }
+                out.push_str(&table.render(&painter));
+                if let Some(path) = summary {
+                    writeln!(out, "Summary: {}", path.display())?;
+                }
+                writeln!(out)?;
+            }
+            writeln!(out, "{} session(s)", sessions.len())?;
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            let listings: Vec<SessionListing> = sessions
+                .iter()
+                .zip(&summary_paths)
+                .map(|(session, summary)| SessionListing {
+                    start: session[session.len() - 1].modified().to_rfc3339(),
+                    end: session[0].modified().to_rfc3339(),
+                    notes: session.iter().map(|n| listing(n)).collect(),
+                    summary: summary.as_ref().map(|p| p.to_string_lossy().to_string()),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for note in sessions.iter().flatten() {
+                println!("{}", notes_dir.join(note.path()).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Header of the note reference column.
+fn note_label_header(config: &Config) -> &'static str {
+    match config.note_refs {
+        NoteRefStyle::Id => "ID",
+        NoteRefStyle::Ref | NoteRefStyle::Both => "Ref",
+    }
+}
+
+/// Prints recently modified notes, most recent first.
+fn print_notes(
+    notes: &[IndexedNote],
+    notes_dir: &Path,
+    config: &Config,
+    format: OutputFormat,
+) -> Result<()> {
+    match format {
+        OutputFormat::Human => {
+            if notes.is_empty() {
+                println!("No notes found.");
+                return Ok(());
+            }
+            let painter = Painter::new(&config.output);
+            let mut table = Table::new(vec![
+                Column::left(note_label_header(config)).role(Role::Id),
+                Column::left("Title").max_width(50).shrink(),
+                Column::right("Modified").role(Role::Muted),
+            ]);
+            for note in notes {
+                table.row(vec![
+                    note_label(note, config.note_refs, &config.slug),
+                    note.title().to_string(),
+                    config.time.datetime(note.modified()),
+                ]);
+            }
+            let mut out = table.render(&painter);
+            writeln!(out)?;
+            writeln!(out, "{} note(s)", notes.len())?;
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            let listings: Vec<NoteListing> = notes.iter().map(listing).collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for note in notes {
+                println!("{}", notes_dir.join(note.path()).display());
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+//! Recover command handler: finishing or undoing an interrupted operation.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use super::index_db_path;
+use crate::cli::RecoverArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{FileChange, Journal};
+
+/// What `recover` did with the interrupted operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoverStatus {
+    /// Nothing yet; the operation is still pending
+    Pending,
+    /// The operation was finished
+    Resumed,
+    /// The files were restored to before the operation
+    RolledBack,
+}
+
+/// A file an interrupted operation changes, in `recover` JSON output.
+#[derive(Debug, Serialize)]
+pub struct RecoverFile {
+    pub path: String,
+    /// "write", "create" or "remove"
+    pub change: &'static str,
+}
+
+/// Result of the recover command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct RecoverResult {
+    pub operation: String,
+    pub started: String,
+    pub status: RecoverStatus,
+    pub files: Vec<RecoverFile>,
+}
+
+/// Describes what the operation does to a file.
+fn change_label(change: &FileChange) -> &'static str {
+    match (&change.before, &change.after) {
+        (_, None) => "remove",
+        (None, Some(_)) => "create",
+        (Some(_), Some(_)) => "write",
+    }
+}
+
+pub fn handle_recover(args: &RecoverArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let journal = Journal::load(notes_dir).with_context(|| "failed to read the journal")?;
+    let Some(journal) = journal else {
+        match args.format {
+            OutputFormat::Json => {
+                let result: Option<RecoverResult> = None;
+                println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+            }
+            OutputFormat::Human => println!("No interrupted operation to recover."),
+            OutputFormat::Paths => {}
+        }
+        return Ok(());
+    };
+
+    let status = if args.resume {
+        journal
+            .resume(notes_dir)
+            .with_context(|| format!("failed to finish '{}'", journal.operation))?;
+        RecoverStatus::Resumed
+    } else if args.rollback {
+        journal
+            .roll_back(notes_dir)
+            .with_context(|| format!("failed to roll back '{}'", journal.operation))?;
+        RecoverStatus::RolledBack
+    } else {
+        RecoverStatus::Pending
+    };
+
+    if status != RecoverStatus::Pending {
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let paths: Vec<_> = journal.changes.iter().map(|c| c.path.clone()).collect();
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &paths)
+            .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+    }
+
+    match args.format {
+        OutputFormat::Json => {
+            let result = RecoverResult {
+                operation: journal.operation.clone(),
+                started: journal.started.to_rfc3339(),
+                status,
+                files: journal
+                    .changes
+                    .iter()
+                    .map(|change| RecoverFile {
+                        path: change.path.display().to_string(),
+                        change: change_label(change),
+                    })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => {
+            for change in &journal.changes {
+                println!("{}", notes_dir.join(&change.path).display());
+            }
+        }
+        OutputFormat::Human => {
+            let files = journal.changes.len();
+            match status {
+                RecoverStatus::Pending => {
+                    println!(
+                        "Interrupted '{}' started {}, changing {} file(s):",
+                        journal.operation,
+                        config.time.datetime(journal.started),
+                        files
+                    );
+                    for change in &journal.changes {
+                        println!("  {} {}", change_label(change), change.path.display());
+                    }
+                    println!();
+                    println!(
+                        "Run `notes recover --resume` to finish it or `notes recover --rollback` to undo it."
+                    );
+                }
+                RecoverStatus::Resumed => {
+                    println!("Finished '{}' ({} file(s))", journal.operation, files)
+                }
+                RecoverStatus::RolledBack => {
+                    println!("Rolled back '{}' ({} file(s))", journal.operation, files)
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,269 @@
+//! `rels export` handler: the rel taxonomy and typed links as JSON-LD or
+//! GraphML.
+//!
+//! Unlike `graph`, which draws one edge per linked pair, every rel on a link
+//! becomes its own edge (or RDF statement), so tools that expect one
+//! relationship type per edge (Neo4j, RDF stores) can load it directly.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+
+use super::ARCHIVED_TAG;
+use super::graph::escape_xml;
+use crate::cli::config::{Config, RelOptions};
+use crate::cli::{RelsExportArgs, RelsExportFormat};
+use crate::domain::{Link, NoteId, Rel, Tag};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// Namespace for note IRIs in JSON-LD (`note:<ULID>`).
+const NOTE_NS: &str = "urn:den:note:";
+
+/// Namespace for rel IRIs in JSON-LD (`rel:<name>`).
+const REL_NS: &str = "urn:den:rel:";
+
+/// A relationship type in the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RelType {
+    pub name: String,
+    /// Links using the rel among the exported notes.
+    pub count: usize,
+    /// The configured reciprocal rel, if any.
+    pub inverse: Option<String>,
+}
+
+/// The rel taxonomy and the typed links between a set of notes.
+pub(crate) struct RelOntology<'a> {
+    /// Ordered by name; includes configured reciprocal rels even if unused.
+    pub rels: Vec<RelType>,
+    /// Notes that are the source or target of a link, by title then ID.
+    pub notes: Vec<&'a IndexedNote>,
+    /// Source and target positions in `notes` and the rel, one per rel on
+    /// each link, ordered by source, target, then rel.
+    pub edges: Vec<(usize, usize, String)>,
+}
+
+impl<'a> RelOntology<'a> {
+    /// Builds the ontology of the links between `notes`, leaving out links
+    /// to notes outside it.
+    pub(crate) fn build(
+        notes: &'a [IndexedNote],
+        links: &HashMap<NoteId, Vec<Link>>,
+        options: &RelOptions,
+    ) -> Self {
+        let by_id: HashMap<&NoteId, &IndexedNote> = notes.iter().map(|n| (n.id(), n)).collect();
+
+        let mut instances: Vec<(&IndexedNote, &IndexedNote, String)> = Vec::new();
+        for note in notes {
+            for link in links.get(note.id()).into_iter().flatten() {
+                if let Some(target) = by_id.get(link.target()) {
+                    for rel in link.rel() {
+                        instances.push((note, target, rel.to_string()));
+                    }
+                }
+            }
+        }
+
+        let mut linked: Vec<&IndexedNote> = instances
+            .iter()
+            .flat_map(|(source, target, _)| [*source, *target])
+            .collect();
+        linked.sort_by_key(|note| (note.title().to_string(), note.id().to_string()));
+        linked.dedup_by_key(|note| note.id().clone());
+        let positions: HashMap<&NoteId, usize> = linked
+            .iter()
+            .enumerate()
+            .map(|(i, note)| (note.id(), i))
+            .collect();
+
+        let mut edges: Vec<(usize, usize, String)> = instances
+            .into_iter()
+            .map(|(source, target, rel)| (positions[source.id()], positions[target.id()], rel))
+            .collect();
+        edges.sort();
+        edges.dedup();
+
+        let mut counts: BTreeMap<String, usize> = options
+            .reciprocal
+            .iter()
+            .flat_map(|(a, b)| [a.to_string(), b.to_string()])
+            .map(|name| (name, 0))
+            .collect();
+        for (_, _, rel) in &edges {
+            *counts.entry(rel.clone()).or_default() += 1;
+        }
+        let rels = counts
+            .into_iter()
+            .map(|(name, count)| {
+                let inverse = Rel::new(&name)
+                    .ok()
+                    .and_then(|rel| options.reciprocal_of(&rel).map(ToString::to_string));
+                RelType {
+                    name,
+                    count,
+                    inverse,
+                }
+            })
+            .collect();
+
+        Self {
+            rels,
+            notes: linked,
+            edges,
+        }
+    }
+
+    /// Renders the ontology as a JSON-LD document: rel types as OWL object
+    /// properties and notes with their links as property values.
+    pub(crate) fn to_jsonld(&self) -> Value {
+        let mut graph: Vec<Value> = self
+            .rels
+            .iter()
+            .map(|rel| {
+                let mut node = json!({
+                    "@id": format!("rel:{}", rel.name),
+                    "@type": "owl:ObjectProperty",
+                    "label": rel.name,
+                    "count": rel.count,
+                });
+                if let Some(inverse) = &rel.inverse {
+                    node["inverseOf"] = json!(format!("rel:{}", inverse));
+                }
+                node
+            })
+            .collect();
+
+        for (i, note) in self.notes.iter().enumerate() {
+            let mut node = json!({
+                "@id": format!("note:{}", note.id()),
+                "@type": "Note",
+                "label": note.title(),
+                "path": note.path().display().to_string(),
+            });
+            let mut targets: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+            for (_, target, rel) in self.edges.iter().filter(|(source, _, _)| *source == i) {
+                targets
+                    .entry(format!("rel:{}", rel))
+                    .or_default()
+                    .push(json!({ "@id": format!("note:{}", self.notes[*target].id()) }));
+            }
+            for (property, values) in targets {
+                node[property] = Value::Array(values);
+            }
+            graph.push(node);
+        }
+
+        json!({
+            "@context": {
+                "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
+                "owl": "http://www.w3.org/2002/07/owl#",
+                "xsd": "http://www.w3.org/2001/XMLSchema#",
+                "den": "urn:den:",
+                "note": NOTE_NS,
+                "rel": REL_NS,
+                "Note": "den:Note",
+                "label": "rdfs:label",
+                "path": "den:path",
+                "count": { "@id": "den:count", "@type": "xsd:integer" },
+                "inverseOf": { "@id": "owl:inverseOf", "@type": "@id" },
+            },
+            "@graph": graph,
+        })
+    }
+
+    /// Renders the ontology as GraphML with one edge per rel, labelled with
+    /// the rel. The rel types and reciprocal pairs are graph attributes.
+    pub(crate) fn to_graphml(&self) -> String {
+        let mut out = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"rels\" for=\"graph\" attr.name=\"rels\" attr.type=\"string\"/>\n",
+            "  <key id=\"reciprocal\" for=\"graph\" attr.name=\"reciprocal\" attr.type=\"string\"/>\n",
+            "  <key id=\"labels\" for=\"node\" attr.name=\"labels\" attr.type=\"string\"/>\n",
+            "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+            "  <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n",
+            "  <key id=\"label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <graph id=\"rels\" edgedefault=\"directed\">\n",
+        ));
+
+        let rels: Vec<&str> = self.rels.iter().map(|r| r.name.as_str()).collect();
+        let reciprocal: Vec<String> = self
+            .rels
+            .iter()
+            .filter_map(|r| r.inverse.as_ref().map(|inverse| (&r.name, inverse)))
+            .filter(|(name, inverse)| name <= inverse)
+            .map(|(name, inverse)| format!("{}/{}", name, inverse))
+            .collect();
+        let _ = writeln!(
+            out,
+            "    <data key=\"rels\">{}</data>",
+            escape_xml(&rels.join(","))
+        );
+        let _ = writeln!(
+            out,
+            "    <data key=\"reciprocal\">{}</data>",
+            escape_xml(&reciprocal.join(","))
+        );
+
+        for note in &self.notes {
+            let _ = writeln!(
+                out,
+                "    <node id=\"{}\"><data key=\"labels\">:Note</data><data key=\"title\">{}</data><data key=\"path\">{}</data></node>",
+                note.id(),
+                escape_xml(note.title()),
+                escape_xml(&note.path().display().to_string())
+            );
+        }
+        for (i, (source, target, rel)) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"><data key=\"label\">{}</data></edge>",
+                i,
+                self.notes[*source].id(),
+                self.notes[*target].id(),
+                escape_xml(rel)
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+}
+
+pub(crate) fn handle_rels_export(
+    args: &RelsExportArgs,
+    index: &SqliteIndex,
+    config: &Config,
+) -> Result<()> {
+    let mut notes = index.list_all().with_context(|| "failed to list notes")?;
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+    let links = index.all_links().with_context(|| "failed to query links")?;
+
+    let ontology = RelOntology::build(&notes, &links, &config.rels);
+    let content = match args.format {
+        RelsExportFormat::Jsonld => serde_json::to_string_pretty(&ontology.to_jsonld())? + "\n",
+        RelsExportFormat::Graphml => ontology.to_graphml(),
+    };
+
+    match &args.output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, &content)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            println!(
+                "Wrote {} rel type(s) and {} link(s) between {} note(s) to {}",
+                ontology.rels.len(),
+                ontology.edges.len(),
+                ontology.notes.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
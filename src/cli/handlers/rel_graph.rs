@@ -0,0 +1,303 @@
+//! `rels graph` handler: renders a hierarchical relationship as a tree.
+//!
+//! Also builds the trees shown by `backlinks --recursive`.
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+
+use crate::cli::RelsGraphArgs;
+use crate::cli::output::{Output, OutputFormat, RelTreeListing};
+use crate::domain::{NoteId, Rel};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// A note in a relationship tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelTreeNode {
+    pub id: NoteId,
+    /// The indexed note, or `None` if the link points at a note that is not indexed.
+    pub note: Option<IndexedNote>,
+    /// True when this node was already an ancestor on the current path.
+    pub cycle: bool,
+    pub children: Vec<RelTreeNode>,
+}
+
+impl RelTreeNode {
+    fn label(&self) -> String {
+        match &self.note {
+            Some(note) => format!("{} [{}]", note.title(), self.id.prefix()),
+            None => format!("<missing> [{}]", self.id.prefix()),
+        }
+    }
+
+    pub(crate) fn to_listing(&self) -> RelTreeListing {
+        RelTreeListing {
+            id: self.id.to_string(),
+            title: self.note.as_ref().map(|n| n.title().to_string()),
+            path: self.note.as_ref().map(|n| n.path().display().to_string()),
+            cycle: self.cycle,
+            children: self.children.iter().map(Self::to_listing).collect(),
+        }
+    }
+}
+
+/// Builds a forest from `(child, parent)` edges.
+///
+/// Roots are notes that have children but no parent. Notes reachable only
+/// through a cycle are rooted at the first of them by title so that every
+/// note in the graph appears at least once. A node that is already an
+/// ancestor on the current path is emitted once more with `cycle` set and
+/// without children.
+pub fn build_rel_forest(
+    edges: &[(NoteId, NoteId)],
+    notes: &HashMap<NoteId, IndexedNote>,
+) -> Vec<RelTreeNode> {
+    let mut children: HashMap<&NoteId, Vec<&NoteId>> = HashMap::new();
+    let mut has_parent: HashSet<&NoteId> = HashSet::new();
+    let mut all: HashSet<&NoteId> = HashSet::new();
+
+    for (child, parent) in edges {
+        let siblings = children.entry(parent).or_default();
+        if !siblings.contains(&child) {
+            siblings.push(child);
+        }
+        has_parent.insert(child);
+        all.insert(child);
+        all.insert(parent);
+    }
+
+    let mut roots: Vec<&NoteId> = all
+        .iter()
+        .copied()
+        .filter(|id| !has_parent.contains(id))
+        .collect();
+    sort_by_title(&mut roots, notes);
+
+    let mut forest = Vec::new();
+    let mut visited: HashSet<NoteId> = HashSet::new();
+    for root in roots {
+        let mut path = Vec::new();
+        forest.push(walk(root, &children, notes, &mut path, &mut visited));
+    }
+
+    let mut remaining: Vec<&NoteId> = all
+        .iter()
+        .copied()
+        .filter(|id| !visited.contains(*id))
+        .collect();
+    sort_by_title(&mut remaining, notes);
+    for id in remaining {
+        if visited.contains(id) {
+            continue;
+        }
+        let mut path = Vec::new();
+        forest.push(walk(id, &children, notes, &mut path, &mut visited));
+    }
+
+    forest
+}
+
+/// Orders sibling notes by title, with missing notes last and ties broken by ID.
+fn sort_by_title(ids: &mut [&NoteId], notes: &HashMap<NoteId, IndexedNote>) {
+    ids.sort_by_key(|id| {
+        let title = notes.get(*id).map(|n| n.title().to_lowercase());
+        (title.is_none(), title, id.to_string())
+    });
+}
+
+fn walk<'a>(
+    id: &'a NoteId,
+    children: &HashMap<&'a NoteId, Vec<&'a NoteId>>,
+    notes: &HashMap<NoteId, IndexedNote>,
+    path: &mut Vec<&'a NoteId>,
+    visited: &mut HashSet<NoteId>,
+) -> RelTreeNode {
+    let note = notes.get(id).cloned();
+
+    if path.contains(&id) {
+        return RelTreeNode {
+            id: id.clone(),
+            note,
+            cycle: true,
+            children: Vec::new(),
+        };
+    }
+
+    visited.insert(id.clone());
+    path.push(id);
+
+    let mut kids = children.get(id).cloned().unwrap_or_default();
+    sort_by_title(&mut kids, notes);
+
+    let child_nodes = kids
+        .into_iter()
+        .map(|child| walk(child, children, notes, path, visited))
+        .collect();
+
+    path.pop();
+
+    RelTreeNode {
+        id: id.clone(),
+        note,
+        cycle: false,
+        children: child_nodes,
+    }
+}
+
+/// Builds the tree of notes linking to `root`, then the notes linking to
+/// those, down to `depth` levels.
+///
+/// `backlinks` returns the notes linking to a note, in display order. A
+/// linker that is already on the path from the root is emitted with `cycle`
+/// set and not expanded.
+pub fn build_backlink_tree<F>(
+    root: &IndexedNote,
+    depth: usize,
+    mut backlinks: F,
+) -> Result<RelTreeNode>
+where
+    F: FnMut(&NoteId) -> Result<Vec<IndexedNote>>,
+{
+    let mut path = Vec::new();
+    expand_backlinks(root.clone(), depth, &mut backlinks, &mut path)
+}
+
+fn expand_backlinks<F>(
+    note: IndexedNote,
+    depth: usize,
+    backlinks: &mut F,
+    path: &mut Vec<NoteId>,
+) -> Result<RelTreeNode>
+where
+    F: FnMut(&NoteId) -> Result<Vec<IndexedNote>>,
+{
+    let id = note.id().clone();
+    if path.contains(&id) {
+        return Ok(RelTreeNode {
+            id,
+            note: Some(note),
+            cycle: true,
+            children: Vec::new(),
+        });
+    }
+
+    let mut children = Vec::new();
+    if depth > 0 {
+        path.push(id.clone());
+        for linker in backlinks(&id)? {
+            children.push(expand_backlinks(linker, depth - 1, backlinks, path)?);
+        }
+        path.pop();
+    }
+
+    Ok(RelTreeNode {
+        id,
+        note: Some(note),
+        cycle: false,
+        children,
+    })
+}
+
+/// Renders a forest as an indented tree, one line per node.
+pub fn render_rel_forest(forest: &[RelTreeNode]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for root in forest {
+        lines.push(node_line(root));
+        render_children(&root.children, "", &mut lines);
+    }
+    lines
+}
+
+fn render_children(nodes: &[RelTreeNode], prefix: &str, lines: &mut Vec<String>) {
+    for (i, node) in nodes.iter().enumerate() {
+        let last = i + 1 == nodes.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        lines.push(format!("{}{}{}", prefix, branch, node_line(node)));
+        render_children(&node.children, &format!("{}{}", prefix, indent), lines);
+    }
+}
+
+fn node_line(node: &RelTreeNode) -> String {
+    if node.cycle {
+        format!("{} (cycle)", node.label())
+    } else {
+        node.label()
+    }
+}
+
+pub(crate) fn collect_paths(
+    nodes: &[RelTreeNode],
+    seen: &mut HashSet<NoteId>,
+    out: &mut Vec<String>,
+) {
+    for node in nodes {
+        if let Some(note) = &node.note
+            && seen.insert(node.id.clone())
+        {
+            out.push(note.path().display().to_string());
+        }
+        collect_paths(&node.children, seen, out);
+    }
+}
+
+pub(crate) fn handle_rels_graph(args: &RelsGraphArgs, index: &SqliteIndex) -> Result<()> {
+    let rel = Rel::new(&args.rel)
+        .map_err(|e| anyhow::anyhow!("invalid relationship type '{}': {}", args.rel, e))?;
+
+    // A link `source -> target` with rel `parent` says the target is the
+    // source's parent; `--invert` reads it the other way round.
+    let edges: Vec<(NoteId, NoteId)> = index
+        .rel_edges(&rel)
+        .with_context(|| "failed to query relationship edges")?
+        .into_iter()
+        .map(|(source, target)| {
+            if args.invert {
+                (target, source)
+            } else {
+                (source, target)
+            }
+        })
+        .collect();
+
+    let mut notes = HashMap::new();
+    for (child, parent) in &edges {
+        for id in [child, parent] {
+            if !notes.contains_key(id)
+                && let Some(note) = index.get_note(id)?
+            {
+                notes.insert(id.clone(), note);
+            }
+        }
+    }
+
+    let forest = build_rel_forest(&edges, &notes);
+
+    match args.format {
+        OutputFormat::Human => {
+            if forest.is_empty() {
+                println!("No '{}' relationships found.", rel);
+            } else {
+                for line in render_rel_forest(&forest) {
+                    println!("{}", line);
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let listings: Vec<RelTreeListing> =
+                forest.iter().map(RelTreeNode::to_listing).collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            let mut paths = Vec::new();
+            collect_paths(&forest, &mut HashSet::new(), &mut paths);
+            for path in paths {
+                println!("{}", path);
+            }
+        }
+    }
+
+    Ok(())
+}
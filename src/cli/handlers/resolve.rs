@@ -1,22 +1,104 @@
 //! Note resolution utilities.
 
-use anyhow::{Context, Result};
+use anyhow::{Result, bail};
+use std::io::BufRead;
 
+use crate::cli::config::{Config, NoteRefStyle};
+use crate::cli::picker::pick_note;
 use crate::index::{IndexRepository, IndexedNote};
+use crate::infra::{SlugOptions, slugify_with};
 
-/// Result of resolving a note identifier.
-#[derive(Debug)]
-pub enum ResolveResult {
-    /// Exactly one note matched.
-    Unique(IndexedNote),
-    /// Multiple notes matched (ambiguous).
-    Ambiguous(Vec<IndexedNote>),
-    /// No notes matched.
-    NotFound,
+pub use crate::api::{ResolveResult, resolve_note};
+
+/// Resolves `identifier` to one note, settling an ambiguous reference with
+/// [`choose_note`] when `config.interactive` is set. Returns `None` if no
+/// note matches.
+///
+/// `noun` names the note in errors, e.g. "source note".
+pub(crate) fn find_note<R: IndexRepository>(
+    index: &R,
+    identifier: &str,
+    noun: &str,
+    config: &Config,
+) -> Result<Option<IndexedNote>> {
+    match resolve_note(index, identifier, &config.slug)? {
+        ResolveResult::Unique(note) => Ok(Some(note)),
+        ResolveResult::Ambiguous(notes) => {
+            choose_note(identifier, notes, noun, config.interactive).map(Some)
+        }
+        ResolveResult::NotFound => Ok(None),
+    }
+}
+
+/// Resolves `identifier` to one note like [`find_note`], failing if no note
+/// matches.
+pub(crate) fn require_note<R: IndexRepository>(
+    index: &R,
+    identifier: &str,
+    noun: &str,
+    config: &Config,
+) -> Result<IndexedNote> {
+    match find_note(index, identifier, noun, config)? {
+        Some(note) => Ok(note),
+        None => bail!("{} not found: '{}'", noun, identifier),
+    }
+}
+
+/// Settles a reference that matches several notes.
+///
+/// With `interactive` the user picks one of the candidates on stdin with
+/// the fuzzy picker. Otherwise they are listed and the reference is an
+/// error, so scripts fail fast instead of waiting for input.
+pub(crate) fn choose_note(
+    identifier: &str,
+    notes: Vec<IndexedNote>,
+    noun: &str,
+    interactive: bool,
+) -> Result<IndexedNote> {
+    if !interactive {
+        print_ambiguous_notes(identifier, &notes);
+        bail!("ambiguous {} identifier", noun);
+    }
+    prompt_for_note(identifier, notes, noun, std::io::stdin().lock())
+}
+
+/// Asks which of `notes` was meant with the fuzzy picker, reading answers
+/// from `input`.
+pub(crate) fn prompt_for_note<R: BufRead>(
+    identifier: &str,
+    notes: Vec<IndexedNote>,
+    noun: &str,
+    input: R,
+) -> Result<IndexedNote> {
+    let header = format!("'{}' matches {} notes:", identifier, notes.len());
+    pick_note(&header, notes, noun, input)
+}
+
+/// Resolves `identifier` like [`require_note`], or with no identifier lets
+/// the user pick any note when `config.interactive` is set.
+pub(crate) fn require_or_pick_note<R: IndexRepository>(
+    index: &R,
+    identifier: Option<&str>,
+    noun: &str,
+    config: &Config,
+) -> Result<IndexedNote> {
+    if let Some(identifier) = identifier {
+        return require_note(index, identifier, noun, config);
+    }
+    if !config.interactive {
+        bail!("a {} is required (or pass --interactive to pick one)", noun);
+    }
+    let mut notes = index.list_all()?;
+    if notes.is_empty() {
+        bail!("there are no notes to pick from");
+    }
+    notes.sort_by_key(|n| std::cmp::Reverse(n.modified()));
+    let header = format!("Pick a {} ({} notes):", noun, notes.len());
+    pick_note(&header, notes, noun, std::io::stdin().lock())
 }
 
 /// Prints detailed information about ambiguous notes to help distinguish them.
-pub(crate) fn print_ambiguous_notes(identifier: &str, notes: &[IndexedNote]) {
+fn print_ambiguous_notes(identifier: &str, notes: &[IndexedNote]) {
     eprintln!("Ambiguous: '{}' matches {} notes:", identifier, notes.len());
     for note in notes {
         eprintln!("  {} - {}", note.id().prefix(), note.title());
@@ -41,62 +123,26 @@ pub(crate) fn print_ambiguous_notes(identifier: &str, notes: &[IndexedNote]) {
     eprintln!("Use the ID prefix to specify which note you mean.");
 }
 
-/// Resolves a note identifier to a unique note.
-///
-/// Resolution order:
-/// 1. ID prefix match (if input looks like a ULID prefix)
-/// 2. Exact title match
-/// 3. Alias match
+/// Returns the human-friendly short ref for a note.
 ///
-/// Returns `Unique` if exactly one note matches across all methods,
-/// `Ambiguous` if multiple notes match, or `NotFound` if no match.
-pub fn resolve_note<R: IndexRepository>(index: &R, identifier: &str) -> Result<ResolveResult> {
-    let identifier = identifier.trim();
-
-    // Check if it looks like a ULID prefix (alphanumeric, typically 8+ chars)
-    let looks_like_id =
-        identifier.len() >= 4 && identifier.chars().all(|c| c.is_ascii_alphanumeric());
-
-    let mut candidates: Vec<IndexedNote> = Vec::new();
-
-    // 1. Try ID prefix match if it looks like one
-    if looks_like_id {
-        let id_matches = index
-            .find_by_id_prefix(identifier)
-            .with_context(|| "failed to search by ID prefix")?;
-
-        // If we get exactly one ID match, return it immediately
-        // ID matches are the most precise
-        if id_matches.len() == 1 {
-            return Ok(ResolveResult::Unique(
-                id_matches.into_iter().next().unwrap(),
-            ));
-        }
-
-        candidates.extend(id_matches);
+/// The ref is the note's primary (first) topic followed by its title slugged
+/// with `slug`, e.g. `rust/api-design`, which [`resolve_note`] resolves with
+/// the same options. Notes without topics use their ID prefix, since a bare
+/// slug wouldn't resolve.
+pub fn short_ref(note: &IndexedNote, slug: &SlugOptions) -> String {
+    match note.topics().first() {
+        Some(topic) => format!("{}/{}", topic, slugify_with(note.title(), slug)),
+        None => note.id().prefix(),
     }
+}
 
-    // 2. Try exact title match
-    let title_matches = index
-        .find_by_title(identifier)
-        .with_context(|| "failed to search by title")?;
-    candidates.extend(title_matches);
-
-    // 3. Try alias match
-    let alias_matches = index
-        .find_by_alias(identifier)
-        .with_context(|| "failed to search by alias")?;
-    candidates.extend(alias_matches);
-
-    // Deduplicate by ID
-    candidates.sort_by_key(|a| a.id().to_string());
-    candidates.dedup_by(|a, b| a.id() == b.id());
-
-    match candidates.len() {
-        0 => Ok(ResolveResult::NotFound),
-        1 => Ok(ResolveResult::Unique(
-            candidates.into_iter().next().unwrap(),
-        )),
-        _ => Ok(ResolveResult::Ambiguous(candidates)),
+/// Returns the label identifying a note in human-readable listings.
+pub(crate) fn note_label(note: &IndexedNote, style: NoteRefStyle, slug: &SlugOptions) -> String {
+    match style {
+        NoteRefStyle::Id => note.id().prefix().to_string(),
+        NoteRefStyle::Ref => short_ref(note, slug),
+        // The short ref of a note without topics is already its prefix
+        NoteRefStyle::Both if note.topics().is_empty() => note.id().prefix(),
+        NoteRefStyle::Both => format!("{} {}", note.id().prefix(), short_ref(note, slug)),
     }
 }
@@ -0,0 +1,164 @@
+//! Rm command handler.
+//!
+//! Removing a note moves its file into the trash, where `trash restore` can
+//! bring it back; `--force` deletes it outright, after asking when run from a
+//! terminal (unless `--yes`). Either way the note leaves
+//! the index, and the notes still referencing it are listed so their links
+//! can be fixed.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use super::batch::{NoteOutcome, STDIN_NOTES, read_stdin_notes, run_batch};
+use super::open_vault;
+use super::resolve::require_note;
+use super::trash::{move_to_trash, scan_trash};
+use crate::cli::RmArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{NoteListing, OutputFormat};
+use crate::index::{IndexBuilder, IndexedNote, SqliteIndex};
+
+/// Result of `rm` for JSON output.
+#[derive(Debug, Serialize)]
+pub struct RmResult {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    /// Where the note went in the trash; absent with `--force`.
+    pub trashed_to: Option<String>,
+    /// Notes that still link to or mention the removed note.
+    pub referenced_by: Vec<NoteListing>,
+}
+
+pub fn handle_rm(args: &RmArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if args.note == STDIN_NOTES {
+        // Stdin holds the notes, so there is no way to ask first
+        if args.force && !args.yes {
+            bail!("rm - --force deletes without asking; add --yes to confirm");
+        }
+        let notes = read_stdin_notes(notes_dir)?;
+        return run_batch(&notes, args.format, |note| {
+            remove_note(note, args, notes_dir, config)?.with_context(|| "not deleted")
+        });
+    }
+    match remove_note(&args.note, args, notes_dir, config)? {
+        Some(outcome) => outcome.print(args.format),
+        None => {
+            println!("Not deleted.");
+            Ok(())
+        }
+    }
+}
+
+/// Trashes or deletes the note `query` refers to and drops it from the index.
+///
+/// Returns `None` if the user declined to delete it.
+fn remove_note(
+    query: &str,
+    args: &RmArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<Option<NoteOutcome<RmResult>>> {
+    let vault = open_vault(notes_dir, config)?;
+    let note = require_note(vault.index(), query, "note", config)?;
+    let report = vault
+        .inbound_references(&note)
+        .with_context(|| "failed to scan notes for references")?;
+    drop(vault);
+
+    // Notes that will be left with a dangling link or reference
+    let mut seen = HashSet::new();
+    let referenced_by: Vec<&IndexedNote> = report
+        .links
+        .iter()
+        .map(|link| &link.source)
+        .chain(report.mentions.iter().map(|mention| &mention.source))
+        .filter(|source| source.id() != note.id() && seen.insert(source.id().clone()))
+        .collect();
+
+    if args.force && !args.yes && std::io::stdin().is_terminal() {
+        eprint!(
+            "Delete '{}' [{}] for good? [y/N] ",
+            note.title(),
+            note.id().prefix()
+        );
+        std::io::stderr().flush()?;
+        if !confirmed(std::io::stdin().lock())? {
+            return Ok(None);
+        }
+    }
+
+    let full_path = notes_dir.join(note.path());
+    let db_path = super::index_db_path(notes_dir);
+    let mut index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let trashed_to = if args.force {
+        std::fs::remove_file(&full_path)
+            .with_context(|| format!("failed to delete {}", full_path.display()))?;
+        None
+    } else {
+        let trashed = move_to_trash(notes_dir, note.path())?;
+        scan_trash(notes_dir, &mut index, Utc::now())?;
+        Some(trashed)
+    };
+    let builder = IndexBuilder::new(notes_dir.to_path_buf()).with_scan_options(config.scan.clone());
+    builder
+        .update_paths(&mut index, &[note.path().to_path_buf()])
+        .with_context(|| "failed to remove the note from the index")?;
+
+    if matches!(args.format, OutputFormat::Human) && !referenced_by.is_empty() {
+        eprintln!(
+            "warning: {} note(s) still reference '{}':",
+            referenced_by.len(),
+            note.title()
+        );
+        for source in &referenced_by {
+            eprintln!("  {} {}", source.id().prefix(), source.title());
+        }
+    }
+
+    let message = match &trashed_to {
+        Some(trashed) => format!(
+            "Moved '{}' [{}] to {}",
+            note.title(),
+            note.id().prefix(),
+            trashed.display()
+        ),
+        None => format!("Deleted '{}' [{}]", note.title(), note.id().prefix()),
+    };
+    Ok(Some(NoteOutcome {
+        result: RmResult {
+            id: note.id().to_string(),
+            title: note.title().to_string(),
+            path: note.path().display().to_string(),
+            trashed_to: trashed_to.as_ref().map(|p| p.display().to_string()),
+            referenced_by: referenced_by
+                .iter()
+                .map(|source| NoteListing {
+                    id: source.id().to_string(),
+                    title: source.title().to_string(),
+                    path: source.path().display().to_string(),
+                    note_type: source.note_type().map(String::from),
+                    description: None,
+                    words: None,
+                })
+                .collect(),
+        },
+        message,
+        path: full_path,
+        changed: true,
+    }))
+}
+
+/// Reads a yes/no answer; anything but "y" or "yes" is no.
+pub(crate) fn confirmed(mut input: impl BufRead) -> Result<bool> {
+    let mut answer = String::new();
+    input
+        .read_line(&mut answer)
+        .with_context(|| "failed to read answer from stdin")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -1,25 +1,50 @@
 //! Search command handler.
 
-use anyhow::{Context, Result};
-use std::collections::HashSet;
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
 use std::path::Path;
 
 use super::ARCHIVED_TAG;
 use super::index_db_path;
 use super::list::{note_matches_topic, parse_topic_filter};
-use crate::cli::SearchArgs;
-use crate::cli::output::{Output, OutputFormat, SearchListing};
-use crate::domain::{Tag, Topic};
-use crate::index::{IndexRepository, SearchResult, SqliteIndex};
+use super::resolve::{note_label, require_note};
+use super::show_edit::{EditorLauncher, RealEditor, handle_edit_impl, handle_show};
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, FieldMatchListing, LineMatchListing, Output, OutputFormat, Painter, Role,
+    SearchListing, Table, page,
+};
+use crate::cli::{EditArgs, SearchArgs, ShowArgs};
+use crate::domain::{NoteId, Tag, Topic};
+use crate::index::{SearchExplanation, SearchField, SearchResult, SqliteIndex};
+use crate::infra::parse;
 
-pub fn handle_search(args: &SearchArgs, notes_dir: &Path) -> Result<()> {
+pub fn handle_search(args: &SearchArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    handle_search_impl(args, notes_dir, config, &RealEditor(config))
+}
+
+/// Internal implementation that accepts a generic editor launcher, for
+/// `--open`.
+pub(crate) fn handle_search_impl<E: EditorLauncher>(
+    args: &SearchArgs,
+    notes_dir: &Path,
+    config: &Config,
+    editor: &E,
+) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    // 1. Execute FTS search
+    if let Some(query) = &args.in_note {
+        let indexed_note = require_note(&index, query, "note", config)?;
+        let file_path = notes_dir.join(indexed_note.path());
+        return search_in_note(&args.query, &file_path, args.format, config);
+    }
+
+    // 1. Execute FTS search over notes and the sections of their bodies
     let mut results = index
-        .search(&args.query)
+        .search_with_sections(&args.query)
         .with_context(|| format!("search failed for query: {}", args.query))?;
 
     // 2. Filter by topic (if provided)
@@ -50,8 +75,186 @@ pub fn handle_search(args: &SearchArgs, notes_dir: &Path) -> Result<()> {
         results.retain(|r| !r.note().tags().contains(&archived_tag));
     }
 
-    // 5. Format and output (results already ranked)
-    format_search_output(&results, args.format, notes_dir)?;
+    // 5. Page through the results
+    let total = results.len();
+    let results: Vec<SearchResult> = results
+        .into_iter()
+        .skip(args.offset)
+        .take(args.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    // 6. Open or print the best match (if --open or --show)
+    if args.open || args.show {
+        let Some(best) = results.first() else {
+            bail!("no matching notes found for '{}'", args.query);
+        };
+        let id = best.note().id().to_string();
+        if args.open {
+            let edit_args = EditArgs {
+                note: Some(id),
+                all: false,
+                topic: None,
+                tags: Vec::new(),
+                query: None,
+                include_archived: false,
+                format: args.format,
+            };
+            return handle_edit_impl(&edit_args, notes_dir, config, editor);
+        }
+        let show_args = ShowArgs {
+            note: Some(id),
+            section: None,
+            history: false,
+            at: None,
+            from_index: false,
+            format: args.format,
+        };
+        return handle_show(&show_args, notes_dir, config);
+    }
+
+    // 7. Explain the remaining results (if --explain)
+    let explanations = if args.explain {
+        index
+            .explain_search(&args.query)
+            .with_context(|| format!("search failed for query: {}", args.query))?
+            .into_iter()
+            .map(|e| (e.id.clone(), e))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    // 8. Format and output (results already ranked)
+    let shown = Page {
+        offset: args.offset,
+        total,
+    };
+    format_search_output(
+        &results,
+        shown,
+        &explanations,
+        args.format,
+        notes_dir,
+        config,
+    )?;
+
+    Ok(())
+}
+
+/// A line of a note body that matches a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LineMatch {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+    /// Nearest preceding heading and its line number.
+    pub heading: Option<(usize, String)>,
+}
+
+/// Finds lines containing every whitespace-separated query term,
+/// case-insensitively.
+///
+/// Line numbers start at `first_line`, so they can point into the file
+/// rather than the body. Lines inside fenced code blocks can match but are
+/// never treated as headings.
+pub(crate) fn find_in_body(body: &str, query: &str, first_line: usize) -> Vec<LineMatch> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matches = Vec::new();
+    let mut heading: Option<(usize, String)> = None;
+    let mut in_fence = false;
+
+    for (i, text) in body.lines().enumerate() {
+        let line = first_line + i;
+        let trimmed = text.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence && trimmed.starts_with('#') {
+            let title = trimmed.trim_start_matches('#');
+            if title.is_empty() || title.starts_with(' ') {
+                heading = Some((line, title.trim().to_string()));
+            }
+        }
+
+        let lower = text.to_lowercase();
+        if terms.iter().all(|t| lower.contains(t.as_str())) {
+            matches.push(LineMatch {
+                line,
+                text: text.to_string(),
+                heading: heading.clone(),
+            });
+        }
+    }
+
+    matches
+}
+
+/// Searches one note file and prints matches grouped by section.
+fn search_in_note(
+    query: &str,
+    file_path: &Path,
+    format: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    let content = std::fs::read_to_string(file_path)
+        .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+    let parsed = parse(&content)
+        .with_context(|| format!("failed to parse note: {}", file_path.display()))?;
+
+    // The body is the tail of the file; count the frontmatter lines before it
+    let frontmatter = &content[..content.len() - parsed.body.len()];
+    let first_line = frontmatter.matches('\n').count() + 1;
+    let matches = find_in_body(&parsed.body, query, first_line);
+
+    match format {
+        OutputFormat::Human => {
+            if matches.is_empty() {
+                println!("No matching lines in '{}'.", parsed.note.title());
+                return Ok(());
+            }
+            let mut out = String::new();
+            let mut current: Option<usize> = None;
+            for m in &matches {
+                let heading_line = m.heading.as_ref().map(|(line, _)| *line);
+                if heading_line != current {
+                    if current.is_some() {
+                        writeln!(out)?;
+                    }
+                    if let Some((line, title)) = &m.heading {
+                        writeln!(out, "{}: {}", line, title)?;
+                    }
+                    current = heading_line;
+                }
+                if heading_line != Some(m.line) {
+                    writeln!(out, "  {}: {}", m.line, m.text.trim())?;
+                }
+            }
+            writeln!(out)?;
+            writeln!(out, "{} matching line(s)", matches.len())?;
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            let listings: Vec<LineMatchListing> = matches
+                .into_iter()
+                .map(|m| LineMatchListing {
+                    line: m.line,
+                    text: m.text,
+                    heading_line: m.heading.as_ref().map(|(line, _)| *line),
+                    heading: m.heading.map(|(_, title)| title),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for m in &matches {
+                println!("{}:{}", file_path.display(), m.line);
+            }
+        }
+    }
 
     Ok(())
 }
@@ -61,32 +264,132 @@ pub(crate) fn strip_html_tags(s: &str) -> String {
     s.replace("<b>", "").replace("</b>", "")
 }
 
+/// Replaces the `<b>` markers in a snippet with the theme's accent color.
+pub(crate) fn highlight_matches(s: &str, painter: &Painter) -> String {
+    if !painter.color() {
+        return strip_html_tags(s);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("<b>") {
+        out.push_str(&rest[..start]);
+        let matched = &rest[start + "<b>".len()..];
+        let (matched, next) = match matched.find("</b>") {
+            Some(end) => (&matched[..end], &matched[end + "</b>".len()..]),
+            None => (matched, ""),
+        };
+        out.push_str(&painter.paint(Role::Accent, matched));
+        rest = next;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Renders how each field and the matched body section contributed to a
+/// result's rank.
+fn render_explanation(explanation: &SearchExplanation, painter: &Painter) -> String {
+    let mut table = Table::new(vec![
+        Column::left("Field"),
+        Column::right("Weight").role(Role::Muted),
+        Column::right("Score"),
+        Column::left("Offsets").shrink(),
+    ])
+    .indent(4)
+    .without_rule();
+    for field in &explanation.fields {
+        if !field.matched() {
+            table.row([field.field.to_string(), field.field.weight().to_string()]);
+            continue;
+        }
+        let offsets: Vec<String> = field
+            .offsets
+            .iter()
+            .map(|o| format!("{}..{}", o.start, o.end))
+            .collect();
+        table.row([
+            field.field.to_string(),
+            field.field.weight().to_string(),
+            format!("{:.2}", field.score),
+            offsets.join(", "),
+        ]);
+    }
+    if let Some(section) = &explanation.section {
+        table.row([
+            "section".to_string(),
+            SearchField::Body.weight().to_string(),
+            format!("{:.2}", section.rank),
+            format!("§ {}", section.path),
+        ]);
+    }
+    table.render(painter)
+}
+
+/// Where a page of search results falls among all of them.
+#[derive(Debug, Clone, Copy)]
+struct Page {
+    /// Results skipped before this page.
+    offset: usize,
+    /// Results on all pages.
+    total: usize,
+}
+
 /// Format and print search results.
 fn format_search_output(
     results: &[SearchResult],
+    shown: Page,
+    explanations: &HashMap<NoteId, SearchExplanation>,
     format: OutputFormat,
     notes_dir: &Path,
+    config: &Config,
 ) -> Result<()> {
     match format {
         OutputFormat::Human => {
-            if results.is_empty() {
+            if results.is_empty() && shown.total > 0 {
+                println!(
+                    "No results after the first {} of {}.",
+                    shown.offset, shown.total
+                );
+            } else if results.is_empty() {
                 println!("No matching notes found.");
             } else {
+                let painter = Painter::new(&config.output);
+                let mut out = String::new();
                 for result in results {
                     let note = result.note();
-                    println!(
-                        "{} {} (rank: {:.2})",
-                        note.id().prefix(),
+                    writeln!(
+                        out,
+                        "{} {} {}",
+                        painter.paint(Role::Id, &note_label(note, config.note_refs, &config.slug)),
                         note.title(),
-                        result.rank()
-                    );
+                        painter.paint(Role::Muted, &format!("(rank: {:.2})", result.rank()))
+                    )?;
+                    if let Some(section) = result.section() {
+                        writeln!(
+                            out,
+                            "  {}",
+                            painter.paint(Role::Muted, &format!("§ {}", section))
+                        )?;
+                    }
                     if let Some(snippet) = result.snippet() {
-                        let clean = strip_html_tags(snippet);
-                        println!("  {}", clean);
+                        writeln!(out, "  {}", highlight_matches(snippet, &painter))?;
                     }
+                    if let Some(explanation) = explanations.get(note.id()) {
+                        out.push_str(&render_explanation(explanation, &painter));
+                    }
+                }
+                writeln!(out)?;
+                if results.len() == shown.total {
+                    writeln!(out, "{} result(s)", results.len())?;
+                } else {
+                    writeln!(
+                        out,
+                        "Showing {}-{} of {} result(s)",
+                        shown.offset + 1,
+                        shown.offset + results.len(),
+                        shown.total
+                    )?;
                 }
-                println!();
-                println!("{} result(s)", results.len());
+                page(&out, &config.output)?;
             }
         }
         OutputFormat::Json => {
@@ -98,6 +401,26 @@ fn format_search_output(
                     path: r.note().path().to_string_lossy().to_string(),
                     rank: r.rank(),
                     snippet: r.snippet().map(|s| s.to_string()),
+                    section: r.section().map(String::from),
+                    explain: explanations.get(r.note().id()).map(|e| {
+                        let fields = e.fields.iter().map(|f| FieldMatchListing {
+                            field: f.field.to_string(),
+                            weight: f.field.weight(),
+                            score: f.score,
+                            matched: f.matched(),
+                            offsets: f.offsets.iter().map(|o| [o.start, o.end]).collect(),
+                            section: None,
+                        });
+                        let section = e.section.iter().map(|s| FieldMatchListing {
+                            field: "section".to_string(),
+                            weight: SearchField::Body.weight(),
+                            score: s.rank,
+                            matched: true,
+                            offsets: Vec::new(),
+                            section: Some(s.path.clone()),
+                        });
+                        fields.chain(section).collect()
+                    }),
                 })
                 .collect();
             let output = Output::new(listings);
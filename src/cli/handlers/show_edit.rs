@@ -1,121 +1,612 @@
 //! Show and Edit command handlers.
 
 use anyhow::{Context, Result, bail};
-use std::path::Path;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 
+use super::archive::ARCHIVED_TAG;
+use super::batch::{STDIN_NOTES, read_stdin_notes};
 use super::index_db_path;
-use super::new::{open_in_editor, update_modified_timestamp};
-use super::resolve::{ResolveResult, print_ambiguous_notes, resolve_note};
+use super::list::{note_matches_topic, parse_topic_filter};
+use super::new::{open_in_editor, open_paths_in_editor, update_modified_timestamp};
+use super::resolve::{require_note, require_or_pick_note};
 use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, HistoryListing, NoteListing, Output, OutputFormat, Painter, Role, Table, page,
+};
 use crate::cli::{EditArgs, ShowArgs};
-use crate::index::{IndexBuilder, SqliteIndex};
-use crate::infra::read_note;
+use crate::domain::{Note, Tag, Topic, find_section, find_sections};
+use crate::index::{IndexBuilder, IndexRepository, IndexedNote, SqliteIndex};
+use crate::infra::{ContentHash, GitRepo, parse_note_from_bytes, read_note};
 
-pub fn handle_show(args: &ShowArgs, notes_dir: &Path) -> Result<()> {
+/// A link in `show` JSON output.
+#[derive(Debug, Serialize)]
+pub struct ShowLink {
+    pub target: String,
+    pub rels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Result of the show command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct ShowResult {
+    pub id: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub created: String,
+    pub modified: String,
+    pub topics: Vec<String>,
+    pub tags: Vec<String>,
+    pub aliases: Vec<String>,
+    pub links: Vec<ShowLink>,
+    pub path: String,
+    /// Heading path of the section shown with `--section`; `body` is then
+    /// just that section.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    pub body: String,
+}
+
+/// Result of the edit command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct EditResult {
+    /// Number of notes opened in the editor.
+    pub opened: usize,
+    /// Notes whose content changed and were re-indexed.
+    pub edited: Vec<NoteListing>,
+}
+
+fn note_listing(note: &IndexedNote, notes_dir: &Path) -> NoteListing {
+    NoteListing {
+        id: note.id().to_string(),
+        title: note.title().to_string(),
+        path: notes_dir.join(note.path()).to_string_lossy().to_string(),
+        note_type: note.note_type().map(String::from),
+        description: None,
+        words: None,
+    }
+}
+
+pub fn handle_show(args: &ShowArgs, notes_dir: &Path, config: &Config) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(note) => {
-            // Read and display the note
-            let file_path = notes_dir.join(note.path());
-            let parsed = read_note(&file_path)
-                .with_context(|| format!("failed to read note: {}", file_path.display()))?;
-
-            // Display frontmatter metadata
-            println!("# {}", parsed.note.title());
-            println!();
+    if args.note.as_deref() == Some(STDIN_NOTES) {
+        if args.history {
+            bail!("--history shows a single note and can't be used with '-'");
+        }
+        return show_stdin_notes(args, &index, notes_dir, config);
+    }
 
-            if let Some(desc) = parsed.note.description() {
-                println!("{}", desc);
-                println!();
-            }
+    let note = require_or_pick_note(&index, args.note.as_deref(), "note", config)?;
+    if args.history {
+        return print_history(&note, notes_dir, args.format, config);
+    }
 
-            // Show metadata
-            println!(
-                "ID: {}  Created: {}  Modified: {}",
-                parsed.note.id().prefix(),
-                parsed.note.created().format("%Y-%m-%d"),
-                parsed.note.modified().format("%Y-%m-%d")
-            );
+    let shown = read_shown(args, &index, &note, notes_dir)?;
+    match args.format {
+        OutputFormat::Human => page(&shown.render(config)?, &config.output)?,
+        OutputFormat::Json => {
+            let result = shown.into_result();
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", shown.path.display()),
+    }
+    Ok(())
+}
 
-            if !parsed.note.topics().is_empty() {
-                let topics: Vec<_> = parsed.note.topics().iter().map(|t| t.to_string()).collect();
-                println!("Topics: {}", topics.join(", "));
+/// Shows each note read from stdin, one after another.
+///
+/// Notes that can't be found or read are reported on stderr and skipped; the
+/// command fails at the end if any were.
+fn show_stdin_notes(
+    args: &ShowArgs,
+    index: &SqliteIndex,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let notes = read_stdin_notes(notes_dir)?;
+    let mut shown = Vec::with_capacity(notes.len());
+    let mut failed = 0;
+    for query in &notes {
+        let read = require_note(index, query, "note", config)
+            .and_then(|note| read_shown(args, index, &note, notes_dir));
+        match read {
+            Ok(note) => shown.push(note),
+            Err(e) => {
+                eprintln!("error: '{}': {:#}", query, e);
+                failed += 1;
             }
+        }
+    }
 
-            if !parsed.note.tags().is_empty() {
-                let tags: Vec<_> = parsed.note.tags().iter().map(|t| t.as_str()).collect();
-                println!("Tags: {}", tags.join(", "));
+    match args.format {
+        OutputFormat::Human => {
+            let rendered = shown
+                .iter()
+                .map(|note| note.render(config))
+                .collect::<Result<Vec<_>>>()?;
+            if !rendered.is_empty() {
+                page(&rendered.join("\n"), &config.output)?;
             }
+        }
+        OutputFormat::Json => {
+            let results: Vec<ShowResult> = shown.into_iter().map(Shown::into_result).collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(results))?);
+        }
+        OutputFormat::Paths => {
+            for note in &shown {
+                println!("{}", note.path.display());
+            }
+        }
+    }
+
+    if failed > 0 {
+        bail!("{} of {} note(s) failed", failed, notes.len());
+    }
+    Ok(())
+}
 
-            println!();
+/// A note read for `show`.
+struct Shown {
+    note: Note,
+    body: String,
+    path: PathBuf,
+    /// Heading path and text of the section asked for with `--section`.
+    section: Option<(String, String)>,
+}
 
-            // Display body
-            if !parsed.body.is_empty() {
-                println!("{}", parsed.body);
+/// Reads the note from its file, the index or a git revision, as `args` ask.
+fn read_shown(
+    args: &ShowArgs,
+    index: &SqliteIndex,
+    note: &IndexedNote,
+    notes_dir: &Path,
+) -> Result<Shown> {
+    let file_path = notes_dir.join(note.path());
+    let (shown, body) = if args.from_index {
+        let stored = index
+            .stored_note(note.id())
+            .with_context(|| "failed to read note from index")?;
+        let Some(stored) = stored else {
+            bail!(
+                "the index holds no body for '{}'; set store_bodies = true under [index] and run `notes index`",
+                note.title()
+            );
+        };
+        stored
+    } else {
+        let parsed = match &args.at {
+            Some(rev) => {
+                let bytes = GitRepo::discover(notes_dir)?
+                    .file_at(note.path(), rev)
+                    .with_context(|| format!("failed to read note at revision '{}'", rev))?;
+                parse_note_from_bytes(bytes, &file_path)
+                    .with_context(|| format!("failed to parse note at revision '{}'", rev))?
             }
+            None => read_note(&file_path)
+                .with_context(|| format!("failed to read note: {}", file_path.display()))?,
+        };
+        (parsed.note, parsed.body)
+    };
 
-            Ok(())
+    let section = match &args.section {
+        Some(heading) => {
+            let sections = find_sections(&body);
+            let Some(section) = find_section(&sections, heading) else {
+                bail!("no section '{}' in '{}'", heading.trim(), shown.title());
+            };
+            Some((
+                section.path.clone(),
+                section.full_text(&body).trim_end().to_string(),
+            ))
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        None => None,
+    };
+
+    Ok(Shown {
+        note: shown,
+        body,
+        path: file_path,
+        section,
+    })
+}
+
+impl Shown {
+    fn into_result(self) -> ShowResult {
+        let note = &self.note;
+        ShowResult {
+            id: note.id().to_string(),
+            title: note.title().to_string(),
+            description: note.description().map(String::from),
+            created: note.created().to_rfc3339(),
+            modified: note.modified().to_rfc3339(),
+            topics: note.topics().iter().map(|t| t.to_string()).collect(),
+            tags: note.tags().iter().map(|t| t.to_string()).collect(),
+            aliases: note.aliases().to_vec(),
+            links: note
+                .links()
+                .iter()
+                .map(|l| ShowLink {
+                    target: l.target().to_string(),
+                    rels: l.rel().iter().map(|r| r.to_string()).collect(),
+                    note: l.context().map(String::from),
+                })
+                .collect(),
+            path: self.path.to_string_lossy().to_string(),
+            section: self.section.as_ref().map(|(path, _)| path.clone()),
+            body: self.section.map_or(self.body, |(_, text)| text),
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+    }
+
+    /// Renders the note (or just its section) for human output.
+    fn render(&self, config: &Config) -> Result<String> {
+        if let Some((_, text)) = &self.section {
+            return Ok(format!("{}\n", text));
         }
+        let shown = &self.note;
+
+        // Display frontmatter metadata
+        let mut out = String::new();
+        writeln!(out, "# {}", shown.title())?;
+        writeln!(out)?;
+
+        if let Some(desc) = shown.description() {
+            writeln!(out, "{}", desc)?;
+            writeln!(out)?;
+        }
+
+        // Show metadata
+        writeln!(
+            out,
+            "ID: {}  Created: {}  Modified: {}",
+            shown.id().prefix(),
+            config.time.date(shown.created()),
+            config.time.date(shown.modified())
+        )?;
+
+        if !shown.topics().is_empty() {
+            let topics: Vec<_> = shown.topics().iter().map(|t| t.to_string()).collect();
+            writeln!(out, "Topics: {}", topics.join(", "))?;
+        }
+
+        if !shown.tags().is_empty() {
+            let color = Painter::new(&config.output).color();
+            let tags = config
+                .tags
+                .render_list(shown.tags().iter().map(|t| t.as_str()), color);
+            writeln!(out, "Tags: {}", tags)?;
+        }
+
+        writeln!(out)?;
+
+        // Display body
+        if !self.body.is_empty() {
+            writeln!(out, "{}", self.body)?;
+        }
+        Ok(out)
     }
 }
 
+/// Lists the commits that changed the note's file, newest first.
+fn print_history(
+    note: &IndexedNote,
+    notes_dir: &Path,
+    format: OutputFormat,
+    config: &Config,
+) -> Result<()> {
+    let history = GitRepo::discover(notes_dir)?
+        .file_history(note.path())
+        .with_context(|| format!("failed to read the history of {}", note.path().display()))?;
+
+    match format {
+        OutputFormat::Json => {
+            let listings: Vec<HistoryListing> = history
+                .into_iter()
+                .map(|commit| HistoryListing {
+                    commit: commit.id,
+                    short_commit: commit.short_id,
+                    date: commit.time.to_rfc3339(),
+                    author: commit.author,
+                    message: commit.summary,
+                    path: commit.path.display().to_string(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for commit in &history {
+                println!("{}", notes_dir.join(&commit.path).display());
+            }
+        }
+        OutputFormat::Human => {
+            if history.is_empty() {
+                println!("No commits for '{}' yet.", note.title());
+                return Ok(());
+            }
+            let painter = Painter::new(&config.output);
+            let mut table = Table::new(vec![
+                Column::left("Commit").role(Role::Id),
+                Column::left("Date").role(Role::Muted),
+                Column::left("Message").max_width(60).shrink(),
+            ]);
+            for commit in &history {
+                let mut message = commit.summary.clone();
+                if commit.path != note.path() {
+                    write!(message, " (as {})", commit.path.display())?;
+                }
+                table.row(vec![
+                    commit.short_id.clone(),
+                    config.time.datetime(commit.time),
+                    message,
+                ]);
+            }
+            let mut out = table.render(&painter);
+            writeln!(out)?;
+            writeln!(
+                out,
+                "{} commit(s) changed '{}'",
+                history.len(),
+                note.title()
+            )?;
+            page(&out, &config.output)?;
+        }
+    }
+    Ok(())
+}
+
 /// Trait for launching an editor (allows mocking in tests).
 pub(crate) trait EditorLauncher {
     fn open(&self, path: &Path) -> Result<()>;
+
+    /// Opens several files in one session. Defaults to opening them one at a time.
+    fn open_all(&self, paths: &[PathBuf]) -> Result<()> {
+        for path in paths {
+            self.open(path)?;
+        }
+        Ok(())
+    }
 }
 
 /// Internal implementation that accepts a generic editor launcher.
 pub(crate) fn handle_edit_impl<E: EditorLauncher>(
     args: &EditArgs,
     notes_dir: &Path,
+    config: &Config,
     editor: &E,
 ) -> Result<()> {
     let db_path = index_db_path(notes_dir);
     let index = SqliteIndex::open(&db_path)
         .with_context(|| format!("failed to open index at {}", db_path.display()))?;
 
-    match resolve_note(&index, &args.note)? {
-        ResolveResult::Unique(note) => {
-            let file_path = notes_dir.join(note.path());
+    if args.all {
+        return edit_all(args, notes_dir, config, &index, editor);
+    }
+    if args.note.as_deref() == Some(STDIN_NOTES) {
+        let refs = read_stdin_notes(notes_dir)?;
+        return edit_listed_notes(args, notes_dir, config, &index, &refs, editor);
+    }
 
-            editor.open(&file_path)?;
-            update_modified_timestamp(&file_path)?;
+    let note = require_or_pick_note(&index, args.note.as_deref(), "note", config)?;
+    let file_path = notes_dir.join(note.path());
 
-            // Update index
-            if let Ok(mut idx) = SqliteIndex::open(&db_path) {
-                let builder = IndexBuilder::new(notes_dir.to_path_buf());
-                let _ = builder.incremental_update(&mut idx);
-            }
+    editor.open(&file_path)?;
+    update_modified_timestamp(&file_path, &config.frontmatter)?;
 
+    // Update index
+    if let Ok(mut idx) = SqliteIndex::open(&db_path) {
+        let builder = IndexBuilder::new(notes_dir.to_path_buf());
+        let _ = builder.incremental_update(&mut idx);
+    }
+
+    match args.format {
+        OutputFormat::Human => {
             println!("Edited: {} [{}]", note.title(), note.id().prefix());
-            Ok(())
         }
-        ResolveResult::Ambiguous(notes) => {
-            print_ambiguous_notes(&args.note, &notes);
-            bail!("ambiguous note identifier");
+        OutputFormat::Json => {
+            let result = EditResult {
+                opened: 1,
+                edited: vec![note_listing(&note, notes_dir)],
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
         }
-        ResolveResult::NotFound => {
-            bail!("note not found: '{}'", args.note);
+        OutputFormat::Paths => {
+            println!("{}", file_path.display());
         }
     }
+    Ok(())
 }
 
-pub fn handle_edit(args: &EditArgs, notes_dir: &Path, config: &Config) -> Result<()> {
-    struct RealEditor<'a>(&'a Config);
-    impl EditorLauncher for RealEditor<'_> {
-        fn open(&self, path: &Path) -> Result<()> {
-            open_in_editor(path, self.0)
+/// Finds the notes selected by the `--all` filters.
+///
+/// Filters combine with AND logic; archived notes are excluded unless
+/// `--include-archived` is given.
+fn select_notes_for_edit(args: &EditArgs, index: &SqliteIndex) -> Result<Vec<IndexedNote>> {
+    if args.topic.is_none() && args.tags.is_empty() && args.query.is_none() {
+        bail!("edit --all requires at least one filter (--topic, --tag, or --query)");
+    }
+
+    let mut notes: Vec<IndexedNote> = match &args.query {
+        Some(query) => index
+            .search(query)
+            .with_context(|| format!("failed to search for '{}'", query))?
+            .into_iter()
+            .map(|r| r.note().clone())
+            .collect(),
+        None => index
+            .list_all()
+            .with_context(|| "failed to list all notes")?,
+    };
+
+    if let Some(topic_arg) = &args.topic {
+        let (topic_str, include_descendants) = parse_topic_filter(topic_arg);
+        let topic =
+            Topic::new(&topic_str).with_context(|| format!("invalid topic: {}", topic_str))?;
+        notes.retain(|n| note_matches_topic(n, &topic, include_descendants));
+    }
+
+    for tag_str in &args.tags {
+        let tag = Tag::new(tag_str).with_context(|| format!("invalid tag: {}", tag_str))?;
+        notes.retain(|n| n.tags().contains(&tag));
+    }
+
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+
+    notes.sort_by_key(|n| n.title().to_lowercase());
+    Ok(notes)
+}
+
+/// Opens every note matching the `--all` filters in one editor session.
+fn edit_all<E: EditorLauncher>(
+    args: &EditArgs,
+    notes_dir: &Path,
+    config: &Config,
+    index: &SqliteIndex,
+    editor: &E,
+) -> Result<()> {
+    let notes = select_notes_for_edit(args, index)?;
+    if notes.is_empty() {
+        if matches!(args.format, OutputFormat::Human) {
+            println!("No notes match the given filters.");
+            return Ok(());
+        }
+        return print_edit_all(args, notes_dir, 0, &[]);
+    }
+    edit_notes(args, notes_dir, config, &notes, editor)
+}
+
+/// Opens the notes `refs` refer to (read from stdin for `-`) in one editor
+/// session, like `--all`.
+///
+/// Notes that can't be found are reported on stderr and left out; the
+/// command fails at the end if any were.
+pub(crate) fn edit_listed_notes<E: EditorLauncher>(
+    args: &EditArgs,
+    notes_dir: &Path,
+    config: &Config,
+    index: &SqliteIndex,
+    refs: &[String],
+    editor: &E,
+) -> Result<()> {
+    let mut notes: Vec<IndexedNote> = Vec::with_capacity(refs.len());
+    let mut failed = 0;
+    for query in refs {
+        match require_note(index, query, "note", config) {
+            Ok(note) if notes.iter().any(|n| n.id() == note.id()) => {}
+            Ok(note) => notes.push(note),
+            Err(e) => {
+                eprintln!("error: '{}': {:#}", query, e);
+                failed += 1;
+            }
+        }
+    }
+
+    if !notes.is_empty() {
+        edit_notes(args, notes_dir, config, &notes, editor)?;
+    }
+    if failed > 0 {
+        bail!("{} of {} note(s) failed", failed, refs.len());
+    }
+    Ok(())
+}
+
+/// Opens `notes` in one editor session, then re-indexes only the files whose
+/// content changed.
+fn edit_notes<E: EditorLauncher>(
+    args: &EditArgs,
+    notes_dir: &Path,
+    config: &Config,
+    notes: &[IndexedNote],
+    editor: &E,
+) -> Result<()> {
+    let mut before = Vec::with_capacity(notes.len());
+    for note in notes {
+        let file_path = notes_dir.join(note.path());
+        let bytes = std::fs::read(&file_path)
+            .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+        before.push(ContentHash::compute(&bytes));
+    }
+
+    let paths: Vec<PathBuf> = notes.iter().map(|n| notes_dir.join(n.path())).collect();
+    editor.open_all(&paths)?;
+
+    let mut changed = Vec::new();
+    for ((note, file_path), hash) in notes.iter().zip(&paths).zip(&before) {
+        // A file removed during the session is dropped from the index below
+        let after = std::fs::read(file_path)
+            .ok()
+            .map(|b| ContentHash::compute(&b));
+        if after.as_ref() == Some(hash) {
+            continue;
+        }
+        if after.is_some() {
+            update_modified_timestamp(file_path, &config.frontmatter)?;
         }
+        changed.push(note);
     }
-    handle_edit_impl(args, notes_dir, &RealEditor(config))
+
+    if !changed.is_empty() {
+        let db_path = index_db_path(notes_dir);
+        if let Ok(mut idx) = SqliteIndex::open(&db_path) {
+            let builder = IndexBuilder::new(notes_dir.to_path_buf());
+            let changed_paths: Vec<PathBuf> =
+                changed.iter().map(|n| n.path().to_path_buf()).collect();
+            let _ = builder.update_paths(&mut idx, &changed_paths);
+        }
+    }
+
+    print_edit_all(args, notes_dir, notes.len(), &changed)
+}
+
+fn print_edit_all(
+    args: &EditArgs,
+    notes_dir: &Path,
+    opened: usize,
+    changed: &[&IndexedNote],
+) -> Result<()> {
+    match args.format {
+        OutputFormat::Human => {
+            println!("Edited {} of {} note(s)", changed.len(), opened);
+            for note in changed {
+                println!("  {} [{}]", note.title(), note.id().prefix());
+            }
+        }
+        OutputFormat::Json => {
+            let result = EditResult {
+                opened,
+                edited: changed.iter().map(|n| note_listing(n, notes_dir)).collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => {
+            for note in changed {
+                println!("{}", notes_dir.join(note.path()).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Launches the configured editor.
+pub(crate) struct RealEditor<'a>(pub &'a Config);
+
+impl EditorLauncher for RealEditor<'_> {
+    fn open(&self, path: &Path) -> Result<()> {
+        open_in_editor(path, self.0)
+    }
+
+    fn open_all(&self, paths: &[PathBuf]) -> Result<()> {
+        open_paths_in_editor(paths, self.0)
+    }
+}
+
+pub fn handle_edit(args: &EditArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    handle_edit_impl(args, notes_dir, config, &RealEditor(config))
 }
@@ -0,0 +1,343 @@
+//! Snippet command handler.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use super::new::{create_new_note, open_in_editor, print_created, update_modified_timestamp};
+use crate::cli::SnippetArgs;
+use crate::cli::config::Config;
+use crate::domain::Note;
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::write_note_with;
+
+/// Tag applied to every snippet note.
+pub const SNIPPET_TAG: &str = "snippet";
+
+/// Maximum length of a title taken from a comment line.
+const MAX_COMMENT_TITLE_LEN: usize = 80;
+
+/// Keyword markers used to guess a snippet's language, with weights.
+///
+/// Languages are listed in tie-break order: when two languages score the
+/// same, the one listed first wins.
+const LANGUAGE_MARKERS: &[(&str, &[(&str, u32)])] = &[
+    (
+        "rust",
+        &[
+            ("fn ", 1),
+            ("let mut ", 3),
+            ("impl ", 2),
+            ("pub fn ", 3),
+            ("use std::", 3),
+            ("#[derive", 3),
+            ("println!", 3),
+            ("&self", 2),
+            ("-> ", 1),
+        ],
+    ),
+    (
+        "python",
+        &[
+            ("def ", 2),
+            ("import ", 1),
+            ("self.", 1),
+            ("elif ", 3),
+            ("print(", 1),
+            ("__init__", 3),
+            ("None", 1),
+            ("    return ", 1),
+            ("):\n", 2),
+        ],
+    ),
+    (
+        "typescript",
+        &[
+            (": string", 3),
+            (": number", 3),
+            ("interface ", 2),
+            ("export type ", 3),
+            (" as const", 3),
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            ("const ", 1),
+            ("function ", 2),
+            ("=> ", 1),
+            ("console.log", 3),
+            ("require(", 2),
+            ("document.", 2),
+        ],
+    ),
+    (
+        "go",
+        &[
+            ("package ", 2),
+            ("func ", 2),
+            (" := ", 2),
+            ("fmt.", 3),
+            ("import (", 3),
+        ],
+    ),
+    (
+        "ruby",
+        &[
+            ("def ", 1),
+            ("\nend", 2),
+            ("puts ", 2),
+            (".each do", 3),
+            ("attr_accessor", 3),
+            ("require '", 2),
+        ],
+    ),
+    (
+        "bash",
+        &[
+            ("echo ", 2),
+            ("\nfi", 3),
+            ("; then", 3),
+            ("$(", 2),
+            ("export ", 1),
+            ("; do", 3),
+        ],
+    ),
+    (
+        "sql",
+        &[
+            ("SELECT ", 2),
+            (" FROM ", 2),
+            (" WHERE ", 2),
+            ("INSERT INTO", 3),
+            ("CREATE TABLE", 3),
+        ],
+    ),
+    (
+        "java",
+        &[
+            ("public class ", 3),
+            ("System.out", 3),
+            ("private ", 1),
+            ("public static void", 3),
+        ],
+    ),
+    (
+        "cpp",
+        &[
+            ("std::", 3),
+            ("#include <iostream>", 3),
+            ("cout", 2),
+            ("template<", 3),
+        ],
+    ),
+    (
+        "c",
+        &[
+            ("#include", 2),
+            ("int main", 2),
+            ("printf(", 2),
+            ("malloc(", 3),
+        ],
+    ),
+    (
+        "css",
+        &[
+            ("color:", 2),
+            ("margin:", 2),
+            ("padding:", 2),
+            ("px;", 2),
+            ("font-", 1),
+        ],
+    ),
+];
+
+/// Guesses the language of a code snippet.
+///
+/// Checks a shebang line and structural cues (JSON, HTML) first, then falls
+/// back to weighted keyword matching. Returns `None` when nothing matches.
+pub fn detect_language(code: &str) -> Option<&'static str> {
+    let trimmed = code.trim_start();
+
+    if let Some(first_line) = trimmed.lines().next()
+        && let Some(interpreter) = first_line.strip_prefix("#!")
+    {
+        for (needle, lang) in [
+            ("python", "python"),
+            ("node", "javascript"),
+            ("ruby", "ruby"),
+            ("bash", "bash"),
+            ("zsh", "bash"),
+            ("/sh", "bash"),
+        ] {
+            if interpreter.contains(needle) {
+                return Some(lang);
+            }
+        }
+    }
+
+    if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        return Some("json");
+    }
+
+    let lower = trimmed.to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        return Some("html");
+    }
+
+    let mut best: Option<(&'static str, u32)> = None;
+    for (lang, markers) in LANGUAGE_MARKERS {
+        let score: u32 = markers
+            .iter()
+            .filter(|(marker, _)| code.contains(marker))
+            .map(|(_, weight)| weight)
+            .sum();
+        if score > 0 && best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((lang, score));
+        }
+    }
+
+    best.map(|(lang, _)| lang)
+}
+
+/// Extracts a title from the first comment line of a snippet.
+///
+/// Skips a leading shebang. Recognizes `//`, `#`, `--`, `;`, `/* */` and
+/// `<!-- -->` comments; `#` is not treated as a comment in C-family code
+/// where it introduces preprocessor directives.
+pub fn snippet_title(code: &str, language: Option<&str>) -> Option<String> {
+    let line = code
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("#!"))?;
+
+    let hash_is_comment = !matches!(language, Some("c") | Some("cpp") | Some("rust"));
+
+    let text = if let Some(rest) = line.strip_prefix("<!--") {
+        rest.trim_end_matches("-->")
+    } else if let Some(rest) = line.strip_prefix("/*") {
+        rest.trim_end_matches("*/").trim_start_matches('*')
+    } else if let Some(rest) = line.strip_prefix("//") {
+        rest.trim_start_matches(['/', '!'])
+    } else if let Some(rest) = line.strip_prefix("--") {
+        rest
+    } else if line.starts_with(';') {
+        line.trim_start_matches(';')
+    } else if hash_is_comment && line.starts_with('#') {
+        line.trim_start_matches('#')
+    } else {
+        return None;
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    Some(text.chars().take(MAX_COMMENT_TITLE_LEN).collect())
+}
+
+/// Wraps code in a fenced block, lengthening the fence if the code contains backticks.
+pub fn fence_code(code: &str, language: Option<&str>) -> String {
+    let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+
+    format!(
+        "{}{}\n{}\n{}\n",
+        fence,
+        language.unwrap_or(""),
+        code.trim_end_matches(['\n', '\r']),
+        fence
+    )
+}
+
+/// Creates a snippet note from the given code and writes it to the notes directory.
+///
+/// Returns the created note and the path it was written to.
+pub(crate) fn create_snippet(
+    args: &SnippetArgs,
+    notes_dir: &Path,
+    code: &str,
+    config: &Config,
+) -> Result<(Note, PathBuf)> {
+    if code.trim().is_empty() {
+        bail!("no snippet content provided on stdin");
+    }
+
+    let language = match &args.lang {
+        Some(lang) => Some(lang.to_lowercase()),
+        None => detect_language(code).map(String::from),
+    };
+
+    let title = args
+        .title
+        .clone()
+        .or_else(|| snippet_title(code, language.as_deref()))
+        .unwrap_or_else(|| {
+            let kind = language.as_deref().unwrap_or("code");
+            format!("{} snippet {}", kind, Utc::now().format("%Y-%m-%d %H:%M"))
+        });
+
+    let mut tags = vec![SNIPPET_TAG.to_string()];
+    if let Some(lang) = &language {
+        tags.push(lang.clone());
+    }
+    for tag in &args.tags {
+        if !tags.contains(tag) {
+            tags.push(tag.clone());
+        }
+    }
+
+    let result = create_new_note(
+        &title,
+        args.desc.as_deref(),
+        &args.topics,
+        &tags,
+        &config.slug,
+    )?;
+    let note = config.frontmatter.apply_defaults(result.note);
+    let file_path = notes_dir.join(&result.filename);
+    let body = fence_code(code, language.as_deref());
+
+    write_note_with(&file_path, &note, &body, &config.frontmatter)
+        .with_context(|| format!("failed to write note to {}", file_path.display()))?;
+
+    Ok((note, file_path))
+}
+
+pub fn handle_snippet(args: &SnippetArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let mut code = String::new();
+    std::io::stdin()
+        .read_to_string(&mut code)
+        .with_context(|| "failed to read snippet from stdin")?;
+
+    let (note, file_path) = create_snippet(args, notes_dir, &code, config)?;
+
+    let db_path = index_db_path(notes_dir);
+    if let Ok(mut index) = SqliteIndex::open(&db_path) {
+        let builder = IndexBuilder::new(notes_dir.to_path_buf());
+        let _ = builder.incremental_update(&mut index);
+    }
+
+    print_created(&note, &file_path, args.format)?;
+
+    if args.edit {
+        open_in_editor(&file_path, config)?;
+        update_modified_timestamp(&file_path, &config.frontmatter)?;
+
+        if let Ok(mut index) = SqliteIndex::open(&db_path) {
+            let builder = IndexBuilder::new(notes_dir.to_path_buf());
+            let _ = builder.incremental_update(&mut index);
+        }
+    }
+
+    Ok(())
+}
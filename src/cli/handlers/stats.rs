@@ -0,0 +1,429 @@
+//! Stats command handler: vault health score and trend, and tag/topic usage.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Duration, Local, Utc};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::index_db_path;
+use super::stats_overview::handle_stats_overview;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, HealthSampleListing, Output, OutputFormat, Painter, Role, Table, UsageListing,
+    UsageStatsListing, VaultStatsListing,
+};
+use crate::cli::{StatsArgs, StatsCommand, StatsUsageArgs};
+use crate::domain::{HealthReport, compute_health};
+use crate::index::{HealthSample, IndexRepository, SqliteIndex};
+use crate::infra::{read_note, scan_notes_directory_with};
+
+fn sample_listing(sample: &HealthSample) -> HealthSampleListing {
+    let r = &sample.report;
+    HealthSampleListing {
+        taken_at: sample.taken_at.to_rfc3339(),
+        score: r.score(),
+        notes: r.notes,
+        orphans: r.orphans,
+        links: r.links,
+        broken_links: r.broken_links,
+        untagged: r.untagged,
+        stale: r.stale,
+    }
+}
+
+/// Describes the change in score between the first and last sample.
+pub(crate) fn trend_summary(samples: &[HealthSample], config: &Config) -> Option<String> {
+    if samples.len() < 2 {
+        return None;
+    }
+    let (first, last) = (samples.first()?, samples.last()?);
+
+    let delta = last.report.score() - first.report.score();
+    let direction = if delta > 0.0 {
+        "improving"
+    } else if delta < 0.0 {
+        "declining"
+    } else {
+        "steady"
+    };
+
+    Some(format!(
+        "{:+.1} since {} ({})",
+        delta,
+        config.time.date(first.taken_at),
+        direction
+    ))
+}
+
+fn percent(ratio: f64) -> String {
+    format!("{:.1}%", ratio * 100.0)
+}
+
+fn print_report(report: &HealthReport, stale_days: u32, painter: &Painter) {
+    println!(
+        "Vault health: {:.1}/100 ({} notes)",
+        report.score(),
+        report.notes
+    );
+    println!();
+
+    let rows = [
+        (
+            "Orphans (no topic)".to_string(),
+            report.orphans,
+            percent(report.orphan_ratio()),
+        ),
+        (
+            "Broken links".to_string(),
+            report.broken_links,
+            format!(
+                "{} of {} links",
+                percent(report.broken_link_ratio()),
+                report.links
+            ),
+        ),
+        (
+            "Untagged".to_string(),
+            report.untagged,
+            percent(report.untagged_ratio()),
+        ),
+        (
+            format!("Stale (>{} days)", stale_days),
+            report.stale,
+            percent(report.stale_ratio()),
+        ),
+    ];
+    let mut table = Table::new(vec![
+        Column::left(""),
+        Column::right(""),
+        Column::left("").role(Role::Muted),
+    ])
+    .indent(2)
+    .without_header();
+    for (label, count, detail) in rows {
+        table.row([label, count.to_string(), detail]);
+    }
+    table.print(painter);
+}
+
+fn print_trend(samples: &[HealthSample], config: &Config, painter: &Painter) {
+    println!();
+    if samples.is_empty() {
+        println!("No health samples recorded.");
+        return;
+    }
+
+    println!("Trend:");
+    let mut table = Table::new(vec![
+        Column::left("Date").role(Role::Muted),
+        Column::right("Score"),
+        Column::right("Notes"),
+        Column::right("Orphans"),
+        Column::right("Broken"),
+        Column::right("Untagged"),
+        Column::right("Stale"),
+    ])
+    .indent(2)
+    .without_rule();
+    for sample in samples {
+        let r = &sample.report;
+        table.row([
+            config.time.date(sample.taken_at),
+            format!("{:.1}", r.score()),
+            r.notes.to_string(),
+            r.orphans.to_string(),
+            r.broken_links.to_string(),
+            r.untagged.to_string(),
+            r.stale.to_string(),
+        ]);
+    }
+    table.print(painter);
+
+    if let Some(summary) = trend_summary(samples, config) {
+        println!();
+        println!("  {}", summary);
+    }
+}
+
+/// A calendar month as (year, month).
+pub(crate) type Month = (i32, u32);
+
+pub(crate) fn month_of(dt: DateTime<Utc>, utc: bool) -> Month {
+    if utc {
+        (dt.year(), dt.month())
+    } else {
+        let local = dt.with_timezone(&Local);
+        (local.year(), local.month())
+    }
+}
+
+fn next_month((year, month): Month) -> Month {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+pub(crate) fn format_month((year, month): Month) -> String {
+    format!("{:04}-{:02}", year, month)
+}
+
+/// Notes per month for one tag or topic.
+#[derive(Debug, PartialEq)]
+pub(crate) struct UsageSeries {
+    pub name: String,
+    /// Counts aligned with [`UsageTable::months`]
+    pub counts: Vec<usize>,
+}
+
+impl UsageSeries {
+    fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+/// Tag or topic usage broken down by month.
+#[derive(Debug, PartialEq)]
+pub(crate) struct UsageTable {
+    /// Every month from the earliest to the latest note, with no gaps
+    pub months: Vec<Month>,
+    /// Most used first; ties are ordered by name
+    pub series: Vec<UsageSeries>,
+}
+
+impl UsageTable {
+    fn first_and_last(&self, series: &UsageSeries) -> (Month, Month) {
+        let first = series.counts.iter().position(|&c| c > 0).unwrap_or(0);
+        let last = series.counts.iter().rposition(|&c| c > 0).unwrap_or(0);
+        (self.months[first], self.months[last])
+    }
+}
+
+/// Counts, per month, the notes carrying each name.
+///
+/// Each entry is a note's creation month and its tags or topics.
+pub(crate) fn usage_by_month(entries: &[(Month, Vec<String>)]) -> UsageTable {
+    let mut months = Vec::new();
+    if let (Some(first), Some(last)) = (
+        entries.iter().map(|(m, _)| *m).min(),
+        entries.iter().map(|(m, _)| *m).max(),
+    ) {
+        let mut month = first;
+        while month <= last {
+            months.push(month);
+            month = next_month(month);
+        }
+    }
+
+    let mut counts: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (month, names) in entries {
+        let slot = months.binary_search(month).expect("month is in range");
+        for name in names {
+            counts.entry(name).or_insert_with(|| vec![0; months.len()])[slot] += 1;
+        }
+    }
+
+    let mut series: Vec<UsageSeries> = counts
+        .into_iter()
+        .map(|(name, counts)| UsageSeries {
+            name: name.to_string(),
+            counts,
+        })
+        .collect();
+    // Stable sort keeps the BTreeMap's name order for ties
+    series.sort_by_key(|s| std::cmp::Reverse(s.total()));
+
+    UsageTable { months, series }
+}
+
+fn usage_listing(table: &UsageTable, monthly: bool) -> UsageStatsListing {
+    UsageStatsListing {
+        months: monthly.then(|| table.months.iter().copied().map(format_month).collect()),
+        items: table
+            .series
+            .iter()
+            .map(|s| {
+                let (first, last) = table.first_and_last(s);
+                UsageListing {
+                    name: s.name.clone(),
+                    notes: s.total(),
+                    first_month: format_month(first),
+                    last_month: format_month(last),
+                    monthly: monthly.then(|| s.counts.clone()),
+                }
+            })
+            .collect(),
+    }
+}
+
+fn print_usage_totals(table: &UsageTable, label: &str, painter: &Painter) {
+    let mut out = Table::new(vec![
+        Column::left(label),
+        Column::right("Notes"),
+        Column::left("First").role(Role::Muted),
+        Column::left("Last").role(Role::Muted),
+    ])
+    .without_rule();
+    for s in &table.series {
+        let (first, last) = table.first_and_last(s);
+        out.row([
+            s.name.clone(),
+            s.total().to_string(),
+            format_month(first),
+            format_month(last),
+        ]);
+    }
+    out.print(painter);
+}
+
+fn print_usage_monthly(table: &UsageTable, painter: &Painter) {
+    let mut columns = vec![Column::left("Month").role(Role::Muted)];
+    columns.extend(table.series.iter().map(|s| Column::right(s.name.clone())));
+
+    let mut out = Table::new(columns).without_rule();
+    for (i, month) in table.months.iter().enumerate() {
+        let mut row = vec![format_month(*month)];
+        row.extend(table.series.iter().map(|s| s.counts[i].to_string()));
+        out.row(row);
+    }
+    out.print(painter);
+}
+
+fn handle_usage_stats(
+    args: &StatsUsageArgs,
+    tags: bool,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let notes = index.list_all().with_context(|| "failed to list notes")?;
+
+    let (label, plural) = if tags {
+        ("Tag", "tags")
+    } else {
+        ("Topic", "topics")
+    };
+    let entries: Vec<(Month, Vec<String>)> = notes
+        .iter()
+        .map(|note| {
+            let names = if tags {
+                note.tags().iter().map(|t| t.to_string()).collect()
+            } else {
+                note.topics().iter().map(|t| t.to_string()).collect()
+            };
+            (month_of(note.created(), config.time.utc), names)
+        })
+        .collect();
+
+    let mut table = usage_by_month(&entries);
+    let total = table.series.len();
+    if args.top > 0 {
+        table.series.truncate(args.top);
+    }
+
+    match args.format {
+        OutputFormat::Json => {
+            let listing = usage_listing(&table, args.monthly);
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            if table.series.is_empty() {
+                println!("No {} found.", plural);
+                return Ok(());
+            }
+            let painter = Painter::new(&config.output);
+            if args.monthly {
+                print_usage_monthly(&table, &painter);
+            } else {
+                print_usage_totals(&table, label, &painter);
+            }
+            if table.series.len() < total {
+                println!();
+                println!(
+                    "Showing the {} most used of {} {} (use --top 0 for all)",
+                    table.series.len(),
+                    total,
+                    plural
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_stats(args: &StatsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    match &args.command {
+        Some(StatsCommand::Tags(usage)) => {
+            return handle_usage_stats(usage, true, notes_dir, config);
+        }
+        Some(StatsCommand::Topics(usage)) => {
+            return handle_usage_stats(usage, false, notes_dir, config);
+        }
+        Some(StatsCommand::Overview(overview)) => {
+            return handle_stats_overview(overview, notes_dir, config);
+        }
+        None => {}
+    }
+
+    // 1. Load notes from disk; unparseable files are left to `check`
+    let notes: Vec<_> = scan_notes_directory_with(notes_dir, &config.scan)?
+        .filter_map(|path| read_note(&notes_dir.join(path)).ok())
+        .map(|parsed| parsed.note)
+        .collect();
+    let note_refs: Vec<_> = notes.iter().collect();
+
+    // 2. Compute health and record today's sample
+    let now = Utc::now();
+    let report = compute_health(&note_refs, now, Duration::days(args.stale_days.into()));
+
+    let db_path = index_db_path(notes_dir);
+    let mut index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    index
+        .record_health_sample(&HealthSample {
+            taken_at: now,
+            report,
+        })
+        .with_context(|| "failed to record health sample")?;
+
+    let samples = if args.trend {
+        index
+            .health_samples(args.limit)
+            .with_context(|| "failed to load health samples")?
+    } else {
+        Vec::new()
+    };
+
+    // 3. Display results
+    match args.format {
+        OutputFormat::Json => {
+            let listing = VaultStatsListing {
+                score: report.score(),
+                notes: report.notes,
+                orphans: report.orphans,
+                links: report.links,
+                broken_links: report.broken_links,
+                untagged: report.untagged,
+                stale: report.stale,
+                stale_days: args.stale_days,
+                trend: args
+                    .trend
+                    .then(|| samples.iter().map(sample_listing).collect()),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            let painter = Painter::new(&config.output);
+            print_report(&report, args.stale_days, &painter);
+            if args.trend {
+                print_trend(&samples, config, &painter);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,318 @@
+//! Stats overview handler: analytics across the whole vault.
+
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::index_db_path;
+use super::resolve::note_label;
+use super::stats::{Month, month_of, usage_by_month};
+use super::topic_stats::{backlink_sources, month_listings, ranked_listing};
+use crate::cli::StatsOverviewArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, Output, OutputFormat, Painter, RankedNoteListing, RelListing, Role, Table, TagListing,
+    TopicListing, VaultOverviewListing, page,
+};
+use crate::domain::{Link, NoteId};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// Statistics on every note in the vault.
+#[derive(Debug)]
+pub(crate) struct VaultOverview<'a> {
+    pub notes: usize,
+    /// Body words across all notes
+    pub words: usize,
+    /// Topics with their note counts, most used first; ties by name
+    pub topics: Vec<(String, usize)>,
+    /// Tags with their note counts, most used first; ties by name
+    pub tags: Vec<(String, usize)>,
+    /// Notes without a topic
+    pub unfiled: usize,
+    pub untagged: usize,
+    pub links: usize,
+    /// Links per rel, most used first; ties by name
+    pub rels: Vec<(String, usize)>,
+    /// Notes without links to or from other notes
+    pub orphans: usize,
+    /// Every month from the first to the last note, with notes created in each
+    pub months: Vec<(Month, usize)>,
+    /// Notes with links from other notes, most linked first
+    pub most_linked: Vec<(&'a IndexedNote, usize)>,
+}
+
+impl VaultOverview<'_> {
+    pub fn average_words(&self) -> f64 {
+        if self.notes == 0 {
+            0.0
+        } else {
+            self.words as f64 / self.notes as f64
+        }
+    }
+}
+
+/// Counts of each name, most common first; ties by name.
+fn ranked_counts(names: impl Iterator<Item = String>) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for name in names {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    // Stable sort keeps the BTreeMap's name order for ties
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// Builds the overview from every indexed note, its links by source note
+/// and its body word counts.
+pub(crate) fn vault_overview<'a>(
+    notes: &'a [IndexedNote],
+    links: &HashMap<NoteId, Vec<Link>>,
+    words: &HashMap<NoteId, usize>,
+    utc: bool,
+) -> VaultOverview<'a> {
+    let backlinks = backlink_sources(links);
+
+    let entries: Vec<(Month, Vec<String>)> = notes
+        .iter()
+        .map(|n| (month_of(n.created(), utc), vec![String::new()]))
+        .collect();
+    let usage = usage_by_month(&entries);
+    let months = match usage.series.first() {
+        Some(series) => usage
+            .months
+            .iter()
+            .copied()
+            .zip(series.counts.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let topics = ranked_counts(
+        notes
+            .iter()
+            .flat_map(|n| n.topics().iter().map(|t| t.to_string())),
+    );
+    let tags = ranked_counts(
+        notes
+            .iter()
+            .flat_map(|n| n.tags().iter().map(|t| t.to_string())),
+    );
+    let rels = ranked_counts(
+        links
+            .values()
+            .flatten()
+            .flat_map(|l| l.rel().iter().map(|r| r.to_string())),
+    );
+
+    let mut most_linked: Vec<(&IndexedNote, usize)> = notes
+        .iter()
+        .map(|n| (n, backlinks.get(n.id()).map_or(0, HashSet::len)))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    most_linked.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.title().cmp(b.title())));
+
+    let orphans = notes
+        .iter()
+        .filter(|n| {
+            !backlinks.contains_key(n.id())
+                && links
+                    .get(n.id())
+                    .is_none_or(|l| l.iter().all(|link| link.target() == n.id()))
+        })
+        .count();
+
+    VaultOverview {
+        notes: notes.len(),
+        words: notes.iter().filter_map(|n| words.get(n.id())).sum(),
+        topics,
+        tags,
+        unfiled: notes.iter().filter(|n| n.topics().is_empty()).count(),
+        untagged: notes.iter().filter(|n| n.tags().is_empty()).count(),
+        links: links.values().map(Vec::len).sum(),
+        rels,
+        orphans,
+        months,
+        most_linked,
+    }
+}
+
+fn render_overview(
+    overview: &VaultOverview,
+    args: &StatsOverviewArgs,
+    config: &Config,
+) -> Result<String> {
+    let painter = Painter::new(&config.output);
+    let mut out = String::new();
+
+    let rows = [
+        ("Notes", overview.notes.to_string()),
+        (
+            "Words",
+            format!(
+                "{} ({:.0} per note)",
+                overview.words,
+                overview.average_words()
+            ),
+        ),
+        (
+            "Topics",
+            format!(
+                "{} ({} notes without one)",
+                overview.topics.len(),
+                overview.unfiled
+            ),
+        ),
+        (
+            "Tags",
+            format!(
+                "{} ({} notes without one)",
+                overview.tags.len(),
+                overview.untagged
+            ),
+        ),
+        ("Links", overview.links.to_string()),
+        ("Orphans (no links)", overview.orphans.to_string()),
+    ];
+    let mut table = Table::new(vec![Column::left(""), Column::left("")]).without_header();
+    for (label, value) in rows {
+        table.row([label.to_string(), value]);
+    }
+    out.push_str(&table.render(&painter));
+
+    // Every rel is listed; topics and tags are cut to --top
+    for (heading, counts, limit) in [
+        ("Top topics:", &overview.topics, args.top),
+        ("Top tags:", &overview.tags, args.top),
+        ("Links per rel:", &overview.rels, overview.rels.len()),
+    ] {
+        if counts.is_empty() {
+            continue;
+        }
+        writeln!(out)?;
+        writeln!(out, "{}", heading)?;
+        let mut table = Table::new(vec![Column::left(""), Column::right("")])
+            .indent(2)
+            .without_header();
+        for (name, count) in counts.iter().take(limit) {
+            table.row([name.clone(), count.to_string()]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+
+    if !overview.months.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Notes per month:")?;
+        let mut table = Table::new(vec![
+            Column::left("Month").role(Role::Muted),
+            Column::right("New"),
+            Column::right("Total"),
+        ])
+        .indent(2)
+        .without_rule();
+        for month in month_listings(&overview.months, args.months) {
+            table.row([
+                month.month,
+                month.created.to_string(),
+                month.total.to_string(),
+            ]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+
+    if !overview.most_linked.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Most linked:")?;
+        let mut table = Table::new(vec![
+            Column::left("").role(Role::Id),
+            Column::left("").max_width(50).shrink(),
+            Column::right(""),
+        ])
+        .indent(2)
+        .without_header();
+        for (note, count) in overview.most_linked.iter().take(args.top) {
+            table.row([
+                note_label(note, config.note_refs, &config.slug),
+                note.title().to_string(),
+                format!("{} backlink(s)", count),
+            ]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+    Ok(out)
+}
+
+pub(super) fn handle_stats_overview(
+    args: &StatsOverviewArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let notes = index.list_all().with_context(|| "failed to list notes")?;
+    let links = index.all_links().with_context(|| "failed to query links")?;
+    let words = index
+        .word_counts()
+        .with_context(|| "failed to load word counts")?;
+
+    let overview = vault_overview(&notes, &links, &words, config.time.utc);
+
+    match args.format {
+        OutputFormat::Json => {
+            let listing = VaultOverviewListing {
+                notes: overview.notes,
+                words: overview.words,
+                average_words: overview.average_words(),
+                topics: overview.topics.len(),
+                tags: overview.tags.len(),
+                unfiled: overview.unfiled,
+                untagged: overview.untagged,
+                links: overview.links,
+                orphans: overview.orphans,
+                top_topics: overview
+                    .topics
+                    .iter()
+                    .take(args.top)
+                    .map(|(path, count)| TopicListing {
+                        path: path.clone(),
+                        count: Some(*count),
+                    })
+                    .collect(),
+                top_tags: overview
+                    .tags
+                    .iter()
+                    .take(args.top)
+                    .map(|(name, count)| TagListing {
+                        name: name.clone(),
+                        count: Some(*count),
+                    })
+                    .collect(),
+                rels: overview
+                    .rels
+                    .iter()
+                    .map(|(name, count)| RelListing {
+                        name: name.clone(),
+                        count: Some(*count),
+                    })
+                    .collect(),
+                months: month_listings(&overview.months, args.months),
+                most_linked: overview
+                    .most_linked
+                    .iter()
+                    .take(args.top)
+                    .map(|(note, count)| RankedNoteListing {
+                        backlinks: Some(*count),
+                        ..ranked_listing(note)
+                    })
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            page(&render_overview(&overview, args, config)?, &config.output)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,300 @@
+//! Handler for `vaults sync-config`: sharing a vault's conventions.
+//!
+//! A profile bundle is a TOML file holding the config sections that define
+//! how a vault is kept (note types, rels, check rules, topic rules and tag
+//! styles) plus the contents of the type templates, so a team can agree on
+//! conventions once and import them on every member's machine. Everything
+//! else in the config, such as vault paths and display settings, stays
+//! personal.
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::path::{Component, Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::config::validate_config;
+use crate::cli::SyncConfigArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::Severity;
+
+/// Config sections that make up a profile.
+pub const PROFILE_SECTIONS: [&str; 5] = ["types", "rels", "check", "topics", "tags"];
+
+/// Bundle table mapping template paths, relative to the notes directory,
+/// to their contents.
+const TEMPLATES_KEY: &str = "templates";
+
+/// What an import does to a template file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateStatus {
+    /// The file doesn't exist yet, or `--force` replaces it
+    Written,
+    /// The file already has the bundle's contents
+    Unchanged,
+    /// The file has other contents and is left alone
+    Skipped,
+}
+
+/// A profile bundle taken apart for importing.
+#[derive(Debug)]
+pub(crate) struct ProfileImport {
+    /// The config file contents with the bundle's sections in place
+    pub config: String,
+    /// The sections the bundle replaces
+    pub sections: Vec<String>,
+    /// Template paths, relative to the notes directory, and contents
+    pub templates: Vec<(PathBuf, String)>,
+}
+
+/// A template in `sync-config --import` JSON output.
+#[derive(Debug, Serialize)]
+pub struct TemplateListing {
+    pub path: String,
+    pub status: TemplateStatus,
+}
+
+/// Result of `sync-config --export` to a file for JSON output.
+#[derive(Debug, Serialize)]
+pub struct ProfileExportResult {
+    pub path: String,
+}
+
+/// Result of `sync-config --import` for JSON output.
+#[derive(Debug, Serialize)]
+pub struct SyncConfigResult {
+    pub config: String,
+    pub sections: Vec<String>,
+    pub templates: Vec<TemplateListing>,
+    pub dry_run: bool,
+}
+
+/// Builds a profile bundle from config file contents, reading the type
+/// templates from the notes directory.
+pub(crate) fn export_profile(config: &str, notes_dir: &Path) -> Result<String> {
+    let document: DocumentMut = config.parse().with_context(|| "failed to parse config")?;
+
+    let mut bundle = DocumentMut::new();
+    for section in PROFILE_SECTIONS {
+        if let Some(item) = document.get(section) {
+            bundle.insert(section, item.clone());
+        }
+    }
+    let parsed: Config =
+        toml::from_str(&bundle.to_string()).with_context(|| "failed to parse config")?;
+
+    // Only types the config defines have templates; the built-ins have none
+    let mut templates = Table::new();
+    if document.contains_key("types") {
+        for name in parsed.types.names() {
+            let Some(template) = parsed.types.get(name).and_then(|t| t.template.as_ref()) else {
+                continue;
+            };
+            let key = template.to_string_lossy().to_string();
+            if templates.contains_key(&key) {
+                continue;
+            }
+            let path = notes_dir.join(template);
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read template: {}", path.display()))?;
+            templates.insert(&key, toml_edit::value(contents));
+        }
+    }
+    if !templates.is_empty() {
+        bundle.insert(TEMPLATES_KEY, Item::Table(templates));
+    }
+
+    Ok(bundle.to_string())
+}
+
+/// Replaces the profile sections of config file contents with a bundle's.
+///
+/// Sections the bundle doesn't have are left as they are. Fails if the
+/// bundle has anything but profile sections and templates, if its settings
+/// are invalid, or if a template path leaves the notes directory.
+pub(crate) fn import_profile(config: &str, bundle: &str) -> Result<ProfileImport> {
+    let mut bundle: DocumentMut = bundle
+        .parse()
+        .with_context(|| "failed to parse profile bundle")?;
+    let mut document: DocumentMut = config.parse().with_context(|| "failed to parse config")?;
+
+    let mut templates = Vec::new();
+    if let Some(item) = bundle.remove(TEMPLATES_KEY) {
+        let Some(table) = item.as_table_like() else {
+            bail!("'{}' in the profile bundle must be a table", TEMPLATES_KEY);
+        };
+        for (key, value) in table.iter() {
+            let Some(contents) = value.as_str() else {
+                bail!("template '{}' in the profile bundle is not a string", key);
+            };
+            let path = PathBuf::from(key);
+            if !path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                bail!(
+                    "template path '{}' must stay inside the notes directory",
+                    key
+                );
+            }
+            templates.push((path, contents.to_string()));
+        }
+    }
+
+    let sections: Vec<String> = bundle.iter().map(|(key, _)| key.to_string()).collect();
+    if let Some(other) = sections
+        .iter()
+        .find(|s| !PROFILE_SECTIONS.contains(&s.as_str()))
+    {
+        bail!(
+            "'{}' is not a profile section (expected {})",
+            other,
+            PROFILE_SECTIONS.join(", ")
+        );
+    }
+    if let Some(issue) = validate_config(&bundle.to_string())
+        .into_iter()
+        .find(|issue| issue.severity == Severity::Error)
+    {
+        bail!("invalid profile bundle: {}", issue);
+    }
+
+    for (key, item) in bundle.iter() {
+        document.insert(key, item.clone());
+    }
+
+    Ok(ProfileImport {
+        config: document.to_string(),
+        sections,
+        templates,
+    })
+}
+
+/// Works out what importing a template does to the file at `path`.
+fn template_status(path: &Path, contents: &str, force: bool) -> TemplateStatus {
+    match std::fs::read_to_string(path) {
+        Ok(existing) if existing == contents => TemplateStatus::Unchanged,
+        Ok(_) if !force => TemplateStatus::Skipped,
+        _ => TemplateStatus::Written,
+    }
+}
+
+pub fn handle_sync_config(args: &SyncConfigArgs, notes_dir: &Path) -> Result<()> {
+    let config_path = args.config.clone().unwrap_or_else(Config::config_path);
+    let config = if config_path.exists() {
+        std::fs::read_to_string(&config_path)
+            .with_context(|| format!("failed to read config file: {}", config_path.display()))?
+    } else {
+        String::new()
+    };
+
+    if let Some(export) = &args.export {
+        let bundle = export_profile(&config, notes_dir)?;
+        if export.as_os_str() == "-" {
+            print!("{}", bundle);
+        } else {
+            std::fs::write(export, &bundle)
+                .with_context(|| format!("failed to write {}", export.display()))?;
+            match args.format {
+                OutputFormat::Json => {
+                    let result = ProfileExportResult {
+                        path: export.display().to_string(),
+                    };
+                    println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+                }
+                OutputFormat::Human => println!("Exported profile to {}", export.display()),
+                OutputFormat::Paths => println!("{}", export.display()),
+            }
+        }
+        return Ok(());
+    }
+
+    let Some(import) = &args.import else {
+        bail!("either --export or --import is required");
+    };
+    let bundle = std::fs::read_to_string(import)
+        .with_context(|| format!("failed to read profile bundle: {}", import.display()))?;
+    let profile = import_profile(&config, &bundle)
+        .with_context(|| format!("failed to import {}", import.display()))?;
+
+    let mut templates = Vec::with_capacity(profile.templates.len());
+    for (path, contents) in &profile.templates {
+        let full_path = notes_dir.join(path);
+        let status = template_status(&full_path, contents, args.force);
+        if status == TemplateStatus::Written && !args.dry_run {
+            if let Some(parent) = full_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&full_path, contents)
+                .with_context(|| format!("failed to write {}", full_path.display()))?;
+        }
+        templates.push((path, status));
+    }
+    if !args.dry_run && profile.config != config {
+        if let Some(parent) = config_path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create config directory: {}", parent.display())
+            })?;
+        }
+        std::fs::write(&config_path, &profile.config)
+            .with_context(|| format!("failed to write config file: {}", config_path.display()))?;
+    }
+
+    match args.format {
+        OutputFormat::Json => {
+            let result = SyncConfigResult {
+                config: config_path.display().to_string(),
+                sections: profile.sections.clone(),
+                templates: templates
+                    .iter()
+                    .map(|(path, status)| TemplateListing {
+                        path: path.display().to_string(),
+                        status: *status,
+                    })
+                    .collect(),
+                dry_run: args.dry_run,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            let verb = if args.dry_run {
+                "Would update"
+            } else {
+                "Updated"
+            };
+            if profile.sections.is_empty() {
+                println!("No config sections in the profile.");
+            } else {
+                let sections: Vec<String> = profile
+                    .sections
+                    .iter()
+                    .map(|s| format!("[{}]", s))
+                    .collect();
+                println!(
+                    "{} {} in {}",
+                    verb,
+                    sections.join(", "),
+                    config_path.display()
+                );
+            }
+            for (path, status) in &templates {
+                match status {
+                    TemplateStatus::Written if args.dry_run => {
+                        println!("Would write template {}", path.display())
+                    }
+                    TemplateStatus::Written => println!("Wrote template {}", path.display()),
+                    TemplateStatus::Unchanged => {
+                        println!("Template {} is up to date", path.display())
+                    }
+                    TemplateStatus::Skipped => println!(
+                        "Skipped template {} (it differs; use --force to replace it)",
+                        path.display()
+                    ),
+                }
+            }
+        }
+    }
+    Ok(())
+}
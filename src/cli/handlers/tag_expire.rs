@@ -0,0 +1,217 @@
+//! Tags expire handler: finding expired tags and removing or replacing them.
+
+use anyhow::{Context, Result, bail};
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use crate::cli::TagExpireArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{Note, Tag, expired_tags};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{Journal, read_note, scan_notes_directory_with, serialize_with};
+
+/// Parses the `[tags.expired]` replacements, by tag name.
+pub(crate) fn expiry_replacements(
+    configured: &BTreeMap<String, String>,
+) -> Result<BTreeMap<String, Tag>> {
+    configured
+        .iter()
+        .map(|(name, replacement)| {
+            let tag = Tag::new(replacement)
+                .with_context(|| format!("invalid [tags.expired] entry '{}'", name))?;
+            Ok((name.clone(), tag))
+        })
+        .collect()
+}
+
+/// Returns `tags` with each of `expired` removed, or replaced by the tag
+/// configured for its name (pure function, no I/O).
+///
+/// Replacements the note already has are not added twice.
+pub(crate) fn expire_tags(
+    tags: &[Tag],
+    expired: &[&Tag],
+    replacements: &BTreeMap<String, Tag>,
+) -> Vec<Tag> {
+    let mut result: Vec<Tag> = Vec::new();
+    for tag in tags {
+        let tag = if expired.contains(&tag) {
+            match replacements.get(tag.name()) {
+                Some(replacement) => replacement.clone(),
+                None => continue,
+            }
+        } else {
+            tag.clone()
+        };
+        if !result.contains(&tag) {
+            result.push(tag);
+        }
+    }
+    result
+}
+
+/// An expired tag, for JSON output.
+#[derive(Debug, Serialize)]
+struct ExpiredTagListing {
+    tag: String,
+    expired: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replacement: Option<String>,
+}
+
+/// A note with expired tags, for JSON output.
+#[derive(Debug, Serialize)]
+struct ExpiredNoteListing {
+    id: String,
+    title: String,
+    path: String,
+    expired: Vec<ExpiredTagListing>,
+}
+
+/// A note with expired tags and the note they leave.
+struct ExpiredNote {
+    path: PathBuf,
+    note: Note,
+    body: String,
+    expired: Vec<(Tag, NaiveDate)>,
+    updated: Note,
+}
+
+pub(super) fn handle_tag_expire(
+    args: &TagExpireArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let replacements = expiry_replacements(&config.tags.expired)?;
+    let now = Utc::now();
+    let today = config.time.today(now);
+
+    let mut paths: Vec<PathBuf> = scan_notes_directory_with(notes_dir, &config.scan)?.collect();
+    paths.sort();
+
+    let mut found: Vec<ExpiredNote> = Vec::new();
+    for path in paths {
+        let parsed = match read_note(&notes_dir.join(&path)) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("warning: skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let note = parsed.note;
+        let expired = expired_tags(&note, &config.tags.ttl, today);
+        if expired.is_empty() {
+            continue;
+        }
+        let tags: Vec<&Tag> = expired.iter().map(|(tag, _)| *tag).collect();
+        let updated = Note::builder(note.id().clone(), note.title(), note.created(), now)
+            .description(note.description().map(String::from))
+            .topics(note.topics().to_vec())
+            .aliases(note.aliases().to_vec())
+            .tags(expire_tags(note.tags(), &tags, &replacements))
+            .links(note.links().to_vec())
+            .extra(note.extra().clone())
+            .build()?;
+        let expired = expired
+            .into_iter()
+            .map(|(tag, date)| (tag.clone(), date))
+            .collect();
+        found.push(ExpiredNote {
+            path,
+            note,
+            body: parsed.body,
+            expired,
+            updated,
+        });
+    }
+
+    if args.apply && !found.is_empty() {
+        let mut journal = Journal::new("tags-expire", now);
+        for entry in &found {
+            let contents = serialize_with(&entry.updated, &entry.body, &config.frontmatter);
+            journal.record(notes_dir, &entry.path, Some(contents))?;
+        }
+        journal
+            .run(notes_dir)
+            .with_context(|| "failed to write notes")?;
+
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let changed: Vec<PathBuf> = found.iter().map(|entry| entry.path.clone()).collect();
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &changed)
+            .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            if found.is_empty() {
+                println!("No expired tags.");
+                return Ok(());
+            }
+            for entry in &found {
+                for (tag, date) in &entry.expired {
+                    let outcome = match replacements.get(tag.name()) {
+                        Some(replacement) => format!("-> {}", replacement),
+                        None if args.apply => "removed".to_string(),
+                        None => "would remove".to_string(),
+                    };
+                    println!(
+                        "{}: {} (expired {}) {}",
+                        entry.path.display(),
+                        tag,
+                        date,
+                        outcome
+                    );
+                }
+            }
+            let count: usize = found.iter().map(|entry| entry.expired.len()).sum();
+            if args.apply {
+                println!("Expired {} tag(s) in {} note(s)", count, found.len());
+            } else {
+                println!(
+                    "{} expired tag(s) in {} note(s); run with --apply to remove or replace them",
+                    count,
+                    found.len()
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let listing: Vec<ExpiredNoteListing> = found
+                .iter()
+                .map(|entry| ExpiredNoteListing {
+                    id: entry.note.id().to_string(),
+                    title: entry.note.title().to_string(),
+                    path: notes_dir.join(&entry.path).to_string_lossy().to_string(),
+                    expired: entry
+                        .expired
+                        .iter()
+                        .map(|(tag, date)| ExpiredTagListing {
+                            tag: tag.to_string(),
+                            expired: *date,
+                            replacement: replacements.get(tag.name()).map(Tag::to_string),
+                        })
+                        .collect(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            for entry in &found {
+                println!("{}", notes_dir.join(&entry.path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
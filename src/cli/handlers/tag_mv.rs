@@ -0,0 +1,171 @@
+//! Tags mv and merge handlers: renaming and merging tags across the vault.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::cli::{TagMergeArgs, TagMvArgs};
+use crate::domain::{Note, Tag};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{Journal, read_note, scan_notes_directory_with, serialize_with};
+
+/// Returns `tags` with each of `from` replaced by `into`; `None` if the
+/// note has none of them (pure function, no I/O).
+///
+/// `into` is kept once, where the first of the replaced tags was.
+pub(crate) fn replace_tags(tags: &[Tag], from: &[Tag], into: &Tag) -> Option<Vec<Tag>> {
+    if !tags.iter().any(|tag| from.contains(tag)) {
+        return None;
+    }
+    let mut result: Vec<Tag> = Vec::new();
+    for tag in tags {
+        let tag = if from.contains(tag) { into } else { tag };
+        if !result.contains(tag) {
+            result.push(tag.clone());
+        }
+    }
+    Some(result)
+}
+
+/// A note whose tags were replaced, for JSON output.
+#[derive(Debug, Serialize)]
+struct RetaggedNoteListing {
+    id: String,
+    title: String,
+    path: String,
+    tags: Vec<String>,
+}
+
+fn parse_tag(tag: &str) -> Result<Tag> {
+    Tag::new(tag.trim_start_matches('#')).with_context(|| format!("invalid tag: {}", tag))
+}
+
+pub(super) fn handle_tag_mv(args: &TagMvArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let from = parse_tag(&args.old)?;
+    let into = parse_tag(&args.new)?;
+    if from == into {
+        bail!("'{}' is renamed to itself", from);
+    }
+    retag(&[from], &into, args.dry_run, args.format, notes_dir, config)
+}
+
+pub(super) fn handle_tag_merge(
+    args: &TagMergeArgs,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    let mut tags = args
+        .tags
+        .iter()
+        .map(|tag| parse_tag(tag))
+        .collect::<Result<Vec<_>>>()?;
+    let into = tags.pop().expect("clap requires at least two tags");
+    if tags.contains(&into) {
+        bail!("'{}' is merged into itself", into);
+    }
+    retag(&tags, &into, args.dry_run, args.format, notes_dir, config)
+}
+
+/// Replaces `from` with `into` in every note, then reindexes the notes
+/// changed.
+fn retag(
+    from: &[Tag],
+    into: &Tag,
+    dry_run: bool,
+    format: OutputFormat,
+    notes_dir: &Path,
+    config: &Config,
+) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let mut paths: Vec<PathBuf> = scan_notes_directory_with(notes_dir, &config.scan)?.collect();
+    paths.sort();
+
+    let now = Utc::now();
+    let mut retagged: Vec<(PathBuf, Note, String)> = Vec::new();
+    for path in paths {
+        let parsed = match read_note(&notes_dir.join(&path)) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("warning: skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let note = parsed.note;
+        let Some(tags) = replace_tags(note.tags(), from, into) else {
+            continue;
+        };
+        let note = Note::builder(note.id().clone(), note.title(), note.created(), now)
+            .description(note.description().map(String::from))
+            .topics(note.topics().to_vec())
+            .aliases(note.aliases().to_vec())
+            .tags(tags)
+            .links(note.links().to_vec())
+            .extra(note.extra().clone())
+            .build()?;
+        retagged.push((path, note, parsed.body));
+    }
+
+    if !dry_run && !retagged.is_empty() {
+        let mut journal = Journal::new("tags-mv", now);
+        for (path, note, body) in &retagged {
+            let contents = serialize_with(note, body, &config.frontmatter);
+            journal.record(notes_dir, path, Some(contents))?;
+        }
+        journal
+            .run(notes_dir)
+            .with_context(|| "failed to write notes")?;
+
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let changed: Vec<PathBuf> = retagged.iter().map(|(path, _, _)| path.clone()).collect();
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &changed)
+            .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+    }
+
+    match format {
+        OutputFormat::Human => {
+            for (path, note, _) in &retagged {
+                let tags: Vec<&str> = note.tags().iter().map(Tag::as_str).collect();
+                println!("{}: {}", path.display(), tags.join(", "));
+            }
+            let verb = if dry_run { "Would retag" } else { "Retagged" };
+            let from: Vec<String> = from.iter().map(|tag| format!("'{}'", tag)).collect();
+            println!(
+                "{} {} note(s): {} -> '{}'",
+                verb,
+                retagged.len(),
+                from.join(", "),
+                into
+            );
+        }
+        OutputFormat::Json => {
+            let listing: Vec<RetaggedNoteListing> = retagged
+                .iter()
+                .map(|(path, note, _)| RetaggedNoteListing {
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    path: notes_dir.join(path).to_string_lossy().to_string(),
+                    tags: note.tags().iter().map(|t| t.to_string()).collect(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            for (path, _, _) in &retagged {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
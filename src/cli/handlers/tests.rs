@@ -2,11 +2,12 @@ use super::*;
 use crate::cli::config::Config;
 use crate::cli::output::OutputFormat;
 use crate::cli::{
-    BacklinksArgs, EditArgs, NewArgs, RelsArgs, ShowArgs, TagArgs, TagsArgs, TopicsArgs, UntagArgs,
+    BacklinksArgs, DescArgs, EditArgs, NewArgs, PersonArgs, PersonCommand, PersonNewArgs, RelsArgs,
+    SearchArgs, ShowArgs, TagArgs, TagsArgs, TopicsArgs, UntagArgs,
 };
 use crate::domain::{NoteId, Tag, Topic};
 use crate::index::{IndexRepository, IndexedNote, SearchResult};
-use crate::infra::ContentHash;
+use crate::infra::{ContentHash, SlugOptions};
 use anyhow::{Result, bail};
 use chrono::{DateTime, Utc};
 use std::collections::HashSet;
@@ -179,6 +180,63 @@ fn strip_html_tags_multiple_bold() {
     assert_eq!(strip_html_tags(input), "foo and bar");
 }
 
+#[test]
+fn highlight_matches_colors_bold_runs() {
+    use crate::cli::output::Painter;
+
+    let input = "<b>foo</b> and <b>bar</b>";
+    assert_eq!(highlight_matches(input, &Painter::plain()), "foo and bar");
+
+    let color = Painter::new(&Default::default()).with_color(true);
+    assert_eq!(
+        highlight_matches(input, &color),
+        "\x1b[36mfoo\x1b[0m and \x1b[36mbar\x1b[0m"
+    );
+}
+
+// ===========================================
+// find_in_body tests
+// ===========================================
+
+#[test]
+fn find_in_body_matches_all_terms_case_insensitively() {
+    let body = "Rust ownership\nrust borrowing rules\nOwnership in Rust";
+    let lines: Vec<usize> = find_in_body(body, "rust OWNERSHIP", 1)
+        .iter()
+        .map(|m| m.line)
+        .collect();
+    assert_eq!(lines, vec![1, 3]);
+}
+
+#[test]
+fn find_in_body_tracks_nearest_heading() {
+    let body = "intro cache\n# Setup\n\nconfigure the cache\n## Usage\ncache hits";
+    let matches = find_in_body(body, "cache", 7);
+
+    assert_eq!(matches.len(), 3);
+    assert_eq!(matches[0].heading, None);
+    assert_eq!(matches[1].line, 10);
+    assert_eq!(matches[1].heading, Some((8, "Setup".to_string())));
+    assert_eq!(matches[2].heading, Some((11, "Usage".to_string())));
+}
+
+#[test]
+fn find_in_body_ignores_comments_in_code_blocks_as_headings() {
+    let body = "# Real\n```sh\n# not a heading\necho value\n```\nvalue";
+    let matches = find_in_body(body, "value", 1);
+
+    assert!(
+        matches
+            .iter()
+            .all(|m| m.heading == Some((1, "Real".to_string())))
+    );
+}
+
+#[test]
+fn find_in_body_empty_query_matches_nothing() {
+    assert!(find_in_body("anything", "  ", 1).is_empty());
+}
+
 // ===========================================
 // Search filtering integration tests
 // ===========================================
@@ -324,9 +382,24 @@ fn search_preserves_rank_order_after_filtering() {
 // create_new_note() tests
 // ===========================================
 
+#[test]
+fn create_new_note_filename_honors_slug_options() {
+    let slug = SlugOptions {
+        transliterate: true,
+        stop_words: vec!["o".to_string()],
+        ..SlugOptions::default()
+    };
+    let result = create_new_note("Заметки о Rust", None, &[], &[], &slug).unwrap();
+    assert!(
+        result.filename.ends_with("-zametki-rust.md"),
+        "unexpected filename: {}",
+        result.filename
+    );
+}
+
 #[test]
 fn create_new_note_generates_valid_note() {
-    let result = create_new_note("Test Note", None, &[], &[]).unwrap();
+    let result = create_new_note("Test Note", None, &[], &[], &SlugOptions::default()).unwrap();
     assert_eq!(result.note.title(), "Test Note");
     assert!(result.note.description().is_none());
     assert!(result.note.topics().is_empty());
@@ -336,7 +409,7 @@ fn create_new_note_generates_valid_note() {
 #[test]
 fn create_new_note_sets_timestamps_to_now() {
     let before = Utc::now();
-    let result = create_new_note("Test Note", None, &[], &[]).unwrap();
+    let result = create_new_note("Test Note", None, &[], &[], &SlugOptions::default()).unwrap();
     let after = Utc::now();
 
     assert!(result.note.created() >= before);
@@ -346,14 +419,21 @@ fn create_new_note_sets_timestamps_to_now() {
 
 #[test]
 fn create_new_note_with_description() {
-    let result = create_new_note("Test Note", Some("A test description"), &[], &[]).unwrap();
+    let result = create_new_note(
+        "Test Note",
+        Some("A test description"),
+        &[],
+        &[],
+        &SlugOptions::default(),
+    )
+    .unwrap();
     assert_eq!(result.note.description(), Some("A test description"));
 }
 
 #[test]
 fn create_new_note_with_valid_topics() {
     let topics = vec!["software/rust".to_string(), "reference".to_string()];
-    let result = create_new_note("Test Note", None, &topics, &[]).unwrap();
+    let result = create_new_note("Test Note", None, &topics, &[], &SlugOptions::default()).unwrap();
     assert_eq!(result.note.topics().len(), 2);
     assert_eq!(result.note.topics()[0].to_string(), "software/rust");
     assert_eq!(result.note.topics()[1].to_string(), "reference");
@@ -362,7 +442,7 @@ fn create_new_note_with_valid_topics() {
 #[test]
 fn create_new_note_rejects_invalid_topic() {
     let topics = vec!["software@invalid".to_string()];
-    let result = create_new_note("Test Note", None, &topics, &[]);
+    let result = create_new_note("Test Note", None, &topics, &[], &SlugOptions::default());
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("invalid topic"));
@@ -371,14 +451,14 @@ fn create_new_note_rejects_invalid_topic() {
 #[test]
 fn create_new_note_normalizes_topics() {
     let topics = vec!["/software/rust/".to_string()];
-    let result = create_new_note("Test Note", None, &topics, &[]).unwrap();
+    let result = create_new_note("Test Note", None, &topics, &[], &SlugOptions::default()).unwrap();
     assert_eq!(result.note.topics()[0].to_string(), "software/rust");
 }
 
 #[test]
 fn create_new_note_with_valid_tags() {
     let tags = vec!["draft".to_string(), "important".to_string()];
-    let result = create_new_note("Test Note", None, &[], &tags).unwrap();
+    let result = create_new_note("Test Note", None, &[], &tags, &SlugOptions::default()).unwrap();
     assert_eq!(result.note.tags().len(), 2);
     assert_eq!(result.note.tags()[0].as_str(), "draft");
     assert_eq!(result.note.tags()[1].as_str(), "important");
@@ -387,7 +467,7 @@ fn create_new_note_with_valid_tags() {
 #[test]
 fn create_new_note_rejects_invalid_tag() {
     let tags = vec!["has spaces".to_string()];
-    let result = create_new_note("Test Note", None, &[], &tags);
+    let result = create_new_note("Test Note", None, &[], &tags, &SlugOptions::default());
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("invalid tag"));
@@ -396,13 +476,13 @@ fn create_new_note_rejects_invalid_tag() {
 #[test]
 fn create_new_note_normalizes_tags_to_lowercase() {
     let tags = vec!["DRAFT".to_string()];
-    let result = create_new_note("Test Note", None, &[], &tags).unwrap();
+    let result = create_new_note("Test Note", None, &[], &tags, &SlugOptions::default()).unwrap();
     assert_eq!(result.note.tags()[0].as_str(), "draft");
 }
 
 #[test]
 fn create_new_note_returns_correct_filename() {
-    let result = create_new_note("API Design", None, &[], &[]).unwrap();
+    let result = create_new_note("API Design", None, &[], &[], &SlugOptions::default()).unwrap();
     // Should be 10-char prefix + slug + .md
     assert!(result.filename.ends_with("-api-design.md"));
     assert_eq!(result.filename.len(), 10 + 1 + "api-design".len() + 3);
@@ -410,7 +490,7 @@ fn create_new_note_returns_correct_filename() {
 
 #[test]
 fn create_new_note_rejects_empty_title() {
-    let result = create_new_note("", None, &[], &[]);
+    let result = create_new_note("", None, &[], &[], &SlugOptions::default());
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("empty"));
@@ -418,7 +498,7 @@ fn create_new_note_rejects_empty_title() {
 
 #[test]
 fn create_new_note_rejects_whitespace_only_title() {
-    let result = create_new_note("   ", None, &[], &[]);
+    let result = create_new_note("   ", None, &[], &[], &SlugOptions::default());
     assert!(result.is_err());
     let err = result.unwrap_err().to_string();
     assert!(err.contains("empty"));
@@ -430,6 +510,7 @@ fn create_new_note_rejects_whitespace_only_title() {
 
 mod handle_new_tests {
     use super::*;
+    use crate::index::{IndexBuilder, SqliteIndex};
     use crate::infra::read_note;
     use tempfile::TempDir;
 
@@ -439,14 +520,57 @@ mod handle_new_tests {
 
     fn test_args(title: &str) -> NewArgs {
         NewArgs {
-            title: title.to_string(),
+            title: Some(title.to_string()),
+            stdin: false,
             topics: vec![],
             tags: vec![],
             desc: None,
+            link: None,
+            rels: vec![],
+            reciprocal: false,
+            note_type: None,
+            lang: None,
+            created: None,
+            modified: None,
+            allow_untopiced: false,
             edit: false,
+            format: OutputFormat::Human,
         }
     }
 
+    #[test]
+    fn split_command_keeps_quoted_paths_and_backslashes() {
+        assert_eq!(split_command("code --wait"), vec!["code", "--wait"]);
+        assert_eq!(
+            split_command(r#""C:\Program Files\Vim\vim.exe" -f"#),
+            vec![r"C:\Program Files\Vim\vim.exe", "-f"]
+        );
+        assert_eq!(split_command(r#"ed """#), vec!["ed", ""]);
+        assert!(split_command("  ").is_empty());
+    }
+
+    #[test]
+    fn body_title_prefers_first_heading() {
+        assert_eq!(
+            body_title("Some intro\n\n## Plan for Q3\n\nDetails\n").as_deref(),
+            Some("Plan for Q3")
+        );
+        assert_eq!(
+            body_title("first line\n# Heading\n").as_deref(),
+            Some("Heading")
+        );
+    }
+
+    #[test]
+    fn body_title_falls_back_to_first_line() {
+        assert_eq!(
+            body_title("\n\n  Call the plumber  \nabout the sink\n").as_deref(),
+            Some("Call the plumber")
+        );
+        assert_eq!(body_title(&"x".repeat(200)).map(|t| t.len()), Some(80));
+        assert_eq!(body_title(" \n\n"), None);
+    }
+
     #[test]
     fn handle_new_creates_file() {
         let dir = TempDir::new().unwrap();
@@ -491,11 +615,21 @@ mod handle_new_tests {
     fn handle_new_file_contains_valid_frontmatter() {
         let dir = TempDir::new().unwrap();
         let args = NewArgs {
-            title: "Test Note".to_string(),
+            title: Some("Test Note".to_string()),
+            stdin: false,
             topics: vec!["software/rust".to_string()],
             tags: vec!["draft".to_string()],
             desc: Some("A test description".to_string()),
+            link: None,
+            rels: vec![],
+            reciprocal: false,
+            note_type: None,
+            lang: None,
+            created: None,
+            modified: None,
+            allow_untopiced: false,
             edit: false,
+            format: OutputFormat::Human,
         };
         let config = test_config();
 
@@ -538,11 +672,21 @@ mod handle_new_tests {
     fn handle_new_fails_with_invalid_topic() {
         let dir = TempDir::new().unwrap();
         let args = NewArgs {
-            title: "Test Note".to_string(),
+            title: Some("Test Note".to_string()),
+            stdin: false,
             topics: vec!["invalid@topic".to_string()],
             tags: vec![],
             desc: None,
+            link: None,
+            rels: vec![],
+            reciprocal: false,
+            note_type: None,
+            lang: None,
+            created: None,
+            modified: None,
+            allow_untopiced: false,
             edit: false,
+            format: OutputFormat::Human,
         };
         let config = test_config();
 
@@ -554,11 +698,21 @@ mod handle_new_tests {
     fn handle_new_fails_with_invalid_tag() {
         let dir = TempDir::new().unwrap();
         let args = NewArgs {
-            title: "Test Note".to_string(),
+            title: Some("Test Note".to_string()),
+            stdin: false,
             topics: vec![],
             tags: vec!["has spaces".to_string()],
             desc: None,
+            link: None,
+            rels: vec![],
+            reciprocal: false,
+            note_type: None,
+            lang: None,
+            created: None,
+            modified: None,
+            allow_untopiced: false,
             edit: false,
+            format: OutputFormat::Human,
         };
         let config = test_config();
 
@@ -566,6 +720,80 @@ mod handle_new_tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn handle_new_requires_topic_when_configured() {
+        let dir = TempDir::new().unwrap();
+        let mut config = test_config();
+        config.topics.required = crate::cli::config::RequiredTopics::All(true);
+
+        let err = handle_new(&test_args("Loose"), dir.path(), &config).unwrap_err();
+        assert!(err.to_string().contains("--allow-untopiced"), "{}", err);
+
+        let filed = NewArgs {
+            topics: vec!["projects".to_string()],
+            ..test_args("Filed")
+        };
+        handle_new(&filed, dir.path(), &config).unwrap();
+
+        let allowed = NewArgs {
+            allow_untopiced: true,
+            ..test_args("Allowed")
+        };
+        handle_new(&allowed, dir.path(), &config).unwrap();
+    }
+
+    #[test]
+    fn handle_new_writes_lang_and_slugs_by_it() {
+        let dir = TempDir::new().unwrap();
+        let args = NewArgs {
+            lang: Some("de".to_string()),
+            ..test_args("Grüße aus Köln")
+        };
+        let mut config = test_config();
+        config.slug.transliterate = true;
+
+        handle_new(&args, dir.path(), &config).unwrap();
+        let file = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .find(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .unwrap();
+        assert!(
+            file.file_name()
+                .to_string_lossy()
+                .ends_with("-gruesse-aus-koeln.md")
+        );
+        let parsed = read_note(&file.path()).unwrap();
+        assert_eq!(parsed.note.lang().as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn handle_new_backdates_unless_forbidden() {
+        let dir = TempDir::new().unwrap();
+        let args = NewArgs {
+            created: Some("2023-05-01".to_string()),
+            ..test_args("Imported")
+        };
+        let mut config = test_config();
+
+        config.frontmatter.forbid_backdating = true;
+        let err = handle_new(&args, dir.path(), &config).unwrap_err();
+        assert!(err.to_string().contains("forbid_backdating"), "{}", err);
+
+        config.frontmatter.forbid_backdating = false;
+        handle_new(&args, dir.path(), &config).unwrap();
+        let file = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .find(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .unwrap();
+        let parsed = read_note(&file.path()).unwrap();
+        assert_eq!(
+            parsed.note.created().to_rfc3339(),
+            "2023-05-01T00:00:00+00:00"
+        );
+    }
+
     #[test]
     fn handle_new_fails_with_empty_title() {
         let dir = TempDir::new().unwrap();
@@ -586,6 +814,187 @@ mod handle_new_tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("does not exist"));
     }
+
+    fn write_indexed_parent(dir: &Path) -> PathBuf {
+        let path = dir.join("01HQ3K5M7N-parent.md");
+        std::fs::write(
+            &path,
+            r#"---
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9A
+title: Parent
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+---
+
+Parent body."#,
+        )
+        .unwrap();
+        let mut index = SqliteIndex::open(&dir.join(".index/notes.db")).unwrap();
+        IndexBuilder::new(dir.to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+        path
+    }
+
+    fn created_note(dir: &Path, parent: &Path) -> crate::infra::ParsedNote {
+        let path = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .find(|p| p.extension().is_some_and(|ext| ext == "md") && p != parent)
+            .unwrap();
+        read_note(&path).unwrap()
+    }
+
+    #[test]
+    fn handle_new_with_link_links_to_existing_note() {
+        let dir = TempDir::new().unwrap();
+        let parent = write_indexed_parent(dir.path());
+        let mut args = test_args("Spin-off");
+        args.link = Some("Parent".to_string());
+        args.rels = vec!["parent".to_string()];
+
+        handle_new(&args, dir.path(), &test_config()).unwrap();
+
+        let created = created_note(dir.path(), &parent);
+        let links = created.note.links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target().to_string(), "01HQ3K5M7NXJK4QZPW8V2R6T9A");
+        assert_eq!(links[0].rel()[0].as_str(), "parent");
+        // Without --reciprocal the existing note is untouched
+        assert!(read_note(&parent).unwrap().note.links().is_empty());
+    }
+
+    #[test]
+    fn handle_new_with_reciprocal_links_back() {
+        let dir = TempDir::new().unwrap();
+        let parent = write_indexed_parent(dir.path());
+        let config: Config = toml::from_str(
+            r#"
+            [rels]
+            reciprocal = [["parent", "child"]]
+            "#,
+        )
+        .unwrap();
+        let mut args = test_args("Spin-off");
+        args.link = Some("01HQ3K5M7N".to_string());
+        args.rels = vec!["parent".to_string()];
+        args.reciprocal = true;
+
+        handle_new(&args, dir.path(), &config).unwrap();
+
+        let created = created_note(dir.path(), &parent);
+        let parsed = read_note(&parent).unwrap();
+        let links = parsed.note.links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target(), created.note.id());
+        assert_eq!(links[0].rel()[0].as_str(), "child");
+        assert!(parsed.body.contains("Parent body."));
+    }
+
+    #[test]
+    fn handle_new_reciprocal_without_configured_rel_fails_before_writing() {
+        let dir = TempDir::new().unwrap();
+        write_indexed_parent(dir.path());
+        let mut args = test_args("Spin-off");
+        args.link = Some("Parent".to_string());
+        args.rels = vec!["see-also".to_string()];
+        args.reciprocal = true;
+
+        let err = handle_new(&args, dir.path(), &test_config()).unwrap_err();
+
+        assert!(err.to_string().contains("no reciprocal configured"));
+        let notes = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .count();
+        assert_eq!(notes, 1);
+    }
+
+    #[test]
+    fn handle_new_with_unknown_link_target_fails() {
+        let dir = TempDir::new().unwrap();
+        write_indexed_parent(dir.path());
+        let mut args = test_args("Spin-off");
+        args.link = Some("Nope".to_string());
+        args.rels = vec!["parent".to_string()];
+
+        let err = handle_new(&args, dir.path(), &test_config()).unwrap_err();
+
+        assert!(err.to_string().contains("link target not found"));
+    }
+
+    fn person_type_config() -> Config {
+        toml::from_str(
+            r#"
+            [types.person]
+            template = ".templates/person.md"
+            required = ["email"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn handle_new_with_type_renders_template() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".templates")).unwrap();
+        std::fs::write(
+            dir.path().join(".templates/person.md"),
+            "# {{ title }}\n\nA {{ type }}.\n",
+        )
+        .unwrap();
+        let mut args = test_args("Ada Lovelace");
+        args.note_type = Some("person".to_string());
+
+        handle_new(&args, dir.path(), &person_type_config()).unwrap();
+
+        let created = created_note(dir.path(), Path::new(""));
+        assert_eq!(created.note.note_type(), Some("person"));
+        assert_eq!(created.body, "# Ada Lovelace\n\nA person.\n");
+    }
+
+    #[test]
+    fn handle_new_with_unknown_type_fails() {
+        let dir = TempDir::new().unwrap();
+        let mut args = test_args("Soup");
+        args.note_type = Some("recipe".to_string());
+
+        let err = handle_new(&args, dir.path(), &person_type_config()).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("unknown note type 'recipe' (configured types: person)"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn handle_person_new_creates_person_note() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".templates")).unwrap();
+        std::fs::write(dir.path().join(".templates/person.md"), "# {{ title }}\n").unwrap();
+        let args = PersonArgs {
+            command: PersonCommand::New(PersonNewArgs {
+                name: "Jane Doe".to_string(),
+                topics: vec![],
+                tags: vec![],
+                desc: None,
+                allow_untopiced: false,
+                edit: false,
+                format: OutputFormat::Human,
+            }),
+        };
+
+        handle_person(&args, dir.path(), &person_type_config()).unwrap();
+
+        let created = created_note(dir.path(), Path::new(""));
+        assert_eq!(created.note.title(), "Jane Doe");
+        assert_eq!(created.note.note_type(), Some("person"));
+        assert_eq!(created.body, "# Jane Doe\n");
+    }
 }
 
 // ===========================================
@@ -594,8 +1003,11 @@ mod handle_new_tests {
 
 mod resolve_note_tests {
     use super::*;
+    use crate::cli::config::Config;
+    use crate::cli::handlers::resolve::{prompt_for_note, require_note};
     use crate::domain::Note;
     use crate::index::SqliteIndex;
+    use crate::infra::SlugOptions;
 
     fn setup_index_with_notes() -> SqliteIndex {
         let mut index = SqliteIndex::open_in_memory().unwrap();
@@ -622,6 +1034,7 @@ mod resolve_note_tests {
             test_datetime(),
             test_datetime(),
         )
+        .topics(vec![Topic::new("software/rust").unwrap()])
         .build()
         .unwrap();
         let hash2 = test_content_hash();
@@ -649,7 +1062,12 @@ mod resolve_note_tests {
     #[test]
     fn resolve_by_full_id() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "01HQ3K5M7NXJK4QZPW8V2R6T9A").unwrap();
+        let result = resolve_note(
+            &index,
+            "01HQ3K5M7NXJK4QZPW8V2R6T9A",
+            &SlugOptions::default(),
+        )
+        .unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -663,7 +1081,7 @@ mod resolve_note_tests {
     fn resolve_by_id_prefix_unique() {
         let index = setup_index_with_notes();
         // "01HQ4A2R" only matches one note
-        let result = resolve_note(&index, "01HQ4A2R").unwrap();
+        let result = resolve_note(&index, "01HQ4A2R", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -677,7 +1095,7 @@ mod resolve_note_tests {
     fn resolve_by_id_prefix_ambiguous() {
         let index = setup_index_with_notes();
         // "01HQ3K5M7N" matches both "API Design" and "Rust Programming"
-        let result = resolve_note(&index, "01HQ3K5M7N").unwrap();
+        let result = resolve_note(&index, "01HQ3K5M7N", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Ambiguous(notes) => {
@@ -690,7 +1108,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_by_title_exact() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "Rust Programming").unwrap();
+        let result = resolve_note(&index, "Rust Programming", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -703,7 +1121,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_by_title_case_insensitive() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "rust programming").unwrap();
+        let result = resolve_note(&index, "rust programming", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -716,7 +1134,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_by_alias() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "REST").unwrap();
+        let result = resolve_note(&index, "REST", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -729,7 +1147,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_by_alias_case_insensitive() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "rest").unwrap();
+        let result = resolve_note(&index, "rest", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -742,7 +1160,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_not_found() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "nonexistent").unwrap();
+        let result = resolve_note(&index, "nonexistent", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::NotFound => {}
@@ -753,7 +1171,7 @@ mod resolve_note_tests {
     #[test]
     fn resolve_whitespace_trimmed() {
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "  Rust Programming  ").unwrap();
+        let result = resolve_note(&index, "  Rust Programming  ", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -768,7 +1186,7 @@ mod resolve_note_tests {
         // If an ID prefix uniquely matches, it should return immediately
         // even if there are also title/alias matches
         let index = setup_index_with_notes();
-        let result = resolve_note(&index, "01HQ4A2R9P").unwrap();
+        let result = resolve_note(&index, "01HQ4A2R9P", &SlugOptions::default()).unwrap();
 
         match result {
             ResolveResult::Unique(note) => {
@@ -777,6 +1195,119 @@ mod resolve_note_tests {
             _ => panic!("Expected Unique result"),
         }
     }
+
+    #[test]
+    fn resolve_by_short_ref() {
+        let index = setup_index_with_notes();
+        let result = resolve_note(
+            &index,
+            "software/rust/rust-programming",
+            &SlugOptions::default(),
+        )
+        .unwrap();
+
+        match result {
+            ResolveResult::Unique(note) => {
+                assert_eq!(note.title(), "Rust Programming");
+            }
+            _ => panic!("Expected Unique result, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn resolve_short_ref_wrong_topic_not_found() {
+        let index = setup_index_with_notes();
+        let result = resolve_note(
+            &index,
+            "software/python/rust-programming",
+            &SlugOptions::default(),
+        )
+        .unwrap();
+
+        assert!(matches!(result, ResolveResult::NotFound));
+    }
+
+    #[test]
+    fn require_note_fails_fast_on_ambiguity_unless_interactive() {
+        let index = setup_index_with_notes();
+
+        let err = require_note(&index, "01HQ3K5M7N", "note", &Config::default()).unwrap_err();
+        assert_eq!(err.to_string(), "ambiguous note identifier");
+
+        let err =
+            require_note(&index, "nonexistent", "source note", &Config::default()).unwrap_err();
+        assert_eq!(err.to_string(), "source note not found: 'nonexistent'");
+    }
+
+    #[test]
+    fn prompt_for_note_picks_numbered_candidate() {
+        let index = setup_index_with_notes();
+        let ResolveResult::Ambiguous(notes) =
+            resolve_note(&index, "01HQ3K5M7N", &SlugOptions::default()).unwrap()
+        else {
+            panic!("expected an ambiguous reference");
+        };
+        let second = notes[1].title().to_string();
+
+        let chosen = prompt_for_note("01HQ3K5M7N", notes.clone(), "note", &b"2\n"[..]).unwrap();
+        assert_eq!(chosen.title(), second);
+
+        for answer in ["", "0\n", "3\n", "abc\n"] {
+            let err = prompt_for_note("01HQ3K5M7N", notes.clone(), "note", answer.as_bytes())
+                .unwrap_err();
+            assert!(err.to_string().contains("no note chosen"), "{}", answer);
+        }
+    }
+
+    #[test]
+    fn short_ref_uses_primary_topic_and_slug() {
+        let index = setup_index_with_notes();
+        let note = index
+            .find_by_title("Rust Programming")
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        assert_eq!(
+            short_ref(&note, &SlugOptions::default()),
+            "software/rust/rust-programming"
+        );
+    }
+
+    #[test]
+    fn short_ref_without_topic_is_id_prefix_and_resolves() {
+        let index = setup_index_with_notes();
+        let note = index.find_by_title("API Testing").unwrap().pop().unwrap();
+        let slug = SlugOptions::default();
+
+        let short = short_ref(&note, &slug);
+        assert_eq!(short, note.id().prefix());
+        let ResolveResult::Unique(found) = resolve_note(&index, &short, &slug).unwrap() else {
+            panic!("expected '{}' to resolve", short);
+        };
+        assert_eq!(found.id(), note.id());
+    }
+
+    #[test]
+    fn short_ref_uses_and_resolves_with_slug_options() {
+        let index = setup_index_with_notes();
+        let note = index
+            .find_by_title("Rust Programming")
+            .unwrap()
+            .pop()
+            .unwrap();
+        let slug = SlugOptions {
+            separator: '_',
+            ..SlugOptions::default()
+        };
+
+        let short = short_ref(&note, &slug);
+        assert_eq!(short, "software/rust/rust_programming");
+        let ResolveResult::Unique(found) = resolve_note(&index, &short, &slug).unwrap() else {
+            panic!("expected '{}' to resolve", short);
+        };
+        assert_eq!(found.id(), note.id());
+    }
 }
 
 // ===========================================
@@ -828,10 +1359,15 @@ This is the body of the note.
     fn handle_show_by_id_prefix() {
         let dir = setup_notes_dir();
         let args = ShowArgs {
-            note: "01HQ3K5M".to_string(),
+            note: Some("01HQ3K5M".to_string()),
+            section: None,
+            history: false,
+            at: None,
+            from_index: false,
+            format: OutputFormat::Human,
         };
 
-        let result = handle_show(&args, dir.path());
+        let result = handle_show(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -839,10 +1375,15 @@ This is the body of the note.
     fn handle_show_by_title() {
         let dir = setup_notes_dir();
         let args = ShowArgs {
-            note: "API Design".to_string(),
+            note: Some("API Design".to_string()),
+            section: None,
+            history: false,
+            at: None,
+            from_index: false,
+            format: OutputFormat::Human,
         };
 
-        let result = handle_show(&args, dir.path());
+        let result = handle_show(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -850,10 +1391,15 @@ This is the body of the note.
     fn handle_show_by_alias() {
         let dir = setup_notes_dir();
         let args = ShowArgs {
-            note: "REST".to_string(),
+            note: Some("REST".to_string()),
+            section: None,
+            history: false,
+            at: None,
+            from_index: false,
+            format: OutputFormat::Human,
         };
 
-        let result = handle_show(&args, dir.path());
+        let result = handle_show(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -861,10 +1407,15 @@ This is the body of the note.
     fn handle_show_not_found() {
         let dir = setup_notes_dir();
         let args = ShowArgs {
-            note: "nonexistent".to_string(),
+            note: Some("nonexistent".to_string()),
+            section: None,
+            history: false,
+            at: None,
+            from_index: false,
+            format: OutputFormat::Human,
         };
 
-        let result = handle_show(&args, dir.path());
+        let result = handle_show(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("not found"));
@@ -907,6 +1458,18 @@ mod handle_edit_tests {
         }
     }
 
+    fn edit_args(note: &str) -> EditArgs {
+        EditArgs {
+            note: Some(note.to_string()),
+            all: false,
+            topic: None,
+            tags: vec![],
+            query: None,
+            include_archived: false,
+            format: OutputFormat::Human,
+        }
+    }
+
     impl EditorLauncher for MockEditor {
         fn open(&self, path: &Path) -> Result<()> {
             *self.opened.borrow_mut() = Some(path.to_path_buf());
@@ -984,12 +1547,10 @@ Note 2
     #[test]
     fn handle_edit_not_found_returns_error() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "nonexistent".to_string(),
-        };
+        let args = edit_args("nonexistent");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -997,12 +1558,10 @@ Note 2
     #[test]
     fn handle_edit_ambiguous_returns_error() {
         let dir = setup_notes_dir_with_ambiguous();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("ambiguous"));
     }
@@ -1012,12 +1571,10 @@ Note 2
     #[test]
     fn handle_edit_by_id_prefix() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "01HQ3K5M".to_string(),
-        };
+        let args = edit_args("01HQ3K5M");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_ok());
 
         let opened = editor.opened_path().unwrap();
@@ -1027,12 +1584,10 @@ Note 2
     #[test]
     fn handle_edit_by_title() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_ok());
 
         let opened = editor.opened_path().unwrap();
@@ -1042,12 +1597,10 @@ Note 2
     #[test]
     fn handle_edit_by_alias() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "REST".to_string(),
-        };
+        let args = edit_args("REST");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_ok());
 
         let opened = editor.opened_path().unwrap();
@@ -1059,9 +1612,7 @@ Note 2
     #[test]
     fn handle_edit_updates_modified_timestamp() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::new();
 
         // Read original modified time
@@ -1072,7 +1623,7 @@ Note 2
         // Small delay to ensure timestamp differs
         std::thread::sleep(std::time::Duration::from_millis(10));
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_ok());
 
         // Read updated modified time
@@ -1088,12 +1639,10 @@ Note 2
     #[test]
     fn handle_edit_editor_failure_returns_error() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::failing();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("editor failed"));
     }
@@ -1101,9 +1650,7 @@ Note 2
     #[test]
     fn handle_edit_no_timestamp_update_on_editor_failure() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::failing();
 
         // Read original modified time
@@ -1111,7 +1658,7 @@ Note 2
         let before = crate::infra::read_note(&file_path).unwrap();
         let original_modified = before.note.modified();
 
-        let _ = handle_edit_impl(&args, dir.path(), &editor);
+        let _ = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
 
         // Read modified time after (should be unchanged)
         let after = crate::infra::read_note(&file_path).unwrap();
@@ -1127,12 +1674,10 @@ Note 2
     #[test]
     fn handle_edit_updates_index() {
         let dir = setup_notes_dir();
-        let args = EditArgs {
-            note: "API Design".to_string(),
-        };
+        let args = edit_args("API Design");
         let editor = MockEditor::new();
 
-        let result = handle_edit_impl(&args, dir.path(), &editor);
+        let result = handle_edit_impl(&args, dir.path(), &Config::default(), &editor);
         assert!(result.is_ok());
 
         // Verify index was updated by checking modified time in index
@@ -1146,6 +1691,267 @@ Note 2
         let file_note = crate::infra::read_note(&file_path).unwrap();
         assert_eq!(notes[0].modified(), file_note.note.modified());
     }
+
+    // Phase 6: --all
+
+    /// Editor that appends a line to the files whose name contains `touch`.
+    struct BatchEditor {
+        opened: RefCell<Vec<PathBuf>>,
+        touch: &'static str,
+    }
+
+    impl EditorLauncher for BatchEditor {
+        fn open(&self, _path: &Path) -> Result<()> {
+            bail!("expected a single batch open");
+        }
+
+        fn open_all(&self, paths: &[PathBuf]) -> Result<()> {
+            *self.opened.borrow_mut() = paths.to_vec();
+            for path in paths {
+                if path.to_string_lossy().contains(self.touch) {
+                    let mut content = std::fs::read_to_string(path)?;
+                    content.push_str("Edited.\n");
+                    std::fs::write(path, content)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn setup_tagged_notes_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".index")).unwrap();
+
+        for (id, slug, title, tags) in [
+            (
+                "01HQ3K5M7NXJK4QZPW8V2R6T9A",
+                "ownership",
+                "Ownership",
+                "[rust]",
+            ),
+            (
+                "01HQ3K5M7NXJK4QZPW8V2R6T9B",
+                "borrowing",
+                "Borrowing",
+                "[rust]",
+            ),
+            (
+                "01HQ3K5M7NXJK4QZPW8V2R6T9C",
+                "goroutines",
+                "Goroutines",
+                "[go]",
+            ),
+            (
+                "01HQ3K5M7NXJK4QZPW8V2R6T9D",
+                "old-rust",
+                "Old Rust",
+                "[rust, archived]",
+            ),
+        ] {
+            let note = format!(
+                "---\nid: {}\ntitle: {}\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\ntags: {}\n---\nBody.\n",
+                id, title, tags
+            );
+            std::fs::write(dir.path().join(format!("{}.md", slug)), note).unwrap();
+        }
+
+        let db_path = dir.path().join(".index/notes.db");
+        let mut index = SqliteIndex::open(&db_path).unwrap();
+        IndexBuilder::new(dir.path().to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+
+        dir
+    }
+
+    fn edit_all_args(tags: &[&str]) -> EditArgs {
+        EditArgs {
+            note: None,
+            all: true,
+            topic: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            query: None,
+            include_archived: false,
+            format: OutputFormat::Human,
+        }
+    }
+
+    #[test]
+    fn handle_edit_all_opens_matching_notes_together() {
+        let dir = setup_tagged_notes_dir();
+        let editor = BatchEditor {
+            opened: RefCell::new(Vec::new()),
+            touch: "nothing",
+        };
+
+        handle_edit_impl(
+            &edit_all_args(&["rust"]),
+            dir.path(),
+            &Config::default(),
+            &editor,
+        )
+        .unwrap();
+
+        let opened = editor.opened.borrow();
+        assert_eq!(
+            *opened,
+            vec![
+                dir.path().join("borrowing.md"),
+                dir.path().join("ownership.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_edit_all_only_updates_changed_notes() {
+        let dir = setup_tagged_notes_dir();
+        let editor = BatchEditor {
+            opened: RefCell::new(Vec::new()),
+            touch: "ownership",
+        };
+
+        handle_edit_impl(
+            &edit_all_args(&["rust"]),
+            dir.path(),
+            &Config::default(),
+            &editor,
+        )
+        .unwrap();
+
+        let edited = crate::infra::read_note(&dir.path().join("ownership.md")).unwrap();
+        let untouched = crate::infra::read_note(&dir.path().join("borrowing.md")).unwrap();
+        assert!(edited.note.modified() > test_datetime());
+        assert_eq!(untouched.note.modified(), test_datetime());
+
+        let index = SqliteIndex::open(&dir.path().join(".index/notes.db")).unwrap();
+        let indexed = index.find_by_title("Ownership").unwrap();
+        assert_eq!(indexed[0].modified(), edited.note.modified());
+    }
+
+    #[test]
+    fn handle_edit_all_requires_filter() {
+        let dir = setup_tagged_notes_dir();
+        let editor = MockEditor::new();
+
+        let result = handle_edit_impl(&edit_all_args(&[]), dir.path(), &Config::default(), &editor);
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("requires at least one filter")
+        );
+        assert!(editor.opened_path().is_none());
+    }
+
+    #[test]
+    fn handle_edit_all_no_matches_skips_editor() {
+        let dir = setup_tagged_notes_dir();
+        let editor = MockEditor::failing();
+
+        let result = handle_edit_impl(
+            &edit_all_args(&["python"]),
+            dir.path(),
+            &Config::default(),
+            &editor,
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn edit_listed_notes_opens_found_notes_and_reports_missing() {
+        let dir = setup_tagged_notes_dir();
+        let index = SqliteIndex::open(&dir.path().join(".index/notes.db")).unwrap();
+        let editor = BatchEditor {
+            opened: RefCell::new(Vec::new()),
+            touch: "nothing",
+        };
+        let mut args = edit_all_args(&[]);
+        args.all = false;
+        args.note = Some("-".to_string());
+        let refs: Vec<String> = ["Ownership", "missing", "Borrowing", "ownership"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+
+        let result = edit_listed_notes(
+            &args,
+            dir.path(),
+            &Config::default(),
+            &index,
+            &refs,
+            &editor,
+        );
+
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("1 of 4 note(s) failed")
+        );
+        assert_eq!(
+            *editor.opened.borrow(),
+            vec![
+                dir.path().join("ownership.md"),
+                dir.path().join("borrowing.md")
+            ]
+        );
+    }
+
+    // Phase 5: search --open
+
+    fn search_open_args(query: &str, offset: usize) -> SearchArgs {
+        SearchArgs {
+            query: query.to_string(),
+            in_note: None,
+            topic: None,
+            tags: vec![],
+            format: OutputFormat::Human,
+            include_archived: false,
+            explain: false,
+            limit: None,
+            offset,
+            open: true,
+            show: false,
+        }
+    }
+
+    #[test]
+    fn search_open_edits_best_match() {
+        let dir = setup_notes_dir();
+        let editor = MockEditor::new();
+
+        handle_search_impl(
+            &search_open_args("body", 0),
+            dir.path(),
+            &Config::default(),
+            &editor,
+        )
+        .unwrap();
+        assert_eq!(
+            editor.opened_path(),
+            Some(dir.path().join("01HQ3K5M7N-api-design.md"))
+        );
+    }
+
+    #[test]
+    fn search_open_past_last_match_fails() {
+        let dir = setup_notes_dir();
+        let editor = MockEditor::new();
+
+        let result = handle_search_impl(
+            &search_open_args("body", 1),
+            dir.path(),
+            &Config::default(),
+            &editor,
+        );
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("no matching notes")
+        );
+        assert!(editor.opened_path().is_none());
+    }
 }
 
 // ===========================================
@@ -1205,10 +2011,12 @@ Body
     fn handle_tags_empty_index() {
         let dir = setup_empty_index();
         let args = TagsArgs {
+            command: None,
             counts: false,
+            groups: false,
             format: OutputFormat::Human,
         };
-        let result = handle_tags(&args, dir.path());
+        let result = handle_tags(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1216,10 +2024,12 @@ Body
     fn handle_tags_lists_tags_sorted() {
         let dir = setup_index_with_tags(&["rust", "draft", "important"]);
         let args = TagsArgs {
+            command: None,
             counts: false,
+            groups: false,
             format: OutputFormat::Human,
         };
-        let result = handle_tags(&args, dir.path());
+        let result = handle_tags(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1227,22 +2037,81 @@ Body
     fn handle_tags_json_output() {
         let dir = setup_index_with_tags(&["rust", "draft"]);
         let args = TagsArgs {
+            command: None,
             counts: true,
+            groups: false,
             format: OutputFormat::Json,
         };
-        let result = handle_tags(&args, dir.path());
+        let result = handle_tags(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
     #[test]
     fn handle_tags_fails_with_nonexistent_dir() {
         let args = TagsArgs {
+            command: None,
             counts: false,
+            groups: false,
             format: OutputFormat::Human,
         };
-        let result = handle_tags(&args, Path::new("/nonexistent/path"));
+        let result = handle_tags(&args, Path::new("/nonexistent/path"), &Config::default());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn handle_tags_groups_requires_configured_groups() {
+        let dir = setup_index_with_tags(&["rust"]);
+        let args = TagsArgs {
+            command: None,
+            counts: false,
+            groups: true,
+            format: OutputFormat::Human,
+        };
+        let err = handle_tags(&args, dir.path(), &Config::default()).unwrap_err();
+        assert!(err.to_string().contains("no tag groups configured"));
+    }
+
+    #[test]
+    fn group_tags_orders_groups_and_collects_ungrouped() {
+        use crate::cli::tag_style::TagStyles;
+        use crate::index::TagWithCount;
+
+        let tags: Vec<TagWithCount> = ["done", "misc", "todo", "work"]
+            .iter()
+            .map(|t| TagWithCount::new(Tag::new(t).unwrap(), 1))
+            .collect();
+        let mut styles = TagStyles::default();
+        styles.groups.insert(
+            "status".to_string(),
+            vec!["todo".to_string(), "doing".to_string(), "done".to_string()],
+        );
+        styles
+            .groups
+            .insert("area".to_string(), vec!["work".to_string()]);
+        styles
+            .groups
+            .insert("type".to_string(), vec!["howto".to_string()]);
+
+        let groups = group_tags(&tags, &styles);
+        let grouped: Vec<(Option<&str>, Vec<&str>)> = groups
+            .iter()
+            .map(|(group, members)| {
+                (
+                    group.as_deref(),
+                    members.iter().map(|t| t.tag().as_str()).collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            grouped,
+            vec![
+                (Some("area"), vec!["work"]),
+                (Some("status"), vec!["todo", "done"]),
+                (None, vec!["misc"]),
+            ]
+        );
+    }
 }
 
 // ===========================================
@@ -1304,10 +2173,11 @@ Body
     fn handle_rels_empty_index() {
         let dir = setup_empty_index();
         let args = RelsArgs {
+            command: None,
             counts: false,
             format: OutputFormat::Human,
         };
-        let result = handle_rels(&args, dir.path());
+        let result = handle_rels(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1315,10 +2185,11 @@ Body
     fn handle_rels_lists_rels_sorted() {
         let dir = setup_index_with_links(&["see-also", "parent", "child"]);
         let args = RelsArgs {
+            command: None,
             counts: false,
             format: OutputFormat::Human,
         };
-        let result = handle_rels(&args, dir.path());
+        let result = handle_rels(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1326,10 +2197,11 @@ Body
     fn handle_rels_with_counts() {
         let dir = setup_index_with_links(&["parent", "see-also"]);
         let args = RelsArgs {
+            command: None,
             counts: true,
             format: OutputFormat::Human,
         };
-        let result = handle_rels(&args, dir.path());
+        let result = handle_rels(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1337,10 +2209,11 @@ Body
     fn handle_rels_json_output() {
         let dir = setup_index_with_links(&["parent", "child"]);
         let args = RelsArgs {
+            command: None,
             counts: true,
             format: OutputFormat::Json,
         };
-        let result = handle_rels(&args, dir.path());
+        let result = handle_rels(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1348,24 +2221,263 @@ Body
     fn handle_rels_paths_output() {
         let dir = setup_index_with_links(&["parent"]);
         let args = RelsArgs {
+            command: None,
             counts: false,
             format: OutputFormat::Paths,
         };
-        let result = handle_rels(&args, dir.path());
+        let result = handle_rels(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
     #[test]
     fn handle_rels_fails_with_nonexistent_dir() {
         let args = RelsArgs {
+            command: None,
             counts: false,
             format: OutputFormat::Human,
         };
-        let result = handle_rels(&args, Path::new("/nonexistent/path"));
+        let result = handle_rels(&args, Path::new("/nonexistent/path"), &Config::default());
         assert!(result.is_err());
     }
 }
 
+// ===========================================
+// rels graph tests
+// ===========================================
+
+mod rel_graph_tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn notes(entries: &[(&str, &str)]) -> HashMap<NoteId, IndexedNote> {
+        entries
+            .iter()
+            .map(|(suffix, title)| {
+                (
+                    test_note_id(suffix),
+                    sample_indexed_note_with_tags(suffix, title, vec![]),
+                )
+            })
+            .collect()
+    }
+
+    fn edge(child: &str, parent: &str) -> (NoteId, NoteId) {
+        (test_note_id(child), test_note_id(parent))
+    }
+
+    #[test]
+    fn builds_hierarchy_from_root() {
+        let notes = notes(&[
+            ("AA", "Root"),
+            ("BB", "Beta"),
+            ("CC", "Alpha"),
+            ("DD", "Leaf"),
+        ]);
+        let edges = vec![edge("BB", "AA"), edge("CC", "AA"), edge("DD", "BB")];
+
+        let forest = build_rel_forest(&edges, &notes);
+
+        assert_eq!(forest.len(), 1);
+        assert_eq!(forest[0].id, test_note_id("AA"));
+        let titles: Vec<&str> = forest[0]
+            .children
+            .iter()
+            .map(|c| c.note.as_ref().unwrap().title())
+            .collect();
+        assert_eq!(titles, vec!["Alpha", "Beta"]);
+        assert_eq!(forest[0].children[1].children[0].id, test_note_id("DD"));
+    }
+
+    #[test]
+    fn marks_cycles_without_infinite_recursion() {
+        let notes = notes(&[("AA", "A"), ("BB", "B"), ("CC", "C")]);
+        // A -> B -> C -> A, with no root
+        let edges = vec![edge("BB", "AA"), edge("CC", "BB"), edge("AA", "CC")];
+
+        let forest = build_rel_forest(&edges, &notes);
+
+        assert_eq!(forest.len(), 1);
+        assert_eq!(forest[0].id, test_note_id("AA"));
+        let c = &forest[0].children[0].children[0];
+        assert_eq!(c.id, test_note_id("CC"));
+        assert!(c.children[0].cycle);
+        assert_eq!(c.children[0].id, test_note_id("AA"));
+        assert!(c.children[0].children.is_empty());
+    }
+
+    #[test]
+    fn includes_missing_targets() {
+        let notes = notes(&[("BB", "Child")]);
+        let edges = vec![edge("BB", "ZZ")];
+
+        let forest = build_rel_forest(&edges, &notes);
+
+        assert_eq!(forest.len(), 1);
+        assert!(forest[0].note.is_none());
+        assert_eq!(forest[0].children[0].id, test_note_id("BB"));
+    }
+
+    #[test]
+    fn renders_tree_connectors() {
+        let notes = notes(&[
+            ("AA", "Root"),
+            ("BB", "Alpha"),
+            ("CC", "Beta"),
+            ("DD", "Leaf"),
+        ]);
+        let edges = vec![edge("BB", "AA"), edge("CC", "AA"), edge("DD", "BB")];
+
+        let lines = render_rel_forest(&build_rel_forest(&edges, &notes));
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Root ["));
+        assert!(lines[1].starts_with("├── Alpha ["));
+        assert!(lines[2].starts_with("│   └── Leaf ["));
+        assert!(lines[3].starts_with("└── Beta ["));
+    }
+
+    #[test]
+    fn empty_edges_give_empty_forest() {
+        assert!(build_rel_forest(&[], &HashMap::new()).is_empty());
+    }
+
+    /// Backlink lookup over `(source, target)` link pairs.
+    fn linkers<'a>(
+        links: &'a [(&'a str, &'a str)],
+        notes: &'a HashMap<NoteId, IndexedNote>,
+    ) -> impl FnMut(&NoteId) -> Result<Vec<IndexedNote>> + 'a {
+        move |id| {
+            Ok(links
+                .iter()
+                .filter(|(_, target)| test_note_id(target) == *id)
+                .map(|(source, _)| notes[&test_note_id(source)].clone())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn backlink_tree_follows_linkers_to_depth() {
+        let notes = notes(&[("AA", "Root"), ("BB", "B"), ("CC", "C"), ("DD", "D")]);
+        let links = [("BB", "AA"), ("CC", "BB"), ("DD", "CC")];
+
+        let tree =
+            build_backlink_tree(&notes[&test_note_id("AA")], 2, linkers(&links, &notes)).unwrap();
+
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].id, test_note_id("BB"));
+        assert_eq!(tree.children[0].children[0].id, test_note_id("CC"));
+        assert!(tree.children[0].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn backlink_tree_marks_cycles() {
+        let notes = notes(&[("AA", "Root"), ("BB", "B")]);
+        let links = [("BB", "AA"), ("AA", "BB")];
+
+        let tree =
+            build_backlink_tree(&notes[&test_note_id("AA")], 5, linkers(&links, &notes)).unwrap();
+
+        let back = &tree.children[0].children[0];
+        assert_eq!(back.id, test_note_id("AA"));
+        assert!(back.cycle);
+        assert!(back.children.is_empty());
+        assert!(render_rel_forest(std::slice::from_ref(&tree))[2].ends_with("(cycle)"));
+    }
+}
+
+// ===========================================
+// rels export tests
+// ===========================================
+
+mod rel_export_tests {
+    use super::*;
+    use crate::cli::config::RelOptions;
+    use crate::cli::handlers::RelOntology;
+    use crate::domain::{Link, Rel};
+    use std::collections::HashMap;
+
+    fn setup() -> (Vec<IndexedNote>, HashMap<NoteId, Vec<Link>>) {
+        let notes = vec![
+            sample_indexed_note_with_tags("AA", "Root", vec![]),
+            sample_indexed_note_with_tags("BB", "Child & Co", vec![]),
+            sample_indexed_note_with_tags("CC", "Lonely", vec![]),
+        ];
+        let links = HashMap::from([(
+            test_note_id("BB"),
+            vec![
+                Link::new(test_note_id("AA"), vec!["parent", "see-also"]).unwrap(),
+                Link::new(test_note_id("ZZ"), vec!["parent"]).unwrap(),
+            ],
+        )]);
+        (notes, links)
+    }
+
+    fn reciprocal() -> RelOptions {
+        RelOptions {
+            reciprocal: vec![(Rel::new("parent").unwrap(), Rel::new("child").unwrap())],
+        }
+    }
+
+    #[test]
+    fn one_edge_per_rel_between_exported_notes() {
+        let (notes, links) = setup();
+        let ontology = RelOntology::build(&notes, &links, &reciprocal());
+
+        let titles: Vec<&str> = ontology.notes.iter().map(|n| n.title()).collect();
+        assert_eq!(titles, vec!["Child & Co", "Root"]);
+        assert_eq!(
+            ontology.edges,
+            vec![(0, 1, "parent".to_string()), (0, 1, "see-also".to_string())]
+        );
+
+        let rels: Vec<(&str, usize, Option<&str>)> = ontology
+            .rels
+            .iter()
+            .map(|r| (r.name.as_str(), r.count, r.inverse.as_deref()))
+            .collect();
+        assert_eq!(
+            rels,
+            vec![
+                ("child", 0, Some("parent")),
+                ("parent", 1, Some("child")),
+                ("see-also", 1, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn jsonld_describes_rels_and_links() {
+        let (notes, links) = setup();
+        let doc = RelOntology::build(&notes, &links, &reciprocal()).to_jsonld();
+
+        assert_eq!(doc["@context"]["rel"], "urn:den:rel:");
+        let graph = doc["@graph"].as_array().unwrap();
+        assert_eq!(graph[1]["@id"], "rel:parent");
+        assert_eq!(graph[1]["@type"], "owl:ObjectProperty");
+        assert_eq!(graph[1]["inverseOf"], "rel:child");
+
+        let child = &graph[3];
+        assert_eq!(child["label"], "Child & Co");
+        assert_eq!(
+            child["rel:parent"][0]["@id"],
+            format!("note:{}", test_note_id("AA"))
+        );
+        assert!(graph[4].get("rel:parent").is_none());
+    }
+
+    #[test]
+    fn graphml_labels_each_edge_with_its_rel() {
+        let (notes, links) = setup();
+        let graphml = RelOntology::build(&notes, &links, &reciprocal()).to_graphml();
+
+        assert!(graphml.contains("<data key=\"rels\">child,parent,see-also</data>"));
+        assert!(graphml.contains("<data key=\"reciprocal\">child/parent</data>"));
+        assert!(graphml.contains("<data key=\"title\">Child &amp; Co</data>"));
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+        assert!(graphml.contains("<data key=\"label\">see-also</data></edge>"));
+    }
+}
+
 // ===========================================
 // handle_topics tests
 // ===========================================
@@ -1421,10 +2533,13 @@ Body
     fn handle_topics_empty_index() {
         let dir = setup_empty_index();
         let args = TopicsArgs {
+            command: None,
             counts: false,
+            tree: false,
+            depth: None,
             format: OutputFormat::Human,
         };
-        let result = handle_topics(&args, dir.path());
+        let result = handle_topics(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1432,10 +2547,13 @@ Body
     fn handle_topics_lists_topics_sorted() {
         let dir = setup_index_with_topics(&["software/rust", "reference", "software"]);
         let args = TopicsArgs {
+            command: None,
             counts: false,
+            tree: false,
+            depth: None,
             format: OutputFormat::Human,
         };
-        let result = handle_topics(&args, dir.path());
+        let result = handle_topics(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1443,10 +2561,13 @@ Body
     fn handle_topics_with_counts() {
         let dir = setup_index_with_topics(&["software/rust", "software"]);
         let args = TopicsArgs {
+            command: None,
             counts: true,
+            tree: false,
+            depth: None,
             format: OutputFormat::Human,
         };
-        let result = handle_topics(&args, dir.path());
+        let result = handle_topics(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1454,22 +2575,101 @@ Body
     fn handle_topics_json_output() {
         let dir = setup_index_with_topics(&["software/rust"]);
         let args = TopicsArgs {
+            command: None,
             counts: true,
+            tree: false,
+            depth: None,
             format: OutputFormat::Json,
         };
-        let result = handle_topics(&args, dir.path());
+        let result = handle_topics(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
     #[test]
     fn handle_topics_fails_with_nonexistent_dir() {
         let args = TopicsArgs {
+            command: None,
             counts: false,
+            tree: false,
+            depth: None,
             format: OutputFormat::Human,
         };
-        let result = handle_topics(&args, Path::new("/nonexistent/path"));
+        let result = handle_topics(&args, Path::new("/nonexistent/path"), &Config::default());
         assert!(result.is_err());
     }
+
+    fn topics(paths: &[&str]) -> Vec<Topic> {
+        paths.iter().map(|p| Topic::new(p).unwrap()).collect()
+    }
+
+    fn tree_notes() -> Vec<IndexedNote> {
+        vec![
+            sample_indexed_note_with_topics("9A", "Ownership", topics(&["software/rust"])),
+            sample_indexed_note_with_topics(
+                "9B",
+                "Rust vs Python",
+                topics(&["software/rust", "software/python"]),
+            ),
+            sample_indexed_note_with_topics("9C", "Algebra", topics(&["math"])),
+        ]
+    }
+
+    #[test]
+    fn build_topic_tree_adds_parents_and_counts_distinct_notes() {
+        let tree = build_topic_tree(&tree_notes(), None);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(
+            (tree[0].path.as_str(), tree[0].exact, tree[0].total),
+            ("math", 1, 1)
+        );
+
+        let software = &tree[1];
+        assert_eq!((software.exact, software.total), (0, 2));
+        let children: Vec<(&str, usize, usize)> = software
+            .children
+            .iter()
+            .map(|c| (c.name.as_str(), c.exact, c.total))
+            .collect();
+        assert_eq!(children, vec![("python", 1, 1), ("rust", 2, 2)]);
+    }
+
+    #[test]
+    fn build_topic_tree_depth_keeps_totals() {
+        let tree = build_topic_tree(&tree_notes(), Some(1));
+
+        assert_eq!(tree.len(), 2);
+        assert!(tree.iter().all(|node| node.children.is_empty()));
+        assert_eq!(tree[1].total, 2);
+    }
+
+    #[test]
+    fn render_topic_tree_draws_branches() {
+        let tree = build_topic_tree(&tree_notes(), None);
+
+        assert_eq!(
+            render_topic_tree(&tree),
+            vec![
+                "math (1/1)",
+                "software (0/2)",
+                "├── python (1/1)",
+                "└── rust (2/2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_topics_tree_rejects_zero_depth() {
+        let dir = setup_index_with_topics(&["software/rust"]);
+        let args = TopicsArgs {
+            command: None,
+            counts: false,
+            tree: true,
+            depth: Some(0),
+            format: OutputFormat::Human,
+        };
+        assert!(handle_topics(&args, dir.path(), &Config::default()).is_err());
+    }
 }
 
 // ===========================================
@@ -1546,9 +2746,13 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "nonexistent".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -1558,13 +2762,45 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "has spaces".to_string(),
+            tag: Some("has spaces".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("invalid tag"));
     }
 
+    #[test]
+    fn handle_tag_keeps_extra_fields_and_configured_order() {
+        let dir = setup_note_without_tags();
+        let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
+        let content = std::fs::read_to_string(&file_path).unwrap();
+        std::fs::write(
+            &file_path,
+            content.replace("---\nBody", "status: draft\n---\nBody"),
+        )
+        .unwrap();
+
+        let config: Config =
+            toml::from_str("[frontmatter]\norder = [\"title\", \"status\"]").unwrap();
+        let args = TagArgs {
+            note: "Test Note".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
+        };
+        handle_tag(&args, dir.path(), &config).unwrap();
+
+        let written = std::fs::read_to_string(&file_path).unwrap();
+        assert!(written.starts_with("---\ntitle: Test Note\nstatus: draft\nid: "));
+        assert!(written.contains("tags:\n- draft\n"));
+    }
+
     // Phase 2: Resolution
 
     #[test]
@@ -1572,9 +2808,13 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "01HQ3K5M".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1587,9 +2827,13 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1604,9 +2848,13 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1620,9 +2868,13 @@ Body content.
         let dir = setup_note_with_tags(&["existing"]);
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "new-tag".to_string(),
+            tag: Some("new-tag".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1637,9 +2889,13 @@ Body content.
         let dir = setup_note_without_tags();
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "DRAFT".to_string(),
+            tag: Some("DRAFT".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1647,6 +2903,31 @@ Body content.
         assert_eq!(parsed.note.tags()[0].as_str(), "draft");
     }
 
+    #[test]
+    fn handle_tag_dates_tags_with_a_ttl() {
+        let dir = setup_note_with_tags(&["wip"]);
+        let mut config = Config::default();
+        config.tags.ttl.insert("wip".to_string(), 30);
+        config.time.utc = true;
+        let args = TagArgs {
+            note: "Test Note".to_string(),
+            tag: Some("wip".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
+        };
+        handle_tag(&args, dir.path(), &config).unwrap();
+
+        // The undated tag gives way to one expiring 30 days from today
+        let parsed = read_note(&dir.path().join("01HQ3K5M7N-test-note.md")).unwrap();
+        let expires = Utc::now().date_naive() + chrono::Duration::days(30);
+        assert_eq!(
+            parsed.note.tags(),
+            &[Tag::new("wip").unwrap().expiring(expires)]
+        );
+    }
+
     // Phase 4: Idempotency
 
     #[test]
@@ -1654,9 +2935,13 @@ Body content.
         let dir = setup_note_with_tags(&["draft"]);
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1670,9 +2955,13 @@ Body content.
         let dir = setup_note_with_tags(&["draft"]);
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "DRAFT".to_string(),
+            tag: Some("DRAFT".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        let result = handle_tag(&args, dir.path());
+        let result = handle_tag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1694,9 +2983,13 @@ Body content.
 
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        handle_tag(&args, dir.path()).unwrap();
+        handle_tag(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         assert!(after.note.modified() > original_modified);
@@ -1711,9 +3004,13 @@ Body content.
 
         let args = TagArgs {
             note: "Test Note".to_string(),
-            tag: "draft".to_string(),
+            tag: Some("draft".to_string()),
+            query: None,
+            dry_run: false,
+            include_archived: false,
+            format: OutputFormat::Human,
         };
-        handle_tag(&args, dir.path()).unwrap();
+        handle_tag(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         // Timestamp should not change since tag was already present
@@ -1796,8 +3093,9 @@ Body content.
         let args = UntagArgs {
             note: "nonexistent".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -1808,8 +3106,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "has spaces".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("invalid tag"));
     }
@@ -1822,8 +3121,9 @@ Body content.
         let args = UntagArgs {
             note: "01HQ3K5M".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1837,8 +3137,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1854,8 +3155,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1870,8 +3172,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1885,8 +3188,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "DRAFT".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1902,8 +3206,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
@@ -1918,8 +3223,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        let result = handle_untag(&args, dir.path());
+        let result = handle_untag(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -1937,8 +3243,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(),
+            format: OutputFormat::Human,
         };
-        handle_untag(&args, dir.path()).unwrap();
+        handle_untag(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         assert!(after.note.modified() > original_modified);
@@ -1954,8 +3261,9 @@ Body content.
         let args = UntagArgs {
             note: "Test Note".to_string(),
             tag: "draft".to_string(), // Not present
+            format: OutputFormat::Human,
         };
-        handle_untag(&args, dir.path()).unwrap();
+        handle_untag(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         // Timestamp should not change since tag wasn't present
@@ -1963,6 +3271,145 @@ Body content.
     }
 }
 
+// ===========================================
+// handle_desc tests
+// ===========================================
+
+mod handle_desc_tests {
+    use super::*;
+    use crate::index::{IndexBuilder, SqliteIndex};
+    use crate::infra::read_note;
+    use tempfile::TempDir;
+
+    fn setup_note() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join(".index")).unwrap();
+
+        let note = r#"---
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9A
+title: Test Note
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+---
+Body content.
+"#;
+        std::fs::write(dir.path().join("01HQ3K5M7N-test-note.md"), note).unwrap();
+
+        let db_path = dir.path().join(".index/notes.db");
+        let mut index = SqliteIndex::open(&db_path).unwrap();
+        IndexBuilder::new(dir.path().to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+
+        dir
+    }
+
+    fn desc_args(note: &str, description: Option<&str>) -> DescArgs {
+        DescArgs {
+            note: note.to_string(),
+            description: description.map(String::from),
+            clear: description.is_none(),
+            format: OutputFormat::Human,
+        }
+    }
+
+    #[test]
+    fn handle_desc_note_not_found() {
+        let dir = setup_note();
+        let args = desc_args("nonexistent", Some("A summary"));
+        assert!(handle_desc(&args, dir.path(), &Config::default()).is_err());
+    }
+
+    #[test]
+    fn handle_desc_rejects_empty_and_multiline() {
+        let dir = setup_note();
+        for text in ["  ", "first\nsecond"] {
+            let args = desc_args("Test Note", Some(text));
+            assert!(handle_desc(&args, dir.path(), &Config::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn handle_desc_sets_description_and_updates_index() {
+        let dir = setup_note();
+        let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
+
+        let args = desc_args("01HQ3K5M7N", Some("  A short summary "));
+        handle_desc(&args, dir.path(), &Config::default()).unwrap();
+
+        let after = read_note(&file_path).unwrap();
+        assert_eq!(after.note.description(), Some("A short summary"));
+        assert!(after.note.modified() > test_datetime());
+        assert!(after.body.contains("Body content."));
+
+        let index = SqliteIndex::open(&dir.path().join(".index/notes.db")).unwrap();
+        let indexed = index.find_by_title("Test Note").unwrap();
+        assert_eq!(indexed[0].description(), Some("A short summary"));
+    }
+
+    #[test]
+    fn handle_desc_no_timestamp_change_when_idempotent() {
+        let dir = setup_note();
+        let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
+
+        // Clearing a missing description leaves the file alone
+        handle_desc(
+            &desc_args("Test Note", None),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            read_note(&file_path).unwrap().note.modified(),
+            test_datetime()
+        );
+
+        handle_desc(
+            &desc_args("Test Note", Some("Summary")),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+        let set = read_note(&file_path).unwrap().note.modified();
+
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        handle_desc(
+            &desc_args("Test Note", Some("Summary")),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+        assert_eq!(read_note(&file_path).unwrap().note.modified(), set);
+    }
+
+    #[test]
+    fn handle_desc_clear_removes_description() {
+        let dir = setup_note();
+        let file_path = dir.path().join("01HQ3K5M7N-test-note.md");
+
+        handle_desc(
+            &desc_args("Test Note", Some("Summary")),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+        handle_desc(
+            &desc_args("Test Note", None),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+
+        let after = read_note(&file_path).unwrap();
+        assert_eq!(after.note.description(), None);
+        assert!(
+            !std::fs::read_to_string(&file_path)
+                .unwrap()
+                .contains("description")
+        );
+    }
+}
+
 // ===========================================
 // handle_backlinks tests
 // ===========================================
@@ -2215,9 +3662,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "nonexistent".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("note not found"));
@@ -2265,9 +3715,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "01HQ3K5M7N".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("ambiguous"));
@@ -2281,9 +3734,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2293,9 +3749,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2305,9 +3764,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2319,9 +3781,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: Some("parent".to_string()),
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2331,9 +3796,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: Some("invalid_rel".to_string()), // underscore is invalid
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("invalid relationship type"));
@@ -2345,9 +3813,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: Some("see-also".to_string()), // link is "parent" only
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2359,9 +3830,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2371,9 +3845,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Json,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2383,9 +3860,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "Target Note".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Paths,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2397,9 +3877,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "01HQ5B3S".to_string(), // 8-char prefix
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2409,9 +3892,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "REST".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2421,9 +3907,12 @@ mod handle_backlinks_tests {
         let args = BacklinksArgs {
             note: "rest".to_string(),
             rel: None,
+            mentions: false,
+            recursive: false,
+            depth: 3,
             format: OutputFormat::Human,
         };
-        let result = handle_backlinks(&args, dir.path());
+        let result = handle_backlinks(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 }
@@ -2508,10 +3997,12 @@ Body content.
 
     fn test_link_args(source: &str, target: &str, rels: Vec<&str>) -> LinkArgs {
         LinkArgs {
-            source: source.to_string(),
-            target: target.to_string(),
+            command: None,
+            source: Some(source.to_string()),
+            target: Some(target.to_string()),
             rels: rels.iter().map(|s| s.to_string()).collect(),
             note: None,
+            format: OutputFormat::Human,
         }
     }
 
@@ -2522,10 +4013,12 @@ Body content.
         context: &str,
     ) -> LinkArgs {
         LinkArgs {
-            source: source.to_string(),
-            target: target.to_string(),
+            command: None,
+            source: Some(source.to_string()),
+            target: Some(target.to_string()),
             rels: rels.iter().map(|s| s.to_string()).collect(),
             note: Some(context.to_string()),
+            format: OutputFormat::Human,
         }
     }
 
@@ -2537,12 +4030,14 @@ Body content.
     fn handle_link_no_rels_returns_error() {
         let dir = setup_two_notes();
         let args = LinkArgs {
-            source: "Source Note".to_string(),
-            target: "Target Note".to_string(),
+            command: None,
+            source: Some("Source Note".to_string()),
+            target: Some("Target Note".to_string()),
             rels: vec![],
             note: None,
+            format: OutputFormat::Human,
         };
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(
             result
@@ -2556,7 +4051,7 @@ Body content.
     fn handle_link_invalid_rel_returns_error() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["has_underscore"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("invalid rel"));
@@ -2566,7 +4061,7 @@ Body content.
     fn handle_link_one_invalid_rel_among_many_returns_error() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["valid", "in@valid"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("invalid rel"));
@@ -2581,7 +4076,7 @@ Body content.
     fn handle_link_source_not_found_returns_error() {
         let dir = setup_two_notes();
         let args = test_link_args("nonexistent", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("source note not found"));
@@ -2592,7 +4087,7 @@ Body content.
     fn handle_link_source_ambiguous_returns_error() {
         let dir = setup_ambiguous_notes();
         let args = test_link_args("01HQ3K5M7N", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("ambiguous source"));
@@ -2603,7 +4098,7 @@ Body content.
         let dir = setup_two_notes();
         // Use unique prefix that matches only Source Note
         let args = test_link_args("01HQ3K5M", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2611,7 +4106,7 @@ Body content.
     fn handle_link_source_by_title_resolves() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
     }
 
@@ -2624,7 +4119,7 @@ Body content.
         let dir = setup_two_notes();
         // Use a partial ID prefix for target
         let args = test_link_args("Source Note", "01HQ4A2R", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         // Verify link was created with full target ID
@@ -2641,7 +4136,7 @@ Body content.
     fn handle_link_target_by_title_uses_resolved_id() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2659,7 +4154,7 @@ Body content.
         let dir = setup_two_notes();
         // Use a valid ULID that doesn't exist in the index
         let args = test_link_args("Source Note", "01HZ9Z9Z9ZXJK4QZPW8V2R6T9Z", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         // Verify broken link was created
@@ -2676,7 +4171,7 @@ Body content.
     fn handle_link_target_ambiguous_returns_error() {
         let dir = setup_ambiguous_notes();
         let args = test_link_args("Target Note", "01HQ3K5M7N", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("ambiguous target"));
@@ -2686,7 +4181,7 @@ Body content.
     fn handle_link_target_invalid_returns_error() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "not-a-ulid", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         let err = result.unwrap_err().to_string();
         assert!(err.contains("target not found and not a valid note ID"));
@@ -2700,7 +4195,7 @@ Body content.
     fn handle_link_creates_link_with_single_rel() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2714,7 +4209,7 @@ Body content.
     fn handle_link_creates_link_with_multiple_rels() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["parent", "see-also"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2732,7 +4227,7 @@ Body content.
             vec!["parent"],
             "Some context",
         );
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2745,7 +4240,7 @@ Body content.
     fn handle_link_normalizes_rels() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["PARENT", "See-Also"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2782,7 +4277,7 @@ Body content.
             .unwrap();
 
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2800,7 +4295,7 @@ Body content.
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
 
         // First call creates the link
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
         let before = read_note(&file_path).unwrap();
@@ -2810,7 +4305,7 @@ Body content.
         std::thread::sleep(std::time::Duration::from_millis(10));
 
         // Second call should be no-op
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let after = read_note(&file_path).unwrap();
@@ -2825,11 +4320,11 @@ Body content.
 
         // First call: add parent rel
         let args1 = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args1, dir.path()).unwrap();
+        handle_link(&args1, dir.path(), &Config::default()).unwrap();
 
         // Second call: add see-also rel to same target
         let args2 = test_link_args("Source Note", "Target Note", vec!["see-also"]);
-        handle_link(&args2, dir.path()).unwrap();
+        handle_link(&args2, dir.path(), &Config::default()).unwrap();
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
         let parsed = read_note(&file_path).unwrap();
@@ -2852,7 +4347,7 @@ Body content.
 
         // First call: no context
         let args1 = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args1, dir.path()).unwrap();
+        handle_link(&args1, dir.path(), &Config::default()).unwrap();
 
         // Second call: add context
         let args2 = test_link_args_with_context(
@@ -2861,7 +4356,7 @@ Body content.
             vec!["parent"],
             "New context",
         );
-        handle_link(&args2, dir.path()).unwrap();
+        handle_link(&args2, dir.path(), &Config::default()).unwrap();
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
         let parsed = read_note(&file_path).unwrap();
@@ -2884,7 +4379,7 @@ Body content.
         std::thread::sleep(std::time::Duration::from_millis(10));
 
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         assert!(after.note.modified() > original_modified);
@@ -2896,7 +4391,7 @@ Body content.
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
 
         // First call
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
         let before = read_note(&file_path).unwrap();
@@ -2905,7 +4400,7 @@ Body content.
         std::thread::sleep(std::time::Duration::from_millis(10));
 
         // Second call (no-op)
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         // Timestamp should NOT change
@@ -2916,7 +4411,7 @@ Body content.
     fn handle_link_updates_index() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         // Verify index was updated by checking backlinks query works
         let db_path = dir.path().join(".index/notes.db");
@@ -2936,7 +4431,7 @@ Body content.
     fn handle_link_self_link_allowed() {
         let dir = setup_two_notes();
         let args = test_link_args("Source Note", "Source Note", vec!["self-reference"]);
-        let result = handle_link(&args, dir.path());
+        let result = handle_link(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -2957,7 +4452,7 @@ Body content.
         let original_body = before.body.clone();
 
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         assert_eq!(after.body, original_body);
@@ -2971,6 +4466,7 @@ Body content.
         UnlinkArgs {
             source: source.to_string(),
             target: target.to_string(),
+            format: OutputFormat::Human,
         }
     }
 
@@ -2978,7 +4474,7 @@ Body content.
         let dir = setup_two_notes();
         // Add a link from source to target
         let args = test_link_args("Source Note", "Target Note", vec!["parent"]);
-        handle_link(&args, dir.path()).unwrap();
+        handle_link(&args, dir.path(), &Config::default()).unwrap();
         dir
     }
 
@@ -2986,7 +4482,7 @@ Body content.
     fn handle_unlink_removes_link_from_note_file() {
         let dir = setup_linked_notes();
         let args = test_unlink_args("Source Note", "Target Note");
-        let result = handle_unlink(&args, dir.path());
+        let result = handle_unlink(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         // Verify link was removed
@@ -3000,7 +4496,7 @@ Body content.
         let dir = setup_two_notes();
         // Source has no links to target
         let args = test_unlink_args("Source Note", "Target Note");
-        let result = handle_unlink(&args, dir.path());
+        let result = handle_unlink(&args, dir.path(), &Config::default());
         assert!(result.is_ok()); // Not an error, just no-op
     }
 
@@ -3008,7 +4504,7 @@ Body content.
     fn handle_unlink_source_not_found_returns_error() {
         let dir = setup_two_notes();
         let args = test_unlink_args("Nonexistent Note", "Target Note");
-        let result = handle_unlink(&args, dir.path());
+        let result = handle_unlink(&args, dir.path(), &Config::default());
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not found"));
     }
@@ -3018,11 +4514,11 @@ Body content.
         let dir = setup_linked_notes();
         // Add another link
         let link_args = test_link_args("Source Note", "Source Note", vec!["self-ref"]);
-        handle_link(&link_args, dir.path()).unwrap();
+        handle_link(&link_args, dir.path(), &Config::default()).unwrap();
 
         // Unlink only the parent link to target
         let args = test_unlink_args("Source Note", "Target Note");
-        handle_unlink(&args, dir.path()).unwrap();
+        handle_unlink(&args, dir.path(), &Config::default()).unwrap();
 
         // Self-link should remain
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -3043,7 +4539,7 @@ Body content.
         std::thread::sleep(std::time::Duration::from_millis(10));
 
         let args = test_unlink_args("Source Note", "Target Note");
-        handle_unlink(&args, dir.path()).unwrap();
+        handle_unlink(&args, dir.path(), &Config::default()).unwrap();
 
         let after = read_note(&file_path).unwrap();
         assert!(after.note.modified() > before.note.modified());
@@ -3054,7 +4550,7 @@ Body content.
         let dir = setup_linked_notes();
         // Use ID prefix instead of title
         let args = test_unlink_args("Source Note", "01HQ4A2R9P");
-        let result = handle_unlink(&args, dir.path());
+        let result = handle_unlink(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let file_path = dir.path().join("01HQ3K5M7N-source-note.md");
@@ -3092,12 +4588,40 @@ Content"#;
 
         // Unlink using the broken target ID
         let args = test_unlink_args("Source Note", "01ZZZZZZZZXJK4QZPW8V2R6T9X");
-        let result = handle_unlink(&args, dir.path());
+        let result = handle_unlink(&args, dir.path(), &Config::default());
         assert!(result.is_ok());
 
         let parsed = read_note(&file_path).unwrap();
         assert!(parsed.note.links().is_empty());
     }
+
+    #[test]
+    fn link_titles_follow_renamed_target() {
+        let dir = setup_two_notes();
+        let config: Config = toml::from_str("[frontmatter]\nlink_titles = true").unwrap();
+        let source_path = dir.path().join("01HQ3K5M7N-source-note.md");
+
+        let args = test_link_args("Source Note", "Target Note", vec!["see-also"]);
+        handle_link(&args, dir.path(), &config).unwrap();
+        let parsed = read_note(&source_path).unwrap();
+        assert_eq!(parsed.note.links()[0].title(), Some("Target Note"));
+
+        let mv_args = crate::cli::MvArgs {
+            note: "Target Note".to_string(),
+            title: Some("Renamed Target".to_string()),
+            title_case: false,
+            no_alias: false,
+            topics: vec![],
+            clear_topics: false,
+            id: None,
+            dry_run: false,
+            format: OutputFormat::Human,
+        };
+        crate::cli::handlers::handle_mv(&mv_args, dir.path(), &config).unwrap();
+
+        let contents = std::fs::read_to_string(&source_path).unwrap();
+        assert!(contents.contains("title: Renamed Target"), "{}", contents);
+    }
 }
 
 // ===========================================
@@ -3106,11 +4630,19 @@ Content"#;
 
 mod handle_check_tests {
     use crate::cli::CheckArgs;
+    use crate::cli::config::{Config, RequiredTopics};
     use crate::cli::handlers::handle_check;
+    use crate::cli::output::OutputFormat;
     use tempfile::TempDir;
 
     fn check_args() -> CheckArgs {
-        CheckArgs { fix: false }
+        CheckArgs {
+            fix: false,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        }
     }
 
     fn valid_note_content(id_suffix: &str, title: &str) -> String {
@@ -3169,7 +4701,7 @@ Body content."#
         let dir = TempDir::new().unwrap();
         let args = check_args();
 
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
     }
@@ -3195,7 +4727,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
     }
@@ -3216,7 +4748,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_err());
     }
@@ -3238,7 +4770,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should fail due to parse error
         assert!(result.is_err());
@@ -3265,7 +4797,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_err());
     }
@@ -3286,7 +4818,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_err());
     }
@@ -3308,7 +4840,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
     }
@@ -3329,7 +4861,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Warnings don't cause failure
         assert!(result.is_ok());
@@ -3352,12 +4884,29 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should still succeed (warnings don't fail)
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn handle_check_fails_on_orphans_when_topics_required() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("01HQ3K5M7N-orphan.md"),
+            orphan_note_content("9A", "Orphan"),
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        config.topics.required = RequiredTopics::Types(vec!["meeting".to_string()]);
+        assert!(handle_check(&check_args(), dir.path(), &config).is_ok());
+
+        config.topics.required = RequiredTopics::All(true);
+        assert!(handle_check(&check_args(), dir.path(), &config).is_err());
+    }
+
     // ===========================================
     // Cycle 7: Output Formatting / Mixed Errors+Warnings
     // ===========================================
@@ -3384,7 +4933,7 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should fail due to duplicate ID error
         assert!(result.is_err());
@@ -3397,7 +4946,11 @@ Body content."#
     #[test]
     fn handle_check_nonexistent_directory_returns_error() {
         let args = check_args();
-        let result = handle_check(&args, std::path::Path::new("/nonexistent/path"));
+        let result = handle_check(
+            &args,
+            std::path::Path::new("/nonexistent/path"),
+            &Config::default(),
+        );
 
         assert!(result.is_err());
     }
@@ -3421,12 +4974,62 @@ Body content."#
         .unwrap();
 
         let args = check_args();
-        let result = handle_check(&args, dir.path());
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should succeed because hidden files are ignored
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn handle_check_link_to_trashed_note_is_warning() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".trash")).unwrap();
+        std::fs::write(
+            dir.path().join(".trash/01HQ3K5M7N-gone.md"),
+            valid_note_content("9B", "Gone"),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("01HQ3K5M7N-source.md"),
+            note_with_multiple_links("9A", "Source", &["01HQ3K5M7NXJK4QZPW8V2R6T9B"]),
+        )
+        .unwrap();
+
+        let result = handle_check(&check_args(), dir.path(), &Config::default());
+
+        // A trashed target is only a warning, unlike a fully broken link
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn handle_check_fix_keeps_links_to_trashed_notes() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(".trash")).unwrap();
+        std::fs::write(
+            dir.path().join(".trash/01HQ3K5M7N-gone.md"),
+            valid_note_content("9B", "Gone"),
+        )
+        .unwrap();
+        let source = dir.path().join("01HQ3K5M7N-source.md");
+        std::fs::write(
+            &source,
+            note_with_multiple_links("9A", "Source", &["01HQ3K5M7NXJK4QZPW8V2R6T9B"]),
+        )
+        .unwrap();
+
+        let args = CheckArgs {
+            fix: true,
+            include_trash: true,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        handle_check(&args, dir.path(), &Config::default()).unwrap();
+
+        let content = std::fs::read_to_string(&source).unwrap();
+        assert!(content.contains("01HQ3K5M7NXJK4QZPW8V2R6T9B"));
+    }
+
     // ===========================================
     // Cycle 9: --fix flag for broken links
     // ===========================================
@@ -3465,8 +5068,14 @@ Body content."#,
         )
         .unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should succeed after fixing
         assert!(result.is_ok());
@@ -3503,8 +5112,14 @@ Body content."#,
         )
         .unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
 
@@ -3533,8 +5148,14 @@ Body content."#,
         )
         .unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
 
@@ -3561,8 +5182,14 @@ Body content."#,
         let original_content = note_with_link("9A", "Source", "01HQ3K5M7NXJK4QZPW8V2R6T9B");
         std::fs::write(&note_path, &original_content).unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
 
@@ -3587,8 +5214,14 @@ Body content."#,
         )
         .unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Should still fail - duplicates are not auto-fixable
         assert!(result.is_err());
@@ -3602,8 +5235,14 @@ Body content."#,
         let note_path = dir.path().join("01HQ3K5M7N-orphan.md");
         std::fs::write(&note_path, orphan_note_content("9A", "Orphan")).unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         // Orphans are warnings, not errors - still succeeds
         assert!(result.is_ok());
@@ -3640,8 +5279,14 @@ This is important body content that must be preserved.
 "#;
         std::fs::write(&note_path, content_with_body).unwrap();
 
-        let args = CheckArgs { fix: true };
-        let result = handle_check(&args, dir.path());
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &Config::default());
 
         assert!(result.is_ok());
 
@@ -3651,32 +5296,373 @@ This is important body content that must be preserved.
         assert!(content.contains("important body content"));
         assert!(content.contains("Bullet point 1"));
     }
-}
 
-// ===========================================
-// completions tests
-// ===========================================
+    #[test]
+    fn handle_check_fix_adds_missing_reciprocal_link() {
+        let dir = TempDir::new().unwrap();
 
-#[test]
-fn completions_generates_bash_script() {
-    use super::generate_completions;
-    use clap_complete::Shell;
+        let child_path = dir.path().join("01HQ3K5M7N-child.md");
+        let parent_path = dir.path().join("01HQ3K5M7N-parent.md");
+        std::fs::write(
+            &child_path,
+            r#"---
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9A
+title: Child
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+topics:
+  - test/topic
+links:
+  - id: 01HQ3K5M7NXJK4QZPW8V2R6T9B
+    rel:
+      - parent
+---
 
-    let mut output = Vec::new();
-    generate_completions(Shell::Bash, &mut output).unwrap();
-    let script = String::from_utf8(output).unwrap();
-    assert!(script.contains("_notes"));
-    assert!(script.contains("complete"));
-}
+Body content."#,
+        )
+        .unwrap();
+        std::fs::write(&parent_path, valid_note_content("9B", "Parent")).unwrap();
 
-#[test]
-fn completions_generates_zsh_script() {
-    use super::generate_completions;
-    use clap_complete::Shell;
+        let config: Config = toml::from_str(
+            r#"
+            [rels]
+            reciprocal = [["parent", "child"]]
+            "#,
+        )
+        .unwrap();
+        let args = CheckArgs {
+            fix: true,
+            include_trash: false,
+            incremental: false,
+            drift: false,
+            format: OutputFormat::Human,
+        };
+        let result = handle_check(&args, dir.path(), &config);
 
-    let mut output = Vec::new();
-    generate_completions(Shell::Zsh, &mut output).unwrap();
-    let script = String::from_utf8(output).unwrap();
+        assert!(result.is_ok());
+        let parsed = crate::infra::read_note(&parent_path).unwrap();
+        let links = parsed.note.links();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target().to_string(), "01HQ3K5M7NXJK4QZPW8V2R6T9A");
+        assert_eq!(links[0].rel()[0].as_str(), "child");
+    }
+
+    #[test]
+    fn handle_check_requires_type_fields() {
+        let dir = TempDir::new().unwrap();
+        let config: Config = toml::from_str(
+            r#"
+            [types.person]
+            required = ["email"]
+            "#,
+        )
+        .unwrap();
+        let person = |email: &str| {
+            format!(
+                "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9A\ntitle: Ada\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\ntopics:\n  - people\ntype: person\n{}---\n",
+                email
+            )
+        };
+        let path = dir.path().join("01HQ3K5M7N-ada.md");
+
+        std::fs::write(&path, person("")).unwrap();
+        assert!(handle_check(&check_args(), dir.path(), &config).is_err());
+
+        std::fs::write(&path, person("email: ada@example.com\n")).unwrap();
+        assert!(handle_check(&check_args(), dir.path(), &config).is_ok());
+    }
+
+    // ===========================================
+    // Incremental Checks
+    // ===========================================
+
+    /// A note whose timestamps match its file, so it passes every check.
+    fn fresh_note(id_suffix: &str, title: &str, links_to: Option<&str>) -> String {
+        let now = chrono::Utc::now().to_rfc3339();
+        let links = links_to
+            .map(|target| format!("links:\n  - id: {}\n    rel:\n      - see-also\n", target))
+            .unwrap_or_default();
+        format!(
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T{id_suffix}\ntitle: {title}\ncreated: {now}\nmodified: {now}\ntopics:\n  - test/topic\n{links}---\n"
+        )
+    }
+
+    fn incremental_args() -> CheckArgs {
+        CheckArgs {
+            incremental: true,
+            ..check_args()
+        }
+    }
+
+    fn checked_files(dir: &TempDir) -> Vec<String> {
+        let index =
+            crate::index::SqliteIndex::open(&crate::cli::handlers::index_db_path(dir.path()))
+                .unwrap();
+        let mut files: Vec<String> = index
+            .checked_files()
+            .unwrap()
+            .into_keys()
+            .map(|p| p.display().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn incremental_check_finds_links_broken_by_other_notes() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("source.md"),
+            fresh_note("9A", "Source", Some("01HQ3K5M7NXJK4QZPW8V2R6T9B")),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("target.md"),
+            fresh_note("9B", "Target", None),
+        )
+        .unwrap();
+
+        handle_check(&incremental_args(), dir.path(), &Config::default()).unwrap();
+        assert_eq!(checked_files(&dir), vec!["source.md", "target.md"]);
+
+        // The source is unchanged, but its link target is gone
+        std::fs::remove_file(dir.path().join("target.md")).unwrap();
+        assert!(handle_check(&incremental_args(), dir.path(), &Config::default()).is_err());
+    }
+
+    #[test]
+    fn incremental_check_rechecks_files_with_issues() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("fresh.md"), fresh_note("9A", "Fresh", None)).unwrap();
+        // modified is long before the file's mtime: an external edit warning
+        std::fs::write(
+            dir.path().join("stale.md"),
+            valid_note_content("9B", "Stale"),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.md"), "---\ntitle: [\n---\n").unwrap();
+
+        handle_check(&incremental_args(), dir.path(), &Config::default()).unwrap_err();
+        assert_eq!(checked_files(&dir), vec!["fresh.md"]);
+    }
+
+    #[test]
+    fn incremental_check_finds_duplicate_ids() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.md"), fresh_note("9A", "First", None)).unwrap();
+        std::fs::write(dir.path().join("b.md"), fresh_note("9A", "Second", None)).unwrap();
+
+        for _ in 0..2 {
+            assert!(handle_check(&incremental_args(), dir.path(), &Config::default()).is_err());
+        }
+    }
+}
+
+// ===========================================
+// fix-ids tests
+// ===========================================
+
+mod fix_ids_tests {
+    use crate::cli::FixIdsArgs;
+    use crate::cli::config::Config;
+    use crate::cli::handlers::handle_fix_ids;
+    use crate::cli::output::OutputFormat;
+    use crate::infra::read_note;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    const ID: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+
+    fn args(files: &[&str]) -> FixIdsArgs {
+        FixIdsArgs {
+            files: files.iter().map(PathBuf::from).collect(),
+            dry_run: false,
+            format: OutputFormat::Human,
+        }
+    }
+
+    /// Writes a note with `extra` frontmatter and `body`.
+    fn write(dir: &Path, name: &str, id: Option<&str>, title: &str, extra: &str, body: &str) {
+        let id = id.map(|id| format!("id: {}\n", id)).unwrap_or_default();
+        std::fs::write(
+            dir.join(name),
+            format!(
+                "---\n{}title: {}\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n{}---\n{}",
+                id, title, extra, body
+            ),
+        )
+        .unwrap();
+    }
+
+    /// A note linking to `ID` in its frontmatter and to `target` in its body.
+    fn write_linker(dir: &Path, target: &str) {
+        write(
+            dir,
+            "01HQ4A2R9P-linker.md",
+            Some("01HQ4A2R9PXJK4QZPW8V2R6T9Y"),
+            "Linker",
+            &format!("links:\n  - id: {}\n    rel: [see-also]\n", ID),
+            &format!("See [it]({})\n", target),
+        );
+    }
+
+    fn md_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.ends_with(".md"))
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn duplicate_gets_new_id_and_keeps_ambiguous_links() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "01HQ3K5M7N-api.md", Some(ID), "API", "", "");
+        write(
+            dir.path(),
+            "01HQ3K5M7N-api-copy.md",
+            Some(ID),
+            "API Copy",
+            "",
+            "",
+        );
+        // The note created first keeps the ID
+        let copy_path = dir.path().join("01HQ3K5M7N-api-copy.md");
+        let copy = std::fs::read_to_string(&copy_path).unwrap();
+        std::fs::write(&copy_path, copy.replacen("2024-01-15", "2024-02-01", 1)).unwrap();
+        write_linker(dir.path(), "01HQ3K5M7N-api-copy.md");
+
+        handle_fix_ids(&args(&[]), dir.path(), &Config::default()).unwrap();
+
+        let files = md_files(dir.path());
+        assert!(files.contains(&"01HQ3K5M7N-api.md".to_string()));
+        let copy = files.iter().find(|n| n.ends_with("-api-copy.md")).unwrap();
+        assert!(!copy.starts_with("01HQ3K5M7N"));
+        let copy_note = read_note(&dir.path().join(copy)).unwrap().note;
+        assert!(copy.starts_with(&copy_note.id().prefix()));
+
+        // The frontmatter link may mean either note, so it stays; the path
+        // link can only mean the copy
+        let linker = read_note(&dir.path().join("01HQ4A2R9P-linker.md")).unwrap();
+        assert_eq!(linker.note.links()[0].target().to_string(), ID);
+        assert!(linker.body.contains(&format!("]({})", copy)));
+    }
+
+    #[test]
+    fn requested_note_gets_new_id_and_links_follow() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "01HQ3K5M7N-api.md", Some(ID), "API", "", "");
+        write_linker(dir.path(), "01HQ3K5M7N");
+
+        handle_fix_ids(
+            &args(&["01HQ3K5M7N-api.md"]),
+            dir.path(),
+            &Config::default(),
+        )
+        .unwrap();
+
+        let files = md_files(dir.path());
+        let api = files.iter().find(|n| n.ends_with("-api.md")).unwrap();
+        let new_id = read_note(&dir.path().join(api)).unwrap().note.id().clone();
+        assert_ne!(new_id.to_string(), ID);
+
+        let linker = read_note(&dir.path().join("01HQ4A2R9P-linker.md")).unwrap();
+        assert_eq!(linker.note.links()[0].target(), &new_id);
+        assert!(linker.body.contains(&format!("]({})", new_id.prefix())));
+    }
+
+    #[test]
+    fn invalid_and_missing_ids_are_repaired() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "BADIDXXXXX-bad.md",
+            Some("BADIDXXXXXNOTAULID"),
+            "Bad",
+            "",
+            "",
+        );
+        write(
+            dir.path(),
+            "missing.md",
+            None,
+            "Missing",
+            "topics: [a]\n",
+            "",
+        );
+
+        handle_fix_ids(&args(&[]), dir.path(), &Config::default()).unwrap();
+
+        let files = md_files(dir.path());
+        assert!(files.contains(&"missing.md".to_string()));
+        let missing = read_note(&dir.path().join("missing.md")).unwrap().note;
+        assert_eq!(missing.topics()[0].to_string(), "a");
+        let bad = files.iter().find(|n| n.ends_with("-bad.md")).unwrap();
+        let bad_note = read_note(&dir.path().join(bad)).unwrap().note;
+        assert!(bad.starts_with(&bad_note.id().prefix()));
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "01HQ3K5M7N-api.md", Some(ID), "API", "", "");
+        write(
+            dir.path(),
+            "01HQ3K5M7N-api-copy.md",
+            Some(ID),
+            "API Copy",
+            "",
+            "",
+        );
+        let mut args = args(&[]);
+        args.dry_run = true;
+
+        handle_fix_ids(&args, dir.path(), &Config::default()).unwrap();
+
+        assert_eq!(
+            md_files(dir.path()),
+            vec!["01HQ3K5M7N-api-copy.md", "01HQ3K5M7N-api.md"]
+        );
+    }
+
+    #[test]
+    fn unknown_file_fails() {
+        let dir = TempDir::new().unwrap();
+        let err = handle_fix_ids(&args(&["nope.md"]), dir.path(), &Config::default()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("not a note in the notes directory")
+        );
+    }
+}
+
+// ===========================================
+// completions tests
+// ===========================================
+
+#[test]
+fn completions_generates_bash_script() {
+    use super::generate_completions;
+    use clap_complete::Shell;
+
+    let mut output = Vec::new();
+    generate_completions(Shell::Bash, &mut output).unwrap();
+    let script = String::from_utf8(output).unwrap();
+    assert!(script.contains("_notes"));
+    assert!(script.contains("complete"));
+}
+
+#[test]
+fn completions_generates_zsh_script() {
+    use super::generate_completions;
+    use clap_complete::Shell;
+
+    let mut output = Vec::new();
+    generate_completions(Shell::Zsh, &mut output).unwrap();
+    let script = String::from_utf8(output).unwrap();
     assert!(script.contains("#compdef notes"));
 }
 
@@ -3690,3 +5676,1923 @@ fn completions_generates_fish_script() {
     let script = String::from_utf8(output).unwrap();
     assert!(script.contains("complete -c notes"));
 }
+
+// ===========================================
+// snippet tests
+// ===========================================
+
+mod snippet_tests {
+    use super::*;
+    use crate::cli::SnippetArgs;
+    use crate::infra::read_note;
+    use tempfile::TempDir;
+
+    fn snippet_args() -> SnippetArgs {
+        SnippetArgs {
+            title: None,
+            lang: None,
+            topics: vec![],
+            tags: vec![],
+            desc: None,
+            edit: false,
+            format: OutputFormat::Human,
+        }
+    }
+
+    #[test]
+    fn detect_language_from_shebang() {
+        assert_eq!(
+            detect_language("#!/usr/bin/env python3\nx = 1\n"),
+            Some("python")
+        );
+        assert_eq!(detect_language("#!/bin/sh\nls\n"), Some("bash"));
+    }
+
+    #[test]
+    fn detect_language_from_keywords() {
+        assert_eq!(
+            detect_language("use std::fs;\n\npub fn main() {\n    let mut x = 1;\n}\n"),
+            Some("rust")
+        );
+        assert_eq!(
+            detect_language("def greet(name):\n    return f\"hi {name}\"\n"),
+            Some("python")
+        );
+        assert_eq!(
+            detect_language("package main\n\nimport (\n\t\"fmt\"\n)\n"),
+            Some("go")
+        );
+        assert_eq!(
+            detect_language("SELECT id FROM notes WHERE title = 'x';"),
+            Some("sql")
+        );
+    }
+
+    #[test]
+    fn detect_language_json() {
+        assert_eq!(detect_language("{\"a\": [1, 2]}"), Some("json"));
+    }
+
+    #[test]
+    fn detect_language_unknown_returns_none() {
+        assert_eq!(detect_language("just some words"), None);
+    }
+
+    #[test]
+    fn snippet_title_from_comment_styles() {
+        assert_eq!(
+            snippet_title("// Parse a config file\nfn x() {}", Some("rust")),
+            Some("Parse a config file".to_string())
+        );
+        assert_eq!(
+            snippet_title("#!/bin/bash\n# Backup photos\nrsync -a", Some("bash")),
+            Some("Backup photos".to_string())
+        );
+        assert_eq!(
+            snippet_title("-- Top customers\nSELECT 1;", Some("sql")),
+            Some("Top customers".to_string())
+        );
+        assert_eq!(
+            snippet_title("/* Reset styles */\nbody {}", Some("css")),
+            Some("Reset styles".to_string())
+        );
+    }
+
+    #[test]
+    fn snippet_title_ignores_preprocessor_directives() {
+        assert_eq!(snippet_title("#include <stdio.h>\n", Some("c")), None);
+    }
+
+    #[test]
+    fn snippet_title_none_without_comment() {
+        assert_eq!(snippet_title("let x = 1;", Some("rust")), None);
+    }
+
+    #[test]
+    fn fence_code_extends_fence_around_backticks() {
+        let fenced = fence_code("echo ```", Some("bash"));
+        assert!(fenced.starts_with("````bash\n"));
+        assert!(fenced.ends_with("\n````\n"));
+    }
+
+    #[test]
+    fn create_snippet_tags_and_titles_note() {
+        let temp_dir = TempDir::new().unwrap();
+        let code = "# Greet a user\ndef greet(name):\n    print(name)\n";
+
+        let (note, path) =
+            create_snippet(&snippet_args(), temp_dir.path(), code, &Config::default()).unwrap();
+
+        assert_eq!(note.title(), "Greet a user");
+        let tags: Vec<&str> = note.tags().iter().map(|t| t.as_str()).collect();
+        assert_eq!(tags, vec!["snippet", "python"]);
+
+        let parsed = read_note(&path).unwrap();
+        assert!(parsed.body.starts_with("```python\n# Greet a user\n"));
+    }
+
+    #[test]
+    fn create_snippet_respects_title_and_lang_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        let args = SnippetArgs {
+            title: Some("My Query".to_string()),
+            lang: Some("SQL".to_string()),
+            tags: vec!["db".to_string()],
+            ..snippet_args()
+        };
+
+        let (note, _) =
+            create_snippet(&args, temp_dir.path(), "select 1", &Config::default()).unwrap();
+
+        assert_eq!(note.title(), "My Query");
+        let tags: Vec<&str> = note.tags().iter().map(|t| t.as_str()).collect();
+        assert_eq!(tags, vec!["snippet", "sql", "db"]);
+    }
+
+    #[test]
+    fn create_snippet_empty_input_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = create_snippet(&snippet_args(), temp_dir.path(), "  \n", &Config::default());
+        assert!(result.is_err());
+    }
+}
+
+// ===========================================
+// clone tests
+// ===========================================
+
+mod clone_tests {
+    use super::*;
+    use crate::domain::{Link, Note};
+
+    fn source_note() -> Note {
+        Note::builder(
+            test_note_id("01"),
+            "Weekly Review",
+            test_datetime(),
+            test_datetime(),
+        )
+        .description(Some("Template for reviews".to_string()))
+        .topics(vec![Topic::new("reviews").unwrap()])
+        .tags(vec![Tag::new("template").unwrap()])
+        .aliases(vec!["weekly".to_string()])
+        .links(vec![
+            Link::new(test_note_id("02"), vec!["see-also"]).unwrap(),
+        ])
+        .extra(std::collections::BTreeMap::from([(
+            "status".to_string(),
+            serde_yaml::Value::from("active"),
+        )]))
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn clone_note_copies_topics_tags_and_description() {
+        let source = source_note();
+
+        let result = clone_note(&source, None, &SlugOptions::default()).unwrap();
+
+        assert_ne!(result.note.id(), source.id());
+        assert_eq!(result.note.title(), "Copy of Weekly Review");
+        assert_eq!(result.note.description(), Some("Template for reviews"));
+        assert_eq!(result.note.topics(), source.topics());
+        assert_eq!(result.note.tags(), source.tags());
+        assert_eq!(result.note.extra(), source.extra());
+        assert!(result.filename.contains("copy-of-weekly-review"));
+    }
+
+    #[test]
+    fn clone_note_drops_links_and_aliases() {
+        let result = clone_note(&source_note(), None, &SlugOptions::default()).unwrap();
+
+        assert!(result.note.links().is_empty());
+        assert!(result.note.aliases().is_empty());
+    }
+
+    #[test]
+    fn clone_note_uses_given_title() {
+        let result =
+            clone_note(&source_note(), Some("  Week 12  "), &SlugOptions::default()).unwrap();
+
+        assert_eq!(result.note.title(), "Week 12");
+    }
+
+    #[test]
+    fn clone_note_rejects_empty_title() {
+        assert!(clone_note(&source_note(), Some("  "), &SlugOptions::default()).is_err());
+    }
+}
+
+// ===========================================
+// stats tests
+// ===========================================
+
+mod stats_tests {
+    use super::*;
+    use crate::cli::StatsArgs;
+    use crate::cli::time_format::TimeFormat;
+    use crate::domain::HealthReport;
+    use crate::index::{HealthSample, SqliteIndex};
+    use tempfile::TempDir;
+
+    fn utc_config() -> Config {
+        Config {
+            time: TimeFormat {
+                utc: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn sample(at: &str, orphans: usize) -> HealthSample {
+        HealthSample {
+            taken_at: DateTime::parse_from_rfc3339(at)
+                .unwrap()
+                .with_timezone(&Utc),
+            report: HealthReport {
+                notes: 4,
+                orphans,
+                links: 0,
+                broken_links: 0,
+                untagged: 0,
+                stale: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn trend_summary_needs_two_samples() {
+        let samples = [sample("2024-01-01T00:00:00Z", 2)];
+        assert_eq!(trend_summary(&samples, &utc_config()), None);
+    }
+
+    #[test]
+    fn trend_summary_reports_improvement() {
+        let samples = [
+            sample("2024-01-01T00:00:00Z", 4),
+            sample("2024-01-02T00:00:00Z", 2),
+            sample("2024-01-03T00:00:00Z", 0),
+        ];
+        assert_eq!(
+            trend_summary(&samples, &utc_config()).unwrap(),
+            "+25.0 since 2024-01-01 (improving)"
+        );
+    }
+
+    #[test]
+    fn trend_summary_reports_decline() {
+        let samples = [
+            sample("2024-01-01T00:00:00Z", 0),
+            sample("2024-01-02T00:00:00Z", 2),
+        ];
+        assert_eq!(
+            trend_summary(&samples, &utc_config()).unwrap(),
+            "-12.5 since 2024-01-01 (declining)"
+        );
+    }
+
+    #[test]
+    fn handle_stats_records_a_sample() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("01HQ3K5M7N-test-note.md"),
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9A\ntitle: Test Note\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\nBody\n",
+        )
+        .unwrap();
+        let args = StatsArgs {
+            command: None,
+            trend: true,
+            limit: 30,
+            stale_days: 180,
+            format: OutputFormat::Json,
+        };
+
+        handle_stats(&args, dir.path(), &Config::default()).unwrap();
+
+        let index = SqliteIndex::open(&index_db_path(dir.path())).unwrap();
+        let samples = index.health_samples(10).unwrap();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].report.notes, 1);
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn usage_by_month_fills_gaps_between_months() {
+        let entries = [
+            ((2023, 11), names(&["rust"])),
+            ((2024, 2), names(&["rust", "draft"])),
+        ];
+
+        let table = usage_by_month(&entries);
+
+        assert_eq!(
+            table.months,
+            vec![(2023, 11), (2023, 12), (2024, 1), (2024, 2)]
+        );
+        assert_eq!(table.series[0].name, "rust");
+        assert_eq!(table.series[0].counts, vec![1, 0, 0, 1]);
+        assert_eq!(table.series[1].name, "draft");
+        assert_eq!(table.series[1].counts, vec![0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn usage_by_month_orders_by_total_then_name() {
+        let entries = [
+            ((2024, 1), names(&["beta", "gamma"])),
+            ((2024, 1), names(&["alpha", "gamma"])),
+        ];
+
+        let table = usage_by_month(&entries);
+
+        let order: Vec<&str> = table.series.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(order, vec!["gamma", "alpha", "beta"]);
+    }
+
+    #[test]
+    fn usage_by_month_with_no_notes_is_empty() {
+        let table = usage_by_month(&[]);
+
+        assert!(table.months.is_empty());
+        assert!(table.series.is_empty());
+    }
+}
+
+// ===========================================
+// topics stats tests
+// ===========================================
+
+mod topic_stats_tests {
+    use super::*;
+    use crate::domain::Link;
+    use std::collections::HashMap;
+
+    fn note(suffix: &str, topics: &[&str], tags: &[&str], date: &str) -> IndexedNote {
+        let time = DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc);
+        IndexedNote::builder(
+            test_note_id(suffix),
+            format!("Note {}", suffix),
+            time,
+            time,
+            PathBuf::from(format!("{}.md", suffix)),
+            test_content_hash(),
+        )
+        .topics(topics.iter().map(|t| Topic::new(t).unwrap()).collect())
+        .tags(tags.iter().map(|t| Tag::new(t).unwrap()).collect())
+        .build()
+    }
+
+    fn links(edges: &[(&str, &str)]) -> HashMap<NoteId, Vec<Link>> {
+        let mut links: HashMap<NoteId, Vec<Link>> = HashMap::new();
+        for (source, target) in edges {
+            links
+                .entry(test_note_id(source))
+                .or_default()
+                .push(Link::new(test_note_id(target), vec!["see-also"]).unwrap());
+        }
+        links
+    }
+
+    fn at(date: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn topic_report_summarizes_subtree() {
+        let topic = Topic::new("software").unwrap();
+        let notes = vec![
+            note("AA", &["software"], &["draft"], "2024-01-10T00:00:00Z"),
+            note(
+                "BB",
+                &["software/api"],
+                &["draft", "rust"],
+                "2024-03-10T00:00:00Z",
+            ),
+            note("CC", &["software/api/rest"], &[], "2024-03-20T00:00:00Z"),
+            note(
+                "DD",
+                &["software/db", "software/api"],
+                &[],
+                "2024-04-01T00:00:00Z",
+            ),
+        ];
+        // ZZ is outside the topic but its link counts
+        let links = links(&[("AA", "BB"), ("ZZ", "BB"), ("CC", "AA"), ("BB", "BB")]);
+
+        let report = topic_report(&topic, &notes, &links, true, at("2024-03-15T00:00:00Z"));
+
+        assert_eq!(report.notes, 4);
+        assert_eq!(report.direct, 1);
+        assert_eq!(
+            report.months,
+            vec![
+                ((2024, 1), 1),
+                ((2024, 2), 0),
+                ((2024, 3), 2),
+                ((2024, 4), 1)
+            ]
+        );
+        assert_eq!(
+            report.tags,
+            vec![("draft".to_string(), 2), ("rust".to_string(), 1)]
+        );
+        let linked: Vec<(&str, usize)> = report
+            .most_linked
+            .iter()
+            .map(|(n, count)| (n.title(), *count))
+            .collect();
+        assert_eq!(linked, vec![("Note BB", 2), ("Note AA", 1)]);
+        // Only DD has no links; BB's link to itself doesn't count
+        assert_eq!(report.orphans, 1);
+        assert_eq!(report.stale, 2);
+        assert_eq!(report.stalest[0].title(), "Note AA");
+
+        let subtopics: Vec<(String, usize)> = report
+            .subtopics
+            .iter()
+            .map(|(t, count)| (t.to_string(), *count))
+            .collect();
+        assert_eq!(
+            subtopics,
+            vec![
+                ("software/api".to_string(), 3),
+                ("software/db".to_string(), 1)
+            ]
+        );
+        let balance = report.balance().unwrap();
+        assert!(balance > 0.8 && balance < 0.82, "{}", balance);
+    }
+
+    #[test]
+    fn topic_report_balance_needs_two_subtopics() {
+        let topic = Topic::new("software").unwrap();
+        let notes = vec![note("AA", &["software/api"], &[], "2024-01-10T00:00:00Z")];
+
+        let report = topic_report(
+            &topic,
+            &notes,
+            &HashMap::new(),
+            true,
+            at("2024-01-01T00:00:00Z"),
+        );
+
+        assert_eq!(report.balance(), None);
+        assert_eq!(report.orphans, 1);
+        assert_eq!(report.stale, 0);
+    }
+}
+
+// ===========================================
+// tags expire tests
+// ===========================================
+
+mod tag_expire_tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn tags(names: &[&str]) -> Vec<Tag> {
+        names.iter().map(|t| Tag::new(t).unwrap()).collect()
+    }
+
+    #[test]
+    fn expire_tags_removes_or_replaces_expired_tags() {
+        let all = tags(&["review-by:2024-06-01", "wip", "draft"]);
+        let replacements = expiry_replacements(&BTreeMap::from([(
+            "review-by".to_string(),
+            "overdue".to_string(),
+        )]))
+        .unwrap();
+
+        let left = expire_tags(&all, &[&all[0], &all[1]], &replacements);
+
+        assert_eq!(left, tags(&["overdue", "draft"]));
+    }
+
+    #[test]
+    fn expire_tags_adds_a_replacement_once() {
+        let all = tags(&["overdue", "review-by:2024-06-01"]);
+        let replacements =
+            BTreeMap::from([("review-by".to_string(), Tag::new("overdue").unwrap())]);
+
+        assert_eq!(
+            expire_tags(&all, &[&all[1]], &replacements),
+            tags(&["overdue"])
+        );
+    }
+
+    #[test]
+    fn expiry_replacements_rejects_invalid_tags() {
+        let configured = BTreeMap::from([("wip".to_string(), "not a tag".to_string())]);
+        let err = expiry_replacements(&configured).unwrap_err();
+        assert!(format!("{:#}", err).contains("[tags.expired] entry 'wip'"));
+    }
+}
+
+// ===========================================
+// tags mv and merge tests
+// ===========================================
+
+mod tag_mv_tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<Tag> {
+        names.iter().map(|t| Tag::new(t).unwrap()).collect()
+    }
+
+    #[test]
+    fn replace_tags_renames_in_place() {
+        assert_eq!(
+            replace_tags(
+                &tags(&["a", "todo", "b"]),
+                &tags(&["todo"]),
+                &tags(&["task"])[0]
+            ),
+            Some(tags(&["a", "task", "b"]))
+        );
+        assert_eq!(
+            replace_tags(&tags(&["a"]), &tags(&["todo"]), &tags(&["task"])[0]),
+            None
+        );
+    }
+
+    #[test]
+    fn replace_tags_dedupes_merged_tags() {
+        assert_eq!(
+            replace_tags(
+                &tags(&["todo", "x", "task", "to-do"]),
+                &tags(&["todo", "to-do"]),
+                &tags(&["task"])[0]
+            ),
+            Some(tags(&["task", "x"]))
+        );
+    }
+}
+
+// ===========================================
+// topics mv tests
+// ===========================================
+
+mod topic_mv_tests {
+    use super::*;
+
+    fn topics(paths: &[&str]) -> Vec<Topic> {
+        paths.iter().map(|p| Topic::new(p).unwrap()).collect()
+    }
+
+    fn moved(paths: &[&str], from: &str, to: &str, descendants: bool) -> Option<Vec<String>> {
+        let from = Topic::new(from).unwrap();
+        let to = Topic::new(to).unwrap();
+        move_topics(&topics(paths), &from, &to, descendants)
+            .map(|topics| topics.iter().map(|t| t.to_string()).collect())
+    }
+
+    #[test]
+    fn move_topics_renames_only_the_exact_topic() {
+        assert_eq!(
+            moved(&["software", "software/rust"], "software", "tech", false),
+            Some(vec!["tech".to_string(), "software/rust".to_string()])
+        );
+        assert_eq!(moved(&["software/rust"], "software", "tech", false), None);
+        assert_eq!(moved(&["soft"], "software", "tech", true), None);
+    }
+
+    #[test]
+    fn move_topics_carries_subtopics_when_asked() {
+        assert_eq!(
+            moved(
+                &["software/rust/async", "science"],
+                "software",
+                "tech/code",
+                true
+            ),
+            Some(vec![
+                "tech/code/rust/async".to_string(),
+                "science".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn move_topics_merges_into_an_existing_topic() {
+        assert_eq!(
+            moved(&["tech", "software"], "software", "tech", false),
+            Some(vec!["tech".to_string()])
+        );
+    }
+}
+
+// ===========================================
+// stats overview tests
+// ===========================================
+
+mod stats_overview_tests {
+    use super::*;
+    use crate::domain::Link;
+    use std::collections::HashMap;
+
+    fn note(suffix: &str, topics: &[&str], tags: &[&str], date: &str) -> IndexedNote {
+        let time = DateTime::parse_from_rfc3339(date)
+            .unwrap()
+            .with_timezone(&Utc);
+        IndexedNote::builder(
+            test_note_id(suffix),
+            format!("Note {}", suffix),
+            time,
+            time,
+            PathBuf::from(format!("{}.md", suffix)),
+            test_content_hash(),
+        )
+        .topics(topics.iter().map(|t| Topic::new(t).unwrap()).collect())
+        .tags(tags.iter().map(|t| Tag::new(t).unwrap()).collect())
+        .build()
+    }
+
+    #[test]
+    fn vault_overview_summarizes_all_notes() {
+        let notes = vec![
+            note("AA", &["rust"], &["draft"], "2024-01-10T00:00:00Z"),
+            note(
+                "BB",
+                &["rust", "go"],
+                &["draft", "idea"],
+                "2024-03-10T00:00:00Z",
+            ),
+            note("CC", &[], &[], "2024-03-20T00:00:00Z"),
+        ];
+        let mut links: HashMap<NoteId, Vec<Link>> = HashMap::new();
+        links.insert(
+            test_note_id("AA"),
+            vec![Link::new(test_note_id("BB"), vec!["parent", "see-also"]).unwrap()],
+        );
+        links.insert(
+            test_note_id("BB"),
+            vec![Link::new(test_note_id("BB"), vec!["see-also"]).unwrap()],
+        );
+        let words = HashMap::from([(test_note_id("AA"), 10), (test_note_id("BB"), 5)]);
+
+        let overview = vault_overview(&notes, &links, &words, true);
+
+        assert_eq!(overview.notes, 3);
+        assert_eq!(overview.words, 15);
+        assert_eq!(overview.average_words(), 5.0);
+        assert_eq!(
+            overview.topics,
+            vec![("rust".to_string(), 2), ("go".to_string(), 1)]
+        );
+        assert_eq!(
+            overview.tags,
+            vec![("draft".to_string(), 2), ("idea".to_string(), 1)]
+        );
+        assert_eq!(overview.unfiled, 1);
+        assert_eq!(overview.untagged, 1);
+        assert_eq!(overview.links, 2);
+        assert_eq!(
+            overview.rels,
+            vec![("see-also".to_string(), 2), ("parent".to_string(), 1)]
+        );
+        // Only CC has no links; BB's link to itself doesn't count
+        assert_eq!(overview.orphans, 1);
+        assert_eq!(
+            overview.months,
+            vec![((2024, 1), 1), ((2024, 2), 0), ((2024, 3), 2)]
+        );
+        let linked: Vec<(&str, usize)> = overview
+            .most_linked
+            .iter()
+            .map(|(n, count)| (n.title(), *count))
+            .collect();
+        assert_eq!(linked, vec![("Note BB", 1)]);
+    }
+
+    #[test]
+    fn vault_overview_of_empty_vault() {
+        let overview = vault_overview(&[], &HashMap::new(), &HashMap::new(), true);
+
+        assert_eq!(overview.notes, 0);
+        assert_eq!(overview.average_words(), 0.0);
+        assert!(overview.months.is_empty());
+        assert!(overview.most_linked.is_empty());
+    }
+}
+
+// ===========================================
+// archive --suggest tests
+// ===========================================
+
+mod archive_suggest_tests {
+    use super::*;
+    use crate::cli::ArchiveArgs;
+    use crate::cli::archive_policy::ArchivePolicy;
+    use crate::domain::{Link, Note};
+    use crate::infra::{ParsedNote, read_note};
+    use chrono::Months;
+    use tempfile::TempDir;
+
+    fn parsed(suffix: &str, months_old: u32, body: &str) -> ParsedNote {
+        parsed_with(suffix, months_old, body, |b| b)
+    }
+
+    fn parsed_with(
+        suffix: &str,
+        months_old: u32,
+        body: &str,
+        f: impl FnOnce(crate::domain::NoteBuilder) -> crate::domain::NoteBuilder,
+    ) -> ParsedNote {
+        let modified = test_datetime() - Months::new(months_old);
+        let note = f(Note::builder(
+            test_note_id(suffix),
+            format!("Note {}", suffix),
+            modified,
+            modified,
+        ))
+        .build()
+        .unwrap();
+        ParsedNote {
+            note,
+            body: body.to_string(),
+            body_line: 1,
+            content_hash: test_content_hash(),
+        }
+    }
+
+    fn suggested(notes: Vec<ParsedNote>, policy: &ArchivePolicy) -> Vec<String> {
+        let notes = notes
+            .into_iter()
+            .map(|p| (PathBuf::from(format!("{}.md", p.note.id())), p))
+            .collect();
+        archive_candidates(notes, policy, None, test_datetime())
+            .into_iter()
+            .map(|c| c.parsed.note.title().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn has_open_tasks_detects_unchecked_items() {
+        assert!(has_open_tasks("Intro\n- [ ] todo"));
+        assert!(has_open_tasks("  * [ ] nested"));
+        assert!(has_open_tasks("1. [ ] numbered"));
+        assert!(!has_open_tasks("- [x] done\n- plain item"));
+        assert!(!has_open_tasks("[ ] not a list item"));
+    }
+
+    #[test]
+    fn suggests_old_notes_oldest_first() {
+        let notes = vec![
+            parsed("0A", 13, ""),
+            parsed("0B", 24, ""),
+            parsed("0C", 2, ""),
+        ];
+        assert_eq!(
+            suggested(notes, &ArchivePolicy::default()),
+            vec!["Note 0B", "Note 0A"]
+        );
+    }
+
+    #[test]
+    fn skips_linked_tasked_and_archived_notes() {
+        let notes = vec![
+            parsed_with("0A", 24, "", |b| {
+                b.links(vec![
+                    Link::new(test_note_id("0B"), vec!["see-also"]).unwrap(),
+                ])
+            }),
+            parsed("0B", 24, ""),
+            parsed("0C", 24, "- [ ] follow up"),
+            parsed_with("0D", 24, "", |b| {
+                b.tags(vec![Tag::new("archived").unwrap()])
+            }),
+        ];
+        assert_eq!(suggested(notes, &ArchivePolicy::default()), vec!["Note 0A"]);
+    }
+
+    #[test]
+    fn topic_thresholds_override_default() {
+        let policy: ArchivePolicy = toml::from_str("[topics]\njournal = 1").unwrap();
+        let notes = vec![
+            parsed_with("0A", 3, "", |b| {
+                b.topics(vec![Topic::new("journal/2024").unwrap()])
+            }),
+            parsed("0B", 3, ""),
+        ];
+        assert_eq!(suggested(notes, &policy), vec!["Note 0A"]);
+    }
+
+    #[test]
+    fn apply_with_yes_archives_candidates() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("01HQ3K5M7N-old-note.md");
+        std::fs::write(
+            &path,
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9A\ntitle: Old Note\ncreated: 2020-01-15T10:30:00Z\nmodified: 2020-01-15T10:30:00Z\n---\nBody\n",
+        )
+        .unwrap();
+        let args = ArchiveArgs {
+            note: None,
+            suggest: true,
+            months: None,
+            apply: true,
+            yes: true,
+            format: OutputFormat::Json,
+        };
+
+        handle_archive(&args, dir.path(), &Config::default()).unwrap();
+
+        let parsed = read_note(&path).unwrap();
+        assert!(parsed.note.tags().contains(&Tag::new("archived").unwrap()));
+    }
+}
+
+// ===========================================
+// import generic tests
+// ===========================================
+
+mod import_tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    fn convert(source: &str, content: &str, flags: &[&str]) -> ImportedNote {
+        let flags: Vec<String> = flags.iter().map(|f| f.to_string()).collect();
+        let map = FieldMap::new(&BTreeMap::new(), &flags).unwrap();
+        convert_note(
+            Path::new(source),
+            content,
+            &map,
+            test_datetime(),
+            &SlugOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn field_map_parses_den_fields_extras_and_drops() {
+        let flags = vec![
+            "keywords=tags".to_string(),
+            "name=:title".to_string(),
+            "type=kind".to_string(),
+            "private=".to_string(),
+        ];
+        let map = FieldMap::new(&BTreeMap::new(), &flags).unwrap();
+
+        assert_eq!(map.target("keywords"), FieldTarget::Tags);
+        assert_eq!(map.target("name"), FieldTarget::Title);
+        assert_eq!(map.target("type"), FieldTarget::Extra("kind".to_string()));
+        assert_eq!(map.target("private"), FieldTarget::Drop);
+        assert_eq!(map.target("title"), FieldTarget::Title);
+        assert_eq!(map.target("id"), FieldTarget::Drop);
+        assert_eq!(
+            map.target("status"),
+            FieldTarget::Extra("status".to_string())
+        );
+    }
+
+    #[test]
+    fn field_map_flags_override_config() {
+        let configured = BTreeMap::from([("keywords".to_string(), "tags".to_string())]);
+        let flags = vec!["keywords=keywords".to_string()];
+        let map = FieldMap::new(&configured, &flags).unwrap();
+
+        assert_eq!(
+            map.target("keywords"),
+            FieldTarget::Extra("keywords".to_string())
+        );
+    }
+
+    #[test]
+    fn field_map_rejects_bad_mappings() {
+        for flag in ["keywords", "=tags", "x=:bogus", "x=id", "x=links"] {
+            let result = FieldMap::new(&BTreeMap::new(), &[flag.to_string()]);
+            assert!(result.is_err(), "expected '{}' to be rejected", flag);
+        }
+    }
+
+    #[test]
+    fn convert_maps_zettlr_frontmatter() {
+        let imported = convert(
+            "rust/ownership.md",
+            "---\ntitle: Ownership\nkeywords: [Rust, \"#memory\"]\nid: 20240115103000\nstatus: draft\n---\n\nBody text.\n",
+            &["keywords=tags"],
+        );
+
+        assert_eq!(imported.note.title(), "Ownership");
+        let tags: Vec<String> = imported.note.tags().iter().map(|t| t.to_string()).collect();
+        assert_eq!(tags, vec!["rust", "memory"]);
+        assert_eq!(imported.note.topics()[0].to_string(), "rust");
+        assert_eq!(
+            imported.note.extra().get("status"),
+            Some(&serde_yaml::Value::String("draft".to_string()))
+        );
+        assert!(!imported.note.extra().contains_key("id"));
+        assert_eq!(imported.body, "\nBody text.\n");
+        assert!(imported.warnings.is_empty());
+    }
+
+    #[test]
+    fn convert_reads_dendron_millisecond_timestamps() {
+        let imported = convert(
+            "notes.md",
+            "---\nid: abc123\ntitle: Daily\ndesc: A journal\ncreated: 1705314600000\nupdated: 1705318200000\n---\n",
+            &["desc=description", "updated=modified"],
+        );
+
+        assert_eq!(imported.note.description(), Some("A journal"));
+        assert_eq!(
+            imported.note.created().to_rfc3339(),
+            "2024-01-15T10:30:00+00:00"
+        );
+        assert_eq!(
+            imported.note.modified().to_rfc3339(),
+            "2024-01-15T11:30:00+00:00"
+        );
+    }
+
+    #[test]
+    fn convert_falls_back_to_heading_then_file_name() {
+        let imported = convert("foam note.md", "# From Heading\n\nText\n", &[]);
+        assert_eq!(imported.note.title(), "From Heading");
+        assert_eq!(imported.note.created(), test_datetime());
+        assert!(imported.note.topics().is_empty());
+
+        let imported = convert("foam note.md", "Just text\n", &[]);
+        assert_eq!(imported.note.title(), "foam note");
+        assert_eq!(imported.body, "Just text\n");
+    }
+
+    #[test]
+    fn convert_warns_about_values_it_cannot_import() {
+        let imported = convert(
+            "note.md",
+            "---\ntags: [ok, \"bad!tag\"]\ncreated: last tuesday\n---\n",
+            &[],
+        );
+
+        assert_eq!(imported.note.tags().len(), 1);
+        assert_eq!(imported.warnings.len(), 2);
+    }
+
+    #[test]
+    fn convert_rejects_invalid_yaml() {
+        let map = FieldMap::default();
+        let result = convert_note(
+            Path::new("note.md"),
+            "---\ntitle: [unclosed\n---\n",
+            &map,
+            test_datetime(),
+            &SlugOptions::default(),
+        );
+        assert!(result.is_err());
+    }
+}
+
+// ===========================================
+// migrate tests
+// ===========================================
+
+mod migrate_tests {
+    use super::*;
+    use crate::domain::Note;
+    use serde_yaml::Value;
+    use std::collections::BTreeMap;
+
+    fn note_with_extra(fields: &[(&str, Value)]) -> Note {
+        let extra = fields
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        Note::builder(test_note_id("AA"), "Note", test_datetime(), test_datetime())
+            .tags(vec![Tag::new("rust").unwrap()])
+            .extra(extra)
+            .build()
+            .unwrap()
+    }
+
+    fn renames(flags: &[&str]) -> BTreeMap<String, FieldTarget> {
+        let flags: Vec<String> = flags.iter().map(|f| f.to_string()).collect();
+        rename_map(&BTreeMap::new(), &flags).unwrap()
+    }
+
+    #[test]
+    fn rename_map_flags_override_config() {
+        let configured = BTreeMap::from([
+            ("keywords".to_string(), "tags".to_string()),
+            ("category".to_string(), "topics".to_string()),
+        ]);
+        let flags = vec!["keywords=".to_string()];
+        let map = rename_map(&configured, &flags).unwrap();
+
+        assert_eq!(map["keywords"], FieldTarget::Drop);
+        assert_eq!(map["category"], FieldTarget::Topics);
+    }
+
+    #[test]
+    fn rename_map_rejects_bad_renames() {
+        for flag in [
+            "keywords",
+            "=tags",
+            "x=:bogus",
+            "x=id",
+            "tags=keywords",
+            "x=x",
+        ] {
+            let result = rename_map(&BTreeMap::new(), &[flag.to_string()]);
+            assert!(result.is_err(), "expected '{}' to be rejected", flag);
+        }
+    }
+
+    #[test]
+    fn migrate_merges_list_values_into_den_fields() {
+        let note = note_with_extra(&[
+            ("keywords", Value::String("cli, #parsing".to_string())),
+            ("category", Value::String("software/tools".to_string())),
+        ]);
+        let result = migrate_note(&note, &renames(&["keywords=tags", "category=topics"]));
+
+        assert!(result.problems.is_empty());
+        let tags: Vec<String> = result.note.tags().iter().map(|t| t.to_string()).collect();
+        assert_eq!(tags, vec!["rust", "cli", "parsing"]);
+        assert_eq!(result.note.topics()[0].to_string(), "software/tools");
+        assert!(result.note.extra().is_empty());
+        assert_eq!(
+            result.changes,
+            vec![
+                KeyChange {
+                    from: "category".to_string(),
+                    to: Some("topics".to_string()),
+                },
+                KeyChange {
+                    from: "keywords".to_string(),
+                    to: Some("tags".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn migrate_renames_and_removes_extra_fields() {
+        let note = note_with_extra(&[
+            ("state", Value::String("draft".to_string())),
+            ("uuid", Value::String("abc".to_string())),
+            ("other", Value::Bool(true)),
+        ]);
+        let result = migrate_note(&note, &renames(&["state=status", "uuid="]));
+
+        assert!(result.problems.is_empty());
+        assert_eq!(
+            result.note.extra().get("status"),
+            Some(&Value::String("draft".to_string()))
+        );
+        assert!(!result.note.extra().contains_key("uuid"));
+        assert!(result.note.extra().contains_key("other"));
+        assert_eq!(result.changes[1].to, None);
+        assert_eq!(result.note.modified(), note.modified());
+    }
+
+    #[test]
+    fn migrate_reports_conflicts_and_invalid_values() {
+        let note = note_with_extra(&[
+            ("state", Value::String("draft".to_string())),
+            ("status", Value::String("done".to_string())),
+            ("keywords", Value::String("ok bad!tag".to_string())),
+            ("date", Value::String("someday".to_string())),
+        ]);
+        let result = migrate_note(
+            &note,
+            &renames(&["state=status", "keywords=tags", "date=created"]),
+        );
+
+        assert_eq!(result.problems.len(), 3);
+        assert!(result.problems.iter().any(|p| p.contains("bad!tag")));
+        assert!(result.problems.iter().any(|p| p.contains("'status'")));
+        assert!(result.problems.iter().any(|p| p.contains("timestamp")));
+    }
+
+    #[test]
+    fn migrate_leaves_notes_without_renamed_keys_alone() {
+        let note = note_with_extra(&[("status", Value::String("draft".to_string()))]);
+        let result = migrate_note(&note, &renames(&["keywords=tags"]));
+
+        assert!(result.changes.is_empty());
+        assert_eq!(result.note, note);
+    }
+}
+
+// ===========================================
+// list-broken tests
+// ===========================================
+
+mod list_broken_tests {
+    use super::*;
+    use crate::cli::handlers::BrokenTarget;
+    use crate::domain::{Link, NoteNames, Rel};
+    use std::collections::HashMap;
+
+    fn link(target: &str, rels: &[&str]) -> Link {
+        Link::new(test_note_id(target), rels.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn broken_links_lists_links_to_missing_notes_by_source_path() {
+        let notes = vec![
+            sample_indexed_note_with_topics("BB", "Second", vec![]),
+            sample_indexed_note_with_topics("AA", "First", vec![]),
+        ];
+        let links = HashMap::from([
+            (
+                test_note_id("AA"),
+                vec![link("BB", &["parent"]), link("ZZ", &["see-also", "parent"])],
+            ),
+            (test_note_id("BB"), vec![link("YY", &["next"])]),
+        ]);
+        let trashed = HashSet::from([test_note_id("YY")]);
+
+        let broken = broken_links(
+            &notes,
+            &links,
+            &HashMap::new(),
+            &NoteNames::new(),
+            &trashed,
+            None,
+        );
+
+        assert_eq!(broken.len(), 2);
+        assert_eq!(broken[0].source.title(), "First");
+        assert_eq!(
+            broken[0].target,
+            BrokenTarget::Note {
+                id: test_note_id("ZZ"),
+                trashed: false
+            }
+        );
+        assert_eq!(broken[0].rels, vec!["see-also", "parent"]);
+        assert_eq!(broken[1].source.title(), "Second");
+        assert_eq!(
+            broken[1].target,
+            BrokenTarget::Note {
+                id: test_note_id("YY"),
+                trashed: true
+            }
+        );
+    }
+
+    #[test]
+    fn broken_links_lists_wikilinks_that_resolve_to_no_single_note() {
+        let notes = vec![
+            sample_indexed_note_with_topics("AA", "First", vec![]),
+            sample_indexed_note_with_topics("BB", "Twin", vec![]),
+            sample_indexed_note_with_topics("CC", "Twin", vec![]),
+        ];
+        let mut names = NoteNames::new();
+        for note in &notes {
+            names.add(note.id(), note.title(), note.aliases());
+        }
+        let wikilinks = HashMap::from([(
+            test_note_id("AA"),
+            vec![
+                "Nowhere".to_string(),
+                "twin".to_string(),
+                "first".to_string(),
+            ],
+        )]);
+
+        let broken = broken_links(
+            &notes,
+            &HashMap::new(),
+            &wikilinks,
+            &names,
+            &HashSet::new(),
+            None,
+        );
+
+        assert_eq!(broken.len(), 2);
+        assert_eq!(
+            broken[0].target,
+            BrokenTarget::Wikilink {
+                target: "Nowhere".to_string(),
+                matches: 0
+            }
+        );
+        assert_eq!(broken[0].rels, vec!["mentions"]);
+        assert_eq!(
+            broken[1].target,
+            BrokenTarget::Wikilink {
+                target: "twin".to_string(),
+                matches: 2
+            }
+        );
+    }
+
+    #[test]
+    fn broken_links_filters_by_rel() {
+        let notes = vec![sample_indexed_note_with_topics("AA", "First", vec![])];
+        let links = HashMap::from([(
+            test_note_id("AA"),
+            vec![link("YY", &["next"]), link("ZZ", &["parent"])],
+        )]);
+        let rel = Rel::new("parent").unwrap();
+
+        let wikilinks = HashMap::from([(test_note_id("AA"), vec!["Nowhere".to_string()])]);
+
+        let broken = broken_links(
+            &notes,
+            &links,
+            &wikilinks,
+            &NoteNames::new(),
+            &HashSet::new(),
+            Some(&rel),
+        );
+
+        assert_eq!(broken.len(), 1);
+        assert_eq!(
+            broken[0].target,
+            BrokenTarget::Note {
+                id: test_note_id("ZZ"),
+                trashed: false
+            }
+        );
+    }
+}
+
+// ===========================================
+// config validate tests
+// ===========================================
+
+mod config_validate_tests {
+    use super::*;
+    use crate::domain::Severity;
+    use tempfile::TempDir;
+
+    fn only_issue(contents: &str) -> ConfigIssue {
+        let issues = validate_config(contents);
+        assert_eq!(issues.len(), 1, "expected one issue, got {:?}", issues);
+        issues.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let dir = TempDir::new().unwrap();
+        let contents = format!(
+            "default_vault = \"work\"\n\n[vaults]\nwork = \"{}\"\n\n[time]\nutc = true\n\n[import.map]\nkeywords = \"tags\"\n",
+            dir.path().display()
+        );
+
+        assert_eq!(validate_config(&contents), vec![]);
+    }
+
+    #[test]
+    fn reports_syntax_error_with_line() {
+        let issue = only_issue("editor = \"vim\"\n[time\nutc = true\n");
+
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.line, Some(2));
+    }
+
+    #[test]
+    fn reports_unknown_keys_as_warnings() {
+        let issues = validate_config(
+            "editor = \"vim\"\n\n[time]\nutcc = true\n\n[colours]\nrust = \"red\"\n",
+        );
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(issues[0].key, "time.utcc");
+        assert_eq!(issues[0].line, Some(4));
+        assert_eq!(issues[1].key, "colours");
+        assert_eq!(issues[1].line, Some(6));
+    }
+
+    #[test]
+    fn map_sections_accept_any_key() {
+        let issues =
+            validate_config("[tags.colors]\nanything = \"red\"\n\n[archive.topics]\nold = 3\n");
+
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn reports_type_error_with_key_and_line() {
+        let issue = only_issue("editor = \"vim\"\n\n[time]\nutc = \"yes\"\n");
+
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.line, Some(4));
+        assert_eq!(issue.key, "time.utc");
+        assert!(issue.message.contains("bool"), "{}", issue.message);
+    }
+
+    #[test]
+    fn reports_missing_vault_directory_and_unknown_default_vault() {
+        let issues = validate_config(
+            "default_vault = \"home\"\n\n[vaults]\nwork = \"/nonexistent/den-vault\"\n",
+        );
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].key, "default_vault");
+        assert_eq!(issues[0].line, Some(1));
+        assert!(issues[0].message.contains("no vault named 'home'"));
+        assert_eq!(issues[1].key, "vaults.work");
+        assert_eq!(issues[1].line, Some(4));
+        assert!(issues[1].message.contains("does not exist"));
+    }
+
+    #[test]
+    fn reports_invalid_time_format() {
+        let issue = only_issue("[time]\ndate_format = \"%Y-%Q\"\n");
+
+        assert_eq!(issue.key, "time.date_format");
+        assert_eq!(issue.line, Some(2));
+    }
+
+    #[test]
+    fn reports_invalid_import_mapping() {
+        let issue = only_issue("[import.map]\nkeywords = \":bogus\"\n");
+
+        assert_eq!(issue.key, "import.map.keywords");
+        assert_eq!(issue.line, Some(2));
+        assert!(
+            issue.message.contains("unknown den field"),
+            "{}",
+            issue.message
+        );
+    }
+}
+
+// ===========================================
+// append/prepend tests
+// ===========================================
+
+mod append_tests {
+    use super::*;
+
+    #[test]
+    fn append_to_empty_body() {
+        assert_eq!(
+            add_to_body("", "First entry", None, Placement::Append),
+            "First entry\n"
+        );
+    }
+
+    #[test]
+    fn append_and_prepend_without_heading() {
+        let body = "\nExisting text.\n\n";
+        assert_eq!(
+            add_to_body(body, "New", None, Placement::Append),
+            "Existing text.\n\nNew\n"
+        );
+        assert_eq!(
+            add_to_body(body, "New", None, Placement::Prepend),
+            "New\n\nExisting text.\n"
+        );
+    }
+
+    #[test]
+    fn append_under_heading_stops_at_next_section() {
+        let body = "Intro\n\n## Log\n\n- one\n\n## Ideas\n\n- idea\n";
+        assert_eq!(
+            add_to_body(body, "- two", Some("Log"), Placement::Append),
+            "Intro\n\n## Log\n\n- one\n\n- two\n\n## Ideas\n\n- idea\n"
+        );
+    }
+
+    #[test]
+    fn prepend_under_heading_goes_after_heading() {
+        let body = "## Log\n\n- one\n";
+        assert_eq!(
+            add_to_body(body, "- zero", Some("log"), Placement::Prepend),
+            "## Log\n\n- zero\n\n- one\n"
+        );
+    }
+
+    #[test]
+    fn append_under_heading_includes_subsections() {
+        let body = "## Log\n\n- one\n\n### Details\n\nMore\n\n## Next\n";
+        assert_eq!(
+            add_to_body(body, "- two", Some("Log"), Placement::Append),
+            "## Log\n\n- one\n\n### Details\n\nMore\n\n- two\n\n## Next\n"
+        );
+    }
+
+    #[test]
+    fn missing_heading_is_created_at_end() {
+        assert_eq!(
+            add_to_body("Intro\n", "- one", Some("Log"), Placement::Prepend),
+            "Intro\n\n## Log\n\n- one\n"
+        );
+    }
+
+    #[test]
+    fn headings_in_code_blocks_are_ignored() {
+        let body = "```md\n## Log\n```\n";
+        assert_eq!(
+            add_to_body(body, "- one", Some("Log"), Placement::Append),
+            "```md\n## Log\n```\n\n## Log\n\n- one\n"
+        );
+    }
+}
+
+// ===========================================
+// index tokenizer tests
+// ===========================================
+
+mod index_tokenizer_tests {
+    use crate::cli::IndexArgs;
+    use crate::cli::config::Config;
+    use crate::cli::handlers::{handle_index, index_db_path};
+    use crate::cli::output::OutputFormat;
+    use crate::index::{IndexBuilder, IndexRepository, SqliteIndex, Tokenizer};
+    use tempfile::TempDir;
+
+    fn index_args(full: bool) -> IndexArgs {
+        IndexArgs {
+            full,
+            stats: false,
+            vacuum: false,
+            watch: false,
+            format: OutputFormat::Json,
+        }
+    }
+
+    fn vault() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("training.md"),
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: Training log\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\n",
+        )
+        .unwrap();
+        dir
+    }
+
+    fn tokenizer(dir: &TempDir) -> Tokenizer {
+        SqliteIndex::open(&index_db_path(dir.path()))
+            .unwrap()
+            .tokenizer()
+            .unwrap()
+    }
+
+    #[test]
+    fn first_index_uses_configured_tokenizer() {
+        let dir = vault();
+        let config: Config = toml::from_str("[index]\ntokenizer = \"porter\"").unwrap();
+
+        handle_index(&index_args(false), dir.path(), false, &config).unwrap();
+        assert_eq!(tokenizer(&dir), Tokenizer::Porter);
+    }
+
+    #[test]
+    fn full_rebuild_switches_tokenizer() {
+        let dir = vault();
+        let mut index = SqliteIndex::open(&index_db_path(dir.path())).unwrap();
+        IndexBuilder::new(dir.path().to_path_buf())
+            .incremental_update(&mut index)
+            .unwrap();
+        let config: Config = toml::from_str("[index]\ntokenizer = \"trigram\"").unwrap();
+
+        handle_index(&index_args(false), dir.path(), false, &config).unwrap();
+        assert_eq!(tokenizer(&dir), Tokenizer::Unicode61);
+
+        handle_index(&index_args(true), dir.path(), false, &config).unwrap();
+        assert_eq!(tokenizer(&dir), Tokenizer::Trigram);
+        let index = SqliteIndex::open(&index_db_path(dir.path())).unwrap();
+        assert_eq!(index.quick_search("rainin", 10, false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn any_update_applies_language_tokenizers() {
+        let dir = vault();
+        std::fs::write(
+            dir.path().join("tenki.md"),
+            "---\nid: 01HQ4A2R9PXJK4QZPW8V2R6T9Z\ntitle: 東京の天気\nlang: ja\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\n",
+        )
+        .unwrap();
+        handle_index(&index_args(false), dir.path(), false, &Config::default()).unwrap();
+        let config: Config = toml::from_str("[index.languages]\nja = \"trigram\"").unwrap();
+
+        handle_index(&index_args(false), dir.path(), false, &config).unwrap();
+        let index = SqliteIndex::open(&index_db_path(dir.path())).unwrap();
+        assert_eq!(tokenizer(&dir), Tokenizer::Unicode61);
+        assert_eq!(index.language_tokenizers().unwrap(), config.index.languages);
+        assert_eq!(index.search("の天気").unwrap().len(), 1);
+    }
+}
+
+// ===========================================
+// index watch tests
+// ===========================================
+
+mod index_watch_tests {
+    use super::*;
+    use crate::infra::{IgnoreRules, ScanOptions};
+    use std::collections::BTreeSet;
+    use tempfile::TempDir;
+
+    fn changed(dir: &TempDir, paths: &[&str], indexed: &[&str]) -> Vec<PathBuf> {
+        let changed: BTreeSet<PathBuf> = paths.iter().map(PathBuf::from).collect();
+        let indexed: Vec<PathBuf> = indexed.iter().map(PathBuf::from).collect();
+        let mut rules = IgnoreRules::new();
+        rules.add("drafts/").unwrap();
+        changed_note_paths(
+            &changed,
+            dir.path(),
+            &indexed,
+            &ScanOptions::default(),
+            &rules,
+        )
+    }
+
+    #[test]
+    fn changed_note_paths_keeps_notes_and_drops_other_files() {
+        let dir = TempDir::new().unwrap();
+
+        let paths = changed(
+            &dir,
+            &["a.md", "b.txt", "drafts/c.md", "gone.md"],
+            &["gone.md"],
+        );
+
+        assert_eq!(paths, vec![PathBuf::from("a.md"), PathBuf::from("gone.md")]);
+    }
+
+    #[test]
+    fn changed_note_paths_expands_renamed_directories() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("new/deep")).unwrap();
+        std::fs::write(dir.path().join("new/deep/a.md"), "").unwrap();
+        std::fs::write(dir.path().join("new/notes.txt"), "").unwrap();
+
+        let paths = changed(&dir, &["old", "new"], &["old/deep/a.md", "other.md"]);
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("new/deep/a.md"),
+                PathBuf::from("old/deep/a.md")
+            ]
+        );
+    }
+}
+
+// ===========================================
+// recent session tests
+// ===========================================
+
+mod recent_session_tests {
+    use super::*;
+    use crate::cli::handlers::group_sessions;
+    use chrono::Duration;
+
+    fn edited(id_suffix: &str, minutes: i64) -> IndexedNote {
+        let modified = test_datetime() + Duration::minutes(minutes);
+        IndexedNote::builder(
+            test_note_id(id_suffix),
+            id_suffix,
+            test_datetime(),
+            modified,
+            PathBuf::from(format!("{}.md", id_suffix)),
+            test_content_hash(),
+        )
+        .build()
+    }
+
+    #[test]
+    fn group_sessions_splits_on_long_gaps() {
+        let notes = vec![
+            edited("AA", 0),
+            edited("DD", 300),
+            edited("BB", 50),
+            edited("CC", 110),
+        ];
+
+        let titles = |gap: i64| -> Vec<Vec<String>> {
+            group_sessions(&notes, Duration::minutes(gap))
+                .iter()
+                .map(|s| s.iter().map(|n| n.title().to_string()).collect())
+                .collect()
+        };
+        assert_eq!(titles(60), [vec!["DD"], vec!["CC", "BB", "AA"],]);
+        assert_eq!(titles(55).len(), 3);
+        assert!(group_sessions(&[], Duration::minutes(60)).is_empty());
+    }
+}
+
+// ===========================================
+// sync-config profile tests
+// ===========================================
+
+mod sync_config_tests {
+    use crate::cli::config::Config;
+    use crate::cli::handlers::{export_profile, import_profile};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    const TEAM_CONFIG: &str = r#"default_vault = "work"
+
+[vaults]
+work = "/home/me/work"
+
+# Meetings need a date
+[types.meeting]
+template = "templates/meeting.md"
+required = ["date"]
+
+[rels]
+reciprocal = [["parent", "child"]]
+
+[output]
+theme = "minimal"
+"#;
+
+    #[test]
+    fn export_keeps_only_profile_sections_and_embeds_templates() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("templates")).unwrap();
+        std::fs::write(
+            dir.path().join("templates/meeting.md"),
+            "## Attendees\n\n## Notes\n",
+        )
+        .unwrap();
+
+        let bundle = export_profile(TEAM_CONFIG, dir.path()).unwrap();
+
+        assert!(bundle.contains("# Meetings need a date"));
+        assert!(!bundle.contains("vaults"));
+        assert!(!bundle.contains("theme"));
+        let parsed: Config = toml::from_str(bundle.split("[templates]").next().unwrap()).unwrap();
+        assert_eq!(
+            parsed.types.required_fields()["meeting"],
+            vec!["date".to_string()]
+        );
+        let table: toml::Table = toml::from_str(&bundle).unwrap();
+        assert_eq!(
+            table["templates"]["templates/meeting.md"].as_str(),
+            Some("## Attendees\n\n## Notes\n")
+        );
+    }
+
+    #[test]
+    fn export_fails_when_a_template_is_missing() {
+        let dir = TempDir::new().unwrap();
+        assert!(export_profile(TEAM_CONFIG, dir.path()).is_err());
+    }
+
+    #[test]
+    fn import_replaces_profile_sections_and_keeps_the_rest() {
+        let personal = r#"default_vault = "work"
+
+# my own colors
+[tags.colors]
+urgent = "red"
+
+[rels]
+reciprocal = [["next", "prev"]]
+
+[output]
+theme = "plain"
+"#;
+        let bundle = r#"[rels]
+reciprocal = [["parent", "child"]]
+
+[check]
+max_words = 5000
+
+[templates]
+"templates/meeting.md" = "- Agenda\n"
+"#;
+
+        let profile = import_profile(personal, bundle).unwrap();
+
+        assert_eq!(profile.sections, vec!["rels", "check"]);
+        assert_eq!(
+            profile.templates,
+            vec![(
+                PathBuf::from("templates/meeting.md"),
+                "- Agenda\n".to_string()
+            )]
+        );
+        assert!(profile.config.contains("# my own colors"));
+        let config: Config = toml::from_str(&profile.config).unwrap();
+        assert_eq!(config.default_vault.as_deref(), Some("work"));
+        assert_eq!(config.check.max_words, 5000);
+        assert_eq!(config.rels.reciprocal.len(), 1);
+        assert!(!profile.config.contains("next"));
+        assert!(profile.config.contains("theme = \"plain\""));
+    }
+
+    #[test]
+    fn import_rejects_bundles_beyond_the_profile() {
+        let cases = [
+            "[vaults]\nwork = \"/tmp\"\n",
+            "[check]\nmax_words = \"many\"\n",
+            "[templates]\n\"../outside.md\" = \"x\"\n",
+            "[templates]\n\"/etc/passwd\" = \"x\"\n",
+        ];
+        for bundle in cases {
+            assert!(import_profile("", bundle).is_err(), "accepted {}", bundle);
+        }
+    }
+}
+
+// ===========================================
+// trash retention tests
+// ===========================================
+
+mod trash_retention_tests {
+    use crate::cli::config::Config;
+    use crate::cli::handlers::{confirmed, move_to_trash, purge_trash, sweep_trash};
+    use crate::index::SqliteIndex;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn trash_file(dir: &TempDir, name: &str) -> PathBuf {
+        let trash = dir.path().join(".trash");
+        std::fs::create_dir_all(&trash).unwrap();
+        std::fs::write(trash.join(name), "---\ntitle: Gone\n---\n").unwrap();
+        PathBuf::from(".trash").join(name)
+    }
+
+    #[test]
+    fn purge_deletes_only_notes_trashed_before_the_cutoff() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+
+        let old = trash_file(&dir, "old.md");
+        purge_trash(dir.path(), &mut index, Some(start), start, false).unwrap();
+        let recent = trash_file(&dir, "recent.md");
+        let now = start + Duration::days(20);
+
+        let cutoff = Some(now - Duration::days(10));
+        assert_eq!(
+            purge_trash(dir.path(), &mut index, cutoff, now, true).unwrap(),
+            vec![old.clone()]
+        );
+        assert!(dir.path().join(&old).exists());
+
+        assert_eq!(
+            purge_trash(dir.path(), &mut index, cutoff, now, false).unwrap(),
+            vec![old.clone()]
+        );
+        assert!(!dir.path().join(&old).exists());
+        assert!(dir.path().join(&recent).exists());
+
+        assert_eq!(
+            purge_trash(dir.path(), &mut index, None, now, false).unwrap(),
+            vec![recent.clone()]
+        );
+        assert!(!dir.path().join(&recent).exists());
+    }
+
+    #[test]
+    fn move_to_trash_keeps_path_and_numbers_taken_names() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/note.md"), "first").unwrap();
+
+        let first = move_to_trash(dir.path(), Path::new("sub/note.md")).unwrap();
+        assert_eq!(first, PathBuf::from(".trash/sub/note.md"));
+
+        std::fs::write(dir.path().join("sub/note.md"), "second").unwrap();
+        let second = move_to_trash(dir.path(), Path::new("sub/note.md")).unwrap();
+        assert_eq!(second, PathBuf::from(".trash/sub/note-2.md"));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(&first)).unwrap(),
+            "first"
+        );
+        assert!(!dir.path().join("sub/note.md").exists());
+
+        // Only the numbered name needs its original path recorded
+        assert!(!dir.path().join(".trash/sub/note.md.origin").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".trash/sub/note-2.md.origin")).unwrap(),
+            "sub/note.md\n"
+        );
+        let now = Utc::now();
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        purge_trash(dir.path(), &mut index, None, now, false).unwrap();
+        assert!(!dir.path().join(".trash/sub/note-2.md.origin").exists());
+    }
+
+    #[test]
+    fn confirmed_accepts_only_yes() {
+        assert!(confirmed("y\n".as_bytes()).unwrap());
+        assert!(confirmed(" YES \n".as_bytes()).unwrap());
+        assert!(!confirmed("n\n".as_bytes()).unwrap());
+        assert!(!confirmed("\n".as_bytes()).unwrap());
+        assert!(!confirmed("".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn sweep_applies_retention_days() {
+        let dir = TempDir::new().unwrap();
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 9, 0, 0).unwrap();
+        let note = trash_file(&dir, "note.md");
+
+        // Without a retention period the trash is only recorded
+        let mut config = Config::default();
+        let within = start + Duration::days(29);
+        assert!(
+            sweep_trash(dir.path(), &mut index, &config, start)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(
+            sweep_trash(dir.path(), &mut index, &config, within)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(dir.path().join(&note).exists());
+
+        config.trash.retention_days = Some(30);
+        assert!(
+            sweep_trash(dir.path(), &mut index, &config, within)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            sweep_trash(dir.path(), &mut index, &config, start + Duration::days(31)).unwrap(),
+            vec![note.clone()]
+        );
+        assert!(!dir.path().join(&note).exists());
+    }
+}
+
+mod bench_tests {
+    use super::*;
+    use crate::cli::handlers::{BenchReport, BenchResult, compare, generate_vault};
+    use crate::index::{IndexBuilder, SqliteIndex};
+
+    fn report(medians: &[(&str, f64)]) -> BenchReport {
+        BenchReport {
+            version: "0.1.0".to_string(),
+            notes: 100,
+            words: 200,
+            runs: 5,
+            results: medians
+                .iter()
+                .map(|(name, median)| BenchResult {
+                    name: name.to_string(),
+                    median_ms: *median,
+                    min_ms: *median,
+                    per_second: 0.0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn generated_vault_indexes_every_note_with_links() {
+        let dir = tempfile::tempdir().unwrap();
+        generate_vault(dir.path(), 25, 120).unwrap();
+
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        let result = IndexBuilder::new(dir.path().to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+        assert_eq!(result.indexed, 25);
+        assert!(result.errors.is_empty());
+        assert_eq!(index.list_all().unwrap().len(), 25);
+        assert!(index.all_links().unwrap().len() >= 24);
+    }
+
+    #[test]
+    fn generated_vault_is_deterministic() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        generate_vault(a.path(), 5, 60).unwrap();
+        generate_vault(b.path(), 5, 60).unwrap();
+
+        let mut files: Vec<_> = std::fs::read_dir(a.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        files.sort();
+        assert_eq!(files.len(), 5);
+        for file in files {
+            assert_eq!(
+                std::fs::read(a.path().join(&file)).unwrap(),
+                std::fs::read(b.path().join(&file)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn compare_flags_slowdowns_beyond_threshold() {
+        let baseline = report(&[("full_rebuild", 100.0), ("search", 10.0), ("ls", 5.0)]);
+        let current = report(&[("full_rebuild", 130.0), ("search", 11.0), ("new", 1.0)]);
+
+        let comparison = compare(&current, &baseline, 20.0);
+        assert_eq!(comparison.len(), 2);
+        assert_eq!(comparison[0].name, "full_rebuild");
+        assert!((comparison[0].change_percent - 30.0).abs() < 1e-9);
+        assert!(comparison[0].regression);
+        assert_eq!(comparison[1].name, "search");
+        assert!(!comparison[1].regression);
+    }
+}
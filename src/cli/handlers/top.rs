@@ -0,0 +1,157 @@
+//! Top command handler: notes ranked by backlinks, links or tags.
+
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::list::notes_matching;
+use super::resolve::note_label;
+use super::{ARCHIVED_TAG, index_db_path};
+use crate::cli::config::{Config, NoteRefStyle};
+use crate::cli::output::{Column, Output, OutputFormat, Painter, Role, Table, TopListing, page};
+use crate::cli::{TopArgs, TopMeasure};
+use crate::domain::{Link, NoteId, Tag};
+use crate::index::{IndexedNote, SqliteIndex};
+
+impl TopMeasure {
+    /// Names what is counted, for messages.
+    fn label(self) -> &'static str {
+        match self {
+            TopMeasure::Backlinks => "backlinks",
+            TopMeasure::Links => "links",
+            TopMeasure::Tags => "tags",
+        }
+    }
+
+    /// Header of the count column.
+    fn header(self) -> &'static str {
+        match self {
+            TopMeasure::Backlinks => "Backlinks",
+            TopMeasure::Links => "Links",
+            TopMeasure::Tags => "Tags",
+        }
+    }
+}
+
+/// Ranks notes by `measure`, highest first with ties by title, leaving out
+/// notes with none.
+///
+/// `links` holds the links of every note in the vault, by source, so
+/// backlinks from notes outside `notes` count too. Links from a note to
+/// itself don't count, and several links between the same notes count once.
+fn rank_notes<'a>(
+    notes: &'a [IndexedNote],
+    links: &HashMap<NoteId, Vec<Link>>,
+    measure: TopMeasure,
+) -> Vec<(&'a IndexedNote, usize)> {
+    let counts: HashMap<&NoteId, usize> = match measure {
+        TopMeasure::Backlinks => {
+            let mut backlinks: HashMap<&NoteId, HashSet<&NoteId>> = HashMap::new();
+            for (source, targets) in links {
+                for link in targets.iter().filter(|l| l.target() != source) {
+                    backlinks.entry(link.target()).or_default().insert(source);
+                }
+            }
+            backlinks
+                .into_iter()
+                .map(|(target, sources)| (target, sources.len()))
+                .collect()
+        }
+        TopMeasure::Links => links
+            .iter()
+            .map(|(source, targets)| {
+                let targets: HashSet<&NoteId> = targets
+                    .iter()
+                    .map(Link::target)
+                    .filter(|target| *target != source)
+                    .collect();
+                (source, targets.len())
+            })
+            .collect(),
+        TopMeasure::Tags => notes.iter().map(|n| (n.id(), n.tags().len())).collect(),
+    };
+
+    let mut ranked: Vec<(&IndexedNote, usize)> = notes
+        .iter()
+        .map(|note| (note, counts.get(note.id()).copied().unwrap_or(0)))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    ranked.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.title().cmp(b.title())));
+    ranked
+}
+
+pub fn handle_top(args: &TopArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let mut notes = notes_matching(&index, args.topic.as_deref(), &args.tags)?;
+    if !args.include_archived {
+        let archived_tag = Tag::new(ARCHIVED_TAG).expect("archived is a valid tag");
+        notes.retain(|n| !n.tags().contains(&archived_tag));
+    }
+    let links = index.all_links().with_context(|| "failed to query links")?;
+
+    let mut ranked = rank_notes(&notes, &links, args.by);
+    ranked.truncate(args.limit);
+
+    match args.format {
+        OutputFormat::Json => {
+            let listings: Vec<TopListing> = ranked
+                .iter()
+                .enumerate()
+                .map(|(i, (note, count))| TopListing {
+                    rank: i + 1,
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    path: note.path().to_string_lossy().to_string(),
+                    count: *count,
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for (note, _) in &ranked {
+                println!("{}", notes_dir.join(note.path()).display());
+            }
+        }
+        OutputFormat::Human => {
+            if ranked.is_empty() {
+                println!("No notes with {}.", args.by.label());
+                return Ok(());
+            }
+            let header = match config.note_refs {
+                NoteRefStyle::Id => "ID",
+                NoteRefStyle::Ref | NoteRefStyle::Both => "Ref",
+            };
+            let painter = Painter::new(&config.output);
+            let mut table = Table::new(vec![
+                Column::right("#").role(Role::Muted),
+                Column::left(header).role(Role::Id),
+                Column::left("Title").max_width(50).shrink(),
+                Column::right(args.by.header()),
+            ]);
+            for (i, (note, count)) in ranked.iter().enumerate() {
+                table.row(vec![
+                    (i + 1).to_string(),
+                    note_label(note, config.note_refs, &config.slug),
+                    note.title().to_string(),
+                    count.to_string(),
+                ]);
+            }
+
+            let mut out = table.render(&painter);
+            writeln!(out)?;
+            writeln!(
+                out,
+                "Top {} of {} note(s) by {}",
+                ranked.len(),
+                notes.len(),
+                args.by.label()
+            )?;
+            page(&out, &config.output)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Topics mv handler: renaming or merging a topic across the vault.
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use crate::cli::TopicMvArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{Output, OutputFormat};
+use crate::domain::{Note, Topic};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{Journal, read_note, scan_notes_directory_with, serialize_with};
+
+/// Returns `topics` with `from` renamed to `to`, and with it every subtopic
+/// of `from` if `descendants` is set; `None` if no topic matched (pure
+/// function, no I/O).
+///
+/// Topics that end up the same are kept once, where they first appear.
+pub(crate) fn move_topics(
+    topics: &[Topic],
+    from: &Topic,
+    to: &Topic,
+    descendants: bool,
+) -> Option<Vec<Topic>> {
+    let mut moved = false;
+    let mut result: Vec<Topic> = Vec::new();
+    for topic in topics {
+        let topic = if topic == from {
+            moved = true;
+            to.clone()
+        } else if descendants && from.is_ancestor_of(topic) {
+            moved = true;
+            let rest = &topic.segments()[from.depth()..];
+            Topic::new(&format!("{}/{}", to, rest.join("/")))
+                .expect("segments of valid topics form a valid topic")
+        } else {
+            topic.clone()
+        };
+        if !result.contains(&topic) {
+            result.push(topic);
+        }
+    }
+    moved.then_some(result)
+}
+
+/// A note whose topics were moved, for JSON output.
+#[derive(Debug, Serialize)]
+struct MovedTopicsListing {
+    id: String,
+    title: String,
+    path: String,
+    topics: Vec<String>,
+}
+
+pub(super) fn handle_topic_mv(args: &TopicMvArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if !notes_dir.exists() {
+        bail!("notes directory does not exist: {}", notes_dir.display());
+    }
+
+    let descendants = args.old.trim().ends_with('/');
+    let from = Topic::new(&args.old).with_context(|| format!("invalid topic: {}", args.old))?;
+    let to = Topic::new(&args.new).with_context(|| format!("invalid topic: {}", args.new))?;
+    if from == to {
+        bail!("'{}' is moved to itself", from);
+    }
+
+    let mut paths: Vec<PathBuf> = scan_notes_directory_with(notes_dir, &config.scan)?.collect();
+    paths.sort();
+
+    let now = Utc::now();
+    let mut moved: Vec<(PathBuf, Note, String)> = Vec::new();
+    for path in paths {
+        let parsed = match read_note(&notes_dir.join(&path)) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                eprintln!("warning: skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let note = parsed.note;
+        let Some(topics) = move_topics(note.topics(), &from, &to, descendants) else {
+            continue;
+        };
+        let note = Note::builder(note.id().clone(), note.title(), note.created(), now)
+            .description(note.description().map(String::from))
+            .topics(topics)
+            .aliases(note.aliases().to_vec())
+            .tags(note.tags().to_vec())
+            .links(note.links().to_vec())
+            .extra(note.extra().clone())
+            .build()?;
+        moved.push((path, note, parsed.body));
+    }
+
+    if !args.dry_run && !moved.is_empty() {
+        let mut journal = Journal::new("topics-mv", now);
+        for (path, note, body) in &moved {
+            let contents = serialize_with(note, body, &config.frontmatter);
+            journal.record(notes_dir, path, Some(contents))?;
+        }
+        journal
+            .run(notes_dir)
+            .with_context(|| "failed to write notes")?;
+
+        let db_path = index_db_path(notes_dir);
+        let mut index = SqliteIndex::open(&db_path)
+            .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+        let changed: Vec<PathBuf> = moved.iter().map(|(path, _, _)| path.clone()).collect();
+        IndexBuilder::new(notes_dir.to_path_buf())
+            .with_scan_options(config.scan.clone())
+            .update_paths(&mut index, &changed)
+            .with_context(|| "failed to update index; run `notes index` to rebuild it")?;
+    }
+
+    match args.format {
+        OutputFormat::Human => {
+            for (path, note, _) in &moved {
+                let topics: Vec<String> = note.topics().iter().map(|t| t.to_string()).collect();
+                println!("{}: {}", path.display(), topics.join(", "));
+            }
+            let verb = if args.dry_run { "Would move" } else { "Moved" };
+            let what = if descendants {
+                format!("{}/", from)
+            } else {
+                from.to_string()
+            };
+            println!("{} '{}' to '{}' in {} note(s)", verb, what, to, moved.len());
+        }
+        OutputFormat::Json => {
+            let listing: Vec<MovedTopicsListing> = moved
+                .iter()
+                .map(|(path, note, _)| MovedTopicsListing {
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    path: notes_dir.join(path).to_string_lossy().to_string(),
+                    topics: note.topics().iter().map(|t| t.to_string()).collect(),
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Paths => {
+            for (path, _, _) in &moved {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+
+    Ok(())
+}
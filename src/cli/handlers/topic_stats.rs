@@ -0,0 +1,388 @@
+//! Topics stats handler: a report on one topic subtree.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::index_db_path;
+use super::resolve::note_label;
+use super::stats::{Month, format_month, month_of, usage_by_month};
+use crate::cli::TopicStatsArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Column, Output, OutputFormat, Painter, RankedNoteListing, Role, SubtopicListing, Table,
+    TagListing, TopicMonthListing, TopicStatsListing, page,
+};
+use crate::domain::{Link, NoteId, Topic};
+use crate::index::{IndexRepository, IndexedNote, SqliteIndex};
+
+/// Statistics on the notes filed under a topic or its descendants.
+#[derive(Debug)]
+pub(crate) struct TopicReport<'a> {
+    pub notes: usize,
+    /// Notes filed directly under the topic
+    pub direct: usize,
+    /// Every month from the first to the last note, with notes created in each
+    pub months: Vec<(Month, usize)>,
+    /// Tags with their note counts, most used first; ties by name
+    pub tags: Vec<(String, usize)>,
+    /// Notes with links from other notes, most linked first
+    pub most_linked: Vec<(&'a IndexedNote, usize)>,
+    /// Notes without links to or from other notes
+    pub orphans: usize,
+    /// Notes not modified within the stale period
+    pub stale: usize,
+    /// All notes, least recently modified first
+    pub stalest: Vec<&'a IndexedNote>,
+    /// Direct subtopics with their note counts, largest first; ties by path
+    pub subtopics: Vec<(Topic, usize)>,
+}
+
+impl TopicReport<'_> {
+    /// How evenly notes spread over the subtopics: the normalized entropy of
+    /// their note counts, from 0 (all in one) to 1 (even).
+    pub fn balance(&self) -> Option<f64> {
+        if self.subtopics.len() < 2 {
+            return None;
+        }
+        let total: usize = self.subtopics.iter().map(|(_, n)| n).sum();
+        let entropy: f64 = self
+            .subtopics
+            .iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(_, n)| {
+                let p = *n as f64 / total as f64;
+                -p * p.ln()
+            })
+            .sum();
+        Some(entropy / (self.subtopics.len() as f64).ln())
+    }
+}
+
+/// Maps each note to the other notes linking to it.
+pub(super) fn backlink_sources(
+    links: &HashMap<NoteId, Vec<Link>>,
+) -> HashMap<&NoteId, HashSet<&NoteId>> {
+    let mut backlinks: HashMap<&NoteId, HashSet<&NoteId>> = HashMap::new();
+    for (source, targets) in links {
+        for link in targets.iter().filter(|l| l.target() != source) {
+            backlinks.entry(link.target()).or_default().insert(source);
+        }
+    }
+    backlinks
+}
+
+/// Builds the report for `topic` from the notes in its subtree.
+///
+/// `links` holds the links of every note in the vault, by source, so links
+/// from notes outside the topic count too.
+pub(crate) fn topic_report<'a>(
+    topic: &Topic,
+    notes: &'a [IndexedNote],
+    links: &HashMap<NoteId, Vec<Link>>,
+    utc: bool,
+    stale_before: DateTime<Utc>,
+) -> TopicReport<'a> {
+    let backlinks = backlink_sources(links);
+
+    let entries: Vec<(Month, Vec<String>)> = notes
+        .iter()
+        .map(|n| (month_of(n.created(), utc), vec![topic.to_string()]))
+        .collect();
+    let usage = usage_by_month(&entries);
+    let months = match usage.series.first() {
+        Some(series) => usage
+            .months
+            .iter()
+            .copied()
+            .zip(series.counts.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut tags: BTreeMap<String, usize> = BTreeMap::new();
+    for tag in notes.iter().flat_map(|n| n.tags()) {
+        *tags.entry(tag.to_string()).or_default() += 1;
+    }
+    let mut tags: Vec<(String, usize)> = tags.into_iter().collect();
+    tags.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let mut most_linked: Vec<(&IndexedNote, usize)> = notes
+        .iter()
+        .map(|n| (n, backlinks.get(n.id()).map_or(0, HashSet::len)))
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    most_linked.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.title().cmp(b.title())));
+
+    let orphans = notes
+        .iter()
+        .filter(|n| {
+            !backlinks.contains_key(n.id())
+                && links
+                    .get(n.id())
+                    .is_none_or(|l| l.iter().all(|link| link.target() == n.id()))
+        })
+        .count();
+
+    let mut stalest: Vec<&IndexedNote> = notes.iter().collect();
+    stalest.sort_by_key(|n| n.modified());
+    let stale = stalest
+        .iter()
+        .take_while(|n| n.modified() < stale_before)
+        .count();
+
+    let mut subtopics: HashMap<Topic, usize> = HashMap::new();
+    let mut direct = 0;
+    for note in notes {
+        let children: HashSet<Topic> = note
+            .topics()
+            .iter()
+            .filter(|t| topic.is_ancestor_of(t))
+            .filter_map(|t| Topic::new(&t.segments()[..=topic.depth()].join("/")).ok())
+            .collect();
+        for child in children {
+            *subtopics.entry(child).or_default() += 1;
+        }
+        if note.topics().contains(topic) {
+            direct += 1;
+        }
+    }
+    let mut subtopics: Vec<(Topic, usize)> = subtopics.into_iter().collect();
+    subtopics.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.to_string().cmp(&b.to_string())));
+
+    TopicReport {
+        notes: notes.len(),
+        direct,
+        months,
+        tags,
+        most_linked,
+        orphans,
+        stale,
+        stalest,
+        subtopics,
+    }
+}
+
+pub(super) fn ranked_listing(note: &IndexedNote) -> RankedNoteListing {
+    RankedNoteListing {
+        id: note.id().to_string(),
+        title: note.title().to_string(),
+        path: note.path().to_string_lossy().to_string(),
+        backlinks: None,
+        modified: None,
+    }
+}
+
+/// Months with their running totals, the last `limit` only (0 keeps all).
+pub(super) fn month_listings(months: &[(Month, usize)], limit: usize) -> Vec<TopicMonthListing> {
+    let mut total = 0;
+    let mut months: Vec<TopicMonthListing> = months
+        .iter()
+        .map(|(month, created)| {
+            total += created;
+            TopicMonthListing {
+                month: format_month(*month),
+                created: *created,
+                total,
+            }
+        })
+        .collect();
+    if limit > 0 && months.len() > limit {
+        months.drain(..months.len() - limit);
+    }
+    months
+}
+
+fn render_report(
+    topic: &Topic,
+    report: &TopicReport,
+    args: &TopicStatsArgs,
+    config: &Config,
+) -> Result<String> {
+    let painter = Painter::new(&config.output);
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Topic {}: {} note(s), {} filed directly",
+        topic, report.notes, report.direct
+    )?;
+
+    writeln!(out)?;
+    writeln!(out, "Notes per month:")?;
+    let mut table = Table::new(vec![
+        Column::left("Month").role(Role::Muted),
+        Column::right("New"),
+        Column::right("Total"),
+    ])
+    .indent(2)
+    .without_rule();
+    for month in month_listings(&report.months, args.months) {
+        table.row([
+            month.month,
+            month.created.to_string(),
+            month.total.to_string(),
+        ]);
+    }
+    out.push_str(&table.render(&painter));
+
+    if !report.tags.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Top tags:")?;
+        let mut table = Table::new(vec![Column::left(""), Column::right("")])
+            .indent(2)
+            .without_header();
+        for (tag, count) in report.tags.iter().take(args.top) {
+            table.row([tag.clone(), count.to_string()]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+
+    if !report.most_linked.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "Most linked:")?;
+        let mut table = Table::new(vec![
+            Column::left("").role(Role::Id),
+            Column::left("").max_width(50).shrink(),
+            Column::right(""),
+        ])
+        .indent(2)
+        .without_header();
+        for (note, count) in report.most_linked.iter().take(args.top) {
+            table.row([
+                note_label(note, config.note_refs, &config.slug),
+                note.title().to_string(),
+                format!("{} backlink(s)", count),
+            ]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "Stalest ({} not modified in {} days):",
+        report.stale, args.stale_days
+    )?;
+    let mut table = Table::new(vec![
+        Column::left("").role(Role::Id),
+        Column::left("").max_width(50).shrink(),
+        Column::right("").role(Role::Muted),
+    ])
+    .indent(2)
+    .without_header();
+    for note in report.stalest.iter().take(args.top) {
+        table.row([
+            note_label(note, config.note_refs, &config.slug),
+            note.title().to_string(),
+            config.time.date(note.modified()),
+        ]);
+    }
+    out.push_str(&table.render(&painter));
+
+    writeln!(out)?;
+    writeln!(out, "Orphans (no links): {}", report.orphans)?;
+
+    if !report.subtopics.is_empty() {
+        writeln!(out)?;
+        match report.balance() {
+            Some(balance) => writeln!(out, "Subtopics (balance {:.2}):", balance)?,
+            None => writeln!(out, "Subtopics:")?,
+        }
+        let mut table = Table::new(vec![
+            Column::left(""),
+            Column::right(""),
+            Column::right("").role(Role::Muted),
+        ])
+        .indent(2)
+        .without_header();
+        for (subtopic, count) in &report.subtopics {
+            table.row([
+                subtopic.to_string(),
+                count.to_string(),
+                format!("{:.1}%", *count as f64 / report.notes as f64 * 100.0),
+            ]);
+        }
+        out.push_str(&table.render(&painter));
+    }
+    Ok(out)
+}
+
+pub fn handle_topic_stats(args: &TopicStatsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let path = args.topic.trim_end_matches('/');
+    let topic = Topic::new(path).with_context(|| format!("invalid topic: {}", path))?;
+
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+    let notes = index
+        .list_by_topic(&topic, true)
+        .with_context(|| "failed to list notes by topic")?;
+    if notes.is_empty() {
+        bail!("topic not found: '{}'", topic);
+    }
+    let links = index.all_links().with_context(|| "failed to query links")?;
+
+    let stale_before = Utc::now() - Duration::days(args.stale_days.into());
+    let report = topic_report(&topic, &notes, &links, config.time.utc, stale_before);
+
+    match args.format {
+        OutputFormat::Json => {
+            let listing = TopicStatsListing {
+                topic: topic.to_string(),
+                notes: report.notes,
+                direct: report.direct,
+                orphans: report.orphans,
+                stale: report.stale,
+                stale_days: args.stale_days,
+                months: month_listings(&report.months, args.months),
+                top_tags: report
+                    .tags
+                    .iter()
+                    .take(args.top)
+                    .map(|(name, count)| TagListing {
+                        name: name.clone(),
+                        count: Some(*count),
+                    })
+                    .collect(),
+                most_linked: report
+                    .most_linked
+                    .iter()
+                    .take(args.top)
+                    .map(|(note, count)| RankedNoteListing {
+                        backlinks: Some(*count),
+                        ..ranked_listing(note)
+                    })
+                    .collect(),
+                stalest: report
+                    .stalest
+                    .iter()
+                    .take(args.top)
+                    .map(|note| RankedNoteListing {
+                        modified: Some(note.modified().to_rfc3339()),
+                        ..ranked_listing(note)
+                    })
+                    .collect(),
+                subtopics: report
+                    .subtopics
+                    .iter()
+                    .map(|(subtopic, count)| SubtopicListing {
+                        path: subtopic.to_string(),
+                        notes: *count,
+                        share: *count as f64 / report.notes as f64,
+                    })
+                    .collect(),
+                balance: report.balance(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(listing))?);
+        }
+        OutputFormat::Human | OutputFormat::Paths => {
+            page(
+                &render_report(&topic, &report, args, config)?,
+                &config.output,
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,409 @@
+//! Trash command handler, and the retention sweep run by `index`.
+//!
+//! Notes moved into `.trash/` (by `rm` or by hand) keep their full content
+//! and their path relative to the vault there until they are restored or
+//! purged; a note given a numbered name to keep it apart from an earlier one
+//! has its path recorded in an `.origin` file instead. The index records
+//! when each file was first seen in the trash, so `trash purge --older-than`
+//! and `trash.retention_days` can go by the time a note was trashed rather
+//! than its last edit.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::index_db_path;
+use crate::cli::config::Config;
+use crate::cli::date_filter::DateFilter;
+use crate::cli::output::{Column, Output, OutputFormat, Painter, Role, Table};
+use crate::cli::{TrashArgs, TrashCommand, TrashPurgeArgs, TrashRestoreArgs};
+use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
+use crate::infra::{TRASH_DIR, index_path_key, read_note, scan_trash_directory};
+
+/// A note in `trash` JSON output.
+#[derive(Debug, Serialize)]
+pub struct TrashListing {
+    pub path: String,
+    /// Note ID, if the file could be parsed.
+    pub id: Option<String>,
+    /// Note title, if the file could be parsed.
+    pub title: Option<String>,
+    pub trashed_at: String,
+}
+
+/// Result of `trash purge` for JSON output.
+#[derive(Debug, Serialize)]
+pub struct TrashPurgeResult {
+    pub purged: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Result of `trash restore` for JSON output.
+#[derive(Debug, Serialize)]
+pub struct TrashRestoreResult {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub from: String,
+    pub path: String,
+}
+
+/// Moves a note file (relative to `notes_dir`) into the trash, keeping its
+/// path under `.trash/`, and returns its path in the trash.
+///
+/// A file already in the trash under the same name is kept; the note gets a
+/// numbered name next to it instead, with its original path recorded in an
+/// `.origin` file beside it for `trash restore`.
+pub(crate) fn move_to_trash(notes_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let unnumbered = Path::new(TRASH_DIR).join(path);
+    let trashed = free_path(notes_dir, &unnumbered);
+    let full_path = notes_dir.join(&trashed);
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    if trashed != unnumbered {
+        let origin = notes_dir.join(origin_path(&trashed));
+        std::fs::write(&origin, format!("{}\n", index_path_key(path)))
+            .with_context(|| format!("failed to write {}", origin.display()))?;
+    }
+    std::fs::rename(notes_dir.join(path), &full_path)
+        .with_context(|| format!("failed to move {} to the trash", path.display()))?;
+    Ok(trashed)
+}
+
+/// Returns the `.origin` file recording where a trashed note came from.
+fn origin_path(trashed: &Path) -> PathBuf {
+    let mut name = trashed.file_name().unwrap_or_default().to_os_string();
+    name.push(".origin");
+    trashed.with_file_name(name)
+}
+
+/// Returns the path in the vault a trashed note came from: the one in its
+/// `.origin` file, or else its path under `.trash/`.
+fn original_path(notes_dir: &Path, trashed: &Path) -> PathBuf {
+    match std::fs::read_to_string(notes_dir.join(origin_path(trashed))) {
+        Ok(origin) if !origin.trim().is_empty() => PathBuf::from(origin.trim()),
+        _ => trashed
+            .strip_prefix(TRASH_DIR)
+            .expect("trashed paths start with the trash directory")
+            .to_path_buf(),
+    }
+}
+
+/// Deletes the `.origin` file of a trashed note, if it has one.
+fn remove_origin(notes_dir: &Path, trashed: &Path) -> Result<()> {
+    let origin = notes_dir.join(origin_path(trashed));
+    match std::fs::remove_file(&origin) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(e).with_context(|| format!("failed to delete {}", origin.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns `path`, or `path` with a `-2`, `-3`... suffix if it is taken.
+fn free_path(notes_dir: &Path, path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut candidate = path.to_path_buf();
+    let mut n = 2;
+    while notes_dir.join(&candidate).exists() {
+        candidate = path.with_file_name(format!("{}-{}.md", stem, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Records the files in the trash in the index and returns them with the
+/// time each was trashed, oldest first.
+pub(crate) fn scan_trash(
+    notes_dir: &Path,
+    index: &mut SqliteIndex,
+    now: DateTime<Utc>,
+) -> Result<Vec<(PathBuf, DateTime<Utc>)>> {
+    let paths = scan_trash_directory(notes_dir)?;
+    let mut trashed = index
+        .sync_trash(&paths, now)
+        .with_context(|| "failed to record trash")?;
+    trashed.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(trashed)
+}
+
+/// Deletes the files trashed before `cutoff` (every file without one) and
+/// returns their paths. With `dry_run` nothing is deleted.
+pub(crate) fn purge_trash(
+    notes_dir: &Path,
+    index: &mut SqliteIndex,
+    cutoff: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> Result<Vec<PathBuf>> {
+    let purged: Vec<PathBuf> = scan_trash(notes_dir, index, now)?
+        .into_iter()
+        .filter(|(_, trashed_at)| cutoff.is_none_or(|cutoff| *trashed_at < cutoff))
+        .map(|(path, _)| path)
+        .collect();
+    if dry_run {
+        return Ok(purged);
+    }
+
+    for path in &purged {
+        let full_path = notes_dir.join(path);
+        std::fs::remove_file(&full_path)
+            .with_context(|| format!("failed to delete {}", full_path.display()))?;
+        remove_origin(notes_dir, path)?;
+    }
+    index
+        .forget_trash(&purged)
+        .with_context(|| "failed to update trash records")?;
+    Ok(purged)
+}
+
+/// Records the trash and deletes notes that have been in it longer than
+/// `trash.retention_days`, returning their paths.
+pub(crate) fn sweep_trash(
+    notes_dir: &Path,
+    index: &mut SqliteIndex,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> Result<Vec<PathBuf>> {
+    match config.trash.retention_days {
+        Some(days) => {
+            let cutoff = now - Duration::days(i64::from(days));
+            purge_trash(notes_dir, index, Some(cutoff), now, false)
+        }
+        None => {
+            scan_trash(notes_dir, index, now)?;
+            Ok(Vec::new())
+        }
+    }
+}
+
+pub fn handle_trash(args: &TrashArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let mut index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    match &args.command {
+        Some(TrashCommand::Restore(restore_args)) => {
+            return handle_restore(restore_args, notes_dir, &mut index, config);
+        }
+        Some(TrashCommand::Purge(purge_args)) => {
+            return handle_purge(purge_args, notes_dir, &mut index);
+        }
+        None => {}
+    }
+
+    let trashed = scan_trash(notes_dir, &mut index, Utc::now())?;
+    let listings: Vec<TrashListing> = trashed
+        .iter()
+        .map(|(path, trashed_at)| {
+            let parsed = read_note(&notes_dir.join(path)).ok();
+            TrashListing {
+                path: path.display().to_string(),
+                id: parsed.as_ref().map(|p| p.note.id().prefix()),
+                title: parsed.as_ref().map(|p| p.note.title().to_string()),
+                trashed_at: trashed_at.to_rfc3339(),
+            }
+        })
+        .collect();
+
+    match args.format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&Output::new(listings))?);
+        }
+        OutputFormat::Paths => {
+            for (path, _) in &trashed {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+        OutputFormat::Human => {
+            if trashed.is_empty() {
+                println!("The trash is empty.");
+                return Ok(());
+            }
+            let mut table = Table::new(vec![
+                Column::left("ID").role(Role::Id),
+                Column::left("Title").max_width(50).shrink(),
+                Column::right("Trashed").role(Role::Muted),
+                Column::left("Path").role(Role::Muted),
+            ]);
+            for ((_, trashed_at), listing) in trashed.iter().zip(&listings) {
+                table.row(vec![
+                    listing.id.clone().unwrap_or_default(),
+                    listing.title.clone().unwrap_or_default(),
+                    config.time.date(*trashed_at),
+                    listing.path.clone(),
+                ]);
+            }
+            table.print(&Painter::new(&config.output));
+            println!();
+            println!("{} note(s) in the trash", trashed.len());
+            if let Some(days) = config.trash.retention_days {
+                println!("Notes are deleted {} days after being trashed.", days);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Finds the trashed file `query` refers to: its path (with or without the
+/// `.trash/` prefix), its note ID prefix or its title.
+fn find_trashed(notes_dir: &Path, trashed: &[PathBuf], query: &str) -> Result<PathBuf> {
+    let as_path = Path::new(query);
+    if let Some(path) = trashed
+        .iter()
+        .find(|p| p.as_path() == as_path || p.strip_prefix(TRASH_DIR).ok() == Some(as_path))
+    {
+        return Ok(path.clone());
+    }
+
+    let query_lower = query.to_lowercase();
+    let matches: Vec<&PathBuf> = trashed
+        .iter()
+        .filter(|path| {
+            read_note(&notes_dir.join(path)).is_ok_and(|parsed| {
+                parsed
+                    .note
+                    .id()
+                    .to_string()
+                    .to_lowercase()
+                    .starts_with(&query_lower)
+                    || parsed.note.title().to_lowercase() == query_lower
+            })
+        })
+        .collect();
+    match matches.as_slice() {
+        [path] => Ok((*path).clone()),
+        [] => bail!("no note in the trash matches '{}'", query),
+        _ => {
+            let paths: Vec<String> = matches.iter().map(|p| p.display().to_string()).collect();
+            bail!(
+                "'{}' matches {} notes in the trash: {}",
+                query,
+                matches.len(),
+                paths.join(", ")
+            )
+        }
+    }
+}
+
+/// Moves a trashed note back to where it was in the vault and indexes it.
+/// If another file has taken its place, it gets a numbered name instead.
+fn handle_restore(
+    args: &TrashRestoreArgs,
+    notes_dir: &Path,
+    index: &mut SqliteIndex,
+    config: &Config,
+) -> Result<()> {
+    let trashed: Vec<PathBuf> = scan_trash(notes_dir, index, Utc::now())?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    let from = find_trashed(notes_dir, &trashed, &args.note)?;
+
+    let parsed = read_note(&notes_dir.join(&from)).ok();
+    if let Some(parsed) = &parsed
+        && let Some(existing) = index.get_note(parsed.note.id())?
+    {
+        bail!(
+            "cannot restore {}: note {} is already in the vault at {}",
+            from.display(),
+            parsed.note.id().prefix(),
+            existing.path().display()
+        );
+    }
+
+    // The original path may have been taken since; only the same note there
+    // (written back by hand, say) stops the restore
+    let original = original_path(notes_dir, &from);
+    if let Some(parsed) = &parsed
+        && read_note(&notes_dir.join(&original))
+            .is_ok_and(|existing| existing.note.id() == parsed.note.id())
+    {
+        bail!(
+            "cannot restore {}: {} already exists",
+            from.display(),
+            original.display()
+        );
+    }
+    let to = free_path(notes_dir, &original);
+    let full_path = notes_dir.join(&to);
+    if let Some(parent) = full_path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::rename(notes_dir.join(&from), &full_path)
+        .with_context(|| format!("failed to restore {}", from.display()))?;
+    remove_origin(notes_dir, &from)?;
+    index
+        .forget_trash(std::slice::from_ref(&from))
+        .with_context(|| "failed to update trash records")?;
+    let builder = IndexBuilder::new(notes_dir.to_path_buf()).with_scan_options(config.scan.clone());
+    let _ = builder.update_paths(index, std::slice::from_ref(&to));
+
+    match args.format {
+        OutputFormat::Json => {
+            let result = TrashRestoreResult {
+                id: parsed.as_ref().map(|p| p.note.id().to_string()),
+                title: parsed.as_ref().map(|p| p.note.title().to_string()),
+                from: from.display().to_string(),
+                path: to.display().to_string(),
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => println!("{}", full_path.display()),
+        OutputFormat::Human => match &parsed {
+            Some(parsed) => println!(
+                "Restored '{}' [{}] to {}",
+                parsed.note.title(),
+                parsed.note.id().prefix(),
+                to.display()
+            ),
+            None => println!("Restored {} to {}", from.display(), to.display()),
+        },
+    }
+    Ok(())
+}
+
+fn handle_purge(args: &TrashPurgeArgs, notes_dir: &Path, index: &mut SqliteIndex) -> Result<()> {
+    let cutoff = match &args.older_than {
+        Some(age) => Some(
+            DateFilter::parse(age)
+                .map_err(|e| anyhow::anyhow!("invalid --older-than: {}", e))?
+                .threshold(),
+        ),
+        None => None,
+    };
+    let purged = purge_trash(notes_dir, index, cutoff, Utc::now(), args.dry_run)?;
+
+    match args.format {
+        OutputFormat::Json => {
+            let result = TrashPurgeResult {
+                purged: purged.iter().map(|p| p.display().to_string()).collect(),
+                dry_run: args.dry_run,
+            };
+            println!("{}", serde_json::to_string_pretty(&Output::new(result))?);
+        }
+        OutputFormat::Paths => {
+            for path in &purged {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+        OutputFormat::Human => {
+            let (delete, purge) = if args.dry_run {
+                ("Would delete", "Would purge")
+            } else {
+                ("Deleted", "Purged")
+            };
+            for path in &purged {
+                println!("{} {}", delete, path.display());
+            }
+            println!("{} {} note(s) from the trash", purge, purged.len());
+        }
+    }
+    Ok(())
+}
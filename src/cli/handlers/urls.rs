@@ -0,0 +1,166 @@
+//! Urls command handler.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use super::index_db_path;
+use super::resolve::note_label;
+use crate::cli::UrlsArgs;
+use crate::cli::config::Config;
+use crate::cli::output::{
+    Cell, Column, NoteListing, Output, OutputFormat, Painter, Role, Table, page,
+};
+use crate::index::{SqliteIndex, UrlReference};
+use crate::infra::{UrlChecker, UrlStatus};
+
+/// URLs requested at the same time with `--broken`.
+const CHECK_WORKERS: usize = 8;
+
+/// A URL in `urls` output.
+#[derive(Debug, Serialize)]
+struct UrlListing {
+    url: String,
+    domain: String,
+    notes: Vec<NoteListing>,
+    /// HTTP status code, with `--broken` when the server answered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    /// Why the URL couldn't be requested, with `--broken`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn handle_urls(args: &UrlsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    let db_path = index_db_path(notes_dir);
+    let index = SqliteIndex::open(&db_path)
+        .with_context(|| format!("failed to open index at {}", db_path.display()))?;
+
+    let references = index
+        .url_references(args.domain.as_deref())
+        .with_context(|| "failed to query URLs")?;
+
+    let urls: Vec<(UrlReference, Option<UrlStatus>)> = if args.broken {
+        let checker = UrlChecker::new(Duration::from_secs(args.timeout));
+        let statuses = check_all(&checker, &references);
+        references
+            .into_iter()
+            .zip(statuses)
+            .filter(|(_, status)| status.is_broken())
+            .map(|(reference, status)| (reference, Some(status)))
+            .collect()
+    } else {
+        references.into_iter().map(|r| (r, None)).collect()
+    };
+
+    match args.format {
+        OutputFormat::Human => {
+            if urls.is_empty() {
+                if args.broken {
+                    println!("No broken URLs found.");
+                } else {
+                    println!("No URLs found.");
+                }
+                return Ok(());
+            }
+
+            let mut columns = vec![
+                Column::left("URL").shrink(),
+                Column::left("Notes").role(Role::Id),
+            ];
+            if args.broken {
+                columns.push(Column::left("Status"));
+            }
+            let mut table = Table::new(columns);
+            for (reference, status) in &urls {
+                let notes = reference
+                    .notes
+                    .iter()
+                    .map(|n| note_label(n, config.note_refs, &config.slug))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let mut row = vec![Cell::from(reference.url.as_str()), Cell::from(notes)];
+                if let Some(status) = status {
+                    row.push(Cell::styled(status.to_string(), Role::Error));
+                }
+                table.row(row);
+            }
+
+            let mut out = table.render(&Painter::new(&config.output));
+            writeln!(out)?;
+            if args.broken {
+                writeln!(out, "{} broken URL(s)", urls.len())?;
+            } else {
+                writeln!(out, "{} URL(s)", urls.len())?;
+            }
+            page(&out, &config.output)?;
+        }
+        OutputFormat::Json => {
+            let listings: Vec<UrlListing> = urls
+                .into_iter()
+                .map(|(reference, status)| UrlListing {
+                    notes: reference
+                        .notes
+                        .iter()
+                        .map(|n| NoteListing {
+                            id: n.id().to_string(),
+                            title: n.title().to_string(),
+                            path: n.path().to_string_lossy().to_string(),
+                            note_type: n.note_type().map(String::from),
+                            description: None,
+                            words: None,
+                        })
+                        .collect(),
+                    url: reference.url,
+                    domain: reference.domain,
+                    status: status.as_ref().and_then(UrlStatus::code),
+                    error: match status {
+                        Some(UrlStatus::Failed(reason)) => Some(reason),
+                        _ => None,
+                    },
+                })
+                .collect();
+            let output = Output::new(listings);
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Paths => {
+            // Each referencing note once
+            let paths: BTreeSet<_> = urls
+                .iter()
+                .flat_map(|(reference, _)| reference.notes.iter().map(|n| n.path()))
+                .collect();
+            for path in paths {
+                println!("{}", notes_dir.join(path).display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks every URL, a few at a time, returning statuses in the same order.
+fn check_all(checker: &UrlChecker, references: &[UrlReference]) -> Vec<UrlStatus> {
+    if references.is_empty() {
+        return Vec::new();
+    }
+    let chunk_size = references.len().div_ceil(CHECK_WORKERS);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = references
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|r| checker.check(&r.url))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("URL check thread panicked"))
+            .collect()
+    })
+}
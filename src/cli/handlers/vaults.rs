@@ -1,13 +1,19 @@
 //! Handler for the `vaults` command.
 
 use anyhow::Result;
+use std::path::Path;
 
+use super::sync_config::handle_sync_config;
 use crate::cli::config::Config;
 use crate::cli::output::OutputFormat;
-use crate::cli::VaultsArgs;
+use crate::cli::{VaultsArgs, VaultsCommand};
 
 /// Handle the vaults command - list configured vaults or set default.
-pub fn handle_vaults(args: &VaultsArgs, config: &Config) -> Result<()> {
+pub fn handle_vaults(args: &VaultsArgs, notes_dir: &Path, config: &Config) -> Result<()> {
+    if let Some(VaultsCommand::SyncConfig(sync_args)) = &args.command {
+        return handle_sync_config(sync_args, notes_dir);
+    }
+
     // Handle --set-default
     if let Some(ref vault_name) = args.set_default {
         Config::set_default_vault(vault_name)?;
@@ -85,6 +91,7 @@ mod tests {
             editor: None,
             default_vault: Some("personal".to_string()),
             vaults,
+            ..Default::default()
         }
     }
 
@@ -92,11 +99,12 @@ mod tests {
     fn handle_vaults_runs_without_error() {
         let config = make_config_with_vaults();
         let args = VaultsArgs {
+            command: None,
             set_default: None,
             format: OutputFormat::Human,
         };
         // Just verify it doesn't panic/error
-        let result = handle_vaults(&args, &config);
+        let result = handle_vaults(&args, Path::new("."), &config);
         assert!(result.is_ok());
     }
 
@@ -104,10 +112,11 @@ mod tests {
     fn handle_vaults_empty_config() {
         let config = Config::default();
         let args = VaultsArgs {
+            command: None,
             set_default: None,
             format: OutputFormat::Human,
         };
-        let result = handle_vaults(&args, &config);
+        let result = handle_vaults(&args, Path::new("."), &config);
         assert!(result.is_ok());
     }
 }
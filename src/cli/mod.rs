@@ -1,14 +1,21 @@
 //! CLI command definitions and handlers
 
+pub mod alias;
+pub mod archive_policy;
 pub mod config;
 pub mod date_filter;
 pub mod handlers;
+pub mod note_type;
 pub mod output;
+pub mod picker;
+pub mod tag_style;
+pub mod time_format;
 
 use clap::{ArgAction, Parser, Subcommand};
 use clap_complete::Shell;
 use std::path::PathBuf;
 
+use crate::export::{DEFAULT_TEXT_WIDTH, DEFAULT_TOC_DEPTH};
 use output::OutputFormat;
 
 /// Markdown notes with virtual folder organization
@@ -27,6 +34,30 @@ pub struct Cli {
     #[arg(short, long, global = true, action = ArgAction::Count)]
     pub verbose: u8,
 
+    /// Show timestamps in UTC instead of local time
+    #[arg(long, global = true)]
+    pub utc: bool,
+
+    /// Show full RFC 3339 timestamps
+    #[arg(long, global = true)]
+    pub iso: bool,
+
+    /// Print a breakdown of where the command spent its time (on stderr)
+    #[arg(long, global = true)]
+    pub profile: bool,
+
+    /// Never pipe long output through $PAGER
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Pick from a fuzzy finder when a note reference is ambiguous or omitted
+    #[arg(short, long, global = true, conflicts_with = "non_interactive")]
+    pub interactive: bool,
+
+    /// Fail on an ambiguous note reference instead of prompting
+    #[arg(long, global = true)]
+    pub non_interactive: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -40,12 +71,37 @@ pub enum Command {
     #[command(name = "ls")]
     List(ListArgs),
 
+    /// List recently modified notes, optionally grouped into editing sessions
+    Recent(RecentArgs),
+
+    /// Rank notes by backlinks, outbound links or tags
+    Top(TopArgs),
+
     /// Full-text search across notes
     Search(SearchArgs),
 
+    /// Search as you type, printing compact JSON for launchers (Raycast, Alfred, editors)
+    #[command(name = "quick-search")]
+    QuickSearch(QuickSearchArgs),
+
     /// Create a new note
     New(NewArgs),
 
+    /// Create or find the journal note for today or another day
+    Daily(DailyArgs),
+
+    /// Create a note from a code snippet read on stdin
+    Snippet(SnippetArgs),
+
+    /// Duplicate a note under a new ID as a starting point
+    Clone(CloneArgs),
+
+    /// Add text to the end of a note's body
+    Append(AppendArgs),
+
+    /// Add text to the start of a note's body
+    Prepend(AppendArgs),
+
     /// Show a note's contents
     Show(ShowArgs),
 
@@ -64,12 +120,37 @@ pub enum Command {
     /// Remove a tag from a note
     Untag(UntagArgs),
 
+    /// Set or clear a note's description
+    Desc(DescArgs),
+
     /// Check for issues (broken links, orphans, etc.)
     Check(CheckArgs),
 
+    /// Give new IDs to notes with duplicate, invalid or missing IDs
+    #[command(name = "fix-ids")]
+    FixIds(FixIdsArgs),
+
+    /// Remove a note, moving it to the trash (.trash/) unless --force is given
+    Rm(RmArgs),
+
+    /// List, restore or purge notes in the trash (.trash/)
+    Trash(TrashArgs),
+
+    /// Finish or undo an operation that was interrupted while changing files
+    Recover(RecoverArgs),
+
+    /// Show a vault health score and its trend over time
+    Stats(StatsArgs),
+
+    /// Show the terms that set a note apart from the rest of the vault
+    Keywords(KeywordsArgs),
+
     /// Show notes that link to a given note
     Backlinks(BacklinksArgs),
 
+    /// Create and manage person notes
+    Person(PersonArgs),
+
     /// Create a link between notes
     Link(LinkArgs),
 
@@ -79,13 +160,22 @@ pub enum Command {
     /// List relationship types used in links
     Rels(RelsArgs),
 
+    /// Export the link graph as DOT, GraphML or JSON
+    Graph(GraphArgs),
+
+    /// List external URLs referenced in note bodies, optionally only broken ones
+    Urls(UrlsArgs),
+
+    /// List links to notes that don't exist and unresolved wikilinks
+    ListBroken(ListBrokenArgs),
+
     /// Generate shell completions
     Completions(CompletionsArgs),
 
-    /// Move/rename a note (change title or topics)
+    /// Move/rename a note (change title, topics or ID)
     Mv(MvArgs),
 
-    /// Archive a note (adds 'archived' tag)
+    /// Archive a note (adds 'archived' tag) or suggest notes to archive
     Archive(ArchiveArgs),
 
     /// Unarchive a note (removes 'archived' tag)
@@ -94,8 +184,29 @@ pub enum Command {
     /// Export notes to HTML, PDF, or static site
     Export(ExportArgs),
 
+    /// Summarize notes created or modified recently, e.g. for a weekly email
+    Digest(DigestArgs),
+
+    /// Share the structure of the vault (titles, topics, tags, links) as an encrypted bundle, without note contents
+    Bundle(BundleArgs),
+
+    /// Quiz due spaced-repetition cards and notes, or manage the drill deck
+    Drill(DrillArgs),
+
+    /// Import notes from another markdown vault
+    Import(ImportArgs),
+
+    /// Rename or remove frontmatter keys across the vault
+    Migrate(MigrateArgs),
+
     /// List configured vaults
     Vaults(VaultsArgs),
+
+    /// Inspect the config file
+    Config(ConfigArgs),
+
+    /// Measure indexing, search and listing speed on a generated vault
+    Bench(BenchArgs),
 }
 
 /// Arguments for the `index` command
@@ -104,6 +215,22 @@ pub struct IndexArgs {
     /// Force full rebuild instead of incremental update
     #[arg(long)]
     pub full: bool,
+
+    /// Print index statistics instead of updating
+    #[arg(long, conflicts_with_all = ["full", "vacuum"])]
+    pub stats: bool,
+
+    /// Compact the index database file
+    #[arg(long, conflicts_with = "full")]
+    pub vacuum: bool,
+
+    /// Keep the index up to date as notes change, until interrupted
+    #[arg(long, conflicts_with_all = ["stats", "vacuum"])]
+    pub watch: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
 /// Arguments for the `ls` (list) command
@@ -131,6 +258,118 @@ pub struct ListArgs {
     /// Include archived notes in results
     #[arg(short = 'a', long)]
     pub include_archived: bool,
+
+    /// Only notes without topics
+    #[arg(long)]
+    pub orphans: bool,
+
+    /// Only notes without tags
+    #[arg(long)]
+    pub untagged: bool,
+
+    /// Only notes with no links and no backlinks
+    #[arg(long)]
+    pub no_links: bool,
+
+    /// Only notes of this type (the `type` frontmatter field)
+    #[arg(long = "type", value_name = "TYPE")]
+    pub note_type: Option<String>,
+
+    /// Extra columns to show, comma-separated
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<ListColumn>,
+
+    /// Sort by body word count, largest first, and show the counts
+    #[arg(long)]
+    pub largest: bool,
+}
+
+/// Optional columns for `ls --columns`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListColumn {
+    /// The note's short description
+    Description,
+    /// Creation date
+    Created,
+    /// The `type` frontmatter field, even when no listed note has one
+    Type,
+    /// Tags, even when no tag styles are configured
+    Tags,
+}
+
+/// Arguments for the `top` command
+#[derive(Parser, Debug)]
+pub struct TopArgs {
+    /// Topic to filter by (trailing / includes descendants)
+    pub topic: Option<String>,
+
+    /// Filter by tag (can be specified multiple times)
+    #[arg(short, long = "tag", action = ArgAction::Append)]
+    pub tags: Vec<String>,
+
+    /// What to rank notes by
+    #[arg(long, value_enum, default_value_t = TopMeasure::Backlinks)]
+    pub by: TopMeasure,
+
+    /// Show at most this many notes
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Include archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// What `top` ranks notes by.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopMeasure {
+    /// Links from other notes
+    Backlinks,
+    /// Links to other notes
+    Links,
+    /// Tags on the note
+    Tags,
+}
+
+/// Arguments for the `recent` command
+#[derive(Parser, Debug)]
+pub struct RecentArgs {
+    /// Include notes modified since (YYYY-MM-DD or relative like "7d")
+    #[arg(long, default_value = "7d")]
+    pub since: String,
+
+    /// Show at most this many notes (sessions with --sessions)
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+
+    /// Group notes into editing sessions
+    #[arg(long)]
+    pub sessions: bool,
+
+    /// Minutes without an edit that start a new session
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        default_value_t = 60,
+        requires = "sessions"
+    )]
+    pub gap: u32,
+
+    /// Write a summary note for each session that doesn't have one yet
+    #[arg(long, requires = "sessions")]
+    pub summarize: bool,
+
+    /// Include archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
 /// Arguments for the `search` command
@@ -139,6 +378,10 @@ pub struct SearchArgs {
     /// Search query
     pub query: String,
 
+    /// Search within a single note (ID or title), listing matching lines
+    #[arg(long = "in", value_name = "NOTE", conflicts_with_all = ["topic", "tags", "include_archived"])]
+    pub in_note: Option<String>,
+
     /// Restrict search to topic (trailing / includes descendants)
     #[arg(short = 'T', long)]
     pub topic: Option<String>,
@@ -154,13 +397,54 @@ pub struct SearchArgs {
     /// Include archived notes in results
     #[arg(short = 'a', long)]
     pub include_archived: bool,
+
+    /// Show which fields matched and how each contributed to the rank
+    #[arg(long, conflicts_with = "in_note")]
+    pub explain: bool,
+
+    /// Maximum number of results
+    #[arg(short = 'n', long, conflicts_with = "in_note")]
+    pub limit: Option<usize>,
+
+    /// Skip this many results (with --open or --show, picks a later match)
+    #[arg(long, default_value_t = 0, conflicts_with = "in_note")]
+    pub offset: usize,
+
+    /// Open the best match in the editor instead of listing results
+    #[arg(long, conflicts_with_all = ["in_note", "explain", "show"])]
+    pub open: bool,
+
+    /// Print the best match instead of listing results
+    #[arg(long, conflicts_with_all = ["in_note", "explain"])]
+    pub show: bool,
+}
+
+/// Arguments for the `quick-search` command
+#[derive(Parser, Debug)]
+pub struct QuickSearchArgs {
+    /// Typed input; every word is matched as a word prefix
+    #[arg(default_value = "")]
+    pub query: String,
+
+    /// Maximum number of results
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Include archived notes in results
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
 }
 
 /// Arguments for the `new` command
 #[derive(Parser, Debug)]
 pub struct NewArgs {
-    /// Note title
-    pub title: String,
+    /// Note title; with --stdin it defaults to the body's first heading or line
+    #[arg(required_unless_present = "stdin")]
+    pub title: Option<String>,
+
+    /// Read the note body from stdin
+    #[arg(long)]
+    pub stdin: bool,
 
     /// Topic for the note (can be specified multiple times)
     #[arg(short = 'T', long = "topic", action = ArgAction::Append)]
@@ -174,133 +458,945 @@ pub struct NewArgs {
     #[arg(short = 'D', long)]
     pub desc: Option<String>,
 
+    /// Link the new note to an existing note (ID or title)
+    #[arg(long, value_name = "NOTE", requires = "rels")]
+    pub link: Option<String>,
+
+    /// Relationship type for --link (can be specified multiple times)
+    #[arg(long = "rel", action = ArgAction::Append, requires = "link")]
+    pub rels: Vec<String>,
+
+    /// Also link the existing note back, using the reciprocal rels from config
+    #[arg(long, requires = "link")]
+    pub reciprocal: bool,
+
+    /// Note type from the [types] config; its template becomes the body
+    #[arg(long = "type", value_name = "TYPE")]
+    pub note_type: Option<String>,
+
+    /// Language the note is written in, e.g. `de` or `ja`; written as the
+    /// `lang` field and used for the filename slug
+    #[arg(long, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Creation time for a note written earlier (YYYY-MM-DD, "YYYY-MM-DD HH:MM" or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    pub created: Option<String>,
+
+    /// Last modification time (YYYY-MM-DD, "YYYY-MM-DD HH:MM" or RFC 3339)
+    #[arg(long, value_name = "DATE")]
+    pub modified: Option<String>,
+
+    /// Create the note without a topic even when `topics.required` asks for one
+    #[arg(long)]
+    pub allow_untopiced: bool,
+
     /// Open in editor after creation
     #[arg(short, long)]
     pub edit: bool,
-}
 
-/// Arguments for the `show` command
-#[derive(Parser, Debug)]
-pub struct ShowArgs {
-    /// Note ID or title
-    pub note: String,
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
-/// Arguments for the `edit` command
+/// Arguments for the `daily` command
 #[derive(Parser, Debug)]
-pub struct EditArgs {
-    /// Note ID or title
-    pub note: String,
-}
+pub struct DailyArgs {
+    /// Day of the note (YYYY-MM-DD); defaults to today
+    #[arg(long, value_name = "DATE", conflicts_with_all = ["yesterday", "tomorrow"])]
+    pub date: Option<String>,
 
-/// Arguments for the `topics` command
-#[derive(Parser, Debug)]
-pub struct TopicsArgs {
-    /// Show note counts for each topic
+    /// Yesterday's note
+    #[arg(long, conflicts_with = "tomorrow")]
+    pub yesterday: bool,
+
+    /// Tomorrow's note
     #[arg(long)]
-    pub counts: bool,
+    pub tomorrow: bool,
+
+    /// Only print the note, without opening it in the editor
+    #[arg(long)]
+    pub no_edit: bool,
 
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
 }
 
-/// Arguments for the `tags` command
+/// Arguments for the `snippet` command
 #[derive(Parser, Debug)]
-pub struct TagsArgs {
-    /// Show note counts for each tag
+pub struct SnippetArgs {
+    /// Note title (defaults to the snippet's first comment line)
     #[arg(long)]
-    pub counts: bool,
+    pub title: Option<String>,
+
+    /// Language of the snippet (auto-detected if omitted)
+    #[arg(short = 'l', long)]
+    pub lang: Option<String>,
+
+    /// Topic for the note (can be specified multiple times)
+    #[arg(short = 'T', long = "topic", action = ArgAction::Append)]
+    pub topics: Vec<String>,
+
+    /// Additional tag for the note (can be specified multiple times)
+    #[arg(short, long = "tag", action = ArgAction::Append)]
+    pub tags: Vec<String>,
+
+    /// Short description
+    #[arg(short = 'D', long)]
+    pub desc: Option<String>,
+
+    /// Open in editor after creation
+    #[arg(short, long)]
+    pub edit: bool,
 
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
 }
 
-/// Arguments for the `tag` command (add tag to note)
+/// Arguments for the `clone` command
 #[derive(Parser, Debug)]
-pub struct TagArgs {
-    /// Note ID or title
+pub struct CloneArgs {
+    /// Note ID or title to copy
     pub note: String,
 
-    /// Tag to add
-    pub tag: String,
+    /// Title for the copy (defaults to "Copy of <title>")
+    #[arg(long)]
+    pub title: Option<String>,
+
+    /// Open in editor after creation
+    #[arg(short, long)]
+    pub edit: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
-/// Arguments for the `untag` command (remove tag from note)
+/// Arguments for the `append` and `prepend` commands
 #[derive(Parser, Debug)]
-pub struct UntagArgs {
+pub struct AppendArgs {
     /// Note ID or title
     pub note: String,
 
-    /// Tag to remove
-    pub tag: String,
-}
+    /// Text to add (may start with '-', e.g. a list item)
+    #[arg(
+        required_unless_present = "stdin",
+        conflicts_with = "stdin",
+        allow_hyphen_values = true
+    )]
+    pub text: Option<String>,
 
-/// Arguments for the `check` command
-#[derive(Parser, Debug)]
-pub struct CheckArgs {
-    /// Attempt to fix issues automatically
+    /// Read the text to add from stdin
     #[arg(long)]
-    pub fix: bool,
+    pub stdin: bool,
+
+    /// Add the text under this heading, creating it at the end if missing
+    #[arg(short = 'H', long)]
+    pub heading: Option<String>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
-/// Arguments for the `backlinks` command
+/// Arguments for the `show` command
 #[derive(Parser, Debug)]
-pub struct BacklinksArgs {
-    /// Note ID or title
-    pub note: String,
+pub struct ShowArgs {
+    /// Note ID or title, or `-` to read notes from stdin (omit with --interactive to pick one)
+    pub note: Option<String>,
 
-    /// Filter by relationship type
-    #[arg(long)]
-    pub rel: Option<String>,
+    /// Print only the section under this heading (title or path like "Guide > Setup")
+    #[arg(short, long, value_name = "HEADING")]
+    pub section: Option<String>,
+
+    /// List the git commits that changed the note (the vault must be in a git repository)
+    #[arg(long, conflicts_with_all = ["section", "at"])]
+    pub history: bool,
+
+    /// Show the note as of a git revision (commit hash, branch, tag or e.g. HEAD~2)
+    #[arg(long, value_name = "REV")]
+    pub at: Option<String>,
+
+    /// Read the note from the index instead of its file; needs `store_bodies` under [index]
+    #[arg(long, conflicts_with_all = ["history", "at"])]
+    pub from_index: bool,
 
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
 }
 
-/// Arguments for the `link` command
+/// Arguments for the `edit` command
 #[derive(Parser, Debug)]
-pub struct LinkArgs {
-    /// Source note ID or title
-    pub source: String,
+pub struct EditArgs {
+    /// Note ID or title, or `-` to read notes from stdin (omit with --interactive to pick one)
+    #[arg(conflicts_with = "all")]
+    pub note: Option<String>,
 
-    /// Target note ID or title
-    pub target: String,
+    /// Open every note matching the filters in a single editor session
+    #[arg(long)]
+    pub all: bool,
 
-    /// Relationship type (can be specified multiple times)
-    #[arg(long = "rel", action = ArgAction::Append)]
-    pub rels: Vec<String>,
+    /// With --all: topic to filter by (trailing / includes descendants)
+    #[arg(short = 'T', long, requires = "all")]
+    pub topic: Option<String>,
 
-    /// Optional context note about the link
-    #[arg(long)]
-    pub note: Option<String>,
-}
+    /// With --all: filter by tag (can be specified multiple times)
+    #[arg(short, long = "tag", action = ArgAction::Append, conflicts_with = "note")]
+    pub tags: Vec<String>,
 
-/// Arguments for the `unlink` command
-#[derive(Parser, Debug)]
-pub struct UnlinkArgs {
-    /// Source note ID or title
-    pub source: String,
+    /// With --all: full-text search query
+    #[arg(short, long, requires = "all")]
+    pub query: Option<String>,
 
-    /// Target note ID or title
-    pub target: String,
+    /// With --all: include archived notes
+    #[arg(short = 'a', long, requires = "all")]
+    pub include_archived: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
 }
 
-/// Arguments for the `rels` command
+/// Arguments for the `topics` command
 #[derive(Parser, Debug)]
-pub struct RelsArgs {
-    /// Show usage counts for each relationship type
+pub struct TopicsArgs {
+    #[command(subcommand)]
+    pub command: Option<TopicsCommand>,
+
+    /// Show note counts for each topic
     #[arg(long)]
     pub counts: bool,
 
+    /// Show topics as a tree with exact/total note counts
+    #[arg(long)]
+    pub tree: bool,
+
+    /// With --tree: show at most N levels
+    #[arg(long, value_name = "N", requires = "tree")]
+    pub depth: Option<usize>,
+
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
 }
 
-/// Arguments for the `completions` command
+/// Subcommands of the `topics` command
+#[derive(Subcommand, Debug)]
+pub enum TopicsCommand {
+    /// Report on a topic and its subtopics: growth, top tags, most linked and stalest notes, orphans and subtopic balance
+    Stats(TopicStatsArgs),
+    /// Rename a topic, or merge it into another, in every note's frontmatter
+    Mv(TopicMvArgs),
+}
+
+/// Arguments for the `topics mv` command
+#[derive(Parser, Debug)]
+pub struct TopicMvArgs {
+    /// Topic to rename; a trailing `/` also moves its subtopics
+    pub old: String,
+
+    /// New topic path; notes already filed under it are merged
+    pub new: String,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `topics stats` command
+#[derive(Parser, Debug)]
+pub struct TopicStatsArgs {
+    /// Topic to report on; notes in its subtopics are included
+    pub topic: String,
+
+    /// Number of top tags, most linked notes and stalest notes to show
+    #[arg(long, default_value_t = 5)]
+    pub top: usize,
+
+    /// Show note counts for the last N months (0 shows all)
+    #[arg(long, default_value_t = 12)]
+    pub months: usize,
+
+    /// Days without modification after which a note counts as stale
+    #[arg(long, default_value_t = 180)]
+    pub stale_days: u32,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `tags` command
+#[derive(Parser, Debug)]
+pub struct TagsArgs {
+    #[command(subcommand)]
+    pub command: Option<TagsCommand>,
+
+    /// Show note counts for each tag
+    #[arg(long)]
+    pub counts: bool,
+
+    /// Organize tags by the groups configured in `[tags.groups]`
+    #[arg(long)]
+    pub groups: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Subcommands of the `tags` command
+#[derive(Subcommand, Debug)]
+pub enum TagsCommand {
+    /// List expired tags: dated ones like `review-by:2024-06-01` and those past their [tags.ttl]
+    Expire(TagExpireArgs),
+    /// Rename a tag in every note's frontmatter; notes that already have the new tag keep one
+    Mv(TagMvArgs),
+    /// Merge tags into one in every note's frontmatter
+    Merge(TagMergeArgs),
+}
+
+/// Arguments for the `tags mv` command
+#[derive(Parser, Debug)]
+pub struct TagMvArgs {
+    /// Tag to rename
+    pub old: String,
+
+    /// New tag name
+    pub new: String,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `tags merge` command
+#[derive(Parser, Debug)]
+pub struct TagMergeArgs {
+    /// Tags to merge, followed by the tag they are merged into
+    #[arg(value_name = "TAG", num_args = 2.., required = true)]
+    pub tags: Vec<String>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `tags expire` command
+#[derive(Parser, Debug)]
+pub struct TagExpireArgs {
+    /// Remove the expired tags, or replace them as configured in [tags.expired]
+    #[arg(long)]
+    pub apply: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `tag` command (add tag to note)
+#[derive(Parser, Debug)]
+pub struct TagArgs {
+    /// Note ID or title, or `-` to read notes from stdin (with --query, the tag to add)
+    pub note: String,
+
+    /// Tag to add
+    #[arg(required_unless_present = "query", conflicts_with = "query")]
+    pub tag: Option<String>,
+
+    /// Tag every note matching a full-text search query
+    #[arg(long, value_name = "QUERY")]
+    pub query: Option<String>,
+
+    /// With --query, list the notes that would be tagged without changing them
+    #[arg(long, requires = "query")]
+    pub dry_run: bool,
+
+    /// With --query, also tag matching archived notes
+    #[arg(short = 'a', long, requires = "query")]
+    pub include_archived: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `untag` command (remove tag from note)
+#[derive(Parser, Debug)]
+pub struct UntagArgs {
+    /// Note ID or title, or `-` to read notes from stdin, one per line
+    pub note: String,
+
+    /// Tag to remove
+    pub tag: String,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `desc` command (set or clear a note's description)
+#[derive(Parser, Debug)]
+pub struct DescArgs {
+    /// Note ID or title, or `-` to read notes from stdin, one per line
+    pub note: String,
+
+    /// New description
+    #[arg(required_unless_present = "clear", conflicts_with = "clear")]
+    pub description: Option<String>,
+
+    /// Remove the description
+    #[arg(long)]
+    pub clear: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `check` command
+#[derive(Parser, Debug)]
+pub struct CheckArgs {
+    /// Attempt to fix issues automatically
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Also list notes in the trash (.trash/)
+    #[arg(long)]
+    pub include_trash: bool,
+
+    /// Only read notes changed since they last passed, checking links and
+    /// IDs across notes against the index
+    #[arg(long, conflicts_with = "drift")]
+    pub incremental: bool,
+
+    /// Compare each note with its index entry, reporting files changed since
+    /// they were indexed, index rows that differ from unchanged files and
+    /// index rows without a file (with --fix, re-index them)
+    #[arg(long)]
+    pub drift: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `fix-ids` command
+#[derive(Parser, Debug)]
+pub struct FixIdsArgs {
+    /// Note files to give new IDs (default: every file `check` reports with a
+    /// duplicate, invalid or missing ID)
+    pub files: Vec<PathBuf>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `recover` command
+#[derive(Parser, Debug)]
+pub struct RecoverArgs {
+    /// Finish the interrupted operation
+    #[arg(long, conflicts_with = "rollback")]
+    pub resume: bool,
+
+    /// Undo the interrupted operation, restoring the files it changed
+    #[arg(long)]
+    pub rollback: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `rm` command
+#[derive(Parser, Debug)]
+pub struct RmArgs {
+    /// Note to remove (ID prefix, title or alias), or `-` to read notes from stdin
+    pub note: String,
+
+    /// Delete the file for good instead of moving it to the trash
+    #[arg(long)]
+    pub force: bool,
+
+    /// With --force, delete without asking
+    #[arg(short = 'y', long, requires = "force")]
+    pub yes: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `trash` command
+#[derive(Parser, Debug)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub command: Option<TrashCommand>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Subcommands of the `trash` command
+#[derive(Subcommand, Debug)]
+pub enum TrashCommand {
+    /// Move a note from the trash back into the vault
+    Restore(TrashRestoreArgs),
+
+    /// Delete notes from the trash for good
+    Purge(TrashPurgeArgs),
+}
+
+/// Arguments for the `trash restore` command
+#[derive(Parser, Debug)]
+pub struct TrashRestoreArgs {
+    /// Trashed note to restore (path in the trash, ID prefix or title)
+    pub note: String,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `trash purge` command
+#[derive(Parser, Debug)]
+pub struct TrashPurgeArgs {
+    /// Only notes trashed before this (YYYY-MM-DD or relative like "30d")
+    #[arg(
+        long,
+        value_name = "AGE",
+        required_unless_present = "all",
+        conflicts_with = "all"
+    )]
+    pub older_than: Option<String>,
+
+    /// Every note in the trash
+    #[arg(long)]
+    pub all: bool,
+
+    /// Show what would be deleted without deleting anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `stats` command
+#[derive(Parser, Debug)]
+pub struct StatsArgs {
+    #[command(subcommand)]
+    pub command: Option<StatsCommand>,
+
+    /// Show recorded health samples over time
+    #[arg(long)]
+    pub trend: bool,
+
+    /// Number of most recent daily samples to show with --trend
+    #[arg(long, default_value_t = 30)]
+    pub limit: usize,
+
+    /// Days without modification after which a note counts as stale
+    #[arg(long, default_value_t = 180)]
+    pub stale_days: u32,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Subcommands of the `stats` command
+#[derive(Subcommand, Debug)]
+pub enum StatsCommand {
+    /// Show how many notes use each tag, optionally per month
+    Tags(StatsUsageArgs),
+    /// Show how many notes are in each topic, optionally per month
+    Topics(StatsUsageArgs),
+    /// Summarize the vault: note and word counts, top topics and tags, links per rel, orphans, growth and most linked notes
+    Overview(StatsOverviewArgs),
+}
+
+/// Arguments for the `stats tags` and `stats topics` commands
+#[derive(Parser, Debug)]
+pub struct StatsUsageArgs {
+    /// Break usage down by the month notes were created
+    #[arg(long)]
+    pub monthly: bool,
+
+    /// Show only the N most used (0 shows all)
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `stats overview` command
+#[derive(Parser, Debug)]
+pub struct StatsOverviewArgs {
+    /// Number of top topics, tags and most linked notes to show
+    #[arg(long, default_value_t = 5)]
+    pub top: usize,
+
+    /// Show note counts for the last N months (0 shows all)
+    #[arg(long, default_value_t = 12)]
+    pub months: usize,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `keywords` command
+#[derive(Parser, Debug)]
+pub struct KeywordsArgs {
+    /// Note ID or title
+    pub note: String,
+
+    /// Number of keywords to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub limit: usize,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `backlinks` command
+#[derive(Parser, Debug)]
+pub struct BacklinksArgs {
+    /// Note ID or title
+    pub note: String,
+
+    /// Filter by relationship type
+    #[arg(long)]
+    pub rel: Option<String>,
+
+    /// Also show notes that mention the note as `@handle` (its title or alias slug)
+    #[arg(long, conflicts_with_all = ["rel", "recursive"])]
+    pub mentions: bool,
+
+    /// Show a tree of the notes linking to the linkers, and so on
+    #[arg(short = 'r', long)]
+    pub recursive: bool,
+
+    /// Levels of backlinks to follow with --recursive
+    #[arg(long, default_value_t = 3, requires = "recursive")]
+    pub depth: usize,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `urls` command
+#[derive(Parser, Debug)]
+pub struct UrlsArgs {
+    /// Only list URLs on this domain or its subdomains
+    #[arg(long)]
+    pub domain: Option<String>,
+
+    /// Request each URL and list only those that don't answer with success
+    #[arg(long)]
+    pub broken: bool,
+
+    /// Seconds to wait for each URL with --broken
+    #[arg(long, value_name = "SECS", default_value_t = 10, requires = "broken")]
+    pub timeout: u64,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `list-broken` command
+#[derive(Parser, Debug)]
+pub struct ListBrokenArgs {
+    /// Only links from notes in this topic (trailing / includes descendants)
+    #[arg(short = 'T', long)]
+    pub topic: Option<String>,
+
+    /// Only links with this relationship type
+    #[arg(long)]
+    pub rel: Option<String>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `person` command
+#[derive(Parser, Debug)]
+pub struct PersonArgs {
+    #[command(subcommand)]
+    pub command: PersonCommand,
+}
+
+/// Subcommands of the `person` command
+#[derive(Subcommand, Debug)]
+pub enum PersonCommand {
+    /// Create a person note (`type: person`), mentionable as `@name-slug`
+    New(PersonNewArgs),
+}
+
+/// Arguments for the `person new` command
+#[derive(Parser, Debug)]
+pub struct PersonNewArgs {
+    /// The person's name
+    pub name: String,
+
+    /// Topic for the note (can be specified multiple times)
+    #[arg(short = 'T', long = "topic", action = ArgAction::Append)]
+    pub topics: Vec<String>,
+
+    /// Tag for the note (can be specified multiple times)
+    #[arg(short, long = "tag", action = ArgAction::Append)]
+    pub tags: Vec<String>,
+
+    /// Short description
+    #[arg(short = 'D', long)]
+    pub desc: Option<String>,
+
+    /// Create the note without a topic even when `topics.required` asks for one
+    #[arg(long)]
+    pub allow_untopiced: bool,
+
+    /// Open in editor after creation
+    #[arg(short, long)]
+    pub edit: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `link` command
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct LinkArgs {
+    #[command(subcommand)]
+    pub command: Option<LinkCommand>,
+
+    /// Source note ID or title, or `-` to read notes from stdin (omit with --interactive to pick one)
+    pub source: Option<String>,
+
+    /// Target note ID or title (omit with --interactive to pick one)
+    pub target: Option<String>,
+
+    /// Relationship type (can be specified multiple times)
+    #[arg(long = "rel", action = ArgAction::Append)]
+    pub rels: Vec<String>,
+
+    /// Optional context note about the link
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Subcommands of the `link` command
+#[derive(Subcommand, Debug)]
+pub enum LinkCommand {
+    /// Report everything referencing a note before removing or archiving it
+    Check(LinkCheckArgs),
+}
+
+/// Arguments for the `link check` command
+#[derive(Parser, Debug)]
+pub struct LinkCheckArgs {
+    /// Note to audit (ID prefix, title or alias)
+    #[arg(long)]
+    pub target: String,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `unlink` command
+#[derive(Parser, Debug)]
+pub struct UnlinkArgs {
+    /// Source note ID or title
+    pub source: String,
+
+    /// Target note ID or title
+    pub target: String,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `rels` command
+#[derive(Parser, Debug)]
+pub struct RelsArgs {
+    #[command(subcommand)]
+    pub command: Option<RelsCommand>,
+
+    /// Show usage counts for each relationship type
+    #[arg(long)]
+    pub counts: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Subcommands of the `rels` command
+#[derive(Subcommand, Debug)]
+pub enum RelsCommand {
+    /// Print the hierarchy formed by a relationship type as a tree
+    Graph(RelsGraphArgs),
+
+    /// Export the rel types and every typed link as JSON-LD or GraphML
+    Export(RelsExportArgs),
+}
+
+/// Output format for the `rels export` command
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RelsExportFormat {
+    /// JSON-LD, for RDF tools
+    #[default]
+    Jsonld,
+    /// GraphML with one edge per rel, for Gephi and Neo4j import
+    Graphml,
+}
+
+/// Arguments for the `rels export` command
+#[derive(Parser, Debug)]
+pub struct RelsExportArgs {
+    /// Include links from and to archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+
+    /// Export format
+    #[arg(short = 'F', long = "format", value_enum, default_value_t = RelsExportFormat::Jsonld)]
+    pub format: RelsExportFormat,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the `rels graph` command
+#[derive(Parser, Debug)]
+pub struct RelsGraphArgs {
+    /// Relationship type to walk
+    #[arg(long, default_value = "parent")]
+    pub rel: String,
+
+    /// Treat the link target as the child instead of the parent (e.g. for `child` rels)
+    #[arg(long)]
+    pub invert: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Output format for the `graph` command
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    #[default]
+    Dot,
+    /// GraphML, for tools like Gephi and yEd
+    Graphml,
+    /// JSON with node and edge lists
+    Json,
+}
+
+/// Arguments for the `graph` command
+#[derive(Parser, Debug)]
+pub struct GraphArgs {
+    /// Topic to filter by (trailing / includes descendants)
+    pub topic: Option<String>,
+
+    /// Filter by tag (can be specified multiple times)
+    #[arg(short, long = "tag", action = ArgAction::Append)]
+    pub tags: Vec<String>,
+
+    /// Only include notes within --hops links of this note (ID or title)
+    #[arg(long, value_name = "NOTE")]
+    pub from: Option<String>,
+
+    /// Number of links to follow from --from, in either direction
+    #[arg(long, default_value_t = 1, requires = "from")]
+    pub hops: usize,
+
+    /// Include archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+
+    /// Graph format
+    #[arg(short = 'F', long = "format", value_enum, default_value_t = GraphFormat::Dot)]
+    pub format: GraphFormat,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the `bench` command
+#[derive(Parser, Debug)]
+pub struct BenchArgs {
+    /// Number of notes in the generated vault
+    #[arg(long, default_value_t = 1000)]
+    pub notes: usize,
+
+    /// Words in each generated note's body
+    #[arg(long, default_value_t = 200)]
+    pub words: usize,
+
+    /// Times to repeat each measurement (the median is reported)
+    #[arg(long, default_value_t = 5)]
+    pub runs: usize,
+
+    /// Compare with a report saved earlier with `bench -f json`
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// With --baseline, fail if a median is this many percent slower
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        default_value_t = 20.0,
+        requires = "baseline"
+    )]
+    pub max_regression: f64,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `completions` command
 #[derive(Parser, Debug)]
 pub struct CompletionsArgs {
     /// Shell to generate completions for (bash, zsh, fish)
@@ -311,13 +1407,21 @@ pub struct CompletionsArgs {
 /// Arguments for the `mv` command (move/rename note)
 #[derive(Parser, Debug)]
 pub struct MvArgs {
-    /// Note ID or title
+    /// Note ID or title, or `-` to read notes from stdin, one per line
     pub note: String,
 
     /// New title for the note (triggers file rename)
     #[arg(long)]
     pub title: Option<String>,
 
+    /// Title-case the new title, keeping short words and acronyms as they are
+    #[arg(long, requires = "title")]
+    pub title_case: bool,
+
+    /// Don't keep the old title as an alias when renaming
+    #[arg(long, requires = "title")]
+    pub no_alias: bool,
+
     /// Topic for the note (replaces all topics, can be specified multiple times)
     #[arg(short = 'T', long = "topic", action = ArgAction::Append)]
     pub topics: Vec<String>,
@@ -326,6 +1430,15 @@ pub struct MvArgs {
     #[arg(long)]
     pub clear_topics: bool,
 
+    /// Give the note a new ID (or the ULID given), renaming its file, relinking
+    /// notes that link to it and keeping the old ID as an alias
+    #[arg(long, value_name = "ULID", num_args = 0..=1, conflicts_with_all = ["title", "topics", "clear_topics"])]
+    pub id: Option<Option<String>>,
+
+    /// Show what --id would change without writing anything
+    #[arg(long, requires = "id")]
+    pub dry_run: bool,
+
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
@@ -334,8 +1447,25 @@ pub struct MvArgs {
 /// Arguments for the `archive` command
 #[derive(Parser, Debug)]
 pub struct ArchiveArgs {
-    /// Note ID or title
-    pub note: String,
+    /// Note ID or title, or `-` to read notes from stdin, one per line
+    #[arg(required_unless_present = "suggest", conflicts_with = "suggest")]
+    pub note: Option<String>,
+
+    /// List notes that look ready to archive instead of archiving one
+    #[arg(long)]
+    pub suggest: bool,
+
+    /// Months without changes before a note is suggested (overrides config)
+    #[arg(long, requires = "suggest")]
+    pub months: Option<u32>,
+
+    /// Archive the suggested notes, asking for each one
+    #[arg(long, requires = "suggest")]
+    pub apply: bool,
+
+    /// With --apply, archive all suggestions without asking
+    #[arg(short = 'y', long, requires = "apply")]
+    pub yes: bool,
 
     /// Output format
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
@@ -345,7 +1475,7 @@ pub struct ArchiveArgs {
 /// Arguments for the `unarchive` command
 #[derive(Parser, Debug)]
 pub struct UnarchiveArgs {
-    /// Note ID or title
+    /// Note ID or title, or `-` to read notes from stdin, one per line
     pub note: String,
 
     /// Output format
@@ -363,6 +1493,10 @@ pub enum ExportFormat {
     Pdf,
     /// Static site with navigation
     Site,
+    /// Plain text for terminals, pagers and email
+    Man,
+    /// EPUB e-book with a chapter per topic
+    Epub,
 }
 
 /// Arguments for the `export` command
@@ -388,7 +1522,7 @@ pub struct ExportArgs {
     #[arg(long)]
     pub template: Option<PathBuf>,
 
-    /// CSS theme (default, dark, or path to CSS file)
+    /// CSS theme (default, dark, serif, compact, high-contrast, or path to CSS file)
     #[arg(long)]
     pub theme: Option<String>,
 
@@ -400,6 +1534,18 @@ pub struct ExportArgs {
     #[arg(short, long = "tag", action = ArgAction::Append)]
     pub tags: Vec<String>,
 
+    /// Leave out notes in a topic with --all (trailing / includes descendants; repeatable)
+    #[arg(long = "exclude-topic", value_name = "TOPIC", action = ArgAction::Append, conflicts_with = "note")]
+    pub exclude_topics: Vec<String>,
+
+    /// Leave out notes with a tag with --all (repeatable)
+    #[arg(long = "exclude-tag", value_name = "TAG", action = ArgAction::Append, conflicts_with = "note")]
+    pub exclude_tags: Vec<String>,
+
+    /// Leave out notes whose path matches a glob with --all, e.g. 'journal/**' (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB", action = ArgAction::Append, conflicts_with = "note")]
+    pub exclude_paths: Vec<String>,
+
     /// Include archived notes
     #[arg(short = 'a', long)]
     pub include_archived: bool,
@@ -408,14 +1554,280 @@ pub struct ExportArgs {
     #[arg(short = 'r', long)]
     pub resolve_links: bool,
 
+    /// Render TeX math in $...$ and $$...$$ with MathJax
+    #[arg(long)]
+    pub math: bool,
+
+    /// Disable footnotes
+    #[arg(long)]
+    pub no_footnotes: bool,
+
+    /// Disable task list checkboxes
+    #[arg(long)]
+    pub no_task_lists: bool,
+
+    /// Disable callout blocks (> [!note])
+    #[arg(long)]
+    pub no_callouts: bool,
+
+    /// Publish vault statistics (stats.json and an index footer) with a site export
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Copy each note's markdown next to its page with a "View source" link (site export)
+    #[arg(long)]
+    pub include_source: bool,
+
+    /// Add a table of contents linking to the note's headings (HTML and site export)
+    #[arg(long)]
+    pub toc: bool,
+
+    /// Deepest heading level listed in the table of contents (1-6)
+    #[arg(long, value_name = "LEVEL", default_value_t = DEFAULT_TOC_DEPTH, value_parser = clap::value_parser!(u8).range(1..=6))]
+    pub toc_depth: u8,
+
+    /// Insert the table of contents after the note's first heading instead of above the content
+    #[arg(long, requires = "toc")]
+    pub toc_after_heading: bool,
+
+    /// Leave image references as they are instead of copying local images into assets/ (HTML and site export)
+    #[arg(long)]
+    pub no_copy_images: bool,
+
+    /// Scale copied PNG and JPEG images wider than this many pixels down to it
+    #[arg(long, value_name = "PX", conflicts_with = "no_copy_images")]
+    pub max_image_width: Option<u32>,
+
+    /// Line width for plain-text (man) export
+    #[arg(long, default_value_t = DEFAULT_TEXT_WIDTH)]
+    pub width: usize,
+
+    /// Cover image for EPUB export (PNG, JPEG, GIF or SVG)
+    #[arg(long, value_name = "IMAGE")]
+    pub cover: Option<PathBuf>,
+
     /// CLI output format (for status messages, not export content)
     #[arg(long = "cli-format", value_enum, default_value_t = OutputFormat::Human)]
     pub cli_format: OutputFormat,
 }
 
+/// Output format for the `digest` command
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DigestFormat {
+    /// Markdown
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+    /// Plain text for email
+    Text,
+}
+
+/// Arguments for the `digest` command
+#[derive(Parser, Debug)]
+pub struct DigestArgs {
+    /// Include notes created or modified since (YYYY-MM-DD or relative like "7d")
+    #[arg(long, default_value = "7d")]
+    pub since: String,
+
+    /// Digest format
+    #[arg(short = 'F', long = "format", value_enum, default_value_t = DigestFormat::Markdown)]
+    pub format: DigestFormat,
+
+    /// Output file (stdout if not specified)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Link notes to their pages on a site export at this URL instead of to their files
+    #[arg(long, value_name = "URL")]
+    pub site_url: Option<String>,
+
+    /// CSS theme for --format html (default, dark, serif, compact, high-contrast, or path to CSS file)
+    #[arg(long)]
+    pub theme: Option<String>,
+
+    /// Include archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+}
+
+/// Arguments for the `bundle` command
+#[derive(Parser, Debug)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+/// Subcommands of the `bundle` command
+#[derive(Subcommand, Debug)]
+pub enum BundleCommand {
+    /// Write the metadata of the vault's notes to an encrypted bundle
+    Create(BundleCreateArgs),
+    /// Decrypt a bundle and list the notes in it
+    Open(BundleOpenArgs),
+}
+
+/// Arguments for the `bundle create` command
+#[derive(Parser, Debug)]
+pub struct BundleCreateArgs {
+    /// Bundle file to write
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Read the passphrase from the first line of this file instead of $DEN_BUNDLE_PASSPHRASE
+    #[arg(long, value_name = "FILE")]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Leave out notes in a topic (trailing / includes descendants; repeatable)
+    #[arg(long = "exclude-topic", value_name = "TOPIC", action = ArgAction::Append)]
+    pub exclude_topics: Vec<String>,
+
+    /// Leave out notes with a tag (repeatable)
+    #[arg(long = "exclude-tag", value_name = "TAG", action = ArgAction::Append)]
+    pub exclude_tags: Vec<String>,
+
+    /// Leave out notes whose path matches a glob, e.g. 'journal/**' (repeatable)
+    #[arg(long = "exclude", value_name = "GLOB", action = ArgAction::Append)]
+    pub exclude_paths: Vec<String>,
+
+    /// Include archived notes
+    #[arg(short = 'a', long)]
+    pub include_archived: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `bundle open` command
+#[derive(Parser, Debug)]
+pub struct BundleOpenArgs {
+    /// Bundle file to read
+    pub bundle: PathBuf,
+
+    /// Read the passphrase from the first line of this file instead of $DEN_BUNDLE_PASSPHRASE
+    #[arg(long, value_name = "FILE")]
+    pub passphrase_file: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `drill` command
+#[derive(Parser, Debug)]
+pub struct DrillArgs {
+    /// Add a note to the drill deck
+    #[arg(long, value_name = "NOTE", conflicts_with_all = ["remove", "list", "limit"])]
+    pub add: Option<String>,
+
+    /// Remove a note from the drill deck, dropping its schedules
+    #[arg(long, value_name = "NOTE", conflicts_with_all = ["list", "limit"])]
+    pub remove: Option<String>,
+
+    /// List due items instead of drilling them
+    #[arg(long)]
+    pub list: bool,
+
+    /// Drill at most this many items
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+
+    /// Output format for --add, --remove and --list
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `import` command
+#[derive(Parser, Debug)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub command: ImportCommand,
+}
+
+/// Subcommands of the `import` command
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    /// Import markdown files with YAML frontmatter (Zettlr, Dendron, Foam, ...)
+    Generic(ImportGenericArgs),
+}
+
+/// Arguments for the `import generic` command
+#[derive(Parser, Debug)]
+pub struct ImportGenericArgs {
+    /// Directory to import from
+    pub source: PathBuf,
+
+    /// Map a source frontmatter field to a den field (SRC=DST, repeatable)
+    ///
+    /// DST is a den field (title, description, topics, tags, aliases,
+    /// created, modified), optionally written `:title` to insist on one, or
+    /// any other name to keep the value as an extra field. An empty DST
+    /// drops the field.
+    #[arg(long = "map", value_name = "SRC=DST")]
+    pub map: Vec<String>,
+
+    /// Show what would be imported without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `migrate` command
+#[derive(Parser, Debug)]
+pub struct MigrateArgs {
+    /// Rename a frontmatter key (SRC=DST, repeatable; adds to `[migrate.rename]`)
+    ///
+    /// DST is a den field (title, description, topics, tags, aliases,
+    /// created, modified), optionally written `:title` to insist on one, or
+    /// any other name for an extra field. An empty DST removes the key.
+    #[arg(long, value_name = "SRC=DST")]
+    pub rename: Vec<String>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `config` command
+#[derive(Parser, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommand,
+}
+
+/// Subcommands of the `config` command
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Check the config file for syntax errors, unknown keys and bad values
+    Validate(ConfigValidateArgs),
+}
+
+/// Arguments for the `config validate` command
+#[derive(Parser, Debug)]
+pub struct ConfigValidateArgs {
+    /// Config file to check (defaults to the one den loads)
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
+
 /// Arguments for the `vaults` command
 #[derive(Parser, Debug)]
 pub struct VaultsArgs {
+    #[command(subcommand)]
+    pub command: Option<VaultsCommand>,
+
     /// Set the default vault
     #[arg(long)]
     pub set_default: Option<String>,
@@ -424,3 +1836,46 @@ pub struct VaultsArgs {
     #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
     pub format: OutputFormat,
 }
+
+/// Subcommands of the `vaults` command
+#[derive(Subcommand, Debug)]
+pub enum VaultsCommand {
+    /// Export or import the vault's conventions (note types and templates,
+    /// rels, check rules, topic rules and tag styles) as a shareable bundle
+    SyncConfig(SyncConfigArgs),
+}
+
+/// Arguments for the `vaults sync-config` command
+#[derive(Parser, Debug)]
+pub struct SyncConfigArgs {
+    /// Write the profile bundle to this file ("-" for stdout)
+    #[arg(
+        long,
+        value_name = "FILE",
+        required_unless_present = "import",
+        conflicts_with = "import"
+    )]
+    pub export: Option<PathBuf>,
+
+    /// Replace the config's profile sections with those of this bundle and
+    /// write its templates into the vault
+    #[arg(long, value_name = "FILE")]
+    pub import: Option<PathBuf>,
+
+    /// Overwrite template files that differ from the bundle's
+    #[arg(long, requires = "import")]
+    pub force: bool,
+
+    /// Show what an import would change without writing anything
+    #[arg(long, requires = "import")]
+    pub dry_run: bool,
+
+    /// Config file to export from or import into (defaults to the one den
+    /// loads)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = OutputFormat::Human)]
+    pub format: OutputFormat,
+}
@@ -0,0 +1,131 @@
+//! Note types (the `[types]` section).
+//!
+//! A note's type is its `type` frontmatter field. Each configured type can
+//! give new notes a body template, require frontmatter fields (checked by
+//! `check`), and carry an icon and color for `ls`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::cli::tag_style::TagColor;
+
+/// Types available when the config has no `[types]` section.
+const BUILTIN_TYPES: [&str; 4] = ["note", "reference", "person", "project"];
+
+/// The type of notes created by `person new`.
+pub const PERSON_TYPE: &str = "person";
+
+/// Settings for one note type (a `[types.<name>]` table).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NoteTypeOptions {
+    /// Body for new notes of this type, a file relative to the notes
+    /// directory. `{{ title }}`, `{{ type }}` and `{{ date }}` are filled in.
+    pub template: Option<PathBuf>,
+    /// Frontmatter fields notes of this type must have, checked by `check`.
+    pub required: Vec<String>,
+    /// Emoji (or any short prefix) shown with the type.
+    pub icon: Option<String>,
+    /// Color of the type in human output.
+    pub color: Option<TagColor>,
+}
+
+/// Config-defined note types by name (the `[types]` section).
+///
+/// Without a `[types]` section the built-in `note`, `reference`, `person`
+/// and `project` types are available, with no template or required fields.
+/// Defining any type replaces the built-ins.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct NoteTypes(BTreeMap<String, NoteTypeOptions>);
+
+impl Default for NoteTypes {
+    fn default() -> Self {
+        Self(
+            BUILTIN_TYPES
+                .iter()
+                .map(|name| (name.to_string(), NoteTypeOptions::default()))
+                .collect(),
+        )
+    }
+}
+
+impl NoteTypes {
+    /// Returns the settings for a type, if it is configured.
+    pub fn get(&self, name: &str) -> Option<&NoteTypeOptions> {
+        self.0.get(name)
+    }
+
+    /// Returns the configured type names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+
+    /// Returns each type's required fields, for `check`.
+    pub fn required_fields(&self) -> BTreeMap<String, Vec<String>> {
+        self.0
+            .iter()
+            .map(|(name, options)| (name.clone(), options.required.clone()))
+            .collect()
+    }
+
+    /// Renders a type name with its icon and, if `color` is set, its color.
+    pub fn render(&self, name: &str, color: bool) -> String {
+        let options = self.get(name);
+        let text = match options.and_then(|o| o.icon.as_deref()) {
+            Some(icon) => format!("{} {}", icon, name),
+            None => name.to_string(),
+        };
+
+        match options.and_then(|o| o.color) {
+            Some(c) if color => format!("\x1b[{}m{}\x1b[0m", c.ansi_code(), text),
+            _ => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types() -> NoteTypes {
+        toml::from_str(
+            r#"
+            [person]
+            template = ".templates/person.md"
+            required = ["email"]
+            icon = "👤"
+            color = "cyan"
+
+            [meeting]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn default_has_builtin_types() {
+        assert_eq!(
+            NoteTypes::default().names(),
+            vec!["note", "person", "project", "reference"]
+        );
+    }
+
+    #[test]
+    fn configured_types_replace_builtins() {
+        let types = types();
+        assert_eq!(types.names(), vec!["meeting", "person"]);
+        assert_eq!(types.required_fields()["person"], vec!["email"]);
+        assert!(types.required_fields()["meeting"].is_empty());
+    }
+
+    #[test]
+    fn render_icon_and_color() {
+        let types = types();
+        assert_eq!(types.render("person", true), "\x1b[36m👤 person\x1b[0m");
+        assert_eq!(types.render("person", false), "👤 person");
+        assert_eq!(types.render("meeting", true), "meeting");
+        assert_eq!(types.render("unknown", true), "unknown");
+    }
+}
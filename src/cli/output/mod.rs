@@ -0,0 +1,464 @@
+//! Output format types for CLI commands, and the table, color and pager
+//! helpers used for human-readable output.
+
+mod pager;
+mod table;
+mod theme;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+pub use pager::page;
+pub use table::{Align, Cell, Column, Table};
+pub use theme::{ColorChoice, OutputOptions, Painter, Role, ThemeName};
+
+/// Output format for command results.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output (default)
+    #[default]
+    Human,
+    /// JSON output for programmatic consumption
+    Json,
+    /// Plain file paths, one per line
+    Paths,
+}
+
+/// Wrapper for serializable command output.
+#[derive(Debug, Serialize)]
+pub struct Output<T: Serialize> {
+    pub data: T,
+}
+
+impl<T: Serialize> Output<T> {
+    pub fn new(data: T) -> Self {
+        Self { data }
+    }
+}
+
+/// A single note in listing output.
+#[derive(Debug, Serialize)]
+pub struct NoteListing {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub note_type: Option<String>,
+    /// Only filled in by `ls --columns description`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Body word count; only filled in by `ls --largest`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<usize>,
+}
+
+/// An editing session in `recent --sessions` output.
+#[derive(Debug, Serialize)]
+pub struct SessionListing {
+    pub start: String,
+    pub end: String,
+    /// The notes edited in the session, most recent first.
+    pub notes: Vec<NoteListing>,
+    /// Path of the summary note written by `--summarize`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// A topic with optional count.
+#[derive(Debug, Serialize)]
+pub struct TopicListing {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+/// A node in `topics --tree` output, with its subtopics nested.
+#[derive(Debug, Serialize)]
+pub struct TopicTreeListing {
+    pub name: String,
+    pub path: String,
+    /// Notes filed directly under this topic
+    pub count: usize,
+    /// Notes filed under this topic or any descendant
+    pub total: usize,
+    pub children: Vec<TopicTreeListing>,
+}
+
+/// A tag with optional count.
+#[derive(Debug, Serialize)]
+pub struct TagListing {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+/// A scored term in `keywords` output.
+#[derive(Debug, Serialize)]
+pub struct KeywordListing {
+    pub term: String,
+    pub score: f64,
+    pub count: usize,
+    /// True if the note already has a tag with this name.
+    pub tagged: bool,
+}
+
+/// A configured tag group and the tags in it (`tags --groups`).
+#[derive(Debug, Serialize)]
+pub struct TagGroupListing {
+    /// Group name, or `None` for tags not in any group.
+    pub group: Option<String>,
+    pub tags: Vec<TagListing>,
+}
+
+/// A relationship type with optional count.
+#[derive(Debug, Serialize)]
+pub struct RelListing {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<usize>,
+}
+
+/// A node in `rels graph` or `backlinks --recursive` output, with its
+/// children nested.
+#[derive(Debug, Serialize)]
+pub struct RelTreeListing {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// True when this node closes a cycle; its children are not repeated.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub cycle: bool,
+    pub children: Vec<RelTreeListing>,
+}
+
+/// A search result in listing output.
+#[derive(Debug, Serialize)]
+pub struct SearchListing {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub rank: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Heading path of the body section that matched, e.g. `Guide > Setup`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// Per-field ranking breakdown, with `search --explain`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<Vec<FieldMatchListing>>,
+}
+
+/// How one field contributed to a search result's rank.
+#[derive(Debug, Serialize)]
+pub struct FieldMatchListing {
+    pub field: String,
+    pub weight: f64,
+    pub score: f64,
+    pub matched: bool,
+    /// Byte ranges `[start, end]` of the matched terms in the field.
+    pub offsets: Vec<[usize; 2]>,
+    /// Heading path of the matched body section, on the `section` entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// A matching line in `search --in` output.
+#[derive(Debug, Serialize)]
+pub struct LineMatchListing {
+    pub line: usize,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading_line: Option<usize>,
+}
+
+/// Row count for one index table in `index --stats` output.
+#[derive(Debug, Serialize)]
+pub struct TableStatsListing {
+    pub name: String,
+    pub rows: usize,
+}
+
+/// Index statistics in `index --stats` output.
+#[derive(Debug, Serialize)]
+pub struct IndexStatsListing {
+    pub schema_version: i64,
+    pub db_size_bytes: u64,
+    pub fts_size_bytes: u64,
+    pub free_pages: u64,
+    pub page_count: u64,
+    pub last_full_rebuild: Option<String>,
+    pub tokenizer: String,
+    pub tables: Vec<TableStatsListing>,
+    pub suggestions: Vec<String>,
+}
+
+/// A note suggested by `archive --suggest`.
+#[derive(Debug, Serialize)]
+pub struct ArchiveCandidateListing {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    pub modified: String,
+    pub months: u32,
+}
+
+/// A recorded vault health sample in `stats --trend` output.
+#[derive(Debug, Serialize)]
+pub struct HealthSampleListing {
+    pub taken_at: String,
+    pub score: f64,
+    pub notes: usize,
+    pub orphans: usize,
+    pub links: usize,
+    pub broken_links: usize,
+    pub untagged: usize,
+    pub stale: usize,
+}
+
+/// Vault health in `stats` output.
+#[derive(Debug, Serialize)]
+pub struct VaultStatsListing {
+    pub score: f64,
+    pub notes: usize,
+    pub orphans: usize,
+    pub links: usize,
+    pub broken_links: usize,
+    pub untagged: usize,
+    pub stale: usize,
+    pub stale_days: u32,
+    /// Recorded samples, oldest first; present only with `--trend`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<Vec<HealthSampleListing>>,
+}
+
+/// Usage of one tag or topic in `stats tags` / `stats topics` output.
+#[derive(Debug, Serialize)]
+pub struct UsageListing {
+    pub name: String,
+    pub notes: usize,
+    pub first_month: String,
+    pub last_month: String,
+    /// Notes per month, aligned with [`UsageStatsListing::months`]; present
+    /// only with `--monthly`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monthly: Option<Vec<usize>>,
+}
+
+/// Tag or topic usage in `stats tags` / `stats topics` output.
+#[derive(Debug, Serialize)]
+pub struct UsageStatsListing {
+    /// Every month from the first to the last note, as `YYYY-MM`; present
+    /// only with `--monthly`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub months: Option<Vec<String>>,
+    pub items: Vec<UsageListing>,
+}
+
+/// Vault analytics in `stats overview` output.
+#[derive(Debug, Serialize)]
+pub struct VaultOverviewListing {
+    pub notes: usize,
+    /// Body words across all notes
+    pub words: usize,
+    pub average_words: f64,
+    pub topics: usize,
+    pub tags: usize,
+    /// Notes without a topic
+    pub unfiled: usize,
+    pub untagged: usize,
+    pub links: usize,
+    /// Notes without links to or from other notes
+    pub orphans: usize,
+    pub top_topics: Vec<TopicListing>,
+    pub top_tags: Vec<TagListing>,
+    /// Links per relationship type, most used first
+    pub rels: Vec<RelListing>,
+    pub months: Vec<TopicMonthListing>,
+    pub most_linked: Vec<RankedNoteListing>,
+}
+
+/// Notes created in a month in `topics stats` and `stats overview` output.
+#[derive(Debug, Serialize)]
+pub struct TopicMonthListing {
+    pub month: String,
+    /// Notes created in the month
+    pub created: usize,
+    /// Notes created up to the end of the month
+    pub total: usize,
+}
+
+/// A note ranked by a statistic in `topics stats` and `stats overview`
+/// output.
+#[derive(Debug, Serialize)]
+pub struct RankedNoteListing {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    /// Links from other notes; present for the most linked notes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backlinks: Option<usize>,
+    /// Last modification; present for the stalest notes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+}
+
+/// A note in `top` output.
+#[derive(Debug, Serialize)]
+pub struct TopListing {
+    pub rank: usize,
+    pub id: String,
+    pub title: String,
+    pub path: String,
+    /// Backlinks, outbound links or tags, whichever notes are ranked by.
+    pub count: usize,
+}
+
+/// A commit in `show --history` output.
+#[derive(Debug, Serialize)]
+pub struct HistoryListing {
+    pub commit: String,
+    pub short_commit: String,
+    pub date: String,
+    pub author: String,
+    pub message: String,
+    /// The note's path in this commit, which differs after a rename.
+    pub path: String,
+}
+
+/// A note in `graph` output.
+#[derive(Debug, Serialize)]
+pub struct GraphNodeListing {
+    pub id: String,
+    pub title: String,
+    pub path: String,
+}
+
+/// The links from one note to another in `graph` output.
+#[derive(Debug, Serialize)]
+pub struct GraphEdgeListing {
+    pub source: String,
+    pub target: String,
+    /// Relationship types of every link between the two notes.
+    pub rels: Vec<String>,
+}
+
+/// Result of the graph command for JSON output.
+#[derive(Debug, Serialize)]
+pub struct GraphListing {
+    pub nodes: Vec<GraphNodeListing>,
+    pub edges: Vec<GraphEdgeListing>,
+}
+
+/// A direct subtopic in `topics stats` output.
+#[derive(Debug, Serialize)]
+pub struct SubtopicListing {
+    pub path: String,
+    /// Notes filed under the subtopic or its descendants
+    pub notes: usize,
+    /// Share of the topic's notes, from 0 to 1
+    pub share: f64,
+}
+
+/// A topic and its subtopics in `topics stats` output.
+#[derive(Debug, Serialize)]
+pub struct TopicStatsListing {
+    pub topic: String,
+    pub notes: usize,
+    /// Notes filed directly under the topic
+    pub direct: usize,
+    /// Notes without links to or from other notes
+    pub orphans: usize,
+    pub stale: usize,
+    pub stale_days: u32,
+    pub months: Vec<TopicMonthListing>,
+    pub top_tags: Vec<TagListing>,
+    pub most_linked: Vec<RankedNoteListing>,
+    pub stalest: Vec<RankedNoteListing>,
+    pub subtopics: Vec<SubtopicListing>,
+    /// How evenly notes spread over the subtopics, from 0 (all in one) to
+    /// 1 (even); absent with fewer than two subtopics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<f64>,
+}
+
+/// A file that could not be indexed.
+#[derive(Debug, Serialize)]
+pub struct IndexErrorListing {
+    pub path: String,
+    pub message: String,
+}
+
+/// Result of an index update or full rebuild.
+#[derive(Debug, Serialize)]
+pub struct IndexUpdateListing {
+    pub full: bool,
+    pub added: usize,
+    pub modified: usize,
+    pub removed: usize,
+    pub errors: Vec<IndexErrorListing>,
+    /// `<!-- den:link -->` comments turned into frontmatter links.
+    pub converted_links: usize,
+    pub skipped_link_comments: Vec<SkippedLinkCommentListing>,
+    /// Notes whose link titles were brought up to date.
+    pub retitled_notes: usize,
+    /// Notes deleted from the trash by `trash.retention_days`.
+    pub purged_trash: usize,
+}
+
+/// A link comment left in place by an index update.
+#[derive(Debug, Serialize)]
+pub struct SkippedLinkCommentListing {
+    pub path: String,
+    pub comment: String,
+    pub reason: String,
+}
+
+/// Result of `index --vacuum`.
+#[derive(Debug, Serialize)]
+pub struct VacuumListing {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub topics_removed: usize,
+    pub tags_removed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_listing_serializes_to_json() {
+        let listing = SearchListing {
+            id: "01HQ3K5M".to_string(),
+            title: "Test Note".to_string(),
+            path: "test.md".to_string(),
+            rank: 0.75,
+            snippet: Some("matching <b>text</b>".to_string()),
+            section: None,
+            explain: None,
+        };
+        let json = serde_json::to_string(&listing).unwrap();
+        assert!(json.contains("\"rank\":0.75"));
+        assert!(json.contains("\"snippet\":"));
+    }
+
+    #[test]
+    fn search_listing_omits_none_snippet() {
+        let listing = SearchListing {
+            id: "01HQ3K5M".to_string(),
+            title: "Test Note".to_string(),
+            path: "test.md".to_string(),
+            rank: 0.5,
+            snippet: None,
+            section: None,
+            explain: None,
+        };
+        let json = serde_json::to_string(&listing).unwrap();
+        assert!(!json.contains("snippet"));
+        assert!(!json.contains("explain"));
+    }
+}
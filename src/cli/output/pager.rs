@@ -0,0 +1,106 @@
+//! Paging long human output.
+//!
+//! Like git, output that doesn't fit on the terminal is piped through
+//! `$PAGER` (`less` by default) instead of scrolling past.
+
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use super::OutputOptions;
+
+/// Pager used when `PAGER` is unset.
+const DEFAULT_PAGER: &str = "less";
+
+/// Options for `less` when `LESS` is unset: quit if the output fits on one
+/// screen, pass colors through and don't clear the screen on exit.
+const DEFAULT_LESS: &str = "FRX";
+
+/// Writes human output to stdout, through the pager if it's enabled, stdout
+/// is a terminal and the output is taller than it.
+///
+/// Output is written directly if the pager can't be started.
+pub fn page(text: &str, options: &OutputOptions) -> std::io::Result<()> {
+    let stdout = std::io::stdout();
+    let height = stdout
+        .is_terminal()
+        .then(terminal_size::terminal_size)
+        .flatten()
+        .map(|(_, terminal_size::Height(h))| h as usize);
+
+    if options.pager
+        && exceeds(text, height)
+        && let Some(command) = pager_command(std::env::var("PAGER").ok())
+        && run_pager(&command, text).is_ok()
+    {
+        return Ok(());
+    }
+    stdout.lock().write_all(text.as_bytes())
+}
+
+/// Splits `PAGER` into a command and its arguments, or returns `None` if
+/// paging is disabled by an empty `PAGER` or `cat`.
+fn pager_command(pager: Option<String>) -> Option<Vec<String>> {
+    let pager = pager.unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let parts: Vec<String> = pager.split_whitespace().map(String::from).collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+/// Checks if output has more lines than fit on a terminal `height` lines
+/// tall, leaving a line for the prompt. Output is never paged if the height
+/// is unknown.
+fn exceeds(text: &str, height: Option<usize>) -> bool {
+    height.is_some_and(|height| text.lines().count() >= height)
+}
+
+/// Pipes `text` through the pager and waits for it to exit.
+fn run_pager(command: &[String], text: &str) -> std::io::Result<()> {
+    let (cmd, args) = command.split_first().expect("pager command is not empty");
+    let mut pager = Command::new(cmd);
+    pager.args(args).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", DEFAULT_LESS);
+    }
+    let mut child = pager.spawn()?;
+
+    let mut stdin = child.stdin.take().expect("pager stdin is piped");
+    // Quitting the pager early closes the pipe, which isn't an error
+    match stdin.write_all(text.as_bytes()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e),
+        _ => {}
+    }
+    drop(stdin);
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pager_command_defaults_to_less() {
+        assert_eq!(pager_command(None), Some(vec!["less".to_string()]));
+        assert_eq!(
+            pager_command(Some("less -S".to_string())),
+            Some(vec!["less".to_string(), "-S".to_string()])
+        );
+    }
+
+    #[test]
+    fn empty_or_cat_pager_disables_paging() {
+        assert_eq!(pager_command(Some(String::new())), None);
+        assert_eq!(pager_command(Some("  ".to_string())), None);
+        assert_eq!(pager_command(Some("cat".to_string())), None);
+    }
+
+    #[test]
+    fn exceeds_only_when_taller_than_terminal() {
+        let text = "a\nb\nc\n";
+        assert!(exceeds(text, Some(3)));
+        assert!(!exceeds(text, Some(4)));
+        assert!(!exceeds(text, None));
+    }
+}
@@ -0,0 +1,371 @@
+//! Aligned tables for human-readable output.
+
+use super::theme::{Painter, Role};
+
+/// Columns narrower than this are never shrunk further to fit the terminal.
+const MIN_SHRUNK_WIDTH: usize = 10;
+
+/// Gap between columns.
+const GAP: &str = "  ";
+
+/// Truncates a string to a maximum display width, adding ellipsis if needed.
+fn truncate_str(s: &str, max_width: usize) -> String {
+    if s.chars().count() <= max_width {
+        s.to_string()
+    } else {
+        let truncated: String = s.chars().take(max_width.saturating_sub(1)).collect();
+        format!("{}…", truncated)
+    }
+}
+
+/// Character count of `s`, ignoring ANSI escape sequences.
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in s.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Horizontal alignment of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A table column.
+#[derive(Debug, Clone)]
+pub struct Column {
+    header: String,
+    align: Align,
+    max_width: Option<usize>,
+    shrink: bool,
+    role: Option<Role>,
+}
+
+impl Column {
+    /// A left-aligned column.
+    pub fn left(header: impl Into<String>) -> Self {
+        Self {
+            header: header.into(),
+            align: Align::Left,
+            max_width: None,
+            shrink: false,
+            role: None,
+        }
+    }
+
+    /// A right-aligned column, e.g. for numbers and dates.
+    pub fn right(header: impl Into<String>) -> Self {
+        Self {
+            align: Align::Right,
+            ..Self::left(header)
+        }
+    }
+
+    /// Truncates cells longer than `width` characters.
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Lets the column be truncated further when the table is wider than
+    /// the terminal.
+    pub fn shrink(mut self) -> Self {
+        self.shrink = true;
+        self
+    }
+
+    /// Styles every cell in the column.
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = Some(role);
+        self
+    }
+}
+
+/// A table cell: text and an optional role overriding the column's.
+#[derive(Debug, Clone)]
+pub struct Cell {
+    text: String,
+    role: Option<Role>,
+}
+
+impl Cell {
+    pub fn styled(text: impl Into<String>, role: Role) -> Self {
+        Self {
+            text: text.into(),
+            role: Some(role),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self { text, role: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+/// Rows of cells rendered in aligned columns.
+///
+/// Column widths fit the widest cell, capped by [`Column::max_width`]. When
+/// the painter knows the terminal width, [`Column::shrink`] columns are
+/// truncated until the table fits.
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+    indent: usize,
+    header: bool,
+    rule: bool,
+}
+
+impl Table {
+    /// A table with a header row and a rule of dashes under it.
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+            indent: 0,
+            header: true,
+            rule: true,
+        }
+    }
+
+    /// Indents every line by `indent` spaces.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Leaves out the header row (and its rule).
+    pub fn without_header(mut self) -> Self {
+        self.header = false;
+        self
+    }
+
+    /// Leaves out the rule under the header.
+    pub fn without_rule(mut self) -> Self {
+        self.rule = false;
+        self
+    }
+
+    /// Adds a row. Missing trailing cells are left empty.
+    pub fn row<C: Into<Cell>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Renders the table, one line per row, each ending in a newline.
+    pub fn render(&self, painter: &Painter) -> String {
+        let widths = self.widths(painter.width());
+        let mut out = String::new();
+
+        if self.header {
+            let headers: Vec<Cell> = self
+                .columns
+                .iter()
+                .map(|c| Cell::styled(c.header.clone(), Role::Header))
+                .collect();
+            self.render_line(&mut out, &headers, &widths, painter);
+            if self.rule {
+                let rules: Vec<Cell> = widths.iter().map(|w| Cell::from("-".repeat(*w))).collect();
+                self.render_line(&mut out, &rules, &widths, painter);
+            }
+        }
+        for row in &self.rows {
+            self.render_line(&mut out, row, &widths, painter);
+        }
+
+        out
+    }
+
+    /// Prints the rendered table to stdout.
+    pub fn print(&self, painter: &Painter) {
+        print!("{}", self.render(painter));
+    }
+
+    fn widths(&self, available: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let header = if self.header {
+                    column.header.chars().count()
+                } else {
+                    0
+                };
+                let cells = self
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| visible_width(&cell.text))
+                    .max()
+                    .unwrap_or(0);
+                let width = header.max(cells);
+                match column.max_width {
+                    Some(max) => width.min(max.max(header)),
+                    None => width,
+                }
+            })
+            .collect();
+
+        let Some(available) = available else {
+            return widths;
+        };
+        let total =
+            self.indent + widths.iter().sum::<usize>() + GAP.len() * widths.len().saturating_sub(1);
+        let mut excess = total.saturating_sub(available);
+
+        // Take from the widest shrinkable columns first
+        let mut order: Vec<usize> = (0..widths.len())
+            .filter(|&i| self.columns[i].shrink)
+            .collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(widths[i]));
+        for i in order {
+            if excess == 0 {
+                break;
+            }
+            let floor = MIN_SHRUNK_WIDTH.max(self.columns[i].header.chars().count());
+            let take = widths[i].saturating_sub(floor).min(excess);
+            widths[i] -= take;
+            excess -= take;
+        }
+
+        widths
+    }
+
+    fn render_line(&self, out: &mut String, cells: &[Cell], widths: &[usize], painter: &Painter) {
+        let mut line = " ".repeat(self.indent);
+        for (i, (column, width)) in self.columns.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.push_str(GAP);
+            }
+            let cell = cells.get(i);
+            let text = cell.map(|c| c.text.as_str()).unwrap_or_default();
+            // Pre-styled text (e.g. colored tags) can't be cut safely
+            let text = if text.contains('\x1b') {
+                text.to_string()
+            } else {
+                truncate_str(text, *width)
+            };
+            let padding = " ".repeat(width.saturating_sub(visible_width(&text)));
+            let painted = match cell.and_then(|c| c.role).or(column.role) {
+                Some(role) => painter.paint(role, &text),
+                None => text,
+            };
+            match column.align {
+                Align::Left => {
+                    line.push_str(&painted);
+                    line.push_str(&padding);
+                }
+                Align::Right => {
+                    line.push_str(&padding);
+                    line.push_str(&painted);
+                }
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new(vec![
+            Column::left("ID"),
+            Column::left("Title").max_width(20).shrink(),
+            Column::right("Count"),
+        ]);
+        table.row(["01HQ", "A fairly long title for a note", "3"]);
+        table.row(["01HR", "Short", "12"]);
+        table
+    }
+
+    #[test]
+    fn truncate_str_adds_ellipsis() {
+        assert_eq!(truncate_str("short", 10), "short");
+        assert_eq!(truncate_str("a longer text", 8), "a longe…");
+    }
+
+    #[test]
+    fn render_aligns_columns_and_caps_width() {
+        let rendered = sample().render(&Painter::plain());
+
+        assert_eq!(
+            rendered,
+            "ID    Title                 Count\n\
+             ----  --------------------  -----\n\
+             01HQ  A fairly long title…      3\n\
+             01HR  Short                    12\n"
+        );
+    }
+
+    #[test]
+    fn render_shrinks_to_available_width() {
+        let rendered = sample().render(&Painter::plain().with_width(Some(25)));
+
+        for line in rendered.lines() {
+            assert!(line.chars().count() <= 25, "{:?}", line);
+        }
+        assert!(
+            rendered.contains("01HQ  A fairly lo…      3"),
+            "{}",
+            rendered
+        );
+    }
+
+    #[test]
+    fn render_without_header_and_with_indent() {
+        let mut table = Table::new(vec![Column::left(""), Column::right("")])
+            .indent(2)
+            .without_header();
+        table.row(["notes", "10"]);
+        table.row(["links", "7"]);
+
+        assert_eq!(
+            table.render(&Painter::plain()),
+            "  notes  10\n  links   7\n"
+        );
+    }
+
+    #[test]
+    fn render_paints_headers_and_roles() {
+        let mut table = Table::new(vec![
+            Column::left("ID").role(Role::Id),
+            Column::left("Note"),
+        ])
+        .without_rule();
+        table.row([Cell::from("01HQ"), Cell::styled("broken", Role::Error)]);
+        let painter = Painter::new(&Default::default()).with_color(true);
+
+        let rendered = table.render(&painter);
+
+        assert!(rendered.starts_with("\x1b[1mID\x1b[0m    \x1b[1mNote\x1b[0m\n"));
+        assert!(rendered.contains("\x1b[33m01HQ\x1b[0m  \x1b[1;31mbroken\x1b[0m"));
+    }
+
+    #[test]
+    fn visible_width_skips_escapes() {
+        assert_eq!(visible_width("\x1b[31mred\x1b[0m"), 3);
+    }
+}
@@ -0,0 +1,247 @@
+//! Colors for human-readable output.
+
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+
+/// A named set of colors (the `[output] theme` setting).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// Colored IDs, dimmed dates and bold headers (default)
+    #[default]
+    Default,
+    /// Only headers, errors and warnings are styled
+    Minimal,
+    /// No colors, even on a terminal
+    Plain,
+}
+
+/// When to write colors (the `[output] color` setting).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Only when stdout is a terminal and `NO_COLOR` is unset (default)
+    #[default]
+    Auto,
+    /// Always, e.g. when piping into `less -R`
+    Always,
+    Never,
+}
+
+/// Human output settings (the `[output]` section).
+///
+/// JSON and paths output are never styled or paged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OutputOptions {
+    pub theme: ThemeName,
+    pub color: ColorChoice,
+    /// Page long `ls`, `search` and `show` output on a terminal.
+    pub pager: bool,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        Self {
+            theme: ThemeName::default(),
+            color: ColorChoice::default(),
+            pager: true,
+        }
+    }
+}
+
+/// What a piece of output is, which decides how the theme styles it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Table headers and section titles
+    Header,
+    /// Note IDs and refs
+    Id,
+    /// Dates, counts and other secondary details
+    Muted,
+    Success,
+    Warning,
+    Error,
+    /// Highlighted values, e.g. matched terms
+    Accent,
+}
+
+impl ThemeName {
+    /// The SGR parameters for a role, or `None` to leave it unstyled.
+    fn sgr(self, role: Role) -> Option<&'static str> {
+        match (self, role) {
+            (ThemeName::Plain, _) => None,
+            (_, Role::Header) => Some("1"),
+            (_, Role::Error) => Some("1;31"),
+            (_, Role::Warning) => Some("33"),
+            (ThemeName::Minimal, _) => None,
+            (ThemeName::Default, Role::Id) => Some("33"),
+            (ThemeName::Default, Role::Muted) => Some("2"),
+            (ThemeName::Default, Role::Success) => Some("32"),
+            (ThemeName::Default, Role::Accent) => Some("36"),
+        }
+    }
+}
+
+/// Applies the configured theme to human output and knows how wide the
+/// terminal is.
+#[derive(Debug, Clone, Copy)]
+pub struct Painter {
+    theme: ThemeName,
+    color: bool,
+    width: Option<usize>,
+}
+
+impl Painter {
+    /// Creates a painter for stdout.
+    pub fn new(options: &OutputOptions) -> Self {
+        let stdout = std::io::stdout();
+        Self {
+            theme: options.theme,
+            color: colors_enabled(
+                options,
+                stdout.is_terminal(),
+                std::env::var_os("NO_COLOR").is_some(),
+            ),
+            width: terminal_width(stdout.is_terminal()),
+        }
+    }
+
+    /// A painter that writes no colors and never truncates.
+    pub fn plain() -> Self {
+        Self {
+            theme: ThemeName::Plain,
+            color: false,
+            width: None,
+        }
+    }
+
+    /// Overrides the available width.
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Overrides whether colors are written.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Returns true if colors are written, e.g. for tag colors.
+    pub fn color(&self) -> bool {
+        self.color
+    }
+
+    /// The terminal width, if known.
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Styles text for its role.
+    pub fn paint(&self, role: Role, text: &str) -> String {
+        match self.theme.sgr(role) {
+            Some(sgr) if self.color && !text.is_empty() => format!("\x1b[{}m{}\x1b[0m", sgr, text),
+            _ => text.to_string(),
+        }
+    }
+}
+
+/// Decides whether to write colors. `NO_COLOR` only applies to `auto`.
+fn colors_enabled(options: &OutputOptions, is_terminal: bool, no_color: bool) -> bool {
+    if options.theme == ThemeName::Plain {
+        return false;
+    }
+    match options.color {
+        ColorChoice::Auto => is_terminal && !no_color,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    }
+}
+
+/// The width to fit output to: `COLUMNS` if set, otherwise the terminal's
+/// width. Output that isn't going to a terminal is not truncated.
+fn terminal_width(is_terminal: bool) -> Option<usize> {
+    let columns = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok());
+    columns.or_else(|| {
+        is_terminal
+            .then(terminal_size::terminal_size)
+            .flatten()
+            .map(|(terminal_size::Width(w), _)| w as usize)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(theme: ThemeName, color: ColorChoice) -> OutputOptions {
+        OutputOptions {
+            theme,
+            color,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn auto_colors_only_on_terminal_without_no_color() {
+        let auto = options(ThemeName::Default, ColorChoice::Auto);
+        assert!(colors_enabled(&auto, true, false));
+        assert!(!colors_enabled(&auto, false, false));
+        assert!(!colors_enabled(&auto, true, true));
+    }
+
+    #[test]
+    fn explicit_choice_overrides_terminal_and_no_color() {
+        let always = options(ThemeName::Default, ColorChoice::Always);
+        let never = options(ThemeName::Default, ColorChoice::Never);
+        assert!(colors_enabled(&always, false, true));
+        assert!(!colors_enabled(&never, true, false));
+    }
+
+    #[test]
+    fn plain_theme_never_colors() {
+        let plain = options(ThemeName::Plain, ColorChoice::Always);
+        assert!(!colors_enabled(&plain, true, false));
+    }
+
+    #[test]
+    fn paint_uses_theme_codes() {
+        let painter = Painter::plain().with_color(true);
+        assert_eq!(painter.paint(Role::Id, "01HQ"), "01HQ");
+
+        let default = Painter {
+            theme: ThemeName::Default,
+            ..painter
+        };
+        assert_eq!(default.paint(Role::Id, "01HQ"), "\x1b[33m01HQ\x1b[0m");
+        assert_eq!(default.paint(Role::Header, "ID"), "\x1b[1mID\x1b[0m");
+
+        let minimal = Painter {
+            theme: ThemeName::Minimal,
+            ..painter
+        };
+        assert_eq!(minimal.paint(Role::Id, "01HQ"), "01HQ");
+        assert_eq!(
+            minimal.paint(Role::Error, "error"),
+            "\x1b[1;31merror\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn paint_without_color_is_unchanged() {
+        let painter = Painter::new(&OutputOptions::default()).with_color(false);
+        assert_eq!(painter.paint(Role::Error, "error"), "error");
+    }
+
+    #[test]
+    fn options_parse_from_toml() {
+        let parsed: OutputOptions =
+            toml::from_str("theme = \"minimal\"\ncolor = \"never\"").unwrap();
+        assert_eq!(parsed, options(ThemeName::Minimal, ColorChoice::Never));
+        assert!(parsed.pager);
+        let parsed: OutputOptions = toml::from_str("pager = false").unwrap();
+        assert!(!parsed.pager);
+        assert!(toml::from_str::<OutputOptions>("theme = \"neon\"").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Fuzzy picker for choosing a note interactively.
+//!
+//! The picker is line-based so it works in any terminal and over pipes: it
+//! lists the best matches for the current query, numbered. Typing one of
+//! the numbers shown picks that note, Enter picks the first, and any other
+//! text (including other numbers, like a year) becomes the new query. Queries match fuzzily (as a subsequence) against each note's
+//! title, aliases, topics and ID.
+
+use anyhow::{Result, bail};
+use std::io::{BufRead, Write};
+
+use crate::index::IndexedNote;
+
+/// How many matches are listed at a time.
+const SHOWN: usize = 10;
+
+/// Scores how well `query` matches `candidate`, or `None` if the query's
+/// characters don't all appear in it, in order.
+///
+/// Case is ignored. Matches that start words or run on from the previous
+/// match score higher, and skipped characters cost a little, so `apd`
+/// prefers "API Design" over "a parsed document".
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+    for (i, c) in chars.iter().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if *c != query[next] {
+            continue;
+        }
+        score += 1;
+        let word_start = i == 0 || !chars[i - 1].is_alphanumeric();
+        if word_start {
+            score += 8;
+        }
+        match last_match {
+            Some(last) if last + 1 == i => score += 5,
+            Some(last) => score -= (i - last - 1).min(5) as i64,
+            None => score -= i.min(5) as i64,
+        }
+        last_match = Some(i);
+        next += 1;
+    }
+    (next == query.len()).then_some(score)
+}
+
+/// Scores a note against `query`: the best match over its title, aliases,
+/// topics and ID, with the title weighted highest.
+fn note_score(query: &str, note: &IndexedNote) -> Option<i64> {
+    let title = fuzzy_score(query, note.title()).map(|s| s + 2);
+    let others = note
+        .aliases()
+        .iter()
+        .cloned()
+        .chain(note.topics().iter().map(|t| t.to_string()))
+        .chain(std::iter::once(note.id().to_string()))
+        .filter_map(|text| fuzzy_score(query, &text));
+    title.into_iter().chain(others).max()
+}
+
+/// Returns the notes matching `query`, best first. Ties keep their order in
+/// `notes`, as does every note for an empty query.
+pub fn rank<'a>(query: &str, notes: &'a [IndexedNote]) -> Vec<&'a IndexedNote> {
+    let mut scored: Vec<(i64, usize, &IndexedNote)> = notes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, note)| note_score(query, note).map(|score| (score, i, note)))
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored.into_iter().map(|(_, _, note)| note).collect()
+}
+
+/// Lets the user pick one of `notes`, reading answers from `input` and
+/// writing the listing to stderr.
+///
+/// `header` is printed once before the first listing. Fails if the input
+/// ends before a note is chosen.
+pub fn pick_note<R: BufRead>(
+    header: &str,
+    mut notes: Vec<IndexedNote>,
+    noun: &str,
+    mut input: R,
+) -> Result<IndexedNote> {
+    eprintln!("{}", header);
+    let mut query = String::new();
+    loop {
+        let matches = rank(&query, &notes);
+        if matches.is_empty() {
+            eprintln!("No notes match '{}'.", query);
+        }
+        for (i, note) in matches.iter().take(SHOWN).enumerate() {
+            let marker = if i == 0 { '>' } else { ' ' };
+            eprintln!(
+                "{} {:>2}) {} - {}",
+                marker,
+                i + 1,
+                note.id().prefix(),
+                note.title()
+            );
+            if let Some(desc) = note.description() {
+                eprintln!("        {}", desc);
+            }
+        }
+        if matches.len() > SHOWN {
+            eprintln!("  ... {} more; type to narrow", matches.len() - SHOWN);
+        }
+        let shown = matches.len().min(SHOWN);
+        eprint!("Which {}? [1-{}, or text to filter] ", noun, shown);
+        std::io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            bail!("ambiguous {} identifier: no note chosen", noun);
+        }
+        let answer = answer.trim();
+        let choice = if answer.is_empty() {
+            (shown > 0).then_some(1)
+        } else if let Some(n) = answer
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=shown).contains(n))
+        {
+            Some(n)
+        } else {
+            query = answer.to_string();
+            None
+        };
+
+        if let Some(n) = choice {
+            let id = matches[n - 1].id().clone();
+            let pos = notes
+                .iter()
+                .position(|note| *note.id() == id)
+                .expect("ranked notes come from the list");
+            return Ok(notes.swap_remove(pos));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{NoteId, Topic};
+    use crate::infra::ContentHash;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn note(id: &str, title: &str, topic: &str) -> IndexedNote {
+        let now = Utc::now();
+        IndexedNote::builder(
+            id.parse::<NoteId>().unwrap(),
+            title,
+            now,
+            now,
+            PathBuf::from(format!("{}.md", title)),
+            ContentHash::compute(title.as_bytes()),
+        )
+        .topics(vec![Topic::new(topic).unwrap()])
+        .build()
+    }
+
+    fn notes() -> Vec<IndexedNote> {
+        vec![
+            note("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "A parsed document", "misc"),
+            note(
+                "01HQ4A2R9PXJK4QZPW8V2R6T9Y",
+                "API Design",
+                "software/architecture",
+            ),
+            note(
+                "01HQ5B3S0QXJK4QZPW8V2R6T9Y",
+                "Rust Ownership",
+                "software/rust",
+            ),
+        ]
+    }
+
+    #[test]
+    fn fuzzy_score_requires_characters_in_order() {
+        assert!(fuzzy_score("rso", "Rust Ownership").is_some());
+        assert!(fuzzy_score("osr", "Rust Ownership").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        let design = fuzzy_score("apd", "API Design").unwrap();
+        let document = fuzzy_score("apd", "A parsed document").unwrap();
+        assert!(design > document, "{} <= {}", design, document);
+    }
+
+    #[test]
+    fn rank_matches_topics_and_ids() {
+        let notes = notes();
+        let ranked = rank("rust", &notes);
+        assert_eq!(ranked[0].title(), "Rust Ownership");
+
+        let ranked = rank("architecture", &notes);
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].title(), "API Design");
+
+        assert_eq!(rank("01HQ5B", &notes)[0].title(), "Rust Ownership");
+        assert_eq!(rank("", &notes).len(), 3);
+    }
+
+    #[test]
+    fn pick_note_filters_then_picks() {
+        let chosen = pick_note("Notes:", notes(), "note", &b"own\n\n"[..]).unwrap();
+        assert_eq!(chosen.title(), "Rust Ownership");
+
+        let chosen = pick_note("Notes:", notes(), "note", &b"api\n1\n"[..]).unwrap();
+        assert_eq!(chosen.title(), "API Design");
+
+        let chosen = pick_note("Notes:", notes(), "note", &b"2\n"[..]).unwrap();
+        assert_eq!(chosen.title(), "API Design");
+    }
+
+    #[test]
+    fn pick_note_filters_by_numbers_not_shown() {
+        let mut notes = notes();
+        notes.push(note("01HQ6C4T1RXJK4QZPW8V2R6T9Y", "Review 2026", "misc"));
+        let chosen = pick_note(
+            "Notes:",
+            notes,
+            "note",
+            &b"2026
+1
+"[..],
+        )
+        .unwrap();
+        assert_eq!(chosen.title(), "Review 2026");
+    }
+
+    #[test]
+    fn pick_note_fails_without_a_choice() {
+        for answer in ["", "4\n", "zzz\n", "zzz\n\n"] {
+            let err = pick_note("Notes:", notes(), "note", answer.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains("no note chosen"), "{:?}", answer);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Tag colors, emojis and groups for human-readable output, and tag expiry.
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::Tag;
+
+/// Terminal color for a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl TagColor {
+    pub(crate) fn ansi_code(self) -> u8 {
+        match self {
+            TagColor::Black => 30,
+            TagColor::Red => 31,
+            TagColor::Green => 32,
+            TagColor::Yellow => 33,
+            TagColor::Blue => 34,
+            TagColor::Magenta => 35,
+            TagColor::Cyan => 36,
+            TagColor::White => 37,
+        }
+    }
+}
+
+/// Config-defined presentation and expiry of tags (the `[tags]` section).
+///
+/// Colors follow the `[output]` color setting; emojis are always shown in
+/// human output. JSON and paths output are unaffected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TagStyles {
+    /// Color per tag name.
+    pub colors: HashMap<String, TagColor>,
+    /// Emoji (or any short prefix) per tag name.
+    pub emojis: HashMap<String, String>,
+    /// Named groups of tags for `tags --groups`, e.g. `status = ["todo", "done"]`.
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Days after it is added that a tag expires, per tag name, e.g.
+    /// `wip = 30`.
+    pub ttl: BTreeMap<String, u32>,
+    /// Tag that replaces an expired tag, per tag name, e.g.
+    /// `review-by = "overdue"`; expired tags without one are removed.
+    pub expired: BTreeMap<String, String>,
+}
+
+impl TagStyles {
+    /// Returns true if any tag has a color or emoji configured.
+    pub fn is_styled(&self) -> bool {
+        !self.colors.is_empty() || !self.emojis.is_empty()
+    }
+
+    /// Renders a single tag with its emoji and, if `color` is set, its color.
+    pub fn render(&self, tag: &str, color: bool) -> String {
+        let text = match self.emojis.get(tag) {
+            Some(emoji) => format!("{} {}", emoji, tag),
+            None => tag.to_string(),
+        };
+
+        match self.colors.get(tag) {
+            Some(c) if color => format!("\x1b[{}m{}\x1b[0m", c.ansi_code(), text),
+            _ => text,
+        }
+    }
+
+    /// Renders a comma-separated list of tags.
+    pub fn render_list<'a>(&self, tags: impl IntoIterator<Item = &'a str>, color: bool) -> String {
+        tags.into_iter()
+            .map(|t| self.render(t, color))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the name of the first group (by name) that contains the tag.
+    pub fn group_of(&self, tag: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(_, tags)| tags.iter().any(|t| t == tag))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Dates a tag being added on `today` if its name has a TTL, so it
+    /// expires that many days later however often the note is edited.
+    pub fn with_ttl(&self, tag: Tag, today: NaiveDate) -> Tag {
+        match self.ttl.get(tag.name()) {
+            Some(days) if tag.expires().is_none() => {
+                tag.expiring(today + Duration::days(i64::from(*days)))
+            }
+            _ => tag,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styles() -> TagStyles {
+        toml::from_str(
+            r#"
+            colors = { urgent = "red", done = "green" }
+            emojis = { urgent = "🔥" }
+
+            [groups]
+            status = ["todo", "done"]
+            area = ["work", "home"]
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn render_plain_without_style() {
+        assert_eq!(styles().render("misc", true), "misc");
+    }
+
+    #[test]
+    fn render_emoji_and_color() {
+        assert_eq!(styles().render("urgent", true), "\x1b[31m🔥 urgent\x1b[0m");
+        assert_eq!(styles().render("urgent", false), "🔥 urgent");
+        assert_eq!(styles().render("done", true), "\x1b[32mdone\x1b[0m");
+    }
+
+    #[test]
+    fn render_list_joins_with_commas() {
+        assert_eq!(
+            styles().render_list(["urgent", "misc"], false),
+            "🔥 urgent, misc"
+        );
+    }
+
+    #[test]
+    fn group_of_finds_configured_group() {
+        let styles = styles();
+        assert_eq!(styles.group_of("done"), Some("status"));
+        assert_eq!(styles.group_of("home"), Some("area"));
+        assert_eq!(styles.group_of("misc"), None);
+    }
+
+    #[test]
+    fn with_ttl_dates_tags_with_a_ttl() {
+        let styles: TagStyles = toml::from_str("ttl = { wip = 30 }").unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let tag = |s: &str| Tag::new(s).unwrap();
+
+        assert_eq!(styles.with_ttl(tag("wip"), today), tag("wip:2024-02-14"));
+        assert_eq!(
+            styles.with_ttl(tag("wip:2024-01-20"), today),
+            tag("wip:2024-01-20")
+        );
+        assert_eq!(styles.with_ttl(tag("draft"), today), tag("draft"));
+    }
+
+    #[test]
+    fn unknown_color_is_rejected() {
+        let result: Result<TagStyles, _> = toml::from_str(r#"colors = { a = "teal" }"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn default_is_unstyled() {
+        assert!(!TagStyles::default().is_styled());
+        assert!(styles().is_styled());
+    }
+}
@@ -0,0 +1,163 @@
+//! Timestamp rendering for human-readable output.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How timestamps are shown in human-readable output.
+///
+/// Times are converted to the local time zone unless `utc` is set. JSON
+/// output always uses RFC 3339 in UTC and ignores these settings.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimeFormat {
+    /// strftime format for dates, e.g. in `ls` and `show`.
+    pub date_format: String,
+    /// strftime format for date-times, e.g. in `index --stats`.
+    pub datetime_format: String,
+    /// Show times in UTC instead of the local time zone.
+    pub utc: bool,
+    /// Show full RFC 3339 timestamps instead of the format strings.
+    pub iso: bool,
+}
+
+impl Default for TimeFormat {
+    fn default() -> Self {
+        Self {
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            datetime_format: DEFAULT_DATETIME_FORMAT.to_string(),
+            utc: false,
+            iso: false,
+        }
+    }
+}
+
+impl TimeFormat {
+    /// Renders a timestamp as a date.
+    pub fn date(&self, dt: DateTime<Utc>) -> String {
+        self.render(dt, &self.date_format, DEFAULT_DATE_FORMAT)
+    }
+
+    /// Renders a timestamp as a date and time.
+    pub fn datetime(&self, dt: DateTime<Utc>) -> String {
+        self.render(dt, &self.datetime_format, DEFAULT_DATETIME_FORMAT)
+    }
+
+    /// Returns the day `now` falls on, in UTC if `utc` is set.
+    pub fn today(&self, now: DateTime<Utc>) -> NaiveDate {
+        if self.utc {
+            now.date_naive()
+        } else {
+            now.with_timezone(&Local).date_naive()
+        }
+    }
+
+    fn render(&self, dt: DateTime<Utc>, format: &str, fallback: &str) -> String {
+        // An invalid format string would panic inside chrono's Display impl
+        let format = if is_valid_format(format) {
+            format
+        } else {
+            fallback
+        };
+
+        match (self.utc, self.iso) {
+            (true, true) => dt.to_rfc3339_opts(SecondsFormat::Secs, true),
+            (true, false) => dt.format(format).to_string(),
+            (false, true) => dt
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::Secs, false),
+            (false, false) => dt.with_timezone(&Local).format(format).to_string(),
+        }
+    }
+}
+
+/// Returns true if `format` is a valid strftime format string.
+pub(crate) fn is_valid_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-01-15T10:30:45Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn utc() -> TimeFormat {
+        TimeFormat {
+            utc: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn default_formats_in_utc() {
+        assert_eq!(utc().date(sample()), "2024-01-15");
+        assert_eq!(utc().datetime(sample()), "2024-01-15 10:30");
+    }
+
+    #[test]
+    fn default_uses_local_time_zone() {
+        let local = sample().with_timezone(&Local);
+
+        assert_eq!(
+            TimeFormat::default().datetime(sample()),
+            local.format("%Y-%m-%d %H:%M").to_string()
+        );
+    }
+
+    #[test]
+    fn iso_utc_is_rfc3339_with_z() {
+        let format = TimeFormat { iso: true, ..utc() };
+
+        assert_eq!(format.date(sample()), "2024-01-15T10:30:45Z");
+        assert_eq!(format.datetime(sample()), "2024-01-15T10:30:45Z");
+    }
+
+    #[test]
+    fn iso_local_round_trips() {
+        let format = TimeFormat {
+            iso: true,
+            ..Default::default()
+        };
+
+        let rendered = format.datetime(sample());
+        assert_eq!(DateTime::parse_from_rfc3339(&rendered).unwrap(), sample());
+    }
+
+    #[test]
+    fn custom_format_strings() {
+        let format = TimeFormat {
+            date_format: "%d/%m/%Y".to_string(),
+            datetime_format: "%b %e %H:%M:%S".to_string(),
+            ..utc()
+        };
+
+        assert_eq!(format.date(sample()), "15/01/2024");
+        assert_eq!(format.datetime(sample()), "Jan 15 10:30:45");
+    }
+
+    #[test]
+    fn invalid_format_falls_back_to_default() {
+        let format = TimeFormat {
+            date_format: "%Q".to_string(),
+            ..utc()
+        };
+
+        assert_eq!(format.date(sample()), "2024-01-15");
+    }
+
+    #[test]
+    fn deserializes_partial_config() {
+        let format: TimeFormat = toml::from_str("utc = true").unwrap();
+
+        assert!(format.utc);
+        assert_eq!(format.date_format, DEFAULT_DATE_FORMAT);
+    }
+}
@@ -0,0 +1,189 @@
+//! Vault health metrics.
+//!
+//! A pure summary of how tidy a collection of notes is, combining the share
+//! of orphaned, untagged and stale notes with the share of broken links.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+use crate::domain::{Note, NoteId};
+
+/// Counts behind a vault health score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HealthReport {
+    /// Number of notes considered.
+    pub notes: usize,
+    /// Notes without any topic.
+    pub orphans: usize,
+    /// Total number of links across all notes.
+    pub links: usize,
+    /// Links whose target is not among the notes.
+    pub broken_links: usize,
+    /// Notes without any tag.
+    pub untagged: usize,
+    /// Notes not modified within the stale threshold.
+    pub stale: usize,
+}
+
+impl HealthReport {
+    /// Share of notes without a topic, from 0.0 to 1.0.
+    pub fn orphan_ratio(&self) -> f64 {
+        ratio(self.orphans, self.notes)
+    }
+
+    /// Share of links pointing at missing notes, from 0.0 to 1.0.
+    pub fn broken_link_ratio(&self) -> f64 {
+        ratio(self.broken_links, self.links)
+    }
+
+    /// Share of notes without a tag, from 0.0 to 1.0.
+    pub fn untagged_ratio(&self) -> f64 {
+        ratio(self.untagged, self.notes)
+    }
+
+    /// Share of stale notes, from 0.0 to 1.0.
+    pub fn stale_ratio(&self) -> f64 {
+        ratio(self.stale, self.notes)
+    }
+
+    /// Composite score from 0 (every ratio at 1.0) to 100 (all ratios zero).
+    ///
+    /// Each of the four ratios is weighted equally.
+    pub fn score(&self) -> f64 {
+        let mean = (self.orphan_ratio()
+            + self.broken_link_ratio()
+            + self.untagged_ratio()
+            + self.stale_ratio())
+            / 4.0;
+        ((1.0 - mean) * 1000.0).round() / 10.0
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Computes health counts for a collection of notes.
+///
+/// A note is stale when it was last modified more than `stale_after` before
+/// `now`. Links are checked against the IDs of the given notes.
+pub fn compute_health(notes: &[&Note], now: DateTime<Utc>, stale_after: Duration) -> HealthReport {
+    let ids: HashSet<&NoteId> = notes.iter().map(|n| n.id()).collect();
+    let cutoff = now - stale_after;
+
+    let mut report = HealthReport {
+        notes: notes.len(),
+        orphans: 0,
+        links: 0,
+        broken_links: 0,
+        untagged: 0,
+        stale: 0,
+    };
+
+    for note in notes {
+        if note.topics().is_empty() {
+            report.orphans += 1;
+        }
+        if note.tags().is_empty() {
+            report.untagged += 1;
+        }
+        if note.modified() < cutoff {
+            report.stale += 1;
+        }
+        for link in note.links() {
+            report.links += 1;
+            if !ids.contains(link.target()) {
+                report.broken_links += 1;
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Link, Tag, Topic};
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn id(suffix: &str) -> NoteId {
+        format!("01HQ3K5M7NXJK4QZPW8V2R6T{}", suffix)
+            .parse()
+            .unwrap()
+    }
+
+    fn note(suffix: &str, days_old: i64) -> crate::domain::NoteBuilder {
+        let modified = now() - Duration::days(days_old);
+        Note::builder(id(suffix), "Note", modified, modified)
+    }
+
+    #[test]
+    fn empty_vault_is_perfectly_healthy() {
+        let report = compute_health(&[], now(), Duration::days(180));
+        assert_eq!(report.notes, 0);
+        assert_eq!(report.score(), 100.0);
+    }
+
+    #[test]
+    fn tidy_note_scores_full_marks() {
+        let a = note("0A", 1)
+            .topics(vec![Topic::new("rust").unwrap()])
+            .tags(vec![Tag::new("draft").unwrap()])
+            .build()
+            .unwrap();
+
+        let report = compute_health(&[&a], now(), Duration::days(180));
+        assert_eq!(report.score(), 100.0);
+    }
+
+    #[test]
+    fn counts_each_kind_of_issue() {
+        let a = note("0A", 400)
+            .links(vec![
+                Link::new(id("0B"), vec!["see-also"]).unwrap(),
+                Link::new(id("0Z"), vec!["see-also"]).unwrap(),
+            ])
+            .build()
+            .unwrap();
+        let b = note("0B", 1)
+            .topics(vec![Topic::new("rust").unwrap()])
+            .tags(vec![Tag::new("draft").unwrap()])
+            .build()
+            .unwrap();
+
+        let report = compute_health(&[&a, &b], now(), Duration::days(180));
+
+        assert_eq!(report.notes, 2);
+        assert_eq!(report.orphans, 1);
+        assert_eq!(report.untagged, 1);
+        assert_eq!(report.stale, 1);
+        assert_eq!(report.links, 2);
+        assert_eq!(report.broken_links, 1);
+        // Every ratio is 0.5
+        assert_eq!(report.score(), 50.0);
+    }
+
+    #[test]
+    fn score_rounds_to_one_decimal() {
+        let report = HealthReport {
+            notes: 3,
+            orphans: 1,
+            links: 0,
+            broken_links: 0,
+            untagged: 0,
+            stale: 0,
+        };
+        // 1 - (1/3)/4 = 0.91666...
+        assert_eq!(report.score(), 91.7);
+    }
+}
@@ -0,0 +1,375 @@
+//! Keyword extraction.
+//!
+//! Scores the terms of a note by TF-IDF against a corpus of notes: a term
+//! ranks high when the note uses it often and few other notes use it at all.
+
+use std::collections::{HashMap, HashSet};
+
+/// Common English words that never make useful keywords.
+const STOP_WORDS: &[&str] = &[
+    "about",
+    "above",
+    "after",
+    "again",
+    "against",
+    "all",
+    "also",
+    "am",
+    "an",
+    "and",
+    "any",
+    "are",
+    "as",
+    "at",
+    "be",
+    "because",
+    "been",
+    "before",
+    "being",
+    "below",
+    "between",
+    "both",
+    "but",
+    "by",
+    "can",
+    "cannot",
+    "could",
+    "did",
+    "do",
+    "does",
+    "doing",
+    "done",
+    "down",
+    "during",
+    "each",
+    "etc",
+    "even",
+    "few",
+    "for",
+    "from",
+    "further",
+    "get",
+    "gets",
+    "got",
+    "had",
+    "has",
+    "have",
+    "having",
+    "he",
+    "her",
+    "here",
+    "hers",
+    "herself",
+    "him",
+    "himself",
+    "his",
+    "how",
+    "however",
+    "if",
+    "in",
+    "into",
+    "is",
+    "it",
+    "its",
+    "itself",
+    "just",
+    "let",
+    "like",
+    "made",
+    "make",
+    "makes",
+    "many",
+    "may",
+    "me",
+    "might",
+    "more",
+    "most",
+    "much",
+    "must",
+    "my",
+    "myself",
+    "need",
+    "needs",
+    "no",
+    "nor",
+    "not",
+    "now",
+    "of",
+    "off",
+    "on",
+    "once",
+    "one",
+    "only",
+    "or",
+    "other",
+    "our",
+    "ours",
+    "ourselves",
+    "out",
+    "over",
+    "own",
+    "same",
+    "see",
+    "she",
+    "should",
+    "so",
+    "some",
+    "still",
+    "such",
+    "than",
+    "that",
+    "the",
+    "their",
+    "theirs",
+    "them",
+    "themselves",
+    "then",
+    "there",
+    "these",
+    "they",
+    "this",
+    "those",
+    "through",
+    "to",
+    "too",
+    "under",
+    "until",
+    "up",
+    "use",
+    "used",
+    "uses",
+    "using",
+    "very",
+    "via",
+    "was",
+    "way",
+    "we",
+    "well",
+    "were",
+    "what",
+    "when",
+    "where",
+    "which",
+    "while",
+    "who",
+    "whom",
+    "why",
+    "will",
+    "with",
+    "within",
+    "without",
+    "would",
+    "yet",
+    "you",
+    "your",
+    "yours",
+    "yourself",
+    "yourselves",
+];
+
+/// Shortest term considered a keyword.
+const MIN_TERM_LEN: usize = 3;
+
+/// A scored term from [`Corpus::keywords`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyword {
+    pub term: String,
+    /// TF-IDF score; only meaningful relative to other scores from the same corpus.
+    pub score: f64,
+    /// Times the term occurs in the note.
+    pub count: usize,
+}
+
+/// Splits markdown text into lowercase terms, dropping stop words.
+///
+/// Fenced code blocks, URLs and link targets are skipped, as are terms
+/// shorter than three characters and terms without a letter.
+pub fn extract_terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut in_fence = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        for word in line.split_whitespace() {
+            if word.contains("://") {
+                continue;
+            }
+            // `[text](target)`: keep the text, drop the target
+            let word = word.split("](").next().unwrap_or(word);
+            terms.extend(
+                word.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '\''))
+                    .filter_map(normalize_term),
+            );
+        }
+    }
+
+    terms
+}
+
+fn normalize_term(raw: &str) -> Option<String> {
+    let term = raw
+        .trim_matches(|c: char| c == '-' || c == '\'')
+        .to_lowercase();
+    let term = term.strip_suffix("'s").unwrap_or(&term);
+    let keep = term.chars().count() >= MIN_TERM_LEN
+        && term.chars().any(char::is_alphabetic)
+        && !STOP_WORDS.contains(&term);
+    keep.then(|| term.to_string())
+}
+
+/// Document frequencies of terms across a collection of notes.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    documents: usize,
+    document_frequency: HashMap<String, usize>,
+}
+
+impl Corpus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a document given its terms, as returned by [`extract_terms`].
+    pub fn add(&mut self, terms: &[String]) {
+        self.documents += 1;
+        let unique: HashSet<&String> = terms.iter().collect();
+        for term in unique {
+            *self.document_frequency.entry(term.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Number of documents added.
+    pub fn len(&self) -> usize {
+        self.documents
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents == 0
+    }
+
+    /// Inverse document frequency. Terms in every document score close to
+    /// zero but stay positive, so a single-note corpus still ranks by count.
+    fn idf(&self, term: &str) -> f64 {
+        let df = self
+            .document_frequency
+            .get(term)
+            .copied()
+            .unwrap_or(0)
+            .max(1);
+        ((self.documents + 1) as f64 / df as f64).ln()
+    }
+
+    /// Returns the `limit` highest-scoring terms of a document, best first.
+    ///
+    /// Ties are broken by count and then alphabetically, so the order is
+    /// stable.
+    pub fn keywords(&self, terms: &[String], limit: usize) -> Vec<Keyword> {
+        if terms.is_empty() {
+            return Vec::new();
+        }
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for term in terms {
+            *counts.entry(term.as_str()).or_insert(0) += 1;
+        }
+
+        let total = terms.len() as f64;
+        let mut keywords: Vec<Keyword> = counts
+            .into_iter()
+            .map(|(term, count)| Keyword {
+                term: term.to_string(),
+                score: count as f64 / total * self.idf(term),
+                count,
+            })
+            .collect();
+
+        keywords.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.count.cmp(&a.count))
+                .then_with(|| a.term.cmp(&b.term))
+        });
+        keywords.truncate(limit);
+        keywords
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(text: &str) -> Vec<String> {
+        extract_terms(text)
+    }
+
+    #[test]
+    fn extract_terms_lowercases_and_drops_stop_words() {
+        assert_eq!(
+            terms("The Borrow checker is about ownership."),
+            vec!["borrow", "checker", "ownership"]
+        );
+    }
+
+    #[test]
+    fn extract_terms_skips_short_and_numeric_terms() {
+        assert_eq!(terms("Go 2024 ok v2 rust2024"), vec!["rust2024"]);
+    }
+
+    #[test]
+    fn extract_terms_keeps_hyphenated_words_and_strips_possessives() {
+        assert_eq!(
+            terms("Rust's well-known -borrow- rules"),
+            vec!["rust", "well-known", "borrow", "rules"]
+        );
+    }
+
+    #[test]
+    fn extract_terms_skips_code_urls_and_link_targets() {
+        let text = "Read [lifetimes](01HQ3K5M7NXJK4QZPW8V2R6T9Y) at https://example.com\n\
+                    ```rust\nfn ignored() {}\n```\nthen traits";
+        assert_eq!(terms(text), vec!["read", "lifetimes", "traits"]);
+    }
+
+    #[test]
+    fn keywords_prefer_terms_rare_in_corpus() {
+        let note = terms("rust ownership rust ownership");
+        let mut corpus = Corpus::new();
+        corpus.add(&note);
+        corpus.add(&terms("rust traits"));
+        corpus.add(&terms("rust macros"));
+
+        let keywords = corpus.keywords(&note, 10);
+
+        assert_eq!(keywords[0].term, "ownership");
+        assert_eq!(keywords[0].count, 2);
+        assert_eq!(keywords[1].term, "rust");
+        assert!(keywords[0].score > keywords[1].score);
+    }
+
+    #[test]
+    fn keywords_are_limited_and_ordered_stably() {
+        let note = terms("alpha beta gamma");
+        let mut corpus = Corpus::new();
+        corpus.add(&note);
+
+        let keywords = corpus.keywords(&note, 2);
+
+        let names: Vec<&str> = keywords.iter().map(|k| k.term.as_str()).collect();
+        assert_eq!(names, vec!["alpha", "beta"]);
+    }
+
+    #[test]
+    fn keywords_of_empty_document_is_empty() {
+        let corpus = Corpus::new();
+        assert!(corpus.keywords(&[], 10).is_empty());
+        assert!(corpus.is_empty());
+    }
+}
@@ -154,7 +154,8 @@ impl<'de> Deserialize<'de> for Rel {
 ///
 /// Links represent explicit references between notes, stored in frontmatter.
 /// Each link has a target note ID, one or more relationship types, and
-/// optional context text.
+/// optional context text. It may also carry the target's title, written next
+/// to the ID so raw files stay readable; the ID is authoritative.
 ///
 /// # Examples
 ///
@@ -168,6 +169,7 @@ impl<'de> Deserialize<'de> for Rel {
 #[derive(Clone)]
 pub struct Link {
     target: NoteId,
+    title: Option<String>,
     rel: Vec<Rel>,
     context: Option<String>,
 }
@@ -232,6 +234,7 @@ impl Link {
 
         Ok(Self {
             target,
+            title: None,
             rel: rels,
             context: None,
         })
@@ -253,6 +256,20 @@ impl Link {
         &self.target
     }
 
+    /// Returns a copy of the link with the target's title set or cleared.
+    pub fn with_title(mut self, title: Option<impl Into<String>>) -> Self {
+        self.title = title.map(Into::into);
+        self
+    }
+
+    /// Returns the target's title as recorded in the link, if any.
+    ///
+    /// This is a copy kept for readability and may be stale; resolve the
+    /// target to get its current title.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
     /// Returns the relationship types.
     pub fn rel(&self) -> &[Rel] {
         &self.rel
@@ -266,7 +283,7 @@ impl Link {
 
 impl PartialEq for Link {
     fn eq(&self, other: &Self) -> bool {
-        // Context is metadata, excluded from equality
+        // Title and context are metadata, excluded from equality
         self.target == other.target && self.rel == other.rel
     }
 }
@@ -275,7 +292,7 @@ impl Eq for Link {}
 
 impl std::hash::Hash for Link {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        // Context is metadata, excluded from hash
+        // Title and context are metadata, excluded from hash
         self.target.hash(state);
         self.rel.hash(state);
     }
@@ -292,6 +309,7 @@ impl fmt::Debug for Link {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Link")
             .field("target", &self.target)
+            .field("title", &self.title)
             .field("rel", &self.rel)
             .field("context", &self.context)
             .finish()
@@ -307,6 +325,9 @@ impl Serialize for Link {
 
         let mut map = serializer.serialize_map(None)?;
         map.serialize_entry("id", &self.target)?;
+        if let Some(ref title) = self.title {
+            map.serialize_entry("title", title)?;
+        }
         map.serialize_entry("rel", &self.rel)?;
         if let Some(ref ctx) = self.context {
             map.serialize_entry("note", ctx)?;
@@ -323,6 +344,7 @@ impl<'de> Deserialize<'de> for Link {
         #[derive(Deserialize)]
         struct LinkHelper {
             id: NoteId,
+            title: Option<String>,
             rel: Vec<Rel>,
             note: Option<String>,
         }
@@ -337,6 +359,7 @@ impl<'de> Deserialize<'de> for Link {
 
         Ok(Link {
             target: helper.id,
+            title: helper.title,
             rel: helper.rel,
             context: helper.note,
         })
@@ -717,6 +740,27 @@ note: "Hired me at Acme Corp, 2019"
         assert_eq!(link.context(), Some("Hired me at Acme Corp, 2019"));
     }
 
+    #[test]
+    fn link_serde_writes_title_after_id() {
+        let link = Link::new(test_note_id(), vec!["parent"])
+            .unwrap()
+            .with_title(Some("API Design"));
+        let yaml = serde_yaml::to_string(&link).unwrap();
+        assert_eq!(
+            yaml,
+            "id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: API Design\nrel:\n- parent\n"
+        );
+
+        let parsed: Link = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.title(), Some("API Design"));
+    }
+
+    #[test]
+    fn link_title_is_excluded_from_equality() {
+        let link = Link::new(test_note_id(), vec!["parent"]).unwrap();
+        assert_eq!(link.clone().with_title(Some("Old title")), link);
+    }
+
     #[test]
     fn link_serde_in_vec_context() {
         #[derive(Serialize, Deserialize, PartialEq, Debug)]
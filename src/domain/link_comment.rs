@@ -0,0 +1,327 @@
+//! Link comments.
+//!
+//! A note body may contain comments like
+//! `<!-- den:link "Target Title" rel=see-also -->` asking for a link to be
+//! added to the note's frontmatter. The comment names the target the same way
+//! the `link` command does, takes one or more rels (`rel=a,b` or repeated
+//! `rel=`), and an optional `context="..."`.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::domain::{ParseRelError, Rel};
+
+const OPEN: &str = "<!--";
+const CLOSE: &str = "-->";
+const KEYWORD: &str = "den:link";
+
+/// A parsed link comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkComment {
+    /// The target reference: an ID prefix, title, alias or short ref.
+    pub target: String,
+    pub rels: Vec<Rel>,
+    pub context: Option<String>,
+}
+
+/// A link comment found in a note body, parsed or not.
+#[derive(Debug, Clone)]
+pub struct LinkCommentMatch {
+    /// Byte range of the comment in the body.
+    pub span: Range<usize>,
+    /// The comment's text, e.g. `<!-- den:link "Rust" rel=see-also -->`.
+    pub text: String,
+    pub comment: Result<LinkComment, ParseLinkCommentError>,
+}
+
+/// Error returned for a malformed link comment.
+#[derive(Debug, Clone)]
+pub enum ParseLinkCommentError {
+    /// No target was given.
+    MissingTarget,
+    /// A quoted value has no closing quote.
+    UnterminatedQuote,
+    /// No `rel=` was given.
+    MissingRel,
+    /// A `rel=` value is not a valid rel.
+    InvalidRel(String, ParseRelError),
+    /// Something other than `rel=` or `context=` follows the target.
+    UnexpectedToken(String),
+}
+
+impl fmt::Display for ParseLinkCommentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseLinkCommentError::MissingTarget => write!(f, "missing link target"),
+            ParseLinkCommentError::UnterminatedQuote => write!(f, "unterminated quote"),
+            ParseLinkCommentError::MissingRel => write!(f, "missing rel="),
+            ParseLinkCommentError::InvalidRel(rel, e) => write!(f, "invalid rel '{}': {}", rel, e),
+            ParseLinkCommentError::UnexpectedToken(token) => {
+                write!(f, "unexpected '{}' (expected rel= or context=)", token)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseLinkCommentError {}
+
+/// Finds the link comments in a note body, skipping fenced code blocks.
+pub fn find_link_comments(body: &str) -> Vec<LinkCommentMatch> {
+    let mut matches = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut pos = 0;
+        while let Some(found) = line[pos..].find(OPEN) {
+            let open = pos + found;
+            let Some(len) = line[open..].find(CLOSE) else {
+                break;
+            };
+            let close = open + len + CLOSE.len();
+            pos = close;
+
+            let inner = line[open + OPEN.len()..open + len].trim();
+            let Some(args) = inner.strip_prefix(KEYWORD) else {
+                continue;
+            };
+            if !args.is_empty() && !args.starts_with(char::is_whitespace) {
+                continue;
+            }
+            matches.push(LinkCommentMatch {
+                span: start + open..start + close,
+                text: line[open..close].to_string(),
+                comment: parse_args(args),
+            });
+        }
+    }
+
+    matches
+}
+
+/// Removes the given comments from a body. A comment alone on its line takes
+/// the line with it.
+pub fn remove_link_comments(body: &str, spans: &[Range<usize>]) -> String {
+    let mut spans = spans.to_vec();
+    spans.sort_by_key(|s| s.start);
+
+    let mut result = String::with_capacity(body.len());
+    let mut pos = 0;
+    for span in spans {
+        let line_start = body[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = body[span.end..]
+            .find('\n')
+            .map_or(body.len(), |i| span.end + i + 1);
+        let alone = body[line_start..span.start].trim().is_empty()
+            && body[span.end..line_end].trim().is_empty();
+
+        let (start, end) = if alone {
+            (line_start, line_end)
+        } else {
+            (span.start, span.end)
+        };
+        if start < pos {
+            continue;
+        }
+        result.push_str(&body[pos..start]);
+        pos = end;
+    }
+    result.push_str(&body[pos..]);
+    result
+}
+
+fn parse_args(args: &str) -> Result<LinkComment, ParseLinkCommentError> {
+    let tokens = tokenize(args)?;
+    let mut tokens = tokens.into_iter();
+
+    let target = match tokens.next() {
+        Some(target) if !target.trim().is_empty() && !target.contains('=') => {
+            target.trim().to_string()
+        }
+        _ => return Err(ParseLinkCommentError::MissingTarget),
+    };
+
+    let mut rels = Vec::new();
+    let mut context = None;
+    for token in tokens {
+        if let Some(value) = token.strip_prefix("rel=") {
+            for rel in value.split(',').filter(|r| !r.is_empty()) {
+                let parsed = Rel::new(rel)
+                    .map_err(|e| ParseLinkCommentError::InvalidRel(rel.to_string(), e))?;
+                if !rels.contains(&parsed) {
+                    rels.push(parsed);
+                }
+            }
+        } else if let Some(value) = token.strip_prefix("context=") {
+            context = Some(value.to_string()).filter(|c| !c.trim().is_empty());
+        } else {
+            return Err(ParseLinkCommentError::UnexpectedToken(token));
+        }
+    }
+
+    if rels.is_empty() {
+        return Err(ParseLinkCommentError::MissingRel);
+    }
+    Ok(LinkComment {
+        target,
+        rels,
+        context,
+    })
+}
+
+/// Splits on whitespace, keeping double-quoted runs together and dropping
+/// the quotes, so `context="a b"` is one token.
+fn tokenize(args: &str) -> Result<Vec<String>, ParseLinkCommentError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut in_quotes = false;
+
+    for c in args.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err(ParseLinkCommentError::UnterminatedQuote);
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rels(names: &[&str]) -> Vec<Rel> {
+        names.iter().map(|r| Rel::new(r).unwrap()).collect()
+    }
+
+    fn parsed(body: &str) -> Vec<Result<LinkComment, ParseLinkCommentError>> {
+        find_link_comments(body)
+            .into_iter()
+            .map(|m| m.comment)
+            .collect()
+    }
+
+    #[test]
+    fn finds_comment_with_quoted_target() {
+        let body = "Intro.\n<!-- den:link \"Target Title\" rel=see-also -->\nMore.\n";
+
+        let matches = find_link_comments(body);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            &body[matches[0].span.clone()],
+            "<!-- den:link \"Target Title\" rel=see-also -->"
+        );
+        assert_eq!(
+            matches[0].comment.as_ref().unwrap(),
+            &LinkComment {
+                target: "Target Title".to_string(),
+                rels: rels(&["see-also"]),
+                context: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_multiple_rels_and_context() {
+        let body =
+            "<!-- den:link 01HQ3K5M rel=parent,see-also rel=parent context=\"for the intro\" -->";
+
+        let comments = parsed(body);
+
+        assert_eq!(comments.len(), 1);
+        assert_eq!(
+            comments[0].as_ref().unwrap(),
+            &LinkComment {
+                target: "01HQ3K5M".to_string(),
+                rels: rels(&["parent", "see-also"]),
+                context: Some("for the intro".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_other_comments_and_code_blocks() {
+        let body =
+            "<!-- todo -->\n<!-- den:linked x -->\n```\n<!-- den:link \"A\" rel=x -->\n```\n";
+
+        assert!(find_link_comments(body).is_empty());
+    }
+
+    #[test]
+    fn reports_malformed_comments() {
+        let body = "<!-- den:link -->\n\
+                    <!-- den:link \"Open rel=x -->\n\
+                    <!-- den:link Target -->\n\
+                    <!-- den:link Target rel=Bad! -->\n\
+                    <!-- den:link Target as=parent -->\n";
+
+        let errors: Vec<String> = parsed(body)
+            .into_iter()
+            .map(|r| r.unwrap_err().to_string())
+            .collect();
+
+        assert_eq!(errors[0], "missing link target");
+        assert_eq!(errors[1], "unterminated quote");
+        assert_eq!(errors[2], "missing rel=");
+        assert!(errors[3].starts_with("invalid rel 'Bad!'"), "{}", errors[3]);
+        assert!(
+            errors[4].starts_with("unexpected 'as=parent'"),
+            "{}",
+            errors[4]
+        );
+    }
+
+    #[test]
+    fn remove_drops_whole_line_when_comment_is_alone() {
+        let body = "Intro.\n  <!-- den:link A rel=x -->\nMore.\n";
+        let spans: Vec<_> = find_link_comments(body)
+            .into_iter()
+            .map(|m| m.span)
+            .collect();
+
+        assert_eq!(remove_link_comments(body, &spans), "Intro.\nMore.\n");
+    }
+
+    #[test]
+    fn remove_keeps_surrounding_text_on_the_line() {
+        let body = "See <!-- den:link A rel=x -->this.\nEnd <!-- den:link B rel=x -->";
+        let spans: Vec<_> = find_link_comments(body)
+            .into_iter()
+            .map(|m| m.span)
+            .collect();
+
+        assert_eq!(remove_link_comments(body, &spans), "See this.\nEnd ");
+    }
+}
@@ -0,0 +1,269 @@
+//! Markdown syntax checks for note bodies.
+//!
+//! Bodies are run through the markdown parser to find mistakes that render
+//! badly without failing: code fences that are never closed, tables whose
+//! header or rows don't line up, and reference-style links to labels that
+//! aren't defined.
+
+use std::ops::Range;
+use std::path::Path;
+
+use pulldown_cmark::{BrokenLink, CodeBlockKind, Event, LinkType, Options, Parser, Tag};
+
+use super::validation::{ValidationIssue, ValidationKind};
+
+/// Finds markdown syntax problems in a note body.
+///
+/// `first_line` is the line of the file the body starts on, so issues point
+/// at file lines rather than body lines.
+pub fn find_markdown_issues(path: &Path, body: &str, first_line: usize) -> Vec<ValidationIssue> {
+    let lines = LineIndex::new(body);
+    let mut found: Vec<(usize, ValidationKind)> = Vec::new();
+
+    let mut undefined: Vec<(Range<usize>, String)> = Vec::new();
+    let mut on_broken_link = |link: BrokenLink| {
+        // Bare `[text]` is common in prose, so only `[text][label]` and
+        // `[label][]` count
+        if matches!(link.link_type, LinkType::Reference | LinkType::Collapsed) {
+            undefined.push((link.span, link.reference.to_string()));
+        }
+        None
+    };
+    let parser = Parser::new_with_broken_link_callback(
+        body,
+        Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH,
+        Some(&mut on_broken_link),
+    );
+
+    // Tables and code blocks, where a leading `|` is expected
+    let mut structured: Vec<Range<usize>> = Vec::new();
+    let mut header_cells = 0;
+    for (event, range) in parser.into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                if let CodeBlockKind::Fenced(_) = kind
+                    && let Some(fence) = unclosed_fence(&body[range.clone()])
+                {
+                    found.push((
+                        lines.line_of(range.start),
+                        ValidationKind::UnclosedFence { fence },
+                    ));
+                }
+                structured.push(range);
+            }
+            Event::Start(Tag::Table(_)) => structured.push(range),
+            // The parser drops extra cells and pads short rows, so cells
+            // are counted in the source
+            Event::Start(Tag::TableHead) => {
+                header_cells = count_cells(lines.text_at(body, range.start));
+            }
+            Event::Start(Tag::TableRow) => {
+                let cells = count_cells(lines.text_at(body, range.start));
+                if cells != header_cells {
+                    found.push((
+                        lines.line_of(range.start),
+                        ValidationKind::TableCellCount {
+                            expected: header_cells,
+                            found: cells,
+                        },
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (span, label) in undefined {
+        found.push((
+            lines.line_of(span.start),
+            ValidationKind::UndefinedReference { label },
+        ));
+    }
+    for line in stray_table_rows(body, &lines, &structured) {
+        found.push((line, ValidationKind::MissingTableSeparator));
+    }
+
+    found.sort_by_key(|(line, _)| *line);
+    found
+        .into_iter()
+        .map(|(line, kind)| ValidationIssue::new(path, kind).at_line(first_line + line))
+        .collect()
+}
+
+/// Returns the opening fence of a fenced code block's source if the block
+/// runs to the end of its container without a closing fence.
+fn unclosed_fence(block: &str) -> Option<String> {
+    let mut block_lines = block.lines();
+    let opening = strip_container(block_lines.next()?);
+    let marker = opening.chars().next()?;
+    let fence: String = opening.chars().take_while(|&c| c == marker).collect();
+
+    let closed = block_lines.next_back().is_some_and(|last| {
+        let last = strip_container(last).trim_end();
+        last.len() >= fence.len() && last.chars().all(|c| c == marker)
+    });
+    (!closed).then_some(fence)
+}
+
+/// Counts the cells of a table row: the `|`s between cells, ignoring
+/// escaped pipes and pipes in code spans.
+fn count_cells(row: &str) -> usize {
+    let row = strip_container(row).trim_end();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let mut dividers = 0;
+    let mut in_code = false;
+    let mut escaped = false;
+    let mut trailing = false;
+    for c in row.chars() {
+        trailing = false;
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '`' => in_code = !in_code,
+            '|' if !in_code => {
+                dividers += 1;
+                trailing = true;
+            }
+            _ => {}
+        }
+    }
+    // A closing pipe ends the last cell rather than starting another
+    if trailing { dividers } else { dividers + 1 }
+}
+
+/// Strips indentation and blockquote markers from the start of a line.
+fn strip_container(line: &str) -> &str {
+    line.trim_start_matches(|c: char| c.is_whitespace() || c == '>')
+}
+
+/// Finds runs of two or more lines starting with `|` that the parser didn't
+/// take for a table, which means the separator row under the header is
+/// missing or has the wrong number of columns. Returns the first line of
+/// each run.
+fn stray_table_rows(body: &str, lines: &LineIndex, structured: &[Range<usize>]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut run: Option<(usize, usize)> = None;
+    for (line, (offset, text)) in lines.iter(body).enumerate() {
+        let stray = strip_container(text).starts_with('|')
+            && !structured.iter().any(|r| r.contains(&offset));
+        run = match (stray, run) {
+            (true, Some((first, len))) => Some((first, len + 1)),
+            (true, None) => Some((line, 1)),
+            (false, Some((first, len))) => {
+                if len > 1 {
+                    starts.push(first);
+                }
+                None
+            }
+            (false, None) => None,
+        };
+    }
+    if let Some((first, len)) = run
+        && len > 1
+    {
+        starts.push(first);
+    }
+    starts
+}
+
+/// Byte offsets of the lines of a text, to map offsets to line numbers.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|&i| i < text.len())
+            .collect();
+        Self { starts }
+    }
+
+    /// The 0-based line containing a byte offset.
+    fn line_of(&self, offset: usize) -> usize {
+        self.starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// The text of the line containing a byte offset.
+    fn text_at<'a>(&self, text: &'a str, offset: usize) -> &'a str {
+        let line = self.line_of(offset);
+        let end = self.starts.get(line + 1).copied().unwrap_or(text.len());
+        text[self.starts[line]..end].trim_end_matches(['\n', '\r'])
+    }
+
+    /// Each line's byte offset and text, without its line ending.
+    fn iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+        self.starts.iter().enumerate().map(move |(i, &start)| {
+            let end = self.starts.get(i + 1).copied().unwrap_or(text.len());
+            (start, text[start..end].trim_end_matches(['\n', '\r']))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(body: &str) -> Vec<(usize, String)> {
+        find_markdown_issues(Path::new("note.md"), body, 1)
+            .into_iter()
+            .map(|issue| (issue.line.unwrap(), issue.kind.code().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn well_formed_body_has_no_issues() {
+        let body = "# Title\n\n\
+                    ```rust\nfn main() {}\n```\n\n\
+                    | A | B |\n| - | - |\n| 1 | 2 |\n\n\
+                    See [the docs][docs] and [docs][], or [just brackets].\n\n\
+                    [docs]: https://example.com\n";
+        assert_eq!(issues(body), []);
+    }
+
+    #[test]
+    fn finds_unclosed_fence() {
+        let body = "Intro\n\n````\ncode\n```\nmore\n";
+        assert_eq!(issues(body), [(3, "unclosed-fence".to_string())]);
+
+        let issues = find_markdown_issues(Path::new("note.md"), "~~~\n", 5);
+        assert_eq!(
+            issues[0].kind,
+            ValidationKind::UnclosedFence {
+                fence: "~~~".to_string()
+            }
+        );
+        assert_eq!(issues[0].line, Some(5));
+    }
+
+    #[test]
+    fn finds_malformed_tables() {
+        // Separator with the wrong number of columns
+        let body = "| A | B |\n| - |\n| 1 | 2 |\n";
+        assert_eq!(issues(body), [(1, "malformed-table".to_string())]);
+
+        // A row with more cells than the header
+        let body = "Text\n\n| A | B |\n| - | - |\n| 1 | 2 | 3 |\n";
+        assert_eq!(issues(body), [(5, "malformed-table".to_string())]);
+
+        // Pipes in code are fine
+        assert_eq!(issues("```\n| a |\n| b |\n```\n"), []);
+    }
+
+    #[test]
+    fn finds_undefined_reference_links() {
+        let body = "Line one\nSee [the spec][spec] and [other][].\n\n[spec]: https://example.com\n";
+        let found = find_markdown_issues(Path::new("note.md"), body, 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].kind,
+            ValidationKind::UndefinedReference {
+                label: "other".to_string()
+            }
+        );
+        assert_eq!(found[0].line, Some(11));
+    }
+}
@@ -0,0 +1,87 @@
+//! Mentions.
+//!
+//! A note body may mention a person as `@jane-doe`, the slug of the person
+//! note's title (or one of its aliases). Mentions are indexed so
+//! `backlinks --mentions` finds notes that reference a person without
+//! linking to them.
+
+/// Finds the handles mentioned in a note body, lowercased, sorted and
+/// without duplicates.
+///
+/// A mention is `@` followed by letters, digits, `-` or `_`. An `@` inside a
+/// word, path or email address (`jane@example.com`) is not a mention, and
+/// fenced code blocks and inline code are skipped.
+pub fn find_mentions(body: &str) -> Vec<String> {
+    let mut handles = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut prev: Option<char> = None;
+        for (i, c) in line.char_indices() {
+            if c == '`' {
+                in_code = !in_code;
+            } else if c == '@' && !in_code && prev.is_none_or(starts_mention) {
+                let rest = &line[i + 1..];
+                let len = rest
+                    .find(|c: char| !is_handle_char(c))
+                    .unwrap_or(rest.len());
+                let handle = rest[..len].trim_end_matches(['-', '_']);
+                if handle.starts_with(|c: char| c.is_alphanumeric()) {
+                    handles.push(handle.to_lowercase());
+                }
+            }
+            prev = Some(c);
+        }
+    }
+
+    handles.sort();
+    handles.dedup();
+    handles
+}
+
+/// Returns true if a mention may follow this character.
+fn starts_mention(c: char) -> bool {
+    !c.is_alphanumeric() && !matches!(c, '_' | '-' | '.' | '/' | '@')
+}
+
+fn is_handle_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_mentions() {
+        let body = "Met @jane-doe and @Bob_Smith.\n(@jane-doe again, @carol)\n";
+        assert_eq!(find_mentions(body), vec!["bob_smith", "carol", "jane-doe"]);
+    }
+
+    #[test]
+    fn trailing_hyphens_are_not_part_of_the_handle() {
+        assert_eq!(find_mentions("ask @jane-- later"), vec!["jane"]);
+    }
+
+    #[test]
+    fn skips_emails_paths_and_bare_at() {
+        let body = "mail jane@example.com, see https://x.com/@jane, foo.@bar, @ @-x";
+        assert!(find_mentions(body).is_empty());
+    }
+
+    #[test]
+    fn skips_code() {
+        let body = "`@inline` and @real\n```\n@fenced\n```\n~~~\n@tilde\n~~~\n";
+        assert_eq!(find_mentions(body), vec!["real"]);
+    }
+}
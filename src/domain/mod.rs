@@ -1,17 +1,52 @@
 //! Core types: Note, Topic, Tag, NoteId (ULID), Link, Rel
 
+mod health;
+mod keywords;
 mod link;
+mod link_comment;
+mod markdown_lint;
+mod mention;
 mod note;
 mod note_id;
+mod reference;
+mod section;
+mod size;
+mod srs;
 mod tag;
 mod topic;
+mod url;
 mod validate;
 mod validation;
 
+pub use health::{HealthReport, compute_health};
+pub use keywords::{Corpus, Keyword, extract_terms};
 pub use link::{Link, ParseLinkError, ParseRelError, Rel};
-pub use note::{Note, NoteBuilder, ParseNoteError};
+pub use link_comment::{
+    LinkComment, LinkCommentMatch, ParseLinkCommentError, find_link_comments, remove_link_comments,
+};
+pub use markdown_lint::find_markdown_issues;
+pub use mention::find_mentions;
+pub use note::{
+    EXPORT_FIELD, EXPORT_SLUG_FIELD, EXPORT_TEMPLATE_FIELD, LANG_FIELD, NOTE_FIELDS, Note,
+    NoteBuilder, ParseNoteError, TYPE_FIELD, language_code,
+};
 pub use note_id::{NoteId, ParseNoteIdError};
-pub use tag::{ParseTagError, Tag};
+pub use reference::{
+    BodyReference, NoteNames, ReferenceKind, WIKILINK_REL, WikilinkTarget, find_local_files,
+    find_note_references, find_wikilinks,
+};
+pub use section::{SECTION_PATH_SEPARATOR, Section, find_section, find_sections, parse_heading};
+pub use size::{SizeLimits, SizeMeasure, count_words, find_size_issues};
+pub use srs::{Card, DEFAULT_EASE, Grade, ParseSrsError, SRS_FIELD, Schedule, Srs, find_cards};
+pub use tag::{ARCHIVED_TAG, ParseTagError, Tag};
 pub use topic::{ParseTopicError, Topic};
-pub use validate::{find_broken_links, find_duplicate_ids, find_orphaned_notes, validate_notes};
-pub use validation::{Severity, ValidationIssue, ValidationKind, ValidationSummary};
+pub use url::{find_urls, url_domain};
+pub use validate::{
+    CLOCK_SKEW_TOLERANCE, EXTERNAL_EDIT_THRESHOLD, expired_tags, find_broken_links,
+    find_broken_wikilinks, find_duplicate_ids, find_expired_tags, find_missing_reciprocals,
+    find_missing_topics, find_orphaned_notes, find_timestamp_anomalies, find_type_issues,
+    reconcile_modified, unresolved_wikilinks, validate_notes, validate_notes_with_trash,
+};
+pub use validation::{
+    IndexField, Severity, TimestampField, ValidationIssue, ValidationKind, ValidationSummary,
+};
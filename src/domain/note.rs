@@ -3,8 +3,58 @@
 use crate::domain::{Link, NoteId, Tag, Topic};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
+/// Frontmatter keys den interprets; any other key is kept as an extra field.
+pub const NOTE_FIELDS: [&str; 9] = [
+    "id",
+    "title",
+    "created",
+    "modified",
+    "description",
+    "topics",
+    "aliases",
+    "tags",
+    "links",
+];
+
+/// Frontmatter key naming a note's type, e.g. `type: person`. It is kept
+/// with the extra fields so notes written before types existed round-trip.
+pub const TYPE_FIELD: &str = "type";
+
+/// Frontmatter key naming the language a note is written in, as a BCP 47
+/// tag such as `de` or `ja-JP`. Like `type`, it is kept with the extra fields.
+pub const LANG_FIELD: &str = "lang";
+
+/// Frontmatter key that keeps a note out of exports when set to `false`.
+pub const EXPORT_FIELD: &str = "export";
+
+/// Frontmatter key giving the slug of a note's exported page, e.g.
+/// `export_slug: custom-url` for `custom-url.html`.
+pub const EXPORT_SLUG_FIELD: &str = "export_slug";
+
+/// Frontmatter key naming the template a note's exported page is rendered
+/// with, e.g. `export_template: .templates/special`.
+pub const EXPORT_TEMPLATE_FIELD: &str = "export_template";
+
+/// Normalizes a language tag to its lowercase primary subtag, e.g. `de` for
+/// `de-AT` or `DE`. Returns `None` for an empty tag.
+///
+/// # Examples
+///
+/// ```
+/// use den::domain::language_code;
+///
+/// assert_eq!(language_code("ja-JP").as_deref(), Some("ja"));
+/// assert_eq!(language_code(" de_AT ").as_deref(), Some("de"));
+/// assert_eq!(language_code(""), None);
+/// ```
+pub fn language_code(tag: &str) -> Option<String> {
+    let primary = tag.trim().split(['-', '_']).next().unwrap_or_default();
+    (!primary.is_empty()).then(|| primary.to_ascii_lowercase())
+}
+
 /// The kind of error that occurred when constructing a note.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ParseNoteErrorKind {
@@ -45,6 +95,9 @@ impl std::error::Error for ParseNoteError {}
 /// - `tags`: Flat labels for filtering
 /// - `links`: References to other notes with relationship context
 ///
+/// Any other frontmatter keys (e.g. `status` or `author`) are kept verbatim
+/// as extra fields so that rewriting a note does not drop them.
+///
 /// # Examples
 ///
 /// ```
@@ -67,6 +120,7 @@ pub struct Note {
     aliases: Vec<String>,
     tags: Vec<Tag>,
     links: Vec<Link>,
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 impl Note {
@@ -108,6 +162,7 @@ impl Note {
             aliases: Vec::new(),
             tags: Vec::new(),
             links: Vec::new(),
+            extra: BTreeMap::new(),
         })
     }
 
@@ -165,6 +220,74 @@ impl Note {
     pub fn links(&self) -> &[Link] {
         &self.links
     }
+
+    /// Returns frontmatter fields den does not interpret, by key.
+    pub fn extra(&self) -> &BTreeMap<String, serde_yaml::Value> {
+        &self.extra
+    }
+
+    /// Returns true if the note has a non-empty value for a frontmatter key,
+    /// e.g. `description`, `tags` or an extra field like `status`.
+    pub fn has_field(&self, key: &str) -> bool {
+        match key {
+            "id" | "title" | "created" | "modified" => true,
+            "description" => self.description.is_some(),
+            "topics" => !self.topics.is_empty(),
+            "aliases" => !self.aliases.is_empty(),
+            "tags" => !self.tags.is_empty(),
+            "links" => !self.links.is_empty(),
+            _ => match self.extra.get(key) {
+                None | Some(serde_yaml::Value::Null) => false,
+                Some(serde_yaml::Value::String(s)) => !s.trim().is_empty(),
+                Some(serde_yaml::Value::Sequence(items)) => !items.is_empty(),
+                Some(serde_yaml::Value::Mapping(map)) => !map.is_empty(),
+                Some(_) => true,
+            },
+        }
+    }
+
+    /// Returns the note's type (the `type` field), if it is a string.
+    pub fn note_type(&self) -> Option<&str> {
+        self.extra
+            .get(TYPE_FIELD)
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.trim().is_empty())
+    }
+
+    /// Returns the note's language (the `lang` field) as a lowercase primary
+    /// subtag, e.g. `de` for `lang: de-AT`.
+    pub fn lang(&self) -> Option<String> {
+        self.extra
+            .get(LANG_FIELD)
+            .and_then(|v| v.as_str())
+            .and_then(language_code)
+    }
+
+    /// Returns false if the note has `export: false`.
+    pub fn is_exported(&self) -> bool {
+        self.extra.get(EXPORT_FIELD).and_then(|v| v.as_bool()) != Some(false)
+    }
+
+    /// Returns the slug of the note's exported page (the `export_slug`
+    /// field), if set.
+    pub fn export_slug(&self) -> Option<&str> {
+        self.extra_str(EXPORT_SLUG_FIELD)
+    }
+
+    /// Returns the template of the note's exported page (the
+    /// `export_template` field), if set.
+    pub fn export_template(&self) -> Option<&str> {
+        self.extra_str(EXPORT_TEMPLATE_FIELD)
+    }
+
+    /// Returns an extra field's value if it is a non-blank string.
+    fn extra_str(&self, key: &str) -> Option<&str> {
+        self.extra
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
 }
 
 impl fmt::Display for Note {
@@ -185,6 +308,7 @@ impl fmt::Debug for Note {
             .field("aliases", &self.aliases)
             .field("tags", &self.tags)
             .field("links", &self.links)
+            .field("extra", &self.extra)
             .finish()
     }
 }
@@ -200,6 +324,7 @@ pub struct NoteBuilder {
     aliases: Vec<String>,
     tags: Vec<Tag>,
     links: Vec<Link>,
+    extra: BTreeMap<String, serde_yaml::Value>,
 }
 
 impl NoteBuilder {
@@ -219,6 +344,7 @@ impl NoteBuilder {
             aliases: Vec::new(),
             tags: Vec::new(),
             links: Vec::new(),
+            extra: BTreeMap::new(),
         }
     }
 
@@ -264,6 +390,17 @@ impl NoteBuilder {
         self
     }
 
+    /// Sets the note's extra frontmatter fields.
+    ///
+    /// Keys that name a field den interprets (see [`NOTE_FIELDS`]) are dropped.
+    pub fn extra(mut self, extra: BTreeMap<String, serde_yaml::Value>) -> Self {
+        self.extra = extra
+            .into_iter()
+            .filter(|(key, _)| !NOTE_FIELDS.contains(&key.as_str()))
+            .collect();
+        self
+    }
+
     /// Builds the Note.
     ///
     /// # Errors
@@ -289,6 +426,7 @@ impl NoteBuilder {
             aliases: self.aliases,
             tags: self.tags,
             links: self.links,
+            extra: self.extra,
         })
     }
 }
@@ -362,6 +500,9 @@ impl Serialize for Note {
         if !self.links.is_empty() {
             map.serialize_entry("links", &self.links)?;
         }
+        for (key, value) in &self.extra {
+            map.serialize_entry(key, value)?;
+        }
 
         map.end()
     }
@@ -388,6 +529,8 @@ impl<'de> Deserialize<'de> for Note {
             tags: Vec<Tag>,
             #[serde(default)]
             links: Vec<Link>,
+            #[serde(flatten)]
+            extra: BTreeMap<String, serde_yaml::Value>,
         }
 
         let helper = NoteHelper::deserialize(deserializer)?;
@@ -398,6 +541,7 @@ impl<'de> Deserialize<'de> for Note {
             .aliases(helper.aliases)
             .tags(helper.tags)
             .links(helper.links)
+            .extra(helper.extra)
             .build()
             .map_err(serde::de::Error::custom)
     }
@@ -1235,4 +1379,119 @@ links:
         assert!(err.to_string().contains("title"));
         assert!(err.to_string().contains("cannot be empty"));
     }
+
+    // ===========================================
+    // Phase 10: Extra Fields
+    // ===========================================
+
+    #[test]
+    fn unknown_fields_are_kept_as_extra() {
+        let yaml = r#"
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y
+title: Test
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+status: draft
+priority: 2
+"#;
+        let note: Note = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(note.extra().len(), 2);
+        assert_eq!(note.extra()["status"], serde_yaml::Value::from("draft"));
+        assert_eq!(note.extra()["priority"], serde_yaml::Value::from(2));
+
+        let serialized = serde_yaml::to_string(&note).unwrap();
+        assert!(serialized.ends_with("priority: 2\nstatus: draft\n"));
+    }
+
+    #[test]
+    fn note_type_reads_the_type_field() {
+        let yaml = r#"
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y
+title: Ada Lovelace
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+type: person
+"#;
+        let note: Note = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(note.note_type(), Some("person"));
+
+        let untyped =
+            Note::new(test_note_id(), "Untyped", note.created(), note.modified()).unwrap();
+        assert_eq!(untyped.note_type(), None);
+    }
+
+    #[test]
+    fn lang_reads_the_primary_subtag() {
+        let yaml = r#"
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y
+title: Grüße
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+lang: de-AT
+"#;
+        let note: Note = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(note.lang().as_deref(), Some("de"));
+
+        let unset = Note::new(test_note_id(), "Plain", note.created(), note.modified()).unwrap();
+        assert_eq!(unset.lang(), None);
+    }
+
+    #[test]
+    fn export_fields_are_read_from_extra_fields() {
+        let yaml = r#"
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y
+title: Draft
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+export: false
+export_slug: " custom-url "
+export_template: ""
+"#;
+        let note: Note = serde_yaml::from_str(yaml).unwrap();
+        assert!(!note.is_exported());
+        assert_eq!(note.export_slug(), Some("custom-url"));
+        assert_eq!(note.export_template(), None);
+
+        let plain = Note::new(test_note_id(), "Plain", note.created(), note.modified()).unwrap();
+        assert!(plain.is_exported());
+        assert_eq!(plain.export_slug(), None);
+    }
+
+    #[test]
+    fn has_field_requires_a_non_empty_value() {
+        let yaml = r#"
+id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y
+title: Ada Lovelace
+created: 2024-01-15T10:30:00Z
+modified: 2024-01-15T10:30:00Z
+tags: [math]
+born: 1815
+email: ""
+"#;
+        let note: Note = serde_yaml::from_str(yaml).unwrap();
+
+        assert!(note.has_field("title"));
+        assert!(note.has_field("tags"));
+        assert!(note.has_field("born"));
+        assert!(!note.has_field("email"));
+        assert!(!note.has_field("description"));
+        assert!(!note.has_field("topics"));
+        assert!(!note.has_field("employer"));
+    }
+
+    #[test]
+    fn builder_drops_extra_fields_named_like_note_fields() {
+        let extra = BTreeMap::from([
+            ("title".to_string(), serde_yaml::Value::from("Shadow")),
+            ("author".to_string(), serde_yaml::Value::from("Ada")),
+        ]);
+        let note = Note::builder(test_note_id(), "Test", test_datetime(), test_datetime())
+            .extra(extra)
+            .build()
+            .unwrap();
+
+        assert_eq!(note.title(), "Test");
+        assert_eq!(note.extra().keys().collect::<Vec<_>>(), vec!["author"]);
+    }
 }
@@ -5,8 +5,13 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::hash::Hash;
 use std::str::FromStr;
+use std::sync::{Mutex, PoisonError};
 use std::time::SystemTime;
-use ulid::Ulid;
+use ulid::{Generator, Ulid};
+
+/// Shared by all IDs created in this process so that they are strictly
+/// increasing, even within one millisecond or after the clock jumps back.
+static GENERATOR: Mutex<Generator> = Mutex::new(Generator::new());
 
 /// A unique identifier for notes based on ULID.
 ///
@@ -29,8 +34,13 @@ pub struct NoteId(Ulid);
 
 impl NoteId {
     /// Creates a new NoteId with the current timestamp.
+    ///
+    /// IDs are monotonic within a process: an ID created in the same
+    /// millisecond as the previous one, or while the system clock is behind
+    /// it, is the previous ID incremented by one.
     pub fn new() -> Self {
-        Self(Ulid::new())
+        let mut generator = GENERATOR.lock().unwrap_or_else(PoisonError::into_inner);
+        Self(next_ulid(&mut generator, SystemTime::now()))
     }
 
     /// Creates a NoteId from a specific datetime (useful for testing).
@@ -63,6 +73,14 @@ impl NoteId {
     }
 }
 
+fn next_ulid(generator: &mut Generator, now: SystemTime) -> Ulid {
+    // Overflow needs 2^80 IDs in one millisecond; a fresh random ULID is
+    // still unique in practice
+    generator
+        .generate_from_datetime(now)
+        .unwrap_or_else(|_| Ulid::from_datetime(now))
+}
+
 impl Default for NoteId {
     fn default() -> Self {
         Self::new()
@@ -152,6 +170,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn new_ids_are_strictly_increasing() {
+        let ids: Vec<NoteId> = (0..1000).map(|_| NoteId::new()).collect();
+
+        for pair in ids.windows(2) {
+            assert!(pair[0].to_string() < pair[1].to_string());
+        }
+    }
+
+    #[test]
+    fn next_ulid_increments_within_the_same_millisecond() {
+        let mut generator = Generator::new();
+        let now = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_000);
+
+        let first = next_ulid(&mut generator, now);
+        let second = next_ulid(&mut generator, now);
+
+        assert_eq!(second, first.increment().unwrap());
+    }
+
+    #[test]
+    fn next_ulid_stays_monotonic_when_clock_goes_back() {
+        let mut generator = Generator::new();
+        let now = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_700_000_000_000);
+        let earlier = now - std::time::Duration::from_secs(60);
+
+        let first = next_ulid(&mut generator, now);
+        let second = next_ulid(&mut generator, earlier);
+
+        assert!(second > first);
+        assert_eq!(second.timestamp_ms(), first.timestamp_ms());
+    }
+
     #[test]
     fn prefix_returns_first_10_chars() {
         let id = NoteId::new();
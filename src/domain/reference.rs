@@ -0,0 +1,364 @@
+//! References to notes and files in note bodies.
+//!
+//! Besides frontmatter links, a note can point at another note from its
+//! body: a markdown link to its ID, a `[[wikilink]]`, or its ID written out.
+//! Bodies can also reference local files such as images, which may be
+//! shared between notes.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+use regex::Regex;
+
+use crate::domain::NoteId;
+
+/// Shortest ID prefix that counts as a reference to a note.
+const MIN_PREFIX_LEN: usize = 4;
+
+/// The rel of the links indexed for wikilinks in note bodies.
+pub const WIKILINK_REL: &str = "mentions";
+
+/// Shortest bare word taken for an ID mention; shorter prefixes are too
+/// likely to be ordinary words or numbers.
+const MIN_MENTION_LEN: usize = 10;
+
+/// How a note body refers to a note.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// A markdown link to the note's ID, e.g. `[design](01HQ3K5M7N)`.
+    Link,
+    /// A wikilink to the note's title, an alias or ID, e.g. `[[API Design]]`.
+    Wikilink,
+    /// The note's ID written out in text.
+    IdMention,
+}
+
+impl ReferenceKind {
+    /// Short machine-readable name, used in JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReferenceKind::Link => "link",
+            ReferenceKind::Wikilink => "wikilink",
+            ReferenceKind::IdMention => "id-mention",
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A reference to a note found in a body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyReference {
+    /// 1-based line in the body.
+    pub line: usize,
+    pub kind: ReferenceKind,
+    /// The reference as written, e.g. `[[API Design]]`.
+    pub text: String,
+}
+
+/// Finds the references to a note in a body, in order.
+///
+/// `names` are the titles and aliases a wikilink may use; they are matched
+/// case-insensitively. Fenced code blocks are skipped.
+pub fn find_note_references(body: &str, id: &NoteId, names: &[&str]) -> Vec<BodyReference> {
+    let id = id.to_string();
+    let link_re = Regex::new(r"\]\(\s*<?([^)\s>#]+)[^)]*\)").unwrap();
+    let wikilink_re = Regex::new(r"\[\[([^\]|#]+)(?:[#|][^\]]*)?\]\]").unwrap();
+    let word_re = Regex::new(r"\b[0-9A-Za-z]{10,26}\b").unwrap();
+
+    let mut references = Vec::new();
+    for (i, line) in code_free_lines(body) {
+        let mut found: Vec<(usize, ReferenceKind, String)> = Vec::new();
+        let mut covered = Vec::new();
+
+        for caps in link_re.captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+            if is_id_prefix(&caps[1], &id, MIN_PREFIX_LEN) {
+                let start = line[..whole.start()].rfind('[').unwrap_or(whole.start());
+                found.push((
+                    start,
+                    ReferenceKind::Link,
+                    line[start..whole.end()].to_string(),
+                ));
+            }
+            covered.push(whole.range());
+        }
+        for caps in wikilink_re.captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+            let target = caps[1].trim();
+            if is_id_prefix(target, &id, MIN_PREFIX_LEN)
+                || names.iter().any(|name| name.eq_ignore_ascii_case(target))
+            {
+                found.push((
+                    whole.start(),
+                    ReferenceKind::Wikilink,
+                    whole.as_str().to_string(),
+                ));
+            }
+            covered.push(whole.range());
+        }
+        for word in word_re.find_iter(line) {
+            let inside_link = covered.iter().any(|range| range.contains(&word.start()));
+            if !inside_link && is_id_prefix(word.as_str(), &id, MIN_MENTION_LEN) {
+                found.push((
+                    word.start(),
+                    ReferenceKind::IdMention,
+                    word.as_str().to_string(),
+                ));
+            }
+        }
+
+        found.sort_by_key(|(column, _, _)| *column);
+        references.extend(found.into_iter().map(|(_, kind, text)| BodyReference {
+            line: i + 1,
+            kind,
+            text,
+        }));
+    }
+    references
+}
+
+/// Finds the notes a body wikilinks to: the targets of its `[[wikilinks]]`
+/// as written before any `|label` or `#heading`, trimmed, sorted and without
+/// duplicates.
+///
+/// Embeds (`![[diagram.png]]`) and fenced code blocks are skipped.
+pub fn find_wikilinks(body: &str) -> Vec<String> {
+    // Scanned by hand rather than with a regex: this runs on every note
+    // indexed
+    let mut targets = Vec::new();
+    for (_, line) in code_free_lines(body) {
+        let mut rest = line;
+        while let Some(start) = rest.find("[[") {
+            let embed = rest[..start].ends_with('!');
+            let inner = &rest[start + 2..];
+            let Some(end) = inner.find(']') else {
+                break;
+            };
+            if !embed && inner[end..].starts_with("]]") {
+                let target = inner[..end].split(['|', '#']).next().unwrap_or("").trim();
+                if !target.is_empty() {
+                    targets.push(target.to_string());
+                }
+            }
+            rest = &inner[end..];
+        }
+    }
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// What a wikilink target refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WikilinkTarget {
+    Note(NoteId),
+    /// No note has the title, alias or ID.
+    Missing,
+    /// This many notes share the title or alias, or the ID prefix.
+    Ambiguous(usize),
+}
+
+/// The titles, aliases and IDs of a set of notes, for resolving wikilinks.
+#[derive(Debug, Default)]
+pub struct NoteNames {
+    /// Sorted, for looking up ID prefixes.
+    ids: BTreeSet<String>,
+    /// Lowercased titles and aliases.
+    names: HashMap<String, Vec<NoteId>>,
+}
+
+impl NoteNames {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a note by its ID, title and aliases.
+    pub fn add(&mut self, id: &NoteId, title: &str, aliases: &[String]) {
+        self.ids.insert(id.to_string());
+        for name in std::iter::once(title).chain(aliases.iter().map(String::as_str)) {
+            let ids = self.names.entry(name.trim().to_lowercase()).or_default();
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+    }
+
+    /// Resolves a wikilink target: an ID or ID prefix of at least four
+    /// characters, or else a title or alias, ignoring case.
+    pub fn resolve(&self, target: &str) -> WikilinkTarget {
+        let target = target.trim();
+        if target.len() >= MIN_PREFIX_LEN && target.chars().all(|c| c.is_ascii_alphanumeric()) {
+            let prefix = target.to_ascii_uppercase();
+            let matching: Vec<&String> = self
+                .ids
+                .range(prefix.clone()..)
+                .take_while(|id| id.starts_with(&prefix))
+                .collect();
+            match matching.as_slice() {
+                [] => {}
+                [id] => {
+                    if let Ok(id) = id.parse() {
+                        return WikilinkTarget::Note(id);
+                    }
+                }
+                _ => return WikilinkTarget::Ambiguous(matching.len()),
+            }
+        }
+        match self.names.get(&target.to_lowercase()).map(Vec::as_slice) {
+            None | Some([]) => WikilinkTarget::Missing,
+            Some([id]) => WikilinkTarget::Note(id.clone()),
+            Some(ids) => WikilinkTarget::Ambiguous(ids.len()),
+        }
+    }
+}
+
+/// Finds the local files a body links to or embeds, as written, sorted and
+/// without duplicates.
+///
+/// Markdown links and images, and HTML `src` and `href` attributes, count
+/// when their target is a relative or absolute path rather than a URL, an
+/// anchor or a note ID. Fenced code blocks are skipped.
+pub fn find_local_files(body: &str) -> Vec<String> {
+    let markdown_re = Regex::new(r"\]\(\s*<?([^)\s>]+)").unwrap();
+    let html_re = Regex::new(r#"\b(?:src|href)\s*=\s*["']([^"']+)"#).unwrap();
+
+    let mut files = Vec::new();
+    for (_, line) in code_free_lines(body) {
+        let targets = markdown_re
+            .captures_iter(line)
+            .chain(html_re.captures_iter(line))
+            .map(|caps| caps.get(1).unwrap().as_str());
+        for target in targets {
+            let path = target.split(['#', '?']).next().unwrap_or(target);
+            if is_local_file(path) {
+                files.push(path.to_string());
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// The lines of a body outside fenced code blocks, with their 0-based
+/// line numbers.
+fn code_free_lines(body: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut in_fence = false;
+    body.lines().enumerate().filter(move |(_, line)| {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            return false;
+        }
+        !in_fence
+    })
+}
+
+/// Whether `candidate` is a prefix of the ID at least `min_len` long.
+fn is_id_prefix(candidate: &str, id: &str, min_len: usize) -> bool {
+    candidate.len() >= min_len
+        && candidate.len() <= id.len()
+        && id[..candidate.len()].eq_ignore_ascii_case(candidate)
+}
+
+/// Whether a link target names a file rather than a URL, an anchor or a
+/// note. A target without an extension is taken for a note reference.
+fn is_local_file(target: &str) -> bool {
+    if target.is_empty() || target.starts_with('#') || target.contains("://") {
+        return false;
+    }
+    if target.starts_with("mailto:") || target.starts_with("tel:") || target.starts_with("data:") {
+        return false;
+    }
+    std::path::Path::new(target).extension().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id() -> NoteId {
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap()
+    }
+
+    fn kinds(body: &str) -> Vec<(usize, ReferenceKind)> {
+        find_note_references(body, &id(), &["API Design", "api"])
+            .into_iter()
+            .map(|r| (r.line, r.kind))
+            .collect()
+    }
+
+    #[test]
+    fn finds_links_wikilinks_and_mentions() {
+        let body = "See [the design](01HQ3K5M7N) for details.\n\
+                    Also [[api design]] and [[API|the API]].\n\
+                    Raw id 01HQ3K5M7NXJK4QZPW8V2R6T9Y here.\n";
+        assert_eq!(
+            kinds(body),
+            [
+                (1, ReferenceKind::Link),
+                (2, ReferenceKind::Wikilink),
+                (2, ReferenceKind::Wikilink),
+                (3, ReferenceKind::IdMention),
+            ]
+        );
+
+        let refs = find_note_references(body, &id(), &["API Design"]);
+        assert_eq!(refs[0].text, "[the design](01HQ3K5M7N)");
+        assert_eq!(refs[1].text, "[[api design]]");
+    }
+
+    #[test]
+    fn ignores_other_notes_code_and_short_words() {
+        let body = "A [link](01HQ3K5M7Z) to another note.\n\
+                    [[Other]] and 01HQ3 are not references.\n\
+                    ```\n[x](01HQ3K5M7N)\n```\n";
+        assert_eq!(kinds(body), []);
+    }
+
+    #[test]
+    fn finds_local_files() {
+        let body = "![diagram](images/flow.png)\n\
+                    [spec](docs/spec.pdf#page=2) and [site](https://example.com/a.html)\n\
+                    <img src=\"./photo.jpg\"> [note](01HQ3K5M7N) [top](#intro)\n\
+                    ![again](images/flow.png)\n";
+        assert_eq!(
+            find_local_files(body),
+            ["./photo.jpg", "docs/spec.pdf", "images/flow.png"]
+        );
+    }
+
+    #[test]
+    fn finds_wikilink_targets() {
+        let body = "See [[API Design]], [[ api design |the API]] and [[Rust#Traits]].\n\
+                    ![[diagram.png]] is an embed.\n\
+                    ```\n\
+                    [[In Code]]\n\
+                    ```\n\
+                    Also [[01HQ3K5M7N|design]].\n";
+        assert_eq!(
+            find_wikilinks(body),
+            vec!["01HQ3K5M7N", "API Design", "Rust", "api design"]
+        );
+    }
+
+    #[test]
+    fn resolves_wikilinks_by_id_prefix_title_and_alias() {
+        let other: NoteId = "01HQ4A2R9PXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let mut names = NoteNames::new();
+        names.add(&id(), "API Design", &["api".to_string()]);
+        names.add(&other, "Rust", &["API Design".to_string()]);
+
+        assert_eq!(names.resolve("01hq3k"), WikilinkTarget::Note(id()));
+        assert_eq!(names.resolve("API"), WikilinkTarget::Note(id()));
+        assert_eq!(names.resolve("rust"), WikilinkTarget::Note(other.clone()));
+        assert_eq!(names.resolve("01HQ"), WikilinkTarget::Ambiguous(2));
+        assert_eq!(names.resolve("api design"), WikilinkTarget::Ambiguous(2));
+        assert_eq!(names.resolve("Python"), WikilinkTarget::Missing);
+    }
+}
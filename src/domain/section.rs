@@ -0,0 +1,211 @@
+//! Sections of a note body.
+//!
+//! Each ATX heading (`## Setup`) starts a section that runs until the next
+//! heading at the same or a higher level. Sections are indexed so search can
+//! say which part of a note matched, and `show --section` can print one.
+
+/// Separator between heading titles in a section path.
+pub const SECTION_PATH_SEPARATOR: &str = " > ";
+
+/// A heading in a note body and the text under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    /// Heading level (1-6), or 0 for the text before the first heading.
+    pub level: u8,
+    /// Heading title, empty for the text before the first heading.
+    pub title: String,
+    /// Titles of the enclosing headings and this one, outermost first,
+    /// joined by [`SECTION_PATH_SEPARATOR`].
+    pub path: String,
+    /// Byte offset of the heading line in the body.
+    pub start: usize,
+    /// Byte offset where the section's own text ends: the next heading of
+    /// any level.
+    pub text_end: usize,
+    /// Byte offset where the section ends, subsections included: the next
+    /// heading at the same or a higher level.
+    pub end: usize,
+}
+
+impl Section {
+    /// The section's heading and text, subsections included.
+    pub fn full_text<'a>(&self, body: &'a str) -> &'a str {
+        &body[self.start..self.end]
+    }
+
+    /// The section's heading and text up to its first subsection.
+    pub fn own_text<'a>(&self, body: &'a str) -> &'a str {
+        &body[self.start..self.text_end]
+    }
+
+    /// Checks if `heading` names this section, by title or by path
+    /// (`Guide > Setup`), ignoring case and leading `#`s.
+    pub fn matches(&self, heading: &str) -> bool {
+        let heading = heading.trim().trim_start_matches('#').trim();
+        !heading.is_empty()
+            && (self.title.eq_ignore_ascii_case(heading) || self.path.eq_ignore_ascii_case(heading))
+    }
+}
+
+/// Finds the sections of a note body in document order.
+///
+/// Text before the first heading is a level-0 section with an empty title,
+/// if it isn't blank. Headings inside fenced code blocks are ignored.
+pub fn find_sections(body: &str) -> Vec<Section> {
+    let mut sections: Vec<Section> = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in body.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((level, title)) = parse_heading(line) else {
+            continue;
+        };
+
+        if sections.is_empty() && !body[..start].trim().is_empty() {
+            sections.push(Section {
+                level: 0,
+                title: String::new(),
+                path: String::new(),
+                start: 0,
+                text_end: start,
+                end: start,
+            });
+        }
+        if let Some(last) = sections.last_mut() {
+            last.text_end = start;
+        }
+        for open in sections.iter_mut().filter(|s| s.end == body.len()) {
+            if open.level >= level {
+                open.end = start;
+            }
+        }
+
+        let parent = sections
+            .iter()
+            .rev()
+            .find(|s| s.level > 0 && s.level < level && s.end == body.len());
+        let path = match parent {
+            Some(parent) => format!("{}{}{}", parent.path, SECTION_PATH_SEPARATOR, title),
+            None => title.to_string(),
+        };
+        sections.push(Section {
+            level,
+            title: title.to_string(),
+            path,
+            start,
+            text_end: body.len(),
+            end: body.len(),
+        });
+    }
+
+    if sections.is_empty() && !body.trim().is_empty() {
+        sections.push(Section {
+            level: 0,
+            title: String::new(),
+            path: String::new(),
+            start: 0,
+            text_end: body.len(),
+            end: body.len(),
+        });
+    }
+    sections
+}
+
+/// Finds the first section `heading` names, by title or path.
+pub fn find_section<'a>(sections: &'a [Section], heading: &str) -> Option<&'a Section> {
+    sections.iter().find(|s| s.level > 0 && s.matches(heading))
+}
+
+/// Parses an ATX heading line into its level and title.
+pub fn parse_heading(line: &str) -> Option<(u8, &str)> {
+    let trimmed = line.trim();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let title = &trimmed[level..];
+    if (1..=6).contains(&level) && (title.is_empty() || title.starts_with([' ', '\t'])) {
+        Some((level as u8, title.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = "Intro\n\n# Guide\n\nOverview\n\n## Setup\n\nInstall it\n\n\
+                        ### Linux\n\napt\n\n## Usage ##\n\nRun it\n";
+
+    #[test]
+    fn finds_nested_sections() {
+        let sections = find_sections(BODY);
+
+        let paths: Vec<(u8, &str)> = sections
+            .iter()
+            .map(|s| (s.level, s.path.as_str()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                (0, ""),
+                (1, "Guide"),
+                (2, "Guide > Setup"),
+                (3, "Guide > Setup > Linux"),
+                (2, "Guide > Usage"),
+            ]
+        );
+        assert_eq!(sections[0].full_text(BODY), "Intro\n\n");
+        assert_eq!(sections[1].own_text(BODY), "# Guide\n\nOverview\n\n");
+        assert_eq!(
+            sections[2].full_text(BODY),
+            "## Setup\n\nInstall it\n\n### Linux\n\napt\n\n"
+        );
+        assert_eq!(sections[2].own_text(BODY), "## Setup\n\nInstall it\n\n");
+        assert_eq!(sections[4].full_text(BODY), "## Usage ##\n\nRun it\n");
+        assert_eq!(sections[1].end, BODY.len());
+    }
+
+    #[test]
+    fn ignores_headings_in_code_blocks() {
+        let body = "# Script\n\n```sh\n# not a heading\n```\n\n#hashtag\n";
+        let sections = find_sections(body);
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].full_text(body), body);
+    }
+
+    #[test]
+    fn body_without_headings_is_one_section() {
+        let sections = find_sections("Just text\n");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].level, 0);
+        assert!(find_sections("  \n").is_empty());
+        assert!(find_sections("").is_empty());
+    }
+
+    #[test]
+    fn find_section_by_title_or_path() {
+        let sections = find_sections(BODY);
+
+        assert_eq!(find_section(&sections, "setup").unwrap().level, 2);
+        assert_eq!(find_section(&sections, "## Setup").unwrap().level, 2);
+        assert_eq!(
+            find_section(&sections, "guide > setup > linux")
+                .unwrap()
+                .level,
+            3
+        );
+        assert!(find_section(&sections, "Missing").is_none());
+        assert!(find_section(&sections, "").is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! Note size measures.
+//!
+//! Notes that grow past a few thousand words, dozens of headings or a long
+//! list of links are usually several notes in one. `check` warns about them
+//! so they can be split while the parts are still easy to tell apart.
+
+use std::path::Path;
+
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::{Deserialize, Serialize};
+
+use super::note::Note;
+use super::section::find_sections;
+use super::validation::{ValidationIssue, ValidationKind};
+
+const DEFAULT_MAX_WORDS: usize = 3000;
+const DEFAULT_MAX_HEADINGS: usize = 30;
+const DEFAULT_MAX_LINKS: usize = 100;
+
+/// Sizes above which `check` suggests splitting a note (the `[check]`
+/// section). A limit of 0 turns that check off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SizeLimits {
+    /// Words in the body.
+    pub max_words: usize,
+    /// Headings in the body.
+    pub max_headings: usize,
+    /// Frontmatter links plus links in the body.
+    pub max_links: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            max_words: DEFAULT_MAX_WORDS,
+            max_headings: DEFAULT_MAX_HEADINGS,
+            max_links: DEFAULT_MAX_LINKS,
+        }
+    }
+}
+
+/// Something about a note that can grow too large.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeMeasure {
+    Words,
+    Headings,
+    Links,
+}
+
+impl std::fmt::Display for SizeMeasure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizeMeasure::Words => write!(f, "words"),
+            SizeMeasure::Headings => write!(f, "headings"),
+            SizeMeasure::Links => write!(f, "links"),
+        }
+    }
+}
+
+/// Counts the whitespace-separated words of a note body.
+pub fn count_words(body: &str) -> usize {
+    body.split_whitespace().count()
+}
+
+/// Counts the markdown headings of a note body, ignoring code blocks.
+fn count_headings(body: &str) -> usize {
+    find_sections(body).iter().filter(|s| s.level > 0).count()
+}
+
+/// Counts the markdown links of a note body.
+fn count_body_links(body: &str) -> usize {
+    Parser::new(body)
+        .filter(|event| matches!(event, Event::Start(Tag::Link(..))))
+        .count()
+}
+
+/// Warns about a note whose body exceeds any of the size limits, with one
+/// issue per exceeded limit.
+pub fn find_size_issues(
+    path: &Path,
+    note: &Note,
+    body: &str,
+    limits: &SizeLimits,
+) -> Vec<ValidationIssue> {
+    let measures = [
+        (SizeMeasure::Words, limits.max_words, count_words(body)),
+        (
+            SizeMeasure::Headings,
+            limits.max_headings,
+            count_headings(body),
+        ),
+        (
+            SizeMeasure::Links,
+            limits.max_links,
+            note.links().len() + count_body_links(body),
+        ),
+    ];
+    measures
+        .into_iter()
+        .filter(|(_, limit, count)| *limit > 0 && count > limit)
+        .map(|(measure, limit, count)| {
+            ValidationIssue::new(
+                path,
+                ValidationKind::LargeNote {
+                    measure,
+                    count,
+                    limit,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::NoteId;
+    use chrono::{TimeZone, Utc};
+
+    fn note() -> Note {
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        Note::builder(id, "Big Note", time, time).build().unwrap()
+    }
+
+    fn limits(max_words: usize, max_headings: usize, max_links: usize) -> SizeLimits {
+        SizeLimits {
+            max_words,
+            max_headings,
+            max_links,
+        }
+    }
+
+    fn measures(body: &str, limits: &SizeLimits) -> Vec<(SizeMeasure, usize)> {
+        find_size_issues(Path::new("big.md"), &note(), body, limits)
+            .into_iter()
+            .map(|issue| match issue.kind {
+                ValidationKind::LargeNote { measure, count, .. } => (measure, count),
+                other => panic!("unexpected issue: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn count_words_splits_on_whitespace() {
+        assert_eq!(count_words("one two\n\nthree\tfour "), 4);
+        assert_eq!(count_words(""), 0);
+    }
+
+    #[test]
+    fn notes_within_limits_have_no_issues() {
+        let body = "# Intro\n\nSee [docs](https://example.com).\n";
+        assert!(measures(body, &SizeLimits::default()).is_empty());
+    }
+
+    #[test]
+    fn each_exceeded_limit_is_reported() {
+        let body = "# One\n\n# Two\n\n[a](x) and [b](y) and three more words\n";
+        assert_eq!(
+            measures(body, &limits(5, 1, 1)),
+            vec![
+                (SizeMeasure::Words, 11),
+                (SizeMeasure::Headings, 2),
+                (SizeMeasure::Links, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn headings_in_code_blocks_are_not_counted() {
+        let body = "# Real\n\n```\n# not a heading\n```\n";
+        assert!(measures(body, &limits(0, 1, 0)).is_empty());
+    }
+
+    #[test]
+    fn zero_turns_a_limit_off() {
+        let body = "many words in this body";
+        assert!(measures(body, &limits(0, 0, 0)).is_empty());
+    }
+}
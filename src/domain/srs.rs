@@ -0,0 +1,351 @@
+//! Spaced repetition.
+//!
+//! A note joins the drill deck when it has an `srs` frontmatter field. Lines
+//! in its body written as `question :: answer` are flashcards, each drilled
+//! and scheduled on its own; a note without cards is drilled as a whole,
+//! its title as the prompt and its body as the answer.
+//!
+//! ```yaml
+//! srs:
+//!   due: 2024-03-01
+//!   interval: 3
+//!   ease: 2.5
+//!   cards:
+//!     5d41402a:
+//!       due: 2024-03-04
+//!       interval: 6
+//!       ease: 2.6
+//! ```
+//!
+//! Scheduling follows SM-2: each review multiplies the interval by the ease,
+//! and the ease rises or falls with how well the item was recalled.
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::Note;
+
+/// Frontmatter key holding a note's spaced-repetition state.
+pub const SRS_FIELD: &str = "srs";
+
+/// Ease of an item that has never been reviewed.
+pub const DEFAULT_EASE: f64 = 2.5;
+
+/// Ease never drops below this, so hard items still get longer intervals.
+const MIN_EASE: f64 = 1.3;
+
+/// How well an item was recalled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    /// Forgotten; the item starts over.
+    Again,
+    /// Recalled with difficulty.
+    Hard,
+    /// Recalled.
+    Good,
+    /// Recalled without effort.
+    Easy,
+}
+
+impl Grade {
+    /// Parses a grade from `1`-`4` or its name (case-insensitive).
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key.trim().to_lowercase().as_str() {
+            "1" | "again" => Some(Grade::Again),
+            "2" | "hard" => Some(Grade::Hard),
+            "3" | "good" => Some(Grade::Good),
+            "4" | "easy" => Some(Grade::Easy),
+            _ => None,
+        }
+    }
+}
+
+/// When an item is next due and how its interval grows.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    /// Next review date; `None` for an item never reviewed, which is due now.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Days between the last review and `due`.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub interval: u32,
+    pub ease: f64,
+}
+
+fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            due: None,
+            interval: 0,
+            ease: DEFAULT_EASE,
+        }
+    }
+}
+
+impl Schedule {
+    /// Returns true if the item should be reviewed on `today`.
+    pub fn is_due(&self, today: NaiveDate) -> bool {
+        self.due.is_none_or(|due| due <= today)
+    }
+
+    /// Returns the schedule after a review on `today`.
+    pub fn review(&self, grade: Grade, today: NaiveDate) -> Schedule {
+        let grown = |factor: f64| {
+            let interval = (self.interval as f64 * factor).round() as u32;
+            interval.max(self.interval + 1)
+        };
+        let (interval, ease) = match grade {
+            Grade::Again => (1, self.ease - 0.2),
+            Grade::Hard => (grown(1.2), self.ease - 0.15),
+            Grade::Good if self.interval == 0 => (1, self.ease),
+            Grade::Good => (grown(self.ease), self.ease),
+            Grade::Easy if self.interval == 0 => (4, self.ease + 0.15),
+            Grade::Easy => (grown(self.ease * 1.3), self.ease + 0.15),
+        };
+
+        Schedule {
+            due: Some(today + Days::new(interval.into())),
+            interval,
+            ease: (ease.max(MIN_EASE) * 100.0).round() / 100.0,
+        }
+    }
+}
+
+/// A `question :: answer` line in a note body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    /// Stable key derived from the question, used to store the card's schedule.
+    pub key: String,
+    pub question: String,
+    pub answer: String,
+}
+
+/// Finds the flashcards in a note body.
+///
+/// A card is a line with ` :: ` between a question and an answer; a leading
+/// list marker is dropped. Lines in fenced code blocks are skipped. Cards
+/// with the same question share a key, so only the first is kept.
+pub fn find_cards(body: &str) -> Vec<Card> {
+    let mut cards: Vec<Card> = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let Some((question, answer)) = trimmed.split_once(" :: ") else {
+            continue;
+        };
+        let question = question
+            .trim_start_matches(['-', '*', '+'])
+            .trim()
+            .to_string();
+        let answer = answer.trim().to_string();
+        if question.is_empty() || answer.is_empty() {
+            continue;
+        }
+
+        let key = card_key(&question);
+        if !cards.iter().any(|c| c.key == key) {
+            cards.push(Card {
+                key,
+                question,
+                answer,
+            });
+        }
+    }
+    cards
+}
+
+/// Hashes a question (FNV-1a) into an 8-digit hex key.
+fn card_key(question: &str) -> String {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in question.as_bytes() {
+        hash ^= u32::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    format!("{:08x}", hash)
+}
+
+/// Error returned when a note's `srs` field can't be read.
+#[derive(Debug, Clone)]
+pub struct ParseSrsError {
+    message: String,
+}
+
+impl fmt::Display for ParseSrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid srs field: {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseSrsError {}
+
+/// A note's spaced-repetition state: its own schedule and its cards'.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Srs {
+    /// Schedule of the note drilled as a whole.
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// Schedules of the note's cards, by card key.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub cards: BTreeMap<String, Schedule>,
+}
+
+impl Srs {
+    /// Reads the state from a note's frontmatter; `None` if the note is not
+    /// in the deck.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseSrsError` if the `srs` field is not a valid schedule.
+    pub fn from_note(note: &Note) -> Result<Option<Self>, ParseSrsError> {
+        let Some(value) = note.extra().get(SRS_FIELD) else {
+            return Ok(None);
+        };
+        if value.is_null() {
+            return Ok(Some(Srs::default()));
+        }
+        serde_yaml::from_value(value.clone())
+            .map(Some)
+            .map_err(|e| ParseSrsError {
+                message: e.to_string(),
+            })
+    }
+
+    /// Returns the state as a frontmatter value.
+    pub fn to_value(&self) -> serde_yaml::Value {
+        serde_yaml::to_value(self).expect("SRS serialization is infallible")
+    }
+
+    /// Returns the schedule of a card (or of the note, for `None`).
+    pub fn schedule(&self, card: Option<&str>) -> Schedule {
+        match card {
+            Some(key) => self.cards.get(key).copied().unwrap_or_default(),
+            None => self.schedule,
+        }
+    }
+
+    /// Sets the schedule of a card (or of the note, for `None`).
+    pub fn set_schedule(&mut self, card: Option<&str>, schedule: Schedule) {
+        match card {
+            Some(key) => {
+                self.cards.insert(key.to_string(), schedule);
+            }
+            None => self.schedule = schedule,
+        }
+    }
+
+    /// Drops the schedules of cards no longer in the body.
+    pub fn retain_cards(&mut self, cards: &[Card]) {
+        self.cards
+            .retain(|key, _| cards.iter().any(|c| &c.key == key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::NoteId;
+    use chrono::{TimeZone, Utc};
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    fn note_with_srs(yaml: &str) -> Note {
+        let time = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        Note::builder(id, "Cards", time, time)
+            .extra(BTreeMap::from([(
+                SRS_FIELD.to_string(),
+                serde_yaml::from_str(yaml).unwrap(),
+            )]))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn new_items_are_due() {
+        assert!(Schedule::default().is_due(day(1)));
+        let scheduled = Schedule {
+            due: Some(day(5)),
+            ..Schedule::default()
+        };
+        assert!(!scheduled.is_due(day(4)));
+        assert!(scheduled.is_due(day(5)));
+    }
+
+    #[test]
+    fn review_grows_interval_with_ease() {
+        let first = Schedule::default().review(Grade::Good, day(1));
+        assert_eq!((first.due, first.interval), (Some(day(2)), 1));
+
+        let second = first.review(Grade::Good, day(2));
+        assert_eq!(second.interval, 3);
+        let third = second.review(Grade::Easy, day(5));
+        assert_eq!(third.interval, 10);
+        assert_eq!(third.ease, 2.65);
+    }
+
+    #[test]
+    fn again_resets_interval_and_lowers_ease() {
+        let schedule = Schedule {
+            due: Some(day(1)),
+            interval: 20,
+            ease: 1.4,
+        };
+        let after = schedule.review(Grade::Again, day(1));
+        assert_eq!(after.interval, 1);
+        assert_eq!(after.ease, MIN_EASE);
+    }
+
+    #[test]
+    fn finds_cards_outside_code() {
+        let body = "Intro\n- Capital of France :: Paris\n```\na :: b\n```\n2 + 2 :: 4\nnot :: \n";
+        let cards = find_cards(body);
+        let pairs: Vec<_> = cards
+            .iter()
+            .map(|c| (c.question.as_str(), c.answer.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("Capital of France", "Paris"), ("2 + 2", "4")]);
+        assert_eq!(cards[0].key, card_key("Capital of France"));
+    }
+
+    #[test]
+    fn srs_roundtrips_through_frontmatter() {
+        let note = note_with_srs("due: 2024-03-04\ninterval: 3\ncards:\n  abc: {interval: 1}\n");
+        let srs = Srs::from_note(&note).unwrap().unwrap();
+
+        assert_eq!(srs.schedule.due, Some(day(4)));
+        assert_eq!(srs.schedule.ease, DEFAULT_EASE);
+        assert_eq!(srs.schedule(Some("abc")).interval, 1);
+        assert_eq!(srs.schedule(Some("new")), Schedule::default());
+
+        let yaml = serde_yaml::to_string(&srs.to_value()).unwrap();
+        assert_eq!(
+            yaml,
+            "due: 2024-03-04\ninterval: 3\nease: 2.5\ncards:\n  abc:\n    interval: 1\n    ease: 2.5\n"
+        );
+    }
+
+    #[test]
+    fn invalid_srs_is_an_error() {
+        let note = note_with_srs("due: someday");
+        assert!(Srs::from_note(&note).is_err());
+    }
+}
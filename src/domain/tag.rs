@@ -1,9 +1,13 @@
 //! Case-insensitive tag type for categorizing notes.
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 
+/// The canonical tag used to mark archived notes.
+pub const ARCHIVED_TAG: &str = "archived";
+
 /// A case-insensitive tag for categorizing notes.
 ///
 /// Tags are flat (non-hierarchical) labels for filtering notes.
@@ -12,6 +16,8 @@ use std::str::FromStr;
 /// # Validation Rules
 /// - Non-empty after normalization
 /// - Must contain only alphanumeric characters, hyphens, and underscores
+/// - May end in `:YYYY-MM-DD`, the date the tag expires, as in
+///   `review-by:2024-06-01`
 ///
 /// # Normalization
 /// - Surrounding whitespace is trimmed
@@ -37,6 +43,7 @@ pub struct Tag(String); // Always stored lowercase
 enum ParseTagErrorKind {
     Empty,
     InvalidCharacters,
+    InvalidExpiry,
 }
 
 /// Error returned when parsing an invalid tag.
@@ -64,6 +71,13 @@ impl fmt::Display for ParseTagError {
                     self.value
                 )
             }
+            ParseTagErrorKind::InvalidExpiry => {
+                write!(
+                    f,
+                    "invalid tag '{}': an expiry after ':' must be a YYYY-MM-DD date",
+                    self.value
+                )
+            }
         }
     }
 }
@@ -93,15 +107,26 @@ impl Tag {
         }
 
         // Validate characters
-        if !normalized
-            .chars()
-            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        let (name, expiry) = match normalized.split_once(':') {
+            Some((name, expiry)) => (name, Some(expiry)),
+            None => (normalized.as_str(), None),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
         {
             return Err(ParseTagError {
                 kind: ParseTagErrorKind::InvalidCharacters,
                 value: normalized,
             });
         }
+        if expiry.is_some_and(|e| NaiveDate::parse_from_str(e, "%Y-%m-%d").is_err()) {
+            return Err(ParseTagError {
+                kind: ParseTagErrorKind::InvalidExpiry,
+                value: normalized,
+            });
+        }
 
         Ok(Self(normalized))
     }
@@ -110,6 +135,23 @@ impl Tag {
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Returns the tag without its expiry date, e.g. `review-by` for
+    /// `review-by:2024-06-01`.
+    pub fn name(&self) -> &str {
+        self.0.split_once(':').map_or(&self.0, |(name, _)| name)
+    }
+
+    /// Returns the date the tag expires, if it carries one.
+    pub fn expires(&self) -> Option<NaiveDate> {
+        let (_, date) = self.0.split_once(':')?;
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    }
+
+    /// Returns this tag set to expire on `date`, replacing any date it has.
+    pub fn expiring(&self, date: NaiveDate) -> Self {
+        Self(format!("{}:{}", self.name(), date.format("%Y-%m-%d")))
+    }
 }
 
 impl fmt::Display for Tag {
@@ -229,6 +271,30 @@ mod tests {
         assert!(Tag::new("tag#1").is_err());
     }
 
+    #[test]
+    fn accepts_expiry_date() {
+        let tag = Tag::new("Review-By:2024-06-01").unwrap();
+        assert_eq!(tag.as_str(), "review-by:2024-06-01");
+        assert_eq!(tag.name(), "review-by");
+        assert_eq!(tag.expires(), NaiveDate::from_ymd_opt(2024, 6, 1));
+
+        let plain = Tag::new("draft").unwrap();
+        assert_eq!(plain.name(), "draft");
+        assert_eq!(plain.expires(), None);
+
+        let date = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        assert_eq!(plain.expiring(date).as_str(), "draft:2024-07-01");
+        assert_eq!(tag.expiring(date).as_str(), "review-by:2024-07-01");
+    }
+
+    #[test]
+    fn rejects_malformed_expiry() {
+        assert!(Tag::new("review-by:soon").is_err());
+        assert!(Tag::new("review-by:2024-13-01").is_err());
+        assert!(Tag::new(":2024-06-01").is_err());
+        assert!(Tag::new("a:2024-06-01:x").is_err());
+    }
+
     #[test]
     fn rejects_slashes() {
         assert!(Tag::new("path/tag").is_err()); // Tags aren't hierarchical
@@ -0,0 +1,155 @@
+//! External URLs.
+//!
+//! Links to web pages in note bodies are indexed, so `urls` can list the
+//! references across the vault and check which of them are broken.
+
+/// Finds the `http` and `https` URLs in a note body, sorted and without
+/// duplicates.
+///
+/// URLs are found bare, in markdown links and in `<...>` autolinks.
+/// Punctuation that ends a sentence isn't part of a URL, nor is a closing
+/// `)` or `]` without a matching opening one in the URL. Fenced code blocks
+/// and inline code are skipped.
+pub fn find_urls(body: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut in_fence = false;
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let line = blank_inline_code(line);
+        let mut rest = line.as_str();
+        while let Some(start) = find_scheme(rest) {
+            let candidate = &rest[start..];
+            let len = candidate
+                .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '`'))
+                .unwrap_or(candidate.len());
+            let url = trim_url(&candidate[..len]);
+            if url_domain(url).is_some_and(|d| !d.is_empty()) {
+                urls.push(url.to_string());
+            }
+            rest = &candidate[len..];
+        }
+    }
+
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+/// Returns the lowercased host of a URL, without credentials or port.
+pub fn url_domain(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+    let host = match host.strip_prefix('[') {
+        // IPv6 literal, e.g. [::1]:8080
+        Some(ipv6) => ipv6.split(']').next().unwrap_or(ipv6),
+        None => host.split(':').next().unwrap_or(host),
+    };
+    Some(host.to_lowercase())
+}
+
+/// Finds the next `http://` or `https://` that starts a URL, i.e. isn't
+/// preceded by a letter or digit.
+fn find_scheme(text: &str) -> Option<usize> {
+    let lower = text.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find("http") {
+        let start = from + pos;
+        let after = &lower[start..];
+        let preceded = lower[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric());
+        if !preceded && (after.starts_with("http://") || after.starts_with("https://")) {
+            return Some(start);
+        }
+        from = start + "http".len();
+    }
+    None
+}
+
+/// Drops trailing characters that belong to the surrounding text.
+fn trim_url(mut url: &str) -> &str {
+    loop {
+        let Some(last) = url.chars().next_back() else {
+            return url;
+        };
+        let unbalanced =
+            |open: char, close: char| url.matches(close).count() > url.matches(open).count();
+        let trailing = match last {
+            '.' | ',' | ';' | ':' | '!' | '?' | '*' | '_' | '~' | '\'' => true,
+            ')' => unbalanced('(', ')'),
+            ']' => unbalanced('[', ']'),
+            _ => false,
+        };
+        if !trailing {
+            return url;
+        }
+        url = &url[..url.len() - last.len_utf8()];
+    }
+}
+
+/// Replaces inline code spans with spaces, keeping the rest of the line.
+fn blank_inline_code(line: &str) -> String {
+    let mut in_code = false;
+    line.chars()
+        .map(|c| {
+            if c == '`' {
+                in_code = !in_code;
+                ' '
+            } else if in_code {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_bare_markdown_and_autolinked_urls() {
+        let body = "See https://example.com/docs.\n\
+                    [Repo](https://github.com/rust-lang/rust) and <http://old.example.org>\n\
+                    Again: https://example.com/docs, (https://en.wikipedia.org/wiki/Rust_(language))\n";
+        assert_eq!(
+            find_urls(body),
+            vec![
+                "http://old.example.org",
+                "https://en.wikipedia.org/wiki/Rust_(language)",
+                "https://example.com/docs",
+                "https://github.com/rust-lang/rust",
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_code_and_non_urls() {
+        let body = "`https://inline.example.com` and https://real.example.com\n\
+                    ```\nhttps://fenced.example.com\n```\n\
+                    nothttps://x.com, https://, ftp://files.example.com\n";
+        assert_eq!(find_urls(body), vec!["https://real.example.com"]);
+    }
+
+    #[test]
+    fn url_domain_strips_credentials_and_port() {
+        assert_eq!(
+            url_domain("https://user:pw@Docs.GitHub.com:8443/a?b#c").as_deref(),
+            Some("docs.github.com")
+        );
+        assert_eq!(url_domain("http://[::1]:8080/").as_deref(), Some("::1"));
+        assert_eq!(url_domain("not a url"), None);
+    }
+}
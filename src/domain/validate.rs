@@ -1,13 +1,27 @@
 //! Validation functions for notes collections.
 //!
 //! This module provides pure functions that validate collections of notes,
-//! detecting issues like duplicate IDs, broken links, and orphaned notes.
+//! detecting issues like duplicate IDs, broken links, missing reciprocal
+//! links, orphaned notes, timestamp anomalies, missing type fields and
+//! expired tags.
 //! All functions are designed to be testable in isolation without I/O.
 
-use std::collections::{HashMap, HashSet};
+use chrono::{DateTime, Duration, NaiveDate, SubsecRound, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 
-use crate::domain::{Note, NoteId, ValidationIssue, ValidationSummary};
+use crate::domain::{
+    Note, NoteId, NoteNames, Rel, Tag, TimestampField, ValidationIssue, ValidationKind,
+    ValidationSummary, WikilinkTarget,
+};
+
+/// How far in the future a timestamp may be before it is reported, to
+/// allow for clock skew between machines.
+pub const CLOCK_SKEW_TOLERANCE: Duration = Duration::minutes(5);
+
+/// How much newer a file's mtime must be than its frontmatter `modified`
+/// time before the note is reported as edited outside den.
+pub const EXTERNAL_EDIT_THRESHOLD: Duration = Duration::days(1);
 
 /// Validates a collection of notes for duplicate IDs.
 ///
@@ -75,6 +89,134 @@ pub fn find_broken_links(
     issues
 }
 
+/// Finds `[[wikilinks]]` that match no note in the collection by title,
+/// alias or ID, or match several.
+///
+/// # Arguments
+///
+/// * `notes` - A slice of (path, note) pairs to validate
+/// * `wikilinks` - The wikilink targets in each note's body, by note ID
+///
+/// # Returns
+///
+/// A vector of `ValidationIssue` (with Warning severity), one per target.
+pub fn find_broken_wikilinks(
+    notes: &[(PathBuf, &Note)],
+    wikilinks: &HashMap<NoteId, Vec<String>>,
+) -> Vec<ValidationIssue> {
+    let mut names = NoteNames::new();
+    for (_, note) in notes {
+        names.add(note.id(), note.title(), note.aliases());
+    }
+
+    let mut issues = Vec::new();
+    for (path, note) in notes {
+        let targets = wikilinks.get(note.id()).map_or(&[][..], Vec::as_slice);
+        for (target, matches) in unresolved_wikilinks(&names, targets) {
+            issues.push(ValidationIssue::new(
+                path.clone(),
+                ValidationKind::BrokenWikilink {
+                    target: target.clone(),
+                    matches,
+                },
+            ));
+        }
+    }
+    issues
+}
+
+/// Returns the wikilink targets that match no note in `names`, or several,
+/// each with the number of notes it matches.
+pub fn unresolved_wikilinks<'a>(
+    names: &'a NoteNames,
+    targets: &'a [String],
+) -> impl Iterator<Item = (&'a String, usize)> + 'a {
+    targets
+        .iter()
+        .filter_map(|target| match names.resolve(target) {
+            WikilinkTarget::Note(_) => None,
+            WikilinkTarget::Missing => Some((target, 0)),
+            WikilinkTarget::Ambiguous(matches) => Some((target, matches)),
+        })
+}
+
+/// Finds links with a reciprocal rel whose target does not link back.
+///
+/// For each pair `(a, b)`, a link from X to Y with rel `a` expects a link
+/// from Y to X with rel `b`, and a link with rel `b` expects one with rel
+/// `a`. A pair may name the same rel twice for symmetric relationships.
+///
+/// # Arguments
+///
+/// * `notes` - A slice of (path, note) pairs to validate
+/// * `pairs` - Reciprocal rel pairs
+///
+/// # Returns
+///
+/// A vector of `ValidationIssue` (with Warning severity), reported on the
+/// note that is missing the link back. Links to notes outside the collection
+/// are left to [`find_broken_links`].
+pub fn find_missing_reciprocals(
+    notes: &[(PathBuf, &Note)],
+    pairs: &[(Rel, Rel)],
+) -> Vec<ValidationIssue> {
+    let mut by_id: HashMap<&NoteId, (&PathBuf, &Note)> = HashMap::new();
+    for (path, note) in notes {
+        by_id.entry(note.id()).or_insert((path, note));
+    }
+
+    let reciprocals_of = |rel: &Rel| -> Vec<&Rel> {
+        let mut expected = Vec::new();
+        for (a, b) in pairs {
+            if a == rel && !expected.contains(&b) {
+                expected.push(b);
+            }
+            if b == rel && !expected.contains(&a) {
+                expected.push(a);
+            }
+        }
+        expected
+    };
+
+    let mut seen = HashSet::new();
+    let mut issues = Vec::new();
+
+    for (_, note) in notes {
+        for link in note.links() {
+            if link.target() == note.id() {
+                continue;
+            }
+            let Some((target_path, target)) = by_id.get(link.target()) else {
+                continue;
+            };
+            let back_rels: Vec<&Rel> = target
+                .links()
+                .iter()
+                .filter(|l| l.target() == note.id())
+                .flat_map(|l| l.rel())
+                .collect();
+
+            for rel in link.rel() {
+                for expected in reciprocals_of(rel) {
+                    if back_rels.contains(&expected) {
+                        continue;
+                    }
+                    if seen.insert((target.id(), note.id(), expected)) {
+                        issues.push(ValidationIssue::missing_reciprocal(
+                            (*target_path).clone(),
+                            note.id().clone(),
+                            rel.clone(),
+                            expected.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    issues
+}
+
 /// Finds notes with no topics (orphaned in the virtual folder hierarchy).
 ///
 /// # Arguments
@@ -92,6 +234,211 @@ pub fn find_orphaned_notes(notes: &[(PathBuf, &Note)]) -> Vec<ValidationIssue> {
         .collect()
 }
 
+/// Finds notes with no topics that must have one: every note when `all` is
+/// set, otherwise notes whose type is in `types`.
+///
+/// These are errors; callers drop the orphaned warnings for the same notes.
+pub fn find_missing_topics(
+    notes: &[(PathBuf, &Note)],
+    all: bool,
+    types: &[String],
+) -> Vec<ValidationIssue> {
+    notes
+        .iter()
+        .filter(|(_, note)| note.topics().is_empty())
+        .filter_map(|(path, note)| {
+            if all {
+                return Some(ValidationIssue::missing_topics(path.clone(), None));
+            }
+            let note_type = note.note_type()?;
+            types
+                .iter()
+                .any(|t| t == note_type)
+                .then(|| ValidationIssue::missing_topics(path.clone(), Some(note_type)))
+        })
+        .collect()
+}
+
+/// Finds notes whose frontmatter timestamps look wrong.
+///
+/// Reports notes created after they were modified, `created` or `modified`
+/// times later than `now` (beyond [`CLOCK_SKEW_TOLERANCE`]), and files whose
+/// mtime is more than [`EXTERNAL_EDIT_THRESHOLD`] newer than `modified`.
+///
+/// # Arguments
+///
+/// * `notes` - A slice of (path, note) pairs to validate
+/// * `mtimes` - File modification times by path; notes without one skip the mtime check
+/// * `now` - The current time
+///
+/// # Returns
+///
+/// A vector of `ValidationIssue` (with Warning severity) for each anomaly.
+pub fn find_timestamp_anomalies(
+    notes: &[(PathBuf, &Note)],
+    mtimes: &HashMap<PathBuf, DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+    let future = now + CLOCK_SKEW_TOLERANCE;
+
+    for (path, note) in notes {
+        let (created, modified) = (note.created(), note.modified());
+        if created > modified {
+            issues.push(ValidationIssue::created_after_modified(
+                path.clone(),
+                created,
+                modified,
+            ));
+        }
+        if created > future {
+            issues.push(ValidationIssue::future_timestamp(
+                path.clone(),
+                TimestampField::Created,
+                created,
+            ));
+        }
+        if modified > future {
+            issues.push(ValidationIssue::future_timestamp(
+                path.clone(),
+                TimestampField::Modified,
+                modified,
+            ));
+        }
+        if let Some(&mtime) = mtimes.get(path)
+            && mtime - modified > EXTERNAL_EDIT_THRESHOLD
+        {
+            issues.push(ValidationIssue::external_edit(
+                path.clone(),
+                modified,
+                mtime,
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Checks typed notes against their type's required fields.
+///
+/// Notes without a `type` are skipped. A type missing from `types` is
+/// reported as unknown; otherwise each required field the note lacks (see
+/// [`Note::has_field`]) is reported.
+///
+/// # Arguments
+///
+/// * `notes` - A slice of (path, note) pairs to validate
+/// * `types` - Required fields by type name
+///
+/// # Returns
+///
+/// A vector of `ValidationIssue`: warnings for unknown types, errors for
+/// missing fields.
+pub fn find_type_issues(
+    notes: &[(PathBuf, &Note)],
+    types: &BTreeMap<String, Vec<String>>,
+) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    for (path, note) in notes {
+        let Some(note_type) = note.note_type() else {
+            continue;
+        };
+        let Some(required) = types.get(note_type) else {
+            issues.push(ValidationIssue::unknown_type(path.clone(), note_type));
+            continue;
+        };
+        for field in required {
+            if !note.has_field(field) {
+                issues.push(ValidationIssue::missing_field(
+                    path.clone(),
+                    note_type,
+                    field.as_str(),
+                ));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Returns a note's tags that expired before `today`, with the day each
+/// expired.
+///
+/// A tag expires on the date it carries, as in `review-by:2024-06-01`, or
+/// else when the TTL configured for its name in `ttl` (in days) has passed
+/// since the note was created. `tag` dates tags with a TTL as it adds them,
+/// so only tags written by hand fall back to the creation date, which,
+/// unlike the modification date, later edits don't move.
+pub fn expired_tags<'a>(
+    note: &'a Note,
+    ttl: &BTreeMap<String, u32>,
+    today: NaiveDate,
+) -> Vec<(&'a Tag, NaiveDate)> {
+    note.tags()
+        .iter()
+        .filter_map(|tag| {
+            let expires = tag.expires().or_else(|| {
+                let days = ttl.get(tag.name())?;
+                Some(note.created().date_naive() + Duration::days(i64::from(*days)))
+            })?;
+            (expires < today).then_some((tag, expires))
+        })
+        .collect()
+}
+
+/// Finds tags that expired before `today` (see [`expired_tags`]).
+///
+/// # Returns
+///
+/// A vector of `ValidationIssue` warnings, one per expired tag.
+pub fn find_expired_tags(
+    notes: &[(PathBuf, &Note)],
+    ttl: &BTreeMap<String, u32>,
+    today: NaiveDate,
+) -> Vec<ValidationIssue> {
+    notes
+        .iter()
+        .flat_map(|(path, note)| {
+            expired_tags(note, ttl, today)
+                .into_iter()
+                .map(|(tag, expired)| {
+                    ValidationIssue::new(
+                        path.clone(),
+                        ValidationKind::ExpiredTag {
+                            tag: tag.clone(),
+                            expired,
+                        },
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Returns the `modified` time a note should have, judging by its file's
+/// mtime, or `None` if it needs no change.
+///
+/// Applies to notes with a `modified` anomaly from
+/// [`find_timestamp_anomalies`]: `modified` becomes the mtime (truncated to
+/// whole seconds), but never earlier than `created`. Notes whose reconciled
+/// time would itself be in the future are left alone.
+pub fn reconcile_modified(
+    note: &Note,
+    mtime: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let (created, modified) = (note.created(), note.modified());
+    let future = now + CLOCK_SKEW_TOLERANCE;
+    let anomalous =
+        created > modified || modified > future || mtime - modified > EXTERNAL_EDIT_THRESHOLD;
+    if !anomalous {
+        return None;
+    }
+
+    let reconciled = mtime.trunc_subsecs(0).max(created);
+    (reconciled <= future && reconciled != modified).then_some(reconciled)
+}
+
 /// Runs all structural validations on a collection of notes.
 ///
 /// Combines duplicate ID, broken link, and orphan checks into a single summary.
@@ -105,6 +452,27 @@ pub fn find_orphaned_notes(notes: &[(PathBuf, &Note)]) -> Vec<ValidationIssue> {
 ///
 /// A `ValidationSummary` containing all issues found.
 pub fn validate_notes(notes: &[(PathBuf, &Note)]) -> ValidationSummary {
+    validate_notes_with_trash(notes, &HashSet::new())
+}
+
+/// Runs all structural validations, treating trashed notes as link targets.
+///
+/// Links whose target is missing from `notes` but present in `trashed_ids`
+/// are reported as trashed-link warnings instead of broken-link errors, since
+/// the target can still be restored.
+///
+/// # Arguments
+///
+/// * `notes` - A slice of (path, note) pairs to validate
+/// * `trashed_ids` - IDs of notes currently in the trash
+///
+/// # Returns
+///
+/// A `ValidationSummary` containing all issues found.
+pub fn validate_notes_with_trash(
+    notes: &[(PathBuf, &Note)],
+    trashed_ids: &HashSet<NoteId>,
+) -> ValidationSummary {
     let mut summary = ValidationSummary::new();
 
     // Collect all known IDs from the input
@@ -115,7 +483,12 @@ pub fn validate_notes(notes: &[(PathBuf, &Note)]) -> ValidationSummary {
         summary.add(issue);
     }
     for issue in find_broken_links(notes, &known_ids) {
-        summary.add(issue);
+        match issue.kind {
+            ValidationKind::BrokenLink { target_id } if trashed_ids.contains(&target_id) => {
+                summary.add(ValidationIssue::trashed_link(issue.path, target_id));
+            }
+            _ => summary.add(issue),
+        }
     }
     for issue in find_orphaned_notes(notes) {
         summary.add(issue);
@@ -129,6 +502,7 @@ mod tests {
     use super::*;
     use crate::domain::{Link, NoteId, Severity, Topic, ValidationKind};
     use chrono::{DateTime, Utc};
+    use std::path::Path;
 
     // ===========================================
     // Test Helpers
@@ -308,7 +682,7 @@ mod tests {
     fn duplicate_ids_different_ids() {
         let id_x: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9X".parse().unwrap();
         let id_y: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Z".parse().unwrap();
-        let notes_owned = vec![
+        let notes_owned = [
             test_note_with_id(id_x.clone(), "X1"),
             test_note_with_id(id_x, "X2"),
             test_note_with_id(id_y.clone(), "Y1"),
@@ -485,6 +859,30 @@ mod tests {
         assert_eq!(issues.len(), 2);
     }
 
+    #[test]
+    fn missing_topics_for_all_notes_or_listed_types() {
+        let orphan = test_note_without_topics();
+        let meeting = typed_note("type: meeting\n");
+        let filed = test_note_with_topics("01HQ3K5M7NXJK4QZPW8V2R6T9A", vec!["work"]);
+        let notes = vec![
+            (PathBuf::from("orphan.md"), &orphan),
+            (PathBuf::from("meeting.md"), &meeting),
+            (PathBuf::from("filed.md"), &filed),
+        ];
+
+        assert!(find_missing_topics(&notes, false, &[]).is_empty());
+        assert_eq!(find_missing_topics(&notes, true, &[]).len(), 2);
+
+        let issues = find_missing_topics(&notes, false, &["meeting".to_string()]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, PathBuf::from("meeting.md"));
+        assert_eq!(issues[0].severity(), Severity::Error);
+        assert_eq!(
+            issues[0].kind.to_string(),
+            "no topics, but type 'meeting' requires one"
+        );
+    }
+
     // ===========================================
     // Phase 4: Combined Validation
     // ===========================================
@@ -582,6 +980,34 @@ mod tests {
         assert!(summary.has_errors());
     }
 
+    #[test]
+    fn validate_notes_with_trash_downgrades_trashed_targets() {
+        let trashed: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9T".parse().unwrap();
+        let missing: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9X".parse().unwrap();
+
+        let note = test_note_with_topics_and_links(
+            "01HQ3K5M7NXJK4QZPW8V2R6T9A",
+            vec!["software"],
+            vec![trashed.clone(), missing.clone()],
+        );
+        let notes = vec![(PathBuf::from("source.md"), &note)];
+
+        let summary = validate_notes_with_trash(&notes, &HashSet::from([trashed.clone()]));
+
+        assert_eq!(summary.error_count(), 1);
+        assert_eq!(summary.warning_count(), 1);
+        let broken: Vec<_> = summary.broken_links().collect();
+        assert_eq!(
+            broken[0].kind,
+            ValidationKind::BrokenLink { target_id: missing }
+        );
+        let trashed_issue = summary.issues.iter().find(|i| i.is_trashed_link()).unwrap();
+        assert_eq!(
+            trashed_issue.kind,
+            ValidationKind::TrashedLink { target_id: trashed }
+        );
+    }
+
     // ===========================================
     // Phase 5: Edge Cases
     // ===========================================
@@ -658,4 +1084,370 @@ mod tests {
         let summary = validate_notes(&notes);
         assert!(summary.is_ok());
     }
+
+    // ===========================================
+    // Missing Reciprocal Links
+    // ===========================================
+
+    fn rel_pairs() -> Vec<(Rel, Rel)> {
+        vec![
+            (Rel::new("parent").unwrap(), Rel::new("child").unwrap()),
+            (Rel::new("related").unwrap(), Rel::new("related").unwrap()),
+        ]
+    }
+
+    fn note_linking(id_str: &str, links: Vec<(NoteId, &str)>) -> Note {
+        let id: NoteId = id_str.parse().unwrap();
+        let links: Vec<Link> = links
+            .into_iter()
+            .map(|(target, rel)| Link::new(target, vec![rel]).unwrap())
+            .collect();
+        Note::builder(id, "Linked", test_datetime(), test_datetime())
+            .links(links)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn find_missing_reciprocals_reports_on_target_note() {
+        let id_a: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9A".parse().unwrap();
+        let id_b: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9B".parse().unwrap();
+        let note_a = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9A", vec![(id_b, "parent")]);
+        let note_b = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9B", vec![]);
+
+        let notes = vec![
+            (PathBuf::from("a.md"), &note_a),
+            (PathBuf::from("b.md"), &note_b),
+        ];
+        let issues = find_missing_reciprocals(&notes, &rel_pairs());
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].path, PathBuf::from("b.md"));
+        assert_eq!(issues[0].severity(), Severity::Warning);
+        assert_eq!(
+            issues[0].kind,
+            ValidationKind::MissingReciprocal {
+                source_id: id_a,
+                rel: Rel::new("parent").unwrap(),
+                expected: Rel::new("child").unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn find_missing_reciprocals_accepts_links_in_both_directions() {
+        let id_a: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9A".parse().unwrap();
+        let id_b: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9B".parse().unwrap();
+        let note_a = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9A", vec![(id_b.clone(), "child")]);
+        let note_b = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9B", vec![(id_a, "parent")]);
+
+        let notes = vec![
+            (PathBuf::from("a.md"), &note_a),
+            (PathBuf::from("b.md"), &note_b),
+        ];
+        assert!(find_missing_reciprocals(&notes, &rel_pairs()).is_empty());
+    }
+
+    #[test]
+    fn find_missing_reciprocals_handles_symmetric_rels() {
+        let id_b: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9B".parse().unwrap();
+        let note_a = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9A", vec![(id_b, "related")]);
+        let note_b = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9B", vec![]);
+
+        let notes = vec![
+            (PathBuf::from("a.md"), &note_a),
+            (PathBuf::from("b.md"), &note_b),
+        ];
+        let issues = find_missing_reciprocals(&notes, &rel_pairs());
+
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0]
+                .kind
+                .to_string()
+                .contains("missing 'related' link back")
+        );
+    }
+
+    #[test]
+    fn find_missing_reciprocals_ignores_unpaired_rels_and_missing_targets() {
+        let id_b: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9B".parse().unwrap();
+        let id_z: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Z".parse().unwrap();
+        let note_a = note_linking(
+            "01HQ3K5M7NXJK4QZPW8V2R6T9A",
+            vec![(id_b, "see-also"), (id_z, "parent")],
+        );
+        let note_b = note_linking("01HQ3K5M7NXJK4QZPW8V2R6T9B", vec![]);
+
+        let notes = vec![
+            (PathBuf::from("a.md"), &note_a),
+            (PathBuf::from("b.md"), &note_b),
+        ];
+        assert!(find_missing_reciprocals(&notes, &rel_pairs()).is_empty());
+    }
+
+    // ===========================================
+    // Broken Wikilinks
+    // ===========================================
+
+    #[test]
+    fn find_broken_wikilinks_reports_missing_and_ambiguous_targets() {
+        let source = test_note("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Source");
+        let first = test_note("01HQ4A2R9PXJK4QZPW8V2R6T9Y", "Design");
+        let second = test_note("01HQ5B3S0QXJK4QZPW8V2R6T9Y", "design");
+        let notes = vec![
+            (PathBuf::from("source.md"), &source),
+            (PathBuf::from("first.md"), &first),
+            (PathBuf::from("second.md"), &second),
+        ];
+        let wikilinks = HashMap::from([(
+            source.id().clone(),
+            vec![
+                "01HQ4A2R".to_string(),
+                "Design".to_string(),
+                "Nowhere".to_string(),
+                "source".to_string(),
+            ],
+        )]);
+
+        let issues = find_broken_wikilinks(&notes, &wikilinks);
+
+        let kinds: Vec<&ValidationKind> = issues.iter().map(|i| &i.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &ValidationKind::BrokenWikilink {
+                    target: "Design".to_string(),
+                    matches: 2
+                },
+                &ValidationKind::BrokenWikilink {
+                    target: "Nowhere".to_string(),
+                    matches: 0
+                },
+            ]
+        );
+        assert!(issues.iter().all(|i| i.path == Path::new("source.md")));
+        assert_eq!(issues[1].severity(), Severity::Warning);
+    }
+
+    // ===========================================
+    // Timestamp anomalies
+    // ===========================================
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn note_with_times(created: &str, modified: &str) -> Note {
+        Note::new(test_note_id(), "Timed", at(created), at(modified)).unwrap()
+    }
+
+    #[test]
+    fn timestamp_anomalies_none_for_consistent_note() {
+        let note = note_with_times("2024-01-15T10:30:00Z", "2024-01-16T10:30:00Z");
+        let path = PathBuf::from("a.md");
+        let mtimes = HashMap::from([(path.clone(), at("2024-01-16T10:30:01Z"))]);
+
+        let issues =
+            find_timestamp_anomalies(&[(path, &note)], &mtimes, at("2024-02-01T00:00:00Z"));
+
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn timestamp_anomalies_detects_created_after_modified() {
+        let note = note_with_times("2024-01-16T10:30:00Z", "2024-01-15T10:30:00Z");
+
+        let issues = find_timestamp_anomalies(
+            &[(PathBuf::from("a.md"), &note)],
+            &HashMap::new(),
+            at("2024-02-01T00:00:00Z"),
+        );
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind.code(), "created-after-modified");
+        assert_eq!(issues[0].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn timestamp_anomalies_detects_future_timestamps_beyond_skew() {
+        let now = at("2024-02-01T00:00:00Z");
+        let note = note_with_times("2024-02-01T00:01:00Z", "2024-03-01T00:00:00Z");
+
+        let issues =
+            find_timestamp_anomalies(&[(PathBuf::from("a.md"), &note)], &HashMap::new(), now);
+
+        // created is within the clock skew tolerance, modified is not
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            issues[0].kind,
+            ValidationKind::FutureTimestamp {
+                field: TimestampField::Modified,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn timestamp_anomalies_detects_external_edit() {
+        let note = note_with_times("2024-01-15T10:30:00Z", "2024-01-15T10:30:00Z");
+        let path = PathBuf::from("a.md");
+        let now = at("2024-02-01T00:00:00Z");
+
+        let recent = HashMap::from([(path.clone(), at("2024-01-15T18:00:00Z"))]);
+        assert!(find_timestamp_anomalies(&[(path.clone(), &note)], &recent, now).is_empty());
+
+        let later = HashMap::from([(path.clone(), at("2024-01-20T09:00:00Z"))]);
+        let issues = find_timestamp_anomalies(&[(path, &note)], &later, now);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind.code(), "external-edit");
+    }
+
+    #[test]
+    fn reconcile_modified_uses_mtime() {
+        let note = note_with_times("2024-01-15T10:30:00Z", "2024-01-15T10:30:00Z");
+        let mtime = at("2024-01-20T09:00:00.750Z");
+
+        let reconciled = reconcile_modified(&note, mtime, at("2024-02-01T00:00:00Z"));
+
+        assert_eq!(reconciled, Some(at("2024-01-20T09:00:00Z")));
+    }
+
+    #[test]
+    fn reconcile_modified_never_precedes_created() {
+        let note = note_with_times("2024-01-16T10:30:00Z", "2024-01-15T10:30:00Z");
+
+        let reconciled = reconcile_modified(
+            &note,
+            at("2024-01-15T12:00:00Z"),
+            at("2024-02-01T00:00:00Z"),
+        );
+
+        assert_eq!(reconciled, Some(at("2024-01-16T10:30:00Z")));
+    }
+
+    #[test]
+    fn reconcile_modified_leaves_consistent_note_alone() {
+        let note = note_with_times("2024-01-15T10:30:00Z", "2024-01-15T10:30:00Z");
+
+        let reconciled = reconcile_modified(
+            &note,
+            at("2024-01-15T10:30:02Z"),
+            at("2024-02-01T00:00:00Z"),
+        );
+
+        assert_eq!(reconciled, None);
+    }
+
+    fn tagged_note(tags: &[&str]) -> Note {
+        Note::builder(test_note_id(), "Tagged", test_datetime(), test_datetime())
+            .tags(tags.iter().map(|t| Tag::new(t).unwrap()).collect())
+            .build()
+            .unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn expired_tags_by_date_and_ttl() {
+        // Created 2024-01-15, so `wip` runs out on 2024-02-14
+        let note = tagged_note(&["review-by:2024-02-01", "wip", "draft", "due:2024-03-01"]);
+        let ttl = BTreeMap::from([("wip".to_string(), 30)]);
+
+        let expired: Vec<(String, NaiveDate)> = expired_tags(&note, &ttl, date("2024-02-15"))
+            .into_iter()
+            .map(|(tag, expired)| (tag.to_string(), expired))
+            .collect();
+        assert_eq!(
+            expired,
+            vec![
+                ("review-by:2024-02-01".to_string(), date("2024-02-01")),
+                ("wip".to_string(), date("2024-02-14")),
+            ]
+        );
+        // Tags last through their expiry date
+        assert!(expired_tags(&note, &ttl, date("2024-02-01")).is_empty());
+
+        // Editing the note doesn't restart the TTL
+        let edited = Note::builder(
+            test_note_id(),
+            "Tagged",
+            test_datetime(),
+            test_datetime() + Duration::days(60),
+        )
+        .tags(vec![Tag::new("wip").unwrap()])
+        .build()
+        .unwrap();
+        assert_eq!(expired_tags(&edited, &ttl, date("2024-02-15")).len(), 1);
+    }
+
+    #[test]
+    fn find_expired_tags_reports_warnings() {
+        let note = tagged_note(&["review-by:2024-02-01"]);
+        let notes = vec![(PathBuf::from("a.md"), &note)];
+
+        let issues = find_expired_tags(&notes, &BTreeMap::new(), date("2024-06-01"));
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity(), Severity::Warning);
+        assert_eq!(
+            issues[0].kind.to_string(),
+            "tag 'review-by:2024-02-01' expired on 2024-02-01"
+        );
+    }
+
+    fn typed_note(yaml_extra: &str) -> Note {
+        let yaml = format!(
+            "id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: Typed\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n{}",
+            yaml_extra
+        );
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+
+    #[test]
+    fn type_issues_report_missing_fields_and_unknown_types() {
+        let person = typed_note("type: person\nemail: ada@example.com\n");
+        let incomplete = typed_note("type: person\n");
+        let unknown = typed_note("type: recipe\n");
+        let untyped = typed_note("");
+        let notes = vec![
+            (PathBuf::from("person.md"), &person),
+            (PathBuf::from("incomplete.md"), &incomplete),
+            (PathBuf::from("unknown.md"), &unknown),
+            (PathBuf::from("untyped.md"), &untyped),
+        ];
+        let types = BTreeMap::from([(
+            "person".to_string(),
+            vec!["email".to_string(), "tags".to_string()],
+        )]);
+
+        let issues = find_type_issues(&notes, &types);
+
+        let found: Vec<(&str, String)> = issues
+            .iter()
+            .map(|i| (i.path.to_str().unwrap(), i.kind.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "person.md",
+                    "missing field 'tags' required by type 'person'".to_string()
+                ),
+                (
+                    "incomplete.md",
+                    "missing field 'email' required by type 'person'".to_string()
+                ),
+                (
+                    "incomplete.md",
+                    "missing field 'tags' required by type 'person'".to_string()
+                ),
+                ("unknown.md", "unknown note type 'recipe'".to_string()),
+            ]
+        );
+        assert_eq!(issues[0].severity(), Severity::Error);
+        assert_eq!(issues[3].severity(), Severity::Warning);
+    }
 }
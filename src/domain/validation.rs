@@ -1,11 +1,16 @@
 //! Validation error types for the check command.
 //!
 //! These types represent issues found during validation of the notes collection,
-//! such as parse errors, duplicate IDs, broken links, and orphaned notes.
+//! such as parse errors, duplicate IDs, broken links, missing reciprocal
+//! links, orphaned notes, timestamp anomalies, notes missing fields
+//! their type requires, expired tags, markdown syntax mistakes in note bodies, notes
+//! large enough to be worth splitting, and index rows that have drifted from
+//! the files they were read from.
 
+use chrono::{DateTime, NaiveDate, Utc};
 use std::path::PathBuf;
 
-use crate::domain::NoteId;
+use crate::domain::{NoteId, Rel, SizeMeasure, Tag};
 use crate::infra::ParseError;
 
 /// A validation issue found during checking.
@@ -13,6 +18,8 @@ use crate::infra::ParseError;
 pub struct ValidationIssue {
     /// The file where the issue was found.
     pub path: PathBuf,
+    /// The 1-based line of the file, for issues in the note body.
+    pub line: Option<usize>,
     /// The kind of validation issue.
     pub kind: ValidationKind,
 }
@@ -22,10 +29,17 @@ impl ValidationIssue {
     pub fn new(path: impl Into<PathBuf>, kind: ValidationKind) -> Self {
         Self {
             path: path.into(),
+            line: None,
             kind,
         }
     }
 
+    /// Sets the line of the file the issue is on.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
     /// Creates a parse error issue.
     pub fn parse_error(path: impl Into<PathBuf>, error: ParseError) -> Self {
         Self::new(path, ValidationKind::ParseError(error.to_string()))
@@ -51,11 +65,103 @@ impl ValidationIssue {
         Self::new(path, ValidationKind::BrokenLink { target_id })
     }
 
+    /// Creates a trashed link issue (target exists only in the trash).
+    pub fn trashed_link(path: impl Into<PathBuf>, target_id: NoteId) -> Self {
+        Self::new(path, ValidationKind::TrashedLink { target_id })
+    }
+
+    /// Creates a missing reciprocal link issue.
+    ///
+    /// `path` is the note that should link back to `source_id` with rel
+    /// `expected`, because `source_id` links to it with rel `rel`.
+    pub fn missing_reciprocal(
+        path: impl Into<PathBuf>,
+        source_id: NoteId,
+        rel: Rel,
+        expected: Rel,
+    ) -> Self {
+        Self::new(
+            path,
+            ValidationKind::MissingReciprocal {
+                source_id,
+                rel,
+                expected,
+            },
+        )
+    }
+
     /// Creates an orphaned note issue (note has no topics).
     pub fn orphaned(path: impl Into<PathBuf>) -> Self {
         Self::new(path, ValidationKind::Orphaned)
     }
 
+    /// Creates an issue for a note without topics that must have one,
+    /// naming the type that requires them, if any.
+    pub fn missing_topics(path: impl Into<PathBuf>, note_type: Option<&str>) -> Self {
+        Self::new(
+            path,
+            ValidationKind::MissingTopics {
+                note_type: note_type.map(String::from),
+            },
+        )
+    }
+
+    /// Creates an issue for a note created after it was last modified.
+    pub fn created_after_modified(
+        path: impl Into<PathBuf>,
+        created: DateTime<Utc>,
+        modified: DateTime<Utc>,
+    ) -> Self {
+        Self::new(
+            path,
+            ValidationKind::CreatedAfterModified { created, modified },
+        )
+    }
+
+    /// Creates an issue for a frontmatter timestamp in the future.
+    pub fn future_timestamp(
+        path: impl Into<PathBuf>,
+        field: TimestampField,
+        value: DateTime<Utc>,
+    ) -> Self {
+        Self::new(path, ValidationKind::FutureTimestamp { field, value })
+    }
+
+    /// Creates an issue for a file changed long after its frontmatter
+    /// `modified` time.
+    pub fn external_edit(
+        path: impl Into<PathBuf>,
+        modified: DateTime<Utc>,
+        mtime: DateTime<Utc>,
+    ) -> Self {
+        Self::new(path, ValidationKind::ExternalEdit { modified, mtime })
+    }
+
+    /// Creates an issue for a note whose type is not configured.
+    pub fn unknown_type(path: impl Into<PathBuf>, note_type: impl Into<String>) -> Self {
+        Self::new(
+            path,
+            ValidationKind::UnknownType {
+                note_type: note_type.into(),
+            },
+        )
+    }
+
+    /// Creates an issue for a note missing a field its type requires.
+    pub fn missing_field(
+        path: impl Into<PathBuf>,
+        note_type: impl Into<String>,
+        field: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            path,
+            ValidationKind::MissingField {
+                note_type: note_type.into(),
+                field: field.into(),
+            },
+        )
+    }
+
     /// Returns true if this is a parse error.
     pub fn is_parse_error(&self) -> bool {
         matches!(self.kind, ValidationKind::ParseError(_))
@@ -71,11 +177,42 @@ impl ValidationIssue {
         matches!(self.kind, ValidationKind::BrokenLink { .. })
     }
 
+    /// Returns true if this is a link to a trashed note.
+    pub fn is_trashed_link(&self) -> bool {
+        matches!(self.kind, ValidationKind::TrashedLink { .. })
+    }
+
+    /// Returns true if this is a missing reciprocal link warning.
+    pub fn is_missing_reciprocal(&self) -> bool {
+        matches!(self.kind, ValidationKind::MissingReciprocal { .. })
+    }
+
     /// Returns true if this is an orphaned note warning.
     pub fn is_orphaned(&self) -> bool {
         matches!(self.kind, ValidationKind::Orphaned)
     }
 
+    /// Returns true if this is a timestamp anomaly warning.
+    pub fn is_timestamp_anomaly(&self) -> bool {
+        matches!(
+            self.kind,
+            ValidationKind::CreatedAfterModified { .. }
+                | ValidationKind::FutureTimestamp { .. }
+                | ValidationKind::ExternalEdit { .. }
+        )
+    }
+
+    /// Returns true if this reports a difference between a file and the index.
+    pub fn is_index_drift(&self) -> bool {
+        matches!(
+            self.kind,
+            ValidationKind::NotIndexed
+                | ValidationKind::StaleIndex { .. }
+                | ValidationKind::IndexMismatch { .. }
+                | ValidationKind::OrphanIndexRow { .. }
+        )
+    }
+
     /// Returns the severity of this issue.
     pub fn severity(&self) -> Severity {
         self.kind.severity()
@@ -84,7 +221,10 @@ impl ValidationIssue {
 
 impl std::fmt::Display for ValidationIssue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.path.display(), self.kind)
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.kind),
+            None => write!(f, "{}: {}", self.path.display(), self.kind),
+        }
     }
 }
 
@@ -108,8 +248,182 @@ pub enum ValidationKind {
         target_id: NoteId,
     },
 
+    /// A link references a note that has been moved to the trash.
+    TrashedLink {
+        /// The ID of the trashed note.
+        target_id: NoteId,
+    },
+
+    /// Another note links here with one side of a reciprocal rel pair, but
+    /// this note does not link back with the other side.
+    MissingReciprocal {
+        /// The note that links here.
+        source_id: NoteId,
+        /// The rel of the existing link.
+        rel: Rel,
+        /// The rel this note should link back with.
+        expected: Rel,
+    },
+
+    /// A `[[wikilink]]` in the body matches no note, or more than one.
+    BrokenWikilink {
+        /// The target as written.
+        target: String,
+        /// How many notes it matches.
+        matches: usize,
+    },
+
     /// Note has no topics (orphaned in the virtual folder hierarchy).
     Orphaned,
+
+    /// Note has no topics, but the config requires notes like it to have one.
+    MissingTopics {
+        /// The note's type, when the requirement comes from it.
+        note_type: Option<String>,
+    },
+
+    /// The frontmatter `created` time is later than `modified`.
+    CreatedAfterModified {
+        created: DateTime<Utc>,
+        modified: DateTime<Utc>,
+    },
+
+    /// A frontmatter timestamp lies in the future.
+    FutureTimestamp {
+        /// Which timestamp.
+        field: TimestampField,
+        value: DateTime<Utc>,
+    },
+
+    /// The file was changed long after its frontmatter `modified` time,
+    /// suggesting it was edited outside den.
+    ExternalEdit {
+        /// The frontmatter `modified` time.
+        modified: DateTime<Utc>,
+        /// The file's modification time.
+        mtime: DateTime<Utc>,
+    },
+
+    /// The note's `type` is not one of the configured note types.
+    UnknownType { note_type: String },
+
+    /// The note lacks a frontmatter field its type requires.
+    MissingField {
+        note_type: String,
+        /// The required frontmatter key.
+        field: String,
+    },
+
+    /// A tag's expiry date, or its configured TTL, has passed.
+    ExpiredTag {
+        tag: Tag,
+        /// The day the tag expired.
+        expired: NaiveDate,
+    },
+
+    /// A fenced code block in the body is never closed, so the rest of the
+    /// note renders as code.
+    UnclosedFence {
+        /// The opening fence, e.g. "```".
+        fence: String,
+    },
+
+    /// Lines starting with `|` that don't form a table, because the row
+    /// separating the header is missing or has the wrong number of columns.
+    MissingTableSeparator,
+
+    /// A table row has a different number of cells than the header.
+    TableCellCount { expected: usize, found: usize },
+
+    /// A reference-style link uses a label with no definition.
+    UndefinedReference { label: String },
+
+    /// The note has grown past a size limit and may be better split.
+    LargeNote {
+        measure: SizeMeasure,
+        count: usize,
+        limit: usize,
+    },
+
+    /// The file has never been indexed.
+    NotIndexed,
+
+    /// The file changed since it was indexed, so the index is out of date.
+    StaleIndex {
+        /// The fields the index has old values for.
+        fields: Vec<IndexField>,
+    },
+
+    /// The index differs from a file that hasn't changed since it was
+    /// indexed, which means the index itself is damaged.
+    IndexMismatch {
+        /// The fields that differ.
+        fields: Vec<IndexField>,
+    },
+
+    /// The index has a note at this path, but the file is gone.
+    OrphanIndexRow {
+        /// The ID the index has for the missing file.
+        id: NoteId,
+    },
+}
+
+/// A note field kept in the index, for reporting index drift.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IndexField {
+    Id,
+    Title,
+    Description,
+    Type,
+    Created,
+    Modified,
+    Aliases,
+    Topics,
+    Tags,
+    Links,
+}
+
+impl std::fmt::Display for IndexField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IndexField::Id => "id",
+            IndexField::Title => "title",
+            IndexField::Description => "description",
+            IndexField::Type => "type",
+            IndexField::Created => "created",
+            IndexField::Modified => "modified",
+            IndexField::Aliases => "aliases",
+            IndexField::Topics => "topics",
+            IndexField::Tags => "tags",
+            IndexField::Links => "links",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Joins index fields for a message, e.g. "title, tags".
+fn join_fields(fields: &[IndexField]) -> String {
+    fields
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A frontmatter timestamp field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampField {
+    Created,
+    Modified,
+}
+
+impl std::fmt::Display for TimestampField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimestampField::Created => write!(f, "created"),
+            TimestampField::Modified => write!(f, "modified"),
+        }
+    }
 }
 
 impl ValidationKind {
@@ -119,7 +433,56 @@ impl ValidationKind {
             ValidationKind::ParseError(_) => Severity::Error,
             ValidationKind::DuplicateId { .. } => Severity::Error,
             ValidationKind::BrokenLink { .. } => Severity::Error,
+            ValidationKind::TrashedLink { .. } => Severity::Warning,
+            ValidationKind::MissingReciprocal { .. } => Severity::Warning,
+            ValidationKind::BrokenWikilink { .. } => Severity::Warning,
             ValidationKind::Orphaned => Severity::Warning,
+            ValidationKind::MissingTopics { .. } => Severity::Error,
+            ValidationKind::CreatedAfterModified { .. } => Severity::Warning,
+            ValidationKind::FutureTimestamp { .. } => Severity::Warning,
+            ValidationKind::ExternalEdit { .. } => Severity::Warning,
+            ValidationKind::UnknownType { .. } => Severity::Warning,
+            ValidationKind::MissingField { .. } => Severity::Error,
+            ValidationKind::ExpiredTag { .. } => Severity::Warning,
+            ValidationKind::UnclosedFence { .. } => Severity::Warning,
+            ValidationKind::MissingTableSeparator => Severity::Warning,
+            ValidationKind::TableCellCount { .. } => Severity::Warning,
+            ValidationKind::UndefinedReference { .. } => Severity::Warning,
+            ValidationKind::LargeNote { .. } => Severity::Warning,
+            ValidationKind::NotIndexed => Severity::Warning,
+            ValidationKind::StaleIndex { .. } => Severity::Warning,
+            ValidationKind::IndexMismatch { .. } => Severity::Error,
+            ValidationKind::OrphanIndexRow { .. } => Severity::Warning,
+        }
+    }
+
+    /// Returns a stable, kebab-case identifier for this kind of issue.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationKind::ParseError(_) => "parse-error",
+            ValidationKind::DuplicateId { .. } => "duplicate-id",
+            ValidationKind::BrokenLink { .. } => "broken-link",
+            ValidationKind::TrashedLink { .. } => "trashed-link",
+            ValidationKind::MissingReciprocal { .. } => "missing-reciprocal",
+            ValidationKind::BrokenWikilink { .. } => "broken-wikilink",
+            ValidationKind::Orphaned => "orphaned",
+            ValidationKind::MissingTopics { .. } => "missing-topics",
+            ValidationKind::CreatedAfterModified { .. } => "created-after-modified",
+            ValidationKind::FutureTimestamp { .. } => "future-timestamp",
+            ValidationKind::ExternalEdit { .. } => "external-edit",
+            ValidationKind::UnknownType { .. } => "unknown-type",
+            ValidationKind::MissingField { .. } => "missing-field",
+            ValidationKind::ExpiredTag { .. } => "expired-tag",
+            ValidationKind::UnclosedFence { .. } => "unclosed-fence",
+            ValidationKind::MissingTableSeparator | ValidationKind::TableCellCount { .. } => {
+                "malformed-table"
+            }
+            ValidationKind::UndefinedReference { .. } => "undefined-reference",
+            ValidationKind::LargeNote { .. } => "large-note",
+            ValidationKind::NotIndexed => "not-indexed",
+            ValidationKind::StaleIndex { .. } => "stale-index",
+            ValidationKind::IndexMismatch { .. } => "index-mismatch",
+            ValidationKind::OrphanIndexRow { .. } => "orphan-index-row",
         }
     }
 }
@@ -139,7 +502,131 @@ impl std::fmt::Display for ValidationKind {
             ValidationKind::BrokenLink { target_id } => {
                 write!(f, "broken link to '{}'", target_id.prefix())
             }
+            ValidationKind::TrashedLink { target_id } => {
+                write!(f, "link to trashed note '{}'", target_id.prefix())
+            }
+            ValidationKind::MissingReciprocal {
+                source_id,
+                rel,
+                expected,
+            } => {
+                write!(
+                    f,
+                    "missing '{}' link back to '{}' (which links here as '{}')",
+                    expected,
+                    source_id.prefix(),
+                    rel
+                )
+            }
+            ValidationKind::BrokenWikilink { target, matches: 0 } => {
+                write!(f, "wikilink '[[{}]]' matches no note", target)
+            }
+            ValidationKind::BrokenWikilink { target, matches } => {
+                write!(
+                    f,
+                    "wikilink '[[{}]]' matches {} notes; link by ID instead",
+                    target, matches
+                )
+            }
             ValidationKind::Orphaned => write!(f, "orphaned note (no topics)"),
+            ValidationKind::MissingTopics { note_type: None } => {
+                write!(f, "no topics, but topics are required")
+            }
+            ValidationKind::MissingTopics {
+                note_type: Some(note_type),
+            } => write!(f, "no topics, but type '{}' requires one", note_type),
+            ValidationKind::CreatedAfterModified { created, modified } => {
+                write!(
+                    f,
+                    "created ({}) is after modified ({})",
+                    created.to_rfc3339(),
+                    modified.to_rfc3339()
+                )
+            }
+            ValidationKind::FutureTimestamp { field, value } => {
+                write!(
+                    f,
+                    "{} time is in the future ({})",
+                    field,
+                    value.to_rfc3339()
+                )
+            }
+            ValidationKind::ExternalEdit { modified, mtime } => {
+                write!(
+                    f,
+                    "file changed at {} but frontmatter says modified {} (edited outside den?)",
+                    mtime.to_rfc3339(),
+                    modified.to_rfc3339()
+                )
+            }
+            ValidationKind::UnknownType { note_type } => {
+                write!(f, "unknown note type '{}'", note_type)
+            }
+            ValidationKind::MissingField { note_type, field } => {
+                write!(
+                    f,
+                    "missing field '{}' required by type '{}'",
+                    field, note_type
+                )
+            }
+            ValidationKind::ExpiredTag { tag, expired } => {
+                write!(f, "tag '{}' expired on {}", tag, expired)
+            }
+            ValidationKind::UnclosedFence { fence } => {
+                write!(f, "code fence '{}' is never closed", fence)
+            }
+            ValidationKind::MissingTableSeparator => {
+                write!(
+                    f,
+                    "malformed table: no separator row matching the header (e.g. | --- | --- |)"
+                )
+            }
+            ValidationKind::TableCellCount { expected, found } => {
+                write!(
+                    f,
+                    "malformed table: row has {} cell(s), header has {}",
+                    found, expected
+                )
+            }
+            ValidationKind::UndefinedReference { label } => {
+                write!(f, "reference link to undefined label '[{}]'", label)
+            }
+            ValidationKind::LargeNote {
+                measure,
+                count,
+                limit,
+            } => {
+                write!(
+                    f,
+                    "note has {} {} (limit {}); consider splitting it",
+                    count, measure, limit
+                )
+            }
+            ValidationKind::NotIndexed => write!(f, "file is not in the index"),
+            ValidationKind::StaleIndex { fields } if fields.is_empty() => {
+                write!(f, "file changed since it was indexed")
+            }
+            ValidationKind::StaleIndex { fields } => {
+                write!(
+                    f,
+                    "file changed since it was indexed (index has old {})",
+                    join_fields(fields)
+                )
+            }
+            ValidationKind::IndexMismatch { fields } => {
+                write!(
+                    f,
+                    "index differs from the unchanged file in {} (index corruption?)",
+                    join_fields(fields)
+                )
+            }
+            ValidationKind::OrphanIndexRow { id } => {
+                write!(
+                    f,
+                    "index has note '{}' here, but the file is gone",
+                    id.prefix()
+                )
+            }
         }
     }
 }
@@ -233,10 +720,20 @@ impl ValidationSummary {
         self.issues.iter().filter(|i| i.is_broken_link())
     }
 
+    /// Returns all missing reciprocal link warnings.
+    pub fn missing_reciprocals(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.is_missing_reciprocal())
+    }
+
     /// Returns all orphaned note warnings.
     pub fn orphaned_notes(&self) -> impl Iterator<Item = &ValidationIssue> {
         self.issues.iter().filter(|i| i.is_orphaned())
     }
+
+    /// Returns all timestamp anomaly warnings.
+    pub fn timestamp_anomalies(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.is_timestamp_anomaly())
+    }
 }
 
 #[cfg(test)]
@@ -299,6 +796,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn creates_trashed_link_issue() {
+        let issue = ValidationIssue::trashed_link("notes/test.md", other_note_id());
+
+        assert!(issue.is_trashed_link());
+        assert!(!issue.is_broken_link());
+        assert_eq!(issue.severity(), Severity::Warning);
+        assert_eq!(issue.kind.code(), "trashed-link");
+    }
+
     #[test]
     fn creates_orphaned_issue() {
         let issue = ValidationIssue::orphaned("notes/lonely.md");
@@ -308,6 +815,38 @@ mod tests {
         assert_eq!(issue.severity(), Severity::Warning);
     }
 
+    #[test]
+    fn creates_timestamp_anomaly_issues() {
+        let earlier = "2024-01-15T10:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let later = "2024-03-01T08:00:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let issues = [
+            ValidationIssue::created_after_modified("a.md", later, earlier),
+            ValidationIssue::future_timestamp("b.md", TimestampField::Modified, later),
+            ValidationIssue::external_edit("c.md", earlier, later),
+        ];
+
+        let codes: Vec<&str> = issues.iter().map(|i| i.kind.code()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "created-after-modified",
+                "future-timestamp",
+                "external-edit"
+            ]
+        );
+        for issue in &issues {
+            assert!(issue.is_timestamp_anomaly());
+            assert_eq!(issue.severity(), Severity::Warning);
+        }
+        assert!(
+            issues[1]
+                .to_string()
+                .contains("modified time is in the future")
+        );
+        assert!(issues[2].to_string().contains("edited outside den"));
+    }
+
     // ===========================================
     // Display formatting
     // ===========================================
@@ -347,6 +886,15 @@ mod tests {
         assert!(display.contains("01HQ4A2R9P")); // prefix (10-char)
     }
 
+    #[test]
+    fn formats_trashed_link() {
+        let issue = ValidationIssue::trashed_link("notes/test.md", other_note_id());
+
+        let display = issue.to_string();
+        assert!(display.contains("link to trashed note"));
+        assert!(display.contains("01HQ4A2R9P"));
+    }
+
     #[test]
     fn formats_orphaned() {
         let issue = ValidationIssue::orphaned("notes/lonely.md");
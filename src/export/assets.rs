@@ -0,0 +1,308 @@
+//! Copying local images into exports.
+//!
+//! Notes reference images by paths relative to the note file (or to the vault
+//! root with a leading `/`), which break once a page is written elsewhere.
+//! Exports copy each referenced image into an `assets/` directory next to the
+//! pages and point the references there.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use regex::{Captures, Regex};
+
+/// Directory, relative to the output directory, that images are copied into.
+pub const ASSETS_DIR: &str = "assets";
+
+/// How images are copied into an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssetOptions {
+    /// Scale PNG and JPEG images wider than this many pixels down to it,
+    /// keeping their aspect ratio.
+    pub max_width: Option<u32>,
+}
+
+/// Copies the images notes reference into an export and rewrites the
+/// references to the copies.
+///
+/// Each image is copied once, however many notes reference it. Images with
+/// the same file name from different directories get distinct names.
+pub struct AssetCopier<'a> {
+    notes_dir: PathBuf,
+    output_dir: &'a Path,
+    options: AssetOptions,
+    /// Copied images by canonical source path, with their file name in the
+    /// assets directory.
+    copied: HashMap<PathBuf, String>,
+    names: HashSet<String>,
+}
+
+impl<'a> AssetCopier<'a> {
+    /// Creates a copier for images in `notes_dir`, writing to `output_dir`.
+    pub fn new(notes_dir: &Path, output_dir: &'a Path, options: AssetOptions) -> Self {
+        Self {
+            notes_dir: notes_dir
+                .canonicalize()
+                .unwrap_or_else(|_| notes_dir.to_path_buf()),
+            output_dir,
+            options,
+            copied: HashMap::new(),
+            names: HashSet::new(),
+        }
+    }
+
+    /// Number of images copied so far.
+    pub fn copied(&self) -> usize {
+        self.copied.len()
+    }
+
+    /// Copies the images `body` references and returns it with the references
+    /// rewritten, prefixed by `base_path` (e.g. "../" for pages in a
+    /// subdirectory).
+    ///
+    /// `note_path` is the note's path relative to the notes directory.
+    /// External, missing and out-of-vault images are left as they are.
+    pub fn rewrite(&mut self, body: &str, note_path: &Path, base_path: &str) -> Result<String> {
+        let note_dir = note_path.parent().unwrap_or(Path::new(""));
+        let mut error = None;
+        let mut rewrite = |caps: &Captures| {
+            let src = &caps[2];
+            let rewritten = match self.copy(src, note_dir) {
+                Ok(Some(name)) => format!("{}{}/{}", base_path, ASSETS_DIR, name),
+                Ok(None) => src.to_string(),
+                Err(e) => {
+                    error.get_or_insert(e);
+                    src.to_string()
+                }
+            };
+            format!("{}{}", &caps[1], rewritten)
+        };
+
+        let markdown_re = Regex::new(r"(!\[[^\]]*\]\(\s*<?)([^)\s>]+)").unwrap();
+        let html_re = Regex::new(r#"(<img\b[^>]*?\bsrc\s*=\s*["'])([^"']+)"#).unwrap();
+        let body = markdown_re.replace_all(body, &mut rewrite);
+        let body = html_re.replace_all(&body, &mut rewrite).into_owned();
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(body),
+        }
+    }
+
+    /// Copies the image at `src` if it is a local file in the vault,
+    /// returning its file name in the assets directory.
+    fn copy(&mut self, src: &str, note_dir: &Path) -> Result<Option<String>> {
+        let Some(source) = self.resolve(src, note_dir) else {
+            return Ok(None);
+        };
+        if let Some(name) = self.copied.get(&source) {
+            return Ok(Some(name.clone()));
+        }
+
+        let name = self.unique_name(&source);
+        let assets_dir = self.output_dir.join(ASSETS_DIR);
+        std::fs::create_dir_all(&assets_dir)?;
+        let dest = assets_dir.join(&name);
+        if !self.resize(&source, &dest)? {
+            std::fs::copy(&source, &dest)
+                .with_context(|| format!("failed to copy image {}", source.display()))?;
+        }
+
+        self.names.insert(name.clone());
+        self.copied.insert(source, name.clone());
+        Ok(Some(name))
+    }
+
+    /// Returns the canonical path of the local image `src` refers to, if it
+    /// exists inside the notes directory.
+    fn resolve(&self, src: &str, note_dir: &Path) -> Option<PathBuf> {
+        if is_remote(src) {
+            return None;
+        }
+        let src = src.split(['?', '#']).next().unwrap_or(src);
+        let src = percent_decode(src);
+        let path = match src.strip_prefix('/') {
+            Some(rooted) => self.notes_dir.join(rooted),
+            None => self.notes_dir.join(note_dir).join(&src),
+        };
+        path.canonicalize()
+            .ok()
+            .filter(|p| p.is_file() && p.starts_with(&self.notes_dir))
+    }
+
+    /// Picks a file name for `source` not yet used in the assets directory.
+    fn unique_name(&self, source: &Path) -> String {
+        let stem = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "image".to_string());
+        let extension = source
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+
+        let mut name = format!("{}{}", stem, extension);
+        let mut n = 2;
+        while self.names.contains(&name) {
+            name = format!("{}-{}{}", stem, n, extension);
+            n += 1;
+        }
+        name
+    }
+
+    /// Writes a scaled-down copy of `source` to `dest` if it is a PNG or JPEG
+    /// wider than the maximum width, returning false if it should be copied
+    /// as is.
+    fn resize(&self, source: &Path, dest: &Path) -> Result<bool> {
+        let Some(max_width) = self.options.max_width else {
+            return Ok(false);
+        };
+        let resizable = source
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| matches!(e.to_lowercase().as_str(), "png" | "jpg" | "jpeg"));
+        if !resizable {
+            return Ok(false);
+        }
+        // Images that can't be decoded are copied untouched
+        let Ok(image) = image::open(source) else {
+            return Ok(false);
+        };
+        if image.width() <= max_width {
+            return Ok(false);
+        }
+        image
+            .resize(max_width, u32::MAX, FilterType::Lanczos3)
+            .save(dest)
+            .with_context(|| format!("failed to resize image {}", source.display()))?;
+        Ok(true)
+    }
+}
+
+/// Checks if an image source is a URL rather than a local path.
+fn is_remote(src: &str) -> bool {
+    let lower = src.to_lowercase();
+    lower.starts_with("//")
+        || lower.starts_with("data:")
+        || lower.starts_with('#')
+        || lower
+            .split_once(':')
+            .is_some_and(|(scheme, _)| scheme.len() > 1 && !scheme.contains('/'))
+}
+
+/// Decodes `%XX` escapes, as in `my%20image.png`.
+fn percent_decode(src: &str) -> String {
+    let bytes = src.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = src
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+            continue;
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| src.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vault() -> (TempDir, TempDir) {
+        let notes = TempDir::new().unwrap();
+        std::fs::create_dir_all(notes.path().join("img")).unwrap();
+        std::fs::create_dir_all(notes.path().join("other")).unwrap();
+        std::fs::write(notes.path().join("img/diagram.svg"), "<svg/>").unwrap();
+        std::fs::write(notes.path().join("other/diagram.svg"), "<svg></svg>").unwrap();
+        std::fs::write(notes.path().join("my photo.svg"), "<svg/>").unwrap();
+        (notes, TempDir::new().unwrap())
+    }
+
+    #[test]
+    fn rewrites_markdown_and_html_images() {
+        let (notes, out) = vault();
+        let mut copier = AssetCopier::new(notes.path(), out.path(), AssetOptions::default());
+
+        let body = "![Diagram](img/diagram.svg \"Title\")\n\
+                    <img alt=\"x\" src=\"/other/diagram.svg\">\n\
+                    ![Photo](my%20photo.svg)\n\
+                    ![Again](./img/diagram.svg)";
+        let rewritten = copier.rewrite(body, Path::new("note.md"), "../").unwrap();
+
+        assert_eq!(
+            rewritten,
+            "![Diagram](../assets/diagram.svg \"Title\")\n\
+             <img alt=\"x\" src=\"../assets/diagram-2.svg\">\n\
+             ![Photo](../assets/my photo.svg)\n\
+             ![Again](../assets/diagram.svg)"
+        );
+        assert_eq!(copier.copied(), 3);
+        assert_eq!(
+            std::fs::read_to_string(out.path().join("assets/diagram-2.svg")).unwrap(),
+            "<svg></svg>"
+        );
+    }
+
+    #[test]
+    fn leaves_remote_missing_and_outside_images() {
+        let (notes, out) = vault();
+        let mut copier = AssetCopier::new(notes.path(), out.path(), AssetOptions::default());
+
+        let body = "![a](https://example.com/a.png) ![b](data:image/png;base64,AAAA) \
+                    ![c](missing.png) ![d](../../etc/passwd) [link](img/diagram.svg)";
+        let rewritten = copier.rewrite(body, Path::new("note.md"), "").unwrap();
+
+        assert_eq!(rewritten, body);
+        assert_eq!(copier.copied(), 0);
+        assert!(!out.path().join(ASSETS_DIR).exists());
+    }
+
+    #[test]
+    fn resolves_images_relative_to_the_note() {
+        let (notes, out) = vault();
+        let mut copier = AssetCopier::new(notes.path(), out.path(), AssetOptions::default());
+
+        let rewritten = copier
+            .rewrite("![d](diagram.svg)", Path::new("other/note.md"), "")
+            .unwrap();
+
+        assert_eq!(rewritten, "![d](assets/diagram.svg)");
+        assert_eq!(
+            std::fs::read_to_string(out.path().join("assets/diagram.svg")).unwrap(),
+            "<svg></svg>"
+        );
+    }
+
+    #[test]
+    fn scales_down_wide_images() {
+        let (notes, out) = vault();
+        image::RgbImage::new(400, 200)
+            .save(notes.path().join("wide.png"))
+            .unwrap();
+        image::RgbImage::new(50, 50)
+            .save(notes.path().join("small.png"))
+            .unwrap();
+        let options = AssetOptions {
+            max_width: Some(100),
+        };
+        let mut copier = AssetCopier::new(notes.path(), out.path(), options);
+
+        copier
+            .rewrite("![w](wide.png) ![s](small.png)", Path::new("note.md"), "")
+            .unwrap();
+
+        let wide = image::open(out.path().join("assets/wide.png")).unwrap();
+        assert_eq!((wide.width(), wide.height()), (100, 50));
+        let small = image::open(out.path().join("assets/small.png")).unwrap();
+        assert_eq!((small.width(), small.height()), (50, 50));
+    }
+}
@@ -0,0 +1,210 @@
+//! Metadata bundles: the structure of a vault without its contents.
+//!
+//! A bundle holds the titles, types, topics, tags and links of notes, but no
+//! bodies, descriptions or link context, so the taxonomy of a vault can be
+//! shared for review without exposing what the notes say. It's encrypted
+//! with a passphrase (see [`seal_bundle`]).
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{Link, NoteId};
+use crate::index::IndexedNote;
+use crate::infra::{open_bundle, seal_bundle};
+
+/// Version of the bundle contents, bumped when fields change incompatibly.
+const BUNDLE_CONTENT_VERSION: u32 = 1;
+
+/// A link between two notes in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleLink {
+    pub target: String,
+    pub rels: Vec<String>,
+}
+
+/// A note's metadata in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleNote {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub note_type: Option<String>,
+    pub topics: Vec<String>,
+    pub tags: Vec<String>,
+    pub links: Vec<BundleLink>,
+}
+
+/// The metadata of a set of notes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataBundle {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    /// Notes sorted by title.
+    pub notes: Vec<BundleNote>,
+}
+
+impl MetadataBundle {
+    /// Collects the metadata of `notes`, with their links from `links`.
+    ///
+    /// Links to notes that aren't in the bundle are left out, so excluded
+    /// notes can't be inferred from them.
+    pub fn new(
+        notes: &[IndexedNote],
+        links: &HashMap<NoteId, Vec<Link>>,
+        created: DateTime<Utc>,
+    ) -> Self {
+        let included: HashSet<&NoteId> = notes.iter().map(|n| n.id()).collect();
+        let mut bundle_notes: Vec<BundleNote> = notes
+            .iter()
+            .map(|note| {
+                let mut note_links: Vec<BundleLink> = links
+                    .get(note.id())
+                    .into_iter()
+                    .flatten()
+                    .filter(|link| included.contains(link.target()))
+                    .map(|link| BundleLink {
+                        target: link.target().to_string(),
+                        rels: link.rel().iter().map(|r| r.as_str().to_string()).collect(),
+                    })
+                    .collect();
+                note_links.sort_by(|a, b| a.target.cmp(&b.target));
+                BundleNote {
+                    id: note.id().to_string(),
+                    title: note.title().to_string(),
+                    note_type: note.note_type().map(String::from),
+                    topics: note.topics().iter().map(|t| t.to_string()).collect(),
+                    tags: note.tags().iter().map(|t| t.as_str().to_string()).collect(),
+                    links: note_links,
+                }
+            })
+            .collect();
+        bundle_notes.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.id.cmp(&b.id)));
+
+        Self {
+            version: BUNDLE_CONTENT_VERSION,
+            created,
+            notes: bundle_notes,
+        }
+    }
+
+    /// Number of distinct topics.
+    pub fn topic_count(&self) -> usize {
+        let topics: HashSet<&str> = self
+            .notes
+            .iter()
+            .flat_map(|n| n.topics.iter().map(String::as_str))
+            .collect();
+        topics.len()
+    }
+
+    /// Number of distinct tags.
+    pub fn tag_count(&self) -> usize {
+        let tags: HashSet<&str> = self
+            .notes
+            .iter()
+            .flat_map(|n| n.tags.iter().map(String::as_str))
+            .collect();
+        tags.len()
+    }
+
+    /// Number of links between notes.
+    pub fn link_count(&self) -> usize {
+        self.notes.iter().map(|n| n.links.len()).sum()
+    }
+
+    /// Serializes and encrypts the bundle.
+    pub fn seal(&self, passphrase: &str) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(self).with_context(|| "failed to serialize bundle")?;
+        Ok(seal_bundle(&json, passphrase)?)
+    }
+
+    /// Decrypts and parses a bundle written by [`MetadataBundle::seal`].
+    pub fn open(bytes: &[u8], passphrase: &str) -> Result<Self> {
+        let json = open_bundle(bytes, passphrase)?;
+        let bundle: Self =
+            serde_json::from_slice(&json).with_context(|| "bundle contents are invalid")?;
+        if bundle.version > BUNDLE_CONTENT_VERSION {
+            anyhow::bail!(
+                "bundle was created by a newer version of den (content version {})",
+                bundle.version
+            );
+        }
+        Ok(bundle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Tag, Topic};
+    use crate::infra::ContentHash;
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    fn note(id: &str, title: &str, topics: &[&str], tags: &[&str]) -> IndexedNote {
+        let time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        IndexedNote::builder(
+            id.parse().unwrap(),
+            title,
+            time,
+            time,
+            PathBuf::from(format!("{}.md", title)),
+            ContentHash::compute(title.as_bytes()),
+        )
+        .topics(topics.iter().map(|t| Topic::new(t).unwrap()).collect())
+        .tags(tags.iter().map(|t| Tag::new(t).unwrap()).collect())
+        .build()
+    }
+
+    const API: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+    const REST: &str = "01HQ4A2R9PXJK4QZPW8V2R6T9Z";
+    const DIARY: &str = "01HQ5B3S1QXJK4QZPW8V2R6T0A";
+
+    fn bundle() -> MetadataBundle {
+        let notes = vec![
+            note(REST, "REST", &["software/api"], &["draft"]),
+            note(API, "API Design", &["software/api", "design"], &[]),
+        ];
+        let mut links = HashMap::new();
+        links.insert(
+            API.parse().unwrap(),
+            vec![
+                Link::new(REST.parse().unwrap(), vec!["parent"]).unwrap(),
+                // The diary isn't part of the bundle
+                Link::new(DIARY.parse().unwrap(), vec!["see-also"]).unwrap(),
+            ],
+        );
+        MetadataBundle::new(&notes, &links, Utc::now())
+    }
+
+    #[test]
+    fn collects_metadata_and_drops_links_to_left_out_notes() {
+        let bundle = bundle();
+
+        let titles: Vec<&str> = bundle.notes.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, ["API Design", "REST"]);
+        assert_eq!(
+            bundle.notes[0].links,
+            [BundleLink {
+                target: REST.to_string(),
+                rels: vec!["parent".to_string()],
+            }]
+        );
+        assert_eq!(bundle.topic_count(), 2);
+        assert_eq!(bundle.tag_count(), 1);
+        assert_eq!(bundle.link_count(), 1);
+    }
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let bundle = bundle();
+        let sealed = bundle.seal("pw").unwrap();
+
+        assert_eq!(MetadataBundle::open(&sealed, "pw").unwrap(), bundle);
+        let err = MetadataBundle::open(&sealed, "other").unwrap_err();
+        assert!(err.to_string().contains("wrong passphrase"));
+    }
+}
@@ -0,0 +1,301 @@
+//! Digests of recently created and modified notes.
+//!
+//! A digest lists the notes created or modified since a point in time, with
+//! their descriptions and links, for sharing changes to a vault with the
+//! people who use it. It is built as markdown and rendered to HTML or plain
+//! text with the same renderers as note exports.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use minijinja::{Environment, context};
+
+use crate::export::html::markdown_to_html;
+use crate::export::theme::get_theme_css;
+use crate::index::IndexedNote;
+
+/// HTML page a rendered digest is placed in.
+const DIGEST_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <title>{{ title }}</title>
+    <style>{{ theme_css }}</style>
+</head>
+<body>
+    <article>
+        {{ content }}
+    </article>
+</body>
+</html>"##;
+
+/// Title of a digest.
+const DIGEST_TITLE: &str = "Notes digest";
+
+/// Options for building a digest.
+#[derive(Debug, Clone, Copy)]
+pub struct DigestOptions<'a> {
+    /// Notes created or modified at or after this time are included.
+    pub since: DateTime<Utc>,
+    /// Base URL of a site export of the vault. Notes link to their pages
+    /// there instead of to their markdown files.
+    pub site_url: Option<&'a str>,
+    /// Slug of each exported note's page by ID prefix (see
+    /// [`page_slugs`](crate::export::page_slugs)). With `site_url`, notes
+    /// without a page are left out.
+    pub page_slugs: &'a HashMap<String, String>,
+}
+
+/// A note listed in a digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestEntry {
+    pub title: String,
+    pub description: Option<String>,
+    /// The note's site page, or its path relative to the notes directory.
+    pub link: String,
+    pub topics: Vec<String>,
+    /// When the note was created (new notes) or last modified (updated ones).
+    pub date: DateTime<Utc>,
+}
+
+/// Notes created and modified since a point in time, most recent first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    /// Notes created since `since`.
+    pub created: Vec<DigestEntry>,
+    /// Older notes modified since `since`.
+    pub modified: Vec<DigestEntry>,
+}
+
+impl Digest {
+    /// Builds a digest of the given notes.
+    pub fn build(notes: &[IndexedNote], options: &DigestOptions) -> Self {
+        let mut created = Vec::new();
+        let mut modified = Vec::new();
+
+        for note in notes {
+            let link = match options.site_url {
+                Some(url) => match options.page_slugs.get(&note.id().prefix()) {
+                    Some(slug) => format!("{}/{}.html", url.trim_end_matches('/'), slug),
+                    None => continue,
+                },
+                None => note.path().to_string_lossy().to_string(),
+            };
+            if note.created() >= options.since {
+                created.push(entry(note, note.created(), link));
+            } else if note.modified() >= options.since {
+                modified.push(entry(note, note.modified(), link));
+            }
+        }
+        created.sort_by_key(|e| std::cmp::Reverse(e.date));
+        modified.sort_by_key(|e| std::cmp::Reverse(e.date));
+
+        Self {
+            since: options.since,
+            created,
+            modified,
+        }
+    }
+
+    /// Returns true if no notes were created or modified.
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.modified.is_empty()
+    }
+
+    /// Renders the digest as markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", DIGEST_TITLE);
+        out.push_str(&format!(
+            "Changes since {}: {} new, {} updated.\n",
+            self.since.format("%Y-%m-%d"),
+            self.created.len(),
+            self.modified.len()
+        ));
+
+        for (heading, entries) in [("New", &self.created), ("Updated", &self.modified)] {
+            if entries.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n## {}\n\n", heading));
+            for entry in entries {
+                out.push_str(&entry_markdown(entry));
+            }
+        }
+        out
+    }
+
+    /// Renders the digest as a standalone HTML page styled with a theme.
+    pub fn to_html(&self, theme: Option<&str>) -> Result<String> {
+        let mut env = Environment::new();
+        env.add_template("digest", DIGEST_TEMPLATE)?;
+        let html = env.get_template("digest")?.render(context! {
+            title => DIGEST_TITLE,
+            theme_css => get_theme_css(theme)?,
+            content => minijinja::Value::from_safe_string(markdown_to_html(&self.to_markdown())),
+        })?;
+        Ok(html)
+    }
+}
+
+fn entry(note: &IndexedNote, date: DateTime<Utc>, link: String) -> DigestEntry {
+    DigestEntry {
+        title: note.title().to_string(),
+        description: note.description().map(String::from),
+        link,
+        topics: note.topics().iter().map(|t| t.to_string()).collect(),
+        date,
+    }
+}
+
+/// Renders one digest entry as a markdown list item.
+fn entry_markdown(entry: &DigestEntry) -> String {
+    let mut line = format!("- [{}](<{}>)", escape(&entry.title), entry.link);
+    if let Some(desc) = &entry.description {
+        line.push_str(&format!(" — {}", escape(desc)));
+    }
+
+    let mut meta = entry.topics.clone();
+    meta.push(entry.date.format("%Y-%m-%d").to_string());
+    line.push_str(&format!(" _({})_\n", escape(&meta.join(", "))));
+    line
+}
+
+/// Escapes characters that would otherwise be read as markdown.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{NoteId, Topic};
+    use crate::infra::{ContentHash, slugify};
+    use chrono::TimeZone;
+    use std::path::PathBuf;
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap()
+    }
+
+    fn note(id: &str, title: &str, created: u32, modified: u32) -> IndexedNote {
+        IndexedNote::builder(
+            id.parse::<NoteId>().unwrap(),
+            title,
+            at(created),
+            at(modified),
+            PathBuf::from(format!("{}.md", slugify(title))),
+            ContentHash::compute(title.as_bytes()),
+        )
+        .build()
+    }
+
+    fn notes() -> Vec<IndexedNote> {
+        vec![
+            note("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Old", 1, 2),
+            note("01HQ4A2R9PXJK4QZPW8V2R6T9Y", "Edited", 1, 9),
+            IndexedNote::builder(
+                "01HQ5B3S0QYJK5RAQX9W3S7T0Z".parse().unwrap(),
+                "API [Design]",
+                at(10),
+                at(10),
+                PathBuf::from("api design.md"),
+                ContentHash::compute(b"api"),
+            )
+            .description("How we *build* APIs")
+            .topics(vec![Topic::new("software/api").unwrap()])
+            .build(),
+        ]
+    }
+
+    #[test]
+    fn build_splits_new_and_updated_notes() {
+        let options = DigestOptions {
+            since: at(8),
+            site_url: None,
+            page_slugs: &HashMap::new(),
+        };
+        let digest = Digest::build(&notes(), &options);
+
+        let titles = |entries: &[DigestEntry]| -> Vec<String> {
+            entries.iter().map(|e| e.title.clone()).collect()
+        };
+        assert_eq!(titles(&digest.created), vec!["API [Design]"]);
+        assert_eq!(titles(&digest.modified), vec!["Edited"]);
+        assert_eq!(digest.modified[0].date, at(9));
+    }
+
+    #[test]
+    fn markdown_lists_entries_with_links() {
+        let options = DigestOptions {
+            since: at(8),
+            site_url: None,
+            page_slugs: &HashMap::new(),
+        };
+        let markdown = Digest::build(&notes(), &options).to_markdown();
+
+        assert!(markdown.contains("Changes since 2024-03-08: 1 new, 1 updated."));
+        assert!(markdown.contains(
+            "- [API \\[Design\\]](<api design.md>) — How we \\*build\\* APIs _(software/api, 2024-03-10)_"
+        ));
+        assert!(markdown.contains("## Updated\n\n- [Edited](<edited.md>) _(2024-03-09)_"));
+        assert!(!markdown.contains("Old"));
+    }
+
+    #[test]
+    fn site_url_links_to_site_pages() {
+        let page_slugs = HashMap::from([
+            ("01HQ5B3S0Q".to_string(), "api-design".to_string()),
+            ("01HQ4A2R9P".to_string(), "edited".to_string()),
+        ]);
+        let options = DigestOptions {
+            since: at(8),
+            site_url: Some("https://notes.example.com/"),
+            page_slugs: &page_slugs,
+        };
+        let html = Digest::build(&notes(), &options).to_html(None).unwrap();
+
+        assert!(html.contains(r#"<a href="https://notes.example.com/api-design.html">"#));
+        assert!(html.contains("<h2>Updated</h2>"));
+    }
+
+    #[test]
+    fn site_url_uses_page_slugs_and_skips_unexported_notes() {
+        // "API [Design]" has an export_slug; "Edited" has `export: false`
+        let page_slugs = HashMap::from([("01HQ5B3S0Q".to_string(), "apis".to_string())]);
+        let options = DigestOptions {
+            since: at(8),
+            site_url: Some("https://notes.example.com"),
+            page_slugs: &page_slugs,
+        };
+        let digest = Digest::build(&notes(), &options);
+
+        assert_eq!(
+            digest.created[0].link,
+            "https://notes.example.com/apis.html"
+        );
+        assert!(digest.modified.is_empty());
+    }
+
+    #[test]
+    fn empty_digest() {
+        let options = DigestOptions {
+            since: at(20),
+            site_url: None,
+            page_slugs: &HashMap::new(),
+        };
+        let digest = Digest::build(&notes(), &options);
+
+        assert!(digest.is_empty());
+        assert!(digest.to_markdown().contains("0 new, 0 updated"));
+    }
+}
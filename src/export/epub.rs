@@ -0,0 +1,600 @@
+//! EPUB generation for reading exported notes on an e-reader.
+//!
+//! Notes are grouped into one chapter per topic, each note a section of its
+//! chapter. Links between notes in the book point at their sections; links
+//! to notes left out of the book become plain text.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Seek, Write};
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use pulldown_cmark::escape::escape_html;
+use regex::{Captures, Regex};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::domain::Topic;
+use crate::export::html::{MarkdownOptions, markdown_to_html_with};
+use crate::export::links::looks_like_note_id;
+use crate::index::IndexedNote;
+use crate::infra::read_note;
+
+/// Title of the chapter holding notes without a topic.
+const UNFILED_CHAPTER: &str = "Unfiled";
+
+/// Stylesheet for the book, kept plain so the reader's own settings apply.
+const EPUB_CSS: &str = r#"body { font-family: serif; line-height: 1.5; }
+h1.chapter { page-break-before: always; }
+section.note { margin-bottom: 2em; }
+p.description { font-style: italic; }
+pre { white-space: pre-wrap; font-size: 0.85em; }
+code { font-family: monospace; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #888; padding: 0.2em 0.4em; }
+blockquote { margin-left: 1em; padding-left: 0.8em; border-left: 3px solid #888; }
+"#;
+
+/// Configuration for EPUB generation.
+pub struct EpubConfig<'a> {
+    /// Book title.
+    pub title: &'a str,
+    /// Book author, if any.
+    pub author: Option<&'a str>,
+    /// Language of the book, as a BCP 47 tag like `en`.
+    pub language: &'a str,
+    /// Cover image (PNG, JPEG, GIF or SVG), if any.
+    pub cover: Option<&'a Path>,
+    /// Topic the chapters are relative to: a note is filed under its first
+    /// topic in this subtree, and chapter titles leave out its path.
+    pub root_topic: Option<&'a Topic>,
+    /// Markdown extensions to enable.
+    pub markdown: MarkdownOptions,
+}
+
+impl Default for EpubConfig<'_> {
+    fn default() -> Self {
+        Self {
+            title: "Notes",
+            author: None,
+            language: "en",
+            cover: None,
+            root_topic: None,
+            markdown: MarkdownOptions::default(),
+        }
+    }
+}
+
+/// Result of EPUB generation.
+pub struct EpubResult {
+    /// Number of notes in the book.
+    pub notes_exported: usize,
+    /// Number of chapters.
+    pub chapters: usize,
+    /// Number of links between notes that point into the book.
+    pub links_resolved: usize,
+}
+
+/// A chapter of the book: a topic and the notes filed under it.
+struct Chapter {
+    title: String,
+    file: String,
+    notes: Vec<ChapterNote>,
+}
+
+/// A note and the section anchor it gets in its chapter.
+struct ChapterNote {
+    title: String,
+    anchor: String,
+    note: IndexedNote,
+}
+
+/// Writes an EPUB of the given notes to `output`.
+pub fn generate_epub(
+    notes: &[IndexedNote],
+    output: &Path,
+    notes_dir: &Path,
+    config: &EpubConfig,
+) -> Result<EpubResult> {
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file =
+        File::create(output).with_context(|| format!("failed to create {}", output.display()))?;
+    write_epub(file, notes, notes_dir, config)
+}
+
+/// Writes an EPUB of the given notes to a writer.
+pub fn write_epub<W: Write + Seek>(
+    writer: W,
+    notes: &[IndexedNote],
+    notes_dir: &Path,
+    config: &EpubConfig,
+) -> Result<EpubResult> {
+    let cover = match config.cover {
+        Some(path) => Some(read_cover(path)?),
+        None => None,
+    };
+    let chapters = group_chapters(notes, config.root_topic);
+
+    // Where each note ends up, by full ID and by ID prefix, for resolving
+    // links
+    let targets: HashMap<String, String> = chapters
+        .iter()
+        .flat_map(|chapter| {
+            chapter.notes.iter().flat_map(|n| {
+                let href = format!("{}#{}", chapter.file, n.anchor);
+                [
+                    (n.note.id().to_string(), href.clone()),
+                    (n.note.id().prefix(), href),
+                ]
+            })
+        })
+        .collect();
+
+    let mut zip = ZipWriter::new(writer);
+    // The mimetype must come first and uncompressed so readers can sniff it
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(CONTAINER_XML.as_bytes())?;
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(EPUB_CSS.as_bytes())?;
+
+    let mut links_resolved = 0;
+    for chapter in &chapters {
+        let mut sections = String::new();
+        for chapter_note in &chapter.notes {
+            let parsed = read_note(&notes_dir.join(chapter_note.note.path()))?;
+            let (body, resolved) = resolve_links(&parsed.body, &targets);
+            links_resolved += resolved;
+            sections.push_str(&format!(
+                "<section class=\"note\" id=\"{}\">\n<h2>{}</h2>\n",
+                chapter_note.anchor,
+                escape(&chapter_note.title)
+            ));
+            if let Some(description) = parsed.note.description() {
+                sections.push_str(&format!(
+                    "<p class=\"description\">{}</p>\n",
+                    escape(description)
+                ));
+            }
+            sections.push_str(&markdown_to_html_with(&body, &config.markdown));
+            sections.push_str("</section>\n");
+        }
+        let page = xhtml_page(
+            &chapter.title,
+            config.language,
+            &format!(
+                "<h1 class=\"chapter\">{}</h1>\n{}",
+                escape(&chapter.title),
+                sections
+            ),
+        );
+        zip.start_file(format!("OEBPS/{}", chapter.file), deflated)?;
+        zip.write_all(page.as_bytes())?;
+    }
+
+    if let Some(cover) = &cover {
+        zip.start_file(format!("OEBPS/{}", cover.file), stored)?;
+        zip.write_all(&cover.data)?;
+        let page = xhtml_page(
+            config.title,
+            config.language,
+            &format!(
+                "<div class=\"cover\"><img src=\"{}\" alt=\"{}\" /></div>\n",
+                cover.file,
+                escape(config.title)
+            ),
+        );
+        zip.start_file("OEBPS/cover.xhtml", deflated)?;
+        zip.write_all(page.as_bytes())?;
+    }
+
+    let identifier = format!("urn:ulid:{}", ulid::Ulid::new());
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(nav_xhtml(&chapters, config).as_bytes())?;
+    zip.start_file("OEBPS/toc.ncx", deflated)?;
+    zip.write_all(toc_ncx(&chapters, config, &identifier).as_bytes())?;
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(content_opf(&chapters, config, cover.as_ref(), &identifier).as_bytes())?;
+    zip.finish()?;
+
+    Ok(EpubResult {
+        notes_exported: chapters.iter().map(|c| c.notes.len()).sum(),
+        chapters: chapters.len(),
+        links_resolved,
+    })
+}
+
+/// Groups notes into chapters by topic, in topic order with unfiled notes
+/// last, and notes in title order within each chapter.
+fn group_chapters(notes: &[IndexedNote], root: Option<&Topic>) -> Vec<Chapter> {
+    let mut by_topic: BTreeMap<Option<String>, Vec<&IndexedNote>> = BTreeMap::new();
+    for note in notes {
+        by_topic
+            .entry(chapter_topic(note, root))
+            .or_default()
+            .push(note);
+    }
+
+    // `None` sorts first, but unfiled notes read better at the end
+    let mut grouped: Vec<_> = by_topic.into_iter().collect();
+    if grouped.first().is_some_and(|(topic, _)| topic.is_none()) {
+        grouped.rotate_left(1);
+    }
+
+    grouped
+        .into_iter()
+        .enumerate()
+        .map(|(i, (topic, mut notes))| {
+            notes.sort_by_key(|n| n.title().to_lowercase());
+            Chapter {
+                title: topic.unwrap_or_else(|| UNFILED_CHAPTER.to_string()),
+                file: format!("chapter-{:03}.xhtml", i + 1),
+                notes: notes
+                    .into_iter()
+                    .map(|note| ChapterNote {
+                        title: note.title().to_string(),
+                        anchor: format!("note-{}", note.id()),
+                        note: note.clone(),
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// The chapter a note is filed under: its first topic in the root's
+/// subtree, relative to the root, or the root itself for notes directly in
+/// it. `None` means the unfiled chapter.
+fn chapter_topic(note: &IndexedNote, root: Option<&Topic>) -> Option<String> {
+    let Some(root) = root else {
+        return note.topics().first().map(|t| t.to_string());
+    };
+    let root_path = root.to_string();
+    note.topics().iter().find_map(|topic| {
+        let path = topic.to_string();
+        if path == root_path {
+            Some(path)
+        } else {
+            path.strip_prefix(&root_path)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .map(str::to_string)
+        }
+    })
+}
+
+/// Points `[text](ID)` links to notes in the book at their sections, and
+/// turns links to other notes into plain text. Returns the new body and the
+/// number of links pointed into the book.
+fn resolve_links(body: &str, targets: &HashMap<String, String>) -> (String, usize) {
+    let link_re = Regex::new(r"\[([^\]]*)\]\(([^)]+)\)").unwrap();
+    let mut resolved = 0;
+    let body = link_re.replace_all(body, |caps: &Captures| {
+        let target = &caps[2];
+        match targets.get(target) {
+            Some(href) => {
+                resolved += 1;
+                format!("[{}]({})", &caps[1], href)
+            }
+            None if looks_like_note_id(target) => caps[1].to_string(),
+            None => caps[0].to_string(),
+        }
+    });
+    (body.into_owned(), resolved)
+}
+
+/// A cover image read into memory.
+struct Cover {
+    file: String,
+    media_type: &'static str,
+    data: Vec<u8>,
+}
+
+/// Reads a cover image, which must be a format EPUB readers support.
+fn read_cover(path: &Path) -> Result<Cover> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let media_type = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => bail!(
+            "unsupported cover image {}: use PNG, JPEG, GIF or SVG",
+            path.display()
+        ),
+    };
+    let data = std::fs::read(path)
+        .with_context(|| format!("failed to read cover image {}", path.display()))?;
+    Ok(Cover {
+        file: format!("cover.{}", extension),
+        media_type,
+        data,
+    })
+}
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+/// Wraps body markup in an XHTML content document.
+fn xhtml_page(title: &str, language: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" xml:lang="{lang}" lang="{lang}">
+<head>
+<meta charset="UTF-8" />
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="style.css" />
+</head>
+<body>
+{body}</body>
+</html>
+"#,
+        lang = escape(language),
+        title = escape(title),
+        body = body
+    )
+}
+
+/// The EPUB 3 navigation document: chapters with their notes nested.
+fn nav_xhtml(chapters: &[Chapter], config: &EpubConfig) -> String {
+    let mut items = String::new();
+    for chapter in chapters {
+        items.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>\n<ol>\n",
+            chapter.file,
+            escape(&chapter.title)
+        ));
+        for note in &chapter.notes {
+            items.push_str(&format!(
+                "<li><a href=\"{}#{}\">{}</a></li>\n",
+                chapter.file,
+                note.anchor,
+                escape(&note.title)
+            ));
+        }
+        items.push_str("</ol>\n</li>\n");
+    }
+    xhtml_page(
+        config.title,
+        config.language,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>\n",
+            escape(config.title),
+            items
+        ),
+    )
+}
+
+/// The EPUB 2 table of contents, for older readers.
+fn toc_ncx(chapters: &[Chapter], config: &EpubConfig, identifier: &str) -> String {
+    let mut points = String::new();
+    let mut order = 0;
+    for chapter in chapters {
+        order += 1;
+        points.push_str(&format!(
+            "<navPoint id=\"nav-{order}\" playOrder=\"{order}\"><navLabel><text>{}</text></navLabel><content src=\"{}\"/>\n",
+            escape(&chapter.title),
+            chapter.file
+        ));
+        for note in &chapter.notes {
+            order += 1;
+            points.push_str(&format!(
+                "<navPoint id=\"nav-{order}\" playOrder=\"{order}\"><navLabel><text>{}</text></navLabel><content src=\"{}#{}\"/></navPoint>\n",
+                escape(&note.title),
+                chapter.file,
+                note.anchor
+            ));
+        }
+        points.push_str("</navPoint>\n");
+    }
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+<head><meta name="dtb:uid" content="{}"/></head>
+<docTitle><text>{}</text></docTitle>
+<navMap>
+{}</navMap>
+</ncx>
+"#,
+        escape(identifier),
+        escape(config.title),
+        points
+    )
+}
+
+/// The package document: metadata, manifest and reading order.
+fn content_opf(
+    chapters: &[Chapter],
+    config: &EpubConfig,
+    cover: Option<&Cover>,
+    identifier: &str,
+) -> String {
+    let mut metadata = format!(
+        "<dc:identifier id=\"book-id\">{}</dc:identifier>\n<dc:title>{}</dc:title>\n<dc:language>{}</dc:language>\n<meta property=\"dcterms:modified\">{}</meta>\n",
+        escape(identifier),
+        escape(config.title),
+        escape(config.language),
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    );
+    if let Some(author) = config.author {
+        metadata.push_str(&format!("<dc:creator>{}</dc:creator>\n", escape(author)));
+    }
+
+    let mut manifest = String::from(
+        "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+         <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n\
+         <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::new();
+    if let Some(cover) = cover {
+        metadata.push_str("<meta name=\"cover\" content=\"cover-image\"/>\n");
+        manifest.push_str(&format!(
+            "<item id=\"cover-image\" href=\"{}\" media-type=\"{}\" properties=\"cover-image\"/>\n\
+             <item id=\"cover\" href=\"cover.xhtml\" media-type=\"application/xhtml+xml\"/>\n",
+            cover.file, cover.media_type
+        ));
+        spine.push_str("<itemref idref=\"cover\" linear=\"no\"/>\n");
+    }
+    for (i, chapter) in chapters.iter().enumerate() {
+        manifest.push_str(&format!(
+            "<item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            i + 1,
+            chapter.file
+        ));
+        spine.push_str(&format!("<itemref idref=\"chapter-{}\"/>\n", i + 1));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{}</metadata>
+<manifest>
+{}</manifest>
+<spine toc="ncx">
+{}</spine>
+</package>
+"#,
+        metadata, manifest, spine
+    )
+}
+
+/// Escapes text for XHTML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    escape_html(&mut escaped, text).unwrap();
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Note, NoteId};
+    use crate::index::{IndexBuilder, IndexRepository, SqliteIndex};
+    use crate::infra::{generate_filename, write_note};
+    use std::io::{Cursor, Read};
+    use tempfile::TempDir;
+    use zip::ZipArchive;
+
+    fn note(dir: &Path, title: &str, topic: Option<&str>, body: &str) -> NoteId {
+        let mut builder = Note::builder(NoteId::new(), title, Utc::now(), Utc::now());
+        if let Some(topic) = topic {
+            builder = builder.topics(vec![Topic::new(topic).unwrap()]);
+        }
+        let note = builder.build().unwrap();
+        let path = dir.join(generate_filename(note.id(), note.title()));
+        write_note(&path, &note, body).unwrap();
+        note.id().clone()
+    }
+
+    fn indexed(dir: &Path) -> Vec<IndexedNote> {
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        IndexBuilder::new(dir.to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+        index.list_all().unwrap()
+    }
+
+    fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> String {
+        let mut contents = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn writes_chapters_per_topic_with_resolved_links() {
+        let dir = TempDir::new().unwrap();
+        let rust = note(dir.path(), "Ownership", Some("software/rust"), "Intro.\n");
+        note(
+            dir.path(),
+            "Borrowing",
+            Some("software/rust"),
+            // The full ID: notes written in the same millisecond share a prefix
+            &format!("See [ownership]({}) and [gone](01ZZZZZZZZ).\n", rust),
+        );
+        note(dir.path(), "Queues", Some("software/ops"), "Text.\n");
+        note(dir.path(), "Loose", None, "Text.\n");
+
+        let root = Topic::new("software").unwrap();
+        let config = EpubConfig {
+            title: "Field Guide",
+            author: Some("Ada"),
+            root_topic: Some(&root),
+            ..EpubConfig::default()
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        let result = write_epub(&mut buffer, &indexed(dir.path()), dir.path(), &config).unwrap();
+
+        assert_eq!(result.notes_exported, 4);
+        assert_eq!(result.chapters, 3);
+        assert_eq!(result.links_resolved, 1);
+
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+
+        let opf = read_entry(&mut archive, "OEBPS/content.opf");
+        assert!(opf.contains("<dc:title>Field Guide</dc:title>"));
+        assert!(opf.contains("<dc:creator>Ada</dc:creator>"));
+
+        let nav = read_entry(&mut archive, "OEBPS/nav.xhtml");
+        let ops = nav.find(">ops<").unwrap();
+        assert!(ops < nav.find(">rust<").unwrap(), "{}", nav);
+
+        let chapter = read_entry(&mut archive, "OEBPS/chapter-002.xhtml");
+        let anchor = format!("note-{}", rust);
+        assert!(chapter.contains(&format!("id=\"{}\"", anchor)));
+        assert!(chapter.contains(&format!("href=\"chapter-002.xhtml#{}\"", anchor)));
+        assert!(chapter.contains("and gone."), "{}", chapter);
+        assert!(!chapter.contains("Loose"));
+    }
+
+    #[test]
+    fn unfiled_notes_come_last_and_cover_is_listed() {
+        let dir = TempDir::new().unwrap();
+        note(dir.path(), "Loose", None, "Text.\n");
+        note(dir.path(), "Filed", Some("reference"), "Text.\n");
+        let cover = dir.path().join("cover.png");
+        std::fs::write(&cover, b"not really a png").unwrap();
+
+        let config = EpubConfig {
+            cover: Some(&cover),
+            ..EpubConfig::default()
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        write_epub(&mut buffer, &indexed(dir.path()), dir.path(), &config).unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let last = read_entry(&mut archive, "OEBPS/chapter-002.xhtml");
+        assert!(last.contains(UNFILED_CHAPTER));
+        let opf = read_entry(&mut archive, "OEBPS/content.opf");
+        assert!(
+            opf.contains("href=\"cover.png\" media-type=\"image/png\" properties=\"cover-image\"")
+        );
+        assert!(archive.by_name("OEBPS/cover.png").is_ok());
+    }
+
+    #[test]
+    fn rejects_unsupported_cover() {
+        let err = read_cover(Path::new("cover.bmp")).err().unwrap();
+        assert!(err.to_string().contains("unsupported cover image"));
+    }
+}
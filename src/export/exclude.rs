@@ -0,0 +1,152 @@
+//! Rules for keeping notes out of bulk exports.
+
+use anyhow::{Context, Result};
+use glob::{MatchOptions, Pattern};
+
+use crate::domain::{Tag, Topic};
+use crate::index::IndexedNote;
+
+/// `*` stays within a directory; `**` crosses directories.
+const PATH_MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Notes to leave out of a bulk export.
+///
+/// A note is excluded if it matches any rule.
+#[derive(Debug, Clone, Default)]
+pub struct ExportExclusions {
+    /// Topics to exclude, and whether their descendants are excluded too.
+    topics: Vec<(Topic, bool)>,
+    tags: Vec<Tag>,
+    /// Glob patterns matched against note paths relative to the notes directory.
+    paths: Vec<Pattern>,
+}
+
+impl ExportExclusions {
+    /// Builds exclusion rules from topic filters (trailing `/` includes
+    /// descendants), tag names and path globs.
+    pub fn new(topics: &[String], tags: &[String], paths: &[String]) -> Result<Self> {
+        let topics = topics
+            .iter()
+            .map(|t| {
+                let include_descendants = t.ends_with('/');
+                let path = t.trim_end_matches('/');
+                let topic = Topic::new(path)
+                    .with_context(|| format!("invalid topic to exclude: {}", path))?;
+                Ok((topic, include_descendants))
+            })
+            .collect::<Result<_>>()?;
+        let tags = tags
+            .iter()
+            .map(|t| Tag::new(t).with_context(|| format!("invalid tag to exclude: {}", t)))
+            .collect::<Result<_>>()?;
+        let paths = paths
+            .iter()
+            .map(|p| Pattern::new(p).with_context(|| format!("invalid exclude pattern: {}", p)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            topics,
+            tags,
+            paths,
+        })
+    }
+
+    /// Returns true if there are no rules.
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty() && self.tags.is_empty() && self.paths.is_empty()
+    }
+
+    /// Returns true if the note must be left out of the export.
+    pub fn excludes(&self, note: &IndexedNote) -> bool {
+        let topic_excluded = note.topics().iter().any(|topic| {
+            self.topics.iter().any(|(excluded, include_descendants)| {
+                topic == excluded || (*include_descendants && excluded.is_ancestor_of(topic))
+            })
+        });
+        topic_excluded
+            || note.tags().iter().any(|tag| self.tags.contains(tag))
+            || self
+                .paths
+                .iter()
+                .any(|pattern| pattern.matches_path_with(note.path(), PATH_MATCH_OPTIONS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::NoteId;
+    use crate::infra::ContentHash;
+    use chrono::{TimeZone, Utc};
+    use std::path::PathBuf;
+
+    fn note(path: &str, topics: &[&str], tags: &[&str]) -> IndexedNote {
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        IndexedNote::builder(
+            id,
+            "Note",
+            time,
+            time,
+            PathBuf::from(path),
+            ContentHash::compute(b"x"),
+        )
+        .topics(topics.iter().map(|t| Topic::new(t).unwrap()).collect())
+        .tags(tags.iter().map(|t| Tag::new(t).unwrap()).collect())
+        .build()
+    }
+
+    fn rules(topics: &[&str], tags: &[&str], paths: &[&str]) -> ExportExclusions {
+        let owned = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        ExportExclusions::new(&owned(topics), &owned(tags), &owned(paths)).unwrap()
+    }
+
+    #[test]
+    fn empty_rules_exclude_nothing() {
+        let rules = ExportExclusions::default();
+        assert!(rules.is_empty());
+        assert!(!rules.excludes(&note("a.md", &["personal"], &["private"])));
+    }
+
+    #[test]
+    fn topic_without_slash_is_exact() {
+        let rules = rules(&["personal"], &[], &[]);
+        assert!(rules.excludes(&note("a.md", &["personal"], &[])));
+        assert!(!rules.excludes(&note("a.md", &["personal/journal"], &[])));
+    }
+
+    #[test]
+    fn topic_with_slash_includes_descendants() {
+        let rules = rules(&["personal/"], &[], &[]);
+        assert!(rules.excludes(&note("a.md", &["personal"], &[])));
+        assert!(rules.excludes(&note("a.md", &["work", "personal/journal"], &[])));
+        assert!(!rules.excludes(&note("a.md", &["personalities"], &[])));
+    }
+
+    #[test]
+    fn tag_excludes_tagged_notes() {
+        let rules = rules(&[], &["private"], &[]);
+        assert!(rules.excludes(&note("a.md", &[], &["draft", "private"])));
+        assert!(!rules.excludes(&note("a.md", &[], &["draft"])));
+    }
+
+    #[test]
+    fn path_globs_match_relative_paths() {
+        let rules = rules(&[], &[], &["journal/**", "*-secret.md"]);
+        assert!(rules.excludes(&note("journal/2024/jan.md", &[], &[])));
+        assert!(rules.excludes(&note("api-secret.md", &[], &[])));
+        assert!(!rules.excludes(&note("work/api-secret.md", &[], &[])));
+        assert!(!rules.excludes(&note("work/api.md", &[], &[])));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let owned = vec!["[".to_string()];
+        assert!(ExportExclusions::new(&[], &[], &owned).is_err());
+        let owned = vec!["Bad Tag!".to_string()];
+        assert!(ExportExclusions::new(&[], &owned, &[]).is_err());
+    }
+}
@@ -1,6 +1,42 @@
 //! Markdown to HTML conversion.
 
-use pulldown_cmark::{html, Options, Parser};
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{html, Event, Options, Parser, Tag};
+
+use crate::export::toc::{add_heading_anchors, TocEntry, TocOptions};
+use crate::infra::profile::{self, Phase};
+
+/// Opening marker for a protected math span in the pre-processed markdown.
+const MATH_OPEN: char = '\u{E000}';
+/// Closing marker for a protected math span in the pre-processed markdown.
+const MATH_CLOSE: char = '\u{E001}';
+
+/// Optional markdown extensions used when rendering exports.
+///
+/// Tables and strikethrough are always enabled. Math is off by default
+/// because `$` is common in ordinary prose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownOptions {
+    /// Footnote references and definitions (`[^1]`).
+    pub footnotes: bool,
+    /// GitHub-style task lists (`- [ ]` / `- [x]`).
+    pub task_lists: bool,
+    /// Obsidian-style callout blocks (`> [!note] Title`).
+    pub callouts: bool,
+    /// TeX math in `$...$` and `$$...$$`, rendered client-side by MathJax.
+    pub math: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            footnotes: true,
+            task_lists: true,
+            callouts: true,
+            math: false,
+        }
+    }
+}
 
 /// Converts markdown text to HTML.
 ///
@@ -9,6 +45,7 @@ use pulldown_cmark::{html, Options, Parser};
 /// - Footnotes
 /// - Strikethrough
 /// - Task lists
+/// - Callouts
 ///
 /// # Example
 ///
@@ -20,16 +57,403 @@ use pulldown_cmark::{html, Options, Parser};
 /// assert!(html.contains("<p>World</p>"));
 /// ```
 pub fn markdown_to_html(markdown: &str) -> String {
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_TABLES);
-    options.insert(Options::ENABLE_FOOTNOTES);
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    options.insert(Options::ENABLE_TASKLISTS);
+    markdown_to_html_with(markdown, &MarkdownOptions::default())
+}
+
+/// Converts markdown text to HTML with the given extensions enabled.
+///
+/// # Example
+///
+/// ```
+/// use den::export::{markdown_to_html_with, MarkdownOptions};
+///
+/// let options = MarkdownOptions { math: true, ..Default::default() };
+/// let html = markdown_to_html_with("Euler: $e^{i\\pi} + 1 = 0$", &options);
+/// assert!(html.contains(r#"<span class="math math-inline">"#));
+/// ```
+pub fn markdown_to_html_with(markdown: &str, options: &MarkdownOptions) -> String {
+    profile::time(Phase::Render, || render_markdown(markdown, options, None).0)
+}
+
+/// Converts markdown text to HTML, giving headings IDs and returning the
+/// table of contents linking to them.
+///
+/// # Example
+///
+/// ```
+/// use den::export::{markdown_to_html_with_toc, MarkdownOptions, TocOptions};
+///
+/// let (html, toc) = markdown_to_html_with_toc(
+///     "## Setup\n\nText",
+///     &MarkdownOptions::default(),
+///     &TocOptions::default(),
+/// );
+/// assert!(html.contains(r#"<h2 id="setup">Setup</h2>"#));
+/// assert_eq!(toc[0].anchor, "setup");
+/// ```
+pub fn markdown_to_html_with_toc(
+    markdown: &str,
+    options: &MarkdownOptions,
+    toc: &TocOptions,
+) -> (String, Vec<TocEntry>) {
+    profile::time(Phase::Render, || {
+        render_markdown(markdown, options, Some(toc))
+    })
+}
+
+fn render_markdown(
+    markdown: &str,
+    options: &MarkdownOptions,
+    toc: Option<&TocOptions>,
+) -> (String, Vec<TocEntry>) {
+    let mut parser_options = Options::empty();
+    parser_options.insert(Options::ENABLE_TABLES);
+    parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+    if options.footnotes {
+        parser_options.insert(Options::ENABLE_FOOTNOTES);
+    }
+    if options.task_lists {
+        parser_options.insert(Options::ENABLE_TASKLISTS);
+    }
+
+    let (source, math_spans) = if options.math {
+        protect_math(markdown)
+    } else {
+        (markdown.to_string(), Vec::new())
+    };
+
+    let mut events: Vec<Event> = Parser::new_ext(&source, parser_options).collect();
+    if options.callouts {
+        events = transform_callouts(events);
+    }
+    let entries = match toc {
+        Some(toc) => add_heading_anchors(&mut events, toc),
+        None => Vec::new(),
+    };
 
-    let parser = Parser::new_ext(markdown, options);
     let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
-    html_output
+    html::push_html(&mut html_output, events.into_iter());
+
+    if math_spans.is_empty() {
+        (html_output, entries)
+    } else {
+        (restore_math(&html_output, &math_spans), entries)
+    }
+}
+
+// ===========================================
+// Callouts
+// ===========================================
+
+/// Rewrites blockquotes that start with `[!kind]` into callout blocks.
+///
+/// The first line of the quote becomes the title (defaulting to the
+/// capitalized kind) and the rest becomes the content. A `+` or `-` fold
+/// marker after the kind is accepted and ignored.
+fn transform_callouts(events: Vec<Event<'_>>) -> Vec<Event<'_>> {
+    let mut out = Vec::with_capacity(events.len());
+    // One entry per open blockquote: whether it was turned into a callout.
+    let mut quotes: Vec<bool> = Vec::new();
+    let mut i = 0;
+
+    while i < events.len() {
+        match &events[i] {
+            Event::Start(Tag::BlockQuote) => match parse_callout(&events[i + 1..]) {
+                Some(callout) => {
+                    out.push(Event::Html(
+                        format!(
+                            "<div class=\"callout callout-{}\">\n<div class=\"callout-title\">",
+                            callout.kind
+                        )
+                        .into(),
+                    ));
+                    if callout.title.is_empty() {
+                        out.push(Event::Text(capitalize(&callout.kind).into()));
+                    } else {
+                        out.extend(callout.title);
+                    }
+                    out.push(Event::Html(
+                        "</div>\n<div class=\"callout-content\">\n".into(),
+                    ));
+                    if callout.continues_paragraph {
+                        out.push(Event::Start(Tag::Paragraph));
+                    }
+                    quotes.push(true);
+                    i += 1 + callout.consumed;
+                    continue;
+                }
+                None => quotes.push(false),
+            },
+            Event::End(Tag::BlockQuote) => {
+                let close = if quotes.pop() == Some(true) {
+                    Event::Html("</div>\n</div>\n".into())
+                } else {
+                    events[i].clone()
+                };
+                out.push(close);
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+        out.push(events[i].clone());
+        i += 1;
+    }
+
+    out
+}
+
+/// A callout header parsed from the start of a blockquote.
+struct Callout<'a> {
+    kind: String,
+    /// Inline events making up the title, without the `[!kind]` marker.
+    title: Vec<Event<'a>>,
+    /// True when the first paragraph has more lines after the title.
+    continues_paragraph: bool,
+    /// Number of events consumed after the blockquote start.
+    consumed: usize,
+}
+
+/// Parses a callout header from the events following a blockquote start.
+fn parse_callout<'a>(events: &[Event<'a>]) -> Option<Callout<'a>> {
+    if !matches!(events.first(), Some(Event::Start(Tag::Paragraph))) {
+        return None;
+    }
+
+    // The parser may split `[!note]` across several text events.
+    let mut leading = String::new();
+    let mut pos = 1;
+    while let Some(Event::Text(text)) = events.get(pos) {
+        leading.push_str(text);
+        pos += 1;
+    }
+
+    let rest = leading.strip_prefix("[!")?;
+    let end = rest.find(']')?;
+    let kind = &rest[..end];
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    let remainder = rest[end + 1..].trim_start_matches(['+', '-']).trim_start();
+
+    let mut title = Vec::new();
+    if !remainder.is_empty() {
+        title.push(Event::Text(remainder.to_string().into()));
+    }
+    loop {
+        match events.get(pos)? {
+            Event::SoftBreak | Event::HardBreak => {
+                return Some(Callout {
+                    kind: kind.to_ascii_lowercase(),
+                    title,
+                    continues_paragraph: true,
+                    consumed: pos + 1,
+                });
+            }
+            Event::End(Tag::Paragraph) => {
+                return Some(Callout {
+                    kind: kind.to_ascii_lowercase(),
+                    title,
+                    continues_paragraph: false,
+                    consumed: pos + 1,
+                });
+            }
+            event => title.push(event.clone()),
+        }
+        pos += 1;
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// ===========================================
+// Math
+// ===========================================
+
+/// A TeX span removed from the markdown before parsing.
+struct MathSpan {
+    tex: String,
+    display: bool,
+}
+
+/// Replaces `$...$` and `$$...$$` spans with placeholders.
+///
+/// Math must be lifted out before parsing so that markdown syntax inside
+/// TeX (`_`, `*`, `\\`) is left alone. Fenced code blocks and inline code
+/// spans are copied verbatim, and `\$` is treated as a literal dollar.
+/// Inline math follows the pandoc rule: the opening `$` must not be
+/// followed by whitespace, and the closing `$` must not be preceded by
+/// whitespace or followed by a digit.
+fn protect_math(markdown: &str) -> (String, Vec<MathSpan>) {
+    let mut out = String::with_capacity(markdown.len());
+    let mut spans = Vec::new();
+    let mut prose = String::new();
+    // Character and length of the open code fence, if any.
+    let mut fence: Option<(char, usize)> = None;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+
+        if let Some((fence_char, len)) = fence {
+            out.push_str(line);
+            let candidate = trimmed.trim_end();
+            if candidate.len() >= len && candidate.chars().all(|c| c == fence_char) {
+                fence = None;
+            }
+            continue;
+        }
+
+        if indent < 4 && (trimmed.starts_with("```") || trimmed.starts_with("~~~")) {
+            let fence_char = trimmed.chars().next().unwrap();
+            let len = trimmed.chars().take_while(|&c| c == fence_char).count();
+            protect_math_prose(&prose, &mut out, &mut spans);
+            prose.clear();
+            out.push_str(line);
+            fence = Some((fence_char, len));
+            continue;
+        }
+
+        prose.push_str(line);
+    }
+
+    protect_math_prose(&prose, &mut out, &mut spans);
+    (out, spans)
+}
+
+/// Lifts math out of a run of text that contains no fenced code.
+fn protect_math_prose(text: &str, out: &mut String, spans: &mut Vec<MathSpan>) {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if bytes.get(i + 1) == Some(&b'$') => {
+                out.push_str("\\$");
+                i += 2;
+                continue;
+            }
+            b'`' => {
+                let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &text[i..i + run];
+                let close = text[i + run..]
+                    .match_indices(fence)
+                    .find(|(pos, _)| bytes.get(i + run + pos + run) != Some(&b'`'));
+                let end = match close {
+                    Some((pos, _)) => i + run + pos + run,
+                    None => i + run,
+                };
+                out.push_str(&text[i..end]);
+                i = end;
+                continue;
+            }
+            b'$' if bytes.get(i + 1) == Some(&b'$') => {
+                if let Some(len) = text[i + 2..].find("$$") {
+                    let tex = text[i + 2..i + 2 + len].trim();
+                    push_math_placeholder(out, spans, tex, true);
+                    i += 2 + len + 2;
+                    continue;
+                }
+            }
+            b'$' => {
+                if let Some(len) = find_inline_math_end(&text[i + 1..]) {
+                    push_math_placeholder(out, spans, &text[i + 1..i + 1 + len], false);
+                    i += 1 + len + 1;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        let ch = text[i..].chars().next().unwrap();
+        out.push(ch);
+        i += ch.len_utf8();
+    }
+}
+
+/// Finds the length of an inline math span body, or `None` if the `$` is literal.
+fn find_inline_math_end(text: &str) -> Option<usize> {
+    let first = text.chars().next()?;
+    if first.is_whitespace() || first == '$' {
+        return None;
+    }
+
+    let line_end = text.find('\n').unwrap_or(text.len());
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < line_end {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'$' => {
+                let before = text[..i].chars().next_back();
+                let after = bytes.get(i + 1);
+                if before.is_some_and(|c| !c.is_whitespace())
+                    && !after.is_some_and(|b| b.is_ascii_digit())
+                {
+                    return Some(i);
+                }
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn push_math_placeholder(out: &mut String, spans: &mut Vec<MathSpan>, tex: &str, display: bool) {
+    out.push(MATH_OPEN);
+    out.push_str(&spans.len().to_string());
+    out.push(MATH_CLOSE);
+    spans.push(MathSpan {
+        tex: tex.to_string(),
+        display,
+    });
+}
+
+/// Substitutes rendered math markup for the placeholders left by [`protect_math`].
+fn restore_math(html: &str, spans: &[MathSpan]) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find(MATH_OPEN) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + MATH_OPEN.len_utf8()..];
+        let span = after
+            .find(MATH_CLOSE)
+            .and_then(|end| after[..end].parse::<usize>().ok().map(|n| (n, end)))
+            .and_then(|(n, end)| spans.get(n).map(|span| (span, end)));
+
+        match span {
+            Some((span, end)) => {
+                let (class, open, close) = if span.display {
+                    ("math math-display", "\\[", "\\]")
+                } else {
+                    ("math math-inline", "\\(", "\\)")
+                };
+                out.push_str("<span class=\"");
+                out.push_str(class);
+                out.push_str("\">");
+                out.push_str(open);
+                let _ = escape_html(&mut out, &span.tex);
+                out.push_str(close);
+                out.push_str("</span>");
+                rest = &after[end + MATH_CLOSE.len_utf8()..];
+            }
+            None => {
+                out.push(MATH_OPEN);
+                rest = after;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
 }
 
 #[cfg(test)]
@@ -198,4 +622,149 @@ mod tests {
 
         assert!(html.contains("<hr"));
     }
+    // ===========================================
+    // Extension Options
+    // ===========================================
+
+    fn with_math() -> MarkdownOptions {
+        MarkdownOptions {
+            math: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_markdown_to_html_footnotes() {
+        let markdown = "Claim.[^1]\n\n[^1]: Source.";
+        let html = markdown_to_html(markdown);
+
+        assert!(html.contains("footnote-reference"));
+        assert!(html.contains("footnote-definition"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_footnotes_disabled() {
+        let options = MarkdownOptions {
+            footnotes: false,
+            ..Default::default()
+        };
+        let html = markdown_to_html_with("Claim.[^1]\n\n[^1]: Source.", &options);
+
+        assert!(!html.contains("footnote-reference"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_task_list_disabled() {
+        let options = MarkdownOptions {
+            task_lists: false,
+            ..Default::default()
+        };
+        let html = markdown_to_html_with("- [ ] Todo", &options);
+
+        assert!(!html.contains("checkbox"));
+        assert!(html.contains("[ ] Todo"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_callout() {
+        let markdown = "> [!warning] Be careful\n> The floor is wet.";
+        let html = markdown_to_html(markdown);
+
+        assert!(html.contains(r#"<div class="callout callout-warning">"#));
+        assert!(html.contains(r#"<div class="callout-title">Be careful</div>"#));
+        assert!(html.contains("<p>The floor is wet.</p>"));
+        assert!(!html.contains("<blockquote>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_callout_default_title() {
+        let markdown = "> [!NOTE]\n>\n> Body text.";
+        let html = markdown_to_html(markdown);
+
+        assert!(html.contains(r#"<div class="callout callout-note">"#));
+        assert!(html.contains(r#"<div class="callout-title">Note</div>"#));
+        assert!(html.contains("<p>Body text.</p>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_callout_fold_marker_and_inline_title() {
+        let markdown = "> [!tip]- Use *this*\n> Content";
+        let html = markdown_to_html(markdown);
+
+        assert!(html.contains(r#"<div class="callout-title">Use <em>this</em></div>"#));
+    }
+
+    #[test]
+    fn test_markdown_to_html_callout_nested_in_blockquote() {
+        let markdown = "> Outer\n>\n> > [!info]\n> > Inner";
+        let html = markdown_to_html(markdown);
+
+        assert!(html.contains("<blockquote>"));
+        assert!(html.contains(r#"<div class="callout callout-info">"#));
+        assert!(html.contains("</div>\n</div>\n</blockquote>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_plain_blockquote_unchanged() {
+        let html = markdown_to_html("> [link] text");
+
+        assert!(html.contains("<blockquote>"));
+        assert!(!html.contains("callout"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_callouts_disabled() {
+        let options = MarkdownOptions {
+            callouts: false,
+            ..Default::default()
+        };
+        let html = markdown_to_html_with("> [!note]\n> Body", &options);
+
+        assert!(html.contains("<blockquote>"));
+        assert!(!html.contains("callout"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_math_disabled_by_default() {
+        let html = markdown_to_html("Costs $5 and $10.");
+
+        assert!(!html.contains("math"));
+        assert!(html.contains("Costs $5 and $10."));
+    }
+
+    #[test]
+    fn test_markdown_to_html_inline_math() {
+        let html = markdown_to_html_with("Let $a_1 < b_2$ hold.", &with_math());
+
+        assert!(html.contains(r#"<span class="math math-inline">\(a_1 &lt; b_2\)</span>"#));
+        assert!(!html.contains("<em>"));
+    }
+
+    #[test]
+    fn test_markdown_to_html_display_math() {
+        let markdown = "$$\n\\sum_{i=1}^n i = \\frac{n(n+1)}{2}\n$$";
+        let html = markdown_to_html_with(markdown, &with_math());
+
+        assert!(html.contains(
+            r#"<span class="math math-display">\[\sum_{i=1}^n i = \frac{n(n+1)}{2}\]</span>"#
+        ));
+    }
+
+    #[test]
+    fn test_markdown_to_html_math_ignores_prices_and_escapes() {
+        let html = markdown_to_html_with("From $5 to $10, or \\$x\\$.", &with_math());
+
+        assert!(!html.contains("math"));
+        assert!(html.contains("From $5 to $10, or $x$."));
+    }
+
+    #[test]
+    fn test_markdown_to_html_math_skips_code() {
+        let markdown = "Inline `$x$` code.\n\n```sh\necho $HOME $PATH\n```";
+        let html = markdown_to_html_with(markdown, &with_math());
+
+        assert!(!html.contains("math"));
+        assert!(html.contains("<code>$x$</code>"));
+        assert!(html.contains("echo $HOME $PATH"));
+    }
 }
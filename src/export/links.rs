@@ -4,9 +4,13 @@
 //! in exported content.
 
 use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
 
 use regex::{Captures, Regex};
 
+use crate::export::page_slugs;
 use crate::index::{IndexRepository, IndexedNote};
 use crate::infra::slugify;
 
@@ -59,10 +63,21 @@ pub struct LinkResolver<'a> {
 }
 
 impl<'a> LinkResolver<'a> {
-    /// Creates a new link resolver from an index.
-    pub fn from_index<R: IndexRepository>(index: &R, options: &'a LinkResolverOptions) -> Self {
+    /// Creates a new link resolver from an index, linking to the notes'
+    /// exported pages (see [`page_slugs`]).
+    ///
+    /// # Errors
+    ///
+    /// Fails if a note file can't be read.
+    pub fn from_index<R: IndexRepository>(
+        index: &R,
+        notes_dir: &Path,
+        options: &'a LinkResolverOptions,
+    ) -> Result<Self> {
         let notes = index.list_all().unwrap_or_default();
-        Self::from_notes(&notes, options)
+        let mut resolver = Self::from_notes(&notes, options);
+        resolver.set_page_slugs(&page_slugs(&notes, notes_dir)?);
+        Ok(resolver)
     }
 
     /// Creates a new link resolver from a list of notes.
@@ -131,6 +146,20 @@ impl<'a> LinkResolver<'a> {
         }
     }
 
+    /// Links each note to its page in `slugs`, by ID prefix, instead of the
+    /// one named after its title. Notes without a page aren't exported, so
+    /// links to them count as broken.
+    pub fn set_page_slugs(&mut self, slugs: &HashMap<String, String>) {
+        self.note_map
+            .retain(|prefix, (slug, _)| match slugs.get(prefix) {
+                Some(page) => {
+                    slug.clone_from(page);
+                    true
+                }
+                None => false,
+            });
+    }
+
     /// Looks up a note by ID prefix.
     pub fn lookup(&self, id_prefix: &str) -> Option<(&str, &str)> {
         self.note_map
@@ -153,7 +182,7 @@ fn is_external_link(target: &str) -> bool {
 }
 
 /// Checks if a string looks like a ULID note ID prefix.
-fn looks_like_note_id(s: &str) -> bool {
+pub(crate) fn looks_like_note_id(s: &str) -> bool {
     // ULID prefixes are 4-26 characters, alphanumeric (Crockford Base32)
     // They start with 0-7 (timestamp encoding)
     s.len() >= 4
@@ -202,6 +231,26 @@ mod tests {
         assert!(result.content.contains("[Target](target-note.html)"));
     }
 
+    #[test]
+    fn test_page_slugs_rename_and_drop_pages() {
+        let notes = vec![
+            create_mock_note("Release Notes", "01HQ4A2R9PXJK4QZPW8V2R6T9Y"),
+            create_mock_note("Hidden Draft", "01HQ5B3S0QYJK5RAQX9W3S7T0Z"),
+        ];
+        let options = LinkResolverOptions::default();
+        let mut resolver = LinkResolver::from_notes(&notes, &options);
+        resolver.set_page_slugs(&HashMap::from([(
+            "01HQ4A2R9P".to_string(),
+            "changes".to_string(),
+        )]));
+
+        let result = resolver.resolve("[Changes](01HQ4A2R9P) and [Draft](01HQ5B3S0Q)");
+
+        assert_eq!(result.resolved, 1);
+        assert_eq!(result.broken, 1);
+        assert!(result.content.contains("[Changes](changes.html)"));
+    }
+
     #[test]
     fn test_preserve_external_links() {
         let notes = vec![];
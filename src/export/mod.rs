@@ -1,16 +1,34 @@
 //! Export functionality for converting notes to various formats.
 //!
-//! Supports HTML, PDF, and static site generation with customizable
-//! templates and CSS themes.
+//! Supports HTML, plain text, PDF, EPUB and static site generation with
+//! customizable templates and CSS themes, and digests of recently changed notes.
 
+mod assets;
+mod bundle;
+mod digest;
+mod epub;
+mod exclude;
 mod html;
 pub mod links;
+mod page;
 pub mod site;
 pub mod template;
+mod text;
 mod theme;
+mod toc;
 
-pub use html::markdown_to_html;
+pub use assets::{ASSETS_DIR, AssetCopier, AssetOptions};
+pub use bundle::{BundleLink, BundleNote, MetadataBundle};
+pub use digest::{Digest, DigestEntry, DigestOptions};
+pub use epub::{EpubConfig, EpubResult, generate_epub, write_epub};
+pub use exclude::ExportExclusions;
+pub use html::{markdown_to_html, markdown_to_html_with, markdown_to_html_with_toc, MarkdownOptions};
 pub use links::{BrokenLinkHandling, LinkResolver, LinkResolverOptions, LinkResolution};
-pub use site::{SiteConfig, SiteResult, generate_site};
+pub use page::{page_slug, page_slug_for, page_slugs, page_template, retain_exported};
+pub use site::{
+    SiteConfig, SiteLabels, SiteResult, SiteStats, SiteStatsOptions, generate_site, is_rtl_language,
+};
 pub use template::{render_note_html, DEFAULT_NOTE_TEMPLATE};
-pub use theme::{get_theme_css, THEME_DARK, THEME_DEFAULT};
+pub use text::{DEFAULT_TEXT_WIDTH, TextOptions, markdown_to_text, render_note_text};
+pub use theme::{BUILTIN_THEMES, get_theme_css, THEME_DARK, THEME_DEFAULT};
+pub use toc::{toc_html, TocEntry, TocOptions, DEFAULT_TOC_DEPTH};
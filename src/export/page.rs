@@ -0,0 +1,121 @@
+//! Per-note export settings from frontmatter: `export: false` keeps a note
+//! out, `export_slug` names its page and `export_template` renders it with
+//! another template.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::domain::Note;
+use crate::index::IndexedNote;
+use crate::infra::{read_note, slugify};
+
+/// Returns the slug of a note's exported page: its `export_slug` if set,
+/// otherwise its title, slugified either way.
+pub fn page_slug(note: &Note) -> String {
+    page_slug_for(note.export_slug().unwrap_or(note.title()))
+}
+
+/// Returns the slug of a page named `name`, the way [`page_slug`] slugifies
+/// titles, so alias redirects land on the same names as the pages.
+pub fn page_slug_for(name: &str) -> String {
+    slugify(name)
+}
+
+/// Returns the slug of each exported note's page by ID prefix, reading the
+/// notes' files for `export_slug`. Notes with `export: false` have no page
+/// and are left out.
+///
+/// # Errors
+///
+/// Fails if a note file can't be read.
+pub fn page_slugs(notes: &[IndexedNote], notes_dir: &Path) -> Result<HashMap<String, String>> {
+    let mut slugs = HashMap::with_capacity(notes.len());
+    for note in notes {
+        let file_path = notes_dir.join(note.path());
+        let parsed = read_note(&file_path)
+            .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+        if parsed.note.is_exported() {
+            slugs.insert(note.id().prefix(), page_slug(&parsed.note));
+        }
+    }
+    Ok(slugs)
+}
+
+/// Returns the template file named by a note's `export_template` field,
+/// relative to the notes directory. A name without an extension gets
+/// `.html`, so `.templates/special` means `.templates/special.html`.
+pub fn page_template(note: &Note, notes_dir: &Path) -> Option<PathBuf> {
+    let template = Path::new(note.export_template()?);
+    let path = notes_dir.join(template);
+    Some(if template.extension().is_none() {
+        path.with_extension("html")
+    } else {
+        path
+    })
+}
+
+/// Drops the notes whose frontmatter has `export: false`.
+///
+/// # Errors
+///
+/// Fails if a note file can't be read.
+pub fn retain_exported(notes: &mut Vec<IndexedNote>, notes_dir: &Path) -> Result<()> {
+    let mut exported = Vec::with_capacity(notes.len());
+    for note in notes.drain(..) {
+        let file_path = notes_dir.join(note.path());
+        let parsed = read_note(&file_path)
+            .with_context(|| format!("failed to read note: {}", file_path.display()))?;
+        if parsed.note.is_exported() {
+            exported.push(note);
+        }
+    }
+    *notes = exported;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::NoteId;
+    use chrono::{TimeZone, Utc};
+
+    fn note(extra: &[(&str, &str)]) -> Note {
+        let time = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        Note::builder(id, "Release Notes", time, time)
+            .extra(
+                extra
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), (*v).into()))
+                    .collect(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn page_slug_prefers_export_slug() {
+        assert_eq!(page_slug(&note(&[])), "release-notes");
+        assert_eq!(
+            page_slug(&note(&[("export_slug", "Changes 2024")])),
+            "changes-2024"
+        );
+    }
+
+    #[test]
+    fn page_template_is_relative_to_the_notes_directory() {
+        let dir = Path::new("/vault");
+
+        assert_eq!(page_template(&note(&[]), dir), None);
+        assert_eq!(
+            page_template(&note(&[("export_template", ".templates/special")]), dir),
+            Some(PathBuf::from("/vault/.templates/special.html"))
+        );
+        assert_eq!(
+            page_template(&note(&[("export_template", "wide.jinja")]), dir),
+            Some(PathBuf::from("/vault/wide.jinja"))
+        );
+    }
+}
@@ -1,17 +1,23 @@
 //! Static site generation for bulk note export.
 
 use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
 use minijinja::{context, Environment};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::domain::Note;
-use crate::export::html::markdown_to_html;
+use crate::export::assets::{AssetCopier, AssetOptions};
+use crate::export::exclude::ExportExclusions;
+use crate::export::html::MarkdownOptions;
+use crate::export::page::{page_slug, page_slug_for, page_template};
 use crate::export::theme::get_theme_css;
+use crate::export::toc::{render_body, TocOptions};
 use crate::index::IndexedNote;
-use crate::infra::{read_note, slugify};
+use crate::infra::read_note;
 
 // =============================================================================
 // JSON Index Types (for client-side filtering)
@@ -57,9 +63,24 @@ pub struct SiteIndex {
     pub tags: Vec<TagCount>,
 }
 
+/// Vault statistics published as `stats.json` and in the index footer.
+///
+/// Fields disabled in [`SiteStatsOptions`] are omitted.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(test, derive(serde::Deserialize))]
+pub struct SiteStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note_count: Option<usize>,
+    /// Most recent note modification time (RFC 3339).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_tags: Option<Vec<TagCount>>,
+}
+
 /// Default template for the site index page.
 pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
-<html lang="en" class="no-js">
+<html lang="{{ lang }}" dir="{{ dir }}" class="no-js">
 <head>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
@@ -69,13 +90,13 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
 </head>
 <body>
     <div class="site-layout">
-        <aside class="sidebar" id="sidebar" aria-label="Filters">
+        <aside class="sidebar" id="sidebar" aria-label="{{ labels.filters }}">
             <button class="sidebar-close" id="sidebar-close" aria-label="Close sidebar">&times;</button>
             <div class="sidebar-search">
-                <input type="search" id="search-input" placeholder="Search notes..." aria-label="Search notes">
+                <input type="search" id="search-input" placeholder="{{ labels.search_placeholder }}" aria-label="{{ labels.search_placeholder }}">
             </div>
             <nav class="sidebar-topics" aria-label="Filter by topic">
-                <h3>Topics</h3>
+                <h3>{{ labels.topics }}</h3>
                 <ul class="topic-tree" id="topic-tree">
                 {% for topic in all_topics %}
                     <li class="topic-item{% if topic.children %} has-children{% endif %}" data-topic="{{ topic.path }}">
@@ -96,7 +117,7 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
             </nav>
             {% if all_tags %}
             <nav class="sidebar-tags" aria-label="Filter by tag">
-                <h3>Tags</h3>
+                <h3>{{ labels.tags }}</h3>
                 <div class="tag-cloud" id="tag-cloud">
                 {% for tag in all_tags %}
                     <button class="tag-filter" data-tag="{{ tag.name }}">{{ tag.name }} <span class="count">({{ tag.count }})</span></button>
@@ -105,21 +126,21 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
             </nav>
             {% endif %}
             <div class="active-filters" id="active-filters" hidden>
-                <h3>Active Filters</h3>
-                <div class="filter-chips" id="filter-chips"></div>
-                <button class="clear-filters" id="clear-filters">Clear All</button>
+                <h3>{{ labels.active_filters }}</h3>
+                <div class="filter-chips" id="filter-chips" data-search="{{ labels.search }}" data-topic="{{ labels.topic }}" data-tag="{{ labels.tag }}"></div>
+                <button class="clear-filters" id="clear-filters">{{ labels.clear_filters }}</button>
             </div>
         </aside>
         <div class="main-content">
             <header>
-                <button class="sidebar-toggle" id="sidebar-toggle" aria-label="Open filters" aria-expanded="false">☰ Filters</button>
+                <button class="sidebar-toggle" id="sidebar-toggle" aria-label="Open filters" aria-expanded="false">☰ {{ labels.filters }}</button>
                 <h1>{{ site_title }}</h1>
-                <p class="note-count" id="note-count">{{ notes | length }} note{% if notes | length != 1 %}s{% endif %}</p>
+                <p class="note-count" id="note-count" data-one="{{ labels.note_count_one }}" data-other="{{ labels.note_count_other }}">{{ note_count }}</p>
             </header>
             <main>
                 {% if topics %}
                 <nav class="topics-nav no-js-only" aria-label="Browse by topic">
-                    <h2>Topics</h2>
+                    <h2>{{ labels.topics }}</h2>
                     <ul>
                     {% for topic in topics %}
                         <li><a href="{{ topic.path }}/index.html">{{ topic.name }} <span class="count">({{ topic.count }})</span></a></li>
@@ -128,8 +149,8 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
                 </nav>
                 {% endif %}
                 <section class="notes-list" aria-label="All notes">
-                    <h2 class="js-only">Notes</h2>
-                    <h2 class="no-js-only">All Notes</h2>
+                    <h2 class="js-only">{{ labels.notes }}</h2>
+                    <h2 class="no-js-only">{{ labels.all_notes }}</h2>
                     <ul id="notes-list">
                     {% for note in notes %}
                         <li class="note-item" data-id="{{ note.id }}" data-title="{{ note.title }}" data-description="{{ note.description }}" data-topics="{{ note.topics | join(',') }}" data-tags="{{ note.tags | join(',') }}">
@@ -139,11 +160,18 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
                         </li>
                     {% endfor %}
                     </ul>
-                    <p class="no-results" id="no-results" hidden>No notes match your filters.</p>
+                    <p class="no-results" id="no-results" hidden>{{ labels.no_results }}</p>
                 </section>
             </main>
             <footer>
-                <p>Generated with den</p>
+                {% if stats %}
+                <p class="site-stats" aria-label="Vault statistics">
+                    {% if stats_note_count %}<span>{{ stats_note_count }}</span>{% endif %}
+                    {% if stats.last_updated is defined %}<span>{{ labels.updated }} <time datetime="{{ stats.last_updated }}">{{ last_updated }}</time></span>{% endif %}
+                    {% if stats.top_tags %}<span>{{ labels.top_tags }}: {% for tag in stats.top_tags %}{{ tag.name }}{% if not loop.last %}, {% endif %}{% endfor %}</span>{% endif %}
+                </p>
+                {% endif %}
+                <p>{{ labels.generated_by }}</p>
             </footer>
         </div>
     </div>
@@ -154,7 +182,7 @@ pub const DEFAULT_INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
 
 /// Default template for topic index pages.
 pub const DEFAULT_TOPIC_TEMPLATE: &str = r##"<!DOCTYPE html>
-<html lang="en">
+<html lang="{{ lang }}" dir="{{ dir }}">
 <head>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
@@ -164,19 +192,19 @@ pub const DEFAULT_TOPIC_TEMPLATE: &str = r##"<!DOCTYPE html>
 <body>
     <header>
         <nav class="breadcrumb" aria-label="Breadcrumb">
-            <a href="{{ root_path }}index.html">Home</a>
+            <a href="{{ root_path }}index.html">{{ labels.home }}</a>
             {% for crumb in breadcrumbs %}
             <span aria-hidden="true">/</span>
             <a href="{{ crumb.path }}">{{ crumb.name }}</a>
             {% endfor %}
         </nav>
         <h1>{{ topic }}</h1>
-        <p class="note-count">{{ notes | length }} note{% if notes | length != 1 %}s{% endif %}</p>
+        <p class="note-count">{{ note_count }}</p>
     </header>
     <main>
         {% if subtopics %}
         <nav class="subtopics" aria-label="Subtopics">
-            <h2>Subtopics</h2>
+            <h2>{{ labels.subtopics }}</h2>
             <ul>
             {% for sub in subtopics %}
                 <li><a href="{{ sub.path }}/index.html">{{ sub.name }} <span class="count">({{ sub.count }})</span></a></li>
@@ -185,7 +213,7 @@ pub const DEFAULT_TOPIC_TEMPLATE: &str = r##"<!DOCTYPE html>
         </nav>
         {% endif %}
         <section class="notes-list" aria-label="Notes in this topic">
-            <h2>Notes</h2>
+            <h2>{{ labels.notes }}</h2>
             <ul>
             {% for note in notes %}
                 <li>
@@ -197,7 +225,7 @@ pub const DEFAULT_TOPIC_TEMPLATE: &str = r##"<!DOCTYPE html>
         </section>
     </main>
     <footer>
-        <a href="{{ root_path }}index.html">&larr; Back to index</a>
+        <a href="{{ root_path }}index.html">&larr; {{ labels.back_to_index }}</a>
     </footer>
 </body>
 </html>"##;
@@ -207,10 +235,11 @@ pub const DEFAULT_TOPIC_TEMPLATE: &str = r##"<!DOCTYPE html>
 /// Features:
 /// - Semantic HTML structure
 /// - highlight.js for syntax highlighting (auto light/dark via media queries)
+/// - MathJax for TeX math when the math extension is enabled
 /// - Breadcrumb navigation back to topics
 /// - Clean typography and metadata display
 pub const DEFAULT_SITE_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
-<html lang="en">
+<html lang="{{ lang }}" dir="{{ dir }}">
 <head>
     <meta charset="utf-8">
     <meta name="viewport" content="width=device-width, initial-scale=1">
@@ -221,10 +250,10 @@ pub const DEFAULT_SITE_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
     <link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/styles/github-dark.min.css" media="(prefers-color-scheme: dark)">
 </head>
 <body>
-    <article>
+    <article data-note-id="{{ id }}" data-note-title="{{ title|e }}">
         <header>
             <nav class="breadcrumb" aria-label="Breadcrumb">
-                <a href="index.html">Home</a>
+                <a href="index.html">{{ labels.home }}</a>
                 {% for topic in topics %}
                 <span aria-hidden="true">/</span>
                 <a href="{{ topic.path }}/index.html">{{ topic.name }}</a>
@@ -243,20 +272,48 @@ pub const DEFAULT_SITE_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
                 <time datetime="{{ created_iso }}">{{ created }}</time>
                 {% if modified != created %}
                 <span aria-hidden="true"> · </span>
-                <span>Updated <time datetime="{{ modified_iso }}">{{ modified }}</time></span>
+                <span>{{ labels.updated }} <time datetime="{{ modified_iso }}">{{ modified }}</time></span>
                 {% endif %}
             </div>
         </header>
         <main>
+            {% if toc %}{{ toc }}{% endif %}
             {{ content }}
         </main>
     </article>
     <footer>
-        <a href="index.html">&larr; Back to index</a>
+        <a href="index.html">&larr; {{ labels.back_to_index }}</a>
+        {% if source %}
+        <span aria-hidden="true"> · </span>
+        <a href="{{ source }}" class="view-source" type="text/markdown">{{ labels.view_source }}</a>
+        {% endif %}
     </footer>
     <!-- Syntax highlighting initialization -->
     <script src="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/highlight.min.js"></script>
     <script>hljs.highlightAll();</script>
+    {% if math %}
+    <!-- Math rendering -->
+    <script src="https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-chtml.js" async></script>
+    {% endif %}
+</body>
+</html>"##;
+
+/// Template for redirect stubs emitted for note aliases.
+///
+/// Uses a meta refresh with a script fallback so the redirect works with and
+/// without JavaScript, plus a canonical link so search engines index the target.
+pub const DEFAULT_REDIRECT_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="{{ lang }}" dir="{{ dir }}">
+<head>
+    <meta charset="utf-8">
+    <title>{{ title }} - {{ site_title }}</title>
+    <link rel="canonical" href="{{ target }}">
+    <meta http-equiv="refresh" content="0; url={{ target }}">
+    <meta name="robots" content="noindex">
+    <script>window.location.replace("{{ target }}" + window.location.hash);</script>
+</head>
+<body>
+    <p>{{ labels.page_moved }} <a href="{{ target }}">{{ title }}</a>.</p>
 </body>
 </html>"##;
 
@@ -325,7 +382,7 @@ pub const SIDEBAR_JS: &str = r##"// Sidebar interactive filtering for den static
         });
 
         // Update count and no-results message
-        const noun = visibleCount === 1 ? 'note' : 'notes';
+        const noun = visibleCount === 1 ? noteCount.dataset.one : noteCount.dataset.other;
         noteCount.textContent = visibleCount + ' ' + noun;
         noResults.hidden = visibleCount > 0;
 
@@ -344,7 +401,7 @@ pub const SIDEBAR_JS: &str = r##"// Sidebar interactive filtering for den static
         filterChips.innerHTML = '';
 
         if (searchQuery) {
-            const chip = createChip('search', '"' + searchQuery + '"', () => {
+            const chip = createChip(filterChips.dataset.search, '"' + searchQuery + '"', () => {
                 searchQuery = '';
                 searchInput.value = '';
                 filterNotes();
@@ -353,7 +410,7 @@ pub const SIDEBAR_JS: &str = r##"// Sidebar interactive filtering for den static
         }
 
         if (selectedTopic) {
-            const chip = createChip('topic', selectedTopic, () => {
+            const chip = createChip(filterChips.dataset.topic, selectedTopic, () => {
                 clearTopicSelection();
                 filterNotes();
             });
@@ -361,7 +418,7 @@ pub const SIDEBAR_JS: &str = r##"// Sidebar interactive filtering for den static
         }
 
         selectedTags.forEach(tag => {
-            const chip = createChip('tag', tag, () => {
+            const chip = createChip(filterChips.dataset.tag, tag, () => {
                 selectedTags.delete(tag);
                 const btn = tagCloud.querySelector('[data-tag="' + tag + '"]');
                 if (btn) btn.classList.remove('active');
@@ -372,10 +429,10 @@ pub const SIDEBAR_JS: &str = r##"// Sidebar interactive filtering for den static
     }
 
     // Create a filter chip element
-    function createChip(type, value, onRemove) {
+    function createChip(label, value, onRemove) {
         const chip = document.createElement('span');
         chip.className = 'filter-chip';
-        chip.innerHTML = '<span class="chip-label">' + type + ': ' + escapeHtml(value) + '</span><button class="chip-remove" aria-label="Remove filter">&times;</button>';
+        chip.innerHTML = '<span class="chip-label">' + escapeHtml(label) + ': ' + escapeHtml(value) + '</span><button class="chip-remove" aria-label="Remove filter">&times;</button>';
         chip.querySelector('.chip-remove').addEventListener('click', onRemove);
         return chip;
     }
@@ -556,6 +613,27 @@ pub struct SiteConfig<'a> {
     pub theme: Option<&'a str>,
     /// Custom template for note pages.
     pub note_template: Option<&'a Path>,
+    /// Markdown extensions to enable.
+    pub markdown: MarkdownOptions,
+    /// Vault statistics to publish, or `None` to skip `stats.json` and the footer widget.
+    pub stats: Option<SiteStatsOptions>,
+    /// Notes that are never published, even if they are passed in.
+    pub exclude: ExportExclusions,
+    /// Copy each note's markdown file next to its page and link to it.
+    pub include_source: bool,
+    /// Copy the local images notes reference into `assets/`, or `None` to
+    /// leave image references as they are.
+    pub assets: Option<AssetOptions>,
+    /// Table of contents to build for each note page, if any.
+    pub toc: Option<TocOptions>,
+    /// Language of the pages as a BCP 47 tag, for `<html lang>`.
+    pub lang: &'a str,
+    /// Lay pages out right to left.
+    pub rtl: bool,
+    /// strftime format of dates shown on pages.
+    pub date_format: &'a str,
+    /// Navigation labels, headings and other fixed text of the pages.
+    pub labels: SiteLabels,
 }
 
 impl Default for SiteConfig<'_> {
@@ -564,6 +642,139 @@ impl Default for SiteConfig<'_> {
             site_title: "Notes",
             theme: None,
             note_template: None,
+            markdown: MarkdownOptions::default(),
+            stats: None,
+            exclude: ExportExclusions::default(),
+            include_source: false,
+            assets: Some(AssetOptions::default()),
+            toc: None,
+            lang: "en",
+            rtl: false,
+            date_format: "%Y-%m-%d",
+            labels: SiteLabels::default(),
+        }
+    }
+}
+
+impl SiteConfig<'_> {
+    /// The `dir` attribute of the pages.
+    fn dir(&self) -> &'static str {
+        if self.rtl { "rtl" } else { "ltr" }
+    }
+
+    /// Formats a date shown on a page.
+    fn format_date(&self, date: DateTime<Utc>) -> Result<String> {
+        let mut formatted = String::new();
+        write!(formatted, "{}", date.format(self.date_format))
+            .map_err(|_| anyhow!("invalid date format '{}'", self.date_format))?;
+        Ok(formatted)
+    }
+}
+
+/// Fixed text of the site's pages, replaceable for sites not in English.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SiteLabels {
+    pub home: String,
+    pub notes: String,
+    pub all_notes: String,
+    /// Noun after a count of one note, e.g. "1 note".
+    pub note_count_one: String,
+    /// Noun after any other count of notes, e.g. "2 notes".
+    pub note_count_other: String,
+    pub topics: String,
+    pub subtopics: String,
+    pub tags: String,
+    pub search_placeholder: String,
+    pub filters: String,
+    pub active_filters: String,
+    pub clear_filters: String,
+    /// Name of the search filter among the active filters.
+    pub search: String,
+    /// Name of a topic filter among the active filters.
+    pub topic: String,
+    /// Name of a tag filter among the active filters.
+    pub tag: String,
+    pub no_results: String,
+    pub updated: String,
+    pub top_tags: String,
+    pub back_to_index: String,
+    pub view_source: String,
+    /// Text before the link on alias redirect pages.
+    pub page_moved: String,
+    pub generated_by: String,
+}
+
+impl Default for SiteLabels {
+    fn default() -> Self {
+        Self {
+            home: "Home".to_string(),
+            notes: "Notes".to_string(),
+            all_notes: "All Notes".to_string(),
+            note_count_one: "note".to_string(),
+            note_count_other: "notes".to_string(),
+            topics: "Topics".to_string(),
+            subtopics: "Subtopics".to_string(),
+            tags: "Tags".to_string(),
+            search_placeholder: "Search notes...".to_string(),
+            filters: "Filters".to_string(),
+            active_filters: "Active Filters".to_string(),
+            clear_filters: "Clear All".to_string(),
+            search: "search".to_string(),
+            topic: "topic".to_string(),
+            tag: "tag".to_string(),
+            no_results: "No notes match your filters.".to_string(),
+            updated: "Updated".to_string(),
+            top_tags: "Top tags".to_string(),
+            back_to_index: "Back to index".to_string(),
+            view_source: "View source".to_string(),
+            page_moved: "This page has moved to".to_string(),
+            generated_by: "Generated with den".to_string(),
+        }
+    }
+}
+
+impl SiteLabels {
+    /// A count of notes with its noun, e.g. "2 notes".
+    fn note_count(&self, count: usize) -> String {
+        let noun = if count == 1 {
+            &self.note_count_one
+        } else {
+            &self.note_count_other
+        };
+        format!("{} {}", count, noun)
+    }
+}
+
+/// Returns true if the language of BCP 47 tag `lang` is written right to
+/// left, e.g. Arabic or Hebrew.
+pub fn is_rtl_language(lang: &str) -> bool {
+    const RTL_LANGUAGES: &[&str] = &[
+        "ar", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "ug", "ur", "yi",
+    ];
+    let primary = lang.split(['-', '_']).next().unwrap_or_default();
+    RTL_LANGUAGES
+        .iter()
+        .any(|rtl| rtl.eq_ignore_ascii_case(primary))
+}
+
+/// Which fields to include in the published vault statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SiteStatsOptions {
+    /// Include the number of exported notes.
+    pub note_count: bool,
+    /// Include the most recent modification time.
+    pub last_updated: bool,
+    /// Number of most-used tags to include (0 to omit).
+    pub top_tags: usize,
+}
+
+impl Default for SiteStatsOptions {
+    fn default() -> Self {
+        Self {
+            note_count: true,
+            last_updated: true,
+            top_tags: 5,
         }
     }
 }
@@ -574,6 +785,10 @@ pub struct SiteResult {
     pub notes_exported: usize,
     /// Number of topic pages generated.
     pub topic_pages: usize,
+    /// Number of alias redirect stubs generated.
+    pub redirects: usize,
+    /// Number of images copied into `assets/`.
+    pub assets: usize,
 }
 
 /// Information about a note for template rendering.
@@ -624,13 +839,24 @@ pub fn generate_site(
     let mut note_infos: Vec<NoteInfo> = Vec::new();
     let mut topic_notes: BTreeMap<String, Vec<NoteInfo>> = BTreeMap::new();
     let mut tag_counts: HashMap<String, usize> = HashMap::new();
+    let mut alias_redirects: Vec<(String, NoteInfo)> = Vec::new();
+    let mut last_updated: Option<DateTime<Utc>> = None;
+
+    let notes: Vec<&IndexedNote> = notes
+        .iter()
+        .filter(|n| !config.exclude.excludes(n))
+        .collect();
+
+    let mut assets = config
+        .assets
+        .map(|options| AssetCopier::new(notes_dir, output_dir, options));
 
     // Export each note
-    for indexed_note in notes {
+    for indexed_note in &notes {
         let file_path = notes_dir.join(indexed_note.path());
         let parsed = read_note(&file_path)?;
 
-        let slug = slugify(parsed.note.title());
+        let slug = page_slug(&parsed.note);
         let topics_strs: Vec<String> = parsed.note.topics().iter().map(|t| t.to_string()).collect();
         let tags_strs: Vec<String> = parsed.note.tags().iter().map(|t| t.to_string()).collect();
 
@@ -647,6 +873,16 @@ pub fn generate_site(
 
         note_infos.push(note_info.clone());
 
+        let modified = parsed.note.modified();
+        if last_updated.is_none_or(|latest| modified > latest) {
+            last_updated = Some(modified);
+        }
+
+        // Remember aliases so old links can be redirected to this page
+        for alias in parsed.note.aliases() {
+            alias_redirects.push((page_slug_for(alias), note_info.clone()));
+        }
+
         // Count tags
         for tag in &tags_strs {
             *tag_counts.entry(tag.clone()).or_insert(0) += 1;
@@ -660,13 +896,38 @@ pub fn generate_site(
                 .push(note_info.clone());
         }
 
+        // Copy the markdown source, frontmatter and all
+        let source = if config.include_source {
+            let source = format!("{}.md", slug);
+            std::fs::copy(&file_path, output_dir.join(&source))?;
+            Some(source)
+        } else {
+            None
+        };
+
+        // Point images at their copies in assets/
+        let body = match &mut assets {
+            Some(assets) => assets.rewrite(&parsed.body, indexed_note.path(), "")?,
+            None => parsed.body,
+        };
+
         // Render note page
-        let html = render_site_note(&parsed.note, &parsed.body, config)?;
+        let template = page_template(&parsed.note, notes_dir);
+        let html = render_site_note(
+            &parsed.note,
+            &body,
+            source.as_deref(),
+            template.as_deref().or(config.note_template),
+            config,
+        )?;
         std::fs::write(output_dir.join(format!("{}.html", slug)), html)?;
     }
 
     // Sort notes by title
-    note_infos.sort_by(|a, b| a.title.to_lowercase().cmp(&b.title.to_lowercase()));
+    note_infos.sort_by_key(|a| a.title.to_lowercase());
+
+    // Write redirect stubs for aliases
+    let redirects = write_redirects(&alias_redirects, &note_infos, output_dir, config)?;
 
     // Build tag list sorted by count (descending), then name
     let mut all_tags: Vec<TagCount> = tag_counts
@@ -695,8 +956,26 @@ pub fn generate_site(
     // Write JSON index for client-side filtering
     write_index_json(&note_infos, &topic_notes, &all_tags, output_dir)?;
 
+    // Write vault statistics if requested
+    let stats = config
+        .stats
+        .map(|options| build_stats(&options, note_infos.len(), last_updated, &all_tags));
+    if let Some(stats) = &stats {
+        std::fs::write(
+            output_dir.join("stats.json"),
+            serde_json::to_string_pretty(stats)?,
+        )?;
+    }
+
     // Generate index page
-    let index_html = render_index(&note_infos, &top_topics, &topic_tree, &all_tags, config)?;
+    let index_html = render_index(
+        &note_infos,
+        &top_topics,
+        &topic_tree,
+        &all_tags,
+        stats.as_ref(),
+        config,
+    )?;
     std::fs::write(output_dir.join("index.html"), index_html)?;
 
     // Generate topic pages
@@ -758,9 +1037,50 @@ pub fn generate_site(
     Ok(SiteResult {
         notes_exported: notes.len(),
         topic_pages,
+        redirects,
+        assets: assets.map_or(0, |a| a.copied()),
     })
 }
 
+/// Writes a redirect stub for each alias slug that doesn't collide with a real page.
+///
+/// The first note to claim an alias slug wins; later claims are skipped so a
+/// redirect never points somewhere unexpected.
+fn write_redirects(
+    aliases: &[(String, NoteInfo)],
+    notes: &[NoteInfo],
+    output_dir: &Path,
+    config: &SiteConfig,
+) -> Result<usize> {
+    let mut taken: BTreeSet<&str> = notes.iter().map(|n| n.slug.as_str()).collect();
+    taken.insert("index");
+
+    // The `.html` name turns on autoescaping for titles
+    let mut env = Environment::new();
+    env.add_template("redirect.html", DEFAULT_REDIRECT_TEMPLATE)?;
+    let tmpl = env.get_template("redirect.html")?;
+
+    let mut written = 0;
+    for (alias_slug, target) in aliases {
+        if alias_slug.is_empty() || !taken.insert(alias_slug.as_str()) {
+            continue;
+        }
+
+        let html = tmpl.render(context! {
+            site_title => config.site_title,
+            lang => config.lang,
+            dir => config.dir(),
+            labels => &config.labels,
+            title => target.title,
+            target => format!("{}.html", target.slug),
+        })?;
+        std::fs::write(output_dir.join(format!("{}.html", alias_slug)), html)?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
 /// Builds a hierarchical topic tree for the sidebar.
 fn build_topic_tree(topic_notes: &BTreeMap<String, Vec<NoteInfo>>) -> Vec<TopicTreeItem> {
     let mut tree: Vec<TopicTreeItem> = Vec::new();
@@ -814,6 +1134,27 @@ fn build_topic_tree(topic_notes: &BTreeMap<String, Vec<NoteInfo>>) -> Vec<TopicT
     tree
 }
 
+/// Collects the vault statistics enabled in `options`.
+///
+/// `tags` must already be sorted by descending count.
+fn build_stats(
+    options: &SiteStatsOptions,
+    note_count: usize,
+    last_updated: Option<DateTime<Utc>>,
+    tags: &[TagCount],
+) -> SiteStats {
+    SiteStats {
+        note_count: options.note_count.then_some(note_count),
+        last_updated: if options.last_updated {
+            last_updated.map(|dt| dt.to_rfc3339())
+        } else {
+            None
+        },
+        top_tags: (options.top_tags > 0)
+            .then(|| tags.iter().take(options.top_tags).cloned().collect()),
+    }
+}
+
 /// Writes the JSON index file for client-side search and filtering.
 fn write_index_json(
     notes: &[NoteInfo],
@@ -878,12 +1219,20 @@ fn write_index_json(
     Ok(())
 }
 
-/// Renders a note page for the static site.
-fn render_site_note(note: &Note, body: &str, config: &SiteConfig) -> Result<String> {
-    let content = markdown_to_html(body);
+/// Renders a note page for the static site with `template`, or the default
+/// template if `None`, linking to its copied source if any.
+fn render_site_note(
+    note: &Note,
+    body: &str,
+    source: Option<&str>,
+    template: Option<&Path>,
+    config: &SiteConfig,
+) -> Result<String> {
+    let (content, toc, toc_entries) = render_body(body, &config.markdown, config.toc.as_ref());
 
-    let template_str = match config.note_template {
-        Some(p) => std::fs::read_to_string(p)?,
+    let template_str = match template {
+        Some(p) => std::fs::read_to_string(p)
+            .with_context(|| format!("failed to read template: {}", p.display()))?,
         None => DEFAULT_SITE_NOTE_TEMPLATE.to_string(),
     };
 
@@ -906,15 +1255,23 @@ fn render_site_note(note: &Note, body: &str, config: &SiteConfig) -> Result<Stri
 
     let html = tmpl.render(context! {
         site_title => config.site_title,
+        lang => config.lang,
+        dir => config.dir(),
+        labels => &config.labels,
+        id => note.id().to_string(),
         title => note.title(),
         description => note.description(),
         content => content,
+        toc => toc,
+        toc_entries => toc_entries,
+        math => config.markdown.math,
         topics => topics,
         tags => tags,
-        created => note.created().format("%Y-%m-%d").to_string(),
+        created => config.format_date(note.created())?,
         created_iso => note.created().to_rfc3339(),
-        modified => note.modified().format("%Y-%m-%d").to_string(),
+        modified => config.format_date(note.modified())?,
         modified_iso => note.modified().to_rfc3339(),
+        source => source,
     })?;
 
     Ok(html)
@@ -926,6 +1283,7 @@ fn render_index(
     topics: &[TopicInfo],
     topic_tree: &[TopicTreeItem],
     all_tags: &[TagCount],
+    stats: Option<&SiteStats>,
     config: &SiteConfig,
 ) -> Result<String> {
     let mut env = Environment::new();
@@ -994,12 +1352,27 @@ fn render_index(
         })
         .collect();
 
+    let last_updated = stats
+        .and_then(|s| s.last_updated.as_deref())
+        .and_then(|dt| DateTime::parse_from_rfc3339(dt).ok())
+        .map(|dt| config.format_date(dt.to_utc()))
+        .transpose()?;
+
     let html = tmpl.render(context! {
         site_title => config.site_title,
+        lang => config.lang,
+        dir => config.dir(),
+        labels => &config.labels,
+        note_count => config.labels.note_count(notes.len()),
         notes => notes_json,
         topics => topics_json,
         all_topics => topic_tree_json,
         all_tags => tags_json,
+        stats => stats,
+        stats_note_count => stats
+            .and_then(|s| s.note_count)
+            .map(|n| config.labels.note_count(n)),
+        last_updated => last_updated,
     })?;
 
     Ok(html)
@@ -1042,7 +1415,11 @@ fn render_topic_page(
 
     let html = tmpl.render(context! {
         site_title => config.site_title,
+        lang => config.lang,
+        dir => config.dir(),
+        labels => &config.labels,
         topic => topic,
+        note_count => config.labels.note_count(notes.len()),
         notes => notes_json,
         subtopics => subtopics_json,
         breadcrumbs => breadcrumbs,
@@ -1056,7 +1433,7 @@ fn render_topic_page(
 mod tests {
     use super::*;
     use crate::domain::{NoteId, Topic};
-    use crate::infra::ContentHash;
+    use crate::infra::{ContentHash, slugify};
     use chrono::Utc;
     use tempfile::TempDir;
 
@@ -1147,6 +1524,41 @@ mod tests {
         assert!(temp_dir.path().join("software").join("python").exists());
     }
 
+    #[test]
+    fn test_site_copies_markdown_source_when_requested() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note(notes_dir.path(), "Source Note", "# Hi\n\nRaw *text*", &[]);
+        let original = std::fs::read_to_string(notes_dir.path().join(indexed.path())).unwrap();
+
+        let config = SiteConfig {
+            include_source: true,
+            ..SiteConfig::default()
+        };
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        let copied = std::fs::read_to_string(temp_dir.path().join("source-note.md")).unwrap();
+        assert_eq!(copied, original);
+        let page = std::fs::read_to_string(temp_dir.path().join("source-note.html")).unwrap();
+        assert!(page.contains("href=\"source-note.md\""));
+    }
+
+    #[test]
+    fn test_site_omits_markdown_source_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note(notes_dir.path(), "Source Note", "Body", &[]);
+
+        let config = SiteConfig::default();
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        assert!(!temp_dir.path().join("source-note.md").exists());
+        let page = std::fs::read_to_string(temp_dir.path().join("source-note.html")).unwrap();
+        assert!(!page.contains("View source"));
+    }
+
     #[test]
     fn test_index_page_contains_notes() {
         let temp_dir = TempDir::new().unwrap();
@@ -1173,6 +1585,7 @@ mod tests {
             site_title: "Dark Site",
             theme: Some("dark"),
             note_template: None,
+            ..Default::default()
         };
         generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
 
@@ -1280,6 +1693,113 @@ mod tests {
         assert!(index.tags.iter().any(|t| t.name == "important" && t.count == 1));
     }
 
+    #[test]
+    fn test_site_writes_redirects_for_aliases() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let id = NoteId::new();
+        let now = Utc::now();
+        let note = Note::builder(id.clone(), "Rust Ownership", now, now)
+            .aliases(vec!["Borrow Checker".to_string(), "Rust Ownership".to_string()])
+            .build()
+            .unwrap();
+        let filename = format!("{}-rust-ownership.md", id.prefix());
+        crate::infra::write_note(&notes_dir.path().join(&filename), &note, "Body").unwrap();
+        let indexed = IndexedNote::builder(
+            id,
+            "Rust Ownership",
+            now,
+            now,
+            filename.into(),
+            ContentHash::compute(b"x"),
+        )
+        .build();
+
+        let config = SiteConfig::default();
+        let result = generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        // The alias matching the note's own slug must not overwrite the page
+        assert_eq!(result.redirects, 1);
+        let stub = std::fs::read_to_string(temp_dir.path().join("borrow-checker.html")).unwrap();
+        assert!(stub.contains(r#"url=rust-ownership.html"#));
+        assert!(stub.contains(r#"rel="canonical" href="rust-ownership.html""#));
+
+        let page = std::fs::read_to_string(temp_dir.path().join("rust-ownership.html")).unwrap();
+        assert!(!page.contains("http-equiv=\"refresh\""));
+    }
+
+    #[test]
+    fn test_site_alias_redirects_skip_the_export_slug_page() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let id = NoteId::new();
+        let now = Utc::now();
+        let note = Note::builder(id.clone(), "Release Notes", now, now)
+            .aliases(vec!["Changes".to_string()])
+            .extra(
+                [("export_slug".to_string(), "Changes".into())]
+                    .into_iter()
+                    .collect(),
+            )
+            .build()
+            .unwrap();
+        let filename = format!("{}-release-notes.md", id.prefix());
+        crate::infra::write_note(&notes_dir.path().join(&filename), &note, "Body").unwrap();
+        let indexed = IndexedNote::builder(
+            id,
+            "Release Notes",
+            now,
+            now,
+            filename.into(),
+            ContentHash::compute(b"x"),
+        )
+        .build();
+
+        let config = SiteConfig::default();
+        let result = generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        // The alias names the page itself, so no stub may replace it
+        assert_eq!(result.redirects, 0);
+        let page = std::fs::read_to_string(temp_dir.path().join("changes.html")).unwrap();
+        assert!(!page.contains("http-equiv=\"refresh\""));
+    }
+
+    #[test]
+    fn test_site_redirects_escape_titles() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let id = NoteId::new();
+        let now = Utc::now();
+        let title = r#"Say "hi" <script>"#;
+        let note = Note::builder(id.clone(), title, now, now)
+            .aliases(vec!["Greeting".to_string()])
+            .build()
+            .unwrap();
+        let filename = format!("{}-say-hi.md", id.prefix());
+        crate::infra::write_note(&notes_dir.path().join(&filename), &note, "Body").unwrap();
+        let indexed = IndexedNote::builder(
+            id,
+            title,
+            now,
+            now,
+            filename.into(),
+            ContentHash::compute(b"x"),
+        )
+        .build();
+
+        let config = SiteConfig::default();
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        let stub = std::fs::read_to_string(temp_dir.path().join("greeting.html")).unwrap();
+        assert!(!stub.contains("<script>\""));
+        assert!(!stub.contains(r#""hi""#));
+        assert!(stub.contains("Say &quot;hi&quot; &lt;script&gt;"));
+        assert!(stub.contains("url=say-hi-script.html"));
+    }
+
     #[test]
     fn test_index_page_shows_tags_in_sidebar() {
         let temp_dir = TempDir::new().unwrap();
@@ -1303,4 +1823,173 @@ mod tests {
         assert!(index_content.contains(r#"data-tag="draft""#));
         assert!(index_content.contains(r#"data-tag="review""#));
     }
+
+    #[test]
+    fn test_site_stats_not_written_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note(notes_dir.path(), "Test Note", "Content", &[]);
+
+        let config = SiteConfig::default();
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        assert!(!temp_dir.path().join("stats.json").exists());
+        let index_content = std::fs::read_to_string(temp_dir.path().join("index.html")).unwrap();
+        assert!(!index_content.contains("site-stats"));
+    }
+
+    #[test]
+    fn test_site_stats_json_and_footer() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let first =
+            create_test_note_with_tags(notes_dir.path(), "First", "Content", &[], &["rust", "cli"]);
+        let second =
+            create_test_note_with_tags(notes_dir.path(), "Second", "Content", &[], &["rust"]);
+
+        let config = SiteConfig {
+            stats: Some(SiteStatsOptions {
+                top_tags: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        generate_site(&[first, second], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        let json_content = std::fs::read_to_string(temp_dir.path().join("stats.json")).unwrap();
+        let stats: SiteStats = serde_json::from_str(&json_content).unwrap();
+        assert_eq!(stats.note_count, Some(2));
+        let top_tags = stats.top_tags.unwrap();
+        assert_eq!(top_tags.len(), 1);
+        assert_eq!(top_tags[0].name, "rust");
+        assert_eq!(top_tags[0].count, 2);
+        let last_updated = stats.last_updated.unwrap();
+
+        let index_content = std::fs::read_to_string(temp_dir.path().join("index.html")).unwrap();
+        assert!(index_content.contains(r#"class="site-stats""#));
+        assert!(index_content.contains("<span>2 notes</span>"));
+        assert!(index_content.contains(&format!(r#"datetime="{}""#, last_updated)));
+        assert!(index_content.contains(&format!(">{}</time>", &last_updated[..10])));
+        assert!(index_content.contains("Top tags: rust</span>"));
+    }
+
+    #[test]
+    fn test_site_stats_omits_disabled_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note_with_tags(notes_dir.path(), "Only", "Content", &[], &["a"]);
+
+        let config = SiteConfig {
+            stats: Some(SiteStatsOptions {
+                note_count: true,
+                last_updated: false,
+                top_tags: 0,
+            }),
+            ..Default::default()
+        };
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        let json_content = std::fs::read_to_string(temp_dir.path().join("stats.json")).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json_content).unwrap();
+        assert_eq!(value, serde_json::json!({ "note_count": 1 }));
+
+        let index_content = std::fs::read_to_string(temp_dir.path().join("index.html")).unwrap();
+        assert!(index_content.contains("<span>1 note</span>"));
+        assert!(!index_content.contains("Updated"));
+        assert!(!index_content.contains("Top tags"));
+    }
+
+    #[test]
+    fn test_generate_site_skips_excluded_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let public = create_test_note(notes_dir.path(), "Public Note", "Content", &["work"]);
+        let private = create_test_note(notes_dir.path(), "Diary", "Secret", &["personal/journal"]);
+
+        let config = SiteConfig {
+            exclude: ExportExclusions::new(&["personal/".to_string()], &[], &[]).unwrap(),
+            ..Default::default()
+        };
+        let result = generate_site(
+            &[public, private],
+            temp_dir.path(),
+            notes_dir.path(),
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(result.notes_exported, 1);
+        assert!(temp_dir.path().join("public-note.html").exists());
+        assert!(!temp_dir.path().join("diary.html").exists());
+        assert!(!temp_dir.path().join("personal").exists());
+
+        let index_json = std::fs::read_to_string(temp_dir.path().join("index.json")).unwrap();
+        assert!(!index_json.contains("Diary"));
+    }
+
+    #[test]
+    fn test_site_uses_language_labels_and_date_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note(notes_dir.path(), "Notiz", "Inhalt", &["arbeit"]);
+        let created = indexed.created().format("%d.%m.%Y").to_string();
+
+        let config = SiteConfig {
+            lang: "he",
+            rtl: true,
+            date_format: "%d.%m.%Y",
+            labels: SiteLabels {
+                home: "Start".to_string(),
+                note_count_one: "Notiz".to_string(),
+                note_count_other: "Notizen".to_string(),
+                ..SiteLabels::default()
+            },
+            ..SiteConfig::default()
+        };
+        generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config).unwrap();
+
+        let index = std::fs::read_to_string(temp_dir.path().join("index.html")).unwrap();
+        assert!(index.contains(r#"<html lang="he" dir="rtl""#));
+        assert!(index.contains(">1 Notiz</p>"));
+        assert!(index.contains(r#"data-other="Notizen""#));
+
+        let topic = std::fs::read_to_string(temp_dir.path().join("arbeit/index.html")).unwrap();
+        assert!(topic.contains(">Start</a>"));
+
+        let page = std::fs::read_to_string(temp_dir.path().join("notiz.html")).unwrap();
+        assert!(page.contains(">Start</a>"));
+        assert!(page.contains(&format!(">{}</time>", created)));
+    }
+
+    #[test]
+    fn test_site_rejects_invalid_date_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let notes_dir = TempDir::new().unwrap();
+
+        let indexed = create_test_note(notes_dir.path(), "Note", "Body", &[]);
+
+        let config = SiteConfig {
+            date_format: "%Q",
+            ..SiteConfig::default()
+        };
+        let Err(err) = generate_site(&[indexed], temp_dir.path(), notes_dir.path(), &config)
+        else {
+            panic!("invalid date format should fail");
+        };
+        assert!(err.to_string().contains("invalid date format '%Q'"));
+    }
+
+    #[test]
+    fn test_is_rtl_language() {
+        assert!(is_rtl_language("ar"));
+        assert!(is_rtl_language("he-IL"));
+        assert!(is_rtl_language("FA"));
+        assert!(!is_rtl_language("en"));
+        assert!(!is_rtl_language("de-AT"));
+    }
 }
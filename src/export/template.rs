@@ -6,14 +6,16 @@ use anyhow::Result;
 use minijinja::{context, Environment};
 
 use crate::domain::Note;
-use crate::export::html::markdown_to_html;
+use crate::export::html::MarkdownOptions;
 use crate::export::theme::get_theme_css;
+use crate::export::toc::{render_body, TocOptions};
 
 /// Default HTML template for single note export.
 ///
 /// Features:
 /// - Semantic HTML structure with article, header, main
 /// - highlight.js for syntax highlighting (auto light/dark via media queries)
+/// - MathJax for TeX math when the math extension is enabled
 /// - Clean typography hierarchy
 /// - Responsive design
 pub const DEFAULT_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
@@ -28,7 +30,7 @@ pub const DEFAULT_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
     <link rel="stylesheet" href="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/styles/github-dark.min.css" media="(prefers-color-scheme: dark)">
 </head>
 <body>
-    <article>
+    <article data-note-id="{{ id }}" data-note-title="{{ title|e }}">
         <header>
             {% if topics %}
             <nav class="topics" aria-label="Topics">
@@ -53,12 +55,17 @@ pub const DEFAULT_NOTE_TEMPLATE: &str = r##"<!DOCTYPE html>
             </div>
         </header>
         <main>
+            {% if toc %}{{ toc }}{% endif %}
             {{ content }}
         </main>
     </article>
     <!-- Syntax highlighting initialization -->
     <script src="https://cdnjs.cloudflare.com/ajax/libs/highlight.js/11.9.0/highlight.min.js"></script>
     <script>hljs.highlightAll();</script>
+    {% if math %}
+    <!-- Math rendering -->
+    <script src="https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-chtml.js" async></script>
+    {% endif %}
 </body>
 </html>"##;
 
@@ -73,6 +80,10 @@ pub struct RenderOptions<'a> {
     pub theme: Option<&'a str>,
     /// Link resolver for resolving internal note references.
     pub link_resolver: Option<&'a LinkResolver<'a>>,
+    /// Markdown extensions to enable.
+    pub markdown: MarkdownOptions,
+    /// Table of contents to build from the note's headings, if any.
+    pub toc: Option<TocOptions>,
 }
 
 /// Renders a note to a complete HTML document.
@@ -93,7 +104,8 @@ pub fn render_note_html(note: &Note, body: &str, options: &RenderOptions) -> Res
         None => body.to_string(),
     };
 
-    let content = markdown_to_html(&resolved_body);
+    let (content, toc, toc_entries) =
+        render_body(&resolved_body, &options.markdown, options.toc.as_ref());
     let theme_css = get_theme_css(options.theme)?;
 
     let template_str = match options.template_path {
@@ -109,9 +121,13 @@ pub fn render_note_html(note: &Note, body: &str, options: &RenderOptions) -> Res
     let tags: Vec<&str> = note.tags().iter().map(|t| t.as_str()).collect();
 
     let html = tmpl.render(context! {
+        id => note.id().to_string(),
         title => note.title(),
         description => note.description(),
         content => content,
+        toc => toc,
+        toc_entries => toc_entries,
+        math => options.markdown.math,
         theme_css => theme_css,
         topics => topics,
         tags => tags,
@@ -219,6 +235,18 @@ mod tests {
         assert!(html.contains("datetime=\"2024-01-15"));
     }
 
+    #[test]
+    fn test_template_exposes_id_and_title_for_print_headers() {
+        let note = make_note("Tom & \"Jerry\"");
+        let body = "";
+
+        let html = render_note_html(&note, body, &RenderOptions::default()).unwrap();
+
+        assert!(html.contains(&format!("data-note-id=\"{}\"", note.id())));
+        assert!(html.contains("data-note-title=\"Tom &amp; &quot;Jerry&quot;\""));
+        assert!(html.contains("string(note-id)"));
+    }
+
     #[test]
     fn test_custom_template() {
         let note = make_note("Custom Template Test");
@@ -231,6 +259,7 @@ mod tests {
             template_path: Some(temp.path()),
             theme: None,
             link_resolver: None,
+            ..Default::default()
         };
 
         let html = render_note_html(&note, body, &options).unwrap();
@@ -248,6 +277,7 @@ mod tests {
             template_path: None,
             theme: Some("dark"),
             link_resolver: None,
+            ..Default::default()
         };
 
         let html = render_note_html(&note, body, &options).unwrap();
@@ -278,6 +308,26 @@ mod tests {
         assert!(html.contains("width=device-width"));
     }
 
+    #[test]
+    fn test_template_includes_mathjax_only_when_math_enabled() {
+        let note = make_note("Math Test");
+        let body = "Area is $\\pi r^2$.";
+
+        let html = render_note_html(&note, body, &RenderOptions::default()).unwrap();
+        assert!(!html.contains("mathjax"));
+
+        let options = RenderOptions {
+            markdown: MarkdownOptions {
+                math: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let html = render_note_html(&note, body, &options).unwrap();
+        assert!(html.contains("mathjax@3"));
+        assert!(html.contains(r#"<span class="math math-inline">\(\pi r^2\)</span>"#));
+    }
+
     #[test]
     fn test_template_escapes_title() {
         let id = NoteId::new();
@@ -292,4 +342,40 @@ mod tests {
         // Title should be escaped in the <title> tag
         assert!(html.contains("Test &amp; Notes") || html.contains("Test & Notes"));
     }
+
+    #[test]
+    fn test_toc_rendered_above_content() {
+        let note = make_note("TOC Test");
+        let body = "## First\n\nText\n\n## Second";
+        let options = RenderOptions {
+            toc: Some(TocOptions::default()),
+            ..Default::default()
+        };
+
+        let html = render_note_html(&note, body, &options).unwrap();
+
+        let toc = html.find("<nav class=\"toc\"").unwrap();
+        assert!(toc < html.find("<h2 id=\"first\">").unwrap());
+        assert!(html.contains("<a href=\"#second\">Second</a>"));
+    }
+
+    #[test]
+    fn test_custom_template_gets_toc_entries() {
+        let mut temp = NamedTempFile::new().unwrap();
+        write!(
+            temp,
+            "{{% for entry in toc_entries %}}[{{{{ entry.level }}}}:{{{{ entry.anchor }}}}]{{% endfor %}}"
+        )
+        .unwrap();
+        let note = make_note("Entries");
+        let options = RenderOptions {
+            template_path: Some(temp.path()),
+            toc: Some(TocOptions::default()),
+            ..Default::default()
+        };
+
+        let html = render_note_html(&note, "# One\n\n## Two", &options).unwrap();
+
+        assert_eq!(html, "[1:one][2:two]");
+    }
 }
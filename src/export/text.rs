@@ -0,0 +1,700 @@
+//! Plain-text rendering for terminals, pagers and email.
+//!
+//! Converts markdown into hard-wrapped text without markup: headings are
+//! underlined, emphasis markers are dropped, code blocks are indented and
+//! links become numbered references listed at the end of the document.
+
+use pulldown_cmark::{Event, HeadingLevel, LinkType, Options, Parser, Tag};
+
+use crate::domain::Note;
+use crate::export::html::MarkdownOptions;
+use crate::infra::profile::{self, Phase};
+
+/// Default line width for plain-text output.
+pub const DEFAULT_TEXT_WIDTH: usize = 80;
+
+/// Narrowest width text is wrapped to, however deeply it is nested.
+const MIN_WRAP_WIDTH: usize = 20;
+
+/// Stands in for a soft line break until a block is wrapped, so a callout
+/// title can still be split from its first line of content.
+const SOFT_BREAK: char = '\u{E002}';
+
+/// Options for plain-text rendering.
+#[derive(Debug, Clone, Copy)]
+pub struct TextOptions {
+    /// Maximum line width for wrapped text.
+    pub width: usize,
+    /// Markdown extensions to recognize.
+    pub markdown: MarkdownOptions,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_TEXT_WIDTH,
+            markdown: MarkdownOptions::default(),
+        }
+    }
+}
+
+/// Renders a note as plain text with its title, topics and tags as a header.
+///
+/// A leading `# Heading` that repeats the note title is omitted.
+pub fn render_note_text(note: &Note, body: &str, options: &TextOptions) -> String {
+    profile::time(Phase::Render, || render_text(note, body, options))
+}
+
+fn render_text(note: &Note, body: &str, options: &TextOptions) -> String {
+    let mut out = String::new();
+    out.push_str(note.title());
+    out.push('\n');
+    out.push_str(&"=".repeat(note.title().chars().count()));
+    out.push('\n');
+
+    let mut meta = Vec::new();
+    if let Some(desc) = note.description() {
+        meta.push(desc.to_string());
+    }
+    if !note.topics().is_empty() {
+        let topics: Vec<_> = note.topics().iter().map(|t| t.to_string()).collect();
+        meta.push(format!("Topics: {}", topics.join(", ")));
+    }
+    if !note.tags().is_empty() {
+        let tags: Vec<_> = note.tags().iter().map(|t| t.as_str()).collect();
+        meta.push(format!("Tags: {}", tags.join(", ")));
+    }
+    if !meta.is_empty() {
+        out.push('\n');
+        for line in meta {
+            for wrapped in wrap(&line, options.width) {
+                out.push_str(&wrapped);
+                out.push('\n');
+            }
+        }
+    }
+
+    let text = markdown_to_text_impl(body, options, Some(note.title()));
+    if !text.is_empty() {
+        out.push('\n');
+        out.push_str(&text);
+    }
+    out
+}
+
+/// Converts markdown to plain text.
+pub fn markdown_to_text(markdown: &str, options: &TextOptions) -> String {
+    markdown_to_text_impl(markdown, options, None)
+}
+
+fn markdown_to_text_impl(markdown: &str, options: &TextOptions, title: Option<&str>) -> String {
+    let mut parser_options = Options::empty();
+    parser_options.insert(Options::ENABLE_TABLES);
+    parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+    if options.markdown.footnotes {
+        parser_options.insert(Options::ENABLE_FOOTNOTES);
+    }
+    if options.markdown.task_lists {
+        parser_options.insert(Options::ENABLE_TASKLISTS);
+    }
+
+    let mut renderer = TextRenderer::new(options, title);
+    for event in Parser::new_ext(markdown, parser_options) {
+        renderer.event(event);
+    }
+    renderer.finish()
+}
+
+/// A block that prefixes the lines inside it.
+enum Container {
+    Quote {
+        /// True until the first block of the quote has been written.
+        first_block: bool,
+    },
+    Item {
+        marker: String,
+        /// True until the marker has been written on a line.
+        marker_pending: bool,
+    },
+}
+
+struct TextRenderer<'a> {
+    options: &'a TextOptions,
+    /// Title whose repetition as the first heading is skipped.
+    title: Option<&'a str>,
+    out: String,
+    containers: Vec<Container>,
+    /// Next number for each open list, `None` for bullet lists.
+    lists: Vec<Option<u64>>,
+    inline: String,
+    code_block: Option<String>,
+    /// Open links and images: URL and where their text starts in `inline`.
+    link_stack: Vec<(String, usize)>,
+    /// Reference URLs in order of first use.
+    references: Vec<String>,
+    table: Option<Vec<Vec<String>>>,
+    pending_blank: bool,
+    first_block: bool,
+}
+
+impl<'a> TextRenderer<'a> {
+    fn new(options: &'a TextOptions, title: Option<&'a str>) -> Self {
+        Self {
+            options,
+            title,
+            out: String::new(),
+            containers: Vec::new(),
+            lists: Vec::new(),
+            inline: String::new(),
+            code_block: None,
+            link_stack: Vec::new(),
+            references: Vec::new(),
+            table: None,
+            pending_blank: false,
+            first_block: true,
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) => match &mut self.code_block {
+                Some(code) => code.push_str(&text),
+                None => self.inline.push_str(&text),
+            },
+            Event::Code(code) => self.inline.push_str(&code),
+            Event::SoftBreak => self.inline.push(SOFT_BREAK),
+            Event::HardBreak => self.inline.push('\n'),
+            Event::Rule => {
+                let width = self.available_width().min(self.options.width);
+                self.write_block(vec!["-".repeat(width)], false);
+            }
+            Event::TaskListMarker(checked) => {
+                self.inline.push_str(if checked { "[x] " } else { "[ ] " });
+            }
+            Event::FootnoteReference(label) => {
+                self.inline.push_str(&format!("[{}]", label));
+            }
+            Event::Html(_) => {}
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::BlockQuote => {
+                self.flush_tight();
+                self.containers.push(Container::Quote { first_block: true });
+            }
+            Tag::CodeBlock(_) => {
+                self.flush_tight();
+                self.code_block = Some(String::new());
+            }
+            Tag::List(start) => {
+                if !self.flush_tight() && self.lists.is_empty() {
+                    self.pending_blank = true;
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush_tight();
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        let marker = format!("  {}. ", n);
+                        *n += 1;
+                        marker
+                    }
+                    _ => "  * ".to_string(),
+                };
+                self.containers.push(Container::Item {
+                    marker,
+                    marker_pending: true,
+                });
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.flush_tight();
+                self.inline.push_str(&format!("[{}] ", label));
+            }
+            Tag::Table(_) => self.table = Some(Vec::new()),
+            Tag::TableHead | Tag::TableRow => {
+                if let Some(rows) = &mut self.table {
+                    rows.push(Vec::new());
+                }
+            }
+            Tag::Link(_, url, _) | Tag::Image(_, url, _) => {
+                self.link_stack.push((url.to_string(), self.inline.len()));
+            }
+            Tag::Paragraph
+            | Tag::Heading(..)
+            | Tag::TableCell
+            | Tag::Emphasis
+            | Tag::Strong
+            | Tag::Strikethrough => {}
+        }
+    }
+
+    fn end(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => {
+                let text = std::mem::take(&mut self.inline);
+                let text = self.strip_callout_marker(text);
+                self.write_block(vec![text], true);
+                self.pending_blank = true;
+            }
+            Tag::Heading(level, _, _) => {
+                let text = std::mem::take(&mut self.inline)
+                    .replace(SOFT_BREAK, " ")
+                    .trim()
+                    .to_string();
+                if self.first_block
+                    && level == HeadingLevel::H1
+                    && Some(text.as_str()) == self.title
+                {
+                    self.first_block = false;
+                    return;
+                }
+                let underline = match level {
+                    HeadingLevel::H1 => Some('='),
+                    HeadingLevel::H2 => Some('-'),
+                    HeadingLevel::H3 => Some('~'),
+                    _ => None,
+                };
+                let mut lines = vec![text.clone()];
+                if let Some(c) = underline {
+                    let len = text.chars().count().min(self.available_width());
+                    lines.push(c.to_string().repeat(len));
+                }
+                self.write_block(lines, false);
+                self.pending_blank = true;
+            }
+            Tag::BlockQuote => {
+                self.flush_tight();
+                self.containers.pop();
+                self.pending_blank = true;
+            }
+            Tag::CodeBlock(_) => {
+                let code = self.code_block.take().unwrap_or_default();
+                let lines: Vec<String> = code
+                    .trim_end_matches('\n')
+                    .lines()
+                    .map(|l| format!("    {}", l).trim_end().to_string())
+                    .collect();
+                if !lines.is_empty() {
+                    self.write_block(lines, false);
+                }
+                self.pending_blank = true;
+            }
+            Tag::List(_) => {
+                self.flush_tight();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.pending_blank = true;
+                }
+            }
+            Tag::Item => {
+                self.flush_tight();
+                self.containers.pop();
+            }
+            Tag::FootnoteDefinition(_) => {
+                self.flush_tight();
+                self.pending_blank = true;
+            }
+            Tag::TableCell => {
+                let text = std::mem::take(&mut self.inline)
+                    .replace(SOFT_BREAK, " ")
+                    .trim()
+                    .to_string();
+                if let Some(row) = self.table.as_mut().and_then(|rows| rows.last_mut()) {
+                    row.push(text);
+                }
+            }
+            Tag::Table(_) => {
+                if let Some(rows) = self.table.take() {
+                    let lines = render_table(&rows);
+                    self.write_block(lines, false);
+                    self.pending_blank = true;
+                }
+            }
+            Tag::Link(link_type, _, _) => self.close_link(link_type, false),
+            Tag::Image(link_type, _, _) => self.close_link(link_type, true),
+            Tag::TableHead | Tag::TableRow | Tag::Emphasis | Tag::Strong | Tag::Strikethrough => {}
+        }
+    }
+
+    /// Replaces a link's text with `text[n]` and records its URL.
+    fn close_link(&mut self, link_type: LinkType, image: bool) {
+        let Some((url, start)) = self.link_stack.pop() else {
+            return;
+        };
+        let text = self.inline.split_off(start.min(self.inline.len()));
+
+        let autolink = matches!(link_type, LinkType::Autolink | LinkType::Email) || text == url;
+        if url.is_empty() || url.starts_with('#') || (autolink && !image) {
+            self.inline.push_str(&text);
+            return;
+        }
+
+        let number = match self.references.iter().position(|u| *u == url) {
+            Some(i) => i + 1,
+            None => {
+                self.references.push(url);
+                self.references.len()
+            }
+        };
+
+        if image {
+            let alt = if text.is_empty() { "image" } else { &text };
+            self.inline
+                .push_str(&format!("[image: {}][{}]", alt, number));
+        } else {
+            self.inline.push_str(&format!("{}[{}]", text, number));
+        }
+    }
+
+    /// Turns a callout marker (`[!note] Title`) at the start of a quote
+    /// into a plain label (`Note: Title`).
+    fn strip_callout_marker(&self, text: String) -> String {
+        if !self.options.markdown.callouts {
+            return text;
+        }
+        let Some(Container::Quote { first_block: true }) = self.containers.last() else {
+            return text;
+        };
+        let Some(rest) = text.strip_prefix("[!") else {
+            return text;
+        };
+        let Some(end) = rest.find(']') else {
+            return text;
+        };
+
+        let kind = &rest[..end];
+        let title = rest[end + 1..].trim_start_matches(['+', '-']).trim();
+        let mut label: String = kind.chars().take(1).flat_map(char::to_uppercase).collect();
+        label.push_str(&kind.chars().skip(1).collect::<String>().to_lowercase());
+
+        if title.is_empty() {
+            label
+        } else if let Some((first, body)) = title.split_once(['\n', SOFT_BREAK]) {
+            format!("{}: {}\n{}", label, first.trim(), body)
+        } else {
+            format!("{}: {}", label, title)
+        }
+    }
+
+    /// Writes text left over in a tight list item or footnote.
+    ///
+    /// Returns true if anything was written.
+    fn flush_tight(&mut self) -> bool {
+        if self.inline.trim().is_empty() {
+            self.inline.clear();
+            return false;
+        }
+        let text = std::mem::take(&mut self.inline);
+        self.write_block(vec![text], true);
+        true
+    }
+
+    /// Width left for text after the container prefixes.
+    fn available_width(&self) -> usize {
+        let prefix: usize = self
+            .containers
+            .iter()
+            .map(|c| match c {
+                Container::Quote { .. } => 2,
+                Container::Item { marker, .. } => marker.chars().count(),
+            })
+            .sum();
+        self.options
+            .width
+            .saturating_sub(prefix)
+            .max(MIN_WRAP_WIDTH)
+    }
+
+    /// Builds the prefix for the next line, consuming pending list markers.
+    fn line_prefix(&mut self) -> String {
+        let mut prefix = String::new();
+        for container in &mut self.containers {
+            match container {
+                Container::Quote { .. } => prefix.push_str("> "),
+                Container::Item {
+                    marker,
+                    marker_pending,
+                } => {
+                    if *marker_pending {
+                        prefix.push_str(marker);
+                        *marker_pending = false;
+                    } else {
+                        prefix.push_str(&" ".repeat(marker.chars().count()));
+                    }
+                }
+            }
+        }
+        prefix
+    }
+
+    fn write_block(&mut self, lines: Vec<String>, wrap_lines: bool) {
+        if self.pending_blank && !self.out.is_empty() {
+            // Blank separator lines keep the quote bars but not list indentation
+            let bars = self
+                .containers
+                .iter()
+                .filter(|c| matches!(c, Container::Quote { .. }))
+                .count();
+            self.out.push_str(&">".repeat(bars));
+            self.out.push('\n');
+        }
+        self.pending_blank = false;
+        self.first_block = false;
+
+        let width = self.available_width();
+        for line in lines {
+            let wrapped = if wrap_lines {
+                wrap(&line.replace(SOFT_BREAK, " "), width)
+            } else {
+                vec![line]
+            };
+            for text in wrapped {
+                let prefix = self.line_prefix();
+                let line = format!("{}{}", prefix, text);
+                self.out.push_str(line.trim_end());
+                self.out.push('\n');
+            }
+        }
+
+        for container in &mut self.containers {
+            if let Container::Quote { first_block } = container {
+                *first_block = false;
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.flush_tight();
+
+        if !self.references.is_empty() {
+            self.out.push_str("\nReferences:\n");
+            for (i, url) in self.references.iter().enumerate() {
+                self.out.push_str(&format!("[{}] {}\n", i + 1, url));
+            }
+        }
+        self.out
+    }
+}
+
+/// Lays out table rows in aligned columns, with a rule under the header.
+fn render_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| c.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(j, w)| {
+                format!(
+                    "{:<w$}",
+                    row.get(j).map(String::as_str).unwrap_or(""),
+                    w = w
+                )
+            })
+            .collect();
+        lines.push(cells.join("  "));
+        if i == 0 {
+            let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+            lines.push(rule.join("  "));
+        }
+    }
+    lines
+}
+
+/// Greedily wraps text to `width` columns, keeping explicit line breaks.
+///
+/// Words longer than the width are placed on a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for hard_line in text.split('\n') {
+        let mut line = String::new();
+        let mut line_len = 0;
+        for word in hard_line.split_whitespace() {
+            let word_len = word.chars().count();
+            if line_len > 0 && line_len + 1 + word_len > width {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            if line_len > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+            line.push_str(word);
+            line_len += word_len;
+        }
+        lines.push(line);
+    }
+    // A trailing hard break leaves an empty final line
+    while lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{NoteId, Tag, Topic};
+    use chrono::{DateTime, Utc};
+
+    fn text(markdown: &str) -> String {
+        markdown_to_text(markdown, &TextOptions::default())
+    }
+
+    fn narrow(markdown: &str, width: usize) -> String {
+        markdown_to_text(
+            markdown,
+            &TextOptions {
+                width,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn wraps_paragraphs_to_width() {
+        let out = narrow("one two three four five six seven eight nine ten", 20);
+        assert_eq!(out, "one two three four\nfive six seven eight\nnine ten\n");
+    }
+
+    #[test]
+    fn long_words_get_their_own_line() {
+        assert_eq!(
+            wrap("a supercalifragilistic b", 10),
+            vec!["a", "supercalifragilistic", "b"]
+        );
+    }
+
+    #[test]
+    fn underlines_headings() {
+        let out = text("# Title\n\n## Section\n\n### Sub\n\n#### Deep");
+        assert_eq!(
+            out,
+            "Title\n=====\n\nSection\n-------\n\nSub\n~~~\n\nDeep\n"
+        );
+    }
+
+    #[test]
+    fn strips_inline_markup() {
+        let out = text("Some *emphasis*, **strong**, ~~gone~~ and `code`.");
+        assert_eq!(out, "Some emphasis, strong, gone and code.\n");
+    }
+
+    #[test]
+    fn links_become_numbered_references() {
+        let out = text(
+            "See [docs](https://a.example) and [more](https://b.example), \
+             again [docs](https://a.example).",
+        );
+        assert_eq!(
+            out,
+            "See docs[1] and more[2], again docs[1].\n\n\
+             References:\n[1] https://a.example\n[2] https://b.example\n"
+        );
+    }
+
+    #[test]
+    fn autolinks_are_left_inline() {
+        let out = text("Visit <https://a.example> now.");
+        assert_eq!(out, "Visit https://a.example now.\n");
+    }
+
+    #[test]
+    fn images_are_described() {
+        let out = text("![A chart](chart.png)");
+        assert_eq!(out, "[image: A chart][1]\n\nReferences:\n[1] chart.png\n");
+    }
+
+    #[test]
+    fn indents_code_blocks_without_wrapping() {
+        let out = narrow(
+            "Before\n\n```rust\nfn main() { println!(\"a very long line here\"); }\n```\n\nAfter",
+            20,
+        );
+        assert_eq!(
+            out,
+            "Before\n\n    fn main() { println!(\"a very long line here\"); }\n\nAfter\n"
+        );
+    }
+
+    #[test]
+    fn renders_tight_and_nested_lists() {
+        let out = text("Intro\n\n- one\n- two\n  - nested\n\n1. first\n2. second\n\nEnd");
+        assert_eq!(
+            out,
+            "Intro\n\n  * one\n  * two\n      * nested\n\n  1. first\n  2. second\n\nEnd\n"
+        );
+    }
+
+    #[test]
+    fn list_items_wrap_with_hanging_indent() {
+        let out = narrow("- alpha beta gamma delta epsilon zeta", 24);
+        assert_eq!(out, "  * alpha beta gamma\n    delta epsilon zeta\n");
+    }
+
+    #[test]
+    fn quotes_are_prefixed() {
+        let out = text("> quoted text\n>\n> second paragraph");
+        assert_eq!(out, "> quoted text\n>\n> second paragraph\n");
+    }
+
+    #[test]
+    fn callout_marker_becomes_label() {
+        let out = text("> [!warning] Careful\n> This bites.");
+        assert_eq!(out, "> Warning: Careful\n> This bites.\n");
+    }
+
+    #[test]
+    fn task_list_markers_are_plain() {
+        let out = text("- [x] done\n- [ ] todo");
+        assert_eq!(out, "  * [x] done\n  * [ ] todo\n");
+    }
+
+    #[test]
+    fn tables_are_aligned() {
+        let out = text("| Name | Qty |\n|------|-----|\n| apple | 3 |\n| fig | 12 |");
+        assert_eq!(out, "Name   Qty\n-----  ---\napple  3\nfig    12\n");
+    }
+
+    #[test]
+    fn footnotes_use_bracketed_labels() {
+        let out = text("Claim.[^1]\n\n[^1]: Source.");
+        assert!(out.starts_with("Claim.[1]\n"));
+        assert!(out.contains("[1] Source.\n"));
+    }
+
+    #[test]
+    fn render_note_text_adds_header_and_skips_repeated_title() {
+        let dt: DateTime<Utc> = DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9A".parse().unwrap();
+        let note = Note::builder(id, "API Design", dt, dt)
+            .topics(vec![Topic::new("software/api").unwrap()])
+            .tags(vec![Tag::new("draft").unwrap()])
+            .build()
+            .unwrap();
+
+        let out = render_note_text(&note, "# API Design\n\nBody text.", &TextOptions::default());
+
+        assert_eq!(
+            out,
+            "API Design\n==========\n\nTopics: software/api\nTags: draft\n\nBody text.\n"
+        );
+    }
+}
@@ -224,7 +224,7 @@ article > header {
 
 .topics a::after {
     content: '/';
-    margin-left: var(--space-2);
+    margin-inline-start: var(--space-2);
     color: var(--color-text-subtle);
 }
 
@@ -300,8 +300,8 @@ pre code {
 blockquote {
     margin: var(--space-6) 0;
     padding: var(--space-4) var(--space-5);
-    padding-left: var(--space-5);
-    border-left: 4px solid var(--color-blockquote-border);
+    padding-inline-start: var(--space-5);
+    border-inline-start: 4px solid var(--color-blockquote-border);
     background-color: var(--color-blockquote-bg);
     border-radius: 0 var(--radius-md) var(--radius-md) 0;
     color: var(--color-text-muted);
@@ -319,6 +319,69 @@ blockquote cite {
     color: var(--color-text-subtle);
 }
 
+/* ==========================================================================
+   Table of Contents
+   ========================================================================== */
+
+.toc {
+    margin: var(--space-6) 0;
+    padding: var(--space-4) var(--space-5);
+    background-color: var(--color-bg-subtle);
+    border-radius: var(--radius-md);
+}
+
+.toc ul {
+    margin: 0;
+    padding-inline-start: var(--space-5);
+    list-style: none;
+}
+
+.toc > ul {
+    padding-inline-start: 0;
+}
+
+/* ==========================================================================
+   Callouts
+   ========================================================================== */
+
+.callout {
+    --callout-color: var(--color-accent);
+    margin: var(--space-6) 0;
+    padding: var(--space-4) var(--space-5);
+    border-inline-start: 4px solid var(--callout-color);
+    background-color: var(--color-bg-subtle);
+    border-radius: 0 var(--radius-md) var(--radius-md) 0;
+}
+
+.callout-title {
+    font-weight: 600;
+    color: var(--callout-color);
+}
+
+.callout-content > :first-child {
+    margin-top: var(--space-2);
+}
+
+.callout-content > :last-child {
+    margin-bottom: 0;
+}
+
+.callout-tip, .callout-success, .callout-check, .callout-done {
+    --callout-color: #16a34a;
+}
+
+.callout-warning, .callout-caution, .callout-attention, .callout-question {
+    --callout-color: #d97706;
+}
+
+.callout-danger, .callout-error, .callout-failure, .callout-bug {
+    --callout-color: #dc2626;
+}
+
+.callout-example, .callout-quote {
+    --callout-color: #7c3aed;
+}
+
 /* ==========================================================================
    Tables
    ========================================================================== */
@@ -332,7 +395,7 @@ table {
 
 th, td {
     padding: var(--space-3) var(--space-4);
-    text-align: left;
+    text-align: start;
     border-bottom: 1px solid var(--color-border);
 }
 
@@ -352,7 +415,7 @@ tr:hover td {
 
 ul, ol {
     margin: var(--space-4) 0;
-    padding-left: var(--space-6);
+    padding-inline-start: var(--space-6);
 }
 
 li {
@@ -364,6 +427,63 @@ li > ul, li > ol {
     margin-bottom: 0;
 }
 
+/* ==========================================================================
+   Task Lists
+   ========================================================================== */
+
+li:has(> input[type="checkbox"]) {
+    list-style: none;
+    margin-inline-start: calc(-1 * var(--space-5));
+}
+
+li > input[type="checkbox"] {
+    margin-inline-end: var(--space-2);
+    accent-color: var(--color-accent);
+}
+
+/* ==========================================================================
+   Footnotes
+   ========================================================================== */
+
+.footnote-reference {
+    font-size: var(--text-xs);
+    vertical-align: super;
+    line-height: 0;
+}
+
+.footnote-definition {
+    display: flex;
+    gap: var(--space-2);
+    font-size: var(--text-sm);
+    color: var(--color-text-muted);
+}
+
+.footnote-definition:first-of-type {
+    margin-top: var(--space-8);
+    padding-top: var(--space-4);
+    border-top: 1px solid var(--color-border);
+}
+
+.footnote-definition p {
+    margin: 0;
+}
+
+.footnote-definition-label {
+    font-size: var(--text-xs);
+    vertical-align: super;
+}
+
+/* ==========================================================================
+   Math
+   ========================================================================== */
+
+.math-display {
+    display: block;
+    margin: var(--space-6) 0;
+    overflow-x: auto;
+    text-align: center;
+}
+
 /* ==========================================================================
    Images
    ========================================================================== */
@@ -506,6 +626,10 @@ body > footer, article + footer {
     color: var(--color-text-subtle);
 }
 
+.site-stats span + span::before {
+    content: " · ";
+}
+
 /* ==========================================================================
    Responsive Adjustments
    ========================================================================== */
@@ -654,7 +778,7 @@ body:has(.site-layout) {
 }
 
 .topic-item.has-children > .topic-link {
-    padding-left: var(--space-1);
+    padding-inline-start: var(--space-1);
 }
 
 .topic-toggle {
@@ -664,7 +788,7 @@ body:has(.site-layout) {
     width: 20px;
     height: 20px;
     padding: 0;
-    margin-right: var(--space-1);
+    margin-inline-end: var(--space-1);
     background: none;
     border: none;
     color: var(--color-text-subtle);
@@ -678,6 +802,10 @@ body:has(.site-layout) {
     color: var(--color-text);
 }
 
+[dir="rtl"] .topic-toggle {
+    transform: scaleX(-1);
+}
+
 .topic-item.expanded > .topic-toggle {
     transform: rotate(90deg);
 }
@@ -909,19 +1037,23 @@ body:has(.site-layout) {
     .sidebar {
         position: fixed;
         top: 0;
-        left: 0;
+        inset-inline-start: 0;
         width: 300px;
         height: 100vh;
         max-height: 100vh;
         border-radius: 0;
         border: none;
-        border-right: 1px solid var(--color-border);
+        border-inline-end: 1px solid var(--color-border);
         z-index: 1000;
         transform: translateX(-100%);
         transition: transform 0.3s ease;
         padding-top: var(--space-8);
     }
 
+    [dir="rtl"] .sidebar {
+        transform: translateX(100%);
+    }
+
     .sidebar.open {
         transform: translateX(0);
     }
@@ -930,7 +1062,7 @@ body:has(.site-layout) {
         display: block;
         position: absolute;
         top: var(--space-2);
-        right: var(--space-2);
+        inset-inline-end: var(--space-2);
         width: 32px;
         height: 32px;
         padding: 0;
@@ -981,40 +1113,256 @@ body:has(.site-layout) {
 }
 "#;
 
+/// Serif preset: book-like typography that also reads well on paper.
+pub const THEME_SERIF_CSS: &str = r#"
+/* ==========================================================================
+   Serif Preset
+   ========================================================================== */
+
+:root {
+    --font-sans: Charter, 'Iowan Old Style', 'Palatino Linotype', Palatino, Georgia, serif;
+    --text-base: 1.0625rem;
+}
+
+body {
+    line-height: 1.75;
+    max-width: 65ch;
+}
+
+h1, h2, h3, h4, h5, h6 {
+    font-weight: 600;
+    letter-spacing: 0;
+}
+
+h2 {
+    border-bottom: none;
+}
+"#;
+
+/// Compact preset: smaller type and tighter spacing for dense reference notes.
+pub const THEME_COMPACT_CSS: &str = r#"
+/* ==========================================================================
+   Compact Preset
+   ========================================================================== */
+
+:root {
+    --text-base: 0.875rem;
+    --text-lg: 1rem;
+    --text-xl: 1.125rem;
+    --text-2xl: 1.25rem;
+    --text-3xl: 1.5rem;
+    --text-4xl: 1.75rem;
+
+    --space-4: 0.75rem;
+    --space-5: 1rem;
+    --space-6: 1rem;
+    --space-8: 1.25rem;
+    --space-10: 1.5rem;
+    --space-12: 2rem;
+    --space-16: 2.5rem;
+}
+
+body {
+    line-height: 1.5;
+    max-width: 90ch;
+}
+"#;
+
+/// High-contrast preset: pure black on white (and white on black in dark
+/// mode) with underlined links.
+pub const THEME_HIGH_CONTRAST_CSS: &str = r#"
+/* ==========================================================================
+   High-Contrast Preset
+   ========================================================================== */
+
+:root {
+    --color-bg: #ffffff;
+    --color-bg-subtle: #ffffff;
+    --color-bg-muted: #e5e5e5;
+    --color-bg-code: #f0f0f0;
+    --color-text: #000000;
+    --color-text-muted: #1a1a1a;
+    --color-text-subtle: #333333;
+    --color-border: #000000;
+    --color-border-muted: #4d4d4d;
+    --color-link: #0000ee;
+    --color-link-hover: #000080;
+    --color-accent: #0000ee;
+    --color-accent-subtle: #e0e0ff;
+    --color-tag-bg: #000000;
+    --color-tag-text: #ffffff;
+    --color-blockquote-border: #000000;
+    --color-blockquote-bg: #ffffff;
+}
+
+@media (prefers-color-scheme: dark) {
+    :root {
+        --color-bg: #000000;
+        --color-bg-subtle: #000000;
+        --color-bg-muted: #262626;
+        --color-bg-code: #141414;
+        --color-text: #ffffff;
+        --color-text-muted: #f0f0f0;
+        --color-text-subtle: #d9d9d9;
+        --color-border: #ffffff;
+        --color-border-muted: #b3b3b3;
+        --color-link: #ffff00;
+        --color-link-hover: #ffffa0;
+        --color-accent: #ffff00;
+        --color-accent-subtle: #333300;
+        --color-tag-bg: #ffffff;
+        --color-tag-text: #000000;
+        --color-blockquote-border: #ffffff;
+        --color-blockquote-bg: #000000;
+    }
+}
+
+a {
+    text-decoration: underline;
+}
+
+:focus-visible {
+    outline: 3px solid var(--color-accent);
+    outline-offset: 2px;
+}
+"#;
+
+/// Print stylesheet appended to every built-in theme.
+///
+/// Sets page margins and running headers/footers (note title, note ID, page
+/// numbers) for paged media such as PDF converters, and keeps code blocks
+/// syntax-highlighted on paper. The title and ID come from the `data-note-*`
+/// attributes on the note's `<article>`.
+pub const PRINT_CSS: &str = r#"
+/* ==========================================================================
+   Print / PDF
+   ========================================================================== */
+
+@page {
+    margin: 2cm 2cm 2.5cm;
+
+    @top-left {
+        content: string(note-title);
+        font-size: 9pt;
+        color: #555;
+    }
+
+    @top-right {
+        content: string(note-id);
+        font-family: monospace;
+        font-size: 8pt;
+        color: #555;
+    }
+
+    @bottom-center {
+        content: counter(page) " / " counter(pages);
+        font-size: 9pt;
+        color: #555;
+    }
+}
+
+@page :first {
+    @top-left { content: none; }
+}
+
+article[data-note-id] {
+    string-set: note-title attr(data-note-title), note-id attr(data-note-id);
+}
+
+@media print {
+    .sidebar,
+    .sidebar-toggle,
+    .sidebar-backdrop,
+    .breadcrumb,
+    body > footer {
+        display: none !important;
+    }
+
+    .site-layout,
+    .main-content {
+        display: block;
+        max-width: none;
+        padding: 0;
+    }
+
+    pre, code {
+        -webkit-print-color-adjust: exact;
+        print-color-adjust: exact;
+    }
+
+    pre {
+        background: #f6f8fa;
+        white-space: pre-wrap;
+        overflow-wrap: anywhere;
+    }
+
+    pre code.hljs {
+        color: #24292e;
+        background: transparent;
+    }
+
+    .hljs-keyword, .hljs-doctag, .hljs-meta .hljs-keyword,
+    .hljs-template-tag, .hljs-template-variable, .hljs-type,
+    .hljs-variable.language_ { color: #d73a49; }
+    .hljs-title, .hljs-title.class_, .hljs-title.function_ { color: #6f42c1; }
+    .hljs-attr, .hljs-attribute, .hljs-literal, .hljs-meta,
+    .hljs-number, .hljs-operator, .hljs-variable,
+    .hljs-selector-attr, .hljs-selector-class, .hljs-selector-id { color: #005cc5; }
+    .hljs-regexp, .hljs-string, .hljs-meta .hljs-string { color: #032f62; }
+    .hljs-built_in, .hljs-symbol { color: #e36209; }
+    .hljs-comment, .hljs-code, .hljs-formula { color: #6a737d; }
+    .hljs-name, .hljs-quote, .hljs-selector-tag, .hljs-selector-pseudo { color: #22863a; }
+}
+"#;
+
 /// Legacy alias for backward compatibility with tests.
 pub const THEME_DEFAULT: &str = THEME_CSS;
 
 /// Legacy dark theme - now returns the unified theme (dark mode is automatic).
 pub const THEME_DARK: &str = THEME_CSS;
 
+/// Names of the built-in themes accepted by `--theme`.
+pub const BUILTIN_THEMES: &[&str] = &["default", "dark", "serif", "compact", "high-contrast"];
+
 /// Gets CSS for the specified theme.
 ///
 /// The default theme uses CSS custom properties with `prefers-color-scheme`
 /// media query for automatic light/dark mode switching. The "dark" theme
 /// name is accepted for backwards compatibility but returns the same
 /// unified theme (dark mode is handled automatically via media queries).
+/// The "serif", "compact" and "high-contrast" presets override the default
+/// theme's design tokens. Every built-in theme includes [`PRINT_CSS`].
 ///
 /// # Arguments
 ///
-/// * `theme` - Theme name ("default", "dark") or path to custom CSS file.
-///   If None, returns the default theme with automatic light/dark support.
+/// * `theme` - Built-in theme name (see [`BUILTIN_THEMES`]) or path to custom
+///   CSS file. If None, returns the default theme with automatic light/dark
+///   support.
 ///
 /// # Errors
 ///
 /// Returns an error if the theme name is unknown or the CSS file cannot be read.
 pub fn get_theme_css(theme: Option<&str>) -> Result<String> {
-    match theme {
+    let preset = match theme {
         // Both default and dark now return the unified theme with automatic switching
-        None | Some("default") | Some("dark") => Ok(THEME_CSS.to_string()),
+        None | Some("default") | Some("dark") => "",
+        Some("serif") => THEME_SERIF_CSS,
+        Some("compact") => THEME_COMPACT_CSS,
+        Some("high-contrast") => THEME_HIGH_CONTRAST_CSS,
         Some(path) => {
             let path = Path::new(path);
             if path.exists() {
-                Ok(std::fs::read_to_string(path)?)
+                return Ok(std::fs::read_to_string(path)?);
             } else {
-                Err(anyhow!("Unknown theme: '{}'. Use 'default', 'dark', or a path to a CSS file.", path.display()))
+                return Err(anyhow!(
+                    "Unknown theme: '{}'. Use one of {}, or a path to a CSS file.",
+                    path.display(),
+                    BUILTIN_THEMES.join(", ")
+                ));
             }
         }
-    }
+    };
+    Ok(format!("{}{}{}", THEME_CSS, preset, PRINT_CSS))
 }
 
 #[cfg(test)]
@@ -1061,6 +1409,16 @@ mod tests {
         assert!(css.contains("@media (prefers-color-scheme: dark)"));
     }
 
+    #[test]
+    fn test_theme_styles_markdown_extensions() {
+        let css = get_theme_css(None).unwrap();
+
+        assert!(css.contains(".callout-title"));
+        assert!(css.contains(".footnote-definition"));
+        assert!(css.contains(".math-display"));
+        assert!(css.contains(r#"input[type="checkbox"]"#));
+    }
+
     #[test]
     fn test_custom_theme_file() {
         let mut temp = NamedTempFile::new().unwrap();
@@ -1071,11 +1429,52 @@ mod tests {
         assert_eq!(css.trim(), "body { color: red; }");
     }
 
+    #[test]
+    fn test_presets_extend_default_theme() {
+        for (name, preset) in [
+            ("serif", THEME_SERIF_CSS),
+            ("compact", THEME_COMPACT_CSS),
+            ("high-contrast", THEME_HIGH_CONTRAST_CSS),
+        ] {
+            let css = get_theme_css(Some(name)).unwrap();
+
+            assert!(
+                css.starts_with(THEME_CSS),
+                "{name} should extend the default theme"
+            );
+            assert!(css.contains(preset), "{name} should include its preset");
+        }
+        assert!(get_theme_css(Some("serif")).unwrap().contains("serif;"));
+    }
+
+    #[test]
+    fn test_builtin_themes_include_print_css() {
+        for name in BUILTIN_THEMES {
+            let css = get_theme_css(Some(name)).unwrap();
+
+            assert!(css.contains("@page"), "{name} should include print styles");
+            assert!(css.contains("string(note-title)"));
+            assert!(css.contains("string(note-id)"));
+        }
+    }
+
+    #[test]
+    fn test_custom_theme_file_has_no_print_css() {
+        let mut temp = NamedTempFile::new().unwrap();
+        writeln!(temp, "body {{ color: red; }}").unwrap();
+
+        let css = get_theme_css(Some(temp.path().to_str().unwrap())).unwrap();
+
+        assert!(!css.contains("@page"));
+    }
+
     #[test]
     fn test_invalid_theme_errors() {
         let result = get_theme_css(Some("nonexistent-theme"));
 
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Unknown theme"));
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("Unknown theme"));
+        assert!(err.contains("high-contrast"));
     }
 }
@@ -0,0 +1,265 @@
+//! Tables of contents for exported notes.
+
+use std::collections::HashMap;
+
+use pulldown_cmark::escape::{escape_href, escape_html};
+use pulldown_cmark::{Event, Tag};
+use serde::Serialize;
+
+use crate::export::html::{MarkdownOptions, markdown_to_html_with, markdown_to_html_with_toc};
+use crate::infra::slugify;
+
+/// Deepest heading level listed in a table of contents by default.
+pub const DEFAULT_TOC_DEPTH: u8 = 3;
+
+/// How a table of contents is built from a note's headings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TocOptions {
+    /// Deepest heading level to list (1-6).
+    pub depth: u8,
+    /// Insert the table of contents into the content after the first
+    /// heading, instead of leaving its placement to the template.
+    pub after_first_heading: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_TOC_DEPTH,
+            after_first_heading: false,
+        }
+    }
+}
+
+/// A heading listed in a table of contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TocEntry {
+    /// Heading level (1-6).
+    pub level: u8,
+    /// Plain text of the heading.
+    pub text: String,
+    /// ID of the heading element, to link to as `#anchor`.
+    pub anchor: String,
+}
+
+/// Renders a note body to HTML for a template, returning the content, the
+/// table of contents HTML and its entries.
+///
+/// The table of contents HTML is empty when none was requested or it was
+/// inserted into the content.
+pub(crate) fn render_body(
+    body: &str,
+    markdown: &MarkdownOptions,
+    toc: Option<&TocOptions>,
+) -> (String, String, Vec<TocEntry>) {
+    let Some(toc) = toc else {
+        return (
+            markdown_to_html_with(body, markdown),
+            String::new(),
+            Vec::new(),
+        );
+    };
+    let (content, entries) = markdown_to_html_with_toc(body, markdown, toc);
+    let toc_html = if toc.after_first_heading {
+        String::new()
+    } else {
+        toc_html(&entries)
+    };
+    (content, toc_html, entries)
+}
+
+/// Gives each heading up to the configured depth an ID, returning them in
+/// document order. The table of contents is inserted after the first
+/// heading if requested.
+pub(crate) fn add_heading_anchors(
+    events: &mut Vec<Event<'_>>,
+    options: &TocOptions,
+) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    let mut used: HashMap<String, usize> = HashMap::new();
+    let mut first_heading_end = None;
+
+    let mut i = 0;
+    while i < events.len() {
+        let Event::Start(Tag::Heading(level, _, _)) = &events[i] else {
+            i += 1;
+            continue;
+        };
+        let level = *level as u8;
+        let start = i;
+        let mut text = String::new();
+        while i < events.len() && !matches!(events[i], Event::End(Tag::Heading(..))) {
+            if let Event::Text(t) | Event::Code(t) = &events[i] {
+                text.push_str(t);
+            }
+            i += 1;
+        }
+        first_heading_end.get_or_insert(i);
+
+        if level <= options.depth {
+            let anchor = unique_anchor(&text, &mut used);
+            let mut open = String::new();
+            escape_href(&mut open, &anchor).unwrap();
+            events[start] = Event::Html(format!("<h{} id=\"{}\">", level, open).into());
+            entries.push(TocEntry {
+                level,
+                text,
+                anchor,
+            });
+        }
+        i += 1;
+    }
+
+    if options.after_first_heading && !entries.is_empty() {
+        let at = first_heading_end.map_or(0, |end| end + 1);
+        events.insert(at, Event::Html(toc_html(&entries).into()));
+    }
+    entries
+}
+
+/// Slugifies heading text into an anchor not used yet, numbering repeats
+/// as `anchor-1`, `anchor-2` and so on.
+fn unique_anchor(text: &str, used: &mut HashMap<String, usize>) -> String {
+    let base = slugify(text);
+    let count = used.entry(base.clone()).or_insert(0);
+    let anchor = if *count == 0 {
+        base.clone()
+    } else {
+        format!("{}-{}", base, count)
+    };
+    *count += 1;
+    anchor
+}
+
+/// Renders a table of contents as a nested list of links to the headings.
+///
+/// Nesting is relative to the shallowest heading listed, so a note whose
+/// sections start at `##` isn't indented under an empty level.
+pub fn toc_html(entries: &[TocEntry]) -> String {
+    let Some(base) = entries.iter().map(|e| e.level).min() else {
+        return String::new();
+    };
+
+    let mut html = String::from("<nav class=\"toc\" aria-label=\"Contents\">");
+    let mut open = 0;
+    for entry in entries {
+        let level = usize::from(entry.level - base) + 1;
+        if level > open {
+            while open < level {
+                html.push_str("<ul>");
+                open += 1;
+                if open < level {
+                    html.push_str("<li>");
+                }
+            }
+        } else {
+            html.push_str("</li>");
+            while open > level {
+                html.push_str("</ul></li>");
+                open -= 1;
+            }
+        }
+        html.push_str("<li><a href=\"#");
+        escape_href(&mut html, &entry.anchor).unwrap();
+        html.push_str("\">");
+        escape_html(&mut html, &entry.text).unwrap();
+        html.push_str("</a>");
+    }
+    html.push_str("</li>");
+    while open > 0 {
+        html.push_str("</ul>");
+        open -= 1;
+        if open > 0 {
+            html.push_str("</li>");
+        }
+    }
+    html.push_str("</nav>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(level: u8, text: &str) -> TocEntry {
+        TocEntry {
+            level,
+            text: text.to_string(),
+            anchor: slugify(text),
+        }
+    }
+
+    #[test]
+    fn toc_html_nests_by_level() {
+        let html = toc_html(&[entry(2, "Setup"), entry(3, "Install"), entry(2, "Usage")]);
+
+        assert_eq!(
+            html,
+            "<nav class=\"toc\" aria-label=\"Contents\"><ul>\
+             <li><a href=\"#setup\">Setup</a><ul><li><a href=\"#install\">Install</a></li></ul></li>\
+             <li><a href=\"#usage\">Usage</a></li>\
+             </ul></nav>\n"
+        );
+    }
+
+    #[test]
+    fn toc_html_handles_skipped_levels() {
+        let html = toc_html(&[entry(1, "Top"), entry(3, "Deep"), entry(1, "Next")]);
+
+        assert_eq!(
+            html,
+            "<nav class=\"toc\" aria-label=\"Contents\"><ul>\
+             <li><a href=\"#top\">Top</a><ul><li><ul><li><a href=\"#deep\">Deep</a></li></ul></li></ul></li>\
+             <li><a href=\"#next\">Next</a></li>\
+             </ul></nav>\n"
+        );
+        assert_eq!(toc_html(&[]), "");
+    }
+
+    #[test]
+    fn headings_get_anchors_up_to_depth() {
+        let markdown = "# Guide\n\n## Setup `cargo`\n\n### Deep\n\n## Setup `cargo`";
+        let options = TocOptions {
+            depth: 2,
+            ..Default::default()
+        };
+
+        let (html, entries) =
+            markdown_to_html_with_toc(markdown, &MarkdownOptions::default(), &options);
+
+        assert!(html.contains("<h1 id=\"guide\">Guide</h1>"));
+        assert!(html.contains("<h2 id=\"setup-cargo\">Setup <code>cargo</code></h2>"));
+        assert!(html.contains("<h2 id=\"setup-cargo-1\">"));
+        assert!(html.contains("<h3>Deep</h3>"));
+        assert!(!html.contains("class=\"toc\""));
+        let anchors: Vec<&str> = entries.iter().map(|e| e.anchor.as_str()).collect();
+        assert_eq!(anchors, ["guide", "setup-cargo", "setup-cargo-1"]);
+        assert_eq!(entries[1].text, "Setup cargo");
+    }
+
+    #[test]
+    fn toc_inserted_after_first_heading() {
+        let markdown = "Intro\n\n# Guide\n\nText\n\n## Setup";
+        let options = TocOptions {
+            after_first_heading: true,
+            ..Default::default()
+        };
+
+        let (content, toc, _) = render_body(markdown, &MarkdownOptions::default(), Some(&options));
+
+        let heading = content.find("</h1>").unwrap();
+        let nav = content.find("<nav class=\"toc\"").unwrap();
+        let text = content.find("<p>Text</p>").unwrap();
+        assert!(heading < nav && nav < text);
+        assert!(content.contains("<a href=\"#setup\">Setup</a>"));
+        assert_eq!(toc, "");
+    }
+
+    #[test]
+    fn unique_anchor_numbers_repeats() {
+        let mut used = HashMap::new();
+        assert_eq!(unique_anchor("Notes", &mut used), "notes");
+        assert_eq!(unique_anchor("Notes", &mut used), "notes-1");
+        assert_eq!(unique_anchor("!!!", &mut used), "untitled");
+    }
+}
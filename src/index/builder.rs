@@ -1,8 +1,15 @@
 //! Index builder for creating and updating the notes index from markdown files.
 
-use crate::index::{IndexRepository, IndexResult, SqliteIndex};
-use crate::infra::{ContentHash, FsError, parse_note_from_bytes, read_note, scan_notes_directory};
-use std::collections::HashMap;
+use crate::domain::{count_words, find_mentions, find_urls, find_wikilinks};
+use crate::index::IndexError;
+use crate::index::{IndexRepository, IndexResult, SqliteIndex, Tokenizer};
+use crate::infra::profile::{self, Phase};
+use crate::infra::{
+    ContentHash, FsError, ScanOptions, check_vault_nesting, long_path, parse_note_from_bytes,
+    read_note, scan_notes_directory_with,
+};
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 // ===========================================
@@ -118,12 +125,56 @@ impl ProgressReporter for NoopReporter {
 /// files).
 pub struct IndexBuilder {
     notes_dir: PathBuf,
+    scan: ScanOptions,
+    tokenizer: Option<Tokenizer>,
+    languages: Option<BTreeMap<String, Tokenizer>>,
+    store_bodies: Option<bool>,
 }
 
 impl IndexBuilder {
     /// Creates a new IndexBuilder for the given notes directory.
     pub fn new(notes_dir: PathBuf) -> Self {
-        Self { notes_dir }
+        Self {
+            notes_dir,
+            scan: ScanOptions::default(),
+            tokenizer: None,
+            languages: None,
+            store_bodies: None,
+        }
+    }
+
+    /// Sets the directories to leave out of the index.
+    pub fn with_scan_options(mut self, scan: ScanOptions) -> Self {
+        self.scan = scan;
+        self
+    }
+
+    /// Sets the tokenizer for the full-text index.
+    ///
+    /// Changing the tokenizer of a populated index takes a full rebuild;
+    /// incremental updates only apply it to an empty index.
+    pub fn with_tokenizer(mut self, tokenizer: Tokenizer) -> Self {
+        self.tokenizer = Some(tokenizer);
+        self
+    }
+
+    /// Sets the tokenizer for notes in each language (by their `lang`
+    /// field), indexed alongside the main full-text index.
+    ///
+    /// Unlike the main tokenizer this applies on every update.
+    pub fn with_languages(mut self, languages: BTreeMap<String, Tokenizer>) -> Self {
+        self.languages = Some(languages);
+        self
+    }
+
+    /// Sets whether the index keeps a copy of each note's body.
+    ///
+    /// Like the language tokenizers this applies on every update. Switching
+    /// it on makes an incremental update store the bodies of unchanged notes
+    /// too; switching it off drops every stored body.
+    pub fn with_store_bodies(mut self, store: bool) -> Self {
+        self.store_bodies = Some(store);
+        self
     }
 
     /// Returns the notes directory.
@@ -131,6 +182,24 @@ impl IndexBuilder {
         &self.notes_dir
     }
 
+    /// Scans the notes directory, refusing to index a vault nested inside
+    /// another or containing one.
+    fn scan_files(&self) -> IndexResult<Vec<PathBuf>> {
+        let io_error = |e: FsError| IndexError::Io {
+            path: self.notes_dir.clone(),
+            source: std::io::Error::other(e.to_string()),
+        };
+        check_vault_nesting(&self.notes_dir, &self.scan).map_err(|e| match e {
+            FsError::NestedVault { .. } | FsError::ContainsVault { .. } => {
+                IndexError::NestedVault(e)
+            }
+            e => io_error(e),
+        })?;
+        Ok(scan_notes_directory_with(&self.notes_dir, &self.scan)
+            .map_err(io_error)?
+            .collect())
+    }
+
     /// Performs a full rebuild of the index.
     ///
     /// This clears all existing data and re-indexes all markdown files in the
@@ -151,16 +220,8 @@ impl IndexBuilder {
         index: &mut SqliteIndex,
         progress: &mut P,
     ) -> IndexResult<BuildResult> {
-        // Clear the index
-        index.clear()?;
-
         // Scan directory for markdown files
-        let files: Vec<PathBuf> = scan_notes_directory(&self.notes_dir)
-            .map_err(|e| crate::index::IndexError::Io {
-                path: self.notes_dir.clone(),
-                source: std::io::Error::other(e.to_string()),
-            })?
-            .collect();
+        let files = self.scan_files()?;
 
         // Parse all files first, collecting results
         let mut parsed_notes = Vec::with_capacity(files.len());
@@ -184,13 +245,40 @@ impl IndexBuilder {
             }
         }
 
-        // Batch insert all parsed notes in a single transaction
+        // Replace the index contents under one write lock, so other
+        // processes never see it half-built
         let batch: Vec<_> = parsed_notes
             .iter()
             .map(|(parsed, path)| (&parsed.note, &parsed.content_hash, path.as_path()))
             .collect();
 
-        index.upsert_notes_batch(&batch)?;
+        index.with_write_lock(|index| {
+            index.clear()?;
+            // Switching tokenizers is cheap while the index is empty
+            if let Some(tokenizer) = self.tokenizer {
+                index.set_tokenizer(tokenizer)?;
+            }
+            if let Some(languages) = &self.languages {
+                index.set_language_tokenizers(languages)?;
+            }
+            if let Some(store) = self.store_bodies {
+                index.set_store_bodies(store)?;
+            }
+            let store_bodies = index.stores_bodies()?;
+            index.upsert_notes_batch(&batch)?;
+            for (parsed, _) in &parsed_notes {
+                index.set_mentions(parsed.note.id(), &find_mentions(&parsed.body))?;
+                index.set_headings(parsed.note.id(), &parsed.body)?;
+                index.set_urls(parsed.note.id(), &find_urls(&parsed.body))?;
+                index.set_word_count(parsed.note.id(), count_words(&parsed.body))?;
+                index.set_wikilinks(parsed.note.id(), &find_wikilinks(&parsed.body))?;
+                if store_bodies {
+                    index.set_body(parsed.note.id(), &parsed.body)?;
+                }
+            }
+            index.resolve_wikilinks()?;
+            index.record_full_rebuild(Utc::now())
+        })?;
 
         let indexed = parsed_notes.len();
         progress.on_complete(indexed, errors.len());
@@ -220,21 +308,33 @@ impl IndexBuilder {
         // Get all currently indexed paths with their hashes
         let indexed_paths: HashMap<PathBuf, ContentHash> =
             index.all_indexed_paths()?.into_iter().collect();
+        if indexed_paths.is_empty()
+            && let Some(tokenizer) = self.tokenizer
+        {
+            index.set_tokenizer(tokenizer)?;
+        }
+        if let Some(languages) = &self.languages {
+            index.set_language_tokenizers(languages)?;
+        }
+        // Bodies of unchanged notes are only read when storage was just enabled
+        let fill_bodies = match self.store_bodies {
+            Some(store) => index.set_store_bodies(store)? && store,
+            None => false,
+        };
+        let store_bodies = index.stores_bodies()?;
 
         // Scan current directory for markdown files
-        let current_files: Vec<PathBuf> = scan_notes_directory(&self.notes_dir)
-            .map_err(|e| crate::index::IndexError::Io {
-                path: self.notes_dir.clone(),
-                source: std::io::Error::other(e.to_string()),
-            })?
-            .collect();
+        let current_files = self.scan_files()?;
 
         let current_files_set: std::collections::HashSet<PathBuf> =
             current_files.iter().cloned().collect();
 
+        // Parse changed files first and write them all under one lock, so
+        // the lock is not held while reading files
+        let mut changed = Vec::new();
+        let mut unchanged = Vec::new();
         let mut added = 0;
         let mut modified = 0;
-        let mut removed = 0;
         let mut errors = Vec::new();
 
         // Process current files
@@ -242,7 +342,7 @@ impl IndexBuilder {
             let full_path = self.notes_dir.join(relative_path);
 
             // Read the file bytes once
-            match std::fs::read(&full_path) {
+            match profile::time(Phase::FileIo, || std::fs::read(long_path(&full_path))) {
                 Ok(bytes) => {
                     let current_hash = ContentHash::compute(&bytes);
 
@@ -251,11 +351,7 @@ impl IndexBuilder {
                             // New file - parse the bytes we already read (no re-read)
                             match parse_note_from_bytes(bytes, &full_path) {
                                 Ok(parsed) => {
-                                    index.upsert_note(
-                                        &parsed.note,
-                                        &parsed.content_hash,
-                                        relative_path,
-                                    )?;
+                                    changed.push((parsed, relative_path));
                                     added += 1;
                                     progress.on_file(relative_path, FileResult::Indexed);
                                 }
@@ -273,11 +369,7 @@ impl IndexBuilder {
                             // File changed - parse the bytes we already read (no re-read)
                             match parse_note_from_bytes(bytes, &full_path) {
                                 Ok(parsed) => {
-                                    index.upsert_note(
-                                        &parsed.note,
-                                        &parsed.content_hash,
-                                        relative_path,
-                                    )?;
+                                    changed.push((parsed, relative_path));
                                     modified += 1;
                                     progress.on_file(relative_path, FileResult::Indexed);
                                 }
@@ -292,7 +384,12 @@ impl IndexBuilder {
                             }
                         }
                         Some(_) => {
-                            // File unchanged - skip (bytes not needed)
+                            // File unchanged - skip, unless its body is to be stored
+                            if fill_bodies
+                                && let Ok(parsed) = parse_note_from_bytes(bytes, &full_path)
+                            {
+                                unchanged.push(parsed);
+                            }
                             progress.on_file(relative_path, FileResult::Skipped);
                         }
                     }
@@ -311,12 +408,43 @@ impl IndexBuilder {
             }
         }
 
-        // Remove files that no longer exist
-        for indexed_path in indexed_paths.keys() {
-            if !current_files_set.contains(indexed_path) && index.remove_by_path(indexed_path)? {
-                removed += 1;
-            }
-        }
+        // Files that no longer exist
+        let deleted: Vec<&PathBuf> = indexed_paths
+            .keys()
+            .filter(|path| !current_files_set.contains(*path))
+            .collect();
+
+        // Unchanged vaults don't need the lock, so readers never queue for it
+        let removed = if changed.is_empty() && deleted.is_empty() && unchanged.is_empty() {
+            0
+        } else {
+            index.with_write_lock(|index| {
+                for (parsed, path) in &changed {
+                    index.remove_other_at_path(path, parsed.note.id())?;
+                    index.upsert_note(&parsed.note, &parsed.content_hash, path)?;
+                    index.set_mentions(parsed.note.id(), &find_mentions(&parsed.body))?;
+                    index.set_headings(parsed.note.id(), &parsed.body)?;
+                    index.set_urls(parsed.note.id(), &find_urls(&parsed.body))?;
+                    index.set_word_count(parsed.note.id(), count_words(&parsed.body))?;
+                    index.set_wikilinks(parsed.note.id(), &find_wikilinks(&parsed.body))?;
+                    if store_bodies {
+                        index.set_body(parsed.note.id(), &parsed.body)?;
+                    }
+                }
+                for parsed in &unchanged {
+                    index.set_body(parsed.note.id(), &parsed.body)?;
+                }
+
+                let mut removed = 0;
+                for path in &deleted {
+                    if index.remove_by_path(path)? {
+                        removed += 1;
+                    }
+                }
+                index.resolve_wikilinks()?;
+                Ok(removed)
+            })?
+        };
 
         progress.on_complete(added + modified, errors.len());
         Ok(UpdateResult {
@@ -326,6 +454,130 @@ impl IndexBuilder {
             errors,
         })
     }
+
+    /// Re-indexes only the given note files (paths relative to the notes directory).
+    ///
+    /// Files whose content hash matches the index are skipped, and files that
+    /// no longer exist are removed. Unlike [`IndexBuilder::incremental_update`],
+    /// the rest of the notes directory is not scanned.
+    pub fn update_paths(
+        &self,
+        index: &mut SqliteIndex,
+        paths: &[PathBuf],
+    ) -> IndexResult<UpdateResult> {
+        self.update_paths_with(index, paths, false)
+    }
+
+    /// Re-indexes the given note files even if their content hash matches
+    /// the index, as when the index rows themselves are damaged.
+    ///
+    /// Files that no longer exist are removed, as with
+    /// [`IndexBuilder::update_paths`].
+    pub fn reindex_paths(
+        &self,
+        index: &mut SqliteIndex,
+        paths: &[PathBuf],
+    ) -> IndexResult<UpdateResult> {
+        self.update_paths_with(index, paths, true)
+    }
+
+    fn update_paths_with(
+        &self,
+        index: &mut SqliteIndex,
+        paths: &[PathBuf],
+        force: bool,
+    ) -> IndexResult<UpdateResult> {
+        let indexed_paths: HashMap<PathBuf, ContentHash> =
+            index.all_indexed_paths()?.into_iter().collect();
+        if indexed_paths.is_empty()
+            && let Some(tokenizer) = self.tokenizer
+        {
+            index.set_tokenizer(tokenizer)?;
+        }
+        if let Some(languages) = &self.languages {
+            index.set_language_tokenizers(languages)?;
+        }
+        if let Some(store) = self.store_bodies {
+            index.set_store_bodies(store)?;
+        }
+        let store_bodies = index.stores_bodies()?;
+
+        let mut result = UpdateResult {
+            added: 0,
+            modified: 0,
+            removed: 0,
+            errors: Vec::new(),
+        };
+        let mut changed = Vec::new();
+        let mut deleted = Vec::new();
+
+        for relative_path in paths {
+            let full_path = self.notes_dir.join(relative_path);
+
+            let bytes = match profile::time(Phase::FileIo, || std::fs::read(long_path(&full_path)))
+            {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    deleted.push(relative_path);
+                    continue;
+                }
+                Err(e) => {
+                    result.errors.push(BuildError::Io {
+                        path: relative_path.clone(),
+                        message: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            let previous = indexed_paths.get(relative_path);
+            if !force && previous == Some(&ContentHash::compute(&bytes)) {
+                continue;
+            }
+
+            match parse_note_from_bytes(bytes, &full_path) {
+                Ok(parsed) => {
+                    if previous.is_some() {
+                        result.modified += 1;
+                    } else {
+                        result.added += 1;
+                    }
+                    changed.push((parsed, relative_path));
+                }
+                Err(e) => result
+                    .errors
+                    .push(fs_error_to_build_error(e, relative_path)),
+            }
+        }
+
+        if !changed.is_empty() || !deleted.is_empty() {
+            result.removed = index.with_write_lock(|index| {
+                for (parsed, path) in &changed {
+                    index.remove_other_at_path(path, parsed.note.id())?;
+                    index.upsert_note(&parsed.note, &parsed.content_hash, path)?;
+                    index.set_mentions(parsed.note.id(), &find_mentions(&parsed.body))?;
+                    index.set_headings(parsed.note.id(), &parsed.body)?;
+                    index.set_urls(parsed.note.id(), &find_urls(&parsed.body))?;
+                    index.set_word_count(parsed.note.id(), count_words(&parsed.body))?;
+                    index.set_wikilinks(parsed.note.id(), &find_wikilinks(&parsed.body))?;
+                    if store_bodies {
+                        index.set_body(parsed.note.id(), &parsed.body)?;
+                    }
+                }
+
+                let mut removed = 0;
+                for path in &deleted {
+                    if index.remove_by_path(path)? {
+                        removed += 1;
+                    }
+                }
+                index.resolve_wikilinks()?;
+                Ok(removed)
+            })?;
+        }
+
+        Ok(result)
+    }
 }
 
 // ===========================================
@@ -352,7 +604,7 @@ fn fs_error_to_build_error(error: FsError, path: &Path) -> BuildError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::NoteId;
+    use crate::domain::{Link, NoteId, Rel};
     use crate::index::IndexRepository;
     use std::fs;
     use tempfile::TempDir;
@@ -430,6 +682,197 @@ Body content."#,
         assert_eq!(note.unwrap().title(), "Test Note");
     }
 
+    #[test]
+    fn full_rebuild_and_incremental_update_index_mentions() {
+        let dir = TempDir::new().unwrap();
+        let content = minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Standup")
+            .replace("Body content.", "Met @jane-doe.");
+        fs::write(dir.path().join("note.md"), &content).unwrap();
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        let jane = ["jane-doe".to_string()];
+        let bob = ["bob".to_string()];
+
+        builder.full_rebuild(&mut index).unwrap();
+        assert_eq!(index.notes_mentioning(&jane).unwrap().len(), 1);
+
+        fs::write(
+            dir.path().join("note.md"),
+            content.replace("@jane-doe", "@bob"),
+        )
+        .unwrap();
+        builder.incremental_update(&mut index).unwrap();
+        assert!(index.notes_mentioning(&jane).unwrap().is_empty());
+        assert_eq!(index.notes_mentioning(&bob).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn wikilinks_are_indexed_as_mentions_links_as_targets_come_and_go() {
+        let dir = TempDir::new().unwrap();
+        let source: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let target: NoteId = "01HQ4A2R9PXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let content = minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Standup")
+            .replace("Body content.", "See [[api design|the API]].");
+        fs::write(dir.path().join("standup.md"), &content).unwrap();
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        builder.full_rebuild(&mut index).unwrap();
+        assert!(index.all_links().unwrap().is_empty());
+
+        // The target appearing resolves the unchanged note's wikilink
+        create_note_file(
+            dir.path(),
+            "api.md",
+            "01HQ4A2R9PXJK4QZPW8V2R6T9Y",
+            "API Design",
+        );
+        builder.incremental_update(&mut index).unwrap();
+        let links = index.all_links().unwrap();
+        assert_eq!(links[&source].len(), 1);
+        assert_eq!(*links[&source][0].target(), target);
+        assert_eq!(links[&source][0].rel()[0].as_str(), "mentions");
+        let mentions = Rel::new("mentions").unwrap();
+        assert_eq!(index.backlinks(&target, Some(&mentions)).unwrap().len(), 1);
+        assert!(index.frontmatter_links().unwrap().is_empty());
+
+        // Renaming the target leaves the wikilink unresolved
+        create_note_file(
+            dir.path(),
+            "api.md",
+            "01HQ4A2R9PXJK4QZPW8V2R6T9Y",
+            "API Guidelines",
+        );
+        builder.incremental_update(&mut index).unwrap();
+        assert!(index.all_links().unwrap().is_empty());
+    }
+
+    #[test]
+    fn wikilinks_merge_into_frontmatter_links() {
+        let dir = TempDir::new().unwrap();
+        let source: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let content = minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Standup").replace(
+            "---\nBody content.",
+            "links:\n  - id: 01HQ4A2R9PXJK4QZPW8V2R6T9Y\n    rel: [parent]\n---\nSee [[01HQ4A2R]].",
+        );
+        fs::write(dir.path().join("standup.md"), &content).unwrap();
+        create_note_file(
+            dir.path(),
+            "api.md",
+            "01HQ4A2R9PXJK4QZPW8V2R6T9Y",
+            "API Design",
+        );
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        builder.full_rebuild(&mut index).unwrap();
+
+        let rels = |links: &HashMap<NoteId, Vec<Link>>| -> Vec<String> {
+            links[&source][0]
+                .rel()
+                .iter()
+                .map(|r| r.to_string())
+                .collect()
+        };
+        assert_eq!(rels(&index.all_links().unwrap()), ["mentions", "parent"]);
+        assert_eq!(rels(&index.frontmatter_links().unwrap()), ["parent"]);
+    }
+
+    #[test]
+    fn full_rebuild_and_incremental_update_index_headings() {
+        let dir = TempDir::new().unwrap();
+        let content = minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Manual")
+            .replace("Body content.", "## Setup\n\nRun the installer.");
+        fs::write(dir.path().join("note.md"), &content).unwrap();
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+
+        builder.full_rebuild(&mut index).unwrap();
+        assert_eq!(index.search_sections("installer").unwrap()[0].path, "Setup");
+
+        fs::write(
+            dir.path().join("note.md"),
+            content.replace("## Setup", "## Install"),
+        )
+        .unwrap();
+        builder.incremental_update(&mut index).unwrap();
+        let matches = index.search_sections("installer").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].path, "Install");
+    }
+
+    #[test]
+    fn full_rebuild_and_incremental_update_index_urls() {
+        let dir = TempDir::new().unwrap();
+        let content = minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Reading")
+            .replace("Body content.", "See https://example.com/a.");
+        fs::write(dir.path().join("note.md"), &content).unwrap();
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+
+        builder.full_rebuild(&mut index).unwrap();
+        assert_eq!(
+            index.url_references(None).unwrap()[0].url,
+            "https://example.com/a"
+        );
+
+        fs::write(
+            dir.path().join("note.md"),
+            content.replace("example.com/a", "example.org/b"),
+        )
+        .unwrap();
+        builder.incremental_update(&mut index).unwrap();
+        let references = index.url_references(None).unwrap();
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].url, "https://example.org/b");
+    }
+
+    #[test]
+    fn store_bodies_fills_unchanged_notes_and_drops_bodies_when_off() {
+        let dir = TempDir::new().unwrap();
+        create_note_file(
+            dir.path(),
+            "note.md",
+            "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+            "Test Note",
+        );
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+
+        IndexBuilder::new(dir.path().to_path_buf())
+            .full_rebuild(&mut index)
+            .unwrap();
+        assert_eq!(index.body(&id).unwrap(), None);
+
+        // Switching it on stores the bodies of notes that didn't change
+        let storing = IndexBuilder::new(dir.path().to_path_buf()).with_store_bodies(true);
+        let result = storing.incremental_update(&mut index).unwrap();
+        assert_eq!(result.modified, 0);
+        assert_eq!(index.body(&id).unwrap().as_deref(), Some("Body content."));
+
+        // Builders that don't set it keep storing
+        fs::write(
+            dir.path().join("note.md"),
+            minimal_note_content("01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Test Note")
+                .replace("Body content.", "New body."),
+        )
+        .unwrap();
+        IndexBuilder::new(dir.path().to_path_buf())
+            .update_paths(&mut index, &[PathBuf::from("note.md")])
+            .unwrap();
+        assert_eq!(index.body(&id).unwrap().as_deref(), Some("New body."));
+
+        IndexBuilder::new(dir.path().to_path_buf())
+            .with_store_bodies(false)
+            .incremental_update(&mut index)
+            .unwrap();
+        assert!(!index.stores_bodies().unwrap());
+        assert_eq!(index.body(&id).unwrap(), None);
+    }
+
     #[test]
     fn full_rebuild_multiple_notes_all_indexed() {
         let dir = TempDir::new().unwrap();
@@ -612,6 +1055,71 @@ Updated body."#;
         assert_eq!(note.title(), "Modified Title");
     }
 
+    #[test]
+    fn update_paths_only_touches_given_files() {
+        let dir = TempDir::new().unwrap();
+        create_note_file(dir.path(), "a.md", "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "A");
+        create_note_file(dir.path(), "b.md", "01HQ4A2R9PXJK4QZPW8V2R6T9Z", "B");
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        builder.full_rebuild(&mut index).unwrap();
+
+        create_note_file(dir.path(), "a.md", "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "A Edited");
+        create_note_file(dir.path(), "b.md", "01HQ4A2R9PXJK4QZPW8V2R6T9Z", "B Edited");
+
+        let result = builder
+            .update_paths(&mut index, &[PathBuf::from("a.md")])
+            .unwrap();
+
+        assert_eq!(result.modified, 1);
+        let a: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let b: NoteId = "01HQ4A2R9PXJK4QZPW8V2R6T9Z".parse().unwrap();
+        assert_eq!(index.get_note(&a).unwrap().unwrap().title(), "A Edited");
+        assert_eq!(index.get_note(&b).unwrap().unwrap().title(), "B");
+    }
+
+    #[test]
+    fn update_paths_skips_unchanged_and_removes_deleted() {
+        let dir = TempDir::new().unwrap();
+        create_note_file(dir.path(), "a.md", "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "A");
+        create_note_file(dir.path(), "b.md", "01HQ4A2R9PXJK4QZPW8V2R6T9Z", "B");
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        builder.full_rebuild(&mut index).unwrap();
+
+        fs::remove_file(dir.path().join("b.md")).unwrap();
+
+        let result = builder
+            .update_paths(&mut index, &[PathBuf::from("a.md"), PathBuf::from("b.md")])
+            .unwrap();
+
+        assert_eq!(result.added + result.modified, 0);
+        assert_eq!(result.removed, 1);
+    }
+
+    #[test]
+    fn update_paths_reindexes_file_whose_id_changed() {
+        let dir = TempDir::new().unwrap();
+        create_note_file(dir.path(), "a.md", "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "A");
+
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut index = SqliteIndex::open_in_memory().unwrap();
+        builder.full_rebuild(&mut index).unwrap();
+
+        create_note_file(dir.path(), "a.md", "01HQ4A2R9PXJK4QZPW8V2R6T9Z", "A");
+        let result = builder
+            .update_paths(&mut index, &[PathBuf::from("a.md")])
+            .unwrap();
+
+        assert_eq!(result.modified, 1);
+        let old: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let new: NoteId = "01HQ4A2R9PXJK4QZPW8V2R6T9Z".parse().unwrap();
+        assert!(index.get_note(&old).unwrap().is_none());
+        assert_eq!(index.get_note(&new).unwrap().unwrap().title(), "A");
+    }
+
     #[test]
     fn incremental_update_unchanged_file_skipped() {
         let dir = TempDir::new().unwrap();
@@ -635,6 +1143,34 @@ Updated body."#;
         assert_eq!(result.removed, 0);
     }
 
+    #[test]
+    fn incremental_update_unchanged_does_not_wait_for_write_lock() {
+        let dir = TempDir::new().unwrap();
+        create_note_file(
+            dir.path(),
+            "note.md",
+            "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+            "Test Note",
+        );
+        let db_path = dir.path().join(".index/notes.db");
+        let builder = IndexBuilder::new(dir.path().to_path_buf());
+        let mut writer = SqliteIndex::open(&db_path).unwrap();
+        let mut reader = SqliteIndex::open(&db_path).unwrap();
+        builder.full_rebuild(&mut writer).unwrap();
+        reader
+            .set_busy_timeout(std::time::Duration::from_millis(5))
+            .unwrap();
+
+        // Another process holding the write lock (e.g. a long rebuild)
+        writer
+            .with_write_lock(|_| {
+                let result = builder.incremental_update(&mut reader).unwrap();
+                assert_eq!(result.added + result.modified + result.removed, 0);
+                Ok(())
+            })
+            .unwrap();
+    }
+
     #[test]
     fn incremental_update_deleted_file_removed_from_index() {
         let dir = TempDir::new().unwrap();
@@ -13,4 +13,8 @@ pub use repository::{
     SearchResult, TagWithCount, TopicWithCount,
 };
 pub use schema::{create_schema, get_schema_version, rebuild_fts};
-pub use sqlite::{SqliteIndex, Transaction};
+pub use sqlite::{
+    DEFAULT_BUSY_TIMEOUT, FieldMatch, HealthSample, IndexStats, QuickHit, SearchExplanation,
+    SearchField, SectionMatch, SectionScore, SqliteIndex, TableStats, Tokenizer, Transaction,
+    UrlReference, VacuumResult,
+};
@@ -1,7 +1,8 @@
 //! IndexRepository trait and result types.
 
+use super::sqlite::SectionMatch;
 use crate::domain::{Note, NoteId, Rel, Tag, Topic};
-use crate::infra::ContentHash;
+use crate::infra::{ContentHash, FsError};
 use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -32,6 +33,14 @@ pub enum IndexError {
         #[source]
         source: std::io::Error,
     },
+
+    /// Another process kept the index locked past the busy timeout and retries.
+    #[error("index is busy: another process is writing to it, try again")]
+    Busy,
+
+    /// The notes directory is inside another vault or contains one.
+    #[error(transparent)]
+    NestedVault(FsError),
 }
 
 /// Result type for index operations.
@@ -57,6 +66,7 @@ pub struct IndexedNote {
     topics: Vec<Topic>,
     aliases: Vec<String>,
     tags: Vec<Tag>,
+    note_type: Option<String>,
 }
 
 impl IndexedNote {
@@ -85,6 +95,7 @@ impl IndexedNote {
             topics,
             aliases,
             tags,
+            note_type: None,
         }
     }
 
@@ -138,6 +149,11 @@ impl IndexedNote {
         &self.tags
     }
 
+    /// Returns the note's `type` frontmatter field, if any.
+    pub fn note_type(&self) -> Option<&str> {
+        self.note_type.as_deref()
+    }
+
     // ===========================================
     // Cycle 3: IndexedNote Builder
     // ===========================================
@@ -162,6 +178,7 @@ impl IndexedNote {
             topics: Vec::new(),
             aliases: Vec::new(),
             tags: Vec::new(),
+            note_type: None,
         }
     }
 }
@@ -178,6 +195,7 @@ pub struct IndexedNoteBuilder {
     topics: Vec<Topic>,
     aliases: Vec<String>,
     tags: Vec<Tag>,
+    note_type: Option<String>,
 }
 
 impl IndexedNoteBuilder {
@@ -205,6 +223,12 @@ impl IndexedNoteBuilder {
         self
     }
 
+    /// Sets the note type.
+    pub fn note_type(mut self, note_type: impl Into<String>) -> Self {
+        self.note_type = Some(note_type.into());
+        self
+    }
+
     /// Builds the IndexedNote.
     pub fn build(self) -> IndexedNote {
         IndexedNote {
@@ -218,6 +242,7 @@ impl IndexedNoteBuilder {
             topics: self.topics,
             aliases: self.aliases,
             tags: self.tags,
+            note_type: self.note_type,
         }
     }
 }
@@ -232,6 +257,7 @@ pub struct SearchResult {
     note: IndexedNote,
     rank: f64,
     snippet: Option<String>,
+    section: Option<String>,
 }
 
 impl SearchResult {
@@ -241,6 +267,7 @@ impl SearchResult {
             note,
             rank,
             snippet: None,
+            section: None,
         }
     }
 
@@ -250,6 +277,7 @@ impl SearchResult {
             note,
             rank,
             snippet: Some(snippet.into()),
+            section: None,
         }
     }
 
@@ -267,6 +295,24 @@ impl SearchResult {
     pub fn snippet(&self) -> Option<&str> {
         self.snippet.as_deref()
     }
+
+    /// Returns the heading path of the body section that matched, if any.
+    pub fn section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+
+    /// Records the best-matching section of the note's body, adding its rank.
+    ///
+    /// The section's snippet is used if the note had none.
+    pub(crate) fn add_section_match(&mut self, section: &SectionMatch) {
+        self.rank += section.rank;
+        if !section.path.is_empty() {
+            self.section = Some(section.path.clone());
+        }
+        if self.snippet.as_deref().is_none_or(str::is_empty) {
+            self.snippet = Some(section.snippet.clone());
+        }
+    }
 }
 
 // ===========================================
@@ -419,6 +465,17 @@ pub trait IndexRepository {
     /// Lists all notes in the index.
     fn list_all(&self) -> IndexResult<Vec<IndexedNote>>;
 
+    /// Lists notes without any topic.
+    fn list_without_topics(&self) -> IndexResult<Vec<IndexedNote>>;
+
+    /// Lists notes without any tag.
+    fn list_without_tags(&self) -> IndexResult<Vec<IndexedNote>>;
+
+    /// Lists notes that neither link to another note nor are linked to.
+    ///
+    /// Links to notes missing from the index still count as links.
+    fn list_unlinked(&self) -> IndexResult<Vec<IndexedNote>>;
+
     /// Finds notes whose ID starts with the given prefix.
     ///
     /// Returns all notes with matching ID prefix (case-insensitive).
@@ -437,6 +494,11 @@ pub trait IndexRepository {
     /// If `rel` is provided, only returns notes with links having that relationship type.
     fn backlinks(&self, target_id: &NoteId, rel: Option<&Rel>) -> IndexResult<Vec<IndexedNote>>;
 
+    /// Returns every `(source, target)` link pair carrying the given relationship type.
+    ///
+    /// Targets are returned even if they are not present in the index.
+    fn rel_edges(&self, rel: &Rel) -> IndexResult<Vec<(NoteId, NoteId)>>;
+
     /// Batch insert/update multiple notes in a single transaction.
     ///
     /// More efficient than calling `upsert_note` repeatedly, as it uses
@@ -1,6 +1,9 @@
 //! SQLite schema creation for the notes index.
 
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+
+use super::Tokenizer;
+use super::sqlite::TOKENIZER_KEY;
 
 // ===========================================
 // Cycle 1: Schema Module Structure
@@ -18,9 +21,22 @@ use rusqlite::Connection;
 /// - `aliases` - Alternative names for notes
 /// - `tags` - Flat tag names
 /// - `note_tags` - Many-to-many junction for notes and tags
+/// - `note_types` - The `type` frontmatter field of typed notes
+/// - `note_langs` - The `lang` frontmatter field, as a primary language subtag
+/// - `note_words` - Body word counts, for `ls --largest`
+/// - `note_bodies` - Full note bodies, kept only with `[index] store_bodies`
+/// - `mentions` - `@handle` mentions in note bodies
+/// - `headings` - Sections of note bodies, with their text for section search
+/// - `urls` - External URLs in note bodies
 /// - `links` - Links between notes
 /// - `link_rels` - Relationship types for links
+/// - `wikilinks` - `[[wikilink]]` targets in note bodies
+/// - `implicit_links` - Links and rels added to `links` for wikilinks
 /// - `schema_version` - Schema version tracking
+/// - `index_meta` - Key/value bookkeeping (e.g. last full rebuild time)
+/// - `health_samples` - Daily vault health samples for `stats --trend`
+/// - `checked_files` - Files that passed their last `check`, for `check --incremental`
+/// - `trash` - When each file in the trash directory was first seen there
 pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
     // ===========================================
     // Cycle 11: Foreign Key Enforcement
@@ -97,6 +113,94 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         );",
     )?;
 
+    // ===========================================
+    // Note Types Table
+    // ===========================================
+    // A separate table rather than a notes column, so indexes created
+    // before note types existed pick it up without a migration
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS note_types (
+            note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+            type TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Note Languages Table
+    // ===========================================
+    // Like note types, kept out of the notes table so older indexes pick it
+    // up without a migration
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS note_langs (
+            note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+            lang TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Note Word Counts Table
+    // ===========================================
+    // Filled from note bodies as they are indexed; notes indexed before it
+    // existed have no row until they change or the index is rebuilt
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS note_words (
+            note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+            words INTEGER NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Note Bodies Table
+    // ===========================================
+    // Only filled while the index stores bodies, for reading notes without
+    // their files
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS note_bodies (
+            note_id TEXT PRIMARY KEY REFERENCES notes(id) ON DELETE CASCADE,
+            body TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Mentions Table
+    // ===========================================
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS mentions (
+            note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            handle TEXT NOT NULL,
+            PRIMARY KEY (note_id, handle)
+        );",
+    )?;
+
+    // ===========================================
+    // Headings Table
+    // ===========================================
+    // One row per section; `text` is the section's own text, up to its
+    // first subsection, so a match points at the innermost section
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS headings (
+            id INTEGER PRIMARY KEY,
+            note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            level INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            start_byte INTEGER NOT NULL,
+            end_byte INTEGER NOT NULL,
+            text TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // URLs Table
+    // ===========================================
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS urls (
+            note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            url TEXT NOT NULL,
+            domain TEXT NOT NULL,
+            PRIMARY KEY (note_id, url)
+        );",
+    )?;
+
     // ===========================================
     // Cycle 8: Links Table
     // ===========================================
@@ -121,6 +225,26 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         );",
     )?;
 
+    // ===========================================
+    // Wikilinks Tables
+    // ===========================================
+    // Targets are kept as written and resolved into `links` with the
+    // `mentions` rel; `implicit_links` records what that added, so it can be
+    // taken out again when a target no longer resolves to the same note
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS wikilinks (
+            note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            target TEXT NOT NULL,
+            PRIMARY KEY (note_id, target)
+        );
+        CREATE TABLE IF NOT EXISTS implicit_links (
+            source_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+            target_id TEXT NOT NULL,
+            added_link INTEGER NOT NULL,
+            PRIMARY KEY (source_id, target_id)
+        );",
+    )?;
+
     // ===========================================
     // Cycle 10: Indexes
     // ===========================================
@@ -128,23 +252,19 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_topics_path ON topics(path);
          CREATE INDEX IF NOT EXISTS idx_tags_name ON tags(name);
          CREATE INDEX IF NOT EXISTS idx_notes_created ON notes(created);
-         CREATE INDEX IF NOT EXISTS idx_notes_modified ON notes(modified);",
+         CREATE INDEX IF NOT EXISTS idx_notes_modified ON notes(modified);
+         CREATE INDEX IF NOT EXISTS idx_links_target ON links(target_id);
+         CREATE INDEX IF NOT EXISTS idx_note_types_type ON note_types(type);
+         CREATE INDEX IF NOT EXISTS idx_note_langs_lang ON note_langs(lang);
+         CREATE INDEX IF NOT EXISTS idx_mentions_handle ON mentions(handle);
+         CREATE INDEX IF NOT EXISTS idx_headings_note ON headings(note_id);
+         CREATE INDEX IF NOT EXISTS idx_urls_domain ON urls(domain);",
     )?;
 
     // ===========================================
     // FTS5 Cycle 1: FTS5 Virtual Table
     // ===========================================
-    // Column names must match notes table for rebuild to work
-    conn.execute_batch(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
-            title,
-            description,
-            aliases_text,
-            body,
-            content='notes',
-            content_rowid='rowid'
-        );",
-    )?;
+    create_fts_table(conn, Tokenizer::default())?;
 
     // ===========================================
     // FTS5 Cycle 8: INSERT Trigger
@@ -191,12 +311,144 @@ pub fn create_schema(conn: &Connection) -> rusqlite::Result<()> {
         );",
     )?;
 
+    // ===========================================
+    // Index Metadata Table
+    // ===========================================
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS index_meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Headings FTS Table
+    // ===========================================
+    // Created after index_meta, so an index built with another tokenizer
+    // gets a matching headings table
+    let tokenizer: Option<String> = conn
+        .query_row(
+            "SELECT value FROM index_meta WHERE key = ?1",
+            [TOKENIZER_KEY],
+            |row| row.get(0),
+        )
+        .optional()?;
+    create_headings_fts_table(
+        conn,
+        tokenizer
+            .and_then(|name| Tokenizer::from_name(&name))
+            .unwrap_or_default(),
+    )?;
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS headings_fts_insert
+        AFTER INSERT ON headings BEGIN
+            INSERT INTO headings_fts(rowid, path, text) VALUES (NEW.id, NEW.path, NEW.text);
+        END;
+        CREATE TRIGGER IF NOT EXISTS headings_fts_delete
+        AFTER DELETE ON headings BEGIN
+            INSERT INTO headings_fts(headings_fts, rowid, path, text)
+            VALUES ('delete', OLD.id, OLD.path, OLD.text);
+        END;",
+    )?;
+
+    // ===========================================
+    // Health Samples Table
+    // ===========================================
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS health_samples (
+            day TEXT PRIMARY KEY,
+            taken_at TEXT NOT NULL,
+            notes INTEGER NOT NULL,
+            orphans INTEGER NOT NULL,
+            links INTEGER NOT NULL,
+            broken_links INTEGER NOT NULL,
+            untagged INTEGER NOT NULL,
+            stale INTEGER NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Checked Files Table
+    // ===========================================
+    // Not tied to the notes table: a full rebuild keeps the records
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS checked_files (
+            path TEXT PRIMARY KEY,
+            content_hash TEXT NOT NULL
+        );",
+    )?;
+
+    // ===========================================
+    // Trash Table
+    // ===========================================
+    // Moving a file into the trash keeps its mtime, so the time it was
+    // trashed is recorded the first time it is seen there
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS trash (
+            path TEXT PRIMARY KEY,
+            trashed_at TEXT NOT NULL
+        );",
+    )?;
+
     // Insert initial version if not exists (version 2 includes FTS5)
     conn.execute(
         "INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (2, datetime('now'))",
         [],
     )?;
 
+    // ===========================================
+    // Version 3: Path Separators
+    // ===========================================
+    // Paths are stored with `/` on every platform; indexes written on
+    // Windows before that have `\` in them
+    if get_schema_version(conn)? < 3 {
+        if cfg!(windows) {
+            normalize_path_separators(conn)?;
+        }
+        conn.execute(
+            "INSERT INTO schema_version (version, applied_at) VALUES (3, datetime('now'))",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Replaces `\` with `/` in the stored paths of notes and of the checked
+/// and trashed files.
+///
+/// If two indexed notes end up with the same path, the notes table is
+/// cleared instead, so the next `notes index` rebuilds it from the files
+/// rather than one of the notes being dropped.
+fn normalize_path_separators(conn: &Connection) -> rusqlite::Result<()> {
+    let conflicts: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM (
+            SELECT REPLACE(path, '\\', '/') AS normalized FROM notes
+            GROUP BY normalized HAVING COUNT(*) > 1
+        )",
+        [],
+        |row| row.get(0),
+    )?;
+    if conflicts > 0 {
+        conn.execute("DELETE FROM notes", [])?;
+    } else {
+        conn.execute(
+            "UPDATE notes SET path = REPLACE(path, '\\', '/') WHERE INSTR(path, '\\') > 0",
+            [],
+        )?;
+    }
+
+    // Rows of these tables describe a file, so two that normalize to the
+    // same path are the same file and one of them can go
+    for table in ["checked_files", "trash"] {
+        conn.execute(
+            &format!(
+                "UPDATE OR REPLACE {} SET path = REPLACE(path, '\\', '/') WHERE INSTR(path, '\\') > 0",
+                table
+            ),
+            [],
+        )?;
+    }
     Ok(())
 }
 
@@ -207,12 +459,50 @@ pub fn get_schema_version(conn: &Connection) -> rusqlite::Result<i64> {
     })
 }
 
-/// Rebuilds the FTS5 index from the notes table.
+/// Creates the FTS5 table over the notes table, unless it exists.
+pub(crate) fn create_fts_table(conn: &Connection, tokenizer: Tokenizer) -> rusqlite::Result<()> {
+    // Column names must match notes table for rebuild to work
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS notes_fts USING fts5(
+            title,
+            description,
+            aliases_text,
+            body,
+            content='notes',
+            content_rowid='rowid',
+            tokenize='{}'
+        );",
+        tokenizer.fts5_option()
+    ))
+}
+
+/// Creates the FTS5 table over the headings table, unless it exists.
+pub(crate) fn create_headings_fts_table(
+    conn: &Connection,
+    tokenizer: Tokenizer,
+) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS headings_fts USING fts5(
+            path,
+            text,
+            content='headings',
+            content_rowid='id',
+            tokenize='{}'
+        );",
+        tokenizer.fts5_option()
+    ))
+}
+
+/// Rebuilds the FTS5 indexes from the notes and headings tables.
 ///
 /// This is useful for recovering from index corruption or after
 /// bulk imports that bypass the triggers.
 pub fn rebuild_fts(conn: &Connection) -> rusqlite::Result<()> {
     conn.execute("INSERT INTO notes_fts(notes_fts) VALUES('rebuild')", [])?;
+    conn.execute(
+        "INSERT INTO headings_fts(headings_fts) VALUES('rebuild')",
+        [],
+    )?;
     Ok(())
 }
 
@@ -1129,6 +1419,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn idx_links_target_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(
+            index_exists(&conn, "idx_links_target"),
+            "idx_links_target should exist"
+        );
+    }
+
     // ===========================================
     // Cycle 11: Foreign Key Enforcement
     // ===========================================
@@ -1411,12 +1711,15 @@ mod tests {
     }
 
     #[test]
-    fn schema_version_initialized_to_2() {
+    fn schema_version_initialized_to_3() {
         let conn = test_connection();
         create_schema(&conn).unwrap();
 
         let version = get_schema_version(&conn).unwrap();
-        assert_eq!(version, 2, "initial schema version should be 2 (with FTS)");
+        assert_eq!(
+            version, 3,
+            "initial schema version should be 3 (with `/` paths)"
+        );
     }
 
     #[test]
@@ -1427,7 +1730,7 @@ mod tests {
         create_schema(&conn).unwrap();
 
         let version = get_schema_version(&conn).unwrap();
-        assert_eq!(version, 2, "schema version should remain 2");
+        assert_eq!(version, 3, "schema version should remain 3");
     }
 
     #[test]
@@ -1437,13 +1740,62 @@ mod tests {
 
         // Manually insert a higher version (simulating migration)
         conn.execute(
-            "INSERT INTO schema_version (version, applied_at) VALUES (3, datetime('now'))",
+            "INSERT INTO schema_version (version, applied_at) VALUES (4, datetime('now'))",
             [],
         )
         .unwrap();
 
         let version = get_schema_version(&conn).unwrap();
-        assert_eq!(version, 3, "should return highest version");
+        assert_eq!(version, 4, "should return highest version");
+    }
+
+    #[test]
+    fn index_meta_table_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(
+            table_exists(&conn, "index_meta"),
+            "index_meta table should exist"
+        );
+    }
+
+    #[test]
+    fn health_samples_table_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(
+            table_exists(&conn, "health_samples"),
+            "health_samples table should exist"
+        );
+    }
+
+    #[test]
+    fn headings_tables_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(
+            table_exists(&conn, "headings"),
+            "headings table should exist"
+        );
+        assert!(index_exists(&conn, "idx_headings_note"));
+    }
+
+    #[test]
+    fn urls_table_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(table_exists(&conn, "urls"), "urls table should exist");
+        assert!(index_exists(&conn, "idx_urls_domain"));
+    }
+
+    #[test]
+    fn checked_files_table_created() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        assert!(
+            table_exists(&conn, "checked_files"),
+            "checked_files table should exist"
+        );
     }
 
     // ===========================================
@@ -1981,12 +2333,79 @@ mod tests {
     // ===========================================
 
     #[test]
-    fn schema_version_is_2_with_fts() {
+    fn schema_version_is_at_least_2_with_fts() {
         let conn = test_connection();
         create_schema(&conn).unwrap();
 
         let version = get_schema_version(&conn).unwrap();
-        assert_eq!(version, 2, "schema version should be 2 with FTS");
+        assert!(version >= 2, "schema version should be at least 2 with FTS");
+    }
+
+    #[test]
+    fn normalize_path_separators_rewrites_backslashes() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO notes (id, path, title, created, modified, content_hash)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            [
+                "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+                r"rust\async.md",
+                "Async",
+                "2024-01-15T10:30:00Z",
+                "2024-01-15T10:30:00Z",
+                "abc123",
+            ],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO trash (path, trashed_at) VALUES (?, ?)",
+            [r"rust\old.md", "2024-01-15T10:30:00Z"],
+        )
+        .unwrap();
+
+        normalize_path_separators(&conn).unwrap();
+
+        let path: String = conn
+            .query_row("SELECT path FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(path, "rust/async.md");
+        let trashed: String = conn
+            .query_row("SELECT path FROM trash", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(trashed, "rust/old.md");
+    }
+
+    #[test]
+    fn normalize_path_separators_clears_notes_on_conflict() {
+        let conn = test_connection();
+        create_schema(&conn).unwrap();
+        for (id, path) in [
+            ("01HQ3K5M7NXJK4QZPW8V2R6T9Y", r"rust\async.md"),
+            ("01HQ4A2R9PXJK4QZPW8V2R6T9Z", "rust/async.md"),
+        ] {
+            conn.execute(
+                "INSERT INTO notes (id, path, title, created, modified, content_hash)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                [
+                    id,
+                    path,
+                    "Async",
+                    "2024-01-15T10:30:00Z",
+                    "2024-01-15T10:30:00Z",
+                    "abc123",
+                ],
+            )
+            .unwrap();
+        }
+
+        normalize_path_separators(&conn).unwrap();
+
+        // Neither note is kept over the other; the next index adds both back
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
     }
 
     // ===========================================
@@ -0,0 +1,90 @@
+//! Optional storage of full note bodies, so notes can be read from the index
+//! when their files are out of reach.
+
+use super::SqliteIndex;
+use crate::domain::{Note, NoteId, TYPE_FIELD};
+use crate::index::{IndexRepository, IndexResult};
+
+/// Metadata key recording whether the index stores note bodies.
+const STORE_BODIES_KEY: &str = "store_bodies";
+
+impl SqliteIndex {
+    /// Returns true if note bodies are stored as notes are indexed.
+    pub fn stores_bodies(&self) -> IndexResult<bool> {
+        Ok(self.get_meta(STORE_BODIES_KEY)?.as_deref() == Some("1"))
+    }
+
+    /// Starts or stops storing note bodies, returning false if the index
+    /// already does as asked.
+    ///
+    /// Stopping drops every stored body. Starting stores nothing by itself:
+    /// bodies are added as notes are indexed.
+    pub fn set_store_bodies(&mut self, store: bool) -> IndexResult<bool> {
+        if self.stores_bodies()? == store {
+            return Ok(false);
+        }
+        self.with_write_lock(|index| {
+            if !store {
+                index.conn.execute("DELETE FROM note_bodies", [])?;
+            }
+            index.set_meta(STORE_BODIES_KEY, if store { "1" } else { "0" })
+        })?;
+        Ok(true)
+    }
+
+    /// Records a note's body.
+    ///
+    /// The note must already be indexed; its body is removed with it.
+    pub fn set_body(&mut self, id: &NoteId, body: &str) -> IndexResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO note_bodies (note_id, body) VALUES (?, ?)",
+            rusqlite::params![id.to_string(), body],
+        )?;
+        Ok(())
+    }
+
+    /// Returns a note's stored body, if the index has one.
+    pub fn body(&self, id: &NoteId) -> IndexResult<Option<String>> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT body FROM note_bodies WHERE note_id = ?")?;
+        let mut rows = stmt.query([id.to_string()])?;
+        Ok(match rows.next()? {
+            Some(row) => Some(row.get(0)?),
+            None => None,
+        })
+    }
+
+    /// Rebuilds a note and its body from the index alone, without reading
+    /// its file; `None` if the note isn't indexed or has no stored body.
+    ///
+    /// The note carries what the index keeps: its metadata, type and
+    /// frontmatter links, but no other extra fields.
+    pub fn stored_note(&self, id: &NoteId) -> IndexResult<Option<(Note, String)>> {
+        let (Some(indexed), Some(body)) = (self.get_note(id)?, self.body(id)?) else {
+            return Ok(None);
+        };
+        let links = self.frontmatter_links()?.remove(id).unwrap_or_default();
+        let extra = indexed
+            .note_type()
+            .map(|t| (TYPE_FIELD.to_string(), serde_yaml::Value::from(t)))
+            .into_iter()
+            .collect();
+        let note = Note::builder(
+            id.clone(),
+            indexed.title(),
+            indexed.created(),
+            indexed.modified(),
+        )
+        .description(indexed.description())
+        .topics(indexed.topics().to_vec())
+        .aliases(indexed.aliases().to_vec())
+        .tags(indexed.tags().to_vec())
+        .links(links)
+        .extra(extra)
+        .build();
+        // Indexed notes were valid when written, so this only fails on a
+        // damaged index
+        Ok(note.ok().map(|note| (note, body)))
+    }
+}
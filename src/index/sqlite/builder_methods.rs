@@ -1,8 +1,9 @@
 //! Additional methods for IndexBuilder support.
 
 use super::SqliteIndex;
+use crate::domain::NoteId;
 use crate::index::IndexResult;
-use crate::infra::ContentHash;
+use crate::infra::{ContentHash, index_path_key};
 use std::path::{Path, PathBuf};
 
 impl SqliteIndex {
@@ -33,7 +34,17 @@ impl SqliteIndex {
     pub fn remove_by_path(&mut self, path: &Path) -> IndexResult<bool> {
         let rows = self.conn.execute(
             "DELETE FROM notes WHERE path = ?",
-            [path.to_string_lossy().as_ref()],
+            [index_path_key(path).as_str()],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Removes the note indexed at `path` if it has an ID other than `id`, as
+    /// when the file's ID was changed, so the note can be indexed afresh.
+    pub fn remove_other_at_path(&mut self, path: &Path, id: &NoteId) -> IndexResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM notes WHERE path = ?1 AND id != ?2",
+            [index_path_key(path).as_str(), id.to_string().as_str()],
         )?;
         Ok(rows > 0)
     }
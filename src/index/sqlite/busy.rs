@@ -0,0 +1,97 @@
+//! Handling of `SQLITE_BUSY` when several processes share one index.
+//!
+//! Every connection waits up to the busy timeout for a lock. Operations that
+//! still find the database busy (e.g. a lock upgrade SQLite refuses to wait
+//! for) are retried a few times with exponential backoff before giving up
+//! with [`IndexError::Busy`].
+
+use super::SqliteIndex;
+use crate::index::{IndexError, IndexResult};
+use rusqlite::ErrorCode;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Default time a connection waits for another process's lock.
+pub const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Retries after the busy timeout, each waiting twice as long as the last.
+const RETRY_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+
+static DEFAULT_BUSY_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_BUSY_TIMEOUT.as_millis() as u64);
+
+impl SqliteIndex {
+    /// Sets the busy timeout used by indexes opened after this call.
+    pub fn set_default_busy_timeout(timeout: Duration) {
+        let millis = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+        DEFAULT_BUSY_TIMEOUT_MS.store(millis, Ordering::Relaxed);
+    }
+
+    /// Returns the busy timeout new indexes are opened with.
+    pub fn default_busy_timeout() -> Duration {
+        Duration::from_millis(DEFAULT_BUSY_TIMEOUT_MS.load(Ordering::Relaxed))
+    }
+
+    /// Sets how long this connection waits for another process's lock.
+    pub fn set_busy_timeout(&self, timeout: Duration) -> IndexResult<()> {
+        self.conn.busy_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Runs `f` while holding the index's write lock.
+    ///
+    /// Everything `f` writes is committed together, or rolled back if it
+    /// fails. Other processes can keep reading the previous state meanwhile;
+    /// writers wait for the lock. Transactions started inside `f` become
+    /// savepoints. If a write lock is already held, `f` simply runs in it.
+    pub fn with_write_lock<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> IndexResult<T>,
+    ) -> IndexResult<T> {
+        if !self.conn.is_autocommit() {
+            return f(self);
+        }
+
+        retry_busy(|| Ok(self.conn.execute_batch("BEGIN IMMEDIATE")?))?;
+        let result = f(self)
+            .and_then(|value| retry_busy(|| Ok(self.conn.execute_batch("COMMIT")?)).map(|_| value));
+        if result.is_err() && !self.conn.is_autocommit() {
+            // Ignore rollback errors; the original error is more useful
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+        result
+    }
+}
+
+/// Runs `op`, retrying with backoff while the database is busy.
+///
+/// Gives up with [`IndexError::Busy`] once the retries are exhausted.
+pub(crate) fn retry_busy<T>(mut op: impl FnMut() -> IndexResult<T>) -> IndexResult<T> {
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 0..RETRY_ATTEMPTS {
+        match op() {
+            Err(e) if is_busy(&e) => {
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    match op() {
+        Err(e) if is_busy(&e) => Err(IndexError::Busy),
+        result => result,
+    }
+}
+
+/// Returns true if an error means another connection holds a lock.
+fn is_busy(error: &IndexError) -> bool {
+    match error {
+        IndexError::Database(rusqlite::Error::SqliteFailure(err, _)) => matches!(
+            err.code,
+            ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked
+        ),
+        IndexError::Busy => true,
+        _ => false,
+    }
+}
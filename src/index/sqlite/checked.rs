@@ -0,0 +1,145 @@
+//! Bookkeeping for incremental `check`.
+
+use chrono::DateTime;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::SqliteIndex;
+use crate::domain::{Link, Note, NoteId, Topic};
+use crate::index::IndexResult;
+use crate::infra::{ContentHash, index_path_key};
+
+impl SqliteIndex {
+    /// Returns the files that passed their last check, with the content hash
+    /// each had then.
+    pub fn checked_files(&self) -> IndexResult<HashMap<PathBuf, ContentHash>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, content_hash FROM checked_files")?;
+        let files = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(path, hash)| {
+                ContentHash::from_hex(&hash)
+                    .ok()
+                    .map(|h| (PathBuf::from(path), h))
+            })
+            .collect();
+        Ok(files)
+    }
+
+    /// Replaces the record of files that passed their last check.
+    pub fn record_checked_files(&mut self, files: &[(PathBuf, ContentHash)]) -> IndexResult<()> {
+        self.with_write_lock(|index| {
+            index.conn.execute("DELETE FROM checked_files", [])?;
+            let mut insert = index
+                .conn
+                .prepare_cached("INSERT INTO checked_files (path, content_hash) VALUES (?1, ?2)")?;
+            for (path, hash) in files {
+                insert.execute([index_path_key(path).as_str(), hash.as_str()])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the indexed notes whose content is what last passed a check,
+    /// with their content hash, by path.
+    ///
+    /// Notes carry their topics, aliases and frontmatter links, but no
+    /// description, tags or other fields.
+    pub fn checked_notes(&self) -> IndexResult<HashMap<PathBuf, (Note, ContentHash)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT n.path, n.id, n.title, n.created, n.modified, n.content_hash,
+                (SELECT GROUP_CONCAT(t.path, '\x1F') FROM note_topics nt
+                 JOIN topics t ON nt.topic_id = t.id WHERE nt.note_id = n.id),
+                (SELECT GROUP_CONCAT(a.alias, '\x1F') FROM aliases a WHERE a.note_id = n.id)
+             FROM notes n
+             JOIN checked_files c ON c.path = n.path AND c.content_hash = n.content_hash",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })?;
+
+        let mut links = self.frontmatter_links()?;
+        let mut notes = HashMap::new();
+        for row in rows {
+            let (path, id, title, created, modified, hash, topics, aliases) = row?;
+            // Notes that can't be rebuilt are simply read again
+            let Some((id, created, modified, hash)) = id.parse::<NoteId>().ok().and_then(|id| {
+                Some((
+                    id,
+                    DateTime::parse_from_rfc3339(&created).ok()?,
+                    DateTime::parse_from_rfc3339(&modified).ok()?,
+                    ContentHash::from_hex(&hash).ok()?,
+                ))
+            }) else {
+                continue;
+            };
+            let topics = topics
+                .iter()
+                .flat_map(|t| t.split('\x1F'))
+                .filter_map(|t| Topic::new(t).ok())
+                .collect();
+            let aliases = aliases
+                .iter()
+                .flat_map(|a| a.split('\x1F'))
+                .map(String::from)
+                .collect();
+            let links = links.remove(&id).unwrap_or_default();
+            let Ok(note) = Note::builder(id, &title, created.to_utc(), modified.to_utc())
+                .aliases(aliases)
+                .topics(topics)
+                .links(links)
+                .build()
+            else {
+                continue;
+            };
+            notes.insert(PathBuf::from(path), (note, hash));
+        }
+        Ok(notes)
+    }
+
+    /// Returns the links of every indexed note, by source note.
+    ///
+    /// Links carry their rels but not their context or target title.
+    pub fn all_links(&self) -> IndexResult<HashMap<NoteId, Vec<Link>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.source_id, l.target_id, GROUP_CONCAT(lr.rel, ' ')
+             FROM links l
+             JOIN link_rels lr ON lr.link_id = l.id
+             GROUP BY l.id
+             ORDER BY l.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?;
+
+        let mut links: HashMap<NoteId, Vec<Link>> = HashMap::new();
+        for row in rows {
+            let (source, target, rels) = row?;
+            let (Ok(source), Ok(target)) = (source.parse::<NoteId>(), target.parse::<NoteId>())
+            else {
+                continue;
+            };
+            if let Ok(link) = Link::new(target, rels.split(' ').collect()) {
+                links.entry(source).or_default().push(link);
+            }
+        }
+        Ok(links)
+    }
+}
@@ -1,11 +1,14 @@
 //! Connection management for SqliteIndex.
 
 use super::SqliteIndex;
+use super::busy::retry_busy;
 use super::transaction::Transaction;
 use crate::index::{IndexError, IndexResult, create_schema};
+use crate::infra::profile::{self, Phase};
 use rusqlite::Connection;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 impl SqliteIndex {
     // ===========================================
@@ -31,6 +34,14 @@ impl SqliteIndex {
     /// Creates parent directories if they don't exist. Initializes the schema
     /// if this is a new database.
     pub fn open(path: &Path) -> IndexResult<Self> {
+        let mut index = profile::time(Phase::IndexOpen, || Self::open_uninstrumented(path))?;
+        if profile::is_enabled() {
+            index.conn.profile(Some(record_query));
+        }
+        Ok(index)
+    }
+
+    fn open_uninstrumented(path: &Path) -> IndexResult<Self> {
         // Create parent directories if needed
         if let Some(parent) = path.parent()
             && !parent.as_os_str().is_empty()
@@ -43,8 +54,10 @@ impl SqliteIndex {
         }
 
         let conn = Connection::open(path)?;
+        conn.busy_timeout(Self::default_busy_timeout())?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-        create_schema(&conn)?;
+        // Two processes may race to create or migrate the schema
+        retry_busy(|| Ok(create_schema(&conn)?))?;
         Ok(Self { conn })
     }
 
@@ -69,8 +82,20 @@ impl SqliteIndex {
     /// Begins a new transaction.
     ///
     /// The transaction will automatically rollback on drop unless `commit()` is called.
+    /// It takes the write lock up front, so it cannot fail halfway on a
+    /// lock held by another process. Inside an open transaction (e.g. under
+    /// [`with_write_lock`](Self::with_write_lock)) a savepoint is used instead.
     pub fn transaction(&mut self) -> IndexResult<Transaction<'_>> {
-        self.conn.execute_batch("BEGIN")?;
+        if !self.conn.is_autocommit() {
+            self.conn.execute_batch("SAVEPOINT den_transaction")?;
+            return Ok(Transaction::savepoint(&self.conn));
+        }
+        retry_busy(|| Ok(self.conn.execute_batch("BEGIN IMMEDIATE")?))?;
         Ok(Transaction::new(&self.conn))
     }
 }
+
+/// Attributes statement execution time to the query phase of `--profile`.
+fn record_query(_sql: &str, elapsed: Duration) {
+    profile::record(Phase::Query, elapsed);
+}
@@ -0,0 +1,118 @@
+//! Comparison of note files with their index rows.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::SqliteIndex;
+use crate::domain::{IndexField, Link, Note, ValidationIssue, ValidationKind};
+use crate::index::{IndexRepository, IndexResult, IndexedNote};
+use crate::infra::ContentHash;
+
+impl SqliteIndex {
+    /// Compares parsed note files with what the index has for their paths.
+    ///
+    /// `files` are the notes read from disk with their content hashes, and
+    /// `scanned` every note file found, including ones that failed to parse.
+    /// Files the index lacks or has different metadata for are reported, as
+    /// are index rows whose file is not among `scanned`. A differing content
+    /// hash means the file changed since it was indexed; metadata that
+    /// differs despite an equal hash means the index is damaged.
+    pub fn find_drift(
+        &self,
+        files: &[(&Path, &Note, &ContentHash)],
+        scanned: &[PathBuf],
+    ) -> IndexResult<Vec<ValidationIssue>> {
+        let indexed = self.list_all()?;
+        let links = self.frontmatter_links()?;
+        let by_path: HashMap<&Path, &IndexedNote> =
+            indexed.iter().map(|note| (note.path(), note)).collect();
+
+        let mut issues = Vec::new();
+        for (path, note, hash) in files {
+            let Some(row) = by_path.get(path) else {
+                issues.push(ValidationIssue::new(*path, ValidationKind::NotIndexed));
+                continue;
+            };
+            let indexed_links = links.get(row.id()).map(Vec::as_slice).unwrap_or(&[]);
+            let fields = differing_fields(note, row, indexed_links);
+            if row.content_hash() != *hash {
+                issues.push(ValidationIssue::new(
+                    *path,
+                    ValidationKind::StaleIndex { fields },
+                ));
+            } else if !fields.is_empty() {
+                issues.push(ValidationIssue::new(
+                    *path,
+                    ValidationKind::IndexMismatch { fields },
+                ));
+            }
+        }
+
+        let scanned: HashSet<&Path> = scanned.iter().map(PathBuf::as_path).collect();
+        let mut orphans: Vec<&IndexedNote> = indexed
+            .iter()
+            .filter(|note| !scanned.contains(note.path()))
+            .collect();
+        orphans.sort_by(|a, b| a.path().cmp(b.path()));
+        for note in orphans {
+            issues.push(ValidationIssue::new(
+                note.path(),
+                ValidationKind::OrphanIndexRow {
+                    id: note.id().clone(),
+                },
+            ));
+        }
+
+        Ok(issues)
+    }
+}
+
+/// Lists the fields an index row has other values for than the note.
+///
+/// Lists are compared as sets, since the index doesn't keep their order.
+fn differing_fields(note: &Note, row: &IndexedNote, indexed_links: &[Link]) -> Vec<IndexField> {
+    let mut fields = Vec::new();
+    if note.id() != row.id() {
+        fields.push(IndexField::Id);
+    }
+    if note.title() != row.title() {
+        fields.push(IndexField::Title);
+    }
+    if note.description() != row.description() {
+        fields.push(IndexField::Description);
+    }
+    if note.note_type() != row.note_type() {
+        fields.push(IndexField::Type);
+    }
+    if note.created() != row.created() {
+        fields.push(IndexField::Created);
+    }
+    if note.modified() != row.modified() {
+        fields.push(IndexField::Modified);
+    }
+    if as_set(note.aliases()) != as_set(row.aliases()) {
+        fields.push(IndexField::Aliases);
+    }
+    if as_set(note.topics()) != as_set(row.topics()) {
+        fields.push(IndexField::Topics);
+    }
+    if as_set(note.tags()) != as_set(row.tags()) {
+        fields.push(IndexField::Tags);
+    }
+    if link_set(note.links()) != link_set(indexed_links) {
+        fields.push(IndexField::Links);
+    }
+    fields
+}
+
+fn as_set<T: ToString>(items: &[T]) -> BTreeSet<String> {
+    items.iter().map(ToString::to_string).collect()
+}
+
+/// Link targets with their rels, ignoring link context and titles.
+fn link_set(links: &[Link]) -> BTreeSet<(String, BTreeSet<String>)> {
+    links
+        .iter()
+        .map(|link| (link.target().to_string(), as_set(link.rel())))
+        .collect()
+}
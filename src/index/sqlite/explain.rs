@@ -0,0 +1,280 @@
+//! Ranking breakdowns for full-text search.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
+
+use super::SqliteIndex;
+use crate::domain::NoteId;
+use crate::index::{IndexError, IndexResult};
+
+/// A column of the full-text index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Title,
+    Description,
+    Aliases,
+    Body,
+}
+
+impl SearchField {
+    /// All fields, in column order.
+    pub const ALL: [SearchField; 4] = [
+        SearchField::Title,
+        SearchField::Description,
+        SearchField::Aliases,
+        SearchField::Body,
+    ];
+
+    /// The BM25 weight search gives matches in this field.
+    pub fn weight(self) -> f64 {
+        match self {
+            SearchField::Title => 10.0,
+            SearchField::Description => 5.0,
+            SearchField::Aliases => 5.0,
+            SearchField::Body => 1.0,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SearchField::Title => "title",
+            SearchField::Description => "description",
+            SearchField::Aliases => "aliases",
+            SearchField::Body => "body",
+        }
+    }
+
+    fn column(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for SearchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How one field contributed to a search result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMatch {
+    pub field: SearchField,
+    /// BM25 score with only this field weighted. Scores of the fields don't
+    /// add up to the rank exactly, since BM25 saturates repeated matches.
+    pub score: f64,
+    /// Byte ranges of the matched terms in the field's indexed text.
+    pub offsets: Vec<Range<usize>>,
+}
+
+impl FieldMatch {
+    /// Returns true if the query matched this field.
+    pub fn matched(&self) -> bool {
+        !self.offsets.is_empty()
+    }
+}
+
+/// The best-matching section of a note's body, whose rank is added to the
+/// note's.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionScore {
+    /// Heading path, e.g. `Guide > Setup`; empty for the text before the
+    /// first heading.
+    pub path: String,
+    pub rank: f64,
+}
+
+/// Why a note matched a search query and how it was ranked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchExplanation {
+    pub id: NoteId,
+    /// The rank [`IndexRepository::search`](crate::index::IndexRepository::search)
+    /// gives the note, 0 if only a section of its body matched.
+    pub rank: f64,
+    /// One entry per field, in column order.
+    pub fields: Vec<FieldMatch>,
+    /// The section that matched, if any.
+    pub section: Option<SectionScore>,
+}
+
+impl SearchExplanation {
+    /// Returns the fields the query matched.
+    pub fn matched_fields(&self) -> impl Iterator<Item = &FieldMatch> {
+        self.fields.iter().filter(|f| f.matched())
+    }
+
+    /// Returns the rank
+    /// [`search_with_sections`](SqliteIndex::search_with_sections) gives the
+    /// note: its own rank plus its section's.
+    pub fn total_rank(&self) -> f64 {
+        self.rank + self.section.as_ref().map_or(0.0, |s| s.rank)
+    }
+}
+
+// Private-use characters mark matches in `highlight()` output; they never
+// appear in note text the tokenizer indexes as words.
+const MATCH_START: char = '\u{E000}';
+const MATCH_END: char = '\u{E001}';
+
+impl SqliteIndex {
+    /// Runs a search and explains each result, best first.
+    ///
+    /// Matches the results and order of
+    /// [`search_with_sections`](SqliteIndex::search_with_sections), so notes
+    /// that matched only in a section of their body are explained too.
+    pub fn explain_search(&self, query: &str) -> IndexResult<Vec<SearchExplanation>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let weights = SearchField::ALL.map(SearchField::weight);
+        let only = |field: SearchField| {
+            let mut w = [0.0; 4];
+            w[field.column()] = field.weight();
+            format!("-bm25(notes_fts, {}, {}, {}, {})", w[0], w[1], w[2], w[3])
+        };
+        let sql = format!(
+            "SELECT
+                n.id,
+                -bm25(notes_fts, {}, {}, {}, {}) as rank,
+                {}, {}, {}, {},
+                highlight(notes_fts, 0, ?2, ?3),
+                highlight(notes_fts, 1, ?2, ?3),
+                highlight(notes_fts, 2, ?2, ?3),
+                highlight(notes_fts, 3, ?2, ?3)
+             FROM notes_fts
+             JOIN notes n ON notes_fts.rowid = n.rowid
+             WHERE notes_fts MATCH ?1
+             ORDER BY rank DESC",
+            weights[0],
+            weights[1],
+            weights[2],
+            weights[3],
+            only(SearchField::Title),
+            only(SearchField::Description),
+            only(SearchField::Aliases),
+            only(SearchField::Body),
+        );
+
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(
+                rusqlite::params![query, MATCH_START.to_string(), MATCH_END.to_string()],
+                |row| {
+                    let id: String = row.get(0)?;
+                    let rank: f64 = row.get(1)?;
+                    let mut fields = Vec::with_capacity(4);
+                    for field in SearchField::ALL {
+                        let score: f64 = row.get(2 + field.column())?;
+                        let highlighted: Option<String> = row.get(6 + field.column())?;
+                        fields.push(FieldMatch {
+                            field,
+                            score,
+                            offsets: highlighted
+                                .as_deref()
+                                .map(match_offsets)
+                                .unwrap_or_default(),
+                        });
+                    }
+                    Ok((id, rank, fields))
+                },
+            )
+            .map_err(query_error)?;
+
+        let mut explanations = Vec::new();
+        for row in rows {
+            let (id, rank, mut fields) = row.map_err(query_error)?;
+            // A field that didn't match contributes nothing; BM25 can still
+            // give it a tiny non-zero score
+            for field in &mut fields {
+                if !field.matched() {
+                    field.score = 0.0;
+                }
+            }
+            let id = id
+                .parse()
+                .map_err(|e| IndexError::InvalidQuery(format!("invalid note ID: {}", e)))?;
+            explanations.push(SearchExplanation {
+                id,
+                rank,
+                fields,
+                section: None,
+            });
+        }
+
+        let sections = match self.search_sections(query) {
+            Ok(sections) => sections,
+            // Column filters such as `title:` only exist in the notes index
+            Err(IndexError::InvalidQuery(_)) => return Ok(explanations),
+            Err(e) => return Err(e),
+        };
+        let mut seen = HashSet::new();
+        for section in sections {
+            if !seen.insert(section.id.clone()) {
+                continue;
+            }
+            let score = SectionScore {
+                path: section.path,
+                rank: section.rank,
+            };
+            match explanations.iter_mut().find(|e| e.id == section.id) {
+                Some(explanation) => explanation.section = Some(score),
+                None => explanations.push(SearchExplanation {
+                    id: section.id,
+                    rank: 0.0,
+                    fields: SearchField::ALL
+                        .map(|field| FieldMatch {
+                            field,
+                            score: 0.0,
+                            offsets: Vec::new(),
+                        })
+                        .to_vec(),
+                    section: Some(score),
+                }),
+            }
+        }
+
+        explanations.sort_by(|a, b| b.total_rank().total_cmp(&a.total_rank()));
+        Ok(explanations)
+    }
+}
+
+fn query_error(e: rusqlite::Error) -> IndexError {
+    let msg = e.to_string();
+    if msg.contains("fts5") || msg.contains("syntax") {
+        IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
+    } else {
+        IndexError::Database(e)
+    }
+}
+
+/// Byte ranges of the marked matches, relative to the text without markers.
+fn match_offsets(highlighted: &str) -> Vec<Range<usize>> {
+    let mut offsets = Vec::new();
+    let mut pos = 0;
+    let mut start = None;
+    for c in highlighted.chars() {
+        match c {
+            MATCH_START => start = Some(pos),
+            MATCH_END => {
+                if let Some(start) = start.take() {
+                    offsets.push(start..pos);
+                }
+            }
+            c => pos += c.len_utf8(),
+        }
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_offsets_are_relative_to_unmarked_text() {
+        let highlighted = "Café \u{E000}rust\u{E001} and \u{E000}Rust\u{E001}";
+        assert_eq!(match_offsets(highlighted), vec![6..10, 15..19]);
+        assert!(match_offsets("no matches").is_empty());
+    }
+}
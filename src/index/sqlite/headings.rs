@@ -0,0 +1,158 @@
+//! Storage and search of the sections of note bodies.
+
+use std::collections::HashSet;
+
+use super::SqliteIndex;
+use super::explain::SearchField;
+use crate::domain::{NoteId, find_sections};
+use crate::index::{IndexError, IndexRepository, IndexResult, SearchResult};
+
+/// A section of a note body that matched a search query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionMatch {
+    pub id: NoteId,
+    /// Heading level, or 0 for the text before the first heading.
+    pub level: u8,
+    /// Heading path, e.g. `Guide > Setup`; empty for the text before the
+    /// first heading.
+    pub path: String,
+    /// Byte offset of the section in the note body.
+    pub start: usize,
+    /// BM25 rank (higher is more relevant).
+    pub rank: f64,
+    /// Matched text with `<b>` markers around the matches.
+    pub snippet: String,
+}
+
+impl SqliteIndex {
+    /// Replaces the indexed sections of a note's body.
+    ///
+    /// The note must already be indexed; its sections are removed with it.
+    pub fn set_headings(&mut self, id: &NoteId, body: &str) -> IndexResult<()> {
+        let id_str = id.to_string();
+        self.conn
+            .execute("DELETE FROM headings WHERE note_id = ?", [&id_str])?;
+
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO headings (note_id, level, path, start_byte, end_byte, text)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for section in find_sections(body) {
+            // The heading itself is searched through the path
+            let text = section.own_text(body);
+            let text = match section.level {
+                0 => text,
+                _ => text.split_once('\n').map_or("", |(_, rest)| rest),
+            };
+            stmt.execute(rusqlite::params![
+                id_str,
+                section.level,
+                section.path,
+                section.start as i64,
+                section.end as i64,
+                text,
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Searches the text of note sections, best match first.
+    ///
+    /// Heading paths count twice as much as the text under them, like the
+    /// title and body of notes.
+    pub fn search_sections(&self, query: &str) -> IndexResult<Vec<SectionMatch>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT
+                h.note_id,
+                h.level,
+                h.path,
+                h.start_byte,
+                -bm25(headings_fts, {}, {}) AS rank,
+                snippet(headings_fts, 1, '<b>', '</b>', '...', 20)
+             FROM headings_fts
+             JOIN headings h ON headings_fts.rowid = h.id
+             WHERE headings_fts MATCH ?1
+             ORDER BY rank DESC",
+            2.0 * SearchField::Body.weight(),
+            SearchField::Body.weight(),
+        ))?;
+        let rows = stmt
+            .query_map([query], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, u8>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, f64>(4)?,
+                    row.get::<_, String>(5)?,
+                ))
+            })
+            .map_err(query_error)?;
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let (id, level, path, start, rank, snippet) = row.map_err(query_error)?;
+            let id = id
+                .parse()
+                .map_err(|e| IndexError::InvalidQuery(format!("invalid note ID: {}", e)))?;
+            matches.push(SectionMatch {
+                id,
+                level,
+                path,
+                start: start as usize,
+                rank,
+                // Snippets can span lines of the body
+                snippet: snippet.split_whitespace().collect::<Vec<_>>().join(" "),
+            });
+        }
+        Ok(matches)
+    }
+
+    /// Searches notes and the sections of their bodies, best first.
+    ///
+    /// Like [`IndexRepository::search`], but notes whose body matched are
+    /// included too, with the best-matching section recorded on the result
+    /// and its rank added to the note's.
+    pub fn search_with_sections(&self, query: &str) -> IndexResult<Vec<SearchResult>> {
+        let mut results = self.search(query)?;
+        let sections = match self.search_sections(query) {
+            Ok(sections) => sections,
+            // Column filters such as `title:` only exist in the notes index
+            Err(IndexError::InvalidQuery(_)) => return Ok(results),
+            Err(e) => return Err(e),
+        };
+
+        let mut seen = HashSet::new();
+        for section in sections {
+            if !seen.insert(section.id.clone()) {
+                continue;
+            }
+            match results.iter_mut().find(|r| r.note().id() == &section.id) {
+                Some(result) => result.add_section_match(&section),
+                None => {
+                    if let Some(note) = self.get_note(&section.id)? {
+                        let mut result = SearchResult::new(note, 0.0);
+                        result.add_section_match(&section);
+                        results.push(result);
+                    }
+                }
+            }
+        }
+
+        results.sort_by(|a, b| b.rank().total_cmp(&a.rank()));
+        Ok(results)
+    }
+}
+
+fn query_error(e: rusqlite::Error) -> IndexError {
+    let msg = e.to_string();
+    if msg.contains("fts5") || msg.contains("syntax") || msg.contains("no such column") {
+        IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
+    } else {
+        IndexError::Database(e)
+    }
+}
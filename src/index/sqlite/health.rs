@@ -0,0 +1,85 @@
+//! Storage of vault health samples for trend reporting.
+
+use super::SqliteIndex;
+use crate::domain::HealthReport;
+use crate::index::IndexResult;
+use chrono::{DateTime, Utc};
+
+/// A vault health report recorded at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthSample {
+    pub taken_at: DateTime<Utc>,
+    pub report: HealthReport,
+}
+
+impl SqliteIndex {
+    /// Records a health sample, keeping at most one per UTC day.
+    ///
+    /// A later sample on the same day replaces the earlier one, so running
+    /// `stats` repeatedly does not skew the trend.
+    pub fn record_health_sample(&mut self, sample: &HealthSample) -> IndexResult<()> {
+        let r = &sample.report;
+        self.conn.execute(
+            "INSERT INTO health_samples
+                (day, taken_at, notes, orphans, links, broken_links, untagged, stale)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(day) DO UPDATE SET
+                taken_at = excluded.taken_at,
+                notes = excluded.notes,
+                orphans = excluded.orphans,
+                links = excluded.links,
+                broken_links = excluded.broken_links,
+                untagged = excluded.untagged,
+                stale = excluded.stale",
+            rusqlite::params![
+                sample.taken_at.format("%Y-%m-%d").to_string(),
+                sample.taken_at.to_rfc3339(),
+                r.notes as i64,
+                r.orphans as i64,
+                r.links as i64,
+                r.broken_links as i64,
+                r.untagged as i64,
+                r.stale as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the most recent `limit` health samples, oldest first.
+    pub fn health_samples(&self, limit: usize) -> IndexResult<Vec<HealthSample>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT taken_at, notes, orphans, links, broken_links, untagged, stale
+             FROM health_samples ORDER BY day DESC LIMIT ?",
+        )?;
+
+        let rows = stmt.query_map([limit as i64], |row| {
+            let taken_at: String = row.get(0)?;
+            let count = |i: usize| row.get::<_, i64>(i).map(|v| v.max(0) as usize);
+            Ok((
+                taken_at,
+                HealthReport {
+                    notes: count(1)?,
+                    orphans: count(2)?,
+                    links: count(3)?,
+                    broken_links: count(4)?,
+                    untagged: count(5)?,
+                    stale: count(6)?,
+                },
+            ))
+        })?;
+
+        let mut samples = Vec::new();
+        for row in rows {
+            let (taken_at, report) = row?;
+            // Rows with an unreadable timestamp are skipped rather than failing the trend
+            if let Ok(dt) = DateTime::parse_from_rfc3339(&taken_at) {
+                samples.push(HealthSample {
+                    taken_at: dt.with_timezone(&Utc),
+                    report,
+                });
+            }
+        }
+        samples.reverse();
+        Ok(samples)
+    }
+}
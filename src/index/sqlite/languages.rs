@@ -0,0 +1,140 @@
+//! Extra full-text tables for notes in languages that need another tokenizer.
+//!
+//! The main `notes_fts` table indexes every note with one tokenizer. Notes
+//! whose `lang` is mapped to a different tokenizer are also indexed in a
+//! `notes_fts_<tokenizer>` table, so e.g. Japanese notes can be searched by
+//! trigram and German ones by stem while the rest of the vault keeps the
+//! default. Searches consult every table and keep each note's best match.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{SqliteIndex, Tokenizer};
+use crate::domain::language_code;
+use crate::index::IndexResult;
+
+/// Metadata key recording the language tokenizers, as `de=porter,ja=trigram`.
+const LANGUAGES_KEY: &str = "fts_languages";
+
+/// Name of the full-text table for notes using `tokenizer`.
+fn language_table(tokenizer: Tokenizer) -> String {
+    format!("notes_fts_{}", tokenizer.as_str())
+}
+
+impl SqliteIndex {
+    /// Returns the tokenizer for each language with its own full-text table.
+    pub fn language_tokenizers(&self) -> IndexResult<BTreeMap<String, Tokenizer>> {
+        let value = self.get_meta(LANGUAGES_KEY)?.unwrap_or_default();
+        Ok(value
+            .split(',')
+            .filter_map(|entry| entry.split_once('='))
+            .filter_map(|(lang, name)| Some((lang.to_string(), Tokenizer::from_name(name)?)))
+            .collect())
+    }
+
+    /// Indexes notes in the given languages with their own tokenizers,
+    /// returning false if the index already does.
+    ///
+    /// Language tags are reduced to their primary subtag, so `de-AT` and `de`
+    /// are the same language. The tables are filled from the indexed notes,
+    /// so this is cheap and needs no rebuild.
+    pub fn set_language_tokenizers(
+        &mut self,
+        languages: &BTreeMap<String, Tokenizer>,
+    ) -> IndexResult<bool> {
+        let languages: BTreeMap<String, Tokenizer> = languages
+            .iter()
+            .filter_map(|(tag, tokenizer)| Some((language_code(tag)?, *tokenizer)))
+            .collect();
+        if self.language_tokenizers()? == languages {
+            return Ok(false);
+        }
+
+        self.with_write_lock(|index| {
+            for tokenizer in Tokenizer::ALL {
+                let table = language_table(tokenizer);
+                index.conn.execute_batch(&format!(
+                    "DROP TRIGGER IF EXISTS {table}_update;
+                     DROP TRIGGER IF EXISTS {table}_delete;
+                     DROP TRIGGER IF EXISTS {table}_lang_insert;
+                     DROP TRIGGER IF EXISTS {table}_lang_delete;
+                     DROP TABLE IF EXISTS {table};"
+                ))?;
+            }
+            let tokenizers: BTreeSet<Tokenizer> = languages.values().copied().collect();
+            for tokenizer in tokenizers {
+                let langs: Vec<&str> = languages
+                    .iter()
+                    .filter(|(_, t)| **t == tokenizer)
+                    .map(|(lang, _)| lang.as_str())
+                    .collect();
+                create_language_table(&index.conn, tokenizer, &langs)?;
+            }
+            let value = languages
+                .iter()
+                .map(|(lang, tokenizer)| format!("{}={}", lang, tokenizer))
+                .collect::<Vec<_>>()
+                .join(",");
+            index.set_meta(LANGUAGES_KEY, &value)
+        })?;
+        Ok(true)
+    }
+
+    /// The language tables in use.
+    pub(crate) fn language_tables(&self) -> IndexResult<Vec<String>> {
+        let tokenizers: BTreeSet<Tokenizer> = self.language_tokenizers()?.into_values().collect();
+        Ok(tokenizers.into_iter().map(language_table).collect())
+    }
+}
+
+/// Creates the full-text table for notes in `langs`, with triggers keeping
+/// it in step with the notes and note_langs tables, and fills it.
+///
+/// Unlike `notes_fts` the table keeps its own copy of the text, so rows can
+/// be dropped by rowid when a note changes language.
+fn create_language_table(
+    conn: &rusqlite::Connection,
+    tokenizer: Tokenizer,
+    langs: &[&str],
+) -> rusqlite::Result<()> {
+    let table = language_table(tokenizer);
+    let langs = langs
+        .iter()
+        .map(|lang| format!("'{}'", lang.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let columns = "title, description, aliases_text, body";
+    let values = "title, COALESCE(description, ''), COALESCE(aliases_text, ''), COALESCE(body, '')";
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE {table} USING fts5(
+            {columns},
+            tokenize='{option}'
+        );
+        CREATE TRIGGER {table}_update
+        AFTER UPDATE ON notes BEGIN
+            DELETE FROM {table} WHERE rowid = OLD.rowid;
+            INSERT INTO {table}(rowid, {columns})
+            SELECT rowid, {values} FROM notes
+            WHERE rowid = NEW.rowid
+              AND EXISTS (SELECT 1 FROM note_langs WHERE note_id = NEW.id AND lang IN ({langs}));
+        END;
+        CREATE TRIGGER {table}_delete
+        AFTER DELETE ON notes BEGIN
+            DELETE FROM {table} WHERE rowid = OLD.rowid;
+        END;
+        CREATE TRIGGER {table}_lang_insert
+        AFTER INSERT ON note_langs WHEN NEW.lang IN ({langs}) BEGIN
+            DELETE FROM {table} WHERE rowid = (SELECT rowid FROM notes WHERE id = NEW.note_id);
+            INSERT INTO {table}(rowid, {columns})
+            SELECT rowid, {values} FROM notes WHERE id = NEW.note_id;
+        END;
+        CREATE TRIGGER {table}_lang_delete
+        AFTER DELETE ON note_langs WHEN OLD.lang IN ({langs}) BEGIN
+            DELETE FROM {table} WHERE rowid = (SELECT rowid FROM notes WHERE id = OLD.note_id);
+        END;
+        INSERT INTO {table}(rowid, {columns})
+        SELECT n.rowid, {values} FROM notes n
+        JOIN note_langs l ON l.note_id = n.id
+        WHERE l.lang IN ({langs});",
+        option = tokenizer.fts5_option(),
+    ))
+}
@@ -0,0 +1,253 @@
+//! Index inspection and maintenance (stats, vacuum, bookkeeping metadata).
+
+use super::SqliteIndex;
+use crate::index::{IndexResult, Tokenizer, get_schema_version};
+use chrono::{DateTime, Utc};
+use rusqlite::OptionalExtension;
+
+/// Metadata key recording when the last full rebuild completed.
+const LAST_FULL_REBUILD_KEY: &str = "last_full_rebuild";
+
+/// Regular tables reported by [`SqliteIndex::stats`], in display order.
+const INDEX_TABLES: &[&str] = &[
+    "notes",
+    "topics",
+    "note_topics",
+    "aliases",
+    "tags",
+    "note_tags",
+    "links",
+    "link_rels",
+    "headings",
+    "urls",
+    "health_samples",
+];
+
+/// Fraction of free pages above which a vacuum is suggested.
+const FREELIST_VACUUM_THRESHOLD: f64 = 0.10;
+
+/// Number of FTS segments above which an FTS merge is suggested.
+const FTS_SEGMENT_THRESHOLD: usize = 32;
+
+// ===========================================
+// Stats Types
+// ===========================================
+
+/// Row count for a single index table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStats {
+    pub name: String,
+    pub rows: usize,
+}
+
+/// A snapshot of index size and health information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStats {
+    /// Highest applied schema version.
+    pub schema_version: i64,
+    /// Total database size in bytes (page count × page size).
+    pub db_size_bytes: u64,
+    /// Bytes used by the FTS5 index segments.
+    pub fts_size_bytes: u64,
+    /// Number of unused pages that a vacuum would reclaim.
+    pub free_pages: u64,
+    /// Total number of pages in the database.
+    pub page_count: u64,
+    /// When the last full rebuild completed, if ever.
+    pub last_full_rebuild: Option<DateTime<Utc>>,
+    /// Tokenizer the full-text index was built with.
+    pub tokenizer: Tokenizer,
+    /// Row counts for each regular table.
+    pub tables: Vec<TableStats>,
+    /// Human-readable maintenance suggestions.
+    pub suggestions: Vec<String>,
+}
+
+/// Result of compacting the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VacuumResult {
+    pub size_before: u64,
+    pub size_after: u64,
+    pub topics_removed: usize,
+    pub tags_removed: usize,
+}
+
+impl SqliteIndex {
+    // ===========================================
+    // Metadata
+    // ===========================================
+
+    /// Reads a bookkeeping value from the `index_meta` table.
+    pub fn get_meta(&self, key: &str) -> IndexResult<Option<String>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM index_meta WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(value)
+    }
+
+    /// Writes a bookkeeping value to the `index_meta` table.
+    pub fn set_meta(&mut self, key: &str, value: &str) -> IndexResult<()> {
+        self.conn.execute(
+            "INSERT INTO index_meta (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            [key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Records the time of a completed full rebuild.
+    pub fn record_full_rebuild(&mut self, at: DateTime<Utc>) -> IndexResult<()> {
+        self.set_meta(LAST_FULL_REBUILD_KEY, &at.to_rfc3339())
+    }
+
+    /// Returns the time of the last full rebuild, if one has been recorded.
+    pub fn last_full_rebuild(&self) -> IndexResult<Option<DateTime<Utc>>> {
+        Ok(self
+            .get_meta(LAST_FULL_REBUILD_KEY)?
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    // ===========================================
+    // Stats
+    // ===========================================
+
+    /// Collects row counts, sizes, and maintenance suggestions for the index.
+    pub fn stats(&self) -> IndexResult<IndexStats> {
+        let mut tables = Vec::with_capacity(INDEX_TABLES.len());
+        for name in INDEX_TABLES {
+            let rows: i64 =
+                self.conn
+                    .query_row(&format!("SELECT COUNT(*) FROM {}", name), [], |row| {
+                        row.get(0)
+                    })?;
+            tables.push(TableStats {
+                name: (*name).to_string(),
+                rows: rows as usize,
+            });
+        }
+
+        let page_count = self.pragma_u64("page_count")?;
+        let page_size = self.pragma_u64("page_size")?;
+        let free_pages = self.pragma_u64("freelist_count")?;
+
+        let fts_size_bytes: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(block)), 0) FROM notes_fts_data",
+            [],
+            |row| row.get(0),
+        )?;
+        let fts_segments = self.fts_segment_count()?;
+
+        let mut suggestions = Vec::new();
+        if page_count > 0 && free_pages as f64 / page_count as f64 > FREELIST_VACUUM_THRESHOLD {
+            suggestions.push(format!(
+                "database: {} of {} pages are free; vacuum to reclaim {} bytes",
+                free_pages,
+                page_count,
+                free_pages * page_size
+            ));
+        }
+        let unused_topics = self.count_unused("topics", "note_topics", "topic_id")?;
+        if unused_topics > 0 {
+            suggestions.push(format!(
+                "topics: {} unreferenced row(s); vacuum will remove them",
+                unused_topics
+            ));
+        }
+        let unused_tags = self.count_unused("tags", "note_tags", "tag_id")?;
+        if unused_tags > 0 {
+            suggestions.push(format!(
+                "tags: {} unreferenced row(s); vacuum will remove them",
+                unused_tags
+            ));
+        }
+        if fts_segments > FTS_SEGMENT_THRESHOLD {
+            suggestions.push(format!(
+                "notes_fts: {} segments; vacuum will merge them",
+                fts_segments
+            ));
+        }
+
+        Ok(IndexStats {
+            schema_version: get_schema_version(&self.conn)?,
+            db_size_bytes: page_count * page_size,
+            fts_size_bytes: fts_size_bytes as u64,
+            free_pages,
+            page_count,
+            last_full_rebuild: self.last_full_rebuild()?,
+            tokenizer: self.tokenizer()?,
+            tables,
+            suggestions,
+        })
+    }
+
+    // ===========================================
+    // Vacuum
+    // ===========================================
+
+    /// Compacts the index.
+    ///
+    /// Removes topics and tags no longer referenced by any note, merges the
+    /// FTS segments, and runs `VACUUM` to return free pages to the filesystem.
+    pub fn vacuum(&mut self) -> IndexResult<VacuumResult> {
+        let size_before = self.pragma_u64("page_count")? * self.pragma_u64("page_size")?;
+
+        let topics_removed = self.conn.execute(
+            "DELETE FROM topics WHERE id NOT IN (SELECT topic_id FROM note_topics)",
+            [],
+        )?;
+        let tags_removed = self.conn.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM note_tags)",
+            [],
+        )?;
+        self.conn
+            .execute("INSERT INTO notes_fts(notes_fts) VALUES('optimize')", [])?;
+        self.conn.execute_batch("VACUUM;")?;
+
+        let size_after = self.pragma_u64("page_count")? * self.pragma_u64("page_size")?;
+
+        Ok(VacuumResult {
+            size_before,
+            size_after,
+            topics_removed,
+            tags_removed,
+        })
+    }
+
+    // ===========================================
+    // Helpers
+    // ===========================================
+
+    fn pragma_u64(&self, name: &str) -> IndexResult<u64> {
+        let value: i64 = self
+            .conn
+            .query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))?;
+        Ok(value.max(0) as u64)
+    }
+
+    fn count_unused(&self, table: &str, junction: &str, column: &str) -> IndexResult<usize> {
+        let count: i64 = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM {} WHERE id NOT IN (SELECT {} FROM {})",
+                table, column, junction
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    fn fts_segment_count(&self) -> IndexResult<usize> {
+        // Row 1 of the data table holds the structure record; the rest are
+        // segment leaves. Counting distinct segment ids gives the segment count.
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(DISTINCT id >> 37) FROM notes_fts_data WHERE id > 10",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+}
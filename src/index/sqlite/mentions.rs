@@ -0,0 +1,54 @@
+//! Storage of `@handle` mentions found in note bodies.
+
+use super::SqliteIndex;
+use crate::domain::NoteId;
+use crate::index::{IndexRepository, IndexResult, IndexedNote};
+
+impl SqliteIndex {
+    /// Replaces the handles a note mentions.
+    ///
+    /// The note must already be indexed; its mentions are removed with it.
+    pub fn set_mentions(&mut self, id: &NoteId, handles: &[String]) -> IndexResult<()> {
+        let id_str = id.to_string();
+        self.conn
+            .execute("DELETE FROM mentions WHERE note_id = ?", [&id_str])?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR IGNORE INTO mentions (note_id, handle) VALUES (?, ?)")?;
+        for handle in handles {
+            stmt.execute([&id_str, handle])?;
+        }
+        Ok(())
+    }
+
+    /// Finds the notes that mention any of the given handles
+    /// (case-insensitive).
+    pub fn notes_mentioning(&self, handles: &[String]) -> IndexResult<Vec<IndexedNote>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT note_id FROM mentions WHERE handle = ? COLLATE NOCASE")?;
+
+        let mut note_ids: Vec<NoteId> = Vec::new();
+        for handle in handles {
+            for id in stmt
+                .query_map([handle], |row| row.get::<_, String>(0))?
+                .filter_map(|r| r.ok())
+                .filter_map(|id_str| id_str.parse().ok())
+            {
+                if !note_ids.contains(&id) {
+                    note_ids.push(id);
+                }
+            }
+        }
+
+        let mut notes = Vec::with_capacity(note_ids.len());
+        for id in note_ids {
+            if let Some(note) = self.get_note(&id)? {
+                notes.push(note);
+            }
+        }
+
+        Ok(notes)
+    }
+}
@@ -1,9 +1,25 @@
 //! SQLite-backed notes index implementation.
 
+mod bodies;
 mod builder_methods;
+mod busy;
+mod checked;
 mod connection;
+mod drift;
+mod explain;
+mod headings;
+mod health;
+mod languages;
+mod maintenance;
+mod mentions;
+mod quick;
 mod repo_impl;
+mod tokenizer;
 mod transaction;
+mod trash;
+mod urls;
+mod wikilinks;
+mod words;
 
 #[cfg(test)]
 mod tests;
@@ -13,6 +29,16 @@ use rusqlite::Connection;
 // Re-export the Transaction type
 pub use transaction::Transaction;
 
+pub use busy::DEFAULT_BUSY_TIMEOUT;
+pub use explain::{FieldMatch, SearchExplanation, SearchField, SectionScore};
+pub use headings::SectionMatch;
+pub use health::HealthSample;
+pub use maintenance::{IndexStats, TableStats, VacuumResult};
+pub use quick::QuickHit;
+pub(crate) use tokenizer::TOKENIZER_KEY;
+pub use tokenizer::Tokenizer;
+pub use urls::UrlReference;
+
 // ===========================================
 // SqliteIndex Struct
 // ===========================================
@@ -0,0 +1,147 @@
+//! Search-as-you-type for launcher integrations.
+
+use std::path::PathBuf;
+
+use super::SqliteIndex;
+use super::explain::SearchField;
+use crate::domain::{ARCHIVED_TAG, NoteId};
+use crate::index::{IndexError, IndexResult};
+
+/// A compact search hit, read in the same query that finds it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickHit {
+    pub id: NoteId,
+    pub title: String,
+    /// Path relative to the notes directory; `quick-search` prints it
+    /// joined to the notes directory.
+    pub path: PathBuf,
+    /// Plain-text excerpt of the body around the match, if the body matched.
+    pub snippet: Option<String>,
+    /// BM25 score weighted like `search`; higher is better.
+    pub score: f64,
+}
+
+impl SqliteIndex {
+    /// Finds notes matching typed input, best first.
+    ///
+    /// Every word of `input` must match the start of a word in the note's
+    /// title, description and aliases, or in one section of its body, so
+    /// partial input like `api des` finds "API Design". Input is never read
+    /// as FTS syntax. Like `search`, a note scores its best match. The
+    /// statement is cached on the connection so repeated calls skip
+    /// preparing it.
+    pub fn quick_search(
+        &self,
+        input: &str,
+        limit: usize,
+        include_archived: bool,
+    ) -> IndexResult<Vec<QuickHit>> {
+        let Some(query) = prefix_query(input) else {
+            return Ok(Vec::new());
+        };
+
+        // Note bodies are searched through their sections, whose text is
+        // always indexed; `notes_fts` only has bodies kept by the builder
+        let weights = SearchField::ALL.map(SearchField::weight);
+        let body = SearchField::Body.weight();
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT n.id, n.title, n.path, m.snippet, m.score
+             FROM (
+                 SELECT n.id AS note_id,
+                     snippet(notes_fts, 3, '', '', '...', 12) AS snippet,
+                     -bm25(notes_fts, {}, {}, {}, {}) AS score
+                 FROM notes_fts
+                 JOIN notes n ON notes_fts.rowid = n.rowid
+                 WHERE notes_fts MATCH ?1
+                 UNION ALL
+                 SELECT h.note_id,
+                     snippet(headings_fts, 1, '', '', '...', 12),
+                     -bm25(headings_fts, {}, {})
+                 FROM headings_fts
+                 JOIN headings h ON headings_fts.rowid = h.id
+                 WHERE headings_fts MATCH ?1
+             ) m
+             JOIN notes n ON n.id = m.note_id
+             WHERE ?2 OR n.id NOT IN (
+                 SELECT nt.note_id FROM note_tags nt
+                 JOIN tags t ON t.id = nt.tag_id
+                 WHERE t.name = ?3)
+             ORDER BY m.score DESC",
+            weights[0],
+            weights[1],
+            weights[2],
+            weights[3],
+            2.0 * body,
+            body,
+        ))?;
+
+        let rows = stmt.query_map(
+            rusqlite::params![query, include_archived, ARCHIVED_TAG],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, f64>(4)?,
+                ))
+            },
+        )?;
+
+        // Rows come best first, so a note's first row has its score; its
+        // snippet is the best of the rows that matched the body
+        let mut hits: Vec<QuickHit> = Vec::new();
+        for row in rows {
+            let (id, title, path, snippet, score) = row?;
+            let id: NoteId = id
+                .parse()
+                .map_err(|e| IndexError::InvalidQuery(format!("invalid note ID: {}", e)))?;
+            let snippet = snippet
+                .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|s| !s.is_empty());
+            if let Some(hit) = hits.iter_mut().find(|hit| hit.id == id) {
+                if hit.snippet.is_none() {
+                    hit.snippet = snippet;
+                }
+            } else if hits.len() < limit {
+                hits.push(QuickHit {
+                    id,
+                    title,
+                    path: PathBuf::from(path),
+                    snippet,
+                    score,
+                });
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// Turns typed input into an FTS query matching each word as a prefix, or
+/// `None` if it has no words.
+fn prefix_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_query_quotes_words() {
+        assert_eq!(
+            prefix_query("api des").as_deref(),
+            Some("\"api\"* \"des\"*")
+        );
+        assert_eq!(
+            prefix_query("c++ \"AND\" (x)").as_deref(),
+            Some("\"c\"* \"AND\"* \"x\"*")
+        );
+        assert_eq!(prefix_query(" -* "), None);
+    }
+}
@@ -1,12 +1,13 @@
 //! IndexRepository trait implementation for SqliteIndex.
 
 use super::SqliteIndex;
+use super::explain::SearchField;
 use crate::domain::{Note, NoteId, Rel, Tag, Topic};
 use crate::index::{
     IndexError, IndexRepository, IndexResult, IndexedNote, RelWithCount, SearchResult,
     TagWithCount, TopicWithCount,
 };
-use crate::infra::ContentHash;
+use crate::infra::{ContentHash, index_path_key};
 use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 
@@ -25,7 +26,8 @@ impl IndexRepository for SqliteIndex {
                 n.id, n.title, n.description, n.created, n.modified, n.path, n.content_hash,
                 (SELECT GROUP_CONCAT(alias, '\x1F') FROM aliases WHERE note_id = n.id) as aliases,
                 (SELECT GROUP_CONCAT(t.path, '\x1F') FROM note_topics nt JOIN topics t ON nt.topic_id = t.id WHERE nt.note_id = n.id) as topics,
-                (SELECT GROUP_CONCAT(t.name, '\x1F') FROM note_tags ntg JOIN tags t ON ntg.tag_id = t.id WHERE ntg.note_id = n.id) as tags
+                (SELECT GROUP_CONCAT(t.name, '\x1F') FROM note_tags ntg JOIN tags t ON ntg.tag_id = t.id WHERE ntg.note_id = n.id) as tags,
+                (SELECT type FROM note_types WHERE note_id = n.id) as note_type
              FROM notes n
              WHERE n.id = ?",
         )?;
@@ -42,6 +44,7 @@ impl IndexRepository for SqliteIndex {
                 row.get::<_, Option<String>>(7)?,
                 row.get::<_, Option<String>>(8)?,
                 row.get::<_, Option<String>>(9)?,
+                row.get::<_, Option<String>>(10)?,
             ))
         });
 
@@ -56,6 +59,7 @@ impl IndexRepository for SqliteIndex {
             aliases_str,
             topics_str,
             tags_str,
+            note_type,
         ) = match result {
             Ok(row) => row,
             Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
@@ -105,6 +109,9 @@ impl IndexRepository for SqliteIndex {
         }
 
         builder = builder.topics(topics).aliases(aliases).tags(tags);
+        if let Some(note_type) = note_type {
+            builder = builder.note_type(note_type);
+        }
 
         Ok(Some(builder.build()))
     }
@@ -119,7 +126,7 @@ impl IndexRepository for SqliteIndex {
 
         // 1. INSERT/UPDATE notes row
         let id_str = note.id().to_string();
-        let path_str = path.to_string_lossy();
+        let path_str = index_path_key(path);
         let created_str = note.created().to_rfc3339();
         let modified_str = note.modified().to_rfc3339();
         let hash_str = content_hash.as_str();
@@ -159,6 +166,10 @@ impl IndexRepository for SqliteIndex {
             .execute("DELETE FROM note_tags WHERE note_id = ?", [&id_str])?;
         tx.conn()
             .execute("DELETE FROM aliases WHERE note_id = ?", [&id_str])?;
+        tx.conn()
+            .execute("DELETE FROM note_types WHERE note_id = ?", [&id_str])?;
+        tx.conn()
+            .execute("DELETE FROM note_langs WHERE note_id = ?", [&id_str])?;
 
         // 3. Insert topics (OR IGNORE) and junctions
         for topic in note.topics() {
@@ -195,11 +206,25 @@ impl IndexRepository for SqliteIndex {
             )?;
         }
 
-        // 6. Delete existing links (cascade will remove link_rels)
+        // 6. Insert the note type
+        if let Some(note_type) = note.note_type() {
+            tx.conn().execute(
+                "INSERT INTO note_types (note_id, type) VALUES (?, ?)",
+                [&id_str, note_type],
+            )?;
+        }
+        if let Some(lang) = note.lang() {
+            tx.conn().execute(
+                "INSERT INTO note_langs (note_id, lang) VALUES (?, ?)",
+                [&id_str, &lang],
+            )?;
+        }
+
+        // 7. Delete existing links (cascade will remove link_rels)
         tx.conn()
             .execute("DELETE FROM links WHERE source_id = ?", [&id_str])?;
 
-        // 7. Insert links and their rels
+        // 8. Insert links and their rels
         for link in note.links() {
             let target_str = link.target().to_string();
             let context = link.context();
@@ -303,47 +328,22 @@ impl IndexRepository for SqliteIndex {
             return Ok(Vec::new());
         }
 
-        // Execute FTS query with weighted BM25 ranking
-        // Weights: title=10, description=5, aliases=5, body=1
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                n.id,
-                -bm25(notes_fts, 10.0, 5.0, 5.0, 1.0) as rank,
-                snippet(notes_fts, -1, '<b>', '</b>', '...', 20) as snippet
-             FROM notes_fts
-             JOIN notes n ON notes_fts.rowid = n.rowid
-             WHERE notes_fts MATCH ?1
-             ORDER BY rank DESC",
-        )?;
-
-        let row_iter = stmt.query_map([query], |row| {
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, f64>(1)?,
-                row.get::<_, String>(2)?,
-            ))
-        });
-
-        // Collect results, properly handling FTS errors
-        let mut results = Vec::new();
-        for row_result in row_iter.map_err(|e| {
-            let msg = e.to_string();
-            if msg.contains("fts5") || msg.contains("syntax") {
-                IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
-            } else {
-                IndexError::Database(e)
-            }
-        })? {
-            // Map rusqlite::Error to IndexError for each row
-            let row = row_result.map_err(|e| {
-                let msg = e.to_string();
-                if msg.contains("fts5") || msg.contains("syntax") {
-                    IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
-                } else {
-                    IndexError::Database(e)
+        let mut results = self.fts_matches("notes_fts", query)?;
+
+        // Notes in languages with their own tokenizer may match there too;
+        // keep each note's best match
+        let tables = self.language_tables()?;
+        if !tables.is_empty() {
+            for table in &tables {
+                for (id, rank, snippet) in self.fts_matches(table, query)? {
+                    match results.iter_mut().find(|(other, _, _)| *other == id) {
+                        Some(existing) if existing.1 >= rank => {}
+                        Some(existing) => *existing = (id, rank, snippet),
+                        None => results.push((id, rank, snippet)),
+                    }
                 }
-            })?;
-            results.push(row);
+            }
+            results.sort_by(|a, b| b.1.total_cmp(&a.1));
         }
 
         // Fetch full notes and build SearchResult
@@ -448,7 +448,7 @@ impl IndexRepository for SqliteIndex {
     }
 
     fn get_content_hash(&self, path: &Path) -> IndexResult<Option<ContentHash>> {
-        let path_str = path.to_string_lossy();
+        let path_str = index_path_key(path);
         let mut stmt = self
             .conn
             .prepare("SELECT content_hash FROM notes WHERE path = ?")?;
@@ -485,6 +485,28 @@ impl IndexRepository for SqliteIndex {
         Ok(notes)
     }
 
+    fn list_without_topics(&self) -> IndexResult<Vec<IndexedNote>> {
+        self.notes_matching(
+            "SELECT id FROM notes n
+             WHERE NOT EXISTS (SELECT 1 FROM note_topics nt WHERE nt.note_id = n.id)",
+        )
+    }
+
+    fn list_without_tags(&self) -> IndexResult<Vec<IndexedNote>> {
+        self.notes_matching(
+            "SELECT id FROM notes n
+             WHERE NOT EXISTS (SELECT 1 FROM note_tags nt WHERE nt.note_id = n.id)",
+        )
+    }
+
+    fn list_unlinked(&self) -> IndexResult<Vec<IndexedNote>> {
+        self.notes_matching(
+            "SELECT id FROM notes n
+             WHERE NOT EXISTS (SELECT 1 FROM links l WHERE l.source_id = n.id)
+               AND NOT EXISTS (SELECT 1 FROM links l WHERE l.target_id = n.id)",
+        )
+    }
+
     fn find_by_id_prefix(&self, prefix: &str) -> IndexResult<Vec<IndexedNote>> {
         if prefix.is_empty() {
             return Ok(Vec::new());
@@ -535,44 +557,21 @@ impl IndexRepository for SqliteIndex {
     }
 
     fn find_by_alias(&self, alias: &str) -> IndexResult<Vec<IndexedNote>> {
-        // Aliases are stored space-separated in aliases_text column
-        // We need to match the alias as a whole word
-        let pattern = format!("%{}%", alias);
-
+        // Match whole aliases; they may contain spaces
         let mut stmt = self
             .conn
-            .prepare("SELECT id FROM notes WHERE aliases_text LIKE ? COLLATE NOCASE")?;
+            .prepare("SELECT DISTINCT note_id FROM aliases WHERE alias = ? COLLATE NOCASE")?;
 
         let note_ids: Vec<NoteId> = stmt
-            .query_map([&pattern], |row| row.get::<_, String>(0))?
+            .query_map([alias], |row| row.get::<_, String>(0))?
             .filter_map(|r| r.ok())
             .filter_map(|id_str| id_str.parse().ok())
             .collect();
 
-        // Filter to ensure exact alias match (not partial)
-        let alias_lower = alias.to_lowercase();
-        let mut notes = Vec::new();
+        let mut notes = Vec::with_capacity(note_ids.len());
         for id in note_ids {
             if let Some(note) = self.get_note(&id)? {
-                // We need to check the actual aliases - fetch from DB
-                let aliases_text: Option<String> = self
-                    .conn
-                    .query_row(
-                        "SELECT aliases_text FROM notes WHERE id = ?",
-                        [id.to_string()],
-                        |row| row.get(0),
-                    )
-                    .ok()
-                    .flatten();
-
-                if let Some(text) = aliases_text {
-                    let has_exact_match = text
-                        .split_whitespace()
-                        .any(|a| a.to_lowercase() == alias_lower);
-                    if has_exact_match {
-                        notes.push(note);
-                    }
-                }
+                notes.push(note);
             }
         }
 
@@ -615,6 +614,23 @@ impl IndexRepository for SqliteIndex {
         Ok(notes)
     }
 
+    fn rel_edges(&self, rel: &Rel) -> IndexResult<Vec<(NoteId, NoteId)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.source_id, l.target_id FROM links l
+             JOIN link_rels lr ON l.id = lr.link_id
+             WHERE lr.rel = ?
+             ORDER BY l.source_id, l.target_id",
+        )?;
+        let edges = stmt
+            .query_map([rel.as_str()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(source, target)| Some((source.parse().ok()?, target.parse().ok()?)))
+            .collect();
+        Ok(edges)
+    }
+
     fn upsert_notes_batch(&mut self, notes: &[(&Note, &ContentHash, &Path)]) -> IndexResult<()> {
         if notes.is_empty() {
             return Ok(());
@@ -664,6 +680,18 @@ impl IndexRepository for SqliteIndex {
             let mut insert_alias = tx
                 .conn()
                 .prepare_cached("INSERT INTO aliases (note_id, alias) VALUES (?, ?)")?;
+            let mut delete_type = tx
+                .conn()
+                .prepare_cached("DELETE FROM note_types WHERE note_id = ?")?;
+            let mut insert_type = tx
+                .conn()
+                .prepare_cached("INSERT INTO note_types (note_id, type) VALUES (?, ?)")?;
+            let mut delete_lang = tx
+                .conn()
+                .prepare_cached("DELETE FROM note_langs WHERE note_id = ?")?;
+            let mut insert_lang = tx
+                .conn()
+                .prepare_cached("INSERT INTO note_langs (note_id, lang) VALUES (?, ?)")?;
             let mut insert_link = tx.conn().prepare_cached(
                 "INSERT INTO links (source_id, target_id, note) VALUES (?, ?, ?)",
             )?;
@@ -676,7 +704,7 @@ impl IndexRepository for SqliteIndex {
 
             for (note, content_hash, path) in notes {
                 let id_str = note.id().to_string();
-                let path_str = path.to_string_lossy();
+                let path_str = index_path_key(path);
                 let created_str = note.created().to_rfc3339();
                 let modified_str = note.modified().to_rfc3339();
                 let hash_str = content_hash.as_str();
@@ -703,6 +731,8 @@ impl IndexRepository for SqliteIndex {
                 delete_topics.execute([&id_str])?;
                 delete_tags.execute([&id_str])?;
                 delete_aliases.execute([&id_str])?;
+                delete_type.execute([&id_str])?;
+                delete_lang.execute([&id_str])?;
                 delete_links.execute([&id_str])?;
 
                 // 3. Insert topics
@@ -723,7 +753,15 @@ impl IndexRepository for SqliteIndex {
                     insert_alias.execute([&id_str, alias])?;
                 }
 
-                // 6. Insert links
+                // 6. Insert the note type
+                if let Some(note_type) = note.note_type() {
+                    insert_type.execute([&id_str, note_type])?;
+                }
+                if let Some(lang) = note.lang() {
+                    insert_lang.execute([&id_str, &lang])?;
+                }
+
+                // 7. Insert links
                 for link in note.links() {
                     let target_str = link.target().to_string();
                     let context = link.context();
@@ -743,3 +781,72 @@ impl IndexRepository for SqliteIndex {
         tx.commit()
     }
 }
+
+impl SqliteIndex {
+    /// Loads the notes whose IDs a query returns, in the query's order.
+    fn notes_matching(&self, query: &str) -> IndexResult<Vec<IndexedNote>> {
+        let mut stmt = self.conn.prepare(query)?;
+        let note_ids: Vec<NoteId> = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter_map(|id_str| id_str.parse().ok())
+            .collect();
+
+        let mut notes = Vec::with_capacity(note_ids.len());
+        for id in note_ids {
+            if let Some(note) = self.get_note(&id)? {
+                notes.push(note);
+            }
+        }
+        Ok(notes)
+    }
+
+    /// Runs a full-text query against `table`, returning note IDs with
+    /// their weighted BM25 rank and a snippet, best first.
+    fn fts_matches(&self, table: &str, query: &str) -> IndexResult<Vec<(String, f64, String)>> {
+        // Weights: title=10, description=5, aliases=5, body=1
+        let weights = SearchField::ALL.map(SearchField::weight);
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT
+                n.id,
+                -bm25({table}, {}, {}, {}, {}) as rank,
+                snippet({table}, -1, '<b>', '</b>', '...', 20) as snippet
+             FROM {table}
+             JOIN notes n ON {table}.rowid = n.rowid
+             WHERE {table} MATCH ?1
+             ORDER BY rank DESC",
+            weights[0], weights[1], weights[2], weights[3],
+        ))?;
+
+        let row_iter = stmt.query_map([query], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        });
+
+        // Collect results, properly handling FTS errors
+        let mut results = Vec::new();
+        for row_result in row_iter.map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("fts5") || msg.contains("syntax") {
+                IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
+            } else {
+                IndexError::Database(e)
+            }
+        })? {
+            // Map rusqlite::Error to IndexError for each row
+            let row = row_result.map_err(|e| {
+                let msg = e.to_string();
+                if msg.contains("fts5") || msg.contains("syntax") {
+                    IndexError::InvalidQuery(format!("invalid FTS query: {}", e))
+                } else {
+                    IndexError::Database(e)
+                }
+            })?;
+            results.push(row);
+        }
+        Ok(results)
+    }
+}
@@ -1,7 +1,9 @@
 use super::*;
-use crate::index::IndexError;
+use crate::index::{IndexError, IndexResult, IndexedNote};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tempfile::tempdir;
 
 // ===========================================
@@ -307,6 +309,109 @@ fn transaction_explicit_rollback() {
     assert_eq!(count, 0, "explicitly rolled back data should not persist");
 }
 
+// ===========================================
+// Concurrent Access
+// ===========================================
+
+fn insert_test_row(index: &SqliteIndex, id: &str, path: &str) -> IndexResult<usize> {
+    Ok(index.conn().execute(
+        "INSERT INTO notes (id, path, title, created, modified, content_hash)
+         VALUES (?, ?, 'Title', '2024-01-15T10:30:00Z', '2024-01-15T10:30:00Z', 'abc123')",
+        [id, path],
+    )?)
+}
+
+fn note_count(index: &SqliteIndex) -> i64 {
+    index
+        .conn()
+        .query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn write_lock_turns_nested_transactions_into_savepoints() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+
+    index
+        .with_write_lock(|index| {
+            let tx = index.transaction()?;
+            tx.execute(
+                "DELETE FROM notes WHERE id = ?",
+                ["01HQ3K5M7NXJK4QZPW8V2R6T9Y"],
+            )?;
+            tx.commit()?;
+            insert_test_row(index, "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "kept.md")?;
+
+            // Dropping a nested transaction only undoes its own work
+            let tx = index.transaction()?;
+            tx.execute(
+                "UPDATE notes SET path = 'undone.md' WHERE id = ?",
+                ["01HQ3K5M7NXJK4QZPW8V2R6T9Y"],
+            )?;
+            drop(tx);
+            Ok(())
+        })
+        .unwrap();
+
+    let path: String = index
+        .conn()
+        .query_row("SELECT path FROM notes", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(path, "kept.md");
+}
+
+#[test]
+fn write_lock_rolls_back_on_error() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+
+    let result: IndexResult<()> = index.with_write_lock(|index| {
+        insert_test_row(index, "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "test.md")?;
+        Err(IndexError::InvalidQuery("boom".to_string()))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(note_count(&index), 0);
+    assert!(index.conn().is_autocommit(), "no transaction left open");
+}
+
+#[test]
+fn second_writer_gives_up_with_busy_error() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("notes.db");
+    let mut writer = SqliteIndex::open(&db_path).unwrap();
+    let mut other = SqliteIndex::open(&db_path).unwrap();
+    other.set_busy_timeout(Duration::from_millis(5)).unwrap();
+
+    writer
+        .with_write_lock(|_| {
+            let result = other.transaction().map(|_| ());
+            assert!(matches!(result, Err(IndexError::Busy)), "{:?}", result);
+            Ok(())
+        })
+        .unwrap();
+
+    // Once the lock is released the other process can write
+    assert!(other.transaction().is_ok());
+}
+
+#[test]
+fn readers_see_previous_state_while_write_lock_held() {
+    let dir = tempdir().unwrap();
+    let db_path = dir.path().join("notes.db");
+    let mut writer = SqliteIndex::open(&db_path).unwrap();
+    let reader = SqliteIndex::open(&db_path).unwrap();
+
+    writer
+        .with_write_lock(|writer| {
+            insert_test_row(writer, "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "test.md")?;
+            assert_eq!(note_count(&reader), 0);
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(note_count(&reader), 1);
+}
+
 // ===========================================
 // IndexRepository Tests - Test Helpers
 // ===========================================
@@ -635,6 +740,62 @@ fn test_get_note_parses_datetime() {
     assert_eq!(retrieved.modified(), modified, "modified should match");
 }
 
+#[test]
+fn test_get_note_loads_note_type() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let extra =
+        std::collections::BTreeMap::from([("type".to_string(), serde_yaml::Value::from("person"))]);
+    let note = Note::builder(
+        test_note_id(),
+        "Ada Lovelace",
+        test_datetime(),
+        test_datetime(),
+    )
+    .extra(extra)
+    .build()
+    .unwrap();
+    let hash = test_content_hash();
+    let path = test_path();
+
+    index.upsert_note(&note, &hash, &path).unwrap();
+    let retrieved = index.get_note(note.id()).unwrap().unwrap();
+    assert_eq!(retrieved.note_type(), Some("person"));
+
+    // Dropping the field clears the type
+    let untyped = Note::new(
+        test_note_id(),
+        "Ada Lovelace",
+        test_datetime(),
+        test_datetime(),
+    )
+    .unwrap();
+    index.upsert_note(&untyped, &hash, &path).unwrap();
+    let retrieved = index.get_note(note.id()).unwrap().unwrap();
+    assert_eq!(retrieved.note_type(), None);
+}
+
+#[test]
+fn test_upsert_notes_batch_stores_note_type() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let extra = std::collections::BTreeMap::from([(
+        "type".to_string(),
+        serde_yaml::Value::from("project"),
+    )]);
+    let note = Note::builder(test_note_id(), "Den", test_datetime(), test_datetime())
+        .extra(extra)
+        .build()
+        .unwrap();
+    let hash = test_content_hash();
+    let path = test_path();
+
+    index
+        .upsert_notes_batch(&[(&note, &hash, path.as_path())])
+        .unwrap();
+
+    let retrieved = index.get_note(note.id()).unwrap().unwrap();
+    assert_eq!(retrieved.note_type(), Some("project"));
+}
+
 // ===========================================
 // Phase 3: upsert_note - Insert Path Tests
 // ===========================================
@@ -1625,6 +1786,69 @@ fn list_by_tag_returns_complete_indexed_note() {
     assert_eq!(indexed.path(), &path);
 }
 
+// ===========================================
+// Hygiene Query Tests
+// ===========================================
+
+fn third_note_id() -> NoteId {
+    "01HQ5B3S0QYJK5RAQX9W3S7T0Z".parse().unwrap()
+}
+
+fn insert(index: &mut SqliteIndex, note: &Note, path: &str) {
+    index
+        .upsert_note(note, &test_content_hash(), Path::new(path))
+        .unwrap();
+}
+
+fn ids(notes: &[IndexedNote]) -> Vec<String> {
+    let mut ids: Vec<String> = notes.iter().map(|n| n.id().to_string()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn list_without_topics_and_tags() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let filed = Note::builder(test_note_id(), "Filed", test_datetime(), test_datetime())
+        .topics(vec![Topic::new("rust").unwrap()])
+        .build()
+        .unwrap();
+    let tagged = Note::builder(other_note_id(), "Tagged", test_datetime(), test_datetime())
+        .tags(vec![Tag::new("draft").unwrap()])
+        .build()
+        .unwrap();
+    insert(&mut index, &filed, "filed.md");
+    insert(&mut index, &tagged, "tagged.md");
+
+    assert_eq!(
+        ids(&index.list_without_topics().unwrap()),
+        vec![other_note_id().to_string()]
+    );
+    assert_eq!(
+        ids(&index.list_without_tags().unwrap()),
+        vec![test_note_id().to_string()]
+    );
+}
+
+#[test]
+fn list_unlinked_excludes_link_sources_and_targets() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let source = Note::builder(test_note_id(), "Source", test_datetime(), test_datetime())
+        .links(vec![Link::new(other_note_id(), vec!["see-also"]).unwrap()])
+        .build()
+        .unwrap();
+    let target = Note::new(other_note_id(), "Target", test_datetime(), test_datetime()).unwrap();
+    let lonely = Note::new(third_note_id(), "Lonely", test_datetime(), test_datetime()).unwrap();
+    insert(&mut index, &source, "source.md");
+    insert(&mut index, &target, "target.md");
+    insert(&mut index, &lonely, "lonely.md");
+
+    assert_eq!(
+        ids(&index.list_unlinked().unwrap()),
+        vec![third_note_id().to_string()]
+    );
+}
+
 // ===========================================
 // all_tags Tests
 // ===========================================
@@ -2171,6 +2395,15 @@ fn insert_note_with_aliases(index: &SqliteIndex, id: &str, title: &str, aliases:
             ],
         )
         .unwrap();
+    for alias in aliases.split_whitespace() {
+        index
+            .conn()
+            .execute(
+                "INSERT INTO aliases (note_id, alias) VALUES (?1, ?2)",
+                rusqlite::params![id, alias],
+            )
+            .unwrap();
+    }
 }
 
 // ===========================================
@@ -2567,7 +2800,7 @@ fn search_phrase_with_quotes() {
     // Exact phrase search
     let results = index.search("\"rust programming\"").unwrap();
     assert!(
-        results.len() >= 1,
+        !results.is_empty(),
         "Phrase search should find at least one result"
     );
     // The exact phrase match should be first or only
@@ -2575,7 +2808,7 @@ fn search_phrase_with_quotes() {
         results[0].note().title() == "Rust Programming"
             || results[0]
                 .snippet()
-                .map_or(false, |s| s.contains("rust programming")),
+                .is_some_and(|s| s.contains("rust programming")),
         "Phrase search should find exact phrase match"
     );
 }
@@ -2608,6 +2841,65 @@ fn search_finds_by_description() {
     assert_eq!(results.len(), 1, "Should find note by description");
 }
 
+#[test]
+fn explain_search_matches_search_order_and_rank() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "Other",
+        "mentions rust once",
+    );
+    insert_note_with_body(
+        &index,
+        "01HQ4A2R9PXJK4QZPW8V2R6T9Z",
+        "Rust Guide",
+        "Learn rust",
+    );
+
+    let results = index.search("rust").unwrap();
+    let explained = index.explain_search("rust").unwrap();
+
+    assert_eq!(explained.len(), results.len());
+    for (result, explanation) in results.iter().zip(&explained) {
+        assert_eq!(&explanation.id, result.note().id());
+        assert!((explanation.rank - result.rank()).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn explain_search_reports_matched_fields_and_offsets() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "Rust Guide",
+        "Learn rust, then more rust",
+    );
+
+    let explained = index.explain_search("rust").unwrap();
+
+    let fields: Vec<&FieldMatch> = explained[0].matched_fields().collect();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].field, SearchField::Title);
+    assert_eq!(fields[0].offsets, vec![0..4]);
+    assert_eq!(fields[1].field, SearchField::Body);
+    assert_eq!(fields[1].offsets, vec![6..10, 22..26]);
+    assert!(fields[0].score > fields[1].score);
+
+    let description = &explained[0].fields[SearchField::Description as usize];
+    assert!(!description.matched());
+    assert_eq!(description.score, 0.0);
+}
+
+#[test]
+fn explain_search_invalid_query_returns_error() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(&index, "01HQ3K5M7NXJK4QZPW8V2R6T9Y", "Test", "body");
+
+    assert!(index.explain_search("AND hello").is_err());
+}
+
 // ===========================================
 // find_by_id_prefix tests
 // ===========================================
@@ -2843,6 +3135,26 @@ fn find_by_alias_multiple_aliases() {
     assert_eq!(results3.len(), 1);
 }
 
+#[test]
+fn find_by_alias_matches_alias_with_spaces() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = Note::builder(
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap(),
+        "New Name",
+        test_datetime(),
+        test_datetime(),
+    )
+    .aliases(vec!["Old Name".to_string()])
+    .build()
+    .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), Path::new("new-name.md"))
+        .unwrap();
+
+    assert_eq!(index.find_by_alias("old name").unwrap().len(), 1);
+    assert!(index.find_by_alias("Old").unwrap().is_empty());
+}
+
 #[test]
 fn find_by_alias_empty_aliases() {
     let mut index = SqliteIndex::open_in_memory().unwrap();
@@ -3171,3 +3483,814 @@ fn backlinks_returns_distinct_source_notes() {
     let results_all = index.backlinks(&target_id, None).unwrap();
     assert_eq!(results_all.len(), 1);
 }
+
+#[test]
+fn rel_edges_returns_pairs_for_rel_only() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let a: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+    let b: NoteId = "01HQ4A2R9PXJK4QZPW8V2R6T9Z".parse().unwrap();
+    let c: NoteId = "01HQ5B3S0QYJK5RZQX9W3S7T0A".parse().unwrap();
+
+    for (id, title) in [(&a, "A"), (&b, "B")] {
+        let note = Note::new(id.clone(), title, test_datetime(), test_datetime()).unwrap();
+        index
+            .upsert_note(
+                &note,
+                &test_content_hash(),
+                &PathBuf::from(format!("{}.md", title)),
+            )
+            .unwrap();
+    }
+
+    insert_link(&index, &a, &b, &["parent"]);
+    insert_link(&index, &b, &c, &["parent", "see-also"]);
+    insert_link(&index, &a, &c, &["see-also"]);
+
+    let edges = index.rel_edges(&Rel::new("parent").unwrap()).unwrap();
+    assert_eq!(edges, vec![(a, b.clone()), (b, c)]);
+
+    let none = index.rel_edges(&Rel::new("child").unwrap()).unwrap();
+    assert!(none.is_empty());
+}
+
+// ===========================================
+// Stats and vacuum tests
+// ===========================================
+
+#[test]
+fn stats_empty_index_reports_zero_rows() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    let stats = index.stats().unwrap();
+
+    assert_eq!(stats.schema_version, 3);
+    assert!(stats.tables.iter().all(|t| t.rows == 0));
+    assert!(stats.tables.iter().any(|t| t.name == "notes"));
+    assert!(stats.db_size_bytes > 0);
+    assert!(stats.last_full_rebuild.is_none());
+}
+
+#[test]
+fn stats_counts_rows_per_table() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = Note::builder(
+        test_note_id(),
+        "Counted Note",
+        test_datetime(),
+        test_datetime(),
+    )
+    .topics(vec![Topic::new("software/rust").unwrap()])
+    .tags(vec![Tag::new("draft").unwrap(), Tag::new("idea").unwrap()])
+    .build()
+    .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+
+    let stats = index.stats().unwrap();
+    let rows = |name: &str| {
+        stats
+            .tables
+            .iter()
+            .find(|t| t.name == name)
+            .map(|t| t.rows)
+            .unwrap()
+    };
+
+    assert_eq!(rows("notes"), 1);
+    assert_eq!(rows("tags"), 2);
+    assert_eq!(rows("note_tags"), 2);
+    assert_eq!(rows("note_topics"), 1);
+    assert!(stats.fts_size_bytes > 0);
+}
+
+#[test]
+fn stats_suggests_removing_unused_tags() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = Note::builder(test_note_id(), "Tagged", test_datetime(), test_datetime())
+        .tags(vec![Tag::new("draft").unwrap()])
+        .build()
+        .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.remove_note(&test_note_id()).unwrap();
+
+    let stats = index.stats().unwrap();
+    assert!(
+        stats.suggestions.iter().any(|s| s.starts_with("tags:")),
+        "expected tag suggestion, got {:?}",
+        stats.suggestions
+    );
+}
+
+#[test]
+fn record_full_rebuild_round_trips() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    index.record_full_rebuild(test_datetime()).unwrap();
+
+    assert_eq!(index.last_full_rebuild().unwrap(), Some(test_datetime()));
+    assert_eq!(
+        index.stats().unwrap().last_full_rebuild,
+        Some(test_datetime())
+    );
+}
+
+#[test]
+fn set_meta_overwrites_existing_value() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    index.set_meta("key", "one").unwrap();
+    index.set_meta("key", "two").unwrap();
+
+    assert_eq!(index.get_meta("key").unwrap().as_deref(), Some("two"));
+    assert_eq!(index.get_meta("missing").unwrap(), None);
+}
+
+#[test]
+fn vacuum_removes_unused_topics_and_tags() {
+    let dir = tempdir().unwrap();
+    let mut index = SqliteIndex::open(&dir.path().join("notes.db")).unwrap();
+    let note = Note::builder(test_note_id(), "Tagged", test_datetime(), test_datetime())
+        .topics(vec![Topic::new("software").unwrap()])
+        .tags(vec![Tag::new("draft").unwrap()])
+        .build()
+        .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.remove_note(&test_note_id()).unwrap();
+
+    let result = index.vacuum().unwrap();
+
+    assert_eq!(result.topics_removed, 1);
+    assert_eq!(result.tags_removed, 1);
+    assert!(index.stats().unwrap().suggestions.is_empty());
+}
+
+// ===========================================
+// Health sample tests
+// ===========================================
+
+use crate::domain::HealthReport;
+
+fn health_sample(at: &str, orphans: usize) -> HealthSample {
+    HealthSample {
+        taken_at: DateTime::parse_from_rfc3339(at)
+            .unwrap()
+            .with_timezone(&Utc),
+        report: HealthReport {
+            notes: 10,
+            orphans,
+            links: 4,
+            broken_links: 1,
+            untagged: 2,
+            stale: 3,
+        },
+    }
+}
+
+#[test]
+fn health_samples_empty_by_default() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    assert!(index.health_samples(10).unwrap().is_empty());
+}
+
+#[test]
+fn health_samples_round_trip_oldest_first() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let later = health_sample("2024-02-01T09:00:00Z", 1);
+    let earlier = health_sample("2024-01-01T09:00:00Z", 5);
+    index.record_health_sample(&later).unwrap();
+    index.record_health_sample(&earlier).unwrap();
+
+    assert_eq!(index.health_samples(10).unwrap(), vec![earlier, later]);
+}
+
+#[test]
+fn health_samples_keep_one_per_day() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    index
+        .record_health_sample(&health_sample("2024-01-01T09:00:00Z", 5))
+        .unwrap();
+    let replacement = health_sample("2024-01-01T18:00:00Z", 2);
+    index.record_health_sample(&replacement).unwrap();
+
+    assert_eq!(index.health_samples(10).unwrap(), vec![replacement]);
+}
+
+#[test]
+fn health_samples_limit_keeps_most_recent() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    for day in 1..=5 {
+        index
+            .record_health_sample(&health_sample(&format!("2024-01-0{}T09:00:00Z", day), day))
+            .unwrap();
+    }
+
+    let samples = index.health_samples(2).unwrap();
+    let orphans: Vec<usize> = samples.iter().map(|s| s.report.orphans).collect();
+    assert_eq!(orphans, vec![4, 5]);
+}
+
+// ===========================================
+// Mention tests
+// ===========================================
+
+#[test]
+fn notes_mentioning_finds_notes_by_handle() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Standup");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index
+        .set_mentions(note.id(), &["jane-doe".to_string()])
+        .unwrap();
+
+    let found = index.notes_mentioning(&["Jane-Doe".to_string()]).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].title(), "Standup");
+    assert!(
+        index
+            .notes_mentioning(&["bob".to_string()])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn set_mentions_replaces_and_removal_cascades() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Standup");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index
+        .set_mentions(note.id(), &["jane-doe".to_string()])
+        .unwrap();
+    index.set_mentions(note.id(), &["bob".to_string()]).unwrap();
+
+    assert!(
+        index
+            .notes_mentioning(&["jane-doe".to_string()])
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        index.notes_mentioning(&["bob".to_string()]).unwrap().len(),
+        1
+    );
+
+    index.remove_note(note.id()).unwrap();
+    let rows: i64 = index
+        .conn
+        .query_row("SELECT COUNT(*) FROM mentions", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 0);
+}
+
+// ===========================================
+// URLs
+// ===========================================
+
+#[test]
+fn url_references_group_notes_and_filter_by_domain() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note1 = sample_note("Reading");
+    index
+        .upsert_note(&note1, &test_content_hash(), &test_path())
+        .unwrap();
+    let note2 = Note::new(other_note_id(), "Tools", test_datetime(), test_datetime()).unwrap();
+    index
+        .upsert_note(
+            &note2,
+            &test_content_hash(),
+            &std::path::PathBuf::from("other.md"),
+        )
+        .unwrap();
+    index
+        .set_urls(
+            note1.id(),
+            &[
+                "https://github.com/rust-lang/rust".to_string(),
+                "https://example.com/".to_string(),
+            ],
+        )
+        .unwrap();
+    index
+        .set_urls(
+            note2.id(),
+            &[
+                "https://github.com/rust-lang/rust".to_string(),
+                "https://gist.github.com/abc".to_string(),
+            ],
+        )
+        .unwrap();
+
+    let all = index.url_references(None).unwrap();
+    let urls: Vec<&str> = all.iter().map(|r| r.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        [
+            "https://example.com/",
+            "https://gist.github.com/abc",
+            "https://github.com/rust-lang/rust",
+        ]
+    );
+    assert_eq!(all[2].domain, "github.com");
+    assert_eq!(all[2].notes.len(), 2);
+
+    let github = index.url_references(Some("www.GitHub.com")).unwrap();
+    assert_eq!(github.len(), 2);
+    assert!(index.url_references(Some("hub.com")).unwrap().is_empty());
+}
+
+#[test]
+fn set_urls_replaces_and_removal_cascades() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Reading");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index
+        .set_urls(note.id(), &["https://old.example.com".to_string()])
+        .unwrap();
+    index
+        .set_urls(note.id(), &["https://new.example.com".to_string()])
+        .unwrap();
+
+    let references = index.url_references(None).unwrap();
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].url, "https://new.example.com");
+
+    index.remove_note(note.id()).unwrap();
+    assert!(index.url_references(None).unwrap().is_empty());
+}
+
+// ===========================================
+// Quick search
+// ===========================================
+
+#[test]
+fn quick_search_matches_word_prefixes() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "API Design",
+        "Resources and verbs",
+    );
+    insert_note_with_body(
+        &index,
+        "01HQ4A2R9PXJK4QZPW8V2R6T9Y",
+        "Meeting notes",
+        "Talked about the API",
+    );
+
+    let hits = index.quick_search("api des", 10, false).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].title, "API Design");
+    assert_eq!(
+        hits[0].path,
+        std::path::PathBuf::from("01HQ3K5M7NXJK4QZPW8V2R6T9Y.md")
+    );
+
+    let hits = index.quick_search("api", 1, false).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].title, "API Design");
+    assert!(hits[0].score > 0.0);
+}
+
+#[test]
+fn quick_search_ignores_fts_syntax_and_hides_archived() {
+    let index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "Old plan",
+        "NEAR the end",
+    );
+    index
+        .conn()
+        .execute_batch(
+            "INSERT INTO tags (name) VALUES ('archived');
+             INSERT INTO note_tags (note_id, tag_id)
+             SELECT '01HQ3K5M7NXJK4QZPW8V2R6T9Y', id FROM tags WHERE name = 'archived';",
+        )
+        .unwrap();
+
+    assert!(index.quick_search("near(", 10, false).unwrap().is_empty());
+    let hits = index.quick_search("near(", 10, true).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].snippet.as_deref(), Some("NEAR the end"));
+    assert!(index.quick_search("  ", 10, true).unwrap().is_empty());
+}
+
+#[test]
+fn quick_search_matches_body_sections() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Cluster");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index
+        .set_headings(
+            note.id(),
+            "Intro\n\n## Scheduling\n\nWe run pods on nodes\n",
+        )
+        .unwrap();
+
+    let hits = index.quick_search("pod", 10, false).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].title, "Cluster");
+    assert_eq!(hits[0].snippet.as_deref(), Some("We run pods on nodes"));
+
+    // A title match has no body excerpt
+    let hits = index.quick_search("clus", 10, false).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].snippet, None);
+}
+
+// ===========================================
+// Heading tests
+// ===========================================
+
+const SECTIONED_BODY: &str =
+    "Intro text\n\n# Guide\n\n## Setup\n\nRun the installer\n\n## Usage\n\nOpen it\n";
+
+#[test]
+fn search_sections_finds_innermost_section() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Manual");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.set_headings(note.id(), SECTIONED_BODY).unwrap();
+
+    let matches = index.search_sections("installer").unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, "Guide > Setup");
+    assert_eq!(matches[0].level, 2);
+    assert_eq!(&SECTIONED_BODY[matches[0].start..][..8], "## Setup");
+    assert_eq!(matches[0].snippet, "Run the <b>installer</b>");
+
+    // Headings match through their path, text before them has none
+    assert_eq!(
+        index.search_sections("usage").unwrap()[0].path,
+        "Guide > Usage"
+    );
+    assert_eq!(index.search_sections("intro").unwrap()[0].path, "");
+}
+
+#[test]
+fn set_headings_replaces_and_removal_cascades() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Manual");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.set_headings(note.id(), SECTIONED_BODY).unwrap();
+    index.set_headings(note.id(), "# Other\n\nText\n").unwrap();
+
+    assert!(index.search_sections("installer").unwrap().is_empty());
+    assert_eq!(index.search_sections("text").unwrap().len(), 1);
+
+    index.remove_note(note.id()).unwrap();
+    let rows: i64 = index
+        .conn
+        .query_row("SELECT COUNT(*) FROM headings", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(rows, 0);
+    assert!(index.search_sections("text").unwrap().is_empty());
+}
+
+#[test]
+fn search_with_sections_adds_body_matches() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Manual");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.set_headings(note.id(), SECTIONED_BODY).unwrap();
+
+    assert!(index.search("installer").unwrap().is_empty());
+    let results = index.search_with_sections("installer").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].section(), Some("Guide > Setup"));
+    assert!(results[0].rank() > 0.0);
+
+    // A title match keeps its snippet and gains the section
+    let results = index.search_with_sections("manual OR installer").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].section(), Some("Guide > Setup"));
+
+    // Column filters only apply to notes
+    assert_eq!(index.search_with_sections("title:manual").unwrap().len(), 1);
+}
+
+#[test]
+fn explain_search_explains_section_only_matches() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = sample_note("Manual");
+    index
+        .upsert_note(&note, &test_content_hash(), &test_path())
+        .unwrap();
+    index.set_headings(note.id(), SECTIONED_BODY).unwrap();
+
+    let results = index.search_with_sections("installer").unwrap();
+    let explained = index.explain_search("installer").unwrap();
+
+    assert_eq!(explained.len(), 1);
+    assert_eq!(&explained[0].id, results[0].note().id());
+    assert_eq!(explained[0].rank, 0.0);
+    assert_eq!(explained[0].matched_fields().count(), 0);
+    let section = explained[0].section.as_ref().expect("section match");
+    assert_eq!(section.path, "Guide > Setup");
+    assert!((explained[0].total_rank() - results[0].rank()).abs() < 1e-9);
+}
+
+// ===========================================
+// Tokenizer
+// ===========================================
+
+#[test]
+fn porter_tokenizer_matches_stems() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "Training log",
+        "Went running twice",
+    );
+    assert_eq!(index.tokenizer().unwrap(), Tokenizer::Unicode61);
+    assert!(index.search("runs").unwrap().is_empty());
+
+    assert!(index.set_tokenizer(Tokenizer::Porter).unwrap());
+    assert!(!index.set_tokenizer(Tokenizer::Porter).unwrap());
+    assert_eq!(index.tokenizer().unwrap(), Tokenizer::Porter);
+    assert_eq!(index.search("runs").unwrap().len(), 1);
+
+    // Sections are indexed with the same tokenizer
+    let id = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+    index.set_headings(&id, "# Log\n\nWent running\n").unwrap();
+    assert_eq!(index.search_sections("runs").unwrap().len(), 1);
+}
+
+#[test]
+fn trigram_tokenizer_matches_substrings() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    index.set_tokenizer(Tokenizer::Trigram).unwrap();
+    insert_note_with_body(
+        &index,
+        "01HQ3K5M7NXJK4QZPW8V2R6T9Y",
+        "Kubernetes",
+        "東京の天気について",
+    );
+
+    assert_eq!(index.search("bernet").unwrap().len(), 1);
+    assert_eq!(index.search("天気に").unwrap().len(), 1);
+    assert_eq!(index.quick_search("berne", 10, false).unwrap().len(), 1);
+}
+
+#[test]
+fn language_tokenizers_index_notes_by_lang() {
+    let note = |id: NoteId, title: &str, lang: Option<&str>| {
+        let extra = lang
+            .map(|l| ("lang".to_string(), serde_yaml::Value::from(l)))
+            .into_iter()
+            .collect();
+        Note::builder(id, title, test_datetime(), test_datetime())
+            .description(Some("東京の天気について"))
+            .extra(extra)
+            .build()
+            .unwrap()
+    };
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let japanese = note(test_note_id(), "Tenki", Some("ja-JP"));
+    let english = note(other_note_id(), "English weather", None);
+    index
+        .upsert_note(&japanese, &test_content_hash(), Path::new("ja.md"))
+        .unwrap();
+    index
+        .upsert_note(&english, &test_content_hash(), Path::new("en.md"))
+        .unwrap();
+    assert!(index.search("天気に").unwrap().is_empty());
+
+    let languages = BTreeMap::from([("ja".to_string(), Tokenizer::Trigram)]);
+    assert!(index.set_language_tokenizers(&languages).unwrap());
+    assert!(
+        !index
+            .set_language_tokenizers(&BTreeMap::from([("JA-jp".to_string(), Tokenizer::Trigram)]))
+            .unwrap()
+    );
+    assert_eq!(index.language_tokenizers().unwrap(), languages);
+
+    // Only the Japanese note is in the trigram table; word matches still
+    // come from the main table
+    let results = index.search("天気に").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].note().title(), "Tenki");
+    assert!(index.search("eather").unwrap().is_empty());
+    assert_eq!(index.search("weather").unwrap().len(), 1);
+
+    // Notes leave the table when their language changes or they are removed
+    let japanese = note(test_note_id(), "Tenki", None);
+    index
+        .upsert_note(&japanese, &test_content_hash(), Path::new("ja.md"))
+        .unwrap();
+    assert!(index.search("天気に").unwrap().is_empty());
+    let japanese = note(test_note_id(), "Tenki", Some("ja"));
+    index
+        .upsert_note(&japanese, &test_content_hash(), Path::new("ja.md"))
+        .unwrap();
+    assert_eq!(index.search("天気に").unwrap().len(), 1);
+    index.remove_by_path(Path::new("ja.md")).unwrap();
+    assert!(index.search("天気に").unwrap().is_empty());
+
+    assert!(index.set_language_tokenizers(&BTreeMap::new()).unwrap());
+    assert!(index.language_tokenizers().unwrap().is_empty());
+}
+
+#[test]
+fn word_counts_are_replaced_and_removed_with_the_note() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = Note::builder(test_note_id(), "Long", test_datetime(), test_datetime())
+        .build()
+        .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), Path::new("long.md"))
+        .unwrap();
+    assert!(index.word_counts().unwrap().is_empty());
+
+    index.set_word_count(note.id(), 120).unwrap();
+    index.set_word_count(note.id(), 450).unwrap();
+    assert_eq!(
+        index.word_counts().unwrap(),
+        HashMap::from([(note.id().clone(), 450)])
+    );
+
+    index.remove_note(note.id()).unwrap();
+    assert!(index.word_counts().unwrap().is_empty());
+}
+
+#[test]
+fn stored_note_rebuilds_note_with_its_body() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let target: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9B".parse().unwrap();
+    let note = Note::builder(test_note_id(), "Kept", test_datetime(), test_datetime())
+        .description(Some("A kept note"))
+        .tags(vec![Tag::new("draft").unwrap()])
+        .links(vec![Link::new(target.clone(), vec!["parent"]).unwrap()])
+        .build()
+        .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), Path::new("kept.md"))
+        .unwrap();
+    assert!(index.stored_note(note.id()).unwrap().is_none());
+
+    index.set_body(note.id(), "The body.\n").unwrap();
+    let (stored, body) = index.stored_note(note.id()).unwrap().unwrap();
+    assert_eq!(stored.title(), "Kept");
+    assert_eq!(stored.description(), Some("A kept note"));
+    assert_eq!(stored.tags(), note.tags());
+    assert_eq!(stored.links()[0].target(), &target);
+    assert_eq!(body, "The body.\n");
+
+    index.remove_note(note.id()).unwrap();
+    assert_eq!(index.body(note.id()).unwrap(), None);
+}
+
+#[test]
+fn set_store_bodies_reports_changes_and_drops_bodies() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let note = Note::builder(test_note_id(), "Kept", test_datetime(), test_datetime())
+        .build()
+        .unwrap();
+    index
+        .upsert_note(&note, &test_content_hash(), Path::new("kept.md"))
+        .unwrap();
+
+    assert!(!index.stores_bodies().unwrap());
+    assert!(index.set_store_bodies(true).unwrap());
+    assert!(!index.set_store_bodies(true).unwrap());
+    index.set_body(note.id(), "The body.").unwrap();
+
+    assert!(index.set_store_bodies(false).unwrap());
+    assert_eq!(index.body(note.id()).unwrap(), None);
+}
+
+#[test]
+fn sync_trash_keeps_the_first_time_a_file_was_seen() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let old = PathBuf::from(".trash/old.md");
+    let new = PathBuf::from(".trash/new.md");
+    let first = test_datetime();
+    let later = first + chrono::Duration::days(3);
+
+    assert_eq!(
+        index.sync_trash(std::slice::from_ref(&old), first).unwrap(),
+        vec![(old.clone(), first)]
+    );
+    assert_eq!(
+        index
+            .sync_trash(&[old.clone(), new.clone()], later)
+            .unwrap(),
+        vec![(old.clone(), first), (new.clone(), later)]
+    );
+
+    // Restored files are forgotten, so trashing them again starts over
+    index.sync_trash(std::slice::from_ref(&new), later).unwrap();
+    assert_eq!(
+        index
+            .sync_trash(&[old.clone(), new.clone()], later)
+            .unwrap(),
+        vec![(old.clone(), later), (new.clone(), later)]
+    );
+
+    index.forget_trash(std::slice::from_ref(&old)).unwrap();
+    assert_eq!(
+        index.sync_trash(std::slice::from_ref(&new), later).unwrap(),
+        vec![(new, later)]
+    );
+}
+
+use crate::domain::{IndexField, ValidationKind};
+
+#[test]
+fn find_drift_tells_changed_files_from_a_damaged_index() {
+    let mut index = SqliteIndex::open_in_memory().unwrap();
+    let tagged = |title: &str, tag: &str| {
+        Note::builder(NoteId::new(), title, test_datetime(), test_datetime())
+            .tags(vec![Tag::new(tag).unwrap()])
+            .build()
+            .unwrap()
+    };
+    let edited = tagged("Edited", "draft");
+    let damaged = tagged("Damaged", "rust");
+    let gone = tagged("Gone", "old");
+    let unindexed = tagged("New", "draft");
+    let old_hash = ContentHash::compute(b"old");
+    let hash = test_content_hash();
+    index
+        .upsert_note(&edited, &old_hash, Path::new("edited.md"))
+        .unwrap();
+    index
+        .upsert_note(&damaged, &hash, Path::new("damaged.md"))
+        .unwrap();
+    index
+        .upsert_note(&gone, &hash, Path::new("gone.md"))
+        .unwrap();
+    index
+        .conn()
+        .execute(
+            "UPDATE notes SET title = 'Mangled' WHERE id = ?",
+            [damaged.id().to_string()],
+        )
+        .unwrap();
+
+    let retitled = Note::builder(
+        edited.id().clone(),
+        "Edited Again",
+        test_datetime(),
+        test_datetime(),
+    )
+    .tags(vec![Tag::new("draft").unwrap(), Tag::new("done").unwrap()])
+    .build()
+    .unwrap();
+    let files = [
+        (Path::new("edited.md"), &retitled, &hash),
+        (Path::new("damaged.md"), &damaged, &hash),
+        (Path::new("new.md"), &unindexed, &hash),
+    ];
+    let scanned: Vec<PathBuf> = files.iter().map(|(p, _, _)| p.to_path_buf()).collect();
+    let issues: Vec<_> = index
+        .find_drift(&files, &scanned)
+        .unwrap()
+        .into_iter()
+        .map(|issue| (issue.path, issue.kind))
+        .collect();
+
+    assert_eq!(
+        issues,
+        vec![
+            (
+                PathBuf::from("edited.md"),
+                ValidationKind::StaleIndex {
+                    fields: vec![IndexField::Title, IndexField::Tags],
+                },
+            ),
+            (
+                PathBuf::from("damaged.md"),
+                ValidationKind::IndexMismatch {
+                    fields: vec![IndexField::Title],
+                },
+            ),
+            (PathBuf::from("new.md"), ValidationKind::NotIndexed),
+            (
+                PathBuf::from("gone.md"),
+                ValidationKind::OrphanIndexRow {
+                    id: gone.id().clone(),
+                },
+            ),
+        ]
+    );
+}
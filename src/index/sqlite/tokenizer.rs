@@ -0,0 +1,102 @@
+//! Choice of FTS5 tokenizer for the full-text index.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::SqliteIndex;
+use crate::index::schema::{create_fts_table, create_headings_fts_table};
+use crate::index::{IndexResult, rebuild_fts};
+
+/// Metadata key recording the tokenizer the full-text index was built with.
+pub(crate) const TOKENIZER_KEY: &str = "fts_tokenizer";
+
+/// How note text is split into searchable terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tokenizer {
+    /// Whole words, case- and diacritic-insensitive (default)
+    #[default]
+    Unicode61,
+    /// Whole words reduced to their English stem, so `running` finds `runs`
+    Porter,
+    /// Every three-character sequence, for substring and CJK search; query
+    /// terms need at least three characters
+    Trigram,
+}
+
+impl Tokenizer {
+    /// All tokenizers.
+    pub const ALL: [Tokenizer; 3] = [Tokenizer::Unicode61, Tokenizer::Porter, Tokenizer::Trigram];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Tokenizer::Unicode61 => "unicode61",
+            Tokenizer::Porter => "porter",
+            Tokenizer::Trigram => "trigram",
+        }
+    }
+
+    /// Parses a tokenizer from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == name)
+    }
+
+    /// The FTS5 `tokenize` option selecting this tokenizer.
+    pub(crate) fn fts5_option(self) -> &'static str {
+        match self {
+            Tokenizer::Unicode61 => "unicode61",
+            Tokenizer::Porter => "porter unicode61",
+            Tokenizer::Trigram => "trigram",
+        }
+    }
+}
+
+impl fmt::Display for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl SqliteIndex {
+    /// Returns the tokenizer the full-text index was built with.
+    pub fn tokenizer(&self) -> IndexResult<Tokenizer> {
+        Ok(self
+            .get_meta(TOKENIZER_KEY)?
+            .and_then(|name| Tokenizer::from_name(&name))
+            .unwrap_or_default())
+    }
+
+    /// Rebuilds the full-text index with `tokenizer`, returning false if it
+    /// already uses it.
+    ///
+    /// The index is recreated from the indexed note text, so searches in
+    /// other processes see either the old index or the new one.
+    pub fn set_tokenizer(&mut self, tokenizer: Tokenizer) -> IndexResult<bool> {
+        if self.tokenizer()? == tokenizer {
+            return Ok(false);
+        }
+        self.with_write_lock(|index| {
+            index
+                .conn
+                .execute_batch("DROP TABLE notes_fts; DROP TABLE headings_fts;")?;
+            create_fts_table(&index.conn, tokenizer)?;
+            create_headings_fts_table(&index.conn, tokenizer)?;
+            rebuild_fts(&index.conn)?;
+            index.set_meta(TOKENIZER_KEY, tokenizer.as_str())
+        })?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizer_names_roundtrip() {
+        for tokenizer in Tokenizer::ALL {
+            assert_eq!(Tokenizer::from_name(tokenizer.as_str()), Some(tokenizer));
+        }
+        assert_eq!(Tokenizer::from_name("ascii"), None);
+    }
+}
@@ -1,5 +1,6 @@
 //! RAII-based transaction support for SQLite.
 
+use super::busy::retry_busy;
 use crate::index::IndexResult;
 use rusqlite::{Connection, Params};
 
@@ -10,6 +11,8 @@ use rusqlite::{Connection, Params};
 pub struct Transaction<'a> {
     conn: &'a Connection,
     finished: bool,
+    /// Nested in an outer transaction, so committing releases a savepoint.
+    savepoint: bool,
 }
 
 impl<'a> Transaction<'a> {
@@ -18,6 +21,16 @@ impl<'a> Transaction<'a> {
         Self {
             conn,
             finished: false,
+            savepoint: false,
+        }
+    }
+
+    /// Creates a transaction backed by the `den_transaction` savepoint.
+    pub(crate) fn savepoint(conn: &'a Connection) -> Self {
+        Self {
+            conn,
+            finished: false,
+            savepoint: true,
         }
     }
 
@@ -35,7 +48,11 @@ impl<'a> Transaction<'a> {
     ///
     /// Consumes the transaction, preventing automatic rollback on drop.
     pub fn commit(mut self) -> IndexResult<()> {
-        self.conn.execute_batch("COMMIT")?;
+        if self.savepoint {
+            self.conn.execute_batch("RELEASE den_transaction")?;
+        } else {
+            retry_busy(|| Ok(self.conn.execute_batch("COMMIT")?))?;
+        }
         self.finished = true;
         Ok(())
     }
@@ -45,17 +62,25 @@ impl<'a> Transaction<'a> {
     /// Consumes the transaction. This is equivalent to dropping without commit,
     /// but makes the intent explicit.
     pub fn rollback(mut self) -> IndexResult<()> {
-        self.conn.execute_batch("ROLLBACK")?;
+        self.conn.execute_batch(self.rollback_sql())?;
         self.finished = true;
         Ok(())
     }
+
+    fn rollback_sql(&self) -> &'static str {
+        if self.savepoint {
+            "ROLLBACK TO den_transaction; RELEASE den_transaction"
+        } else {
+            "ROLLBACK"
+        }
+    }
 }
 
 impl Drop for Transaction<'_> {
     fn drop(&mut self) {
         if !self.finished {
             // Attempt rollback, but ignore errors since we're in drop
-            let _ = self.conn.execute_batch("ROLLBACK");
+            let _ = self.conn.execute_batch(self.rollback_sql());
         }
     }
 }
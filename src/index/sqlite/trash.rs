@@ -0,0 +1,77 @@
+//! Bookkeeping for files in the trash directory.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::SqliteIndex;
+use crate::index::IndexResult;
+use crate::infra::index_path_key;
+
+impl SqliteIndex {
+    /// Records the files now in the trash and returns when each was trashed.
+    ///
+    /// Files seen for the first time are taken to have been trashed at
+    /// `now`; records of files no longer in the trash are dropped.
+    pub fn sync_trash(
+        &mut self,
+        paths: &[PathBuf],
+        now: DateTime<Utc>,
+    ) -> IndexResult<Vec<(PathBuf, DateTime<Utc>)>> {
+        let mut known = self.trashed_at()?;
+        self.with_write_lock(|index| {
+            let mut insert = index
+                .conn
+                .prepare_cached("INSERT OR IGNORE INTO trash (path, trashed_at) VALUES (?1, ?2)")?;
+            for path in paths {
+                if !known.contains_key(path) {
+                    insert.execute([index_path_key(path).as_str(), &now.to_rfc3339()])?;
+                }
+            }
+            let mut delete = index
+                .conn
+                .prepare_cached("DELETE FROM trash WHERE path = ?1")?;
+            for path in known.keys() {
+                if !paths.contains(path) {
+                    delete.execute([index_path_key(path).as_str()])?;
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(paths
+            .iter()
+            .map(|path| (path.clone(), known.remove(path).unwrap_or(now)))
+            .collect())
+    }
+
+    /// Drops the records of trashed files, e.g. once they are purged.
+    pub fn forget_trash(&mut self, paths: &[PathBuf]) -> IndexResult<()> {
+        self.with_write_lock(|index| {
+            let mut delete = index
+                .conn
+                .prepare_cached("DELETE FROM trash WHERE path = ?1")?;
+            for path in paths {
+                delete.execute([index_path_key(path).as_str()])?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns when each recorded file in the trash was trashed.
+    fn trashed_at(&self) -> IndexResult<HashMap<PathBuf, DateTime<Utc>>> {
+        let mut stmt = self.conn.prepare("SELECT path, trashed_at FROM trash")?;
+        let trashed = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(path, at)| {
+                DateTime::parse_from_rfc3339(&at)
+                    .ok()
+                    .map(|at| (PathBuf::from(path), at.with_timezone(&Utc)))
+            })
+            .collect();
+        Ok(trashed)
+    }
+}
@@ -0,0 +1,75 @@
+//! Storage of external URLs found in note bodies.
+
+use super::SqliteIndex;
+use crate::domain::{NoteId, url_domain};
+use crate::index::{IndexRepository, IndexResult, IndexedNote};
+
+/// An external URL and the notes that reference it.
+#[derive(Debug, Clone)]
+pub struct UrlReference {
+    pub url: String,
+    /// Lowercased host of the URL.
+    pub domain: String,
+    pub notes: Vec<IndexedNote>,
+}
+
+impl SqliteIndex {
+    /// Replaces the URLs a note references.
+    ///
+    /// The note must already be indexed; its URLs are removed with it.
+    pub fn set_urls(&mut self, id: &NoteId, urls: &[String]) -> IndexResult<()> {
+        let id_str = id.to_string();
+        self.conn
+            .execute("DELETE FROM urls WHERE note_id = ?", [&id_str])?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR IGNORE INTO urls (note_id, url, domain) VALUES (?, ?, ?)")?;
+        for url in urls {
+            let domain = url_domain(url).unwrap_or_default();
+            stmt.execute([&id_str, url, &domain])?;
+        }
+        Ok(())
+    }
+
+    /// Lists the referenced URLs, sorted, with the notes referencing each.
+    ///
+    /// With a domain, only URLs on it or one of its subdomains are listed
+    /// (`github.com` also matches `gist.github.com`).
+    pub fn url_references(&self, domain: Option<&str>) -> IndexResult<Vec<UrlReference>> {
+        let domain = domain.map(|d| d.trim().trim_start_matches("www.").to_lowercase());
+        let mut stmt = self.conn.prepare(
+            "SELECT url, domain, note_id FROM urls
+             WHERE ?1 IS NULL OR domain = ?1 OR domain LIKE '%.' || ?1
+             ORDER BY url, note_id",
+        )?;
+        let rows = stmt
+            .query_map([&domain], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut references: Vec<UrlReference> = Vec::new();
+        for (url, domain, id_str) in rows {
+            let Ok(id) = id_str.parse::<NoteId>() else {
+                continue;
+            };
+            let Some(note) = self.get_note(&id)? else {
+                continue;
+            };
+            match references.last_mut() {
+                Some(last) if last.url == url => last.notes.push(note),
+                _ => references.push(UrlReference {
+                    url,
+                    domain,
+                    notes: vec![note],
+                }),
+            }
+        }
+        Ok(references)
+    }
+}
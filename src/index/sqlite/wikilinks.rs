@@ -0,0 +1,208 @@
+//! Storage of `[[wikilinks]]` found in note bodies.
+//!
+//! Wikilink targets are stored as written, then resolved to notes by title,
+//! alias or ID and indexed as links with the `mentions` rel, so backlinks,
+//! graphs and link counts include them. Resolution runs over the whole
+//! vault after each change, as a new or renamed note can change what an
+//! unchanged note's wikilinks point at.
+
+use std::collections::{BTreeSet, HashMap};
+
+use rusqlite::OptionalExtension;
+
+use super::SqliteIndex;
+use crate::domain::{Link, NoteId, NoteNames, WIKILINK_REL, WikilinkTarget};
+use crate::index::IndexResult;
+
+impl SqliteIndex {
+    /// Replaces the wikilink targets of a note.
+    ///
+    /// Call after the note is upserted, which drops the links its wikilinks
+    /// resolved to; [`SqliteIndex::resolve_wikilinks`] adds them again.
+    pub fn set_wikilinks(&mut self, id: &NoteId, targets: &[String]) -> IndexResult<()> {
+        let id_str = id.to_string();
+        self.conn
+            .execute("DELETE FROM wikilinks WHERE note_id = ?", [&id_str])?;
+        self.conn
+            .execute("DELETE FROM implicit_links WHERE source_id = ?", [&id_str])?;
+
+        let mut stmt = self
+            .conn
+            .prepare("INSERT OR IGNORE INTO wikilinks (note_id, target) VALUES (?, ?)")?;
+        for target in targets {
+            stmt.execute([&id_str, target])?;
+        }
+        Ok(())
+    }
+
+    /// Returns the wikilink targets of every indexed note, by note.
+    pub fn all_wikilinks(&self) -> IndexResult<HashMap<NoteId, Vec<String>>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT note_id, target FROM wikilinks ORDER BY note_id, target")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut wikilinks: HashMap<NoteId, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (id, target) = row?;
+            if let Ok(id) = id.parse() {
+                wikilinks.entry(id).or_default().push(target);
+            }
+        }
+        Ok(wikilinks)
+    }
+
+    /// Returns the titles, aliases and IDs of every indexed note.
+    pub fn note_names(&self) -> IndexResult<NoteNames> {
+        let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+        let mut stmt = self.conn.prepare("SELECT note_id, alias FROM aliases")?;
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (id, alias) = row?;
+            aliases.entry(id).or_default().push(alias);
+        }
+
+        let mut names = NoteNames::new();
+        let mut stmt = self.conn.prepare("SELECT id, title FROM notes")?;
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (id_str, title) = row?;
+            if let Ok(id) = id_str.parse() {
+                let note_aliases = aliases.remove(&id_str).unwrap_or_default();
+                names.add(&id, &title, &note_aliases);
+            }
+        }
+        Ok(names)
+    }
+
+    /// Brings the links for wikilinks up to date with the indexed notes.
+    ///
+    /// Each wikilink that resolves to another note gets a link with the
+    /// `mentions` rel, merged into a frontmatter link to the same note if
+    /// there is one. Links for wikilinks that no longer resolve, or resolve
+    /// elsewhere, are removed.
+    pub fn resolve_wikilinks(&mut self) -> IndexResult<()> {
+        let names = self.note_names()?;
+        let mut wanted: BTreeSet<(String, String)> = BTreeSet::new();
+        for (source, targets) in self.all_wikilinks()? {
+            for target in targets {
+                if let WikilinkTarget::Note(id) = names.resolve(&target)
+                    && id != source
+                {
+                    wanted.insert((source.to_string(), id.to_string()));
+                }
+            }
+        }
+
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source_id, target_id, added_link FROM implicit_links")?;
+        let current: Vec<(String, String, bool)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<_, _>>()?;
+        drop(stmt);
+
+        for (source, target, added_link) in &current {
+            if wanted.remove(&(source.clone(), target.clone())) {
+                continue;
+            }
+            if *added_link {
+                self.conn.execute(
+                    "DELETE FROM links WHERE source_id = ? AND target_id = ?",
+                    [source, target],
+                )?;
+            } else {
+                self.conn.execute(
+                    "DELETE FROM link_rels WHERE rel = ? AND link_id =
+                        (SELECT id FROM links WHERE source_id = ? AND target_id = ?)",
+                    [WIKILINK_REL, source, target],
+                )?;
+            }
+            self.conn.execute(
+                "DELETE FROM implicit_links WHERE source_id = ? AND target_id = ?",
+                [source, target],
+            )?;
+        }
+
+        for (source, target) in &wanted {
+            let existing: Option<i64> = self
+                .conn
+                .query_row(
+                    "SELECT id FROM links WHERE source_id = ? AND target_id = ?",
+                    [source, target],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let link_id = match existing {
+                Some(id) => id,
+                None => {
+                    self.conn.execute(
+                        "INSERT INTO links (source_id, target_id) VALUES (?, ?)",
+                        [source, target],
+                    )?;
+                    self.conn.last_insert_rowid()
+                }
+            };
+            let added_rel = self.conn.execute(
+                "INSERT OR IGNORE INTO link_rels (link_id, rel) VALUES (?, ?)",
+                rusqlite::params![link_id, WIKILINK_REL],
+            )? > 0;
+            // A frontmatter link that already has the rel needs no record
+            if added_rel {
+                self.conn.execute(
+                    "INSERT INTO implicit_links (source_id, target_id, added_link) VALUES (?, ?, ?)",
+                    rusqlite::params![source, target, existing.is_none()],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the links declared in the frontmatter of every indexed note:
+    /// [`SqliteIndex::all_links`] without what wikilinks added.
+    pub fn frontmatter_links(&self) -> IndexResult<HashMap<NoteId, Vec<Link>>> {
+        let mut links = self.all_links()?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source_id, target_id, added_link FROM implicit_links")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, bool>(2)?,
+            ))
+        })?;
+
+        for row in rows {
+            let (source, target, added_link) = row?;
+            let (Ok(source), Ok(target)) = (source.parse::<NoteId>(), target.parse::<NoteId>())
+            else {
+                continue;
+            };
+            let Some(source_links) = links.get_mut(&source) else {
+                continue;
+            };
+            let Some(pos) = source_links.iter().position(|l| *l.target() == target) else {
+                continue;
+            };
+            let rels: Vec<&str> = source_links[pos]
+                .rel()
+                .iter()
+                .map(|rel| rel.as_str())
+                .filter(|rel| *rel != WIKILINK_REL)
+                .collect();
+            match Link::new(target, rels) {
+                Ok(link) if !added_link => source_links[pos] = link,
+                _ => {
+                    source_links.remove(pos);
+                }
+            }
+        }
+        links.retain(|_, source_links| !source_links.is_empty());
+        Ok(links)
+    }
+}
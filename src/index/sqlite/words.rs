@@ -0,0 +1,33 @@
+//! Storage of note body word counts.
+
+use std::collections::HashMap;
+
+use super::SqliteIndex;
+use crate::domain::NoteId;
+use crate::index::IndexResult;
+
+impl SqliteIndex {
+    /// Records the number of words in a note's body.
+    ///
+    /// The note must already be indexed; its count is removed with it.
+    pub fn set_word_count(&mut self, id: &NoteId, words: usize) -> IndexResult<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO note_words (note_id, words) VALUES (?, ?)",
+            rusqlite::params![id.to_string(), words as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the body word count of every note that has one.
+    pub fn word_counts(&self) -> IndexResult<HashMap<NoteId, usize>> {
+        let mut stmt = self.conn.prepare("SELECT note_id, words FROM note_words")?;
+        let counts = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(id, words)| Some((id.parse().ok()?, words.max(0) as usize)))
+            .collect();
+        Ok(counts)
+    }
+}
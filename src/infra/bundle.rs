@@ -0,0 +1,167 @@
+//! Passphrase-encrypted bundles.
+//!
+//! A bundle is a header followed by data encrypted with XChaCha20-Poly1305
+//! under a key derived from a passphrase with Argon2id. The header is
+//! authenticated along with the data, so a wrong passphrase and any change
+//! to the file are both detected when it's opened.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use thiserror::Error;
+
+/// First bytes of every bundle.
+const MAGIC: &[u8; 4] = b"DENB";
+
+/// Current bundle format version.
+const VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Errors from sealing or opening a bundle.
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("not a den bundle")]
+    NotABundle,
+
+    #[error("unsupported bundle version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("wrong passphrase, or the bundle was modified")]
+    Decrypt,
+
+    #[error("passphrase is empty")]
+    EmptyPassphrase,
+
+    #[error("failed to derive key: {0}")]
+    KeyDerivation(String),
+
+    #[error("failed to encrypt bundle")]
+    Encrypt,
+}
+
+/// Encrypts `data` with a key derived from `passphrase`.
+pub fn seal_bundle(data: &[u8], passphrase: &str) -> Result<Vec<u8>, BundleError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut bundle = Vec::with_capacity(HEADER_LEN + data.len() + 16);
+    bundle.extend_from_slice(MAGIC);
+    bundle.push(VERSION);
+    bundle.extend_from_slice(&salt);
+    bundle.extend_from_slice(&nonce);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: data,
+                aad: &bundle,
+            },
+        )
+        .map_err(|_| BundleError::Encrypt)?;
+    bundle.extend_from_slice(&ciphertext);
+    Ok(bundle)
+}
+
+/// Decrypts a bundle sealed with [`seal_bundle`].
+pub fn open_bundle(bundle: &[u8], passphrase: &str) -> Result<Vec<u8>, BundleError> {
+    if bundle.len() < HEADER_LEN || !bundle.starts_with(MAGIC) {
+        return Err(BundleError::NotABundle);
+    }
+    let (header, ciphertext) = bundle.split_at(HEADER_LEN);
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+        return Err(BundleError::UnsupportedVersion(version));
+    }
+    let salt = &header[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN];
+    let nonce = XNonce::from_slice(&header[HEADER_LEN - NONCE_LEN..]);
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| BundleError::Decrypt)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, BundleError> {
+    if passphrase.is_empty() {
+        return Err(BundleError::EmptyPassphrase);
+    }
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| BundleError::KeyDerivation(e.to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let bundle = seal_bundle(b"topics and tags", "correct horse").unwrap();
+        assert!(bundle.starts_with(MAGIC));
+        assert!(!bundle.windows(6).any(|w| w == b"topics"));
+
+        assert_eq!(
+            open_bundle(&bundle, "correct horse").unwrap(),
+            b"topics and tags"
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_or_tampering_is_detected() {
+        let bundle = seal_bundle(b"data", "correct horse").unwrap();
+        assert!(matches!(
+            open_bundle(&bundle, "battery staple"),
+            Err(BundleError::Decrypt)
+        ));
+
+        // The header is authenticated too
+        let mut tampered = bundle.clone();
+        tampered[MAGIC.len() + 1] ^= 1;
+        assert!(matches!(
+            open_bundle(&tampered, "correct horse"),
+            Err(BundleError::Decrypt)
+        ));
+
+        let mut tampered = bundle;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            open_bundle(&tampered, "correct horse"),
+            Err(BundleError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn rejects_other_files_and_versions() {
+        assert!(matches!(
+            open_bundle(b"# A note", "pw"),
+            Err(BundleError::NotABundle)
+        ));
+
+        let mut bundle = seal_bundle(b"data", "pw").unwrap();
+        bundle[MAGIC.len()] = 9;
+        assert!(matches!(
+            open_bundle(&bundle, "pw"),
+            Err(BundleError::UnsupportedVersion(9))
+        ));
+
+        assert!(matches!(
+            seal_bundle(b"data", ""),
+            Err(BundleError::EmptyPassphrase)
+        ));
+    }
+}
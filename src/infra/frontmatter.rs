@@ -1,7 +1,10 @@
 //! Frontmatter parser for extracting YAML metadata from markdown files.
 
-use crate::domain::Note;
+use crate::domain::{NOTE_FIELDS, Note};
 use crate::infra::ContentHash;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 /// Result of parsing a markdown file with frontmatter.
@@ -9,6 +12,8 @@ use thiserror::Error;
 pub struct ParsedNote {
     pub note: Note,
     pub body: String,
+    /// The 1-based line of the file the body starts on
+    pub body_line: usize,
     pub content_hash: ContentHash,
 }
 
@@ -64,6 +69,30 @@ pub(crate) fn parse_with_hash(
     content: &str,
     content_hash: ContentHash,
 ) -> Result<ParsedNote, ParseError> {
+    let (yaml_content, body) = split_frontmatter(content)?;
+
+    // Parse the YAML
+    let note: Note = serde_yaml::from_str(yaml_content)?;
+
+    let frontmatter = &content[..content.len() - body.len()];
+    Ok(ParsedNote {
+        note,
+        body: body.to_string(),
+        body_line: frontmatter.matches('\n').count() + 1,
+        content_hash,
+    })
+}
+
+/// Splits markdown content into its raw YAML frontmatter and body.
+///
+/// Unlike [`parse`], the YAML is not interpreted, so this also works for
+/// markdown written by other tools.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the content doesn't start with `---` or there's
+/// no closing `---` delimiter.
+pub fn split_frontmatter(content: &str) -> Result<(&str, &str), ParseError> {
     // Check for opening delimiter - must be at the very start
     if !content.starts_with("---") {
         return Err(ParseError::MissingOpeningDelimiter);
@@ -101,19 +130,12 @@ pub(crate) fn parse_with_hash(
     };
 
     let body = if after_opening + body_start <= content.len() {
-        content[after_opening + body_start..].to_string()
+        &content[after_opening + body_start..]
     } else {
-        String::new()
+        ""
     };
 
-    // Parse the YAML
-    let note: Note = serde_yaml::from_str(yaml_content)?;
-
-    Ok(ParsedNote {
-        note,
-        body,
-        content_hash,
-    })
+    Ok((yaml_content, body))
 }
 
 /// Serializes a Note and body to markdown with YAML frontmatter.
@@ -138,6 +160,89 @@ pub fn serialize(note: &Note, body: &str) -> String {
     format!("---\n{}---\n{}", yaml, body)
 }
 
+/// Frontmatter conventions for written notes.
+///
+/// Loaded from the `[frontmatter]` section of the config file. Defaults are
+/// only added when a note is created; the key order applies to every write.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FrontmatterOptions {
+    /// Extra fields for new notes, e.g. `status: draft`. Keys den interprets
+    /// itself (`id`, `title`, `tags`, ...) are ignored.
+    pub defaults: BTreeMap<String, Value>,
+    /// Keys written first, in this order. Remaining keys keep the usual
+    /// order; listed keys the note does not have are skipped.
+    pub order: Vec<String>,
+    /// Write each link's target title next to its ID, kept in sync when the
+    /// index is updated.
+    pub link_titles: bool,
+    /// Refuse `created` and `modified` times in the past for new notes, so
+    /// timestamps on a shared vault can be trusted.
+    pub forbid_backdating: bool,
+}
+
+impl FrontmatterOptions {
+    /// Returns the note with any default fields it does not already have.
+    pub fn apply_defaults(&self, note: Note) -> Note {
+        let missing: Vec<_> = self
+            .defaults
+            .iter()
+            .filter(|(key, _)| {
+                !NOTE_FIELDS.contains(&key.as_str()) && !note.extra().contains_key(*key)
+            })
+            .collect();
+        if missing.is_empty() {
+            return note;
+        }
+
+        let mut extra = note.extra().clone();
+        for (key, value) in missing {
+            extra.insert(key.clone(), value.clone());
+        }
+
+        Note::builder(
+            note.id().clone(),
+            note.title(),
+            note.created(),
+            note.modified(),
+        )
+        .description(note.description().map(|s| s.to_string()))
+        .topics(note.topics().to_vec())
+        .aliases(note.aliases().to_vec())
+        .tags(note.tags().to_vec())
+        .links(note.links().to_vec())
+        .extra(extra)
+        .build()
+        .expect("rebuilding a valid note cannot fail")
+    }
+}
+
+/// Serializes a Note and body using the given frontmatter options.
+///
+/// Like [`serialize`], but keys listed in `options.order` are written first.
+pub fn serialize_with(note: &Note, body: &str, options: &FrontmatterOptions) -> String {
+    if options.order.is_empty() {
+        return serialize(note, body);
+    }
+
+    let Value::Mapping(mut fields) =
+        serde_yaml::to_value(note).expect("Note serialization is infallible")
+    else {
+        unreachable!("a note always serializes to a mapping");
+    };
+
+    let mut ordered = Mapping::new();
+    for key in &options.order {
+        if let Some((key, value)) = fields.shift_remove_entry(key.as_str()) {
+            ordered.insert(key, value);
+        }
+    }
+    ordered.extend(fields);
+
+    let yaml = serde_yaml::to_string(&ordered).expect("Note serialization is infallible");
+    format!("---\n{}---\n{}", yaml, body)
+}
+
 /// Finds the position of the closing `---` delimiter.
 ///
 /// The closing delimiter must:
@@ -244,6 +349,7 @@ It has multiple paragraphs.
         assert_eq!(result.note.title(), "Test Note");
         assert!(result.body.contains("This is the body content."));
         assert!(result.body.contains("multiple paragraphs"));
+        assert_eq!(result.body_line, 7);
     }
 
     #[test]
@@ -1047,4 +1153,66 @@ More text after.
             "Double roundtrip should produce identical output"
         );
     }
+
+    // ===========================================
+    // Phase 10: Frontmatter Options
+    // ===========================================
+
+    fn options(toml: &str) -> FrontmatterOptions {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn serialize_with_default_options_matches_serialize() {
+        let note = minimal_note();
+        assert_eq!(
+            serialize_with(&note, "Body", &FrontmatterOptions::default()),
+            serialize(&note, "Body")
+        );
+    }
+
+    #[test]
+    fn serialize_with_puts_ordered_keys_first() {
+        let (created, modified) = test_timestamps();
+        let note = Note::builder(test_note_id(), "Test", created, modified)
+            .tags(vec![Tag::new("rust").unwrap()])
+            .build()
+            .unwrap();
+        let note = options(r#"defaults = { status = "draft" }"#).apply_defaults(note);
+        let opts = options(r#"order = ["title", "status", "missing", "id"]"#);
+
+        let output = serialize_with(&note, "", &opts);
+        let keys: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.split_once(':').map(|(key, _)| key))
+            .filter(|key| !key.starts_with('-') && !key.starts_with(' '))
+            .collect();
+
+        assert_eq!(
+            keys,
+            vec!["title", "status", "id", "created", "modified", "tags"]
+        );
+        assert_eq!(parse(&output).unwrap().note, note);
+    }
+
+    #[test]
+    fn apply_defaults_keeps_existing_values() {
+        let yaml = "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: Test\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\nstatus: done\n---\n";
+        let note = parse(yaml).unwrap().note;
+        let opts = options(r#"defaults = { status = "draft", author = "Ada", title = "Ignored" }"#);
+
+        let note = opts.apply_defaults(note);
+
+        assert_eq!(note.title(), "Test");
+        assert_eq!(note.extra()["status"], Value::from("done"));
+        assert_eq!(note.extra()["author"], Value::from("Ada"));
+        assert!(!note.extra().contains_key("title"));
+    }
+
+    #[test]
+    fn roundtrip_preserves_extra_fields() {
+        let content = "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: Test\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\nreviewers:\n- ada\n- grace\n---\nBody\n";
+        let parsed = parse(content).unwrap();
+        assert_eq!(serialize(&parsed.note, &parsed.body), content);
+    }
 }
@@ -2,7 +2,13 @@
 
 use crate::domain::Note;
 use crate::infra::content_hash::ContentHash;
-use crate::infra::frontmatter::{ParseError, ParsedNote, parse_with_hash, serialize};
+use crate::infra::frontmatter::{
+    FrontmatterOptions, ParseError, ParsedNote, parse_with_hash, serialize_with,
+};
+use crate::infra::ignore::{IGNORE_FILE, IgnoreRules};
+use crate::infra::platform::long_path;
+use crate::infra::profile::{self, Phase};
+use serde::{Deserialize, Serialize};
 use std::io::{self, Write as IoWrite};
 use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
@@ -42,11 +48,43 @@ pub enum FsError {
     #[error("parent directory does not exist: {path}")]
     ParentNotFound { path: PathBuf },
 
+    #[error("file already exists: {path}")]
+    AlreadyExists { path: PathBuf },
+
     #[error("path is not a directory: {path}")]
     NotADirectory { path: PathBuf },
 
     #[error("invalid encoding in {path}: {encoding}")]
     InvalidEncoding { path: PathBuf, encoding: String },
+
+    #[error(
+        "{path} is inside the vault at {outer}; use --dir {outer}, or if the nesting is \
+         deliberate add \"{relative}/\" to exclude under [scan] in the config"
+    )]
+    NestedVault {
+        path: PathBuf,
+        outer: PathBuf,
+        /// `path` relative to `outer`.
+        relative: PathBuf,
+    },
+
+    #[error(
+        "{path} contains another vault at {inner}; move it out, or add \"{inner}/\" to \
+         exclude under [scan] in the config to index it separately"
+    )]
+    ContainsVault {
+        path: PathBuf,
+        /// The other vault, relative to `path`.
+        inner: PathBuf,
+    },
+
+    #[error("invalid ignore pattern '{pattern}' in {origin}: {reason}")]
+    InvalidIgnorePattern {
+        /// Where the pattern came from, e.g. `.denignore:3`.
+        origin: String,
+        pattern: String,
+        reason: String,
+    },
 }
 
 impl FsError {
@@ -72,7 +110,8 @@ impl FsError {
 /// Returns `FsError::InvalidEncoding` if the file is not valid UTF-8 or uses unsupported encoding.
 /// Returns `FsError::Parse` if the file content is invalid.
 pub fn read_note(path: &Path) -> Result<ParsedNote, FsError> {
-    let bytes = std::fs::read(path).map_err(|e| FsError::from_io(path, e))?;
+    let bytes = profile::time(Phase::FileIo, || std::fs::read(long_path(path)))
+        .map_err(|e| FsError::from_io(path, e))?;
     parse_note_from_bytes(bytes, path)
 }
 
@@ -86,6 +125,10 @@ pub fn read_note(path: &Path) -> Result<ParsedNote, FsError> {
 /// Returns `FsError::InvalidEncoding` if the bytes are not valid UTF-8 or use unsupported encoding.
 /// Returns `FsError::Parse` if the content is invalid.
 pub fn parse_note_from_bytes(bytes: Vec<u8>, path: &Path) -> Result<ParsedNote, FsError> {
+    profile::time(Phase::Parse, || parse_bytes(bytes, path))
+}
+
+fn parse_bytes(bytes: Vec<u8>, path: &Path) -> Result<ParsedNote, FsError> {
     // Compute hash from raw bytes BEFORE any BOM stripping or encoding conversion
     let content_hash = ContentHash::compute(&bytes);
 
@@ -142,6 +185,44 @@ pub fn parse_note_from_bytes(bytes: Vec<u8>, path: &Path) -> Result<ParsedNote,
 /// Returns `FsError::ParentNotFound` if the parent directory doesn't exist.
 /// Returns `FsError::AtomicWrite` if the atomic rename fails.
 pub fn write_note(path: &Path, note: &Note, body: &str) -> Result<(), FsError> {
+    write_note_with(path, note, body, &FrontmatterOptions::default())
+}
+
+/// Writes a note to a file path atomically, using the given frontmatter options.
+///
+/// See [`write_note`] for the write semantics and errors.
+pub fn write_note_with(
+    path: &Path,
+    note: &Note,
+    body: &str,
+    frontmatter: &FrontmatterOptions,
+) -> Result<(), FsError> {
+    write_note_file(path, note, body, frontmatter, true)
+}
+
+/// Writes a new note file atomically like [`write_note_with`], but never
+/// replaces an existing file.
+///
+/// # Errors
+///
+/// Returns `FsError::AlreadyExists` if a file is already at `path`, e.g. a
+/// note created in the same millisecond with the same title.
+pub fn create_note_with(
+    path: &Path,
+    note: &Note,
+    body: &str,
+    frontmatter: &FrontmatterOptions,
+) -> Result<(), FsError> {
+    write_note_file(path, note, body, frontmatter, false)
+}
+
+fn write_note_file(
+    path: &Path,
+    note: &Note,
+    body: &str,
+    frontmatter: &FrontmatterOptions,
+    overwrite: bool,
+) -> Result<(), FsError> {
     let parent = path
         .parent()
         .ok_or_else(|| FsError::ParentNotFound { path: path.into() })?;
@@ -152,30 +233,109 @@ pub fn write_note(path: &Path, note: &Note, body: &str) -> Result<(), FsError> {
         });
     }
 
-    let content = serialize(note, body);
-    let mut temp = NamedTempFile::new_in(parent).map_err(|e| FsError::Io {
-        path: path.into(),
-        source: e,
-    })?;
-
-    temp.write_all(content.as_bytes())
-        .map_err(|e| FsError::Io {
+    let content = serialize_with(note, body, frontmatter);
+    profile::time(Phase::FileIo, || {
+        let mut temp = NamedTempFile::new_in(long_path(parent)).map_err(|e| FsError::Io {
             path: path.into(),
             source: e,
         })?;
 
-    temp.persist(path).map_err(|e| FsError::AtomicWrite {
-        path: path.into(),
-        source: e.error,
-    })?;
+        temp.write_all(content.as_bytes())
+            .map_err(|e| FsError::Io {
+                path: path.into(),
+                source: e,
+            })?;
 
-    Ok(())
+        let persisted = if overwrite {
+            temp.persist(long_path(path))
+        } else {
+            temp.persist_noclobber(long_path(path))
+        };
+        persisted.map_err(|e| match e.error.kind() {
+            io::ErrorKind::AlreadyExists => FsError::AlreadyExists { path: path.into() },
+            _ => FsError::AtomicWrite {
+                path: path.into(),
+                source: e.error,
+            },
+        })?;
+
+        Ok(())
+    })
+}
+
+/// Directory (relative to the notes directory) holding trashed notes.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Directory (relative to the notes directory) holding the index. Its
+/// presence marks a directory as a vault.
+pub const INDEX_DIR: &str = ".index";
+
+/// Directories and files left out of scans (the `[scan]` section).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    /// Directories relative to the notes directory, e.g. `"subvault/"`.
+    /// Everything under them is skipped.
+    pub exclude: Vec<String>,
+    /// Gitignore-style patterns, e.g. `"drafts/"` or `"*.swp"`, applied
+    /// after the patterns in the vault's `.denignore`.
+    pub ignore: Vec<String>,
+}
+
+impl ScanOptions {
+    /// Returns true if `path`, relative to the notes directory, is an
+    /// excluded directory or inside one.
+    pub fn excludes(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|dir| {
+            let dir = Path::new(dir.trim_end_matches('/'));
+            !dir.as_os_str().is_empty() && path.starts_with(dir)
+        })
+    }
+
+    /// Reads the ignore patterns for the notes directory `dir`: those in its
+    /// `.denignore`, if it has one, followed by [`ScanOptions::ignore`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `FsError::InvalidIgnorePattern` for a pattern that doesn't
+    /// parse, or an I/O error if `.denignore` can't be read.
+    pub fn ignore_rules(&self, dir: &Path) -> Result<IgnoreRules, FsError> {
+        let mut rules = IgnoreRules::new();
+        let path = dir.join(IGNORE_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                for (number, line) in contents.lines().enumerate() {
+                    rules
+                        .add(line)
+                        .map_err(|reason| FsError::InvalidIgnorePattern {
+                            origin: format!("{}:{}", IGNORE_FILE, number + 1),
+                            pattern: line.trim().to_string(),
+                            reason,
+                        })?;
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(FsError::from_io(&path, e)),
+        }
+        for pattern in &self.ignore {
+            rules
+                .add(pattern)
+                .map_err(|reason| FsError::InvalidIgnorePattern {
+                    origin: "[scan] ignore".to_string(),
+                    pattern: pattern.clone(),
+                    reason,
+                })?;
+        }
+        Ok(rules)
+    }
 }
 
 /// Scans a directory recursively for markdown (.md) files.
 ///
 /// Skips hidden files and directories (starting with `.`), including
-/// the `.index/` directory used for the SQLite index.
+/// the `.index/` directory used for the SQLite index and the `.trash/`
+/// directory holding trashed notes, and whatever the directory's
+/// `.denignore` matches.
 ///
 /// Returns paths relative to the input directory.
 ///
@@ -183,7 +343,18 @@ pub fn write_note(path: &Path, note: &Note, body: &str) -> Result<(), FsError> {
 ///
 /// Returns `FsError::NotFound` if the directory doesn't exist.
 /// Returns `FsError::NotADirectory` if the path is not a directory.
+/// Returns `FsError::InvalidIgnorePattern` if `.denignore` has an invalid
+/// pattern.
 pub fn scan_notes_directory(dir: &Path) -> Result<impl Iterator<Item = PathBuf>, FsError> {
+    scan_notes_directory_with(dir, &ScanOptions::default())
+}
+
+/// Scans a directory like [`scan_notes_directory`], skipping excluded
+/// directories and files matching the ignore patterns in `options`.
+pub fn scan_notes_directory_with(
+    dir: &Path,
+    options: &ScanOptions,
+) -> Result<impl Iterator<Item = PathBuf> + use<>, FsError> {
     if !dir.exists() {
         return Err(FsError::NotFound {
             path: dir.to_path_buf(),
@@ -196,16 +367,118 @@ pub fn scan_notes_directory(dir: &Path) -> Result<impl Iterator<Item = PathBuf>,
     }
 
     let dir_owned = dir.to_path_buf();
+    let root = dir.to_path_buf();
+    let rules = options.ignore_rules(dir)?;
+    let options = options.clone();
     let iter = WalkDir::new(dir)
         .follow_links(true)
         .into_iter()
-        .filter_entry(|e| e.depth() == 0 || !is_hidden(e))
+        .filter_entry(move |e| {
+            e.depth() == 0
+                || !(is_hidden(e)
+                    || is_excluded(e, &root, &options)
+                    || is_ignored(e, &root, &rules))
+        })
         .filter_map(Result::ok)
         .filter(|e| e.file_type().is_file())
         .filter(has_md_extension)
         .map(move |e| e.path().strip_prefix(&dir_owned).unwrap().to_path_buf());
 
-    Ok(iter)
+    Ok(profile::timed_iter(Phase::FileIo, iter))
+}
+
+/// Returns true if `path`, relative to the notes directory, is a note file
+/// [`scan_notes_directory_with`] would find: a `.md` file outside hidden,
+/// excluded and ignored directories, and not ignored itself.
+///
+/// `rules` are the notes directory's [`ScanOptions::ignore_rules`]. The
+/// file itself need not exist, so paths of removed notes can be checked too.
+pub fn is_note_path(path: &Path, options: &ScanOptions, rules: &IgnoreRules) -> bool {
+    path.extension().is_some_and(|e| e == "md")
+        && !path
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        && !path.parent().is_some_and(|dir| options.excludes(dir))
+        && !rules.is_ignored(path, false)
+}
+
+/// Scans the trash directory for markdown files.
+///
+/// Returns paths relative to `notes_dir` (e.g. `.trash/old-note.md`), or an
+/// empty list if there is no trash directory.
+pub fn scan_trash_directory(notes_dir: &Path) -> Result<Vec<PathBuf>, FsError> {
+    let trash_dir = notes_dir.join(TRASH_DIR);
+    if !trash_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = scan_notes_directory(&trash_dir)?
+        .map(|p| Path::new(TRASH_DIR).join(p))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Checks that a notes directory is neither inside another vault nor
+/// contains one, unless the nesting is excluded or ignored by `options`.
+///
+/// A vault is a directory with an `.index/` directory. For a vault inside
+/// another, the exclusions and the outer vault's ignore patterns are matched
+/// against its path relative to the outer vault.
+pub fn check_vault_nesting(dir: &Path, options: &ScanOptions) -> Result<(), FsError> {
+    let dir = dir.canonicalize().map_err(|e| FsError::from_io(dir, e))?;
+
+    if let Some(outer) = dir.ancestors().skip(1).find(|a| a.join(INDEX_DIR).is_dir()) {
+        let relative = dir.strip_prefix(outer).unwrap_or(&dir).to_path_buf();
+        if !(options.excludes(&relative)
+            || options.ignore_rules(outer)?.is_ignored(&relative, true))
+        {
+            return Err(FsError::NestedVault {
+                path: dir.clone(),
+                outer: outer.to_path_buf(),
+                relative,
+            });
+        }
+    }
+
+    let rules = options.ignore_rules(&dir)?;
+    let inner = WalkDir::new(&dir)
+        .follow_links(true)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| {
+            e.file_type().is_dir()
+                && !(is_hidden(e) || is_excluded(e, &dir, options) || is_ignored(e, &dir, &rules))
+        })
+        .filter_map(Result::ok)
+        .find(|e| e.path().join(INDEX_DIR).is_dir());
+    if let Some(inner) = inner {
+        return Err(FsError::ContainsVault {
+            inner: inner.path().strip_prefix(&dir).unwrap().to_path_buf(),
+            path: dir,
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns true if the entry is a directory the options exclude.
+fn is_excluded(entry: &DirEntry, root: &Path, options: &ScanOptions) -> bool {
+    entry.file_type().is_dir()
+        && entry
+            .path()
+            .strip_prefix(root)
+            .is_ok_and(|relative| options.excludes(relative))
+}
+
+/// Ignored entries are pruned during the walk, so only the entry itself
+/// needs matching.
+fn is_ignored(entry: &DirEntry, root: &Path, rules: &IgnoreRules) -> bool {
+    !rules.is_empty()
+        && entry
+            .path()
+            .strip_prefix(root)
+            .is_ok_and(|relative| rules.matches(relative, entry.file_type().is_dir()))
 }
 
 fn is_hidden(entry: &DirEntry) -> bool {
@@ -296,7 +569,7 @@ Body content."#
 
     #[test]
     fn fs_error_from_io_maps_other_to_io() {
-        let io_error = io::Error::new(io::ErrorKind::Other, "some other error");
+        let io_error = io::Error::other("some other error");
         let path = Path::new("/test/path.md");
         let error = FsError::from_io(path, io_error);
         assert!(matches!(error, FsError::Io { .. }));
@@ -458,6 +731,21 @@ Body"#;
         assert_eq!(parsed.body, "Second body");
     }
 
+    #[test]
+    fn create_note_never_replaces_existing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("note.md");
+        let (note, _) = create_test_note_and_body();
+        let options = FrontmatterOptions::default();
+
+        create_note_with(&path, &note, "First body", &options).unwrap();
+        let err = create_note_with(&path, &note, "Second body", &options).unwrap_err();
+
+        assert!(matches!(err, FsError::AlreadyExists { .. }), "{:?}", err);
+        assert_eq!(read_note(&path).unwrap().body, "First body");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
     // ===========================================
     // Cycle 6: write_note Error Cases
     // ===========================================
@@ -799,6 +1087,191 @@ modified: 2024-01-15T10:30:00Z
         assert_eq!(result[0], PathBuf::from("note.md"));
     }
 
+    #[test]
+    fn scan_skips_trash_directory() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("note.md"), "content").unwrap();
+        fs::create_dir(dir.path().join(TRASH_DIR)).unwrap();
+        fs::write(dir.path().join(".trash/old.md"), "content").unwrap();
+
+        let result: Vec<_> = scan_notes_directory(dir.path()).unwrap().collect();
+
+        assert_eq!(result, vec![PathBuf::from("note.md")]);
+    }
+
+    #[test]
+    fn scan_with_skips_excluded_directories() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("subvault/deep")).unwrap();
+        fs::create_dir(dir.path().join("subvaults")).unwrap();
+        fs::write(dir.path().join("note.md"), "content").unwrap();
+        fs::write(dir.path().join("subvault/deep/inner.md"), "content").unwrap();
+        fs::write(dir.path().join("subvaults/kept.md"), "content").unwrap();
+        let options = ScanOptions {
+            exclude: vec!["subvault/".to_string()],
+            ..Default::default()
+        };
+
+        let mut result: Vec<_> = scan_notes_directory_with(dir.path(), &options)
+            .unwrap()
+            .collect();
+        result.sort();
+
+        assert_eq!(
+            result,
+            vec![PathBuf::from("note.md"), PathBuf::from("subvaults/kept.md")]
+        );
+    }
+
+    #[test]
+    fn scan_skips_files_matching_denignore_and_config_patterns() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("drafts/deep")).unwrap();
+        fs::create_dir_all(dir.path().join("web/node_modules/pkg")).unwrap();
+        fs::write(
+            dir.path().join(".denignore"),
+            "drafts/\n**/node_modules/\n*.tmp.md\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("note.md"), "content").unwrap();
+        fs::write(dir.path().join("scratch.tmp.md"), "content").unwrap();
+        fs::write(dir.path().join("keep.tmp.md"), "content").unwrap();
+        fs::write(dir.path().join("drafts/deep/draft.md"), "content").unwrap();
+        fs::write(dir.path().join("web/node_modules/pkg/README.md"), "content").unwrap();
+        fs::write(dir.path().join("web/page.md"), "content").unwrap();
+        let options = ScanOptions {
+            ignore: vec!["!keep.tmp.md".to_string(), "web/page.md".to_string()],
+            ..Default::default()
+        };
+
+        let mut result: Vec<_> = scan_notes_directory_with(dir.path(), &options)
+            .unwrap()
+            .collect();
+        result.sort();
+
+        assert_eq!(
+            result,
+            vec![PathBuf::from("keep.tmp.md"), PathBuf::from("note.md")]
+        );
+    }
+
+    #[test]
+    fn is_note_path_matches_what_scan_finds() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".denignore"), "drafts/\n*.tmp.md\n").unwrap();
+        let options = ScanOptions {
+            exclude: vec!["subvault/".to_string()],
+            ..Default::default()
+        };
+        let rules = options.ignore_rules(dir.path()).unwrap();
+
+        for (path, expected) in [
+            ("note.md", true),
+            ("projects/deep/note.md", true),
+            ("note.txt", false),
+            (".index/notes.md", false),
+            ("projects/.hidden.md", false),
+            ("subvault/note.md", false),
+            ("drafts/deep/note.md", false),
+            ("scratch.tmp.md", false),
+        ] {
+            assert_eq!(
+                is_note_path(Path::new(path), &options, &rules),
+                expected,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn scan_rejects_invalid_ignore_pattern() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".denignore"), "# junk\n[oops\n").unwrap();
+
+        let err = scan_notes_directory(dir.path()).err().unwrap();
+
+        assert!(
+            matches!(&err, FsError::InvalidIgnorePattern { origin, .. } if origin == ".denignore:2"),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn check_vault_nesting_rejects_vault_inside_notes_dir() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("projects/other").join(INDEX_DIR)).unwrap();
+
+        let err = check_vault_nesting(dir.path(), &ScanOptions::default()).unwrap_err();
+
+        match &err {
+            FsError::ContainsVault { inner, .. } => {
+                assert_eq!(inner, &PathBuf::from("projects/other"))
+            }
+            other => panic!("expected ContainsVault, got {:?}", other),
+        }
+        assert!(err.to_string().contains("\"projects/other/\""));
+    }
+
+    #[test]
+    fn check_vault_nesting_rejects_notes_dir_inside_vault() {
+        let outer = TempDir::new().unwrap();
+        fs::create_dir(outer.path().join(INDEX_DIR)).unwrap();
+        let inner = outer.path().join("inner");
+        fs::create_dir(&inner).unwrap();
+
+        let err = check_vault_nesting(&inner, &ScanOptions::default()).unwrap_err();
+
+        assert!(
+            matches!(&err, FsError::NestedVault { relative, .. } if relative == Path::new("inner")),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn check_vault_nesting_allows_excluded_nesting() {
+        let outer = TempDir::new().unwrap();
+        fs::create_dir(outer.path().join(INDEX_DIR)).unwrap();
+        fs::create_dir_all(outer.path().join("inner").join(INDEX_DIR)).unwrap();
+        let options = ScanOptions {
+            exclude: vec!["inner".to_string()],
+            ..Default::default()
+        };
+
+        assert!(check_vault_nesting(outer.path(), &options).is_ok());
+        assert!(check_vault_nesting(&outer.path().join("inner"), &options).is_ok());
+        assert!(check_vault_nesting(outer.path(), &ScanOptions::default()).is_err());
+    }
+
+    #[test]
+    fn scan_trash_lists_trashed_notes() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("note.md"), "content").unwrap();
+        fs::create_dir_all(dir.path().join(".trash/sub")).unwrap();
+        fs::write(dir.path().join(".trash/b.md"), "content").unwrap();
+        fs::write(dir.path().join(".trash/sub/a.md"), "content").unwrap();
+        fs::write(dir.path().join(".trash/ignored.txt"), "content").unwrap();
+
+        let result = scan_trash_directory(dir.path()).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                PathBuf::from(".trash/b.md"),
+                PathBuf::from(".trash/sub/a.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_trash_without_trash_directory_is_empty() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(scan_trash_directory(dir.path()).unwrap().is_empty());
+    }
+
     #[test]
     fn scan_skips_hidden_files() {
         let dir = TempDir::new().unwrap();
@@ -0,0 +1,294 @@
+//! Read access to the git history of a vault kept in a git repository.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use git2::{Delta, DiffFindOptions, ErrorCode, Oid, Repository, Sort, Tree};
+use thiserror::Error;
+
+/// Errors from reading a vault's git history.
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("{path} is not in a git repository")]
+    NotARepository { path: PathBuf },
+
+    #[error("{path} does not exist at revision '{rev}'")]
+    NotFoundAt { path: PathBuf, rev: String },
+
+    #[error("{path} at revision '{rev}' is not a file")]
+    NotAFile { path: PathBuf, rev: String },
+
+    #[error("git error: {0}")]
+    Git(#[from] git2::Error),
+}
+
+/// A commit that changed a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCommit {
+    /// Full commit hash.
+    pub id: String,
+    /// Shortest unambiguous abbreviation of the hash.
+    pub short_id: String,
+    pub time: DateTime<Utc>,
+    /// First line of the commit message.
+    pub summary: String,
+    pub author: String,
+    /// The file's path in this commit, relative to the notes directory.
+    pub path: PathBuf,
+}
+
+/// The git repository a notes directory is in.
+pub struct GitRepo {
+    repo: Repository,
+    /// The notes directory relative to the repository's working directory.
+    prefix: PathBuf,
+}
+
+impl GitRepo {
+    /// Opens the repository containing `notes_dir`, which may be its
+    /// working directory or a directory inside it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `GitError::NotARepository` if `notes_dir` is not in a git
+    /// repository with a working directory.
+    pub fn discover(notes_dir: &Path) -> Result<Self, GitError> {
+        let not_a_repository = || GitError::NotARepository {
+            path: notes_dir.to_path_buf(),
+        };
+        let repo = match Repository::discover(notes_dir) {
+            Ok(repo) => repo,
+            Err(e) if e.code() == ErrorCode::NotFound => return Err(not_a_repository()),
+            Err(e) => return Err(e.into()),
+        };
+        let workdir = repo.workdir().ok_or_else(not_a_repository)?;
+        let (Ok(notes_dir), Ok(workdir)) = (notes_dir.canonicalize(), workdir.canonicalize())
+        else {
+            return Err(not_a_repository());
+        };
+        let prefix = notes_dir
+            .strip_prefix(&workdir)
+            .map_err(|_| not_a_repository())?
+            .to_path_buf();
+        Ok(Self { repo, prefix })
+    }
+
+    /// Lists the commits on the current branch that changed the file at
+    /// `path` (relative to the notes directory), newest first.
+    ///
+    /// Renames are followed, so commits from before the file got its current
+    /// name are listed too. Only first parents are walked, so a merge is
+    /// listed in place of the commits it brought in.
+    pub fn file_history(&self, path: &Path) -> Result<Vec<FileCommit>, GitError> {
+        let mut walk = self.repo.revwalk()?;
+        match walk.push_head() {
+            Ok(()) => {}
+            // A repository without commits has no history
+            Err(e) if e.code() == ErrorCode::UnbornBranch => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        }
+        walk.simplify_first_parent()?;
+        walk.set_sorting(Sort::TOPOLOGICAL)?;
+
+        let mut path = self.prefix.join(path);
+        let mut history = Vec::new();
+        for oid in walk {
+            let commit = self.repo.find_commit(oid?)?;
+            let tree = commit.tree()?;
+            let Some(blob) = blob_at(&tree, &path) else {
+                continue;
+            };
+            let parent_tree = match commit.parents().next() {
+                Some(parent) => Some(parent.tree()?),
+                None => None,
+            };
+            let parent_blob = parent_tree.as_ref().and_then(|t| blob_at(t, &path));
+            if parent_blob == Some(blob) {
+                continue;
+            }
+
+            history.push(FileCommit {
+                id: commit.id().to_string(),
+                short_id: commit
+                    .as_object()
+                    .short_id()?
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                time: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                path: self.relative(&path),
+            });
+
+            if parent_blob.is_none()
+                && let Some(parent_tree) = &parent_tree
+                && let Some(old_path) = self.renamed_from(parent_tree, &tree, &path)?
+            {
+                path = old_path;
+            }
+        }
+        Ok(history)
+    }
+
+    /// Returns the contents of the file at `path` (relative to the notes
+    /// directory) as of revision `rev`, e.g. a commit hash, branch, tag or
+    /// `HEAD~2`.
+    ///
+    /// If the file had another name at `rev`, the names from its history are
+    /// tried.
+    pub fn file_at(&self, path: &Path, rev: &str) -> Result<Vec<u8>, GitError> {
+        let tree = self.repo.revparse_single(rev)?.peel_to_commit()?.tree()?;
+
+        let mut candidates = vec![path.to_path_buf()];
+        for commit in self.file_history(path)? {
+            if !candidates.contains(&commit.path) {
+                candidates.push(commit.path);
+            }
+        }
+        for candidate in candidates {
+            let Ok(entry) = tree.get_path(&self.prefix.join(&candidate)) else {
+                continue;
+            };
+            let object = entry.to_object(&self.repo)?;
+            let blob = object.as_blob().ok_or_else(|| GitError::NotAFile {
+                path: candidate.clone(),
+                rev: rev.to_string(),
+            })?;
+            return Ok(blob.content().to_vec());
+        }
+        Err(GitError::NotFoundAt {
+            path: path.to_path_buf(),
+            rev: rev.to_string(),
+        })
+    }
+
+    /// Finds the path a file at `path` in `new` was renamed from in `old`.
+    fn renamed_from(
+        &self,
+        old: &Tree,
+        new: &Tree,
+        path: &Path,
+    ) -> Result<Option<PathBuf>, GitError> {
+        let mut diff = self.repo.diff_tree_to_tree(Some(old), Some(new), None)?;
+        diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+        Ok(diff
+            .deltas()
+            .find(|d| d.status() == Delta::Renamed && d.new_file().path() == Some(path))
+            .and_then(|d| d.old_file().path().map(Path::to_path_buf)))
+    }
+
+    /// Makes a repository path relative to the notes directory.
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(&self.prefix)
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+/// Returns the ID of the blob at `path` in `tree`, if there is one.
+fn blob_at(tree: &Tree, path: &Path) -> Option<Oid> {
+    tree.get_path(path).ok().map(|entry| entry.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{IndexAddOption, Signature};
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// Stages every change in the working directory and commits it.
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            parent.as_slice().iter().collect::<Vec<_>>().as_slice(),
+        )
+        .unwrap();
+    }
+
+    /// A repository with the vault in `notes/`, where `a.md` was created,
+    /// left alone by a commit, edited and renamed to `b.md`.
+    fn renamed_file_repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let notes = dir.path().join("notes");
+        fs::create_dir(&notes).unwrap();
+        let lines = "one\ntwo\nthree\nfour\nfive\n";
+        fs::write(notes.join("a.md"), lines).unwrap();
+        commit_all(&repo, "Add a");
+        fs::write(dir.path().join("other.md"), "other").unwrap();
+        commit_all(&repo, "Add other");
+        fs::write(notes.join("a.md"), format!("{}six\n", lines)).unwrap();
+        commit_all(&repo, "Edit a\n\nLonger description.");
+        fs::rename(notes.join("a.md"), notes.join("b.md")).unwrap();
+        commit_all(&repo, "Rename a to b");
+        dir
+    }
+
+    #[test]
+    fn file_history_lists_changes_across_renames() {
+        let dir = renamed_file_repo();
+        let git = GitRepo::discover(&dir.path().join("notes")).unwrap();
+
+        let history = git.file_history(Path::new("b.md")).unwrap();
+
+        let summaries: Vec<(&str, &Path)> = history
+            .iter()
+            .map(|c| (c.summary.as_str(), c.path.as_path()))
+            .collect();
+        assert_eq!(
+            summaries,
+            vec![
+                ("Rename a to b", Path::new("b.md")),
+                ("Edit a", Path::new("a.md")),
+                ("Add a", Path::new("a.md")),
+            ]
+        );
+        assert_eq!(history[0].author, "Test");
+        assert!(history[0].id.starts_with(&history[0].short_id));
+    }
+
+    #[test]
+    fn file_at_reads_old_contents_under_old_names() {
+        let dir = renamed_file_repo();
+        let git = GitRepo::discover(&dir.path().join("notes")).unwrap();
+
+        let old = git.file_at(Path::new("b.md"), "HEAD~3").unwrap();
+        assert_eq!(old, b"one\ntwo\nthree\nfour\nfive\n");
+        assert!(
+            git.file_at(Path::new("b.md"), "HEAD")
+                .unwrap()
+                .ends_with(b"six\n")
+        );
+        assert!(matches!(
+            git.file_at(Path::new("missing.md"), "HEAD"),
+            Err(GitError::NotFoundAt { .. })
+        ));
+        assert!(matches!(
+            git.file_at(Path::new("b.md"), "no-such-rev"),
+            Err(GitError::Git(_))
+        ));
+    }
+
+    #[test]
+    fn discover_outside_a_repository_fails() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(matches!(
+            GitRepo::discover(dir.path()),
+            Err(GitError::NotARepository { .. })
+        ));
+    }
+}
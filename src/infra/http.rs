@@ -0,0 +1,148 @@
+//! HTTP liveness checks for external URLs.
+
+use std::fmt;
+use std::time::Duration;
+
+/// What a URL answered when checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlStatus {
+    /// A success status, after following redirects.
+    Ok(u16),
+    /// An error status, e.g. 404.
+    Http(u16),
+    /// No answer: the host couldn't be resolved or reached, TLS failed or
+    /// the request timed out.
+    Failed(String),
+}
+
+impl UrlStatus {
+    /// Returns true if the URL didn't answer with a success status.
+    pub fn is_broken(&self) -> bool {
+        !matches!(self, UrlStatus::Ok(_))
+    }
+
+    /// The HTTP status code, if the server answered.
+    pub fn code(&self) -> Option<u16> {
+        match self {
+            UrlStatus::Ok(code) | UrlStatus::Http(code) => Some(*code),
+            UrlStatus::Failed(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for UrlStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlStatus::Ok(code) | UrlStatus::Http(code) => write!(f, "{}", code),
+            UrlStatus::Failed(reason) => f.write_str(reason),
+        }
+    }
+}
+
+/// Checks whether URLs still answer.
+pub struct UrlChecker {
+    agent: ureq::Agent,
+}
+
+impl UrlChecker {
+    /// Creates a checker that gives up on a URL after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(timeout)
+                .user_agent(concat!("den/", env!("CARGO_PKG_VERSION")))
+                .build(),
+        }
+    }
+
+    /// Requests a URL and reports how it answered.
+    ///
+    /// A `HEAD` request is tried first; servers that refuse it get a `GET`.
+    pub fn check(&self, url: &str) -> UrlStatus {
+        match self.request("HEAD", url) {
+            UrlStatus::Http(403 | 405 | 501) => self.request("GET", url),
+            status => status,
+        }
+    }
+
+    fn request(&self, method: &str, url: &str) -> UrlStatus {
+        match self.agent.request(method, url).call() {
+            Ok(response) => UrlStatus::Ok(response.status()),
+            Err(ureq::Error::Status(code, _)) => UrlStatus::Http(code),
+            Err(ureq::Error::Transport(e)) => UrlStatus::Failed(e.kind().to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Serves requests on a local port, answering `HEAD` with `head` and
+    /// everything else with `get`.
+    fn serve(head: u16, get: u16) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                let code = if request_line.starts_with("HEAD") {
+                    head
+                } else {
+                    get
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    code
+                )
+                .unwrap();
+            }
+        });
+        format!("http://{}/page", addr)
+    }
+
+    #[test]
+    fn reports_success_and_error_statuses() {
+        let checker = UrlChecker::new(Duration::from_secs(5));
+
+        let ok = checker.check(&serve(200, 200));
+        assert_eq!(ok, UrlStatus::Ok(200));
+        assert!(!ok.is_broken());
+
+        let missing = checker.check(&serve(404, 404));
+        assert_eq!(missing, UrlStatus::Http(404));
+        assert!(missing.is_broken());
+        assert_eq!(missing.to_string(), "404");
+    }
+
+    #[test]
+    fn falls_back_to_get_when_head_is_refused() {
+        let checker = UrlChecker::new(Duration::from_secs(5));
+        assert_eq!(checker.check(&serve(405, 200)), UrlStatus::Ok(200));
+    }
+
+    #[test]
+    fn unreachable_host_fails() {
+        // Nothing listens on a port that was just released
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let checker = UrlChecker::new(Duration::from_secs(5));
+
+        let status = checker.check(&format!("http://127.0.0.1:{}/", port));
+        assert!(matches!(status, UrlStatus::Failed(_)));
+        assert_eq!(status.code(), None);
+    }
+}
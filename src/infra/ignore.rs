@@ -0,0 +1,174 @@
+//! Gitignore-style patterns for leaving files out of scans.
+//!
+//! The supported syntax is the common part of gitignore: blank lines and
+//! lines starting with `#` are skipped, `!` re-includes what an earlier
+//! pattern left out, a trailing `/` matches only directories, and a pattern
+//! with a `/` anywhere else is matched against the whole path from the notes
+//! directory, while one without is matched against the name at any depth.
+//! `*`, `?`, `[...]` and `**` work as in gitignore. The last matching
+//! pattern decides.
+
+use std::path::{Component, Path};
+
+use glob::{MatchOptions, Pattern};
+
+/// Name of the ignore file read from the notes directory.
+pub const IGNORE_FILE: &str = ".denignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Clone)]
+struct Rule {
+    pattern: Pattern,
+    negated: bool,
+    dir_only: bool,
+    /// Matched against the whole relative path rather than the name.
+    anchored: bool,
+}
+
+/// An ordered list of ignore patterns.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Creates an empty list, which ignores nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the pattern on one line of an ignore file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason if the line is not a valid pattern.
+    pub fn add(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        if line.is_empty() {
+            return Err("pattern is empty".to_string());
+        }
+        let pattern = Pattern::new(line).map_err(|e| e.msg.to_string())?;
+        self.rules.push(Rule {
+            pattern,
+            negated,
+            dir_only,
+            anchored,
+        });
+        Ok(())
+    }
+
+    /// Returns true if there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Returns true if `path`, relative to the notes directory, is ignored
+    /// itself, without looking at the directories it is in.
+    pub fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = slash_path(path);
+        let name = relative.rsplit('/').next().unwrap_or(&relative);
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && if rule.anchored {
+                        rule.pattern.matches_with(&relative, MATCH_OPTIONS)
+                    } else {
+                        rule.pattern.matches_with(name, MATCH_OPTIONS)
+                    }
+            })
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Returns true if `path`, relative to the notes directory, is ignored
+    /// or inside an ignored directory.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ancestors: Vec<&Path> = path.ancestors().skip(1).collect();
+        ancestors.retain(|a| !a.as_os_str().is_empty());
+        ancestors.iter().rev().any(|dir| self.matches(dir, true)) || self.matches(path, is_dir)
+    }
+}
+
+/// Joins the normal components of a path with `/`.
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(lines: &[&str]) -> IgnoreRules {
+        let mut rules = IgnoreRules::new();
+        for line in lines {
+            rules.add(line).unwrap();
+        }
+        rules
+    }
+
+    #[test]
+    fn unanchored_patterns_match_names_at_any_depth() {
+        let rules = rules(&["# editor files", "", "*.swp", "node_modules/"]);
+
+        assert!(rules.matches(Path::new("a.swp"), false));
+        assert!(rules.matches(Path::new("deep/down/.b.md.swp"), false));
+        assert!(rules.matches(Path::new("web/node_modules"), true));
+        assert!(!rules.matches(Path::new("web/node_modules"), false));
+        assert!(!rules.matches(Path::new("note.md"), false));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_are_anchored() {
+        let rules = rules(&["/drafts/", "archive/*.md", "**/tmp/**"]);
+
+        assert!(rules.matches(Path::new("drafts"), true));
+        assert!(!rules.matches(Path::new("work/drafts"), true));
+        assert!(rules.matches(Path::new("archive/old.md"), false));
+        assert!(!rules.matches(Path::new("archive/2023/old.md"), false));
+        assert!(rules.matches(Path::new("a/tmp/b/c.md"), false));
+    }
+
+    #[test]
+    fn the_last_matching_pattern_decides() {
+        let rules = rules(&["drafts/", "*.md", "!keep.md", "\\!bang.md"]);
+
+        assert!(rules.matches(Path::new("note.md"), false));
+        assert!(!rules.matches(Path::new("keep.md"), false));
+        assert!(rules.matches(Path::new("!bang.md"), false));
+        assert!(rules.is_ignored(Path::new("drafts/keep.md"), false));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let mut rules = IgnoreRules::new();
+
+        assert!(rules.add("[unclosed").is_err());
+        assert!(rules.add("/").is_err());
+        assert!(rules.is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Operation journal for changes that touch several files.
+//!
+//! Before an operation like an ID change rewrites notes, it records every
+//! file's contents before and after the change in a journal under the index
+//! directory. The journal is removed once all files are written, so one left
+//! behind means the operation was interrupted; it holds everything needed to
+//! finish the operation or undo it, and no other journaled operation starts
+//! until that is done.
+
+use std::io::{self, Write as IoWrite};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::infra::fs::INDEX_DIR;
+use crate::infra::platform::long_path;
+
+/// Name of the journal file in the index directory.
+pub const JOURNAL_FILE: &str = "journal.json";
+
+/// Errors from recording or replaying a journal.
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("an interrupted '{operation}' is pending; run `notes recover` first")]
+    Pending { operation: String },
+
+    #[error("I/O error for {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("invalid journal at {path}: {source}")]
+    Invalid {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// A file an operation changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChange {
+    /// Path relative to the notes directory.
+    pub path: PathBuf,
+    /// Contents before the operation, or `None` if the file didn't exist.
+    pub before: Option<String>,
+    /// Contents after the operation, or `None` if the file is removed.
+    pub after: Option<String>,
+}
+
+/// The file changes of one operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Journal {
+    /// The command that started the operation, e.g. "fix-ids".
+    pub operation: String,
+    pub started: DateTime<Utc>,
+    pub changes: Vec<FileChange>,
+}
+
+impl Journal {
+    /// Starts an empty journal for `operation`.
+    pub fn new(operation: impl Into<String>, started: DateTime<Utc>) -> Self {
+        Self {
+            operation: operation.into(),
+            started,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Returns where the journal of the vault at `notes_dir` is kept.
+    pub fn path(notes_dir: &Path) -> PathBuf {
+        notes_dir.join(INDEX_DIR).join(JOURNAL_FILE)
+    }
+
+    /// Loads the journal of an interrupted operation, if there is one.
+    pub fn load(notes_dir: &Path) -> Result<Option<Self>, JournalError> {
+        let path = Self::path(notes_dir);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(source) => return Err(JournalError::Io { path, source }),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|source| JournalError::Invalid { path, source })
+    }
+
+    /// Records that the file at `path` (relative to the notes directory)
+    /// gets the contents `after`, or is removed if `after` is `None`. Its
+    /// current contents are read now.
+    pub fn record(
+        &mut self,
+        notes_dir: &Path,
+        path: impl Into<PathBuf>,
+        after: Option<String>,
+    ) -> Result<(), JournalError> {
+        let path = path.into();
+        let full_path = notes_dir.join(&path);
+        let before = match std::fs::read_to_string(long_path(&full_path)) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(source) => {
+                return Err(JournalError::Io {
+                    path: full_path,
+                    source,
+                });
+            }
+        };
+        self.changes.push(FileChange {
+            path,
+            before,
+            after,
+        });
+        Ok(())
+    }
+
+    /// Saves the journal, makes the changes and removes the journal.
+    ///
+    /// Fails without changing anything if another operation's journal is
+    /// pending. If a change fails or the run is interrupted, the journal
+    /// stays behind so `notes recover` can finish the operation.
+    pub fn run(&self, notes_dir: &Path) -> Result<(), JournalError> {
+        if let Some(pending) = Self::load(notes_dir)? {
+            return Err(JournalError::Pending {
+                operation: pending.operation,
+            });
+        }
+        let path = Self::path(notes_dir);
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|source| JournalError::Invalid {
+                path: path.clone(),
+                source,
+            })?;
+        write_atomic(&path, &contents)?;
+        self.resume(notes_dir)
+    }
+
+    /// Makes every change, whether or not it was made before, and removes
+    /// the journal.
+    pub fn resume(&self, notes_dir: &Path) -> Result<(), JournalError> {
+        for change in &self.changes {
+            apply(notes_dir, &change.path, change.after.as_deref())?;
+        }
+        self.finish(notes_dir)
+    }
+
+    /// Restores every file to its contents before the operation, in reverse
+    /// order, and removes the journal.
+    pub fn roll_back(&self, notes_dir: &Path) -> Result<(), JournalError> {
+        for change in self.changes.iter().rev() {
+            apply(notes_dir, &change.path, change.before.as_deref())?;
+        }
+        self.finish(notes_dir)
+    }
+
+    fn finish(&self, notes_dir: &Path) -> Result<(), JournalError> {
+        let path = Self::path(notes_dir);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(JournalError::Io { path, source }),
+        }
+    }
+}
+
+/// Gives the file at `path` the contents, or removes it for `None`.
+fn apply(notes_dir: &Path, path: &Path, contents: Option<&str>) -> Result<(), JournalError> {
+    let full_path = notes_dir.join(path);
+    match contents {
+        Some(contents) => write_atomic(&full_path, contents),
+        None => match std::fs::remove_file(long_path(&full_path)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(JournalError::Io {
+                path: full_path,
+                source,
+            }),
+        },
+    }
+}
+
+/// Writes a file through a temporary file renamed into place, creating its
+/// directory if needed.
+fn write_atomic(path: &Path, contents: &str) -> Result<(), JournalError> {
+    let io_error = |source| JournalError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let parent = long_path(path.parent().unwrap_or(Path::new(".")));
+    std::fs::create_dir_all(&parent).map_err(io_error)?;
+    let mut temp = NamedTempFile::new_in(&parent).map_err(io_error)?;
+    temp.write_all(contents.as_bytes()).map_err(io_error)?;
+    temp.persist(long_path(path))
+        .map_err(|e| io_error(e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn started() -> DateTime<Utc> {
+        "2024-01-15T10:30:00Z".parse().unwrap()
+    }
+
+    /// A vault with `a.md` and `b.md`, and a journal renaming `b.md` to
+    /// `c.md` while rewriting `a.md`.
+    fn renaming_journal() -> (TempDir, Journal) {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.md"), "a before").unwrap();
+        std::fs::write(dir.path().join("b.md"), "b").unwrap();
+        let mut journal = Journal::new("fix-ids", started());
+        journal
+            .record(dir.path(), "a.md", Some("a after".to_string()))
+            .unwrap();
+        journal
+            .record(dir.path(), "c.md", Some("b".to_string()))
+            .unwrap();
+        journal.record(dir.path(), "b.md", None).unwrap();
+        (dir, journal)
+    }
+
+    fn read(dir: &TempDir, name: &str) -> Option<String> {
+        std::fs::read_to_string(dir.path().join(name)).ok()
+    }
+
+    #[test]
+    fn record_reads_the_current_contents() {
+        let (_dir, journal) = renaming_journal();
+        let before: Vec<_> = journal
+            .changes
+            .iter()
+            .map(|c| c.before.as_deref())
+            .collect();
+        assert_eq!(before, vec![Some("a before"), None, Some("b")]);
+    }
+
+    #[test]
+    fn run_makes_the_changes_and_removes_the_journal() {
+        let (dir, journal) = renaming_journal();
+        journal.run(dir.path()).unwrap();
+
+        assert_eq!(read(&dir, "a.md").as_deref(), Some("a after"));
+        assert_eq!(read(&dir, "b.md"), None);
+        assert_eq!(read(&dir, "c.md").as_deref(), Some("b"));
+        assert_eq!(Journal::load(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn an_interrupted_operation_can_be_resumed_or_rolled_back() {
+        let (dir, journal) = renaming_journal();
+        // Interrupted after saving the journal and writing only c.md
+        write_atomic(
+            &Journal::path(dir.path()),
+            &serde_json::to_string(&journal).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("c.md"), "b").unwrap();
+
+        let pending = Journal::load(dir.path()).unwrap().unwrap();
+        assert_eq!(pending, journal);
+        assert!(matches!(
+            Journal::new("mv", started()).run(dir.path()),
+            Err(JournalError::Pending { operation }) if operation == "fix-ids"
+        ));
+
+        pending.roll_back(dir.path()).unwrap();
+        assert_eq!(read(&dir, "a.md").as_deref(), Some("a before"));
+        assert_eq!(read(&dir, "b.md").as_deref(), Some("b"));
+        assert_eq!(read(&dir, "c.md"), None);
+        assert_eq!(Journal::load(dir.path()).unwrap(), None);
+
+        journal.resume(dir.path()).unwrap();
+        assert_eq!(read(&dir, "a.md").as_deref(), Some("a after"));
+        assert_eq!(read(&dir, "b.md"), None);
+        assert_eq!(read(&dir, "c.md").as_deref(), Some("b"));
+    }
+}
@@ -1,11 +1,33 @@
 //! File I/O, frontmatter parsing, config
 
+mod bundle;
 mod content_hash;
 mod frontmatter;
 mod fs;
+mod git;
+mod http;
+mod ignore;
+mod journal;
+mod platform;
+pub mod profile;
 mod slug;
 
+pub use bundle::{BundleError, open_bundle, seal_bundle};
 pub use content_hash::{ContentHash, ContentHashError};
-pub use frontmatter::{ParseError, ParsedNote, parse, serialize};
-pub use fs::{FsError, parse_note_from_bytes, read_note, scan_notes_directory, write_note};
-pub use slug::{generate_filename, slugify};
+pub use frontmatter::{
+    FrontmatterOptions, ParseError, ParsedNote, parse, serialize, serialize_with, split_frontmatter,
+};
+pub use fs::{
+    FsError, INDEX_DIR, ScanOptions, TRASH_DIR, check_vault_nesting, create_note_with,
+    is_note_path, parse_note_from_bytes, read_note, scan_notes_directory,
+    scan_notes_directory_with, scan_trash_directory, write_note, write_note_with,
+};
+pub use git::{FileCommit, GitError, GitRepo};
+pub use http::{UrlChecker, UrlStatus};
+pub use ignore::{IGNORE_FILE, IgnoreRules};
+pub use journal::{FileChange, JOURNAL_FILE, Journal, JournalError};
+pub use platform::{find_program, index_path_key, is_case_change, long_path, same_file};
+pub use slug::{
+    DEFAULT_SLUG_MAX_LENGTH, SlugOptions, generate_filename, generate_filename_with, slugify,
+    slugify_with,
+};
@@ -0,0 +1,251 @@
+//! Platform differences in file paths: separators, drive letters, long
+//! paths on Windows and case-insensitive file systems.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+/// Returns the form a note path takes in the index: its components joined
+/// by `/` on every platform, so `rust\async.md` on Windows and
+/// `rust/async.md` elsewhere are stored alike. A drive letter is kept, as
+/// in `C:/notes/a.md`.
+///
+/// Only real separators are replaced; a backslash within a Unix file name
+/// stays as it is.
+pub fn index_path_key(path: &Path) -> String {
+    let mut key = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => key.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => key.push('/'),
+            Component::CurDir => {}
+            Component::ParentDir | Component::Normal(_) => {
+                if !key.is_empty() && !key.ends_with('/') {
+                    key.push('/');
+                }
+                key.push_str(&component.as_os_str().to_string_lossy());
+            }
+        }
+    }
+    key
+}
+
+/// Longest path Windows opens without the `\\?\` prefix, in UTF-16 units.
+const MAX_PATH: usize = 259;
+
+/// Returns `path` in a form Windows can open past its usual limit of 260
+/// characters, by giving long absolute paths the `\\?\` prefix.
+///
+/// Elsewhere, and for paths within the limit, `path` is returned as is.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    if cfg!(windows)
+        && path.is_absolute()
+        && let Some(verbatim) = verbatim_path(&path.to_string_lossy())
+    {
+        return Cow::Owned(verbatim.into());
+    }
+    Cow::Borrowed(path)
+}
+
+/// Returns the `\\?\` form of an absolute Windows path longer than
+/// [`MAX_PATH`], or `None` if it needs none: `C:\a` becomes `\\?\C:\a`
+/// and the share `\\server\a` becomes `\\?\UNC\server\a`.
+fn verbatim_path(path: &str) -> Option<String> {
+    if path.encode_utf16().count() <= MAX_PATH || path.starts_with(r"\\?\") {
+        return None;
+    }
+    // Windows doesn't normalize verbatim paths, so `/` must become `\`
+    let path = path.replace('/', r"\");
+    Some(match path.strip_prefix(r"\\") {
+        Some(share) => format!(r"\\?\UNC\{}", share),
+        None => format!(r"\\?\{}", path),
+    })
+}
+
+/// Returns true if `a` and `b` name the same existing file, as two
+/// spellings of a name differing only in case do on case-insensitive file
+/// systems (the default on Windows and macOS). Links to a file count as the
+/// same file; see [`is_case_change`] for telling the two apart.
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (std::fs::metadata(a), std::fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        match (a.canonicalize(), b.canonicalize()) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Returns true if `to` is `from` spelled with different case, in the same
+/// directory, and both reach the same file, as on a case-insensitive file
+/// system. A link to the file under another name is not a case change.
+pub fn is_case_change(from: &Path, to: &Path) -> bool {
+    let (Some(from_name), Some(to_name)) = (from.file_name(), to.file_name()) else {
+        return false;
+    };
+    from_name != to_name
+        && from.parent() == to.parent()
+        && from_name
+            .to_string_lossy()
+            .eq_ignore_ascii_case(&to_name.to_string_lossy())
+        && same_file(from, to)
+}
+
+/// Finds a program on `PATH` as a shell would. On Windows each extension
+/// in `PATHEXT` is tried, since commands like `code` are really `code.cmd`.
+///
+/// Returns `None` for names with a directory part; those are run as given.
+pub fn find_program(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    let extensions: Vec<String> = if cfg!(windows) {
+        std::env::var("PATHEXT")
+            .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    find_program_in(name, &path, &extensions)
+}
+
+fn find_program_in(name: &str, path: &OsStr, extensions: &[String]) -> Option<PathBuf> {
+    if Path::new(name).components().count() != 1 {
+        return None;
+    }
+    let with_extensions = Path::new(name).extension().is_none() && !extensions.is_empty();
+    std::env::split_paths(path).find_map(|dir| {
+        if with_extensions {
+            extensions
+                .iter()
+                .map(|ext| dir.join(format!("{}{}", name, ext)))
+                .find(|candidate| candidate.is_file())
+        } else {
+            Some(dir.join(name)).filter(|candidate| candidate.is_file())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_path_key_joins_components_with_slashes() {
+        let path: PathBuf = ["rust", "async", "note.md"].iter().collect();
+        assert_eq!(index_path_key(&path), "rust/async/note.md");
+        assert_eq!(index_path_key(Path::new("./note.md")), "note.md");
+        assert_eq!(index_path_key(Path::new("/notes/a.md")), "/notes/a.md");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn index_path_key_keeps_backslashes_in_unix_names() {
+        assert_eq!(index_path_key(Path::new(r"dir/a\b.md")), r"dir/a\b.md");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn index_path_key_uses_slashes_and_keeps_drive_letters() {
+        assert_eq!(index_path_key(Path::new(r"rust\note.md")), "rust/note.md");
+        assert_eq!(index_path_key(Path::new(r"C:\notes\a.md")), "C:/notes/a.md");
+    }
+
+    #[test]
+    fn verbatim_path_prefixes_long_paths_only() {
+        assert_eq!(verbatim_path(r"C:\notes\a.md"), None);
+
+        let long = format!(r"C:\notes\{}.md", "a".repeat(300));
+        assert_eq!(
+            verbatim_path(&long),
+            Some(format!(r"\\?\C:\notes\{}.md", "a".repeat(300)))
+        );
+        let mixed = format!("C:/notes/{}.md", "a".repeat(300));
+        assert_eq!(verbatim_path(&mixed), verbatim_path(&long));
+
+        let share = format!(r"\\server\notes\{}.md", "a".repeat(300));
+        assert_eq!(
+            verbatim_path(&share),
+            Some(format!(r"\\?\UNC\server\notes\{}.md", "a".repeat(300)))
+        );
+        assert_eq!(verbatim_path(&verbatim_path(&long).unwrap()), None);
+
+        // The limit counts UTF-16 units, not bytes
+        let accented = format!(r"C:\notes\{}.md", "é".repeat(200));
+        assert!(accented.len() > MAX_PATH);
+        assert_eq!(verbatim_path(&accented), None);
+    }
+
+    #[test]
+    fn long_path_leaves_short_paths_alone() {
+        let path = Path::new("notes/a.md");
+        assert_eq!(long_path(path), Cow::Borrowed(path));
+    }
+
+    #[test]
+    fn find_program_in_tries_each_extension() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("code.cmd"), "").unwrap();
+        std::fs::write(dir.path().join("vi"), "").unwrap();
+        let path = std::env::join_paths([Path::new("/nonexistent"), dir.path()]).unwrap();
+        let extensions = vec![".EXE".to_string(), ".cmd".to_string()];
+
+        assert_eq!(
+            find_program_in("code", &path, &extensions),
+            Some(dir.path().join("code.cmd"))
+        );
+        assert_eq!(
+            find_program_in("code.cmd", &path, &extensions),
+            Some(dir.path().join("code.cmd"))
+        );
+        assert_eq!(
+            find_program_in("vi", &path, &[]),
+            Some(dir.path().join("vi"))
+        );
+        assert_eq!(find_program_in("notepad", &path, &extensions), None);
+        assert_eq!(find_program_in("bin/vi", &path, &[]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn same_file_sees_through_other_names() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("Note.md");
+        std::fs::write(&file, "").unwrap();
+        // A link stands in for a second spelling on a case-insensitive system
+        let other = dir.path().join("note.md");
+        std::os::unix::fs::symlink(&file, &other).unwrap();
+
+        assert!(same_file(&file, &other));
+        assert!(!same_file(&file, &dir.path().join("missing.md")));
+        std::fs::write(dir.path().join("other.md"), "").unwrap();
+        assert!(!same_file(&file, &dir.path().join("other.md")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn is_case_change_needs_same_name_and_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("Note.md");
+        std::fs::write(&file, "").unwrap();
+        std::os::unix::fs::symlink(&file, dir.path().join("note.md")).unwrap();
+        std::os::unix::fs::symlink(&file, dir.path().join("link.md")).unwrap();
+        std::fs::hard_link(&file, dir.path().join("hard.md")).unwrap();
+        std::fs::write(dir.path().join("NOTE.md"), "").unwrap();
+
+        assert!(is_case_change(&file, &dir.path().join("note.md")));
+        assert!(!is_case_change(&file, &dir.path().join("link.md")));
+        assert!(!is_case_change(&file, &dir.path().join("hard.md")));
+        assert!(!is_case_change(&file, &dir.path().join("NOTE.md")));
+        assert!(!is_case_change(&file, &file));
+    }
+}
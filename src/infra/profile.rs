@@ -0,0 +1,192 @@
+//! Opt-in timing of where a command spends its time (`--profile`).
+//!
+//! Timers are no-ops until [`enable`] is called, so instrumented code pays a
+//! single atomic load when profiling is off.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// A kind of work a command's time is attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Opening the SQLite index (including schema checks)
+    IndexOpen,
+    /// Executing SQL statements
+    Query,
+    /// Reading, writing and listing note files
+    FileIo,
+    /// Parsing frontmatter
+    Parse,
+    /// Rendering markdown to HTML or plain text
+    Render,
+}
+
+impl Phase {
+    /// All phases, in report order.
+    pub const ALL: [Phase; 5] = [
+        Phase::IndexOpen,
+        Phase::Query,
+        Phase::FileIo,
+        Phase::Parse,
+        Phase::Render,
+    ];
+
+    /// Returns the label used in the report.
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::IndexOpen => "index open",
+            Phase::Query => "query",
+            Phase::FileIo => "file i/o",
+            Phase::Parse => "parsing",
+            Phase::Render => "rendering",
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NANOS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+static CALLS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+/// Turns on timing for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns true if [`enable`] has been called.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Attributes `elapsed` to `phase`, if profiling is enabled.
+pub fn record(phase: Phase, elapsed: Duration) {
+    if !is_enabled() {
+        return;
+    }
+    let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+    NANOS[phase as usize].fetch_add(nanos, Ordering::Relaxed);
+    CALLS[phase as usize].fetch_add(1, Ordering::Relaxed);
+}
+
+/// Runs `f`, attributing its running time to `phase`.
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record(phase, start.elapsed());
+    result
+}
+
+/// Wraps an iterator so the time spent producing each item counts towards
+/// `phase` (e.g. walking a directory lazily).
+pub fn timed_iter<I: Iterator>(phase: Phase, inner: I) -> TimedIter<I> {
+    TimedIter { phase, inner }
+}
+
+/// Iterator returned by [`timed_iter`].
+#[derive(Debug)]
+pub struct TimedIter<I> {
+    phase: Phase,
+    inner: I,
+}
+
+impl<I: Iterator> Iterator for TimedIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        time(self.phase, || self.inner.next())
+    }
+}
+
+/// Returns the time recorded so far.
+pub fn snapshot() -> Profile {
+    let mut profile = Profile::default();
+    for phase in Phase::ALL {
+        let slot = &mut profile.phases[phase as usize];
+        slot.0 = Duration::from_nanos(NANOS[phase as usize].load(Ordering::Relaxed));
+        slot.1 = CALLS[phase as usize].load(Ordering::Relaxed);
+    }
+    profile
+}
+
+/// Time and call counts per phase.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    phases: [(Duration, u64); 5],
+}
+
+impl Profile {
+    /// Adds one call taking `elapsed` to `phase`.
+    pub fn add(&mut self, phase: Phase, elapsed: Duration) {
+        let slot = &mut self.phases[phase as usize];
+        slot.0 += elapsed;
+        slot.1 += 1;
+    }
+
+    /// Returns the total time and number of calls for `phase`.
+    pub fn get(&self, phase: Phase) -> (Duration, u64) {
+        self.phases[phase as usize]
+    }
+
+    /// Formats the breakdown of a command that took `total`.
+    ///
+    /// Time not attributed to any phase is shown as "other".
+    pub fn render(&self, total: Duration) -> String {
+        let mut out = format!("profile: {} total\n", format_ms(total));
+        let mut attributed = Duration::ZERO;
+        for phase in Phase::ALL {
+            let (elapsed, calls) = self.get(phase);
+            attributed += elapsed;
+            let plural = if calls == 1 { "" } else { "s" };
+            out.push_str(&format!(
+                "  {:<12} {:>10}  ({} call{})\n",
+                phase.label(),
+                format_ms(elapsed),
+                calls,
+                plural
+            ));
+        }
+        out.push_str(&format!(
+            "  {:<12} {:>10}\n",
+            "other",
+            format_ms(total.saturating_sub(attributed))
+        ));
+        out
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_lists_every_phase_and_other() {
+        let mut profile = Profile::default();
+        profile.add(Phase::Query, Duration::from_millis(4));
+        profile.add(Phase::Query, Duration::from_millis(6));
+        profile.add(Phase::FileIo, Duration::from_micros(2500));
+
+        let report = profile.render(Duration::from_millis(20));
+
+        assert!(report.starts_with("profile: 20.0 ms total\n"));
+        assert!(report.contains("  query           10.0 ms  (2 calls)\n"));
+        assert!(report.contains("  file i/o         2.5 ms  (1 call)\n"));
+        assert!(report.contains("  index open       0.0 ms  (0 calls)\n"));
+        assert!(report.contains("  other            7.5 ms\n"));
+    }
+
+    #[test]
+    fn other_never_goes_negative() {
+        let mut profile = Profile::default();
+        profile.add(Phase::Parse, Duration::from_millis(5));
+
+        let report = profile.render(Duration::from_millis(1));
+
+        assert!(report.contains("  other            0.0 ms\n"));
+    }
+}
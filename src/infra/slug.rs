@@ -1,6 +1,57 @@
 //! Slug generation for note filenames.
 
-use crate::domain::NoteId;
+use crate::domain::{NoteId, language_code};
+use serde::{Deserialize, Serialize};
+
+/// Default maximum slug length in characters.
+pub const DEFAULT_SLUG_MAX_LENGTH: usize = 50;
+
+/// Options controlling slug generation.
+///
+/// Loaded from the `[slug]` section of the config file; every field is
+/// optional and defaults to the historical behaviour.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SlugOptions {
+    /// Transliterate accented Latin, Greek, and Cyrillic letters to ASCII
+    /// instead of dropping them.
+    pub transliterate: bool,
+    /// Maximum slug length (truncated at a word boundary when possible).
+    pub max_length: usize,
+    /// Words removed from the slug (case-insensitive), unless that would
+    /// leave it empty.
+    pub stop_words: Vec<String>,
+    /// Character placed between words.
+    pub separator: char,
+    /// Language of titles when a note has no `lang` field, e.g. `de`. With
+    /// `transliterate`, German umlauts become `ae`, `oe` and `ue`, and
+    /// Japanese kana are romanized.
+    pub lang: Option<String>,
+}
+
+impl Default for SlugOptions {
+    fn default() -> Self {
+        Self {
+            transliterate: false,
+            max_length: DEFAULT_SLUG_MAX_LENGTH,
+            stop_words: Vec::new(),
+            separator: '-',
+            lang: None,
+        }
+    }
+}
+
+impl SlugOptions {
+    /// Returns these options for a title in `lang`, e.g. a note's `lang`
+    /// field, falling back to the configured language.
+    pub fn for_lang(&self, lang: Option<&str>) -> SlugOptions {
+        let mut options = self.clone();
+        if let Some(lang) = lang {
+            options.lang = Some(lang.to_string());
+        }
+        options
+    }
+}
 
 /// Converts a title to a URL-friendly slug.
 ///
@@ -22,62 +73,343 @@ use crate::domain::NoteId;
 /// assert_eq!(slugify(""), "untitled");
 /// ```
 pub fn slugify(title: &str) -> String {
-    const MAX_LENGTH: usize = 50;
+    slugify_with(title, &SlugOptions::default())
+}
 
-    // Convert to lowercase
-    let lower = title.to_lowercase();
+/// Converts a title to a slug using the given options.
+///
+/// # Examples
+///
+/// ```
+/// use den::infra::{SlugOptions, slugify_with};
+///
+/// let options = SlugOptions {
+///     transliterate: true,
+///     stop_words: vec!["the".to_string()],
+///     separator: '_',
+///     ..SlugOptions::default()
+/// };
+/// assert_eq!(slugify_with("The Café Menu", &options), "cafe_menu");
+/// ```
+pub fn slugify_with(title: &str, options: &SlugOptions) -> String {
+    let sep = options.separator;
 
-    // Replace spaces with hyphens and filter invalid characters
-    let mut result = String::new();
-    for c in lower.chars() {
-        if c.is_ascii_alphanumeric() {
-            result.push(c);
-        } else if c == ' ' || c == '-' || c == '_' {
-            // Replace spaces with hyphens, keep hyphens and underscores
-            result.push(if c == ' ' { '-' } else { c });
+    // Convert to lowercase, transliterating if requested
+    let lower = title.to_lowercase();
+    let source: String = if options.transliterate {
+        let lang = options.lang.as_deref().and_then(language_code);
+        transliterate(&lower, lang.as_deref())
+    } else {
+        lower
+    };
+
+    // Split into words on spaces and hyphens, keeping only valid characters
+    let mut words: Vec<String> = source
+        .split([' ', '-'])
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    // Remove stop words, unless that would remove everything
+    if !options.stop_words.is_empty() {
+        let kept: Vec<String> = words
+            .iter()
+            .filter(|w| !options.stop_words.iter().any(|s| s.eq_ignore_ascii_case(w)))
+            .cloned()
+            .collect();
+        if !kept.is_empty() {
+            words = kept;
         }
-        // Skip all other characters
     }
 
-    // Collapse consecutive hyphens
+    // Join with the separator, collapsing any runs of separators
     let mut collapsed = String::new();
-    let mut prev_was_hyphen = false;
-    for c in result.chars() {
-        if c == '-' {
-            if !prev_was_hyphen {
+    let mut prev_was_sep = false;
+    for c in words.join(&sep.to_string()).chars() {
+        if c == sep {
+            if !prev_was_sep {
                 collapsed.push(c);
             }
-            prev_was_hyphen = true;
+            prev_was_sep = true;
         } else {
             collapsed.push(c);
-            prev_was_hyphen = false;
+            prev_was_sep = false;
         }
     }
 
-    // Trim leading and trailing hyphens
-    let trimmed = collapsed.trim_matches('-');
+    // Trim leading and trailing separators
+    let trimmed = collapsed.trim_matches(sep);
 
     // Return "untitled" for empty result
     if trimmed.is_empty() {
         return "untitled".to_string();
     }
 
-    // Truncate to MAX_LENGTH
-    if trimmed.len() <= MAX_LENGTH {
+    // Truncate to max length
+    let max_length = options.max_length.max(1);
+    if trimmed.chars().count() <= max_length {
         return trimmed.to_string();
     }
 
-    // Try to truncate at a hyphen boundary
-    let truncated = &trimmed[..MAX_LENGTH];
-    if let Some(last_hyphen) = truncated.rfind('-')
-        && last_hyphen > MAX_LENGTH / 2
+    // Try to truncate at a separator boundary
+    let truncated: String = trimmed.chars().take(max_length).collect();
+    if let Some(last_sep) = truncated.rfind(sep)
+        && last_sep > max_length / 2
     {
-        // Only use hyphen boundary if it's not too early
-        return truncated[..last_hyphen].to_string();
+        // Only use the boundary if it's not too early
+        return truncated[..last_sep].to_string();
+    }
+
+    // Otherwise just truncate and trim trailing separators
+    truncated.trim_end_matches(sep).to_string()
+}
+
+/// Transliterates lowercase text to ASCII, using the rules of `lang` (a
+/// primary language subtag) where they differ from the generic ones.
+fn transliterate(text: &str, lang: Option<&str>) -> String {
+    match lang {
+        Some("de") => text
+            .chars()
+            .map(|c| match c {
+                'ä' => "ae".to_string(),
+                'ö' => "oe".to_string(),
+                'ü' => "ue".to_string(),
+                _ => transliterate_char(c),
+            })
+            .collect(),
+        Some("ja") => romanize_kana(text),
+        _ => text.chars().map(transliterate_char).collect(),
+    }
+}
+
+/// Romanizes hiragana and katakana (Hepburn, with long vowels doubled).
+///
+/// Kanji have no reading without a dictionary, so they and Japanese
+/// punctuation separate words instead; other characters are transliterated
+/// as usual.
+fn romanize_kana(text: &str) -> String {
+    let mut out = String::new();
+    let mut geminate = false;
+    let mut chars = text.chars().map(to_hiragana).peekable();
+    while let Some(c) = chars.next() {
+        let Some(mut syllable) = kana_romaji(c).map(str::to_string) else {
+            geminate = false;
+            match c {
+                'っ' => geminate = true,
+                'ー' => {
+                    if let Some(vowel) = out.chars().last().filter(|v| "aeiou".contains(*v)) {
+                        out.push(vowel);
+                    }
+                }
+                '\u{3000}'..='\u{303f}' | '\u{3400}'..='\u{9fff}' | '・' => out.push(' '),
+                _ => out.push_str(&transliterate_char(c)),
+            }
+            continue;
+        };
+
+        // A small ゃ, ゅ or ょ after an i-row kana forms one syllable
+        if let Some(&small @ ('ゃ' | 'ゅ' | 'ょ')) = chars.peek()
+            && syllable.len() > 1
+            && syllable.ends_with('i')
+        {
+            chars.next();
+            syllable.pop();
+            if !matches!(syllable.as_str(), "sh" | "ch" | "j") {
+                syllable.push('y');
+            }
+            syllable.push(match small {
+                'ゃ' => 'a',
+                'ゅ' => 'u',
+                _ => 'o',
+            });
+        }
+
+        // A small っ doubles the next consonant (っち is "tchi")
+        if std::mem::take(&mut geminate)
+            && let Some(first) = syllable.chars().next().filter(|c| !"aeiou".contains(*c))
+        {
+            out.push(if first == 'c' { 't' } else { first });
+        }
+        out.push_str(&syllable);
+    }
+    out
+}
+
+/// Maps katakana to the matching hiragana, leaving other characters alone.
+fn to_hiragana(c: char) -> char {
+    match c {
+        '\u{30a1}'..='\u{30f6}' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
     }
+}
 
-    // Otherwise just truncate and trim trailing hyphens
-    truncated.trim_end_matches('-').to_string()
+/// The romaji for a single hiragana.
+fn kana_romaji(c: char) -> Option<&'static str> {
+    let romaji = match c {
+        'あ' | 'ぁ' => "a",
+        'い' | 'ぃ' | 'ゐ' => "i",
+        'う' | 'ぅ' => "u",
+        'え' | 'ぇ' | 'ゑ' => "e",
+        'お' | 'ぉ' | 'を' => "o",
+        'か' | 'ゕ' => "ka",
+        'き' => "ki",
+        'く' => "ku",
+        'け' | 'ゖ' => "ke",
+        'こ' => "ko",
+        'さ' => "sa",
+        'し' => "shi",
+        'す' => "su",
+        'せ' => "se",
+        'そ' => "so",
+        'た' => "ta",
+        'ち' => "chi",
+        'つ' => "tsu",
+        'て' => "te",
+        'と' => "to",
+        'な' => "na",
+        'に' => "ni",
+        'ぬ' => "nu",
+        'ね' => "ne",
+        'の' => "no",
+        'は' => "ha",
+        'ひ' => "hi",
+        'ふ' => "fu",
+        'へ' => "he",
+        'ほ' => "ho",
+        'ま' => "ma",
+        'み' => "mi",
+        'む' => "mu",
+        'め' => "me",
+        'も' => "mo",
+        'や' | 'ゃ' => "ya",
+        'ゆ' | 'ゅ' => "yu",
+        'よ' | 'ょ' => "yo",
+        'ら' => "ra",
+        'り' => "ri",
+        'る' => "ru",
+        'れ' => "re",
+        'ろ' => "ro",
+        'わ' | 'ゎ' => "wa",
+        'ん' => "n",
+        'が' => "ga",
+        'ぎ' => "gi",
+        'ぐ' => "gu",
+        'げ' => "ge",
+        'ご' => "go",
+        'ざ' => "za",
+        'じ' | 'ぢ' => "ji",
+        'ず' | 'づ' => "zu",
+        'ぜ' => "ze",
+        'ぞ' => "zo",
+        'だ' => "da",
+        'で' => "de",
+        'ど' => "do",
+        'ば' => "ba",
+        'び' => "bi",
+        'ぶ' => "bu",
+        'べ' => "be",
+        'ぼ' => "bo",
+        'ぱ' => "pa",
+        'ぴ' => "pi",
+        'ぷ' => "pu",
+        'ぺ' => "pe",
+        'ぽ' => "po",
+        'ゔ' => "vu",
+        _ => return None,
+    };
+    Some(romaji)
+}
+
+/// Transliterates a single lowercase character to ASCII where a mapping is known.
+///
+/// Covers Latin letters with diacritics, Greek, and Russian Cyrillic. Unknown
+/// characters are returned unchanged (and later dropped by the slug filter).
+fn transliterate_char(c: char) -> String {
+    let mapped = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'ı' => "i",
+        'ł' | 'ľ' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        // Greek
+        'α' | 'ά' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' | 'έ' => "e",
+        'ζ' => "z",
+        'η' | 'ή' => "i",
+        'θ' => "th",
+        'ι' | 'ί' | 'ϊ' | 'ΐ' => "i",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' | 'ό' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' | 'ύ' | 'ϋ' | 'ΰ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        'ω' | 'ώ' => "o",
+        // Cyrillic
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' => "e",
+        'ё' => "yo",
+        'ж' => "zh",
+        'з' => "z",
+        'и' => "i",
+        'й' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ы' => "y",
+        'э' => "e",
+        'ю' => "yu",
+        'я' => "ya",
+        _ => return c.to_string(),
+    };
+    mapped.to_string()
 }
 
 /// Generates a filename from a NoteId and title.
@@ -94,7 +426,12 @@ pub fn slugify(title: &str) -> String {
 /// assert_eq!(generate_filename(&id, "API Design"), "01HQ3K5M7N-api-design.md");
 /// ```
 pub fn generate_filename(id: &NoteId, title: &str) -> String {
-    format!("{}-{}.md", id.prefix(), slugify(title))
+    generate_filename_with(id, title, &SlugOptions::default())
+}
+
+/// Generates a filename from a NoteId and title using the given slug options.
+pub fn generate_filename_with(id: &NoteId, title: &str, options: &SlugOptions) -> String {
+    format!("{}-{}.md", id.prefix(), slugify_with(title, options))
 }
 
 #[cfg(test)]
@@ -201,6 +538,161 @@ mod tests {
         assert_eq!(slugify("2024 Goals"), "2024-goals");
     }
 
+    // ===========================================
+    // slugify_with() Options
+    // ===========================================
+
+    #[test]
+    fn slugify_with_default_options_matches_slugify() {
+        let options = SlugOptions::default();
+        for title in ["API Design", "Café Design", "foo--bar", "my_title", ""] {
+            assert_eq!(slugify_with(title, &options), slugify(title));
+        }
+    }
+
+    #[test]
+    fn slugify_with_transliterates_latin_diacritics() {
+        let options = SlugOptions {
+            transliterate: true,
+            ..SlugOptions::default()
+        };
+        assert_eq!(slugify_with("Café Design", &options), "cafe-design");
+        assert_eq!(slugify_with("Straße in Łódź", &options), "strasse-in-lodz");
+    }
+
+    #[test]
+    fn slugify_with_transliterates_cyrillic_and_greek() {
+        let options = SlugOptions {
+            transliterate: true,
+            ..SlugOptions::default()
+        };
+        assert_eq!(slugify_with("Привет мир", &options), "privet-mir");
+        assert_eq!(slugify_with("Καλημέρα κόσμε", &options), "kalimera-kosme");
+    }
+
+    #[test]
+    fn slugify_with_untransliterable_script_returns_untitled() {
+        let options = SlugOptions {
+            transliterate: true,
+            ..SlugOptions::default()
+        };
+        assert_eq!(slugify_with("日本語タイトル", &options), "untitled");
+    }
+
+    #[test]
+    fn slugify_with_follows_language_rules() {
+        let german = SlugOptions {
+            transliterate: true,
+            lang: Some("de-AT".to_string()),
+            ..SlugOptions::default()
+        };
+        assert_eq!(slugify_with("Grüße aus Köln", &german), "gruesse-aus-koeln");
+        assert_eq!(
+            slugify_with("Grüße aus Köln", &german.for_lang(Some("fr"))),
+            "grusse-aus-koln"
+        );
+
+        let japanese = SlugOptions {
+            transliterate: true,
+            ..SlugOptions::default()
+        }
+        .for_lang(Some("ja"));
+        assert_eq!(slugify_with("きょうのにっき", &japanese), "kyounonikki");
+        assert_eq!(slugify_with("東京タワー", &japanese), "tawaa");
+        assert_eq!(
+            slugify_with("日本語のノート・メモ", &japanese),
+            "nonooto-memo"
+        );
+        assert_eq!(
+            slugify_with("マッチ と しゃしん", &japanese),
+            "matchi-to-shashin"
+        );
+        assert_eq!(slugify_with("漢字", &japanese), "untitled");
+    }
+
+    #[test]
+    fn for_lang_keeps_the_configured_language_by_default() {
+        let options = SlugOptions {
+            lang: Some("de".to_string()),
+            ..SlugOptions::default()
+        };
+        assert_eq!(options.for_lang(None).lang.as_deref(), Some("de"));
+        assert_eq!(options.for_lang(Some("ja")).lang.as_deref(), Some("ja"));
+    }
+
+    #[test]
+    fn slugify_with_non_latin_without_transliteration_is_dropped() {
+        assert_eq!(slugify("Привет мир"), "untitled");
+        assert_eq!(slugify("Rust и Go"), "rust-go");
+    }
+
+    #[test]
+    fn slugify_with_respects_max_length() {
+        let options = SlugOptions {
+            max_length: 12,
+            ..SlugOptions::default()
+        };
+        assert_eq!(
+            slugify_with("Designing Data Intensive", &options),
+            "designing"
+        );
+        assert_eq!(slugify_with("abcdefghijklmnopq", &options), "abcdefghijkl");
+    }
+
+    #[test]
+    fn slugify_with_removes_stop_words() {
+        let options = SlugOptions {
+            stop_words: vec!["the".to_string(), "of".to_string(), "a".to_string()],
+            ..SlugOptions::default()
+        };
+        assert_eq!(
+            slugify_with("The Art of Programming", &options),
+            "art-programming"
+        );
+    }
+
+    #[test]
+    fn slugify_with_keeps_stop_words_if_nothing_else_remains() {
+        let options = SlugOptions {
+            stop_words: vec!["the".to_string()],
+            ..SlugOptions::default()
+        };
+        assert_eq!(slugify_with("The", &options), "the");
+    }
+
+    #[test]
+    fn slugify_with_custom_separator() {
+        let options = SlugOptions {
+            separator: '_',
+            ..SlugOptions::default()
+        };
+        assert_eq!(
+            slugify_with("API Design - Part 2", &options),
+            "api_design_part_2"
+        );
+    }
+
+    #[test]
+    fn slug_options_deserialize_partial() {
+        let options: SlugOptions = toml::from_str("transliterate = true").unwrap();
+        assert!(options.transliterate);
+        assert_eq!(options.max_length, DEFAULT_SLUG_MAX_LENGTH);
+        assert_eq!(options.separator, '-');
+    }
+
+    #[test]
+    fn generate_filename_with_uses_options() {
+        let id: NoteId = "01HQ3K5M7NXJK4QZPW8V2R6T9Y".parse().unwrap();
+        let options = SlugOptions {
+            transliterate: true,
+            ..SlugOptions::default()
+        };
+        assert_eq!(
+            generate_filename_with(&id, "Заметки", &options),
+            "01HQ3K5M7N-zametki.md"
+        );
+    }
+
     // ===========================================
     // Phase 2: generate_filename()
     // ===========================================
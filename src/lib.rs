@@ -1,55 +1,115 @@
 //! den - markdown notes with virtual folder organization
 
+pub mod api;
 pub mod cli;
 pub mod domain;
 pub mod export;
 pub mod index;
 pub mod infra;
+#[cfg(feature = "testkit")]
+pub mod testkit;
 
 use anyhow::Result;
 use clap::Parser;
+use std::time::{Duration, Instant};
 
 use cli::{
     Cli, Command,
     config::Config,
     handlers::{
-        handle_archive, handle_backlinks, handle_check, handle_completions, handle_edit,
-        handle_export, handle_index, handle_link, handle_list, handle_mv, handle_new, handle_rels,
-        handle_search, handle_show, handle_tag, handle_tags, handle_topics, handle_unarchive,
-        handle_unlink, handle_untag, handle_vaults,
+        handle_append, handle_archive, handle_backlinks, handle_bench, handle_bundle, handle_check,
+        handle_clone, handle_completions, handle_config, handle_daily, handle_desc, handle_digest,
+        handle_drill, handle_edit, handle_export, handle_fix_ids, handle_graph, handle_import,
+        handle_index, handle_keywords, handle_link, handle_list, handle_list_broken,
+        handle_migrate, handle_mv, handle_new, handle_person, handle_prepend, handle_quick_search,
+        handle_recent, handle_recover, handle_rels, handle_rm, handle_search, handle_show,
+        handle_snippet, handle_stats, handle_tag, handle_tags, handle_top, handle_topics,
+        handle_trash, handle_unarchive, handle_unlink, handle_untag, handle_urls, handle_vaults,
     },
 };
+use index::SqliteIndex;
 
 /// Main entry point for the CLI application.
 pub fn run() -> Result<()> {
-    let cli = Cli::parse();
-    let config = Config::load()?;
+    // A config that fails to load is reported once the command runs
+    let aliases = Config::load().map(|c| c.alias).unwrap_or_default();
+    let args = cli::alias::expand_aliases(std::env::args_os().collect(), &aliases)?;
+    let cli = Cli::parse_from(args);
+    if !cli.profile {
+        return run_command(&cli);
+    }
+
+    infra::profile::enable();
+    let start = Instant::now();
+    let result = run_command(&cli);
+    eprint!("{}", infra::profile::snapshot().render(start.elapsed()));
+    result
+}
+
+fn run_command(cli: &Cli) -> Result<()> {
+    // Validation has to work on a config file that fails to load
+    if let Command::Config(args) = &cli.command {
+        return handle_config(args);
+    }
+
+    let mut config = Config::load()?;
+    SqliteIndex::set_default_busy_timeout(Duration::from_millis(config.index.busy_timeout_ms));
+    config.time.utc |= cli.utc;
+    config.time.iso |= cli.iso;
+    config.output.pager &= !cli.no_pager;
+    config.interactive = (config.interactive || cli.interactive) && !cli.non_interactive;
 
     let resolved = config.resolve_notes_dir(cli.dir.as_ref(), cli.vault.as_deref())?;
     let notes_dir = resolved.path;
     let verbose = cli.verbose > 0;
 
     match &cli.command {
-        Command::Index(args) => handle_index(args, &notes_dir, verbose),
-        Command::List(args) => handle_list(args, &notes_dir),
-        Command::Search(args) => handle_search(args, &notes_dir),
+        Command::Index(args) => handle_index(args, &notes_dir, verbose, &config),
+        Command::List(args) => handle_list(args, &notes_dir, &config),
+        Command::Recent(args) => handle_recent(args, &notes_dir, &config),
+        Command::Top(args) => handle_top(args, &notes_dir, &config),
+        Command::Search(args) => handle_search(args, &notes_dir, &config),
+        Command::QuickSearch(args) => handle_quick_search(args, &notes_dir),
         Command::New(args) => handle_new(args, &notes_dir, &config),
-        Command::Show(args) => handle_show(args, &notes_dir),
+        Command::Daily(args) => handle_daily(args, &notes_dir, &config),
+        Command::Snippet(args) => handle_snippet(args, &notes_dir, &config),
+        Command::Clone(args) => handle_clone(args, &notes_dir, &config),
+        Command::Append(args) => handle_append(args, &notes_dir, &config),
+        Command::Prepend(args) => handle_prepend(args, &notes_dir, &config),
+        Command::Show(args) => handle_show(args, &notes_dir, &config),
         Command::Edit(args) => handle_edit(args, &notes_dir, &config),
-        Command::Topics(args) => handle_topics(args, &notes_dir),
-        Command::Tags(args) => handle_tags(args, &notes_dir),
-        Command::Tag(args) => handle_tag(args, &notes_dir),
-        Command::Untag(args) => handle_untag(args, &notes_dir),
-        Command::Check(args) => handle_check(args, &notes_dir),
-        Command::Backlinks(args) => handle_backlinks(args, &notes_dir),
-        Command::Link(args) => handle_link(args, &notes_dir),
-        Command::Unlink(args) => handle_unlink(args, &notes_dir),
-        Command::Rels(args) => handle_rels(args, &notes_dir),
+        Command::Topics(args) => handle_topics(args, &notes_dir, &config),
+        Command::Tags(args) => handle_tags(args, &notes_dir, &config),
+        Command::Tag(args) => handle_tag(args, &notes_dir, &config),
+        Command::Untag(args) => handle_untag(args, &notes_dir, &config),
+        Command::Desc(args) => handle_desc(args, &notes_dir, &config),
+        Command::Check(args) => handle_check(args, &notes_dir, &config),
+        Command::FixIds(args) => handle_fix_ids(args, &notes_dir, &config),
+        Command::Rm(args) => handle_rm(args, &notes_dir, &config),
+        Command::Trash(args) => handle_trash(args, &notes_dir, &config),
+        Command::Recover(args) => handle_recover(args, &notes_dir, &config),
+        Command::Stats(args) => handle_stats(args, &notes_dir, &config),
+        Command::Keywords(args) => handle_keywords(args, &notes_dir, &config),
+        Command::Backlinks(args) => handle_backlinks(args, &notes_dir, &config),
+        Command::Person(args) => handle_person(args, &notes_dir, &config),
+        Command::Link(args) => handle_link(args, &notes_dir, &config),
+        Command::Unlink(args) => handle_unlink(args, &notes_dir, &config),
+        Command::Rels(args) => handle_rels(args, &notes_dir, &config),
+        Command::Graph(args) => handle_graph(args, &notes_dir, &config),
+        Command::ListBroken(args) => handle_list_broken(args, &notes_dir, &config),
+        Command::Urls(args) => handle_urls(args, &notes_dir, &config),
         Command::Completions(args) => handle_completions(args),
-        Command::Mv(args) => handle_mv(args, &notes_dir),
-        Command::Archive(args) => handle_archive(args, &notes_dir),
-        Command::Unarchive(args) => handle_unarchive(args, &notes_dir),
-        Command::Export(args) => handle_export(args, &notes_dir),
-        Command::Vaults(args) => handle_vaults(args, &config),
+        Command::Mv(args) => handle_mv(args, &notes_dir, &config),
+        Command::Archive(args) => handle_archive(args, &notes_dir, &config),
+        Command::Unarchive(args) => handle_unarchive(args, &notes_dir, &config),
+        Command::Export(args) => handle_export(args, &notes_dir, &config),
+        Command::Digest(args) => handle_digest(args, &notes_dir),
+        Command::Bundle(args) => handle_bundle(args, &notes_dir, &config),
+        Command::Drill(args) => handle_drill(args, &notes_dir, &config),
+        Command::Import(args) => handle_import(args, &notes_dir, &config),
+        Command::Migrate(args) => handle_migrate(args, &notes_dir, &config),
+        Command::Vaults(args) => handle_vaults(args, &notes_dir, &config),
+        Command::Config(args) => handle_config(args),
+        Command::Bench(args) => handle_bench(args, &config),
     }
 }
@@ -1,23 +1,45 @@
 //! Fluent wrapper around assert_cmd::Command.
 
-// Allow dead code since this is a test utility with methods for future tests
-#![allow(dead_code)]
-
 use assert_cmd::Command;
 use serde::de::DeserializeOwned;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the `notes` binary to run, for crates that
+/// don't build it themselves.
+pub const BIN_ENV: &str = "DEN_BIN";
+
+/// The `notes` binary to run: the one named by [`BIN_ENV`], else the one
+/// built alongside the tests, else `notes` on the `PATH`.
+fn program() -> PathBuf {
+    if let Some(path) = std::env::var_os(BIN_ENV) {
+        return path.into();
+    }
+    #[allow(deprecated)]
+    let built = assert_cmd::cargo::cargo_bin("notes");
+    if built.is_file() {
+        built
+    } else {
+        PathBuf::from("notes")
+    }
+}
 
 /// Fluent wrapper around `assert_cmd::Command` for the `den` binary.
 ///
 /// Provides a builder-style API for constructing and executing CLI commands.
 pub struct DenCommand {
     args: Vec<String>,
+    stdin: Option<String>,
+    envs: Vec<(String, String)>,
 }
 
 impl DenCommand {
     /// Creates a new command for the `den` binary.
     pub fn new() -> Self {
-        Self { args: Vec::new() }
+        Self {
+            args: Vec::new(),
+            stdin: None,
+            envs: Vec::new(),
+        }
     }
 
     /// Sets the `--dir` option to specify the notes directory.
@@ -38,19 +60,47 @@ impl DenCommand {
         self
     }
 
+    /// Provides input to be written to the command's stdin.
+    pub fn stdin(mut self, input: impl Into<String>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    /// Sets an environment variable for the command, e.g. `EDITOR`.
+    pub fn env(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.envs.push((key.to_string(), value.into()));
+        self
+    }
+
     /// Returns the current arguments (for testing).
     pub fn get_args(&self) -> &[String] {
         &self.args
     }
 
     /// Runs the command and returns an Assert for making assertions.
-    #[allow(deprecated)]
     pub fn assert(self) -> assert_cmd::assert::Assert {
-        let mut cmd = Command::cargo_bin("den").expect("Failed to find den binary");
-        cmd.args(&self.args);
+        let mut cmd = Command::new(program());
+        cmd.args(&self.args).envs(self.envs);
+        if let Some(input) = self.stdin {
+            cmd.write_stdin(input);
+        }
         cmd.assert()
     }
 
+    /// Starts the command without waiting for it, for commands that run
+    /// until stopped, like `index --watch`. Its stdout is piped; stderr is
+    /// discarded.
+    pub fn spawn(self) -> std::process::Child {
+        std::process::Command::new(program())
+            .args(&self.args)
+            .envs(self.envs)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .expect("Failed to start command")
+    }
+
     /// Runs the command, expects success, and returns stdout as a string.
     pub fn output_success(self) -> String {
         let output = self.assert().success().get_output().stdout.clone();
@@ -77,6 +127,11 @@ impl DenCommand {
         self.args(["ls"])
     }
 
+    /// Configures for the `recent` command.
+    pub fn recent(self) -> Self {
+        self.args(["recent"])
+    }
+
     /// Configures for the `search` command with a query.
     pub fn search(self, query: &str) -> Self {
         self.args(["search", query])
@@ -117,6 +172,11 @@ impl DenCommand {
         self.args(["untag", note, tag])
     }
 
+    /// Configures for the `desc` command (set a note's description).
+    pub fn desc(self, note: &str, description: &str) -> Self {
+        self.args(["desc", note, description])
+    }
+
     /// Configures for the `backlinks` command.
     pub fn backlinks(self, note: &str) -> Self {
         self.args(["backlinks", note])
@@ -137,6 +197,16 @@ impl DenCommand {
         self.args(["rels"])
     }
 
+    /// Configures for the `rels graph` command.
+    pub fn rels_graph(self) -> Self {
+        self.args(["rels", "graph"])
+    }
+
+    /// Configures for the `urls` command.
+    pub fn urls(self) -> Self {
+        self.args(["urls"])
+    }
+
     /// Configures for the `check` command.
     pub fn check(self) -> Self {
         self.args(["check"])
@@ -157,6 +227,11 @@ impl DenCommand {
         self.args(["unarchive", note])
     }
 
+    /// Configures for the `snippet` command.
+    pub fn snippet(self) -> Self {
+        self.args(["snippet"])
+    }
+
     // ===========================================
     // Format Options
     // ===========================================
@@ -300,12 +375,6 @@ mod tests {
     // Phase 5: DenCommand Basics
     // ===========================================
 
-    #[test]
-    fn test_command_runs_binary() {
-        // Just verify the binary can be found and runs (with --help)
-        DenCommand::new().args(["--help"]).assert().success();
-    }
-
     #[test]
     fn test_command_with_dir() {
         let temp = TempDir::new().unwrap();
@@ -315,12 +384,6 @@ mod tests {
         assert_eq!(args[1], temp.path().to_string_lossy());
     }
 
-    #[test]
-    fn test_command_output_success() {
-        let output = DenCommand::new().args(["--help"]).output_success();
-        assert!(output.contains("den") || output.contains("notes"));
-    }
-
     #[test]
     fn test_command_shortcuts() {
         let cmd = DenCommand::new().ls().format_json();
@@ -1,11 +1,13 @@
 //! Isolated test environment with temp directory.
 
 use super::{DenCommand, TestNote};
+use crate::index::{IndexBuilder, SqliteIndex};
+use crate::infra::{generate_filename, write_note};
 use anyhow::Result;
-use den::index::{IndexBuilder, SqliteIndex};
-use den::infra::{generate_filename, write_note};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
+use walkdir::WalkDir;
 
 /// Isolated test environment with a temporary notes directory.
 ///
@@ -74,6 +76,29 @@ impl TestEnv {
         std::fs::write(&path, content).expect("Failed to write file");
         path
     }
+
+    /// Returns the contents of every note file, keyed by path relative to
+    /// the notes directory.
+    ///
+    /// Hidden directories such as the index are skipped. Compare snapshots
+    /// taken before and after a command to see exactly what it changed.
+    pub fn snapshot(&self) -> BTreeMap<PathBuf, String> {
+        WalkDir::new(&self.notes_dir)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "md"))
+            .map(|e| {
+                let relative = e
+                    .path()
+                    .strip_prefix(&self.notes_dir)
+                    .unwrap()
+                    .to_path_buf();
+                let content = std::fs::read_to_string(e.path()).expect("Failed to read note");
+                (relative, content)
+            })
+            .collect()
+    }
 }
 
 impl Default for TestEnv {
@@ -85,8 +110,8 @@ impl Default for TestEnv {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use den::index::IndexRepository;
-    use den::infra::read_note;
+    use crate::index::IndexRepository;
+    use crate::infra::read_note;
 
     // ===========================================
     // Phase 1: TestEnv Foundation
@@ -123,6 +148,20 @@ mod tests {
         assert!(index_path.starts_with(env.notes_dir()));
     }
 
+    #[test]
+    fn test_env_snapshot_lists_notes() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Snapshot Note").body("Snapshot body"));
+        env.write_file("style.css", "body {}");
+        env.build_index().unwrap();
+
+        let snapshot = env.snapshot();
+        assert_eq!(snapshot.len(), 1);
+        let (path, content) = snapshot.iter().next().unwrap();
+        assert!(path.is_relative());
+        assert!(content.contains("Snapshot body"));
+    }
+
     #[test]
     fn test_env_provides_command() {
         let env = TestEnv::new();
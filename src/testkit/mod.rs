@@ -0,0 +1,29 @@
+//! Helpers for integration tests against a real vault.
+//!
+//! Enabled by the `testkit` feature. [`TestEnv`] creates a throwaway notes
+//! directory, [`TestNote`] builds notes to put in it and [`DenCommand`] runs
+//! the `notes` binary against it.
+//!
+//! ```no_run
+//! use den::testkit::{TestEnv, TestNote};
+//!
+//! let env = TestEnv::new();
+//! env.add_note(&TestNote::new("Draft").topic("writing"));
+//! env.build_index().unwrap();
+//!
+//! let before = env.snapshot();
+//! env.cmd().args(["tag", "Draft", "review"]).assert().success();
+//! assert_ne!(before, env.snapshot());
+//! ```
+//!
+//! Crates that don't build the `notes` binary themselves can point
+//! [`DenCommand`] at one with the `DEN_BIN` environment variable; otherwise
+//! it is looked up on the `PATH`.
+
+mod command;
+mod env;
+mod note;
+
+pub use command::{BIN_ENV, DenCommand};
+pub use env::TestEnv;
+pub use note::TestNote;
@@ -1,7 +1,7 @@
 //! Builder for test notes with sensible defaults.
 
+use crate::domain::{Link, Note, NoteId, Tag, Topic};
 use chrono::{DateTime, Utc};
-use den::domain::{Link, Note, NoteId, Tag, Topic};
 
 /// Builder for creating test notes with sensible defaults.
 ///
@@ -39,6 +39,18 @@ impl TestNote {
         }
     }
 
+    /// Sets the created timestamp.
+    pub fn created(mut self, created: DateTime<Utc>) -> Self {
+        self.created = created;
+        self
+    }
+
+    /// Sets the modified timestamp.
+    pub fn modified(mut self, modified: DateTime<Utc>) -> Self {
+        self.modified = modified;
+        self
+    }
+
     /// Sets an explicit ID for the note.
     pub fn id(mut self, id: impl Into<String>) -> Self {
         self.id = id.into().parse().expect("Invalid NoteId");
@@ -5,7 +5,7 @@
 
 mod common;
 
-use common::harness::{TestEnv, TestNote};
+use den::testkit::{TestEnv, TestNote};
 use predicates::prelude::*;
 
 // ===========================================
@@ -94,6 +94,200 @@ mod index_tests {
             .success()
             .stdout(predicate::str::contains("Valid Note"));
     }
+
+    #[test]
+    fn test_index_converts_link_comments() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Target Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y"));
+        let source = env.add_note(&TestNote::new("Source Note").body(
+            "Intro.\n<!-- den:link \"Target Note\" rel=see-also -->\n<!-- den:link Missing rel=see-also -->\n",
+        ));
+
+        env.cmd()
+            .index()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Converted 1 link comment(s) in 1 note(s)",
+            ))
+            .stderr(predicate::str::contains("note not found: 'Missing'"));
+
+        let contents = std::fs::read_to_string(&source).unwrap();
+        assert!(
+            contents.contains("01HQ3K5M7NXJK4QZPW8V2R6T9Y"),
+            "{}",
+            contents
+        );
+        assert!(!contents.contains("Target Note\""), "{}", contents);
+        assert!(contents.contains("<!-- den:link Missing"), "{}", contents);
+        env.cmd()
+            .backlinks("Target Note")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Source Note"));
+
+        let output: serde_json::Value = env.cmd().index().format_json().output_json();
+        assert_eq!(output["data"]["converted_links"], 0);
+        assert_eq!(
+            output["data"]["skipped_link_comments"][0]["comment"],
+            "<!-- den:link Missing rel=see-also -->"
+        );
+    }
+
+    #[test]
+    fn test_index_stats_shows_tables_and_rebuild_time() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Stats Note").tag("draft"));
+
+        env.cmd().index().with_full().assert().success();
+
+        env.cmd()
+            .index()
+            .args(["--stats"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Schema version: 3"))
+            .stdout(predicate::str::contains("notes"))
+            .stdout(predicate::str::contains("Last full rebuild: never").not());
+    }
+
+    #[test]
+    fn test_index_stats_json() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Stats Note"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .index()
+            .args(["--stats", "--format", "json"])
+            .output_json();
+
+        let tables = json["data"]["tables"].as_array().unwrap();
+        let notes = tables.iter().find(|t| t["name"] == "notes").unwrap();
+        assert_eq!(notes["rows"], 1);
+        assert!(json["data"]["last_full_rebuild"].is_string());
+    }
+
+    #[test]
+    fn test_index_vacuum() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Vacuum Note"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .index()
+            .args(["--vacuum"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Vacuumed index"));
+    }
+
+    #[test]
+    fn test_index_refuses_nested_vault() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Outer Note"));
+        std::fs::create_dir_all(env.notes_dir().join("archive/old-vault/.index")).unwrap();
+
+        env.cmd()
+            .index()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "contains another vault at archive/old-vault",
+            ))
+            .stderr(predicate::str::contains("[scan]"));
+    }
+
+    #[test]
+    fn test_index_ls_and_check_honor_denignore() {
+        let env = TestEnv::new();
+        let note = TestNote::new("Kept Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        // A draft copy with the same ID and a broken swap file would both
+        // fail check if they were scanned
+        std::fs::create_dir(env.notes_dir().join("drafts")).unwrap();
+        let draft = env.add_note(&TestNote::new("Draft Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y"));
+        std::fs::rename(&draft, env.notes_dir().join("drafts/draft-note.md")).unwrap();
+        env.write_file("kept-note.swp.md", "no frontmatter");
+        env.write_file(".denignore", "# not notes\ndrafts/\n*.swp.md\n");
+
+        env.cmd().index().assert().success();
+
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Kept Note"))
+            .stdout(predicate::str::contains("Draft Note").not());
+        env.cmd().check().assert().success();
+
+        env.write_file(".denignore", "[drafts\n");
+        env.cmd()
+            .index()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(".denignore:1"));
+    }
+
+    /// Polls `ls` until the listed titles satisfy `done`, for up to 10 seconds.
+    fn wait_for_titles(env: &TestEnv, done: impl Fn(&[String]) -> bool) -> bool {
+        for _ in 0..100 {
+            let json: serde_json::Value = env.cmd().ls().format_json().output_json();
+            let titles: Vec<String> = json["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|n| n["title"].as_str().unwrap().to_string())
+                .collect();
+            if done(&titles) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        false
+    }
+
+    #[test]
+    fn test_index_watch_picks_up_changes() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Existing Note"));
+        let mut watcher = env.cmd().args(["index", "--watch"]).spawn();
+        // Changes made before the watcher has started would be missed
+        // and the output is kept open so the watcher can keep printing
+        let stdout = std::io::BufReader::new(watcher.stdout.take().unwrap());
+        let mut lines = std::io::BufRead::lines(stdout).map_while(Result::ok);
+        let watching = lines.any(|line| line.starts_with("Watching"));
+
+        let indexed = watching && wait_for_titles(&env, |titles| titles.len() == 1);
+        let added = indexed && {
+            let path = env.add_note(&TestNote::new("Watched Note"));
+            let added = wait_for_titles(&env, |titles| titles.len() == 2);
+            std::fs::remove_file(path).unwrap();
+            added
+        };
+        let removed = added && wait_for_titles(&env, |titles| titles.len() == 1);
+
+        watcher.kill().unwrap();
+        watcher.wait().unwrap();
+        drop(lines);
+        assert!(watching, "the watcher should start");
+        assert!(indexed, "initial update should index the vault");
+        assert!(added, "a new note should be indexed");
+        assert!(removed, "a removed note should leave the index");
+    }
+
+    #[test]
+    fn test_index_watch_rejects_json() {
+        let env = TestEnv::new();
+
+        env.cmd()
+            .args(["index", "--watch"])
+            .format_json()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--watch does not support JSON"));
+    }
 }
 
 // ===========================================
@@ -220,6 +414,55 @@ mod ls_tests {
             .stdout(predicate::str::contains("Only CLI").not());
     }
 
+    #[test]
+    fn test_ls_orphans_and_untagged() {
+        let env = TestEnv::new();
+
+        let filed = TestNote::new("Filed Note").topic("software/rust");
+        let tagged = TestNote::new("Tagged Note").tag("draft");
+        env.add_note(&filed);
+        env.add_note(&tagged);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .args(["--orphans"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Tagged Note"))
+            .stdout(predicate::str::contains("Filed Note").not());
+
+        env.cmd()
+            .ls()
+            .args(["--untagged"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Filed Note"))
+            .stdout(predicate::str::contains("Tagged Note").not());
+    }
+
+    #[test]
+    fn test_ls_no_links() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target Note");
+        let source = TestNote::new("Source Note").link(target.note_id().to_string(), &["see-also"]);
+        let lonely = TestNote::new("Lonely Note");
+        env.add_note(&target);
+        env.add_note(&source);
+        env.add_note(&lonely);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .args(["--no-links"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Lonely Note"))
+            .stdout(predicate::str::contains("Source Note").not())
+            .stdout(predicate::str::contains("Target Note").not());
+    }
+
     #[test]
     fn test_ls_format_json() {
         let env = TestEnv::new();
@@ -290,6 +533,28 @@ mod ls_tests {
             .success()
             .stdout(predicate::str::contains("Modified Recently"));
     }
+
+    #[test]
+    fn test_ls_not_paged_when_piped() {
+        let env = TestEnv::new();
+        for i in 0..60 {
+            env.add_note(&TestNote::new(format!("Note {}", i)));
+        }
+        env.build_index().expect("Should build index");
+
+        // stdout isn't a terminal, so long output is written directly
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("60 note(s)"));
+        env.cmd()
+            .ls()
+            .args(["--no-pager"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("60 note(s)"));
+    }
 }
 
 // ===========================================
@@ -317,19 +582,49 @@ mod search_tests {
     fn test_search_finds_body() {
         let env = TestEnv::new();
 
-        // Note: Body content search may not be implemented in current indexer.
-        // This test verifies search gracefully handles the query even if body isn't indexed.
         let note = TestNote::new("Microservices Architecture Guide")
             .body("# Content\n\nThis is about microservices patterns.");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Search for term that appears in title (body indexing may not be active)
         env.cmd()
             .search("Microservices")
             .assert()
             .success()
             .stdout(predicate::str::contains("Microservices"));
+
+        // "patterns" only appears in the body, under the Content heading
+        env.cmd()
+            .search("patterns")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Microservices Architecture Guide"))
+            .stdout(predicate::str::contains("§ Content"));
+    }
+
+    #[test]
+    fn test_search_json_includes_section() {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Deploy Runbook")
+                .body("# Deploy\n\n## Rollback\n\nRevert the canary first.\n"),
+        );
+        env.add_note(&TestNote::new("Canary Birds"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env.cmd().search("canary").format_json().output_json();
+        let results = json["data"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        let runbook = results
+            .iter()
+            .find(|r| r["title"] == "Deploy Runbook")
+            .unwrap();
+        assert_eq!(runbook["section"], "Deploy > Rollback");
+        let birds = results
+            .iter()
+            .find(|r| r["title"] == "Canary Birds")
+            .unwrap();
+        assert!(birds.get("section").is_none());
     }
 
     #[test]
@@ -442,6 +737,184 @@ mod search_tests {
             );
         }
     }
+
+    #[test]
+    fn test_search_in_note_lists_matching_lines_by_section() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Long Guide")
+            .body("# Setup\n\nInstall the toolchain.\n\n# Usage\n\nRun the toolchain daily.");
+        env.add_note(&note);
+        env.add_note(&TestNote::new("Other Note").body("toolchain elsewhere"));
+        env.build_index().expect("Should build index");
+
+        let output = env
+            .cmd()
+            .args(["search", "toolchain", "--in", "Long Guide"])
+            .output_success();
+
+        assert!(output.contains("Setup"));
+        assert!(output.contains("Install the toolchain."));
+        assert!(output.contains("Usage"));
+        assert!(output.contains("2 matching line(s)"));
+        assert!(!output.contains("elsewhere"));
+    }
+
+    #[test]
+    fn test_search_in_note_json_includes_line_numbers() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Json Guide").body("# Setup\n\nInstall the toolchain.");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["search", "toolchain", "--in", &note.id_prefix()])
+            .format_json()
+            .output_json();
+
+        let matches = output["data"].as_array().expect("data should be an array");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["heading"], "Setup");
+        assert_eq!(
+            matches[0]["line"].as_u64().unwrap(),
+            matches[0]["heading_line"].as_u64().unwrap() + 2
+        );
+    }
+
+    #[test]
+    fn test_search_in_unknown_note_fails() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["search", "anything", "--in", "missing"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("note not found"));
+    }
+
+    #[test]
+    fn test_search_explain_shows_field_breakdown() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Borrow Checker").body("The borrow rules.");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output = env
+            .cmd()
+            .args(["search", "borrow", "--explain"])
+            .output_success();
+
+        assert!(output.contains("Field"));
+        assert!(output.contains("title"));
+        assert!(output.contains("0..6"));
+        assert!(output.contains("description"));
+    }
+
+    #[test]
+    fn test_search_explain_json_lists_fields() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Borrow Checker").body("The borrow rules.");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["search", "borrow", "--explain"])
+            .format_json()
+            .output_json();
+
+        let fields = output["data"][0]["explain"]
+            .as_array()
+            .expect("explain should be an array");
+        // Four fields, then the body section the word is in
+        assert_eq!(fields.len(), 5);
+        assert_eq!(fields[4]["field"], "section");
+        assert_eq!(fields[0]["field"], "title");
+        assert_eq!(fields[0]["weight"], 10.0);
+        assert_eq!(fields[0]["matched"], true);
+        assert_eq!(fields[0]["offsets"], serde_json::json!([[0, 6]]));
+        assert_eq!(fields[1]["matched"], false);
+    }
+
+    #[test]
+    fn test_search_explain_includes_section_matches() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Math").body("# Algorithms\n\nFibonacci numbers grow fast.\n");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output = env
+            .cmd()
+            .args(["search", "fibonacci", "--explain"])
+            .output_success();
+        assert!(output.contains("section"), "{}", output);
+        assert!(output.contains("§ Algorithms"), "{}", output);
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["search", "fibonacci", "--explain"])
+            .format_json()
+            .output_json();
+        let fields = output["data"][0]["explain"]
+            .as_array()
+            .expect("explain should be an array");
+        let section = fields.last().unwrap();
+        assert_eq!(section["field"], "section");
+        assert_eq!(section["section"], "Algorithms");
+        assert!(section["score"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_search_pages_and_shows_best_match() {
+        let env = TestEnv::new();
+
+        for title in ["Gardening Basics", "Gardening Tools", "Gardening Calendar"] {
+            env.add_note(&TestNote::new(title).body(format!("{} body text.", title)));
+        }
+        env.build_index().expect("Should build index");
+
+        let all: serde_json::Value = env.cmd().search("gardening").format_json().output_json();
+        let titles: Vec<&str> = all["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles.len(), 3);
+
+        let page: serde_json::Value = env
+            .cmd()
+            .args(["search", "gardening", "--offset", "1", "--limit", "1"])
+            .format_json()
+            .output_json();
+        assert_eq!(page["data"].as_array().unwrap().len(), 1);
+        assert_eq!(page["data"][0]["title"], titles[1]);
+
+        env.cmd()
+            .args(["search", "gardening", "-n", "2"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Showing 1-2 of 3 result(s)"));
+
+        env.cmd()
+            .args(["search", "gardening", "--offset", "2", "--show"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(format!("# {}", titles[2])))
+            .stdout(predicate::str::contains("body text."));
+
+        env.cmd()
+            .args(["search", "zeppelin", "--show"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no matching notes"));
+    }
 }
 
 // ===========================================
@@ -538,924 +1011,3444 @@ mod new_tests {
     }
 
     #[test]
-    fn test_new_updates_index() {
+    fn test_new_with_type_and_ls_type_filter() {
         let env = TestEnv::new();
+        env.add_note(&TestNote::new("Plain Note"));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .new_note("Indexed After Creation")
+            .new_note("Ada Lovelace")
+            .args(["--type", "person"])
             .assert()
             .success();
 
-        // Note should appear in ls immediately after creation
-        // (new command should update the index)
         env.cmd()
             .ls()
+            .args(["--type", "person"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Indexed After Creation"));
+            .stdout(predicate::str::contains("Ada Lovelace"))
+            .stdout(predicate::str::contains("Type"))
+            .stdout(predicate::str::contains("Plain Note").not());
+
+        let json: serde_json::Value = env
+            .cmd()
+            .ls()
+            .args(["--type", "person"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"][0]["type"], "person");
     }
-}
-
-// ===========================================
-// show command tests
-// ===========================================
-mod show_tests {
-    use super::*;
 
     #[test]
-    fn test_show_by_id_prefix() {
+    fn test_new_with_unknown_type_fails() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Showable Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .description("Test description");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .show("01HQ3K5M7N")
+            .new_note("Soup")
+            .args(["--type", "recipe"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Showable Note"));
+            .failure()
+            .stderr(predicate::str::contains("unknown note type 'recipe'"));
     }
 
     #[test]
-    fn test_show_by_full_id() {
+    fn test_new_with_created_and_modified() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Full ID Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
+        let json: serde_json::Value = env
+            .cmd()
+            .new_note("Old Minutes")
+            .args(["--created", "2023-05-01", "--modified", "2023-06-01 12:00"])
+            .format_json()
+            .output_json();
+        let contents = fs::read_to_string(json["data"]["path"].as_str().unwrap()).unwrap();
+        assert!(
+            contents.contains("created: 2023-05-01T00:00:00Z"),
+            "{}",
+            contents
+        );
+        assert!(
+            contents.contains("modified: 2023-06-01T12:00:00Z"),
+            "{}",
+            contents
+        );
+
         env.cmd()
-            .show("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .new_note("Backwards")
+            .args(["--created", "2023-06-01", "--modified", "2023-05-01"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Full ID Note"));
+            .failure()
+            .stderr(predicate::str::contains("is after modified"));
+
+        env.cmd()
+            .new_note("Later")
+            .args(["--created", "2999-01-01"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--created is in the future"));
     }
 
     #[test]
-    fn test_show_by_title() {
+    fn test_new_updates_index() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Unique Title For Show");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .show("Unique Title For Show")
+            .new_note("Indexed After Creation")
+            .assert()
+            .success();
+
+        // Note should appear in ls immediately after creation
+        // (new command should update the index)
+        env.cmd()
+            .ls()
             .assert()
             .success()
-            .stdout(predicate::str::contains("Unique Title For Show"));
+            .stdout(predicate::str::contains("Indexed After Creation"));
     }
 
     #[test]
-    fn test_show_displays_metadata() {
+    fn test_new_with_link_shows_in_backlinks() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Metadata Note")
-            .topic("software/testing")
-            .tag("important")
-            .description("Critical information");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Design Doc").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .show("Metadata Note")
+            .new_note("Spin-off Idea")
+            .args(["--link", "01HQ4A2R9P", "--rel", "see-also"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Metadata Note"))
-            .stdout(
-                predicate::str::contains("software/testing")
-                    .or(predicate::str::contains("testing")),
-            )
-            .stdout(predicate::str::contains("important"))
-            .stdout(predicate::str::contains("Critical information"));
-    }
-
-    #[test]
-    fn test_show_displays_body() {
-        let env = TestEnv::new();
-
-        let note = TestNote::new("Body Note").body("# Main Content\n\nParagraph text here.");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+            .stdout(predicate::str::contains(
+                "Linked: 'Spin-off Idea' -> 'Design Doc'",
+            ));
 
         env.cmd()
-            .show("Body Note")
+            .backlinks("01HQ4A2R9P")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Main Content"))
-            .stdout(predicate::str::contains("Paragraph text"));
+            .stdout(predicate::str::contains("Spin-off Idea"));
     }
 
     #[test]
-    fn test_show_not_found() {
+    fn test_new_link_requires_rel() {
         let env = TestEnv::new();
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .show("nonexistent-id-xyz")
+            .new_note("Spin-off Idea")
+            .args(["--link", "01HQ4A2R9P"])
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("not found").or(predicate::str::contains("No note")));
+            .failure();
     }
-}
-
-// ===========================================
-// topics command tests
-// ===========================================
-mod topics_tests {
-    use super::*;
 
     #[test]
-    fn test_topics_lists_all() {
+    fn test_new_stdin_titles_note_from_body() {
         let env = TestEnv::new();
-
-        let note1 = TestNote::new("Note 1").topic("software/rust");
-        let note2 = TestNote::new("Note 2").topic("software/python");
-        let note3 = TestNote::new("Note 3").topic("science/math");
-        env.add_note(&note1);
-        env.add_note(&note2);
-        env.add_note(&note3);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .topics()
+            .args(["new", "--stdin"])
+            .stdin("# Standup Notes\n\n- shipped the parser\n")
             .assert()
             .success()
-            .stdout(predicate::str::contains("software").or(predicate::str::contains("rust")))
-            .stdout(predicate::str::contains("python"))
-            .stdout(predicate::str::contains("science").or(predicate::str::contains("math")));
+            .stdout(predicate::str::contains("Created: Standup Notes"));
+
+        let entries: Vec<_> = fs::read_dir(env.notes_dir())
+            .expect("Should read directory")
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].to_string_lossy().ends_with("standup-notes.md"));
+        let content = fs::read_to_string(&entries[0]).unwrap();
+        assert!(content.contains("- shipped the parser"));
     }
 
     #[test]
-    fn test_topics_with_counts() {
+    fn test_new_needs_title_without_stdin() {
         let env = TestEnv::new();
 
-        let note1 = TestNote::new("Note 1").topic("software/rust");
-        let note2 = TestNote::new("Note 2").topic("software/rust");
-        let note3 = TestNote::new("Note 3").topic("software/python");
-        env.add_note(&note1);
-        env.add_note(&note2);
-        env.add_note(&note3);
-        env.build_index().expect("Should build index");
-
-        // With counts flag
-        let output = env.cmd().topics().with_counts().output_success();
-
-        // Should show counts (rust has 2, python has 1)
-        assert!(
-            output.contains('2') || output.contains('1'),
-            "Output should include counts"
-        );
+        env.cmd().args(["new"]).assert().failure();
     }
+}
+
+// ===========================================
+// snippet command tests
+// ===========================================
+mod snippet_tests {
+    use super::*;
 
     #[test]
-    fn test_topics_format_json() {
+    fn test_snippet_from_stdin() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Note").topic("software/rust");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        let output: serde_json::Value = env.cmd().topics().format_json().output_json();
+        env.cmd()
+            .snippet()
+            .stdin(
+                "// Read a file to string
+use std::fs;
+let mut s = fs::read_to_string(p)?;
+",
+            )
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Created: Read a file to string"));
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert!(data.is_array(), "data should be an array");
+        env.cmd()
+            .ls()
+            .with_tag("rust")
+            .with_tag("snippet")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Read a file to string"));
     }
 
     #[test]
-    fn test_topics_empty() {
+    fn test_snippet_empty_stdin_fails() {
         let env = TestEnv::new();
 
-        // Add note without topic
-        let note = TestNote::new("No Topic Note");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
-
         env.cmd()
-            .topics()
+            .snippet()
+            .stdin("")
             .assert()
-            .success()
-            .stdout(predicate::str::is_empty().or(predicate::str::contains("No topics")));
+            .failure()
+            .stderr(predicate::str::contains("no snippet content"));
     }
 }
 
 // ===========================================
-// tags command tests
+// append/prepend command tests
 // ===========================================
-mod tags_tests {
+mod append_tests {
     use super::*;
 
     #[test]
-    fn test_tags_lists_all() {
-        let env = TestEnv::new();
+    fn test_append_under_heading_updates_modified() {
+        use chrono::{TimeZone, Utc};
 
-        let note1 = TestNote::new("Note 1").tag("rust").tag("cli");
-        let note2 = TestNote::new("Note 2").tag("python").tag("web");
-        env.add_note(&note1);
-        env.add_note(&note2);
-        env.build_index().expect("Should build index");
-
-        env.cmd()
-            .tags()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("rust"))
-            .stdout(predicate::str::contains("cli"))
-            .stdout(predicate::str::contains("python"))
-            .stdout(predicate::str::contains("web"));
-    }
-
-    #[test]
-    fn test_tags_with_counts() {
         let env = TestEnv::new();
 
-        let note1 = TestNote::new("Note 1").tag("rust");
-        let note2 = TestNote::new("Note 2").tag("rust");
-        let note3 = TestNote::new("Note 3").tag("python");
-        env.add_note(&note1);
-        env.add_note(&note2);
-        env.add_note(&note3);
+        let old = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let note = TestNote::new("Daily Log")
+            .modified(old)
+            .body("Intro\n\n## Log\n\n- started\n");
+        let path = env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        let output = env.cmd().tags().with_counts().output_success();
+        let json: serde_json::Value = env
+            .cmd()
+            .args([
+                "append",
+                &note.id_prefix(),
+                "- finished",
+                "--heading",
+                "Log",
+            ])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["heading"], "Log");
 
-        // Should show counts (rust has 2, python has 1)
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.ends_with("## Log\n\n- started\n\n- finished\n"));
         assert!(
-            output.contains('2') || output.contains('1'),
-            "Output should include counts"
+            !content.contains("2020-01-01"),
+            "modified should be updated"
         );
+
+        // The index sees the new modified date
+        env.cmd()
+            .ls()
+            .args(["--modified", "1d"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Daily Log"));
     }
 
     #[test]
-    fn test_tags_format_json() {
+    fn test_prepend_from_stdin() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Note").tag("test-tag");
-        env.add_note(&note);
+        let note = TestNote::new("Inbox").body("Older item\n");
+        let path = env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        let output: serde_json::Value = env.cmd().tags().format_json().output_json();
+        env.cmd()
+            .args(["prepend", &note.id_prefix(), "--stdin"])
+            .stdin("Newest item\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Prepended to 'Inbox'"));
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert!(data.is_array(), "data should be an array");
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.ends_with("Newest item\n\nOlder item\n"));
     }
 
     #[test]
-    fn test_tags_empty() {
+    fn test_append_requires_text() {
         let env = TestEnv::new();
 
-        // Add note without tags
-        let note = TestNote::new("No Tag Note");
+        let note = TestNote::new("Inbox");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .tags()
+            .args(["append", &note.id_prefix()])
             .assert()
-            .success()
-            .stdout(predicate::str::is_empty().or(predicate::str::contains("No tags")));
+            .failure();
+
+        env.cmd()
+            .args(["append", &note.id_prefix(), "--stdin"])
+            .stdin("\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no text to add"));
     }
 }
 
 // ===========================================
-// tag command (add tag) tests
+// clone command tests
 // ===========================================
-mod tag_add_tests {
+mod clone_tests {
     use super::*;
 
     #[test]
-    fn test_tag_adds_to_note() {
+    fn test_clone_copies_body_and_metadata() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Taggable Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        let target = TestNote::new("Link Target");
+        env.add_note(&target);
+        env.add_note(
+            &TestNote::new("Weekly Review")
+                .topic("reviews")
+                .tag("template")
+                .body("## Wins\n\n## Misses\n")
+                .link(target.note_id().to_string(), &["see-also"]),
+        );
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .tag_add("01HQ3K5M7N", "new-tag")
+        let output = env
+            .cmd()
+            .args(["clone", "Weekly Review"])
+            .format_json()
             .assert()
             .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+        assert_eq!(json["data"]["title"], "Copy of Weekly Review");
+        assert_eq!(json["data"]["topics"], serde_json::json!(["reviews"]));
+        assert_eq!(json["data"]["tags"], serde_json::json!(["template"]));
+
+        let path = json["data"]["path"].as_str().unwrap();
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("## Wins"));
+        assert!(!content.contains("see-also"));
 
-        // Verify tag was added
         env.cmd()
             .ls()
-            .with_tag("new-tag")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Taggable Note"));
+            .stdout(predicate::str::contains("Copy of Weekly Review"));
     }
 
     #[test]
-    fn test_tag_updates_index() {
+    fn test_clone_with_title() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Tag Index Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Sprint Plan").body("Goals:"));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .tag_add("01HQ3K5M7N", "indexed-tag")
-            .assert()
-            .success();
-
-        // Tag should appear in tags list
-        env.cmd()
-            .tags()
+            .args(["clone", "Sprint Plan", "--title", "Sprint 42 Plan"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("indexed-tag"));
+            .stdout(predicate::str::contains("Created: Sprint 42 Plan"));
     }
 
     #[test]
-    fn test_tag_duplicate_noop() {
+    fn test_clone_not_found() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Already Tagged")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("existing");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Adding same tag again should succeed (idempotent)
         env.cmd()
-            .tag_add("01HQ3K5M7N", "existing")
-            .assert()
-            .success();
-    }
-
-    #[test]
-    fn test_tag_not_found() {
-        let env = TestEnv::new();
-        env.build_index().expect("Should build index");
-
-        env.cmd()
-            .tag_add("nonexistent", "some-tag")
+            .args(["clone", "Missing"])
             .assert()
             .failure()
-            .stderr(predicate::str::contains("not found").or(predicate::str::contains("No note")));
+            .stderr(predicate::str::contains("note not found"));
     }
 }
 
 // ===========================================
-// untag command tests
+// show command tests
 // ===========================================
-mod untag_tests {
+mod show_tests {
     use super::*;
 
     #[test]
-    fn test_untag_removes_from_note() {
+    fn test_show_by_id_prefix() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Has Tag")
+        let note = TestNote::new("Showable Note")
             .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("removable");
+            .description("Test description");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .untag("01HQ3K5M7N", "removable")
+            .show("01HQ3K5M7N")
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("Showable Note"));
+    }
+
+    #[test]
+    fn test_show_reads_notes_from_stdin() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("First").body("First body"));
+        env.add_note(&TestNote::new("Second").body("Second body"));
+        env.build_index().expect("Should build index");
 
-        // Note should no longer have the tag
         env.cmd()
-            .ls()
-            .with_tag("removable")
+            .args(["show", "-"])
+            .stdin("First\nSecond\n")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Has Tag").not());
+            .stdout(predicate::str::contains("# First"))
+            .stdout(predicate::str::contains("Second body"));
+
+        let assert = env
+            .cmd()
+            .args(["show", "-"])
+            .stdin("Second\nmissing\n")
+            .format_json()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("error: 'missing': note not found"));
+        let json: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+        assert_eq!(json["data"][0]["title"], "Second");
     }
 
     #[test]
-    fn test_untag_updates_index() {
+    fn test_show_by_short_ref() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Only Tagged")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("sole-tag");
+        let note = TestNote::new("API Design")
+            .topic("rust")
+            .body("Short ref body");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        env.cmd().untag("01HQ3K5M7N", "sole-tag").assert().success();
-
-        // Tag should disappear from tags list if no other notes have it
         env.cmd()
-            .tags()
+            .show("rust/api-design")
             .assert()
             .success()
-            .stdout(predicate::str::contains("sole-tag").not());
+            .stdout(predicate::str::contains("Short ref body"));
     }
 
     #[test]
-    fn test_untag_missing_noop() {
+    fn test_show_by_full_id() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("No Such Tag").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note = TestNote::new("Full ID Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Removing nonexistent tag should succeed gracefully
         env.cmd()
-            .untag("01HQ3K5M7N", "nonexistent-tag")
+            .show("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("Full ID Note"));
     }
-}
-
-// ===========================================
-// check command tests
-// ===========================================
-mod check_tests {
-    use super::*;
 
     #[test]
-    fn test_check_clean() {
+    fn test_show_by_title() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Clean Note").topic("software");
+        let note = TestNote::new("Unique Title For Show");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        env.cmd().check().assert().success();
+        env.cmd()
+            .show("Unique Title For Show")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Unique Title For Show"));
     }
 
     #[test]
-    fn test_check_broken_links() {
+    fn test_show_displays_metadata() {
         let env = TestEnv::new();
 
-        // Note linking to nonexistent target
-        let note = TestNote::new("Broken Link Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HZZZZZZZXJK4QZPW8V2R6T9Y", &["see-also"]);
+        let note = TestNote::new("Metadata Note")
+            .topic("software/testing")
+            .tag("important")
+            .description("Critical information");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Check command reports errors with non-zero exit and "broken" in output
         env.cmd()
-            .check()
+            .show("Metadata Note")
             .assert()
-            .failure()
-            .stdout(predicate::str::contains("broken"));
+            .success()
+            .stdout(predicate::str::contains("Metadata Note"))
+            .stdout(
+                predicate::str::contains("software/testing")
+                    .or(predicate::str::contains("testing")),
+            )
+            .stdout(predicate::str::contains("important"))
+            .stdout(predicate::str::contains("Critical information"));
     }
 
     #[test]
-    fn test_check_duplicate_ids() {
+    fn test_show_displays_body() {
         let env = TestEnv::new();
 
-        // Two notes with same ID (unusual case)
-        let note1 = TestNote::new("First Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let note2 = TestNote::new("Second Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-
-        env.add_note(&note1);
-        // Adding second note with same ID creates a different file
-        let _path2 = env.add_note(&note2);
-
+        let note = TestNote::new("Body Note").body("# Main Content\n\nParagraph text here.");
+        env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Check should report duplicate IDs with non-zero exit
         env.cmd()
-            .check()
+            .show("Body Note")
             .assert()
-            .failure()
-            .stdout(predicate::str::contains("duplicate"));
+            .success()
+            .stdout(predicate::str::contains("Main Content"))
+            .stdout(predicate::str::contains("Paragraph text"));
     }
 
     #[test]
-    fn test_check_orphaned_notes() {
+    fn test_show_utc_iso_timestamps() {
         let env = TestEnv::new();
-
-        // Note without any topic (orphaned in terms of hierarchy)
-        let note = TestNote::new("Orphan Note");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Timestamp Note"));
         env.build_index().expect("Should build index");
 
-        // Check should succeed even with orphaned notes
-        env.cmd().check().assert().success();
-    }
-}
+        let output = env
+            .cmd()
+            .args(["--utc", "--iso"])
+            .show("Timestamp Note")
+            .assert()
+            .success();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
 
-// ===========================================
-// backlinks command tests
-// ===========================================
-mod backlinks_tests {
-    use super::*;
+        let created = stdout
+            .split("Created: ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .expect("Should print created timestamp");
+        assert!(created.ends_with('Z'), "not UTC: {}", created);
+        assert!(chrono::DateTime::parse_from_rfc3339(created).is_ok());
+    }
 
     #[test]
-    fn test_backlinks_finds_linking_notes() {
+    fn test_show_not_found() {
         let env = TestEnv::new();
-
-        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Source Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["see-also"]);
-        env.add_note(&target);
-        env.add_note(&source);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .backlinks("01HQ4A2R9P")
+            .show("nonexistent-id-xyz")
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Source Note"));
+            .failure()
+            .stderr(predicate::str::contains("not found").or(predicate::str::contains("No note")));
     }
 
     #[test]
-    fn test_backlinks_with_rel_filter() {
+    fn test_show_from_index_needs_stored_bodies() {
         let env = TestEnv::new();
-
-        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let parent = TestNote::new("Parent Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
-        let sibling = TestNote::new("Sibling Note")
-            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["sibling"]);
-        env.add_note(&target);
-        env.add_note(&parent);
-        env.add_note(&sibling);
+        env.add_note(&TestNote::new("Offline").body("Kept elsewhere."));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .backlinks("01HQ4A2R9P")
-            .with_rel("parent")
+            .args(["show", "Offline", "--from-index"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Parent Note"))
-            .stdout(predicate::str::contains("Sibling Note").not());
+            .failure()
+            .stderr(predicate::str::contains("holds no body for 'Offline'"));
     }
 
     #[test]
-    fn test_backlinks_format_json() {
+    fn test_show_section() {
         let env = TestEnv::new();
-
-        let target = TestNote::new("JSON Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("JSON Source")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["related"]);
-        env.add_note(&target);
-        env.add_note(&source);
+        env.add_note(&TestNote::new("Manual").body(
+            "Intro\n\n# Setup\n\nInstall it.\n\n## Linux\n\nUse apt.\n\n# Usage\n\nRun it.\n",
+        ));
         env.build_index().expect("Should build index");
 
-        let output: serde_json::Value = env
+        let stdout = env
             .cmd()
-            .backlinks("01HQ4A2R9P")
+            .show("Manual")
+            .args(["--section", "setup"])
+            .output_success();
+        assert_eq!(stdout, "# Setup\n\nInstall it.\n\n## Linux\n\nUse apt.\n");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .show("Manual")
+            .args(["-s", "Setup > Linux"])
             .format_json()
             .output_json();
+        assert_eq!(json["data"]["section"], "Setup > Linux");
+        assert_eq!(json["data"]["body"], "## Linux\n\nUse apt.");
 
-        assert!(output.is_object(), "Output should be a JSON object");
+        env.cmd()
+            .show("Manual")
+            .args(["--section", "Missing"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("no section 'Missing' in 'Manual'"));
+    }
+
+    fn add_duplicate_notes(env: &TestEnv) {
+        let first = TestNote::new("Duplicate Title")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9A")
+            .body("First body");
+        let second = TestNote::new("Duplicate Title")
+            .id("01HQ4A2R9PXJK4QZPW8V2R6T9B")
+            .body("Second body");
+        env.add_note(&first);
+        env.add_note(&second);
+        env.build_index().expect("Should build index");
     }
 
     #[test]
-    fn test_backlinks_empty() {
+    fn test_show_interactive_prompts_for_ambiguous_note() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("No Backlinks").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        add_duplicate_notes(&env);
 
         env.cmd()
-            .backlinks("01HQ4A2R9P")
+            .args(["--interactive"])
+            .show("Duplicate Title")
+            .stdin("2\n")
             .assert()
             .success()
-            .stdout(predicate::str::is_empty().or(predicate::str::contains("No backlinks")));
+            .stdout(predicate::str::contains("Second body"))
+            .stderr(predicate::str::contains("1) 01HQ3K5M7N - Duplicate Title"));
     }
-}
-
-// ===========================================
-// link command tests
-// ===========================================
-mod link_tests {
-    use super::*;
 
     #[test]
-    fn test_link_creates_link() {
+    fn test_show_interactive_without_choice_fails() {
         let env = TestEnv::new();
+        add_duplicate_notes(&env);
 
-        let source = TestNote::new("Link Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let target = TestNote::new("Link Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
-        env.add_note(&target);
-        env.build_index().expect("Should build index");
-
-        env.cmd()
-            .link("01HQ3K5M7N", "01HQ4A2R9P")
-            .with_rel("see-also")
-            .assert()
-            .success();
-
-        // Verify link appears in backlinks
         env.cmd()
-            .backlinks("01HQ4A2R9P")
+            .args(["--interactive"])
+            .show("Duplicate Title")
+            .stdin("")
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Link Source"));
+            .failure()
+            .stderr(predicate::str::contains("no note chosen"));
     }
 
     #[test]
-    fn test_link_with_rel() {
+    fn test_show_non_interactive_fails_on_ambiguous_note() {
         let env = TestEnv::new();
-
-        let source = TestNote::new("Rel Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let target = TestNote::new("Rel Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
-        env.add_note(&target);
-        env.build_index().expect("Should build index");
+        add_duplicate_notes(&env);
 
         env.cmd()
-            .link("01HQ3K5M7N", "01HQ4A2R9P")
-            .with_rel("parent")
+            .args(["--non-interactive"])
+            .show("Duplicate Title")
+            .stdin("2\n")
             .assert()
-            .success();
+            .failure()
+            .stderr(predicate::str::contains("ambiguous note identifier"))
+            .stderr(predicate::str::contains("01HQ4A2R9P - Duplicate Title"));
+    }
+
+    #[test]
+    fn test_show_interactive_filters_ambiguous_notes() {
+        let env = TestEnv::new();
+        add_duplicate_notes(&env);
 
-        // Verify link with specific rel
         env.cmd()
-            .backlinks("01HQ4A2R9P")
-            .with_rel("parent")
+            .args(["-i"])
+            .show("Duplicate Title")
+            .stdin("01HQ4A\n\n")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Rel Source"));
+            .stdout(predicate::str::contains("Second body"));
     }
 
     #[test]
-    fn test_link_with_note() {
+    fn test_show_without_note_picks_one_interactively() {
         let env = TestEnv::new();
-
-        let source = TestNote::new("Context Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let target = TestNote::new("Context Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
-        env.add_note(&target);
+        env.add_note(&TestNote::new("API Design").body("Design body"));
+        env.add_note(&TestNote::new("Rust Ownership").body("Ownership body"));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .link("01HQ3K5M7N", "01HQ4A2R9P")
-            .with_rel("related")
-            .with_note("Important connection")
+            .args(["--interactive", "show"])
+            .stdin("ownership\n1\n")
             .assert()
-            .success();
-    }
-
-    #[test]
-    fn test_link_updates_index() {
-        let env = TestEnv::new();
-
-        let source = TestNote::new("Index Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let target = TestNote::new("Index Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
-        env.add_note(&target);
-        env.build_index().expect("Should build index");
+            .success()
+            .stdout(predicate::str::contains("Ownership body"))
+            .stderr(predicate::str::contains("Pick a note (2 notes):"));
 
         env.cmd()
-            .link("01HQ3K5M7N", "01HQ4A2R9P")
-            .with_rel("related")
+            .args(["show"])
             .assert()
-            .success();
+            .failure()
+            .stderr(predicate::str::contains("a note is required"));
+    }
+
+    /// Stages the notes in the vault and commits them.
+    fn commit_notes(repo: &git2::Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*.md"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_show_history_and_at_revision() {
+        let env = TestEnv::new();
+        let repo = git2::Repository::init(env.notes_dir()).unwrap();
+        let path = env.add_note(&TestNote::new("Versioned Note").body("First draft"));
+        commit_notes(&repo, "Add note");
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, contents.replace("First draft", "Second draft")).unwrap();
+        commit_notes(&repo, "Revise note");
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .show("Versioned Note")
+            .args(["--history"])
+            .format_json()
+            .output_json();
+        let messages: Vec<&str> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| c["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(messages, vec!["Revise note", "Add note"]);
 
-        // Link should be immediately visible in backlinks (index updated)
         env.cmd()
-            .backlinks("01HQ4A2R9P")
+            .show("Versioned Note")
+            .args(["--at", "HEAD~1"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Index Source"));
+            .stdout(predicate::str::contains("First draft"))
+            .stdout(predicate::str::contains("Second draft").not());
+        env.cmd()
+            .show("Versioned Note")
+            .args(["--history"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "2 commit(s) changed 'Versioned Note'",
+            ));
     }
 
     #[test]
-    fn test_link_target_not_found() {
+    fn test_show_history_outside_git_repository_fails() {
         let env = TestEnv::new();
-
-        let source = TestNote::new("Lonely Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
+        env.add_note(&TestNote::new("Unversioned Note"));
         env.build_index().expect("Should build index");
 
-        // Linking to nonexistent target should warn or fail
         env.cmd()
-            .link("01HQ3K5M7N", "nonexistent-id")
-            .with_rel("broken")
+            .show("Unversioned Note")
+            .args(["--history"])
             .assert()
-            .failure();
+            .failure()
+            .stderr(predicate::str::contains("is not in a git repository"));
     }
 }
 
 // ===========================================
-// unlink command tests
+// urls command tests
 // ===========================================
-mod unlink_tests {
+mod urls_tests {
     use super::*;
 
+    fn add_reading_notes(env: &TestEnv) {
+        env.add_note(
+            &TestNote::new("Reading")
+                .body("See https://github.com/rust-lang/rust and <https://example.com/docs>."),
+        );
+        env.add_note(
+            &TestNote::new("Tools")
+                .body("[Gist](https://gist.github.com/abc), `https://code.example`"),
+        );
+        env.build_index().expect("Should build index");
+    }
+
     #[test]
-    fn test_unlink_removes_link() {
+    fn test_urls_lists_references() {
         let env = TestEnv::new();
+        add_reading_notes(&env);
 
-        let target = TestNote::new("Unlink Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Unlink Source")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["removable"]);
-        env.add_note(&target);
-        env.add_note(&source);
-        env.build_index().expect("Should build index");
+        let stdout = env.cmd().urls().output_success();
+        assert!(stdout.contains("https://github.com/rust-lang/rust"));
+        assert!(stdout.contains("https://example.com/docs"));
+        assert!(stdout.contains("https://gist.github.com/abc"));
+        assert!(!stdout.contains("code.example"));
+        assert!(stdout.contains("3 URL(s)"));
+    }
 
-        env.cmd()
-            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
-            .assert()
-            .success();
+    #[test]
+    fn test_urls_filters_by_domain() {
+        let env = TestEnv::new();
+        add_reading_notes(&env);
 
-        // Link should be removed from backlinks
-        env.cmd()
-            .backlinks("01HQ4A2R9P")
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Unlink Source").not());
+        let json: serde_json::Value = env
+            .cmd()
+            .urls()
+            .args(["--domain", "github.com"])
+            .format_json()
+            .output_json();
+        let data = json["data"].as_array().unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0]["url"], "https://gist.github.com/abc");
+        assert_eq!(data[0]["domain"], "gist.github.com");
+        assert_eq!(data[0]["notes"][0]["title"], "Tools");
+        assert_eq!(data[1]["notes"][0]["title"], "Reading");
+        assert!(data[0].get("status").is_none());
+
+        let stdout = env
+            .cmd()
+            .urls()
+            .args(["--domain", "nowhere.org"])
+            .output_success();
+        assert!(stdout.contains("No URLs found."));
     }
 
     #[test]
-    fn test_unlink_updates_index() {
+    fn test_urls_broken_reports_unreachable() {
         let env = TestEnv::new();
-
-        let target = TestNote::new("Unindex Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Unindex Source")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["indexed"]);
-        env.add_note(&target);
-        env.add_note(&source);
+        // Nothing listens on a port that was just released
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        env.add_note(&TestNote::new("Dead").body(format!("Gone: http://127.0.0.1:{}/page", port)));
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
-            .assert()
-            .success();
-
-        // Index should be updated immediately
-        env.cmd()
-            .backlinks("01HQ4A2R9P")
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Unindex Source").not());
+        let json: serde_json::Value = env
+            .cmd()
+            .urls()
+            .args(["--broken", "--timeout", "5"])
+            .format_json()
+            .output_json();
+        let data = json["data"].as_array().unwrap();
+        assert_eq!(data.len(), 1);
+        assert!(data[0]["error"].is_string());
+        assert!(data[0].get("status").is_none());
     }
 
     #[test]
-    fn test_unlink_missing_noop() {
+    fn test_urls_timeout_requires_broken() {
         let env = TestEnv::new();
-
-        let source = TestNote::new("No Link Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let target = TestNote::new("No Link Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&source);
-        env.add_note(&target);
-        env.build_index().expect("Should build index");
-
-        // Removing nonexistent link should succeed gracefully
-        env.cmd()
-            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
-            .assert()
-            .success();
+        env.cmd().urls().args(["--timeout", "5"]).assert().failure();
     }
 }
 
 // ===========================================
-// rels command tests
+// list-broken command tests
 // ===========================================
-mod rels_tests {
+mod list_broken_tests {
     use super::*;
 
-    #[test]
-    fn test_rels_lists_all() {
-        let env = TestEnv::new();
+    const MISSING: &str = "01HZZZZZZZXJK4QZPW8V2R6T9Y";
 
-        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source1 = TestNote::new("Source 1")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
-        let source2 = TestNote::new("Source 2")
-            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["see-also"]);
+    fn add_linked_notes(env: &TestEnv) -> TestNote {
+        let target = TestNote::new("Target");
         env.add_note(&target);
-        env.add_note(&source1);
-        env.add_note(&source2);
-        env.build_index().expect("Should build index");
+        env.add_note(
+            &TestNote::new("Rust Note")
+                .topic("software/rust")
+                .link(MISSING, &["see-also"])
+                .link(target.note_id().to_string(), &["parent"]),
+        );
+        env.add_note(
+            &TestNote::new("Journal Note")
+                .topic("journal")
+                .link(MISSING, &["parent"]),
+        );
+        env.build_index().unwrap();
+        target
+    }
+
+    #[test]
+    fn test_list_broken_lists_each_link() {
+        let env = TestEnv::new();
+        add_linked_notes(&env);
 
         env.cmd()
-            .rels()
+            .args(["list-broken"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("parent"))
-            .stdout(predicate::str::contains("see-also"));
+            .stdout(predicate::str::contains("Rust Note"))
+            .stdout(predicate::str::contains("Journal Note"))
+            .stdout(predicate::str::contains(MISSING))
+            .stdout(predicate::str::contains("2 broken link(s)"));
     }
 
     #[test]
-    fn test_rels_with_counts() {
+    fn test_list_broken_json_filtered_by_topic() {
         let env = TestEnv::new();
+        add_linked_notes(&env);
 
-        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source1 = TestNote::new("Source 1")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
-        let source2 = TestNote::new("Source 2")
-            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
-        env.add_note(&target);
-        env.add_note(&source1);
-        env.add_note(&source2);
-        env.build_index().expect("Should build index");
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["list-broken", "--topic", "software/"])
+            .format_json()
+            .output_json();
 
-        let output = env.cmd().rels().with_counts().output_success();
+        let links = json["data"].as_array().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0]["source"]["title"], "Rust Note");
+        assert_eq!(links[0]["target_id"], MISSING);
+        assert_eq!(links[0]["rels"], serde_json::json!(["see-also"]));
+        assert_eq!(links[0]["trashed"], false);
+    }
 
-        // Parent should have count of 2
-        assert!(
-            output.contains('2'),
-            "Output should show count for parent relationship"
-        );
+    #[test]
+    fn test_list_broken_marks_links_into_the_trash() {
+        let env = TestEnv::new();
+        let target = add_linked_notes(&env);
+        env.cmd()
+            .args(["rm", &target.note_id().to_string()])
+            .assert()
+            .success();
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["list-broken", "--rel", "parent"])
+            .format_json()
+            .output_json();
+
+        let links = json["data"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        let trashed: Vec<bool> = links
+            .iter()
+            .map(|l| l["trashed"].as_bool().unwrap())
+            .collect();
+        assert!(trashed.contains(&true) && trashed.contains(&false));
     }
 
     #[test]
-    fn test_rels_format_json() {
+    fn test_list_broken_lists_wikilinks_check_warns_about() {
         let env = TestEnv::new();
+        env.add_note(&TestNote::new("API Design").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
+        env.add_note(
+            &TestNote::new("Standup")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("See [[API Design]], [[01HQ4A2R|the API]] and [[Roadmap]].\n"),
+        );
+        env.build_index().unwrap();
 
-        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Source")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["related"]);
-        env.add_note(&target);
-        env.add_note(&source);
-        env.build_index().expect("Should build index");
+        env.cmd()
+            .args(["check"])
+            .assert()
+            .stdout(predicate::str::contains("[[Roadmap]]"));
+        env.cmd()
+            .args(["list-broken"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[[Roadmap]]"))
+            .stdout(predicate::str::contains("1 broken link(s)"));
 
-        let output: serde_json::Value = env.cmd().rels().format_json().output_json();
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["list-broken", "--rel", "mentions"])
+            .format_json()
+            .output_json();
+        let links = json["data"].as_array().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0]["source"]["title"], "Standup");
+        assert_eq!(links[0]["target_id"], serde_json::Value::Null);
+        assert_eq!(links[0]["wikilink"], "Roadmap");
+        assert_eq!(links[0]["matches"], 0);
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert!(data.is_array(), "data should be an array");
+        let output = env
+            .cmd()
+            .args(["list-broken", "--rel", "parent"])
+            .output_success();
+        assert!(output.contains("No broken links found."));
     }
 
     #[test]
-    fn test_rels_empty() {
+    fn test_list_broken_paths_and_empty_vault() {
         let env = TestEnv::new();
-
-        // Notes without any links
-        let note = TestNote::new("No Links");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        env.add_note(&TestNote::new("Lonely"));
+        env.build_index().unwrap();
 
         env.cmd()
-            .rels()
+            .args(["list-broken"])
             .assert()
             .success()
-            .stdout(predicate::str::is_empty().or(predicate::str::contains("No relationship")));
+            .stdout(predicate::str::contains("No broken links found."));
+
+        add_linked_notes(&env);
+        let output = env
+            .cmd()
+            .args(["list-broken"])
+            .format_paths()
+            .output_success();
+        assert_eq!(output.lines().count(), 2);
     }
 }
 
 // ===========================================
-// Edge cases and error handling tests
+// bundle command tests
 // ===========================================
-mod edge_case_tests {
+mod bundle_tests {
     use super::*;
 
     #[test]
-    fn test_special_characters_in_title() {
+    fn test_bundle_round_trip_without_contents() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Note with 'quotes' and \"double quotes\"");
-        env.add_note(&note);
+        let rest = TestNote::new("REST Principles")
+            .topic("software/api")
+            .body("Secret body text");
+        let api = TestNote::new("API Design")
+            .topic("software/api")
+            .tag("draft")
+            .description("Secret description")
+            .link(rest.note_id().to_string(), &["parent"]);
+        let diary = TestNote::new("Diary").tag("private");
+        env.add_note(&rest);
+        env.add_note(&api);
+        env.add_note(&diary);
         env.build_index().expect("Should build index");
+        let passphrase = env.write_file("passphrase.txt", "correct horse\n");
+        let passphrase = passphrase.to_str().unwrap();
+        let bundle = env.notes_dir().join("shared/vault.denb");
+        let bundle_arg = bundle.to_str().unwrap();
 
-        env.cmd()
-            .ls()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("quotes"));
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["bundle", "create", "-o", bundle_arg])
+            .args(["--passphrase-file", passphrase])
+            .args(["--exclude-tag", "private"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["notes"], 2);
+        assert_eq!(json["data"]["links"], 1);
+
+        let bytes = std::fs::read(&bundle).unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(!text.contains("API Design"));
+        assert!(!text.contains("Secret"));
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["bundle", "open", bundle_arg])
+            .args(["--passphrase-file", passphrase])
+            .format_json()
+            .output_json();
+        let notes = json["data"]["notes"].as_array().unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0]["title"], "API Design");
+        assert_eq!(notes[0]["tags"][0], "draft");
+        assert_eq!(notes[0]["links"][0]["rels"][0], "parent");
+        assert!(notes[0].get("description").is_none());
+        assert!(notes[0].get("body").is_none());
+
+        let stdout = env
+            .cmd()
+            .args(["bundle", "open", bundle_arg])
+            .args(["--passphrase-file", passphrase])
+            .output_success();
+        assert!(stdout.contains("2 note(s), 1 topic(s), 1 tag(s) and 1 link(s)"));
+        assert!(stdout.contains("REST Principles"));
     }
 
     #[test]
-    fn test_unicode_in_content() {
+    fn test_bundle_open_wrong_passphrase_fails() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Unicode Note").body("# 日本語\n\nEmoji: \u{1F980} \u{1F40D}");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("API Design"));
         env.build_index().expect("Should build index");
+        let right = env.write_file("right.txt", "correct horse");
+        let wrong = env.write_file("wrong.txt", "battery staple");
+        let bundle = env.notes_dir().join("vault.denb");
+        let bundle = bundle.to_str().unwrap();
 
         env.cmd()
-            .show("Unicode Note")
+            .args(["bundle", "create", "-o", bundle])
+            .args(["--passphrase-file", right.to_str().unwrap()])
+            .output_success();
+
+        env.cmd()
+            .args(["bundle", "open", bundle])
+            .args(["--passphrase-file", wrong.to_str().unwrap()])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("日本語"));
+            .failure()
+            .stderr(predicate::str::contains("wrong passphrase"));
     }
+}
+
+// ===========================================
+// topics command tests
+// ===========================================
+mod topics_tests {
+    use super::*;
 
     #[test]
-    fn test_very_long_title() {
+    fn test_topics_lists_all() {
         let env = TestEnv::new();
 
-        let long_title = "A".repeat(200);
-        let note = TestNote::new(&long_title);
-        env.add_note(&note);
+        let note1 = TestNote::new("Note 1").topic("software/rust");
+        let note2 = TestNote::new("Note 2").topic("software/python");
+        let note3 = TestNote::new("Note 3").topic("science/math");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.add_note(&note3);
         env.build_index().expect("Should build index");
 
-        env.cmd().ls().assert().success();
+        env.cmd()
+            .topics()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("software").or(predicate::str::contains("rust")))
+            .stdout(predicate::str::contains("python"))
+            .stdout(predicate::str::contains("science").or(predicate::str::contains("math")));
     }
 
     #[test]
-    fn test_empty_body() {
+    fn test_topics_with_counts() {
+        let env = TestEnv::new();
+
+        let note1 = TestNote::new("Note 1").topic("software/rust");
+        let note2 = TestNote::new("Note 2").topic("software/rust");
+        let note3 = TestNote::new("Note 3").topic("software/python");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.add_note(&note3);
+        env.build_index().expect("Should build index");
+
+        // With counts flag
+        let output = env.cmd().topics().with_counts().output_success();
+
+        // Should show counts (rust has 2, python has 1)
+        assert!(
+            output.contains('2') || output.contains('1'),
+            "Output should include counts"
+        );
+    }
+
+    #[test]
+    fn test_topics_format_json() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Note").topic("software/rust");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().topics().format_json().output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert!(data.is_array(), "data should be an array");
+    }
+
+    #[test]
+    fn test_topics_empty() {
+        let env = TestEnv::new();
+
+        // Add note without topic
+        let note = TestNote::new("No Topic Note");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .topics()
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty().or(predicate::str::contains("No topics")));
+    }
+
+    #[test]
+    fn test_topics_tree() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Note 1").topic("software/rust"));
+        env.add_note(&TestNote::new("Note 2").topic("software/rust/async"));
+        env.add_note(&TestNote::new("Note 3").topic("software/python"));
+        env.build_index().expect("Should build index");
+
+        let output = env.cmd().topics().args(["--tree"]).output_success();
+        let expected = [
+            "software (0/3)",
+            "├── python (1/1)",
+            "└── rust (1/2)",
+            "    └── async (1/1)",
+            "",
+        ];
+        assert_eq!(output, expected.join("\n"));
+
+        let output = env
+            .cmd()
+            .topics()
+            .args(["--tree", "--depth", "1"])
+            .output_success();
+        assert_eq!(output, "software (0/3)\n");
+    }
+
+    #[test]
+    fn test_topics_tree_json_is_nested() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Note").topic("software/rust"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .topics()
+            .args(["--tree"])
+            .format_json()
+            .output_json();
+        let software = &json["data"][0];
+        assert_eq!(software["path"], "software");
+        assert_eq!(software["count"], 0);
+        assert_eq!(software["total"], 1);
+        assert_eq!(software["children"][0]["path"], "software/rust");
+        assert_eq!(software["children"][0]["count"], 1);
+    }
+
+    #[test]
+    fn test_topics_stats_reports_subtree() {
+        let env = TestEnv::new();
+        let rest = TestNote::new("REST Principles")
+            .topic("software/api")
+            .tag("draft");
+        let api = TestNote::new("API Design")
+            .topic("software/api")
+            .tag("draft")
+            .link(rest.note_id().to_string(), &["parent"]);
+        env.add_note(&rest);
+        env.add_note(&api);
+        env.add_note(&TestNote::new("Schema").topic("software/db"));
+        env.add_note(&TestNote::new("Calculus").topic("science/math"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .topics()
+            .args(["stats", "software/"])
+            .format_json()
+            .output_json();
+        let data = &json["data"];
+        assert_eq!(data["topic"], "software");
+        assert_eq!(data["notes"], 3);
+        assert_eq!(data["direct"], 0);
+        assert_eq!(data["orphans"], 1);
+        assert_eq!(data["top_tags"][0]["name"], "draft");
+        assert_eq!(data["top_tags"][0]["count"], 2);
+        assert_eq!(data["most_linked"][0]["title"], "REST Principles");
+        assert_eq!(data["most_linked"][0]["backlinks"], 1);
+        assert_eq!(data["subtopics"][0]["path"], "software/api");
+        assert_eq!(data["subtopics"][0]["notes"], 2);
+        assert!(data["balance"].is_number());
+
+        let stdout = env
+            .cmd()
+            .topics()
+            .args(["stats", "software"])
+            .output_success();
+        assert!(stdout.contains("Topic software: 3 note(s), 0 filed directly"));
+        assert!(stdout.contains("Orphans (no links): 1"));
+        assert!(stdout.contains("software/db"));
+        assert!(!stdout.contains("Calculus"));
+    }
+
+    #[test]
+    fn test_topics_stats_unknown_topic_fails() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Calculus").topic("science/math"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .topics()
+            .args(["stats", "software"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("topic not found: 'software'"));
+    }
+
+    #[test]
+    fn test_topics_mv_moves_subtopics_and_reindexes() {
+        let env = TestEnv::new();
+        let api = env.add_note(&TestNote::new("API Design").topic("software/api"));
+        env.add_note(&TestNote::new("Software Map").topic("software"));
+        env.add_note(&TestNote::new("Calculus").topic("science/math"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .topics()
+            .args(["mv", "software/", "tech", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Would move 'software/' to 'tech' in 2 note(s)",
+            ));
+        assert!(
+            std::fs::read_to_string(&api)
+                .unwrap()
+                .contains("software/api")
+        );
+
+        env.cmd()
+            .topics()
+            .args(["mv", "software/", "tech"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Moved 'software/' to 'tech' in 2 note(s)",
+            ));
+
+        let content = std::fs::read_to_string(&api).unwrap();
+        assert!(content.contains("tech/api"));
+        assert!(!content.contains("software"));
+        let topics = env.cmd().topics().output_success();
+        assert!(topics.contains("tech/api"));
+        assert!(!topics.contains("software"));
+        assert!(topics.contains("science/math"));
+    }
+
+    #[test]
+    fn test_topics_mv_without_slash_keeps_subtopics() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("API Design").topic("software/api"));
+        env.add_note(&TestNote::new("Software Map").topic("software"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .topics()
+            .args(["mv", "software", "tech"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("in 1 note(s)"));
+
+        let topics = env.cmd().topics().output_success();
+        assert!(topics.contains("software/api"));
+        assert!(topics.contains("tech"));
+    }
+}
+
+// ===========================================
+// tags command tests
+// ===========================================
+mod tags_tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_lists_all() {
+        let env = TestEnv::new();
+
+        let note1 = TestNote::new("Note 1").tag("rust").tag("cli");
+        let note2 = TestNote::new("Note 2").tag("python").tag("web");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("rust"))
+            .stdout(predicate::str::contains("cli"))
+            .stdout(predicate::str::contains("python"))
+            .stdout(predicate::str::contains("web"));
+    }
+
+    #[test]
+    fn test_tags_with_counts() {
+        let env = TestEnv::new();
+
+        let note1 = TestNote::new("Note 1").tag("rust");
+        let note2 = TestNote::new("Note 2").tag("rust");
+        let note3 = TestNote::new("Note 3").tag("python");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.add_note(&note3);
+        env.build_index().expect("Should build index");
+
+        let output = env.cmd().tags().with_counts().output_success();
+
+        // Should show counts (rust has 2, python has 1)
+        assert!(
+            output.contains('2') || output.contains('1'),
+            "Output should include counts"
+        );
+    }
+
+    #[test]
+    fn test_tags_format_json() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Note").tag("test-tag");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().tags().format_json().output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert!(data.is_array(), "data should be an array");
+    }
+
+    #[test]
+    fn test_tags_empty() {
+        let env = TestEnv::new();
+
+        // Add note without tags
+        let note = TestNote::new("No Tag Note");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty().or(predicate::str::contains("No tags")));
+    }
+
+    #[test]
+    fn test_tags_expire_lists_then_removes_expired_tags() {
+        let env = TestEnv::new();
+        let path = env.add_note(
+            &TestNote::new("Quarterly Plan")
+                .tag("review-by:2020-01-01")
+                .tag("plan"),
+        );
+        env.add_note(&TestNote::new("Later").tag("review-by:2999-01-01"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["check"])
+            .assert()
+            .stdout(predicate::str::contains(
+                "tag 'review-by:2020-01-01' expired on 2020-01-01",
+            ));
+
+        env.cmd()
+            .tags()
+            .args(["expire"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "review-by:2020-01-01 (expired 2020-01-01) would remove",
+            ))
+            .stdout(predicate::str::contains("1 expired tag(s) in 1 note(s)"));
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("review-by:2020-01-01")
+        );
+
+        env.cmd()
+            .tags()
+            .args(["expire", "--apply"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Expired 1 tag(s) in 1 note(s)"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("review-by"));
+        assert!(content.contains("plan"));
+
+        let tags = env.cmd().tags().output_success();
+        assert!(!tags.contains("review-by:2020-01-01"));
+        assert!(tags.contains("review-by:2999-01-01"));
+    }
+
+    #[test]
+    fn test_tags_mv_renames_and_dedupes() {
+        let env = TestEnv::new();
+        let both = env.add_note(&TestNote::new("Both").tag("todo").tag("task"));
+        env.add_note(&TestNote::new("Old").tag("todo"));
+        env.add_note(&TestNote::new("Other").tag("rust"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tags()
+            .args(["mv", "todo", "task"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Retagged 2 note(s): 'todo' -> 'task'",
+            ));
+
+        let content = std::fs::read_to_string(&both).unwrap();
+        assert!(!content.contains("todo"));
+        assert_eq!(content.matches("task").count(), 1);
+
+        let json: serde_json::Value = env.cmd().tags().with_counts().format_json().output_json();
+        let names: Vec<&str> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(!names.contains(&"todo"));
+        assert!(names.contains(&"task"));
+    }
+
+    #[test]
+    fn test_tags_merge_folds_tags_into_one() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("One").tag("todo"));
+        env.add_note(&TestNote::new("Two").tag("to-do"));
+        env.add_note(&TestNote::new("Three").tag("task"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tags()
+            .args(["merge", "todo", "to-do", "task", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Would retag 2 note(s): 'todo', 'to-do' -> 'task'",
+            ));
+
+        env.cmd()
+            .tags()
+            .args(["merge", "todo", "to-do", "task"])
+            .assert()
+            .success();
+        let tags = env.cmd().tags().output_success();
+        assert!(tags.contains("task"));
+        assert!(!tags.contains("todo"));
+        assert!(!tags.contains("to-do"));
+    }
+}
+
+// ===========================================
+// tag command (add tag) tests
+// ===========================================
+mod tag_add_tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_adds_to_note() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Taggable Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tag_add("01HQ3K5M7N", "new-tag")
+            .assert()
+            .success();
+
+        // Verify tag was added
+        env.cmd()
+            .ls()
+            .with_tag("new-tag")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Taggable Note"));
+    }
+
+    #[test]
+    fn test_tag_updates_index() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Tag Index Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tag_add("01HQ3K5M7N", "indexed-tag")
+            .assert()
+            .success();
+
+        // Tag should appear in tags list
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("indexed-tag"));
+    }
+
+    #[test]
+    fn test_tag_duplicate_noop() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Already Tagged")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("existing");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Adding same tag again should succeed (idempotent)
+        env.cmd()
+            .tag_add("01HQ3K5M7N", "existing")
+            .assert()
+            .success();
+    }
+
+    #[test]
+    fn test_tag_not_found() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .tag_add("nonexistent", "some-tag")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found").or(predicate::str::contains("No note")));
+    }
+
+    fn query_env() -> TestEnv {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Kubernetes Deploy"));
+        env.add_note(&TestNote::new("Kubernetes Networking").tag("k8s"));
+        env.add_note(&TestNote::new("Old Kubernetes Setup").tag("archived"));
+        env.add_note(&TestNote::new("Unrelated"));
+        env.build_index().expect("Should build index");
+        env
+    }
+
+    #[test]
+    fn test_tag_query_dry_run_changes_nothing() {
+        let env = query_env();
+
+        env.cmd()
+            .args(["tag", "--query", "kubernetes", "k8s", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would tag 1 note(s) with 'k8s'"))
+            .stdout(predicate::str::contains("Kubernetes Deploy"))
+            .stdout(predicate::str::contains(
+                "1 matching note(s) already tagged",
+            ));
+
+        env.cmd()
+            .ls()
+            .with_tag("k8s")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Kubernetes Deploy").not());
+    }
+
+    #[test]
+    fn test_tag_query_tags_matching_notes() {
+        let env = query_env();
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["tag", "--query", "kubernetes", "k8s"])
+            .format_json()
+            .output_json();
+
+        let results = output["data"].as_array().expect("data should be an array");
+        assert_eq!(results.len(), 2, "archived note is skipped");
+
+        let tagged = env.cmd().ls().with_tag("k8s").output_success();
+        assert!(tagged.contains("Kubernetes Deploy"));
+        assert!(tagged.contains("Kubernetes Networking"));
+        assert!(!tagged.contains("Unrelated"));
+    }
+
+    #[test]
+    fn test_tag_query_rejects_second_positional() {
+        let env = query_env();
+
+        env.cmd()
+            .args(["tag", "--query", "kubernetes", "note", "k8s"])
+            .assert()
+            .failure();
+    }
+
+    #[test]
+    fn test_tag_reads_notes_from_stdin() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("First").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y"));
+        env.add_note(
+            &TestNote::new("Second")
+                .id("01HQ4A2R9PXJK4QZPW8V2R6T9Y")
+                .tag("review"),
+        );
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["tag", "-", "review"])
+            .stdin("01HQ3K5M7N\n\nSecond\n")
+            .format_json()
+            .output_json();
+
+        let results = output["data"].as_array().expect("data should be an array");
+        let changed: Vec<bool> = results
+            .iter()
+            .map(|r| r["changed"].as_bool().unwrap())
+            .collect();
+        assert_eq!(changed, vec![true, false]);
+
+        let tagged = env.cmd().ls().with_tag("review").output_success();
+        assert!(tagged.contains("First"));
+        assert!(tagged.contains("Second"));
+    }
+}
+
+// ===========================================
+// untag command tests
+// ===========================================
+mod untag_tests {
+    use super::*;
+
+    #[test]
+    fn test_untag_removes_from_note() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Has Tag")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("removable");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .untag("01HQ3K5M7N", "removable")
+            .assert()
+            .success();
+
+        // Note should no longer have the tag
+        env.cmd()
+            .ls()
+            .with_tag("removable")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Has Tag").not());
+    }
+
+    #[test]
+    fn test_untag_updates_index() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Only Tagged")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("sole-tag");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd().untag("01HQ3K5M7N", "sole-tag").assert().success();
+
+        // Tag should disappear from tags list if no other notes have it
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("sole-tag").not());
+    }
+
+    #[test]
+    fn test_untag_missing_noop() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("No Such Tag").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Removing nonexistent tag should succeed gracefully
+        env.cmd()
+            .untag("01HQ3K5M7N", "nonexistent-tag")
+            .assert()
+            .success();
+    }
+}
+
+// ===========================================
+// recent command tests
+// ===========================================
+mod recent_tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn env_with_two_sessions() -> TestEnv {
+        let env = TestEnv::new();
+        let now = Utc::now();
+        for (title, id, ago) in [
+            (
+                "Morning Plan",
+                "01HQ3K5M7NXJK4QZPW8V2R6T9A",
+                Duration::hours(5),
+            ),
+            ("Draft", "01HQ3K5M7NXJK4QZPW8V2R6T9B", Duration::minutes(40)),
+            (
+                "Review",
+                "01HQ3K5M7NXJK4QZPW8V2R6T9C",
+                Duration::minutes(10),
+            ),
+        ] {
+            env.add_note(
+                &TestNote::new(title)
+                    .id(id)
+                    .created(now - Duration::days(1))
+                    .modified(now - ago),
+            );
+        }
+        env.build_index().expect("Should build index");
+        env
+    }
+
+    #[test]
+    fn test_recent_lists_newest_first() {
+        let env = env_with_two_sessions();
+
+        let json: serde_json::Value = env.cmd().recent().format_json().output_json();
+        let titles: Vec<&str> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, ["Review", "Draft", "Morning Plan"]);
+
+        let json: serde_json::Value = env
+            .cmd()
+            .recent()
+            .args(["-n", "1"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_recent_sessions_and_summaries() {
+        let env = env_with_two_sessions();
+
+        env.cmd()
+            .recent()
+            .args(["--sessions"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("(2 note(s))"))
+            .stdout(predicate::str::contains("(1 note(s))"))
+            .stdout(predicate::str::contains("2 session(s)"));
+
+        let json: serde_json::Value = env
+            .cmd()
+            .recent()
+            .args(["--sessions", "--summarize"])
+            .format_json()
+            .output_json();
+        let sessions = json["data"].as_array().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0]["notes"][0]["title"], "Review");
+        assert_eq!(sessions[0]["notes"][1]["title"], "Draft");
+        let summary = sessions[0]["summary"].as_str().unwrap();
+        let content = std::fs::read_to_string(summary).unwrap();
+        assert!(content.contains("- session"), "{}", content);
+        assert!(content.contains("[Draft](01HQ3K5M7N)"), "{}", content);
+
+        // Summaries don't form sessions of their own and aren't written twice
+        env.cmd().index().assert().success();
+        let json: serde_json::Value = env
+            .cmd()
+            .recent()
+            .args(["--sessions", "--summarize"])
+            .format_json()
+            .output_json();
+        let sessions = json["data"].as_array().unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert!(sessions.iter().all(|s| s.get("summary").is_none()));
+
+        // A smaller gap splits the first session
+        let json: serde_json::Value = env
+            .cmd()
+            .recent()
+            .args(["--sessions", "--gap", "15"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"].as_array().unwrap().len(), 3);
+    }
+}
+
+// ===========================================
+// desc command tests
+// ===========================================
+mod desc_tests {
+    use super::*;
+
+    #[test]
+    fn test_desc_shows_in_ls_columns() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Described").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .desc("01HQ3K5M7N", "A one-line summary")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Set description of 'Described'"));
+
+        env.cmd()
+            .ls()
+            .args(["--columns", "description,created"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Description"))
+            .stdout(predicate::str::contains("Created"))
+            .stdout(predicate::str::contains("A one-line summary"));
+
+        let json: serde_json::Value = env
+            .cmd()
+            .ls()
+            .args(["--columns", "description"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"][0]["description"], "A one-line summary");
+
+        // Without the column the description stays out of the listing
+        let json: serde_json::Value = env.cmd().ls().format_json().output_json();
+        assert!(json["data"][0].get("description").is_none());
+    }
+
+    #[test]
+    fn test_desc_clear() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Summarized")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .description("Old summary");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["desc", "Summarized", "--clear"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["changed"], true);
+        assert!(json["data"]["description"].is_null());
+
+        env.cmd()
+            .args(["desc", "Summarized", "--clear"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("has no description"));
+
+        env.cmd()
+            .args(["desc", "Summarized", "New", "--clear"])
+            .assert()
+            .failure();
+    }
+}
+
+mod largest_tests {
+    use super::*;
+
+    #[test]
+    fn test_ls_largest_sorts_by_word_count() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Short").body("just a few words"));
+        env.add_note(&TestNote::new("Long").body("word ".repeat(50)));
+        env.add_note(&TestNote::new("Medium").body("word ".repeat(10)));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .ls()
+            .args(["--largest"])
+            .format_json()
+            .output_json();
+        let listed: Vec<(&str, u64)> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| (n["title"].as_str().unwrap(), n["words"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(listed, vec![("Long", 50), ("Medium", 10), ("Short", 4)]);
+
+        env.cmd()
+            .ls()
+            .args(["--largest"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Words"));
+
+        // Word counts only show up with --largest
+        let json: serde_json::Value = env.cmd().ls().format_json().output_json();
+        assert!(json["data"][0].get("words").is_none());
+    }
+}
+
+// ===========================================
+// check command tests
+// ===========================================
+mod check_tests {
+    use super::*;
+
+    #[test]
+    fn test_check_clean() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Clean Note").topic("software");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd().check().assert().success();
+    }
+
+    #[test]
+    fn test_check_broken_links() {
+        let env = TestEnv::new();
+
+        // Note linking to nonexistent target
+        let note = TestNote::new("Broken Link Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HZZZZZZZXJK4QZPW8V2R6T9Y", &["see-also"]);
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Check command reports errors with non-zero exit and "broken" in output
+        env.cmd()
+            .check()
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("broken"));
+    }
+
+    #[test]
+    fn test_check_duplicate_ids() {
+        let env = TestEnv::new();
+
+        // Two notes with same ID (unusual case)
+        let note1 = TestNote::new("First Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note2 = TestNote::new("Second Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+
+        env.add_note(&note1);
+        // Adding second note with same ID creates a different file
+        let _path2 = env.add_note(&note2);
+
+        env.build_index().expect("Should build index");
+
+        // Check should report duplicate IDs with non-zero exit
+        env.cmd()
+            .check()
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("duplicate"));
+    }
+
+    #[test]
+    fn test_check_orphaned_notes() {
+        let env = TestEnv::new();
+
+        // Note without any topic (orphaned in terms of hierarchy)
+        let note = TestNote::new("Orphan Note");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Check should succeed even with orphaned notes
+        env.cmd().check().assert().success();
+    }
+
+    #[test]
+    fn test_check_links_to_trashed_notes() {
+        let env = TestEnv::new();
+
+        let gone = env.add_note(
+            &TestNote::new("Gone Note")
+                .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+                .topic("software"),
+        );
+        std::fs::create_dir(env.notes_dir().join(".trash")).unwrap();
+        std::fs::rename(&gone, env.notes_dir().join(".trash/gone-note.md")).unwrap();
+        env.add_note(
+            &TestNote::new("Source")
+                .topic("software")
+                .link("01HQ5B3S0QYJK5RAQX9W3S7T0Z", &["see-also"]),
+        );
+
+        env.cmd()
+            .check()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("link to trashed note"))
+            .stdout(predicate::str::contains("Trash:").not());
+
+        let output: serde_json::Value = env
+            .cmd()
+            .check()
+            .args(["--include-trash"])
+            .format_json()
+            .output_json();
+        assert_eq!(output["data"]["ok"], true);
+        assert_eq!(output["data"]["notes"], 1);
+        assert_eq!(output["data"]["issues"][0]["kind"], "trashed-link");
+        assert_eq!(output["data"]["issues"][0]["severity"], "warning");
+        assert_eq!(output["data"]["trash"][0]["title"], "Gone Note");
+    }
+
+    #[test]
+    fn test_check_timestamp_anomalies() {
+        use chrono::{Duration, Utc};
+
+        let env = TestEnv::new();
+        let now = Utc::now();
+        env.add_note(
+            &TestNote::new("Backwards")
+                .topic("software")
+                .created(now - Duration::hours(1))
+                .modified(now - Duration::hours(2)),
+        );
+        env.add_note(
+            &TestNote::new("Ahead")
+                .topic("software")
+                .modified(now + Duration::days(30)),
+        );
+        // Written now, but frontmatter says it was last modified weeks ago
+        env.add_note(
+            &TestNote::new("Stale")
+                .topic("software")
+                .created(now - Duration::days(30))
+                .modified(now - Duration::days(30)),
+        );
+
+        let output: serde_json::Value = env.cmd().check().format_json().output_json();
+
+        assert_eq!(output["data"]["ok"], true);
+        let mut kinds: Vec<&str> = output["data"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["kind"].as_str().unwrap())
+            .collect();
+        kinds.sort();
+        assert_eq!(
+            kinds,
+            vec![
+                "created-after-modified",
+                "external-edit",
+                "future-timestamp"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_markdown_syntax_in_body() {
+        let env = TestEnv::new();
+        env.write_file(
+            "01HQ3K5M7N-markdown.md",
+            "---\n\
+             id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\n\
+             title: Markdown\n\
+             created: 2024-01-15T10:30:00Z\n\
+             modified: 2024-01-15T10:30:00Z\n\
+             topics:\n\
+             - software\n\
+             ---\n\
+             See [the spec][spec].\n\
+             \n\
+             | A | B |\n\
+             | - |\n\
+             \n\
+             ```rust\n\
+             fn main() {}\n",
+        );
+
+        let output: serde_json::Value = env.cmd().check().format_json().output_json();
+
+        assert_eq!(output["data"]["ok"], true);
+        let issues: Vec<(&str, u64)> = output["data"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            // Body issues; the old modified time also shows up as an external edit
+            .filter_map(|i| Some((i["kind"].as_str().unwrap(), i["line"].as_u64()?)))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("undefined-reference", 9),
+                ("malformed-table", 11),
+                ("unclosed-fence", 14)
+            ]
+        );
+
+        env.cmd()
+            .check()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "01HQ3K5M7N-markdown.md:14: code fence '```' is never closed",
+            ));
+    }
+
+    #[test]
+    fn test_check_warns_about_large_notes() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Small").topic("software"));
+        env.add_note(
+            &TestNote::new("Big")
+                .topic("software")
+                .body("word ".repeat(3001)),
+        );
+
+        let output: serde_json::Value = env.cmd().check().format_json().output_json();
+
+        assert_eq!(output["data"]["ok"], true);
+        let large: Vec<&str> = output["data"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|i| i["kind"] == "large-note")
+            .map(|i| i["message"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            large,
+            vec!["note has 3001 words (limit 3000); consider splitting it"]
+        );
+    }
+
+    #[test]
+    fn test_check_drift_reports_and_fixes_index_drift() {
+        let env = TestEnv::new();
+        let kept = env.add_note(&TestNote::new("Kept").topic("software"));
+        let gone = env.add_note(&TestNote::new("Gone").topic("software"));
+        env.cmd().index().assert().success();
+
+        let content = std::fs::read_to_string(&kept).unwrap();
+        std::fs::write(&kept, content.replace("Kept", "Renamed")).unwrap();
+        std::fs::remove_file(&gone).unwrap();
+
+        let output: serde_json::Value = env
+            .cmd()
+            .check()
+            .args(["--drift"])
+            .format_json()
+            .output_json();
+        let drift: Vec<(&str, &str)> = output["data"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| (i["kind"].as_str().unwrap(), i["message"].as_str().unwrap()))
+            .collect();
+        assert_eq!(output["data"]["ok"], true);
+        assert!(drift.contains(&(
+            "stale-index",
+            "file changed since it was indexed (index has old title)"
+        )));
+        assert!(drift.iter().any(|(kind, _)| *kind == "orphan-index-row"));
+
+        env.cmd()
+            .check()
+            .args(["--drift", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Re-indexed 2 note(s) that drifted from the index.",
+            ));
+        env.cmd()
+            .check()
+            .args(["--drift"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("All notes OK."));
+    }
+
+    #[test]
+    fn test_check_fix_reconciles_modified_from_mtime() {
+        use chrono::{DateTime, Duration, Utc};
+
+        let env = TestEnv::new();
+        let now = Utc::now();
+        let path = env.add_note(
+            &TestNote::new("Stale")
+                .topic("software")
+                .created(now - Duration::days(30))
+                .modified(now - Duration::days(30)),
+        );
+        let mtime: DateTime<Utc> = std::fs::metadata(&path).unwrap().modified().unwrap().into();
+
+        env.cmd()
+            .check()
+            .args(["--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Reconciled 1 modified time(s)"))
+            .stdout(predicate::str::contains("All notes OK."));
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let modified = contents
+            .lines()
+            .find_map(|l| l.strip_prefix("modified: "))
+            .unwrap();
+        let modified = DateTime::parse_from_rfc3339(modified.trim_matches(['\'', '"'])).unwrap();
+        assert!((mtime - modified.with_timezone(&Utc)).num_seconds().abs() <= 1);
+
+        // The rewrite keeps the reconciled mtime, so a second check is clean
+        env.cmd()
+            .check()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("All notes OK."));
+    }
+
+    #[test]
+    fn test_check_warns_about_unresolved_wikilinks() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("API Design").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
+        env.add_note(
+            &TestNote::new("Standup")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("See [[API Design]], [[01HQ4A2R|the API]] and [[Roadmap]].\n"),
+        );
+
+        let output = env.cmd().args(["check", "-f", "json"]).output_success();
+        let result: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let issues: Vec<&serde_json::Value> = result["data"]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|issue| issue["kind"] == "broken-wikilink")
+            .collect();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0]["severity"], "warning");
+        assert!(
+            issues[0]["message"]
+                .as_str()
+                .unwrap()
+                .contains("'[[Roadmap]]' matches no note")
+        );
+    }
+}
+
+// ===========================================
+// stats command tests
+// ===========================================
+mod stats_tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_reports_health() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Tidy").topic("rust").tag("draft"));
+        env.add_note(&TestNote::new("Loose"));
+
+        let json: serde_json::Value = env.cmd().args(["stats"]).format_json().output_json();
+
+        assert_eq!(json["data"]["notes"], 2);
+        assert_eq!(json["data"]["orphans"], 1);
+        assert_eq!(json["data"]["untagged"], 1);
+        assert_eq!(json["data"]["score"], 75.0);
+        assert!(json["data"].get("trend").is_none());
+    }
+
+    #[test]
+    fn test_stats_trend_includes_todays_sample() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Only").topic("rust"));
+
+        env.cmd().args(["stats"]).assert().success();
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["stats", "--trend"])
+            .format_json()
+            .output_json();
+
+        // Repeated runs on the same day keep a single sample
+        let trend = json["data"]["trend"].as_array().unwrap();
+        assert_eq!(trend.len(), 1);
+        assert_eq!(trend[0]["notes"], 1);
+
+        env.cmd()
+            .args(["stats", "--trend"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Vault health: 75.0/100"))
+            .stdout(predicate::str::contains("Trend:"));
+    }
+
+    #[test]
+    fn test_stats_tags_monthly() {
+        use chrono::{TimeZone, Utc};
+
+        let env = TestEnv::new();
+        let jan = Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap();
+        let mar = Utc.with_ymd_and_hms(2024, 3, 10, 12, 0, 0).unwrap();
+        env.add_note(&TestNote::new("Old").created(jan).tag("rust"));
+        env.add_note(&TestNote::new("New").created(mar).tag("rust").tag("go"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["--utc", "stats", "tags", "--monthly"])
+            .format_json()
+            .output_json();
+
+        assert_eq!(
+            json["data"]["months"],
+            serde_json::json!(["2024-01", "2024-02", "2024-03"])
+        );
+        assert_eq!(json["data"]["items"][0]["name"], "rust");
+        assert_eq!(
+            json["data"]["items"][0]["monthly"],
+            serde_json::json!([1, 0, 1])
+        );
+        assert_eq!(json["data"]["items"][1]["name"], "go");
+        assert_eq!(json["data"]["items"][1]["first_month"], "2024-03");
+
+        env.cmd()
+            .args(["stats", "topics"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No topics found."));
+    }
+
+    #[test]
+    fn test_stats_overview() {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Hub")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .topic("rust")
+                .tag("draft"),
+        );
+        env.add_note(
+            &TestNote::new("Spoke")
+                .topic("rust")
+                .link("01HQ3K5M7NXJK4QZPW8V2R6T9Y", &["parent"])
+                .body("Four words in here."),
+        );
+        env.add_note(&TestNote::new("Alone"));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["stats", "overview"])
+            .format_json()
+            .output_json();
+
+        assert_eq!(json["data"]["notes"], 3);
+        assert_eq!(json["data"]["unfiled"], 1);
+        assert_eq!(json["data"]["untagged"], 2);
+        assert_eq!(json["data"]["orphans"], 1);
+        assert_eq!(json["data"]["top_topics"][0]["path"], "rust");
+        assert_eq!(json["data"]["top_topics"][0]["count"], 2);
+        assert_eq!(json["data"]["rels"][0]["name"], "parent");
+        assert_eq!(json["data"]["most_linked"][0]["title"], "Hub");
+        assert_eq!(json["data"]["most_linked"][0]["backlinks"], 1);
+
+        env.cmd()
+            .args(["stats", "overview"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Links per rel:"))
+            .stdout(predicate::str::contains("Most linked:"));
+    }
+}
+
+// ===========================================
+// keywords command tests
+// ===========================================
+mod keywords_tests {
+    use super::*;
+
+    #[test]
+    fn test_keywords_ranks_distinctive_terms() {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Rust Ownership")
+                .tag("ownership")
+                .body("Ownership and borrowing: the borrow checker enforces ownership in Rust."),
+        );
+        env.add_note(&TestNote::new("Rust Traits").body("Traits in Rust define behaviour."));
+        env.add_note(&TestNote::new("Rust Macros").body("Macros in Rust generate code."));
+        env.build_index().expect("Should build index");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["keywords", "Rust Ownership", "--limit", "3"])
+            .format_json()
+            .output_json();
+
+        let keywords = json["data"].as_array().unwrap();
+        assert_eq!(keywords.len(), 3);
+        assert_eq!(keywords[0]["term"], "ownership");
+        assert_eq!(keywords[0]["count"], 3);
+        assert_eq!(keywords[0]["tagged"], true);
+        assert!(keywords.iter().all(|k| k["term"] != "rust"));
+    }
+
+    #[test]
+    fn test_keywords_human_output() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Gardening").body("Tomatoes need sun."));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["keywords", "Gardening"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Keywords for 'Gardening'"))
+            .stdout(predicate::str::contains("tomatoes"))
+            .stdout(predicate::str::contains("Compared against 1 note(s)"));
+    }
+
+    #[test]
+    fn test_keywords_unknown_note_fails() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["keywords", "Nope"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("note not found"));
+    }
+}
+
+// ===========================================
+// backlinks command tests
+// ===========================================
+mod backlinks_tests {
+    use super::*;
+
+    #[test]
+    fn test_backlinks_finds_linking_notes() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Source Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["see-also"]);
+        env.add_note(&target);
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Source Note"));
+    }
+
+    #[test]
+    fn test_backlinks_with_rel_filter() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let parent = TestNote::new("Parent Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
+        let sibling = TestNote::new("Sibling Note")
+            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["sibling"]);
+        env.add_note(&target);
+        env.add_note(&parent);
+        env.add_note(&sibling);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .with_rel("parent")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Parent Note"))
+            .stdout(predicate::str::contains("Sibling Note").not());
+    }
+
+    #[test]
+    fn test_backlinks_format_json() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("JSON Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("JSON Source")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["related"]);
+        env.add_note(&target);
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .backlinks("01HQ4A2R9P")
+            .format_json()
+            .output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+    }
+
+    #[test]
+    fn test_backlinks_empty() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("No Backlinks").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty().or(predicate::str::contains("No backlinks")));
+    }
+
+    #[test]
+    fn test_backlinks_mentions_finds_unlinked_mentions() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["person", "new", "Jane Doe"])
+            .assert()
+            .success();
+
+        env.add_note(
+            &TestNote::new("Standup")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("Talked to @jane-doe about the release.\n"),
+        );
+        env.add_note(
+            &TestNote::new("Email Thread")
+                .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+                .body("Wrote to jane-doe@example.com.\n"),
+        );
+        env.cmd().index().assert().success();
+
+        env.cmd()
+            .backlinks("Jane Doe")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No backlinks"));
+
+        let json: serde_json::Value = env
+            .cmd()
+            .backlinks("jane-doe")
+            .args(["--mentions"])
+            .format_json()
+            .output_json();
+        let titles: Vec<&str> = json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["title"].as_str().unwrap())
+            .collect();
+        assert_eq!(titles, vec!["Standup"]);
+    }
+
+    #[test]
+    fn test_backlinks_recursive_nests_linkers() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Tree Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let direct = TestNote::new("Direct Linker")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["related"]);
+        let indirect = TestNote::new("Indirect Linker")
+            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+            .link("01HQ3K5M7NXJK4QZPW8V2R6T9Y", &["related"]);
+        env.add_note(&target);
+        env.add_note(&direct);
+        env.add_note(&indirect);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .backlinks("01HQ4A2R9P")
+            .args(["--recursive"])
+            .format_json()
+            .output_json();
+
+        let root = &output["data"];
+        assert_eq!(root["title"], "Tree Target");
+        assert_eq!(root["children"][0]["title"], "Direct Linker");
+        assert_eq!(
+            root["children"][0]["children"][0]["title"],
+            "Indirect Linker"
+        );
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .args(["--recursive", "--depth", "1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("└── Direct Linker"))
+            .stdout(predicate::str::contains("Indirect Linker").not());
+    }
+
+    #[test]
+    fn test_backlinks_include_wikilinks_as_mentions() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("API Design").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
+        env.add_note(
+            &TestNote::new("Standup")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("Talked about [[api design|the API]].\n"),
+        );
+        env.add_note(
+            &TestNote::new("Review")
+                .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+                .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]),
+        );
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .backlinks("API Design")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Standup"))
+            .stdout(predicate::str::contains("Review"));
+        env.cmd()
+            .backlinks("API Design")
+            .args(["--rel", "mentions"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Standup"))
+            .stdout(predicate::str::contains("Review").not());
+    }
+}
+
+// ===========================================
+// link command tests
+// ===========================================
+mod link_tests {
+    use super::*;
+
+    #[test]
+    fn test_link_reads_sources_from_stdin() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("First Source"));
+        env.add_note(&TestNote::new("Second Source"));
+        env.add_note(&TestNote::new("Hub").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["link", "-", "Hub", "--rel", "parent"])
+            .stdin("First Source\nSecond Source\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Added link: 'First Source'"))
+            .stdout(predicate::str::contains(
+                "2 note(s): 2 changed, 0 unchanged, 0 failed",
+            ));
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("First Source"))
+            .stdout(predicate::str::contains("Second Source"));
+    }
+
+    #[test]
+    fn test_link_creates_link() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Link Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("Link Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .link("01HQ3K5M7N", "01HQ4A2R9P")
+            .with_rel("see-also")
+            .assert()
+            .success();
+
+        // Verify link appears in backlinks
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Link Source"));
+    }
+
+    #[test]
+    fn test_link_with_rel() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Rel Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("Rel Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .link("01HQ3K5M7N", "01HQ4A2R9P")
+            .with_rel("parent")
+            .assert()
+            .success();
+
+        // Verify link with specific rel
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .with_rel("parent")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rel Source"));
+    }
+
+    #[test]
+    fn test_link_with_note() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Context Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("Context Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .link("01HQ3K5M7N", "01HQ4A2R9P")
+            .with_rel("related")
+            .with_note("Important connection")
+            .assert()
+            .success();
+    }
+
+    #[test]
+    fn test_link_updates_index() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Index Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("Index Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .link("01HQ3K5M7N", "01HQ4A2R9P")
+            .with_rel("related")
+            .assert()
+            .success();
+
+        // Link should be immediately visible in backlinks (index updated)
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Index Source"));
+    }
+
+    #[test]
+    fn test_link_target_not_found() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Lonely Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        // Linking to nonexistent target should warn or fail
+        env.cmd()
+            .link("01HQ3K5M7N", "nonexistent-id")
+            .with_rel("broken")
+            .assert()
+            .failure();
+    }
+
+    #[test]
+    fn test_link_picks_omitted_target_interactively() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("Picked Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("Picked Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["-i", "link", "01HQ3K5M7N", "--rel", "see-also"])
+            .stdin("target\n\n")
+            .assert()
+            .success();
+
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Picked Source"));
+    }
+
+    #[test]
+    fn test_link_check_reports_inbound_references() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Doomed").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y"));
+        env.add_note(
+            &TestNote::new("Fan")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"])
+                .body("Built on [[Doomed]], see [it](01HQ4A2R9P)."),
+        );
+        env.add_note(&TestNote::new("Bystander"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["link", "check", "--target", "Doomed"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Frontmatter links (1):"))
+            .stdout(predicate::str::contains("Body references (2):"))
+            .stdout(predicate::str::contains("wikilink [[Doomed]]"))
+            .stdout(predicate::str::contains("Bystander").not());
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["link", "check", "--target", "Doomed"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["links"][0]["rels"][0], "parent");
+        assert_eq!(json["data"]["mentions"][1]["kind"], "link");
+
+        env.cmd()
+            .args(["link", "check", "--target", "Bystander"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Nothing references 'Bystander'"));
+    }
+}
+
+// ===========================================
+// unlink command tests
+// ===========================================
+mod unlink_tests {
+    use super::*;
+
+    #[test]
+    fn test_unlink_removes_link() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Unlink Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Unlink Source")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["removable"]);
+        env.add_note(&target);
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
+            .assert()
+            .success();
+
+        // Link should be removed from backlinks
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Unlink Source").not());
+    }
+
+    #[test]
+    fn test_unlink_updates_index() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Unindex Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Unindex Source")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["indexed"]);
+        env.add_note(&target);
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
+            .assert()
+            .success();
+
+        // Index should be updated immediately
+        env.cmd()
+            .backlinks("01HQ4A2R9P")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Unindex Source").not());
+    }
+
+    #[test]
+    fn test_unlink_missing_noop() {
+        let env = TestEnv::new();
+
+        let source = TestNote::new("No Link Source").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let target = TestNote::new("No Link Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&source);
+        env.add_note(&target);
+        env.build_index().expect("Should build index");
+
+        // Removing nonexistent link should succeed gracefully
+        env.cmd()
+            .unlink("01HQ3K5M7N", "01HQ4A2R9P")
+            .assert()
+            .success();
+    }
+}
+
+// ===========================================
+// rels command tests
+// ===========================================
+mod rels_tests {
+    use super::*;
+
+    #[test]
+    fn test_rels_lists_all() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source1 = TestNote::new("Source 1")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
+        let source2 = TestNote::new("Source 2")
+            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["see-also"]);
+        env.add_note(&target);
+        env.add_note(&source1);
+        env.add_note(&source2);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .rels()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("parent"))
+            .stdout(predicate::str::contains("see-also"));
+    }
+
+    #[test]
+    fn test_rels_with_counts() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source1 = TestNote::new("Source 1")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
+        let source2 = TestNote::new("Source 2")
+            .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["parent"]);
+        env.add_note(&target);
+        env.add_note(&source1);
+        env.add_note(&source2);
+        env.build_index().expect("Should build index");
+
+        let output = env.cmd().rels().with_counts().output_success();
+
+        // Parent should have count of 2
+        assert!(
+            output.contains('2'),
+            "Output should show count for parent relationship"
+        );
+    }
+
+    #[test]
+    fn test_rels_format_json() {
+        let env = TestEnv::new();
+
+        let target = TestNote::new("Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Source")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .link("01HQ4A2R9PXJK4QZPW8V2R6T9Y", &["related"]);
+        env.add_note(&target);
+        env.add_note(&source);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().rels().format_json().output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert!(data.is_array(), "data should be an array");
+    }
+
+    #[test]
+    fn test_rels_empty() {
+        let env = TestEnv::new();
+
+        // Notes without any links
+        let note = TestNote::new("No Links");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .rels()
+            .assert()
+            .success()
+            .stdout(predicate::str::is_empty().or(predicate::str::contains("No relationship")));
+    }
+}
+
+// ===========================================
+// Rels graph tests
+// ===========================================
+mod rels_graph_tests {
+    use super::*;
+
+    const ROOT: &str = "01HQ4A2R9PXJK4QZPW8V2R6T9Y";
+    const CHILD: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+    const GRANDCHILD: &str = "01HQ5B3S0QYJK5RAQX9W3S7T0Z";
+
+    fn setup_hierarchy(env: &TestEnv) {
+        env.add_note(&TestNote::new("Root").id(ROOT));
+        env.add_note(&TestNote::new("Child").id(CHILD).link(ROOT, &["parent"]));
+        env.add_note(
+            &TestNote::new("Grandchild")
+                .id(GRANDCHILD)
+                .link(CHILD, &["parent"]),
+        );
+        env.build_index().expect("Should build index");
+    }
+
+    #[test]
+    fn test_rels_graph_prints_tree() {
+        let env = TestEnv::new();
+        setup_hierarchy(&env);
+
+        let output = env.cmd().rels_graph().output_success();
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines[0].starts_with("Root"), "got: {}", output);
+        assert!(lines[1].starts_with("└── Child"), "got: {}", output);
+        assert!(
+            lines[2].starts_with("    └── Grandchild"),
+            "got: {}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_rels_graph_reports_cycles() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("A").id(ROOT).link(CHILD, &["parent"]));
+        env.add_note(&TestNote::new("B").id(CHILD).link(ROOT, &["parent"]));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .rels_graph()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("(cycle)"));
+    }
+
+    #[test]
+    fn test_rels_graph_json_nests_children() {
+        let env = TestEnv::new();
+        setup_hierarchy(&env);
+
+        let output: serde_json::Value = env.cmd().rels_graph().format_json().output_json();
+
+        let roots = output["data"].as_array().expect("data should be an array");
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0]["title"], "Root");
+        assert_eq!(roots[0]["children"][0]["title"], "Child");
+        assert_eq!(
+            roots[0]["children"][0]["children"][0]["title"],
+            "Grandchild"
+        );
+    }
+
+    #[test]
+    fn test_rels_graph_invert_uses_child_rel() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Root").id(ROOT).link(CHILD, &["child"]));
+        env.add_note(&TestNote::new("Child").id(CHILD));
+        env.build_index().expect("Should build index");
+
+        let output = env
+            .cmd()
+            .rels_graph()
+            .args(["--rel", "child", "--invert"])
+            .output_success();
+
+        assert!(output.starts_with("Root"), "got: {}", output);
+        assert!(output.contains("└── Child"), "got: {}", output);
+    }
+
+    #[test]
+    fn test_rels_graph_empty() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Lonely"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .rels_graph()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No 'parent' relationships found."));
+    }
+}
+
+// ===========================================
+// Rels export tests
+// ===========================================
+mod rels_export_tests {
+    use super::*;
+
+    const ROOT: &str = "01HQ4A2R9PXJK4QZPW8V2R6T9Y";
+    const CHILD: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+
+    fn setup(env: &TestEnv) {
+        env.add_note(&TestNote::new("Root").id(ROOT));
+        env.add_note(
+            &TestNote::new("Child")
+                .id(CHILD)
+                .link(ROOT, &["parent", "see-also"]),
+        );
+        env.build_index().expect("Should build index");
+    }
+
+    #[test]
+    fn test_rels_export_jsonld() {
+        let env = TestEnv::new();
+        setup(&env);
+
+        let output = env.cmd().args(["rels", "export"]).output_success();
+        let doc: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(doc["@context"]["note"], "urn:den:note:");
+        let graph = doc["@graph"].as_array().unwrap();
+        assert_eq!(graph[0]["@id"], "rel:parent");
+        assert_eq!(graph[1]["@id"], "rel:see-also");
+        assert_eq!(graph[2]["label"], "Child");
+        assert_eq!(graph[2]["rel:parent"][0]["@id"], format!("note:{}", ROOT));
+    }
+
+    #[test]
+    fn test_rels_export_graphml_to_file() {
+        let env = TestEnv::new();
+        setup(&env);
+        let out = env.notes_dir().join("out/rels.graphml");
+
+        env.cmd()
+            .args(["rels", "export", "-F", "graphml", "-o"])
+            .args([out.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Wrote 2 rel type(s) and 2 link(s) between 2 note(s)",
+            ));
+
+        let graphml = std::fs::read_to_string(&out).unwrap();
+        assert!(graphml.contains(&format!(
+            "<edge id=\"e0\" source=\"{}\" target=\"{}\"><data key=\"label\">parent</data>",
+            CHILD, ROOT
+        )));
+    }
+}
+
+// ===========================================
+// JSON output tests
+// ===========================================
+mod json_output_tests {
+    use super::*;
+
+    const SOURCE: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+    const TARGET: &str = "01HQ4A2R9PXJK4QZPW8V2R6T9Y";
+
+    fn setup_pair(env: &TestEnv) {
+        env.add_note(&TestNote::new("Source").id(SOURCE).topic("software"));
+        env.add_note(&TestNote::new("Target").id(TARGET).topic("software"));
+        env.build_index().expect("Should build index");
+    }
+
+    #[test]
+    fn test_new_json() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .new_note("JSON Note")
+            .args(["--tag", "draft"])
+            .format_json()
+            .output_json();
+
+        assert_eq!(output["data"]["title"], "JSON Note");
+        assert_eq!(output["data"]["tags"][0], "draft");
+        assert!(output["data"]["path"].as_str().unwrap().ends_with(".md"));
+        assert_eq!(output["data"]["id"].as_str().unwrap().len(), 26);
+    }
+
+    #[test]
+    fn test_show_json_includes_body_and_metadata() {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Shown")
+                .id(SOURCE)
+                .tag("rust")
+                .body("Hello body."),
+        );
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().show("Shown").format_json().output_json();
+
+        assert_eq!(output["data"]["id"], SOURCE);
+        assert_eq!(output["data"]["tags"][0], "rust");
+        assert!(
+            output["data"]["body"]
+                .as_str()
+                .unwrap()
+                .contains("Hello body.")
+        );
+    }
+
+    #[test]
+    fn test_tag_json_reports_change() {
+        let env = TestEnv::new();
+        setup_pair(&env);
+
+        let first: serde_json::Value = env
+            .cmd()
+            .tag_add("Source", "draft")
+            .format_json()
+            .output_json();
+        assert_eq!(first["data"]["tag"], "draft");
+        assert_eq!(first["data"]["changed"], true);
+
+        let second: serde_json::Value = env
+            .cmd()
+            .tag_add("Source", "draft")
+            .format_json()
+            .output_json();
+        assert_eq!(second["data"]["changed"], false);
+    }
+
+    #[test]
+    fn test_untag_json() {
+        let env = TestEnv::new();
+        setup_pair(&env);
+
+        let output: serde_json::Value = env
+            .cmd()
+            .untag("Source", "missing")
+            .format_json()
+            .output_json();
+        assert_eq!(output["data"]["id"], SOURCE);
+        assert_eq!(output["data"]["changed"], false);
+    }
+
+    #[test]
+    fn test_link_and_unlink_json() {
+        let env = TestEnv::new();
+        setup_pair(&env);
+
+        let linked: serde_json::Value = env
+            .cmd()
+            .link("Source", "Target")
+            .with_rel("parent")
+            .format_json()
+            .output_json();
+        assert_eq!(linked["data"]["source_id"], SOURCE);
+        assert_eq!(linked["data"]["target_id"], TARGET);
+        assert_eq!(linked["data"]["rels"][0], "parent");
+        assert_eq!(linked["data"]["changed"], true);
+
+        let unlinked: serde_json::Value = env
+            .cmd()
+            .unlink("Source", "Target")
+            .format_json()
+            .output_json();
+        assert_eq!(unlinked["data"]["changed"], true);
+        assert_eq!(unlinked["data"]["rels"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_check_json_lists_issues() {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Broken")
+                .topic("software")
+                .link("01HQ5B3S0QYJK5RAQX9W3S7T0Z", &["see-also"]),
+        );
+
+        let output = env.cmd().check().format_json().assert().failure();
+        let stdout = String::from_utf8(output.get_output().stdout.clone()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+        assert_eq!(json["data"]["ok"], false);
+        assert_eq!(json["data"]["errors"], 1);
+        assert_eq!(json["data"]["issues"][0]["kind"], "broken-link");
+        assert_eq!(json["data"]["issues"][0]["severity"], "error");
+    }
+
+    #[test]
+    fn test_check_json_ok() {
+        let env = TestEnv::new();
+        setup_pair(&env);
+
+        let output: serde_json::Value = env.cmd().check().format_json().output_json();
+        assert_eq!(output["data"]["ok"], true);
+        assert_eq!(output["data"]["notes"], 2);
+        assert_eq!(output["data"]["issues"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_index_json() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("One"));
+        env.add_note(&TestNote::new("Two"));
+
+        let output: serde_json::Value = env.cmd().index().with_full().format_json().output_json();
+        assert_eq!(output["data"]["full"], true);
+        assert_eq!(output["data"]["added"], 2);
+
+        let output: serde_json::Value = env.cmd().index().format_json().output_json();
+        assert_eq!(output["data"]["full"], false);
+        assert_eq!(output["data"]["added"], 0);
+    }
+}
+
+// ===========================================
+// Edge cases and error handling tests
+// ===========================================
+mod edge_case_tests {
+    use super::*;
+
+    #[test]
+    fn test_special_characters_in_title() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Note with 'quotes' and \"double quotes\"");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("quotes"));
+    }
+
+    #[test]
+    fn test_unicode_in_content() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Unicode Note").body("# 日本語\n\nEmoji: \u{1F980} \u{1F40D}");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .show("Unicode Note")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("日本語"));
+    }
+
+    #[test]
+    fn test_very_long_title() {
+        let env = TestEnv::new();
+
+        let long_title = "A".repeat(200);
+        let note = TestNote::new(&long_title);
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd().ls().assert().success();
+    }
+
+    #[test]
+    fn test_empty_body() {
         let env = TestEnv::new();
 
         let note = TestNote::new("Empty Body Note").body("");
@@ -1463,1489 +4456,3707 @@ mod edge_case_tests {
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .show("Empty Body Note")
+            .show("Empty Body Note")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Empty Body Note"));
+    }
+
+    #[test]
+    fn test_multiple_topics_on_note() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Multi Topic")
+            .topic("software/rust")
+            .topic("tutorials/beginner");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Should appear in both topic filters
+        env.cmd()
+            .ls()
+            .args(["software/rust"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Multi Topic"));
+
+        env.cmd()
+            .ls()
+            .args(["tutorials/beginner"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Multi Topic"));
+    }
+
+    #[test]
+    fn test_deeply_nested_topic() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Deep Note").topic("level1/level2/level3/level4/level5");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .args(["level1/"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Deep Note"));
+    }
+}
+
+// ===========================================
+// mv command tests
+// ===========================================
+mod mv_tests {
+    use super::*;
+
+    #[test]
+    fn test_mv_moves_notes_from_stdin() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Parser").topic("inbox"));
+        env.add_note(&TestNote::new("Lexer").topic("inbox"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["mv", "-", "--topic", "compilers"])
+            .stdin("Parser\nLexer\n")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Moved 'Parser' to compilers"))
+            .stdout(predicate::str::contains(
+                "2 note(s): 2 changed, 0 unchanged, 0 failed",
+            ));
+
+        let listed = env.cmd().ls().args(["compilers"]).output_success();
+        assert!(listed.contains("Parser"));
+        assert!(listed.contains("Lexer"));
+
+        // One title can't be given to several notes
+        env.cmd()
+            .args(["mv", "-", "--title", "Same"])
+            .stdin("Parser\n")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--title"));
+    }
+
+    // ===========================================
+    // Title Rename Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_renames_title() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Original Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("New Title")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Renamed"));
+
+        // Verify new title is visible
+        env.cmd()
+            .show("New Title")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("New Title"));
+    }
+
+    #[test]
+    fn test_mv_renames_file_on_title_change() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Old Name").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("New Name")
+            .assert()
+            .success();
+
+        // Check that the new filename exists
+        let entries: Vec<_> = std::fs::read_dir(env.notes_dir())
+            .expect("Should read directory")
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .collect();
+
+        assert_eq!(entries.len(), 1);
+        let filename = entries[0].file_name().to_string_lossy().to_string();
+        assert!(
+            filename.contains("new-name"),
+            "Filename should contain slugified new title"
+        );
+        assert!(
+            !filename.contains("old-name"),
+            "Old filename should not exist"
+        );
+    }
+
+    #[test]
+    fn test_mv_title_updates_index() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Index Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("Renamed Index Test")
+            .assert()
+            .success();
+
+        // Should find by new title
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Renamed Index Test"))
+            .stdout(predicate::str::contains("Index Test Note").not());
+    }
+
+    // ===========================================
+    // Topic Move Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_changes_topic() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Movable Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .topic("software/rust");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("tutorials/beginner")
+            .assert()
+            .success();
+
+        // Should be in new topic
+        env.cmd()
+            .ls()
+            .args(["tutorials/beginner"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Movable Note"));
+
+        // Should NOT be in old topic
+        env.cmd()
+            .ls()
+            .args(["software/rust"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Movable Note").not());
+    }
+
+    #[test]
+    fn test_mv_replaces_all_topics() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Multi Topic Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .topic("software/rust")
+            .topic("tutorials");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Move to single new topic
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("archive")
+            .assert()
+            .success();
+
+        // Should only be in archive
+        env.cmd()
+            .ls()
+            .args(["archive"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Multi Topic Note"));
+
+        env.cmd()
+            .ls()
+            .args(["software/rust"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Multi Topic Note").not());
+    }
+
+    #[test]
+    fn test_mv_multiple_topics() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Topic Test").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("software")
+            .with_topic("tutorials")
+            .assert()
+            .success();
+
+        // Should be in both topics
+        env.cmd()
+            .ls()
+            .args(["software"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Topic Test"));
+
+        env.cmd()
+            .ls()
+            .args(["tutorials"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Topic Test"));
+    }
+
+    #[test]
+    fn test_mv_clear_topics() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Clearable Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .topic("software/rust");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_clear_topics()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Cleared topics"));
+
+        // Should NOT be in old topic
+        env.cmd()
+            .ls()
+            .args(["software/rust"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Clearable Note").not());
+
+        // Should still exist (in ls without topic filter)
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Clearable Note"));
+    }
+
+    // ===========================================
+    // Combined Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_title_and_topic_together() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Original Name")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .topic("software");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("New Name")
+            .with_topic("tutorials")
+            .assert()
+            .success();
+
+        // Should have new title and be in new topic
+        env.cmd()
+            .ls()
+            .args(["tutorials"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("New Name"));
+
+        // Should not be in old topic or have old name
+        env.cmd()
+            .ls()
+            .args(["software"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("New Name").not())
+            .stdout(predicate::str::contains("Original Name").not());
+    }
+
+    // ===========================================
+    // Error Handling Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_note_not_found() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("nonexistent")
+            .with_title("New Title")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found"));
+    }
+
+    #[test]
+    fn test_mv_no_changes_specified() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("at least one of"));
+    }
+
+    #[test]
+    fn test_mv_empty_title_rejected() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be empty"));
+    }
+
+    #[test]
+    fn test_mv_invalid_topic_rejected() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("invalid topic with spaces")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid topic"));
+    }
+
+    #[test]
+    fn test_mv_clear_topics_with_topic_rejected() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_clear_topics()
+            .with_topic("software")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("mutually exclusive"));
+    }
+
+    #[test]
+    fn test_mv_ambiguous_note() {
+        let env = TestEnv::new();
+
+        // Two notes with same title prefix
+        let note1 = TestNote::new("Duplicate Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note2 = TestNote::new("Duplicate Title").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("Duplicate Title")
+            .with_title("New Title")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("ambiguous"));
+    }
+
+    // ===========================================
+    // Idempotency Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_same_title_is_idempotent() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Same Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Move to same title
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("Same Title")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No changes needed"));
+    }
+
+    #[test]
+    fn test_mv_same_topics_is_idempotent() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Same Topics Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .topic("software/rust");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Move to same topic
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("software/rust")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No changes needed"));
+    }
+
+    // ===========================================
+    // Collision and Alias Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_title_collision_adds_disambiguator() {
+        let env = TestEnv::new();
+
+        let alpha = TestNote::new("Alpha")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .body("Alpha body.");
+        let alpha_path = env.add_note(&alpha);
+        env.add_note(&TestNote::new("Beta").id("01HQ3K5M7NXJK4QZPW8V2R6T9Z"));
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("01HQ3K5M7NXJK4QZPW8V2R6T9Z")
+            .with_title("Alpha")
+            .format_json()
+            .output_json();
+
+        assert_eq!(output["data"]["new_path"], "01HQ3K5M7N-alpha-2.md");
+        assert!(env.notes_dir().join("01HQ3K5M7N-alpha-2.md").exists());
+        let alpha_content = std::fs::read_to_string(alpha_path).unwrap();
+        assert!(alpha_content.contains("Alpha body."));
+
+        let listed: serde_json::Value = env.cmd().ls().format_json().output_json();
+        let mut paths: Vec<&str> = listed["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["path"].as_str().unwrap())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["01HQ3K5M7N-alpha-2.md", "01HQ3K5M7N-alpha.md"]);
+    }
+
+    /// On case-insensitive file systems (macOS and Windows CI) the new name
+    /// is the old file under another spelling, which mustn't be deleted.
+    #[test]
+    fn test_mv_case_only_rename_keeps_note() {
+        let env = TestEnv::new();
+        env.write_file(
+            "01HQ3K5M7N-Rust.md",
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\ntitle: Rust\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\nOwnership.\n",
+        );
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("rust")
+            .assert()
+            .success();
+
+        let names: Vec<String> = std::fs::read_dir(env.notes_dir())
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name.ends_with(".md"))
+            .collect();
+        assert_eq!(names, vec!["01HQ3K5M7N-rust.md"]);
+        let content = std::fs::read_to_string(env.notes_dir().join("01HQ3K5M7N-rust.md")).unwrap();
+        assert!(content.contains("title: rust"));
+        assert!(content.contains("Ownership."));
+
+        env.cmd()
+            .show("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Ownership."));
+    }
+
+    #[test]
+    fn test_mv_title_keeps_old_title_as_alias() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Old Name").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("New Name")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Kept 'Old Name' as an alias"));
+
+        env.cmd()
+            .show("Old Name")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("New Name"));
+
+        let output_dir = env.notes_dir().join("site");
+        env.cmd()
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
+            .assert()
+            .success();
+        let redirect = std::fs::read_to_string(output_dir.join("old-name.html")).unwrap();
+        assert!(redirect.contains("new-name.html"));
+    }
+
+    #[test]
+    fn test_mv_no_alias_skips_alias() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Old Name").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("New Name")
+            .args(["--no-alias"])
+            .format_json()
+            .output_json();
+
+        assert!(output["data"]["alias"].is_null());
+        env.cmd().show("Old Name").assert().failure();
+    }
+
+    #[test]
+    fn test_mv_title_case() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Draft").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("a tour of the rust API")
+            .args(["--title-case"])
+            .format_json()
+            .output_json();
+
+        assert_eq!(output["data"]["title"], "A Tour of the Rust API");
+        assert_eq!(
+            output["data"]["new_path"],
+            "01HQ3K5M7N-a-tour-of-the-rust-api.md"
+        );
+    }
+
+    #[test]
+    fn test_mv_title_case_requires_title() {
+        let env = TestEnv::new();
+
+        env.cmd()
+            .mv("01HQ3K5M7N")
+            .with_topic("software")
+            .args(["--title-case"])
+            .assert()
+            .failure();
+    }
+
+    // ===========================================
+    // Output Format Tests
+    // ===========================================
+
+    #[test]
+    fn test_mv_format_json() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("JSON Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("JSON Renamed")
+            .format_json()
+            .output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert_eq!(data["title"], "JSON Renamed");
+        assert!(data["id"].as_str().unwrap().starts_with("01HQ3K5M7N"));
+        assert!(data["old_path"].as_str().is_some());
+        assert!(data["new_path"].as_str().is_some());
+    }
+
+    #[test]
+    fn test_mv_format_paths() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Paths Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output = env
+            .cmd()
+            .mv("01HQ3K5M7N")
+            .with_title("Paths Renamed")
+            .format_paths()
+            .output_success();
+
+        // Should contain path information
+        assert!(output.contains("01HQ3K5M7N"));
+        assert!(output.contains(".md"));
+    }
+
+    // ===========================================
+    // ID Change Tests
+    // ===========================================
+
+    const OLD_ID: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+    const NEW_ID: &str = "01HQ9Z8Y7XWVTSRQPNMKJHGFED";
+
+    fn setup_id_change() -> (TestEnv, std::path::PathBuf) {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Target").id(OLD_ID));
+        let source = env.add_note(
+            &TestNote::new("Source")
+                .id("01HQ4A2R9PXJK4QZPW8V2R6T9Z")
+                .link(OLD_ID, &["see-also"])
+                .body("See [Target](01HQ3K5M7N)."),
+        );
+        env.build_index().expect("Should build index");
+        (env, source)
+    }
+
+    #[test]
+    fn test_mv_id_relinks_notes_and_keeps_old_id_as_alias() {
+        let (env, source) = setup_id_change();
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("Target")
+            .args(["--id", NEW_ID])
+            .format_json()
+            .output_json();
+
+        let data = &output["data"];
+        assert_eq!(data["old_id"], OLD_ID);
+        assert_eq!(data["new_id"], NEW_ID);
+        assert!(data["new_path"].as_str().unwrap().starts_with("01HQ9Z8Y7X"));
+        assert_eq!(data["links_rewritten"], 2);
+        assert_eq!(data["notes_relinked"], 1);
+
+        let contents = std::fs::read_to_string(&source).unwrap();
+        assert!(contents.contains(NEW_ID), "{}", contents);
+        assert!(contents.contains("[Target](01HQ9Z8Y7X)"), "{}", contents);
+        assert!(!contents.contains("01HQ3K5M7N"), "{}", contents);
+
+        // The old ID still finds the note, now through its alias
+        env.cmd()
+            .show(OLD_ID)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Target"));
+        env.cmd()
+            .args(["backlinks", "01HQ9Z8Y7X"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Source"));
+        env.cmd().args(["check"]).assert().success();
+    }
+
+    #[test]
+    fn test_mv_id_rewrites_wikilinks_by_id() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Target").id(OLD_ID));
+        let source = env.add_note(
+            &TestNote::new("Source")
+                .id("01HQ4A2R9PXJK4QZPW8V2R6T9Z")
+                .body(format!(
+                    "See [[{}|the target]], [[{}#Usage]], [[01hq3k5m|short]] and [[Target]].\n",
+                    OLD_ID, OLD_ID
+                )),
+        );
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .mv("Target")
+            .args(["--id", NEW_ID])
+            .format_json()
+            .output_json();
+        assert_eq!(output["data"]["links_rewritten"], 3);
+        assert_eq!(output["data"]["notes_relinked"], 1);
+
+        let contents = std::fs::read_to_string(&source).unwrap();
+        assert!(
+            contents.contains(&format!(
+                "See [[{}|the target]], [[{}#Usage]], [[01HQ9Z8Y7X|short]] and [[Target]].",
+                NEW_ID, NEW_ID
+            )),
+            "{}",
+            contents
+        );
+        assert!(!env.notes_dir().join(".index/journal.json").exists());
+        env.cmd()
+            .args(["check"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("matches no note").not());
+    }
+
+    #[test]
+    fn test_mv_id_generates_new_id() {
+        let (env, _) = setup_id_change();
+
+        env.cmd()
+            .mv("Target")
+            .args(["--id"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Changed ID of 'Target' from"))
+            .stdout(predicate::str::contains(
+                "Kept 01HQ3K5M7NXJK4QZPW8V2R6T9Y as an alias",
+            ));
+    }
+
+    #[test]
+    fn test_mv_id_refuses_taken_id() {
+        let (env, _) = setup_id_change();
+
+        env.cmd()
+            .mv("Target")
+            .args(["--id", "01HQ4A2R9PXJK4QZPW8V2R6T9Z"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("already used by 'Source'"));
+    }
+
+    #[test]
+    fn test_mv_id_dry_run_writes_nothing() {
+        let (env, source) = setup_id_change();
+        let before = std::fs::read_to_string(&source).unwrap();
+
+        env.cmd()
+            .mv("Target")
+            .args(["--id", NEW_ID, "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would change ID of 'Target'"));
+
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), before);
+        env.cmd().show("01HQ3K5M7N").assert().success();
+    }
+}
+
+// ===========================================
+// archive command tests
+// ===========================================
+mod archive_tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_adds_archived_tag() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .archive("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived"));
+
+        // Verify tag was added by checking tags list
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("archived"));
+    }
+
+    #[test]
+    fn test_archive_idempotent() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Already Archived Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("archived");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .archive("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("already archived"));
+    }
+
+    #[test]
+    fn test_archive_not_found() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .archive("nonexistent")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found"));
+    }
+
+    #[test]
+    fn test_archive_reads_paths_from_stdin() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Stale One").tag("stale"));
+        env.add_note(&TestNote::new("Stale Two").tag("stale"));
+        env.add_note(&TestNote::new("Fresh"));
+        env.build_index().expect("Should build index");
+
+        let paths = env
+            .cmd()
+            .ls()
+            .with_tag("stale")
+            .format_paths()
+            .output_success();
+
+        env.cmd()
+            .args(["archive", "-"])
+            .stdin(paths)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived 'Stale One'"))
+            .stdout(predicate::str::contains(
+                "2 note(s): 2 changed, 0 unchanged, 0 failed",
+            ));
+
+        let listed = env.cmd().ls().output_success();
+        assert!(!listed.contains("Stale"));
+        assert!(listed.contains("Fresh"));
+    }
+
+    #[test]
+    fn test_archive_from_stdin_continues_past_failures() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Real Note"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["archive", "-"])
+            .stdin("missing\nReal Note\n")
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("Archived 'Real Note'"))
+            .stderr(predicate::str::contains("error: 'missing': note not found"))
+            .stderr(predicate::str::contains("1 of 2 note(s) failed"));
+    }
+
+    #[test]
+    fn test_archive_by_title() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Unique Title Note");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .archive("Unique Title Note")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived"));
+    }
+
+    #[test]
+    fn test_archive_updates_index() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Index Archive Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd().archive("01HQ3K5M7N").assert().success();
+
+        // Tag should appear in tags list (index was updated)
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("archived"));
+    }
+
+    #[test]
+    fn test_archive_format_json() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("JSON Archive Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().archive("01HQ3K5M7N").format_json().output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert_eq!(data["archived"], true);
+    }
+}
+
+// ===========================================
+// archive --suggest tests
+// ===========================================
+mod archive_suggest_tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_archive_suggest_lists_stale_unlinked_notes() {
+        let env = TestEnv::new();
+        let old = Utc::now() - Duration::days(800);
+
+        let stale = TestNote::new("Stale Note").modified(old);
+        let linked = TestNote::new("Linked Note").modified(old);
+        let tasked = TestNote::new("Tasked Note")
+            .modified(old)
+            .body("- [ ] finish this");
+        let fresh = TestNote::new("Fresh Note").link(linked.note_id().to_string(), &["see-also"]);
+        for note in [&stale, &linked, &tasked, &fresh] {
+            env.add_note(note);
+        }
+        env.build_index().expect("Should build index");
+
+        let stdout = env.cmd().args(["archive", "--suggest"]).output_success();
+
+        assert!(stdout.contains("Stale Note"));
+        assert!(!stdout.contains("Linked Note"));
+        assert!(!stdout.contains("Tasked Note"));
+        assert!(!stdout.contains("Fresh Note"));
+    }
+
+    #[test]
+    fn test_archive_suggest_apply_yes_archives_in_bulk() {
+        let env = TestEnv::new();
+        let old = Utc::now() - Duration::days(800);
+
+        env.add_note(&TestNote::new("First Stale").modified(old));
+        env.add_note(&TestNote::new("Second Stale").modified(old));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["archive", "--suggest", "--apply", "--yes"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived 2 of 2"));
+
+        env.cmd()
+            .args(["archive", "--suggest"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No archive candidates."));
+    }
+
+    #[test]
+    fn test_archive_suggest_apply_prompts_for_each_note() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Older Stale").modified(Utc::now() - Duration::days(900)));
+        env.add_note(&TestNote::new("Newer Stale").modified(Utc::now() - Duration::days(800)));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .args(["archive", "--suggest", "--apply"])
+            .stdin("y\nn\n")
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("Archive 'Older Stale'"))
+            .stdout(predicate::str::contains("Archived 1 of 2"));
+
+        let stdout = env.cmd().args(["archive", "--suggest"]).output_success();
+        assert!(stdout.contains("Newer Stale"));
+        assert!(!stdout.contains("Older Stale"));
+    }
+
+    #[test]
+    fn test_archive_requires_note_or_suggest() {
+        let env = TestEnv::new();
+
+        env.cmd().args(["archive"]).assert().failure();
+        env.cmd()
+            .args(["archive", "--apply"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--suggest"));
+    }
+}
+
+// ===========================================
+// unarchive command tests
+// ===========================================
+mod unarchive_tests {
+    use super::*;
+
+    #[test]
+    fn test_unarchive_removes_archived_tag() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Archived Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("archived");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unarchive("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Unarchived"));
+
+        // Verify tag was removed
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("archived").not());
+    }
+
+    #[test]
+    fn test_unarchive_idempotent() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Not Archived Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unarchive("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("not archived"));
+    }
+
+    #[test]
+    fn test_unarchive_not_found() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unarchive("nonexistent")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("not found"));
+    }
+
+    #[test]
+    fn test_unarchive_by_title() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Archived By Title").tag("archived");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .unarchive("Archived By Title")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Unarchived"));
+    }
+
+    #[test]
+    fn test_unarchive_updates_index() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Index Unarchive Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("archived");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd().unarchive("01HQ3K5M7N").assert().success();
+
+        // Archived tag should be gone from tags list
+        env.cmd()
+            .tags()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("archived").not());
+    }
+
+    #[test]
+    fn test_unarchive_format_json() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("JSON Unarchive Note")
+            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+            .tag("archived");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .unarchive("01HQ3K5M7N")
+            .format_json()
+            .output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert_eq!(data["archived"], false);
+    }
+}
+
+// ===========================================
+// archive filtering tests (ls excludes archived by default)
+// ===========================================
+mod archive_filtering_tests {
+    use super::*;
+
+    #[test]
+    fn test_ls_excludes_archived_by_default() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Note");
+        let archived = TestNote::new("Archived Note").tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Active Note"))
+            .stdout(predicate::str::contains("Archived Note").not());
+    }
+
+    #[test]
+    fn test_ls_include_archived_shows_all() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Note");
+        let archived = TestNote::new("Archived Note").tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .ls()
+            .with_include_archived()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Active Note"))
+            .stdout(predicate::str::contains("Archived Note"));
+    }
+
+    #[test]
+    fn test_ls_archived_only_via_tag_filter() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Note");
+        let archived = TestNote::new("Archived Note").tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        // Using --tag archived with --include-archived shows only archived
+        env.cmd()
+            .ls()
+            .with_include_archived()
+            .with_tag("archived")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived Note"))
+            .stdout(predicate::str::contains("Active Note").not());
+    }
+
+    #[test]
+    fn test_ls_all_archived_shows_empty() {
+        let env = TestEnv::new();
+
+        // Only archived notes in the vault
+        let archived1 = TestNote::new("Archived One").tag("archived");
+        let archived2 = TestNote::new("Archived Two").tag("archived");
+        env.add_note(&archived1);
+        env.add_note(&archived2);
+        env.build_index().expect("Should build index");
+
+        // Default ls should show no notes
+        env.cmd()
+            .ls()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Archived One").not())
+            .stdout(predicate::str::contains("Archived Two").not());
+    }
+
+    #[test]
+    fn test_ls_topic_filter_respects_archived() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Software Note").topic("software");
+        let archived = TestNote::new("Archived Software Note")
+            .topic("software")
+            .tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        // Topic filter should also exclude archived
+        env.cmd()
+            .ls()
+            .args(["software"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Active Software Note"))
+            .stdout(predicate::str::contains("Archived Software Note").not());
+    }
+
+    #[test]
+    fn test_ls_json_excludes_archived() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active JSON Note");
+        let archived = TestNote::new("Archived JSON Note").tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().ls().format_json().output_json();
+
+        let data = output.get("data").expect("Should have 'data' field");
+        let items = data.as_array().expect("data should be array");
+
+        // Should only have one item (the active note)
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["title"], "Active JSON Note");
+    }
+
+    #[test]
+    fn test_search_excludes_archived_by_default() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Rust Note").body("Programming in Rust");
+        let archived = TestNote::new("Archived Rust Note")
+            .tag("archived")
+            .body("Old Rust content");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .search("Rust")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Active Rust Note"))
+            .stdout(predicate::str::contains("Archived Rust Note").not());
+    }
+
+    #[test]
+    fn test_search_include_archived() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Rust Note").body("Programming in Rust");
+        let archived = TestNote::new("Archived Rust Note")
+            .tag("archived")
+            .body("Old Rust content");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .search("Rust")
+            .with_include_archived()
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Active Rust Note"))
+            .stdout(predicate::str::contains("Archived Rust Note"));
+    }
+
+    #[test]
+    fn test_search_json_excludes_archived() {
+        let env = TestEnv::new();
+
+        let active = TestNote::new("Active Findable Note");
+        let archived = TestNote::new("Archived Findable Note").tag("archived");
+        env.add_note(&active);
+        env.add_note(&archived);
+        env.build_index().expect("Should build index");
+
+        let output: serde_json::Value = env.cmd().search("Findable").format_json().output_json();
+
+        let data = output.get("data").expect("Should have 'data' field");
+        let items = data.as_array().expect("data should be array");
+
+        // Should only find the active note
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["title"], "Active Findable Note");
+    }
+}
+
+// ===========================================
+// export command tests
+// ===========================================
+mod export_tests {
+    use super::*;
+
+    #[test]
+    fn test_export_single_note_to_stdout() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Export Test").body("# Hello World\n\nContent here.");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export("Export Test")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("<!DOCTYPE html>"))
+            .stdout(predicate::str::contains("Export Test"))
+            .stdout(predicate::str::contains("Hello World"));
+    }
+
+    #[test]
+    fn test_export_man_format_to_stdout() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Plain Export")
+            .body("## Setup\n\nRead the **manual** at [the docs](https://docs.example).");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export("Plain Export")
+            .args(["--format", "man", "--width", "40"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Plain Export\n============"))
+            .stdout(predicate::str::contains("Setup\n-----"))
+            .stdout(predicate::str::contains("Read the manual at the docs[1]."))
+            .stdout(predicate::str::contains("[1] https://docs.example"))
+            .stdout(predicate::str::contains("<").not());
+    }
+
+    #[test]
+    fn test_export_man_format_bulk_writes_txt_files() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("First Note").body("One."));
+        env.add_note(&TestNote::new("Second Note").body("Two."));
+        env.build_index().expect("Should build index");
+        let output_dir = env.notes_dir().join("text-out");
+
+        env.cmd()
+            .args(["export", "--all", "--format", "man", "--output"])
+            .args([output_dir.to_str().unwrap()])
+            .assert()
+            .success();
+
+        let first = std::fs::read_to_string(output_dir.join("first-note.txt")).unwrap();
+        assert!(first.starts_with("First Note\n=========="));
+        assert!(output_dir.join("second-note.txt").exists());
+    }
+
+    #[test]
+    fn test_export_epub_by_topic() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Rust Note").topic("software/rust"));
+        env.add_note(&TestNote::new("Python Note").topic("software/python"));
+        env.add_note(&TestNote::new("Other Note").topic("other"));
+        env.build_index().expect("Should build index");
+        let output = env.notes_dir().join("books/software.epub");
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["export", "--all", "--format", "epub"])
+            .args(["--topic", "software/"])
+            .args(["--output", output.to_str().unwrap(), "--cli-format", "json"])
+            .output_json();
+        assert_eq!(json["data"]["notes_exported"], 2);
+
+        let book = std::fs::read(&output).unwrap();
+        assert!(book.starts_with(b"PK"));
+        assert_eq!(&book[30..58], b"mimetypeapplication/epub+zip");
+
+        env.cmd()
+            .export("Rust Note")
+            .args(["--format", "epub"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("requires --all"));
+    }
+
+    #[test]
+    fn test_export_by_id_prefix() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("ID Export Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export("01HQ3K5M7N")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ID Export Note"));
+    }
+
+    #[test]
+    fn test_export_to_file() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("File Export").body("Export content");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output_dir = env.notes_dir().join("export");
+
+        env.cmd()
+            .export("File Export")
+            .with_output(&output_dir)
+            .assert()
+            .success();
+
+        // Check that HTML file was created
+        let html_file = output_dir.join("file-export.html");
+        assert!(html_file.exists(), "HTML file should be created");
+
+        let content = std::fs::read_to_string(html_file).unwrap();
+        assert!(content.contains("File Export"));
+        assert!(content.contains("Export content"));
+    }
+
+    #[test]
+    fn test_export_with_custom_template() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Template Test").body("Body text");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let template =
+            env.write_file("custom.html", "<!DOCTYPE html><html><body>CUSTOM: {{ title }} - {{ content }}</body></html>");
+
+        env.cmd()
+            .export("Template Test")
+            .with_template(&template)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("CUSTOM: Template Test"))
+            .stdout(predicate::str::contains("Body text"));
+    }
+
+    #[test]
+    fn test_export_with_dark_theme() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Dark Theme Test");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        // Dark theme now returns unified CSS with automatic light/dark switching
+        env.cmd()
+            .export("Dark Theme Test")
+            .with_theme("dark")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("prefers-color-scheme: dark"))
+            .stdout(predicate::str::contains("--color-bg: #0d1117")); // Dark mode background
+    }
+
+    #[test]
+    fn test_export_with_custom_css() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Custom CSS Test");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let css = env.write_file("custom.css", "body { color: purple; }");
+
+        env.cmd()
+            .export("Custom CSS Test")
+            .with_theme(css.to_str().unwrap())
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("color: purple"));
+    }
+
+    #[test]
+    fn test_export_includes_topics() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Topic Note").topic("software/rust");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export("Topic Note")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Empty Body Note"));
+            .stdout(predicate::str::contains("software/rust"));
     }
 
     #[test]
-    fn test_multiple_topics_on_note() {
+    fn test_export_includes_tags() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Multi Topic")
-            .topic("software/rust")
-            .topic("tutorials/beginner");
+        let note = TestNote::new("Tagged Note").tag("important").tag("draft");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Should appear in both topic filters
         env.cmd()
-            .ls()
-            .args(["software/rust"])
+            .export("Tagged Note")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Multi Topic"));
+            .stdout(predicate::str::contains("important"))
+            .stdout(predicate::str::contains("draft"));
+    }
+
+    #[test]
+    fn test_export_note_not_found() {
+        let env = TestEnv::new();
+        env.build_index().expect("Should build index");
 
         env.cmd()
-            .ls()
-            .args(["tutorials/beginner"])
+            .export("nonexistent")
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Multi Topic"));
+            .failure()
+            .stderr(predicate::str::contains("not found"));
     }
 
     #[test]
-    fn test_deeply_nested_topic() {
+    fn test_export_markdown_converted() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Deep Note").topic("level1/level2/level3/level4/level5");
+        let note = TestNote::new("Markdown Test")
+            .body("# Heading\n\n**Bold** and *italic*\n\n- List item");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .ls()
-            .args(["level1/"])
+            .export("Markdown Test")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Deep Note"));
+            .stdout(predicate::str::contains("<h1>Heading</h1>"))
+            .stdout(predicate::str::contains("<strong>Bold</strong>"))
+            .stdout(predicate::str::contains("<em>italic</em>"))
+            .stdout(predicate::str::contains("<li>List item</li>"));
     }
-}
-
-// ===========================================
-// mv command tests
-// ===========================================
-mod mv_tests {
-    use super::*;
-
-    // ===========================================
-    // Title Rename Tests
-    // ===========================================
 
     #[test]
-    fn test_mv_renames_title() {
+    fn test_export_code_blocks() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Original Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note = TestNote::new("Code Test").body("```rust\nfn main() {}\n```");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("New Title")
+            .export("Code Test")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Renamed"));
+            .stdout(predicate::str::contains("<pre>"))
+            .stdout(predicate::str::contains("fn main()"));
+    }
+
+    #[test]
+    fn test_export_callouts_and_math() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Extensions Test")
+            .body("> [!tip] Remember\n> Square it.\n\nArea is $\\pi r^2$.");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
 
-        // Verify new title is visible
         env.cmd()
-            .show("New Title")
+            .export("Extensions Test")
+            .args(["--math"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("New Title"));
+            .stdout(predicate::str::contains(
+                r#"<div class="callout callout-tip">"#,
+            ))
+            .stdout(predicate::str::contains(
+                r#"<span class="math math-inline">\(\pi r^2\)</span>"#,
+            ))
+            .stdout(predicate::str::contains("mathjax@3"));
     }
 
     #[test]
-    fn test_mv_renames_file_on_title_change() {
+    fn test_export_extensions_can_be_disabled() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Old Name").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note = TestNote::new("Plain Test")
+            .body("> [!tip] Remember\n\n- [x] Done\n\nCosts $5 and $10.");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("New Name")
+            .export("Plain Test")
+            .args(["--no-callouts", "--no-task-lists"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("<blockquote>"))
+            .stdout(predicate::str::contains("[x] Done"))
+            .stdout(predicate::str::contains("Costs $5 and $10."))
+            .stdout(predicate::str::contains("mathjax").not());
+    }
 
-        // Check that the new filename exists
-        let entries: Vec<_> = std::fs::read_dir(env.notes_dir())
-            .expect("Should read directory")
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
-            .collect();
+    #[test]
+    fn test_export_json_output() {
+        let env = TestEnv::new();
 
-        assert_eq!(entries.len(), 1);
-        let filename = entries[0].file_name().to_string_lossy().to_string();
-        assert!(
-            filename.contains("new-name"),
-            "Filename should contain slugified new title"
-        );
-        assert!(
-            !filename.contains("old-name"),
-            "Old filename should not exist"
-        );
+        let note = TestNote::new("JSON Output Test").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
+
+        let output_dir = env.notes_dir().join("export");
+
+        let output: serde_json::Value = env
+            .cmd()
+            .export("JSON Output Test")
+            .with_output(&output_dir)
+            .cli_format_json()
+            .output_json();
+
+        assert!(output.is_object(), "Output should be a JSON object");
+        let data = output.get("data").expect("Should have 'data' field");
+        assert_eq!(data["notes_exported"], 1);
+        assert!(data["path"].as_str().is_some());
+        assert!(data["title"].as_str().unwrap().contains("JSON Output Test"));
     }
 
     #[test]
-    fn test_mv_title_updates_index() {
+    fn test_export_pdf_not_implemented() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Index Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note = TestNote::new("PDF Test");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("Renamed Index Test")
+            .export("PDF Test")
+            .export_format_pdf()
             .assert()
-            .success();
+            .failure()
+            .stderr(predicate::str::contains("not yet implemented"));
+    }
+
+    #[test]
+    fn test_export_site_requires_all() {
+        let env = TestEnv::new();
+
+        let note = TestNote::new("Site Test");
+        env.add_note(&note);
+        env.build_index().expect("Should build index");
 
-        // Should find by new title
         env.cmd()
-            .ls()
+            .export("Site Test")
+            .export_format_site()
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Renamed Index Test"))
-            .stdout(predicate::str::contains("Index Test Note").not());
+            .failure()
+            .stderr(predicate::str::contains("--all"));
+    }
+
+    #[test]
+    fn test_export_ambiguous_note() {
+        let env = TestEnv::new();
+
+        // Two notes with same title
+        let note1 = TestNote::new("Duplicate Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note2 = TestNote::new("Duplicate Title").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        env.add_note(&note1);
+        env.add_note(&note2);
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export("Duplicate Title")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Ambiguous"));
     }
 
     // ===========================================
-    // Topic Move Tests
+    // Bulk Export Tests
     // ===========================================
 
     #[test]
-    fn test_mv_changes_topic() {
+    fn test_export_all_html() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Note One").body("Content one"));
+        env.add_note(&TestNote::new("Note Two").body("Content two"));
+        env.add_note(&TestNote::new("Note Three").body("Content three"));
+        env.build_index().expect("Should build index");
+
+        let output_dir = env.notes_dir().join("export");
+
+        env.cmd()
+            .export_all()
+            .with_output(&output_dir)
+            .assert()
+            .success();
+
+        // Check all files exist
+        assert!(output_dir.join("note-one.html").exists());
+        assert!(output_dir.join("note-two.html").exists());
+        assert!(output_dir.join("note-three.html").exists());
+    }
+
+    #[test]
+    fn test_export_all_requires_output() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Test Note"));
+        env.build_index().expect("Should build index");
+
+        env.cmd()
+            .export_all()
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--output"));
+    }
+
+    #[test]
+    fn test_export_all_by_topic() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Rust Note").topic("software/rust"));
+        env.add_note(&TestNote::new("Python Note").topic("software/python"));
+        env.add_note(&TestNote::new("Other Note").topic("other"));
+        env.build_index().expect("Should build index");
+
+        let output_dir = env.notes_dir().join("export");
+
+        env.cmd()
+            .export_all()
+            .with_topic("software/")
+            .with_output(&output_dir)
+            .assert()
+            .success();
+
+        // Only software notes should be exported
+        assert!(output_dir.join("rust-note.html").exists());
+        assert!(output_dir.join("python-note.html").exists());
+        assert!(!output_dir.join("other-note.html").exists());
+    }
+
+    #[test]
+    fn test_export_all_by_tag() {
+        let env = TestEnv::new();
+
+        env.add_note(&TestNote::new("Tagged Note").tag("important"));
+        env.add_note(&TestNote::new("Untagged Note"));
+        env.build_index().expect("Should build index");
+
+        let output_dir = env.notes_dir().join("export");
+
+        env.cmd()
+            .export_all()
+            .with_tag("important")
+            .with_output(&output_dir)
+            .assert()
+            .success();
+
+        assert!(output_dir.join("tagged-note.html").exists());
+        assert!(!output_dir.join("untagged-note.html").exists());
+    }
+
+    #[test]
+    fn test_export_all_excludes_archived() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Movable Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .topic("software/rust");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Active Note"));
+        env.add_note(&TestNote::new("Archived Note").tag("archived"));
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_topic("tutorials/beginner")
-            .assert()
-            .success();
+        let output_dir = env.notes_dir().join("export");
 
-        // Should be in new topic
         env.cmd()
-            .ls()
-            .args(["tutorials/beginner"])
+            .export_all()
+            .with_output(&output_dir)
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Movable Note"));
+            .success();
 
-        // Should NOT be in old topic
-        env.cmd()
-            .ls()
-            .args(["software/rust"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Movable Note").not());
+        assert!(output_dir.join("active-note.html").exists());
+        assert!(!output_dir.join("archived-note.html").exists());
     }
 
     #[test]
-    fn test_mv_replaces_all_topics() {
+    fn test_export_all_include_archived() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Multi Topic Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .topic("software/rust")
-            .topic("tutorials");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Active Note"));
+        env.add_note(&TestNote::new("Archived Note").tag("archived"));
         env.build_index().expect("Should build index");
 
-        // Move to single new topic
-        env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_topic("archive")
-            .assert()
-            .success();
+        let output_dir = env.notes_dir().join("export");
 
-        // Should only be in archive
         env.cmd()
-            .ls()
-            .args(["archive"])
+            .export_all()
+            .with_output(&output_dir)
+            .with_include_archived()
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Multi Topic Note"));
+            .success();
 
-        env.cmd()
-            .ls()
-            .args(["software/rust"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Multi Topic Note").not());
+        assert!(output_dir.join("active-note.html").exists());
+        assert!(output_dir.join("archived-note.html").exists());
     }
 
     #[test]
-    fn test_mv_multiple_topics() {
+    fn test_export_site_generates_index() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Topic Test").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Site Note One"));
+        env.add_note(&TestNote::new("Site Note Two"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("site");
+
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_topic("software")
-            .with_topic("tutorials")
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
             .assert()
             .success();
 
-        // Should be in both topics
-        env.cmd()
-            .ls()
-            .args(["software"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Topic Test"));
+        // Check index and notes exist
+        let index_path = output_dir.join("index.html");
+        assert!(index_path.exists());
 
-        env.cmd()
-            .ls()
-            .args(["tutorials"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Topic Test"));
+        let index_content = std::fs::read_to_string(index_path).unwrap();
+        assert!(index_content.contains("Site Note One"));
+        assert!(index_content.contains("Site Note Two"));
+
+        // Check CSS file
+        assert!(output_dir.join("style.css").exists());
     }
 
     #[test]
-    fn test_mv_clear_topics() {
+    fn test_export_site_with_stats() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Clearable Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .topic("software/rust");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Stats One").tag("rust"));
+        env.add_note(&TestNote::new("Stats Two").tag("rust"));
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_clear_topics()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Cleared topics"));
+        let output_dir = env.notes_dir().join("site");
 
-        // Should NOT be in old topic
         env.cmd()
-            .ls()
-            .args(["software/rust"])
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
+            .args(["--stats"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Clearable Note").not());
+            .success();
 
-        // Should still exist (in ls without topic filter)
-        env.cmd()
-            .ls()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Clearable Note"));
-    }
+        let stats: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(output_dir.join("stats.json")).unwrap())
+                .unwrap();
+        assert_eq!(stats["note_count"], 2);
+        assert_eq!(stats["top_tags"][0]["name"], "rust");
+        assert!(stats["last_updated"].is_string());
 
-    // ===========================================
-    // Combined Tests
-    // ===========================================
+        let index_content = std::fs::read_to_string(output_dir.join("index.html")).unwrap();
+        assert!(index_content.contains("site-stats"));
+    }
 
     #[test]
-    fn test_mv_title_and_topic_together() {
+    fn test_export_site_with_include_source() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Original Name")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .topic("software");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Source Note").body("Raw *markdown*"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("site");
+
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("New Name")
-            .with_topic("tutorials")
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
+            .args(["--include-source"])
             .assert()
             .success();
 
-        // Should have new title and be in new topic
-        env.cmd()
-            .ls()
-            .args(["tutorials"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("New Name"));
+        let source = std::fs::read_to_string(output_dir.join("source-note.md")).unwrap();
+        assert!(source.contains("title: Source Note"));
+        assert!(source.contains("Raw *markdown*"));
 
-        // Should not be in old topic or have old name
-        env.cmd()
-            .ls()
-            .args(["software"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("New Name").not())
-            .stdout(predicate::str::contains("Original Name").not());
+        let page = std::fs::read_to_string(output_dir.join("source-note.html")).unwrap();
+        assert!(page.contains("View source"));
     }
 
-    // ===========================================
-    // Error Handling Tests
-    // ===========================================
-
     #[test]
-    fn test_mv_note_not_found() {
+    fn test_export_site_copies_images() {
         let env = TestEnv::new();
+
+        std::fs::create_dir_all(env.notes_dir().join("img")).unwrap();
+        env.write_file("img/diagram.svg", "<svg/>");
+        env.add_note(
+            &TestNote::new("Image Note")
+                .body("![Diagram](img/diagram.svg)\n![Logo](https://example.com/logo.png)"),
+        );
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .mv("nonexistent")
-            .with_title("New Title")
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("not found"));
+        let output_dir = env.notes_dir().join("site");
+
+        let output = env
+            .cmd()
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
+            .args(["--cli-format", "json"])
+            .output_json::<serde_json::Value>();
+
+        assert_eq!(output["data"]["images"], 1);
+        let copied = std::fs::read_to_string(output_dir.join("assets/diagram.svg")).unwrap();
+        assert_eq!(copied, "<svg/>");
+        let page = std::fs::read_to_string(output_dir.join("image-note.html")).unwrap();
+        assert!(page.contains("src=\"assets/diagram.svg\""));
+        assert!(page.contains("src=\"https://example.com/logo.png\""));
     }
 
     #[test]
-    fn test_mv_no_changes_specified() {
+    fn test_export_no_copy_images_keeps_references() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.write_file("diagram.svg", "<svg/>");
+        env.add_note(&TestNote::new("Image Note").body("![Diagram](diagram.svg)"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("out");
+
         env.cmd()
-            .mv("01HQ3K5M7N")
+            .export_all()
+            .export_format_html()
+            .with_output(&output_dir)
+            .args(["--no-copy-images"])
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("at least one of"));
+            .success();
+
+        assert!(!output_dir.join("assets").exists());
+        let page = std::fs::read_to_string(output_dir.join("image-note.html")).unwrap();
+        assert!(page.contains("src=\"diagram.svg\""));
     }
 
     #[test]
-    fn test_mv_empty_title_rejected() {
+    fn test_export_toc_after_first_heading() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(
+            &TestNote::new("Guide").body("# Overview\n\nIntro\n\n## Install\n\n### Linux"),
+        );
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("")
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("cannot be empty"));
+        let html = env
+            .cmd()
+            .args(["export", "Guide", "--toc", "--toc-depth", "2"])
+            .args(["--toc-after-heading"])
+            .output_success();
+
+        let toc = html.find("<nav class=\"toc\"").unwrap();
+        assert!(html.find("</h1>\n").unwrap() < toc);
+        assert!(toc < html.find("<p>Intro</p>").unwrap());
+        assert!(html.contains("<a href=\"#install\">Install</a>"));
+        assert!(!html.contains("#linux"));
     }
 
     #[test]
-    fn test_mv_invalid_topic_rejected() {
+    fn test_export_site_with_topics() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Rust Note").topic("software/rust"));
+        env.add_note(&TestNote::new("Python Note").topic("software/python"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("site");
+
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_topic("invalid topic with spaces")
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("invalid topic"));
+            .success();
+
+        // Check topic directories exist
+        assert!(output_dir.join("software").join("rust").exists());
+        assert!(output_dir.join("software").join("python").exists());
+
+        // Check topic index pages
+        assert!(
+            output_dir
+                .join("software")
+                .join("rust")
+                .join("index.html")
+                .exists()
+        );
     }
 
     #[test]
-    fn test_mv_clear_topics_with_topic_rejected() {
+    fn test_export_site_excludes_topics_tags_and_paths() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Some Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Public Note").topic("software/rust"));
+        env.add_note(&TestNote::new("Diary Entry").topic("personal/journal"));
+        env.add_note(&TestNote::new("Draft Note").tag("private"));
+        env.add_note(&TestNote::new("Secret Plan"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("site");
+
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_clear_topics()
-            .with_topic("software")
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
+            .args(["--exclude-topic", "personal/"])
+            .args(["--exclude-tag", "private"])
+            .args(["--exclude", "*-secret-plan.md"])
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("mutually exclusive"));
+            .success();
+
+        assert!(output_dir.join("public-note.html").exists());
+        assert!(!output_dir.join("diary-entry.html").exists());
+        assert!(!output_dir.join("draft-note.html").exists());
+        assert!(!output_dir.join("secret-plan.html").exists());
+        assert!(!output_dir.join("personal").exists());
+
+        let index_content = std::fs::read_to_string(output_dir.join("index.html")).unwrap();
+        assert!(!index_content.contains("Diary Entry"));
+    }
+
+    /// Writes notes using the `export`, `export_slug` and `export_template`
+    /// frontmatter keys, plus a note linking to the renamed one.
+    fn add_notes_with_export_settings(env: &TestEnv) {
+        env.write_file(
+            "hidden-draft.md",
+            "---\nid: 01HQ4AAAAAAAAAAAAAAAAAAAAA\ntitle: Hidden Draft\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\nexport: false\n---\nSecret\n",
+        );
+        env.write_file(
+            "release-notes.md",
+            "---\nid: 01HQ5AAAAAAAAAAAAAAAAAAAAA\ntitle: Release Notes\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\nexport_slug: changes\nexport_template: .templates/special\n---\nWhat changed\n",
+        );
+        env.write_file(
+            "overview.md",
+            "---\nid: 01HQ6AAAAAAAAAAAAAAAAAAAAA\ntitle: Overview\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\nSee [the changes](01HQ5AAAAA).\n",
+        );
+        std::fs::create_dir(env.notes_dir().join(".templates")).unwrap();
+        env.write_file(
+            ".templates/special.html",
+            "SPECIAL {{ title }}: {{ content }}",
+        );
+        env.build_index().expect("Should build index");
     }
 
     #[test]
-    fn test_mv_ambiguous_note() {
+    fn test_export_site_honors_note_export_settings() {
         let env = TestEnv::new();
-
-        // Two notes with same title prefix
-        let note1 = TestNote::new("Duplicate Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let note2 = TestNote::new("Duplicate Title").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&note1);
-        env.add_note(&note2);
-        env.build_index().expect("Should build index");
+        add_notes_with_export_settings(&env);
+        let output_dir = env.notes_dir().join("site");
 
         env.cmd()
-            .mv("Duplicate Title")
-            .with_title("New Title")
+            .export_all()
+            .export_format_site()
+            .with_output(&output_dir)
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("ambiguous"));
-    }
+            .success()
+            .stdout(predicate::str::contains("Generated site with 2 notes"));
 
-    // ===========================================
-    // Idempotency Tests
-    // ===========================================
+        assert!(!output_dir.join("hidden-draft.html").exists());
+        assert!(!output_dir.join("release-notes.html").exists());
+        let page = std::fs::read_to_string(output_dir.join("changes.html")).unwrap();
+        assert!(page.starts_with("SPECIAL Release Notes:"));
+        let index_content = std::fs::read_to_string(output_dir.join("index.html")).unwrap();
+        assert!(!index_content.contains("Hidden Draft"));
+        assert!(index_content.contains("href=\"changes.html\""));
+    }
 
     #[test]
-    fn test_mv_same_title_is_idempotent() {
+    fn test_export_html_honors_note_export_settings() {
         let env = TestEnv::new();
+        add_notes_with_export_settings(&env);
+        let output_dir = env.notes_dir().join("html");
 
-        let note = TestNote::new("Same Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        env.cmd()
+            .export_all()
+            .with_output(&output_dir)
+            .args(["--resolve-links"])
+            .assert()
+            .success();
 
-        // Move to same title
+        assert!(!output_dir.join("hidden-draft.html").exists());
+        assert!(output_dir.join("changes.html").exists());
+        let overview = std::fs::read_to_string(output_dir.join("overview.html")).unwrap();
+        assert!(overview.contains("href=\"changes.html\""));
+
+        // A single note links to the renamed page too
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("Same Title")
+            .args(["export", "01HQ6AAAAA", "--resolve-links"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("No changes needed"));
+            .stdout(predicate::str::contains("href=\"changes.html\""));
+
+        env.cmd()
+            .args(["export", "01HQ4AAAAA"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("export: false"));
     }
 
     #[test]
-    fn test_mv_same_topics_is_idempotent() {
+    fn test_export_exclude_requires_all() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Same Topics Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .topic("software/rust");
+        let note = TestNote::new("Only Note");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        // Move to same topic
         env.cmd()
-            .mv("01HQ3K5M7N")
-            .with_topic("software/rust")
+            .args(["export", &note.id_prefix(), "--exclude-tag", "private"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("No changes needed"));
+            .failure();
     }
 
-    // ===========================================
-    // Output Format Tests
-    // ===========================================
-
     #[test]
-    fn test_mv_format_json() {
+    fn test_export_json_bulk_output() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("JSON Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Bulk Note One"));
+        env.add_note(&TestNote::new("Bulk Note Two"));
         env.build_index().expect("Should build index");
 
+        let output_dir = env.notes_dir().join("export");
+
         let output: serde_json::Value = env
             .cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("JSON Renamed")
-            .format_json()
+            .export_all()
+            .with_output(&output_dir)
+            .cli_format_json()
             .output_json();
 
-        assert!(output.is_object(), "Output should be a JSON object");
         let data = output.get("data").expect("Should have 'data' field");
-        assert_eq!(data["title"], "JSON Renamed");
-        assert!(data["id"].as_str().unwrap().starts_with("01HQ3K5M7N"));
-        assert!(data["old_path"].as_str().is_some());
-        assert!(data["new_path"].as_str().is_some());
-    }
-
-    #[test]
-    fn test_mv_format_paths() {
-        let env = TestEnv::new();
-
-        let note = TestNote::new("Paths Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
-
-        let output = env
-            .cmd()
-            .mv("01HQ3K5M7N")
-            .with_title("Paths Renamed")
-            .format_paths()
-            .output_success();
-
-        // Should contain path information
-        assert!(output.contains("01HQ3K5M7N"));
-        assert!(output.contains(".md"));
+        assert_eq!(data["notes_exported"], 2);
+        assert!(data["path"].as_str().is_some());
     }
-}
 
-// ===========================================
-// archive command tests
-// ===========================================
-mod archive_tests {
-    use super::*;
+    // ===========================================
+    // Link Resolution Tests
+    // ===========================================
 
     #[test]
-    fn test_archive_adds_archived_tag() {
+    fn test_export_resolves_internal_links() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Test Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Source Note").body("See [Target](01HQ4A2R9P) for more.");
+        env.add_note(&target);
+        env.add_note(&source);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .archive("01HQ3K5M7N")
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Archived"));
-
-        // Verify tag was added by checking tags list
-        env.cmd()
-            .tags()
+            .export("Source Note")
+            .args(["--resolve-links"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("archived"));
+            .stdout(predicate::str::contains("target-note.html"));
     }
 
     #[test]
-    fn test_archive_idempotent() {
+    fn test_export_without_resolve_keeps_links() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Already Archived Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("archived");
-        env.add_note(&note);
+        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Source Note").body("See [Target](01HQ4A2R9P) for more.");
+        env.add_note(&target);
+        env.add_note(&source);
         env.build_index().expect("Should build index");
 
+        // Without --resolve-links, the ID should remain unchanged
         env.cmd()
-            .archive("01HQ3K5M7N")
+            .export("Source Note")
             .assert()
             .success()
-            .stdout(predicate::str::contains("already archived"));
-    }
-
-    #[test]
-    fn test_archive_not_found() {
-        let env = TestEnv::new();
-        env.build_index().expect("Should build index");
-
-        env.cmd()
-            .archive("nonexistent")
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("not found"));
+            .stdout(predicate::str::contains("01HQ4A2R9P"));
     }
 
     #[test]
-    fn test_archive_by_title() {
+    fn test_export_preserves_external_links() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Unique Title Note");
+        let note =
+            TestNote::new("Link Note").body("Visit [example](https://example.com) for more.");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .archive("Unique Title Note")
+            .export("Link Note")
+            .args(["--resolve-links"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Archived"));
+            .stdout(predicate::str::contains("https://example.com"));
     }
 
     #[test]
-    fn test_archive_updates_index() {
+    fn test_export_marks_broken_links() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("Index Archive Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let note = TestNote::new("Broken Link Note")
+            .body("See [Missing](01HZZZZZZZZZ) for details.");
         env.add_note(&note);
         env.build_index().expect("Should build index");
 
-        env.cmd().archive("01HQ3K5M7N").assert().success();
-
-        // Tag should appear in tags list (index was updated)
         env.cmd()
-            .tags()
+            .export("Broken Link Note")
+            .args(["--resolve-links"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("archived"));
+            .stdout(predicate::str::contains("Broken link"));
     }
 
     #[test]
-    fn test_archive_format_json() {
+    fn test_export_bulk_resolves_links() {
         let env = TestEnv::new();
 
-        let note = TestNote::new("JSON Archive Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        let target = TestNote::new("Bulk Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
+        let source = TestNote::new("Bulk Source").body("Link to [target](01HQ4A2R9P).");
+        env.add_note(&target);
+        env.add_note(&source);
         env.build_index().expect("Should build index");
 
-        let output: serde_json::Value = env.cmd().archive("01HQ3K5M7N").format_json().output_json();
+        let output_dir = env.notes_dir().join("export");
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert_eq!(data["archived"], true);
+        env.cmd()
+            .export_all()
+            .args(["--resolve-links"])
+            .with_output(&output_dir)
+            .assert()
+            .success();
+
+        let source_content =
+            std::fs::read_to_string(output_dir.join("bulk-source.html")).unwrap();
+        assert!(source_content.contains("bulk-target.html"));
     }
 }
 
 // ===========================================
-// unarchive command tests
+// digest command tests
 // ===========================================
-mod unarchive_tests {
+mod digest_tests {
     use super::*;
+    use chrono::{Duration, Utc};
 
-    #[test]
-    fn test_unarchive_removes_archived_tag() {
+    fn setup() -> TestEnv {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Archived Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("archived");
-        env.add_note(&note);
+        let long_ago = Utc::now() - Duration::days(60);
+        env.add_note(
+            &TestNote::new("Fresh Note")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .description("Just written"),
+        );
+        env.add_note(
+            &TestNote::new("Edited Note")
+                .id("01HQ4A2R9PXJK4QZPW8V2R6T9Y")
+                .created(long_ago),
+        );
+        env.add_note(
+            &TestNote::new("Stale Note")
+                .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+                .created(long_ago)
+                .modified(long_ago),
+        );
         env.build_index().expect("Should build index");
+        env
+    }
 
-        env.cmd()
-            .unarchive("01HQ3K5M7N")
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Unarchived"));
+    #[test]
+    fn test_digest_lists_new_and_updated_notes() {
+        let env = setup();
 
-        // Verify tag was removed
         env.cmd()
-            .tags()
+            .args(["digest", "--since", "7d"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("archived").not());
+            .stdout(predicate::str::contains("1 new, 1 updated"))
+            .stdout(predicate::str::contains("Fresh Note"))
+            .stdout(predicate::str::contains("Just written"))
+            .stdout(predicate::str::contains("## Updated"))
+            .stdout(predicate::str::contains("Edited Note"))
+            .stdout(predicate::str::contains("Stale Note").not());
     }
 
     #[test]
-    fn test_unarchive_idempotent() {
-        let env = TestEnv::new();
-
-        let note = TestNote::new("Not Archived Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+    fn test_digest_html_to_file_with_site_urls() {
+        let env = setup();
+        let output = env.notes_dir().join("out/digest.html");
 
         env.cmd()
-            .unarchive("01HQ3K5M7N")
+            .args(["digest", "--format", "html"])
+            .args(["--site-url", "https://notes.example.com"])
+            .args(["--output", output.to_str().unwrap()])
             .assert()
             .success()
-            .stdout(predicate::str::contains("not archived"));
+            .stdout(predicate::str::contains("1 new and 1 updated"));
+
+        let html = std::fs::read_to_string(output).unwrap();
+        assert!(html.contains("<!DOCTYPE html>"));
+        assert!(html.contains(r#"href="https://notes.example.com/fresh-note.html""#));
     }
 
     #[test]
-    fn test_unarchive_not_found() {
-        let env = TestEnv::new();
-        env.build_index().expect("Should build index");
+    fn test_digest_rejects_bad_since() {
+        let env = setup();
 
         env.cmd()
-            .unarchive("nonexistent")
+            .args(["digest", "--since", "last week"])
             .assert()
             .failure()
-            .stderr(predicate::str::contains("not found"));
+            .stderr(predicate::str::contains("invalid --since"));
     }
+}
 
-    #[test]
-    fn test_unarchive_by_title() {
-        let env = TestEnv::new();
+// ===========================================
+// fix-ids command tests
+// ===========================================
 
-        let note = TestNote::new("Archived By Title").tag("archived");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+mod fix_ids_tests {
+    use super::*;
+    use chrono::{Duration, Utc};
 
-        env.cmd()
-            .unarchive("Archived By Title")
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Unarchived"));
+    const ID: &str = "01HQ3K5M7NXJK4QZPW8V2R6T9Y";
+
+    fn setup() -> TestEnv {
+        let env = TestEnv::new();
+        env.add_note(
+            &TestNote::new("Original")
+                .id(ID)
+                .created(Utc::now() - Duration::days(2)),
+        );
+        env.add_note(&TestNote::new("Copy").id(ID));
+        env
     }
 
     #[test]
-    fn test_unarchive_updates_index() {
-        let env = TestEnv::new();
+    fn test_fix_ids_gives_duplicate_a_new_id() {
+        let env = setup();
 
-        let note = TestNote::new("Index Unarchive Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("archived");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["fix-ids", "--format", "json"])
+            .output_json();
+        let fixed = output["data"].as_array().unwrap();
 
-        env.cmd().unarchive("01HQ3K5M7N").assert().success();
+        assert_eq!(fixed.len(), 1);
+        assert_eq!(fixed[0]["reason"], "duplicate");
+        assert_eq!(fixed[0]["old_id"], ID);
+        assert!(fixed[0]["path"].as_str().unwrap().ends_with("-copy.md"));
+        assert_ne!(fixed[0]["new_id"], ID);
 
-        // Archived tag should be gone from tags list
         env.cmd()
-            .tags()
+            .args(["check"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("archived").not());
+            .stdout(predicate::str::contains("Duplicate").not());
     }
 
     #[test]
-    fn test_unarchive_format_json() {
-        let env = TestEnv::new();
-
-        let note = TestNote::new("JSON Unarchive Note")
-            .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
-            .tag("archived");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+    fn test_fix_ids_dry_run_writes_nothing() {
+        let env = setup();
 
-        let output: serde_json::Value = env
-            .cmd()
-            .unarchive("01HQ3K5M7N")
-            .format_json()
-            .output_json();
+        env.cmd()
+            .args(["fix-ids", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("duplicate ID"))
+            .stdout(predicate::str::contains("Would give 1 note(s) new IDs"));
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert_eq!(data["archived"], false);
+        env.cmd()
+            .args(["check"])
+            .assert()
+            .stdout(predicate::str::contains("notes fix-ids"));
     }
 }
 
 // ===========================================
-// archive filtering tests (ls excludes archived by default)
+// drill command tests
 // ===========================================
-mod archive_filtering_tests {
+
+mod drill_tests {
     use super::*;
 
-    #[test]
-    fn test_ls_excludes_archived_by_default() {
+    fn setup() -> TestEnv {
         let env = TestEnv::new();
-
-        let active = TestNote::new("Active Note");
-        let archived = TestNote::new("Archived Note").tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
+        env.add_note(
+            &TestNote::new("French")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("- bonjour :: hello\n- merci :: thank you\n"),
+        );
         env.build_index().expect("Should build index");
-
-        env.cmd()
-            .ls()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Active Note"))
-            .stdout(predicate::str::contains("Archived Note").not());
+        env
     }
 
     #[test]
-    fn test_ls_include_archived_shows_all() {
-        let env = TestEnv::new();
-
-        let active = TestNote::new("Active Note");
-        let archived = TestNote::new("Archived Note").tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
-        env.build_index().expect("Should build index");
+    fn test_drill_add_and_list_cards() {
+        let env = setup();
 
         env.cmd()
-            .ls()
-            .with_include_archived()
+            .args(["drill", "--add", "French"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Active Note"))
-            .stdout(predicate::str::contains("Archived Note"));
+            .stdout(predicate::str::contains(
+                "Added to the drill deck: 'French'",
+            ))
+            .stdout(predicate::str::contains("2 card(s)"));
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["drill", "--list", "--format", "json"])
+            .output_json();
+        let due = output["data"].as_array().unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0]["card"], "bonjour");
+        assert!(due[0]["due"].is_null());
     }
 
     #[test]
-    fn test_ls_archived_only_via_tag_filter() {
-        let env = TestEnv::new();
-
-        let active = TestNote::new("Active Note");
-        let archived = TestNote::new("Archived Note").tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
-        env.build_index().expect("Should build index");
+    fn test_drill_schedules_graded_cards() {
+        let env = setup();
+        env.cmd()
+            .args(["drill", "--add", "French"])
+            .assert()
+            .success();
 
-        // Using --tag archived with --include-archived shows only archived
         env.cmd()
-            .ls()
-            .with_include_archived()
-            .with_tag("archived")
+            .args(["drill"])
+            .stdin("\n4\nq\n")
             .assert()
             .success()
-            .stdout(predicate::str::contains("Archived Note"))
-            .stdout(predicate::str::contains("Active Note").not());
+            .stdout(predicate::str::contains("bonjour"))
+            .stdout(predicate::str::contains("hello"))
+            .stdout(predicate::str::contains("Next review in 4 day(s)"))
+            .stdout(predicate::str::contains("Reviewed 1 of 2 due item(s)"));
+
+        let output: serde_json::Value = env
+            .cmd()
+            .args(["drill", "--list", "--format", "json"])
+            .output_json();
+        let due = output["data"].as_array().unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0]["card"], "merci");
     }
 
     #[test]
-    fn test_ls_all_archived_shows_empty() {
-        let env = TestEnv::new();
-
-        // Only archived notes in the vault
-        let archived1 = TestNote::new("Archived One").tag("archived");
-        let archived2 = TestNote::new("Archived Two").tag("archived");
-        env.add_note(&archived1);
-        env.add_note(&archived2);
-        env.build_index().expect("Should build index");
+    fn test_drill_with_empty_deck() {
+        let env = setup();
 
-        // Default ls should show no notes
         env.cmd()
-            .ls()
+            .args(["drill"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Archived One").not())
-            .stdout(predicate::str::contains("Archived Two").not());
+            .stdout(predicate::str::contains("Nothing to drill today."));
     }
+}
+
+// ===========================================
+// quick-search command tests
+// ===========================================
+
+mod quick_search_tests {
+    use super::*;
 
     #[test]
-    fn test_ls_topic_filter_respects_archived() {
+    fn test_quick_search_prints_compact_json_array() {
         let env = TestEnv::new();
-
-        let active = TestNote::new("Active Software Note").topic("software");
-        let archived = TestNote::new("Archived Software Note")
-            .topic("software")
-            .tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
+        env.add_note(
+            &TestNote::new("API Design")
+                .id("01HQ3K5M7NXJK4QZPW8V2R6T9Y")
+                .body("Resources and verbs"),
+        );
+        env.add_note(&TestNote::new("Old API").tag("archived"));
         env.build_index().expect("Should build index");
 
-        // Topic filter should also exclude archived
-        env.cmd()
-            .ls()
-            .args(["software"])
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Active Software Note"))
-            .stdout(predicate::str::contains("Archived Software Note").not());
+        let output = env.cmd().args(["quick-search", "api des"]).output_success();
+        assert_eq!(output.lines().count(), 1);
+
+        let hits: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["id"], "01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        assert_eq!(hits[0]["title"], "API Design");
+        assert!(
+            hits[0]["path"]
+                .as_str()
+                .unwrap()
+                .ends_with("-api-design.md")
+        );
+        assert!(hits[0]["score"].is_f64());
+        // Only the title matched, so there's no body excerpt
+        assert!(hits[0]["snippet"].is_null());
     }
 
     #[test]
-    fn test_ls_json_excludes_archived() {
+    fn test_quick_search_matches_body_text() {
         let env = TestEnv::new();
-
-        let active = TestNote::new("Active JSON Note");
-        let archived = TestNote::new("Archived JSON Note").tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
+        env.add_note(&TestNote::new("Cluster").body("We run pods on nodes."));
         env.build_index().expect("Should build index");
 
-        let output: serde_json::Value = env.cmd().ls().format_json().output_json();
-
-        let data = output.get("data").expect("Should have 'data' field");
-        let items = data.as_array().expect("data should be array");
+        let output = env.cmd().args(["quick-search", "pods"]).output_success();
 
-        // Should only have one item (the active note)
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0]["title"], "Active JSON Note");
+        let hits: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let hits = hits.as_array().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["title"], "Cluster");
+        assert_eq!(hits[0]["snippet"], "We run pods on nodes.");
     }
 
     #[test]
-    fn test_search_excludes_archived_by_default() {
+    fn test_quick_search_empty_query_prints_empty_array() {
         let env = TestEnv::new();
-
-        let active = TestNote::new("Active Rust Note").body("Programming in Rust");
-        let archived = TestNote::new("Archived Rust Note")
-            .tag("archived")
-            .body("Old Rust content");
-        env.add_note(&active);
-        env.add_note(&archived);
+        env.add_note(&TestNote::new("API Design"));
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .search("Rust")
+            .args(["quick-search", ""])
             .assert()
             .success()
-            .stdout(predicate::str::contains("Active Rust Note"))
-            .stdout(predicate::str::contains("Archived Rust Note").not());
+            .stdout("[]\n");
+    }
+}
+
+// ===========================================
+// import generic tests
+// ===========================================
+
+mod import_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn zettlr_vault() -> TempDir {
+        let source = TempDir::new().unwrap();
+        std::fs::create_dir(source.path().join("rust")).unwrap();
+        std::fs::write(
+            source.path().join("rust/ownership.md"),
+            "---\ntitle: Ownership\nkeywords:\n  - rust\n  - memory\nstatus: draft\n---\n\n# Ownership\n\nBorrowing rules.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            source.path().join("inbox.md"),
+            "# Inbox\n\nLoose thoughts.\n",
+        )
+        .unwrap();
+        source
     }
 
     #[test]
-    fn test_search_include_archived() {
+    fn test_import_generic_maps_fields_and_indexes_notes() {
         let env = TestEnv::new();
+        let source = zettlr_vault();
 
-        let active = TestNote::new("Active Rust Note").body("Programming in Rust");
-        let archived = TestNote::new("Archived Rust Note")
-            .tag("archived")
-            .body("Old Rust content");
-        env.add_note(&active);
-        env.add_note(&archived);
-        env.build_index().expect("Should build index");
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["import", "generic"])
+            .args([source.path().to_str().unwrap()])
+            .args(["--map", "keywords=tags"])
+            .format_json()
+            .output_json();
 
-        env.cmd()
-            .search("Rust")
-            .with_include_archived()
-            .assert()
-            .success()
-            .stdout(predicate::str::contains("Active Rust Note"))
-            .stdout(predicate::str::contains("Archived Rust Note"));
+        assert_eq!(json["data"].as_array().unwrap().len(), 2);
+
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["ls", "--tag", "memory"])
+            .format_json()
+            .output_json();
+        let notes = json["data"].as_array().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0]["title"], "Ownership");
+
+        let path = env.notes_dir().join(notes[0]["path"].as_str().unwrap());
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.contains("status: draft"));
+        assert!(content.contains("- rust\n"));
+        assert!(content.contains("Borrowing rules."));
     }
 
     #[test]
-    fn test_search_json_excludes_archived() {
+    fn test_import_generic_dry_run_writes_nothing() {
         let env = TestEnv::new();
+        let source = zettlr_vault();
 
-        let active = TestNote::new("Active Findable Note");
-        let archived = TestNote::new("Archived Findable Note").tag("archived");
-        env.add_note(&active);
-        env.add_note(&archived);
-        env.build_index().expect("Should build index");
+        env.cmd()
+            .args(["import", "generic", "--dry-run"])
+            .args([source.path().to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would import 2 note(s)"));
 
-        let output: serde_json::Value = env.cmd().search("Findable").format_json().output_json();
+        let files = std::fs::read_dir(env.notes_dir()).unwrap().count();
+        assert_eq!(files, 0);
+    }
 
-        let data = output.get("data").expect("Should have 'data' field");
-        let items = data.as_array().expect("data should be array");
+    #[test]
+    fn test_import_generic_rejects_bad_mapping() {
+        let env = TestEnv::new();
+        let source = zettlr_vault();
 
-        // Should only find the active note
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0]["title"], "Active Findable Note");
+        env.cmd()
+            .args(["import", "generic"])
+            .args([source.path().to_str().unwrap()])
+            .args(["--map", "keywords"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("expected SRC=DST"));
     }
 }
 
 // ===========================================
-// export command tests
+// --profile tests
 // ===========================================
-mod export_tests {
+
+mod profile_tests {
     use super::*;
 
     #[test]
-    fn test_export_single_note_to_stdout() {
+    fn test_profile_prints_breakdown_to_stderr() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Export Test").body("# Hello World\n\nContent here.");
-        env.add_note(&note);
+        env.add_note(&TestNote::new("Profiled Note"));
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .export("Export Test")
+        let output = env
+            .cmd()
+            .args(["--profile", "ls"])
+            .format_json()
             .assert()
-            .success()
-            .stdout(predicate::str::contains("<!DOCTYPE html>"))
-            .stdout(predicate::str::contains("Export Test"))
-            .stdout(predicate::str::contains("Hello World"));
+            .success();
+
+        let stdout = String::from_utf8_lossy(&output.get_output().stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.get_output().stderr).to_string();
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(json["data"][0]["title"], "Profiled Note");
+        for label in [
+            "index open",
+            "query",
+            "file i/o",
+            "parsing",
+            "rendering",
+            "other",
+        ] {
+            assert!(stderr.contains(label), "missing '{}' in {}", label, stderr);
+        }
     }
 
     #[test]
-    fn test_export_by_id_prefix() {
+    fn test_no_profile_output_without_flag() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("ID Export Note").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
         env.build_index().expect("Should build index");
 
         env.cmd()
-            .export("01HQ3K5M7N")
+            .args(["ls"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("ID Export Note"));
+            .stderr(predicate::str::contains("profile:").not());
     }
+}
 
-    #[test]
-    fn test_export_to_file() {
-        let env = TestEnv::new();
-
-        let note = TestNote::new("File Export").body("Export content");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
-
-        let output_dir = env.notes_dir().join("export");
+// ===========================================
+// config validate tests
+// ===========================================
 
-        env.cmd()
-            .export("File Export")
-            .with_output(&output_dir)
-            .assert()
-            .success();
+mod config_validate_tests {
+    use super::*;
 
-        // Check that HTML file was created
-        let html_file = output_dir.join("file-export.html");
-        assert!(html_file.exists(), "HTML file should be created");
+    #[test]
+    fn test_config_validate_reports_issues_with_lines() {
+        let env = TestEnv::new();
+        let config = env.write_file(
+            "config.toml",
+            "editor = \"vim\"\n\n[time]\nutc = \"yes\"\nutcc = true\n",
+        );
 
-        let content = std::fs::read_to_string(html_file).unwrap();
-        assert!(content.contains("File Export"));
-        assert!(content.contains("Export content"));
+        env.cmd()
+            .args(["config", "validate", "--file", config.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("error: line 4: time.utc:"))
+            .stdout(predicate::str::contains(
+                "warning: line 5: time.utcc: unknown key",
+            ));
     }
 
     #[test]
-    fn test_export_with_custom_template() {
+    fn test_config_validate_json_for_valid_file() {
         let env = TestEnv::new();
+        let config = env.write_file("config.toml", "[time]\nutc = true\n");
 
-        let note = TestNote::new("Template Test").body("Body text");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["config", "validate", "--file", config.to_str().unwrap()])
+            .format_json()
+            .output_json();
 
-        let template =
-            env.write_file("custom.html", "<!DOCTYPE html><html><body>CUSTOM: {{ title }} - {{ content }}</body></html>");
+        assert_eq!(json["data"]["ok"], true);
+        assert_eq!(json["data"]["issues"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_config_validate_checks_aliases() {
+        let env = TestEnv::new();
+        let config = env.write_file(
+            "config.toml",
+            "[alias]\ninbox = \"ls inbox/ --untagged\"\nls = \"recent\"\nbad = \"search 'open\"\n",
+        );
 
         env.cmd()
-            .export("Template Test")
-            .with_template(&template)
+            .args(["config", "validate", "--file", config.to_str().unwrap()])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("CUSTOM: Template Test"))
-            .stdout(predicate::str::contains("Body text"));
+            .failure()
+            .stdout(predicate::str::contains(
+                "error: line 4: alias.bad: unclosed single quote",
+            ))
+            .stdout(predicate::str::contains(
+                "warning: line 3: alias.ls: 'ls' is a built-in command",
+            ))
+            .stdout(predicate::str::contains("alias.inbox").not());
     }
 
     #[test]
-    fn test_export_with_dark_theme() {
+    fn test_config_validate_checks_site_settings() {
         let env = TestEnv::new();
+        let config = env.write_file(
+            "config.toml",
+            "[export]\ndate_format = \"%Q\"\nrtl = true\n\n[export.labels]\nhome = \"Start\"\nhme = \"x\"\n",
+        );
 
-        let note = TestNote::new("Dark Theme Test");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
-
-        // Dark theme now returns unified CSS with automatic light/dark switching
         env.cmd()
-            .export("Dark Theme Test")
-            .with_theme("dark")
+            .args(["config", "validate", "--file", config.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "error: line 2: export.date_format: invalid strftime format '%Q'",
+            ))
+            .stdout(predicate::str::contains(
+                "warning: line 7: export.labels.hme: unknown key",
+            ))
+            .stdout(predicate::str::contains("export.labels.home").not());
+    }
+}
+
+// ===========================================
+// vaults sync-config tests
+// ===========================================
+
+mod sync_config_tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_config_round_trip() {
+        let team = TestEnv::new();
+        let team_config = team.write_file(
+            "team.toml",
+            "[vaults]\n\
+             work = \"/home/lead/work\"\n\
+             \n\
+             [types.meeting]\n\
+             template = \"templates/meeting.md\"\n\
+             required = [\"date\"]\n",
+        );
+        std::fs::create_dir(team.notes_dir().join("templates")).unwrap();
+        team.write_file("templates/meeting.md", "## Attendees\n");
+        let bundle = team.notes_dir().join("profile.toml");
+        team.cmd()
+            .args(["vaults", "sync-config", "--export"])
+            .args([bundle.to_str().unwrap(), "--config"])
+            .args([team_config.to_str().unwrap()])
             .assert()
             .success()
-            .stdout(predicate::str::contains("prefers-color-scheme: dark"))
-            .stdout(predicate::str::contains("--color-bg: #0d1117")); // Dark mode background
+            .stdout(predicate::str::contains("Exported profile to"));
+
+        let member = TestEnv::new();
+        let member_config = member.write_file("mine.toml", "[output]\ntheme = \"plain\"\n");
+        let import = |extra: &[&str]| -> serde_json::Value {
+            member
+                .cmd()
+                .args(["vaults", "sync-config", "--import"])
+                .args([bundle.to_str().unwrap(), "--config"])
+                .args([member_config.to_str().unwrap()])
+                .args(extra)
+                .format_json()
+                .output_json()
+        };
+
+        let json = import(&[]);
+        assert_eq!(json["data"]["sections"], serde_json::json!(["types"]));
+        assert_eq!(json["data"]["templates"][0]["status"], "written");
+        let config = std::fs::read_to_string(&member_config).unwrap();
+        assert!(config.contains("theme = \"plain\""));
+        assert!(config.contains("[types.meeting]"));
+        assert!(!config.contains("/home/lead/work"));
+        let template = member.notes_dir().join("templates/meeting.md");
+        assert_eq!(
+            std::fs::read_to_string(&template).unwrap(),
+            "## Attendees\n"
+        );
+
+        // Local edits to a template survive unless forced
+        std::fs::write(&template, "## Who\n").unwrap();
+        assert_eq!(import(&[])["data"]["templates"][0]["status"], "skipped");
+        assert_eq!(std::fs::read_to_string(&template).unwrap(), "## Who\n");
+        assert_eq!(
+            import(&["--force"])["data"]["templates"][0]["status"],
+            "written"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&template).unwrap(),
+            "## Attendees\n"
+        );
     }
 
     #[test]
-    fn test_export_with_custom_css() {
+    fn test_sync_config_requires_a_direction() {
         let env = TestEnv::new();
+        env.cmd().args(["vaults", "sync-config"]).assert().failure();
+    }
+}
 
-        let note = TestNote::new("Custom CSS Test");
-        env.add_note(&note);
+// ===========================================
+// trash command tests
+// ===========================================
+
+mod trash_tests {
+    use super::*;
+
+    #[test]
+    fn test_trash_lists_and_purges() {
+        let env = TestEnv::new();
+        env.add_note(&TestNote::new("Kept"));
+        std::fs::create_dir(env.notes_dir().join(".trash")).unwrap();
+        env.write_file(
+            ".trash/01HQ3K5M7N-gone.md",
+            "---\n\
+             id: 01HQ3K5M7NXJK4QZPW8V2R6T9Y\n\
+             title: Gone\n\
+             created: 2024-01-15T10:30:00Z\n\
+             modified: 2024-01-15T10:30:00Z\n\
+             ---\n\
+             Body\n",
+        );
         env.build_index().expect("Should build index");
 
-        let css = env.write_file("custom.css", "body { color: purple; }");
+        let json: serde_json::Value = env.cmd().args(["trash"]).format_json().output_json();
+        assert_eq!(json["data"][0]["title"], "Gone");
+        assert_eq!(json["data"][0]["path"], ".trash/01HQ3K5M7N-gone.md");
 
+        // Just trashed, so not old enough yet
         env.cmd()
-            .export("Custom CSS Test")
-            .with_theme(css.to_str().unwrap())
+            .args(["trash", "purge", "--older-than", "1d"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("color: purple"));
-    }
+            .stdout(predicate::str::contains("Purged 0 note(s)"));
 
-    #[test]
-    fn test_export_includes_topics() {
-        let env = TestEnv::new();
+        env.cmd()
+            .args(["trash", "purge", "--all", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would purge 1 note(s)"));
+        assert!(env.notes_dir().join(".trash/01HQ3K5M7N-gone.md").exists());
 
-        let note = TestNote::new("Topic Note").topic("software/rust");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        env.cmd()
+            .args(["trash", "purge", "--all"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Purged 1 note(s)"));
+        assert!(!env.notes_dir().join(".trash/01HQ3K5M7N-gone.md").exists());
 
         env.cmd()
-            .export("Topic Note")
+            .args(["trash"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("software/rust"));
+            .stdout(predicate::str::contains("The trash is empty."));
     }
 
     #[test]
-    fn test_export_includes_tags() {
+    fn test_rm_moves_note_to_trash_and_restore_brings_it_back() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Tagged Note").tag("important").tag("draft");
-        env.add_note(&note);
+        let path = env.add_note(
+            &TestNote::new("Doomed")
+                .id("01HQ5B3S0QYJK5RAQX9W3S7T0Z")
+                .topic("software"),
+        );
+        env.add_note(
+            &TestNote::new("Source")
+                .topic("software")
+                .link("01HQ5B3S0QYJK5RAQX9W3S7T0Z", &["see-also"]),
+        );
         env.build_index().expect("Should build index");
+        let filename = path.file_name().unwrap().to_str().unwrap().to_string();
 
         env.cmd()
-            .export("Tagged Note")
+            .args(["rm", "Doomed"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("important"))
-            .stdout(predicate::str::contains("draft"));
+            .stdout(predicate::str::contains("Moved 'Doomed'"))
+            .stderr(predicate::str::contains("1 note(s) still reference 'Doomed'"))
+            .stderr(predicate::str::contains("Source"));
+        assert!(!path.exists());
+        assert!(env.notes_dir().join(".trash").join(&filename).exists());
+        env.cmd().args(["show", "Doomed"]).assert().failure();
+
+        let json: serde_json::Value = env.cmd().args(["trash"]).format_json().output_json();
+        assert_eq!(json["data"][0]["title"], "Doomed");
+
+        env.cmd()
+            .args(["trash", "restore", "01HQ5B3S0Q"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Restored 'Doomed'"));
+        assert!(path.exists());
+        env.cmd().args(["show", "Doomed"]).assert().success();
+        env.cmd()
+            .args(["trash"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("The trash is empty."));
     }
 
     #[test]
-    fn test_export_note_not_found() {
+    fn test_rm_force_deletes_the_note() {
         let env = TestEnv::new();
+        let path = env.add_note(&TestNote::new("Gone"));
         env.build_index().expect("Should build index");
 
-        env.cmd()
-            .export("nonexistent")
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("not found"));
+        // Stdin isn't a terminal, so nothing is asked
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["rm", "Gone", "--force"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["title"], "Gone");
+        assert!(json["data"]["trashed_to"].is_null());
+        assert!(!path.exists());
+        assert!(!env.notes_dir().join(".trash").exists());
+        env.cmd().args(["show", "Gone"]).assert().failure();
     }
 
     #[test]
-    fn test_export_markdown_converted() {
+    fn test_rm_reads_notes_from_stdin() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Markdown Test")
-            .body("# Heading\n\n**Bold** and *italic*\n\n- List item");
-        env.add_note(&note);
+        let first = env.add_note(&TestNote::new("Old One").tag("stale"));
+        let second = env.add_note(&TestNote::new("Old Two").tag("stale"));
+        env.add_note(&TestNote::new("Kept"));
         env.build_index().expect("Should build index");
 
+        let paths = env
+            .cmd()
+            .ls()
+            .with_tag("stale")
+            .format_paths()
+            .output_success();
+
+        // The notes come from stdin, so --force can't ask first
         env.cmd()
-            .export("Markdown Test")
+            .args(["rm", "-", "--force"])
+            .stdin(paths.clone())
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--yes"));
+        assert!(first.exists());
+
+        env.cmd()
+            .args(["rm", "-"])
+            .stdin(paths)
             .assert()
             .success()
-            .stdout(predicate::str::contains("<h1>Heading</h1>"))
-            .stdout(predicate::str::contains("<strong>Bold</strong>"))
-            .stdout(predicate::str::contains("<em>italic</em>"))
-            .stdout(predicate::str::contains("<li>List item</li>"));
+            .stdout(predicate::str::contains("Moved 'Old One'"))
+            .stdout(predicate::str::contains(
+                "2 note(s): 2 changed, 0 unchanged, 0 failed",
+            ));
+        assert!(!first.exists());
+        assert!(!second.exists());
+
+        let listed = env.cmd().ls().output_success();
+        assert!(!listed.contains("Old"));
+        assert!(listed.contains("Kept"));
     }
 
     #[test]
-    fn test_export_code_blocks() {
+    fn test_trash_restore_refuses_to_overwrite() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Code Test").body("```rust\nfn main() {}\n```");
-        env.add_note(&note);
+        let path = env.add_note(&TestNote::new("Twice"));
         env.build_index().expect("Should build index");
+        let contents = std::fs::read_to_string(&path).unwrap();
+
+        env.cmd().args(["rm", "Twice"]).assert().success();
+        std::fs::write(&path, &contents).unwrap();
 
         env.cmd()
-            .export("Code Test")
+            .args(["trash", "restore", "Twice"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("<pre>"))
-            .stdout(predicate::str::contains("fn main()"));
+            .failure()
+            .stderr(predicate::str::contains("already exists"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), contents);
     }
 
     #[test]
-    fn test_export_json_output() {
+    fn test_trash_restore_uses_original_name_of_numbered_note() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("JSON Output Test").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        env.add_note(&note);
+        let first = TestNote::new("Draft").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
+        let path = env.add_note(&first);
         env.build_index().expect("Should build index");
+        let filename = path.file_name().unwrap().to_str().unwrap().to_string();
+        env.cmd().args(["rm", "Draft"]).assert().success();
 
-        let output_dir = env.notes_dir().join("export");
-
-        let output: serde_json::Value = env
+        // A second note under the same file name is numbered in the trash
+        env.write_file(
+            &filename,
+            "---\nid: 01HQ4A2R9PXJK4QZPW8V2R6T9Y\ntitle: Draft\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\nSecond\n",
+        );
+        env.build_index().expect("Should build index");
+        let json: serde_json::Value = env
             .cmd()
-            .export("JSON Output Test")
-            .with_output(&output_dir)
-            .cli_format_json()
+            .args(["rm", "01HQ4A2R9P"])
+            .format_json()
             .output_json();
+        let numbered = json["data"]["trashed_to"].as_str().unwrap().to_string();
+        assert!(numbered.ends_with("-2.md"));
 
-        assert!(output.is_object(), "Output should be a JSON object");
-        let data = output.get("data").expect("Should have 'data' field");
-        assert_eq!(data["notes_exported"], 1);
-        assert!(data["path"].as_str().is_some());
-        assert!(data["title"].as_str().unwrap().contains("JSON Output Test"));
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["trash", "restore", "01HQ4A2R9P"])
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"]["path"], filename);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Second"));
+        assert!(
+            !env.notes_dir()
+                .join(format!("{}.origin", numbered))
+                .exists()
+        );
     }
 
     #[test]
-    fn test_export_pdf_not_implemented() {
+    fn test_trash_purge_requires_an_age_or_all() {
         let env = TestEnv::new();
+        env.cmd().args(["trash", "purge"]).assert().failure();
+    }
+}
 
-        let note = TestNote::new("PDF Test");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+// ===========================================
+// Recover Tests
+// ===========================================
 
-        env.cmd()
-            .export("PDF Test")
-            .export_format_pdf()
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("not yet implemented"));
+mod recover_tests {
+    use super::*;
+
+    /// Leaves behind the journal of a `mv` that crashed after writing the
+    /// renamed file but before removing the old one.
+    fn interrupt_rename(env: &TestEnv, old: &std::path::Path) -> std::path::PathBuf {
+        let contents = std::fs::read_to_string(old).unwrap();
+        let renamed = env.notes_dir().join("01HQ3K5M7N-renamed.md");
+        std::fs::write(&renamed, &contents).unwrap();
+        let journal = serde_json::json!({
+            "operation": "mv",
+            "started": "2024-01-15T10:30:00Z",
+            "changes": [
+                {"path": "01HQ3K5M7N-renamed.md", "before": null, "after": contents},
+                {
+                    "path": old.file_name().unwrap().to_str().unwrap(),
+                    "before": contents,
+                    "after": null
+                },
+            ],
+        });
+        std::fs::write(
+            env.notes_dir().join(".index/journal.json"),
+            journal.to_string(),
+        )
+        .unwrap();
+        renamed
     }
 
     #[test]
-    fn test_export_site_requires_all() {
+    fn test_recover_rolls_back_an_interrupted_rename() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Site Test");
-        env.add_note(&note);
+        let old = env.add_note(&TestNote::new("Old"));
+        env.add_note(&TestNote::new("Other"));
         env.build_index().expect("Should build index");
+        let renamed = interrupt_rename(&env, &old);
 
         env.cmd()
-            .export("Site Test")
-            .export_format_site()
+            .args(["recover"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Interrupted 'mv'"))
+            .stdout(predicate::str::contains("create 01HQ3K5M7N-renamed.md"));
+        env.cmd()
+            .mv("Other")
+            .args(["--title", "Another"])
             .assert()
             .failure()
-            .stderr(predicate::str::contains("--all"));
+            .stderr(predicate::str::contains("run `notes recover` first"));
+
+        env.cmd()
+            .args(["recover", "--rollback"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Rolled back 'mv' (2 file(s))"));
+        assert!(old.exists());
+        assert!(!renamed.exists());
+        env.cmd()
+            .args(["recover"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("No interrupted operation"));
+        env.cmd()
+            .mv("Other")
+            .args(["--title", "Another"])
+            .assert()
+            .success();
     }
 
     #[test]
-    fn test_export_ambiguous_note() {
+    fn test_recover_resumes_an_interrupted_rename() {
         let env = TestEnv::new();
-
-        // Two notes with same title
-        let note1 = TestNote::new("Duplicate Title").id("01HQ3K5M7NXJK4QZPW8V2R6T9Y");
-        let note2 = TestNote::new("Duplicate Title").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        env.add_note(&note1);
-        env.add_note(&note2);
+        let old = env.add_note(&TestNote::new("Old"));
         env.build_index().expect("Should build index");
+        let renamed = interrupt_rename(&env, &old);
 
-        env.cmd()
-            .export("Duplicate Title")
-            .assert()
-            .failure()
-            .stderr(predicate::str::contains("Ambiguous"));
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["recover", "--resume"])
+            .format_json()
+            .output_json();
+
+        assert_eq!(json["data"]["status"], "resumed");
+        assert!(!old.exists());
+        assert!(renamed.exists());
+        assert!(!env.notes_dir().join(".index/journal.json").exists());
     }
+}
 
-    // ===========================================
-    // Bulk Export Tests
-    // ===========================================
+// ===========================================
+// Top Tests
+// ===========================================
 
-    #[test]
-    fn test_export_all_html() {
-        let env = TestEnv::new();
+mod top_tests {
+    use super::*;
 
-        env.add_note(&TestNote::new("Note One").body("Content one"));
-        env.add_note(&TestNote::new("Note Two").body("Content two"));
-        env.add_note(&TestNote::new("Note Three").body("Content three"));
-        env.build_index().expect("Should build index");
+    /// A hub linked from two notes (once from itself), and a spoke linking
+    /// to the hub and the other spoke.
+    fn linked_env() -> TestEnv {
+        let env = TestEnv::new();
+        let hub = TestNote::new("Hub").topic("software").tag("core");
+        let leaf = TestNote::new("Leaf")
+            .topic("software")
+            .tag("core")
+            .tag("draft")
+            .link(hub.note_id().to_string(), &["see-also"]);
+        let spoke = TestNote::new("Spoke")
+            .topic("reading")
+            .link(hub.note_id().to_string(), &["see-also"])
+            .link(leaf.note_id().to_string(), &["see-also"]);
+        let hub_id = hub.note_id().to_string();
+        let hub = hub.link(hub_id, &["see-also"]);
+        env.add_note(&hub);
+        env.add_note(&leaf);
+        env.add_note(&spoke);
+        env.build_index().expect("Should build index");
+        env
+    }
+
+    fn ranking(env: &TestEnv, args: &[&str]) -> Vec<(String, u64)> {
+        let json: serde_json::Value = env.cmd().args(args).format_json().output_json();
+        json["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| {
+                (
+                    n["title"].as_str().unwrap().to_string(),
+                    n["count"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_top_ranks_by_backlinks_links_and_tags() {
+        let env = linked_env();
+
+        assert_eq!(
+            ranking(&env, &["top"]),
+            vec![("Hub".to_string(), 2), ("Leaf".to_string(), 1)]
+        );
+        assert_eq!(
+            ranking(&env, &["top", "--by", "links"]),
+            vec![("Spoke".to_string(), 2), ("Leaf".to_string(), 1)]
+        );
+        assert_eq!(
+            ranking(&env, &["top", "--by", "tags", "-n", "1"]),
+            vec![("Leaf".to_string(), 2)]
+        );
+    }
 
-        let output_dir = env.notes_dir().join("export");
+    #[test]
+    fn test_top_filters_by_topic_and_tag() {
+        let env = linked_env();
 
+        assert_eq!(
+            ranking(&env, &["top", "software", "--tag", "draft"]),
+            vec![("Leaf".to_string(), 1)]
+        );
         env.cmd()
-            .export_all()
-            .with_output(&output_dir)
+            .args(["top", "reading"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("No notes with backlinks."));
+        env.cmd()
+            .args(["top", "--by", "links"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Top 2 of 3 note(s) by links"));
+    }
+}
+
+// ===========================================
+// graph command tests
+// ===========================================
+
+mod graph_tests {
+    use super::*;
+
+    /// A chain "Alpha" -> "Beta" -> "Gamma" -> "Delta" in topic software,
+    /// and a note in another topic linking to Alpha.
+    fn chain_env() -> (TestEnv, Vec<String>) {
+        let env = TestEnv::new();
+        let delta = TestNote::new("Delta").topic("software");
+        let gamma = TestNote::new("Gamma")
+            .topic("software")
+            .link(delta.note_id().to_string(), &["see-also"]);
+        let beta = TestNote::new("Beta")
+            .topic("software")
+            .link(gamma.note_id().to_string(), &["parent"]);
+        let alpha = TestNote::new("Alpha")
+            .topic("software")
+            .link(beta.note_id().to_string(), &["parent", "see-also"]);
+        let other = TestNote::new("The \"Other\" Note")
+            .topic("reading")
+            .link(alpha.note_id().to_string(), &["see-also"]);
+        let ids = [&alpha, &beta, &gamma, &delta, &other]
+            .iter()
+            .map(|n| n.note_id().to_string())
+            .collect();
+        for note in [&alpha, &beta, &gamma, &delta, &other] {
+            env.add_note(note);
+        }
+        env.build_index().expect("Should build index");
+        (env, ids)
+    }
 
-        // Check all files exist
-        assert!(output_dir.join("note-one.html").exists());
-        assert!(output_dir.join("note-two.html").exists());
-        assert!(output_dir.join("note-three.html").exists());
+    fn node_titles(json: &serde_json::Value) -> Vec<&str> {
+        json["data"]["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["title"].as_str().unwrap())
+            .collect()
     }
 
     #[test]
-    fn test_export_all_requires_output() {
-        let env = TestEnv::new();
-
-        env.add_note(&TestNote::new("Test Note"));
-        env.build_index().expect("Should build index");
+    fn test_graph_dot_and_graphml() {
+        let (env, ids) = chain_env();
 
         env.cmd()
-            .export_all()
+            .args(["graph"])
             .assert()
-            .failure()
-            .stderr(predicate::str::contains("--output"));
+            .success()
+            .stdout(predicate::str::starts_with("digraph notes {"))
+            .stdout(predicate::str::contains(format!(
+                "\"{}\" -> \"{}\" [label=\"parent, see-also\"];",
+                ids[0], ids[1]
+            )))
+            .stdout(predicate::str::contains(format!(
+                "\"{}\" -> \"{}\" [label=\"see-also\"];",
+                ids[4], ids[0]
+            )))
+            .stdout(predicate::str::contains(
+                "[label=\"The \\\"Other\\\" Note\"]",
+            ));
+
+        let out = env.notes_dir().join("out/graph.graphml");
+        env.cmd()
+            .args(["graph", "software", "-F", "graphml", "-o"])
+            .args([out.to_str().unwrap()])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Wrote graph of 4 note(s) and 3 link(s)",
+            ));
+        let graphml = std::fs::read_to_string(&out).unwrap();
+        assert!(graphml.contains(&format!(
+            "<edge source=\"{}\" target=\"{}\"><data key=\"rels\">parent</data></edge>",
+            ids[1], ids[2]
+        )));
+        assert!(!graphml.contains("Other"), "{}", graphml);
     }
 
     #[test]
-    fn test_export_all_by_topic() {
-        let env = TestEnv::new();
+    fn test_graph_limits_to_hops_from_a_note() {
+        let (env, ids) = chain_env();
 
-        env.add_note(&TestNote::new("Rust Note").topic("software/rust"));
-        env.add_note(&TestNote::new("Python Note").topic("software/python"));
-        env.add_note(&TestNote::new("Other Note").topic("other"));
-        env.build_index().expect("Should build index");
+        let json: serde_json::Value = env
+            .cmd()
+            .args(["graph", "--from", "Beta", "-F", "json"])
+            .output_json();
+        assert_eq!(node_titles(&json), vec!["Alpha", "Beta", "Gamma"]);
+        assert_eq!(json["data"]["edges"][0]["source"], ids[0].as_str());
+        assert_eq!(json["data"]["edges"][0]["rels"][1], "see-also");
 
-        let output_dir = env.notes_dir().join("export");
+        let json: serde_json::Value = env
+            .cmd()
+            .args([
+                "graph", "software", "--from", "Delta", "--hops", "3", "-F", "json",
+            ])
+            .output_json();
+        assert_eq!(node_titles(&json), vec!["Alpha", "Beta", "Delta", "Gamma"]);
 
         env.cmd()
-            .export_all()
-            .with_topic("software/")
-            .with_output(&output_dir)
+            .args(["graph", "--hops", "2"])
             .assert()
-            .success();
-
-        // Only software notes should be exported
-        assert!(output_dir.join("rust-note.html").exists());
-        assert!(output_dir.join("python-note.html").exists());
-        assert!(!output_dir.join("other-note.html").exists());
+            .failure()
+            .stderr(predicate::str::contains("--from"));
     }
+}
 
-    #[test]
-    fn test_export_all_by_tag() {
-        let env = TestEnv::new();
-
-        env.add_note(&TestNote::new("Tagged Note").tag("important"));
-        env.add_note(&TestNote::new("Untagged Note"));
-        env.build_index().expect("Should build index");
+// ===========================================
+// daily command tests
+// ===========================================
 
-        let output_dir = env.notes_dir().join("export");
+mod daily_tests {
+    use super::*;
 
+    fn daily_json(env: &TestEnv, args: &[&str]) -> serde_json::Value {
         env.cmd()
-            .export_all()
-            .with_tag("important")
-            .with_output(&output_dir)
-            .assert()
-            .success();
-
-        assert!(output_dir.join("tagged-note.html").exists());
-        assert!(!output_dir.join("untagged-note.html").exists());
+            .args(["daily", "--no-edit"])
+            .args(args)
+            .format_json()
+            .output_json()
     }
 
     #[test]
-    fn test_export_all_excludes_archived() {
+    fn test_daily_creates_then_finds_the_days_note() {
         let env = TestEnv::new();
 
-        env.add_note(&TestNote::new("Active Note"));
-        env.add_note(&TestNote::new("Archived Note").tag("archived"));
-        env.build_index().expect("Should build index");
+        let first = daily_json(&env, &["--date", "2024-06-03"]);
+        assert_eq!(first["data"]["created"], true);
+        assert_eq!(first["data"]["title"], "2024-06-03");
+        assert_eq!(
+            first["data"]["topics"],
+            serde_json::json!(["journal/2024/06"])
+        );
+        assert_eq!(first["data"]["tags"], serde_json::json!(["journal"]));
 
-        let output_dir = env.notes_dir().join("export");
+        let second = daily_json(&env, &["--date", "2024-06-03"]);
+        assert_eq!(second["data"]["created"], false);
+        assert_eq!(second["data"]["id"], first["data"]["id"]);
 
         env.cmd()
-            .export_all()
-            .with_output(&output_dir)
+            .args(["daily", "--date", "2024-06-03", "--no-edit"])
             .assert()
-            .success();
-
-        assert!(output_dir.join("active-note.html").exists());
-        assert!(!output_dir.join("archived-note.html").exists());
+            .success()
+            .stdout(predicate::str::contains("Exists: 2024-06-03"));
+        env.cmd()
+            .ls()
+            .args(["journal/"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("2024-06-03"));
     }
 
     #[test]
-    fn test_export_all_include_archived() {
+    fn test_daily_offsets_and_invalid_dates() {
         let env = TestEnv::new();
 
-        env.add_note(&TestNote::new("Active Note"));
-        env.add_note(&TestNote::new("Archived Note").tag("archived"));
-        env.build_index().expect("Should build index");
-
-        let output_dir = env.notes_dir().join("export");
+        let day = |args: &[&str]| {
+            let json = daily_json(&env, args);
+            let title = json["data"]["title"].as_str().unwrap().to_string();
+            chrono::NaiveDate::parse_from_str(&title, "%Y-%m-%d").unwrap()
+        };
+        let yesterday = day(&["--yesterday"]);
+        let tomorrow = day(&["--tomorrow"]);
+        assert_eq!((tomorrow - yesterday).num_days(), 2);
 
         env.cmd()
-            .export_all()
-            .with_output(&output_dir)
-            .with_include_archived()
+            .args(["daily", "--date", "2024-13-01"])
             .assert()
-            .success();
-
-        assert!(output_dir.join("active-note.html").exists());
-        assert!(output_dir.join("archived-note.html").exists());
+            .failure()
+            .stderr(predicate::str::contains("invalid --date '2024-13-01'"));
+        env.cmd()
+            .args(["daily", "--date", "2024-06-03", "--tomorrow"])
+            .assert()
+            .failure();
     }
 
     #[test]
-    fn test_export_site_generates_index() {
+    fn test_daily_finds_the_note_whatever_its_tags_and_unindexed() {
         let env = TestEnv::new();
+        // Written by hand and not indexed yet, without the journal tag
+        let note = TestNote::new("2024-06-03").topic("journal/2024/06");
+        env.add_note(&note);
 
-        env.add_note(&TestNote::new("Site Note One"));
-        env.add_note(&TestNote::new("Site Note Two"));
-        env.build_index().expect("Should build index");
+        let json = daily_json(&env, &["--date", "2024-06-03"]);
+        assert_eq!(json["data"]["created"], false);
+        assert_eq!(json["data"]["id"], note.note_id().to_string());
+    }
 
-        let output_dir = env.notes_dir().join("site");
+    #[cfg(unix)]
+    #[test]
+    fn test_daily_opens_the_editor_by_default() {
+        use std::os::unix::fs::PermissionsExt;
 
-        env.cmd()
-            .export_all()
-            .export_format_site()
-            .with_output(&output_dir)
-            .assert()
-            .success();
+        let env = TestEnv::new();
+        let editor = env.write_file("editor.sh", "#!/bin/sh\necho 'Wrote this.' >> \"$1\"\n");
+        std::fs::set_permissions(&editor, std::fs::Permissions::from_mode(0o755)).unwrap();
 
-        // Check index and notes exist
-        let index_path = output_dir.join("index.html");
-        assert!(index_path.exists());
+        let path = env
+            .cmd()
+            .args(["daily", "--date", "2024-06-03"])
+            .format_paths()
+            .env("EDITOR", editor.to_string_lossy())
+            .output_success();
+        let content = std::fs::read_to_string(path.trim()).unwrap();
+        assert!(content.contains("Wrote this."));
+    }
+}
 
-        let index_content = std::fs::read_to_string(index_path).unwrap();
-        assert!(index_content.contains("Site Note One"));
-        assert!(index_content.contains("Site Note Two"));
+// ===========================================
+// migrate command tests
+// ===========================================
+mod migrate_tests {
+    use super::*;
 
-        // Check CSS file
-        assert!(output_dir.join("style.css").exists());
+    fn add_zettlr_notes(env: &TestEnv) {
+        env.write_file(
+            "01HQ3K5M7N-ownership.md",
+            "---\nid: 01HQ3K5M7NXJK4QZPW8V2R6TAA\ntitle: Ownership\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\ntags:\n- rust\nkeywords: memory, borrowing\ncategory: software\n---\n\nBorrowing rules.\n",
+        );
+        env.write_file(
+            "01HQ3K5M7P-inbox.md",
+            "---\nid: 01HQ3K5M7PXJK4QZPW8V2R6TAB\ntitle: Inbox\ncreated: 2024-01-15T10:30:00Z\nmodified: 2024-01-15T10:30:00Z\n---\n\nLoose thoughts.\n",
+        );
     }
 
     #[test]
-    fn test_export_site_with_topics() {
+    fn test_migrate_renames_keys_and_updates_index() {
         let env = TestEnv::new();
-
-        env.add_note(&TestNote::new("Rust Note").topic("software/rust"));
-        env.add_note(&TestNote::new("Python Note").topic("software/python"));
-        env.build_index().expect("Should build index");
-
-        let output_dir = env.notes_dir().join("site");
+        add_zettlr_notes(&env);
+        env.build_index().unwrap();
 
         env.cmd()
-            .export_all()
-            .export_format_site()
-            .with_output(&output_dir)
+            .args(["migrate", "--rename", "keywords=tags"])
+            .args(["--rename", "category=topics"])
             .assert()
-            .success();
+            .success()
+            .stdout(predicate::str::contains("keywords -> tags"))
+            .stdout(predicate::str::contains("Migrated 1 note(s)"));
 
-        // Check topic directories exist
-        assert!(output_dir.join("software").join("rust").exists());
-        assert!(output_dir.join("software").join("python").exists());
+        let content =
+            std::fs::read_to_string(env.notes_dir().join("01HQ3K5M7N-ownership.md")).unwrap();
+        assert!(!content.contains("keywords"));
+        assert!(!content.contains("category"));
+        assert!(content.contains("- borrowing\n"));
+        assert!(content.contains("Borrowing rules."));
 
-        // Check topic index pages
-        assert!(
-            output_dir
-                .join("software")
-                .join("rust")
-                .join("index.html")
-                .exists()
-        );
+        let json: serde_json::Value = env
+            .cmd()
+            .ls()
+            .with_tag("memory")
+            .format_json()
+            .output_json();
+        assert_eq!(json["data"].as_array().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_export_json_bulk_output() {
+    fn test_migrate_json_lists_changes() {
         let env = TestEnv::new();
+        add_zettlr_notes(&env);
 
-        env.add_note(&TestNote::new("Bulk Note One"));
-        env.add_note(&TestNote::new("Bulk Note Two"));
-        env.build_index().expect("Should build index");
-
-        let output_dir = env.notes_dir().join("export");
-
-        let output: serde_json::Value = env
+        let json: serde_json::Value = env
             .cmd()
-            .export_all()
-            .with_output(&output_dir)
-            .cli_format_json()
+            .args(["migrate", "--dry-run"])
+            .args(["--rename", "keywords=tags", "--rename", "category="])
+            .format_json()
             .output_json();
-
-        let data = output.get("data").expect("Should have 'data' field");
-        assert_eq!(data["notes_exported"], 2);
-        assert!(data["path"].as_str().is_some());
+        let notes = json["data"].as_array().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0]["title"], "Ownership");
+        let changes = notes[0]["changes"].as_array().unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0]["from"], "category");
+        assert!(changes[0]["to"].is_null());
+        assert_eq!(changes[1]["to"], "tags");
     }
 
-    // ===========================================
-    // Link Resolution Tests
-    // ===========================================
-
     #[test]
-    fn test_export_resolves_internal_links() {
+    fn test_config_validate_checks_migrate_renames() {
         let env = TestEnv::new();
-
-        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Source Note").body("See [Target](01HQ4A2R9P) for more.");
-        env.add_note(&target);
-        env.add_note(&source);
-        env.build_index().expect("Should build index");
+        let config = env.write_file(
+            "config.toml",
+            "[migrate.rename]\nkeywords = \"tags\"\ntags = \"labels\"\n",
+        );
 
         env.cmd()
-            .export("Source Note")
-            .args(["--resolve-links"])
+            .args(["config", "validate", "--file", config.to_str().unwrap()])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("target-note.html"));
+            .failure()
+            .stdout(predicate::str::contains("migrate.rename.tags"));
     }
 
     #[test]
-    fn test_export_without_resolve_keeps_links() {
+    fn test_migrate_dry_run_writes_nothing() {
         let env = TestEnv::new();
+        add_zettlr_notes(&env);
+        let before = env.snapshot();
 
-        let target = TestNote::new("Target Note").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Source Note").body("See [Target](01HQ4A2R9P) for more.");
-        env.add_note(&target);
-        env.add_note(&source);
-        env.build_index().expect("Should build index");
-
-        // Without --resolve-links, the ID should remain unchanged
         env.cmd()
-            .export("Source Note")
+            .args(["migrate", "--dry-run", "--rename", "keywords=tags"])
             .assert()
             .success()
-            .stdout(predicate::str::contains("01HQ4A2R9P"));
+            .stdout(predicate::str::contains("Would migrate 1 note(s)"));
+
+        assert_eq!(env.snapshot(), before);
     }
 
     #[test]
-    fn test_export_preserves_external_links() {
+    fn test_migrate_aborts_on_conflicts() {
         let env = TestEnv::new();
-
-        let note =
-            TestNote::new("Link Note").body("Visit [example](https://example.com) for more.");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        add_zettlr_notes(&env);
+        let before = env.snapshot();
 
         env.cmd()
-            .export("Link Note")
-            .args(["--resolve-links"])
+            .args(["migrate", "--rename", "keywords=category"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("https://example.com"));
+            .failure()
+            .stderr(predicate::str::contains(
+                "would overwrite a different 'category'",
+            ))
+            .stderr(predicate::str::contains("no notes were changed"));
+
+        assert_eq!(env.snapshot(), before);
     }
 
     #[test]
-    fn test_export_marks_broken_links() {
+    fn test_migrate_requires_renames() {
         let env = TestEnv::new();
-
-        let note = TestNote::new("Broken Link Note")
-            .body("See [Missing](01HZZZZZZZZZ) for details.");
-        env.add_note(&note);
-        env.build_index().expect("Should build index");
+        add_zettlr_notes(&env);
 
         env.cmd()
-            .export("Broken Link Note")
-            .args(["--resolve-links"])
+            .args(["migrate"])
             .assert()
-            .success()
-            .stdout(predicate::str::contains("Broken link"));
+            .failure()
+            .stderr(predicate::str::contains("no renames given"));
     }
+}
+
+mod bench_tests {
+    use super::*;
 
     #[test]
-    fn test_export_bulk_resolves_links() {
+    fn test_bench_json_report() {
         let env = TestEnv::new();
 
-        let target = TestNote::new("Bulk Target").id("01HQ4A2R9PXJK4QZPW8V2R6T9Y");
-        let source = TestNote::new("Bulk Source").body("Link to [target](01HQ4A2R9P).");
-        env.add_note(&target);
-        env.add_note(&source);
-        env.build_index().expect("Should build index");
+        let output = env
+            .cmd()
+            .args(["bench", "--notes", "20", "--words", "50", "--runs", "1"])
+            .args(["-f", "json"])
+            .output_success();
+        let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(report["data"]["notes"], 20);
+        let names: Vec<&str> = report["data"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "full_rebuild",
+                "incremental_unchanged",
+                "incremental_changed",
+                "search",
+                "ls",
+                "ls_filtered",
+            ]
+        );
+    }
 
-        let output_dir = env.notes_dir().join("export");
+    #[test]
+    fn test_bench_fails_on_regression_against_baseline() {
+        let env = TestEnv::new();
+        let baseline = env.notes_dir().join("baseline.json");
+        std::fs::write(
+            &baseline,
+            r#"{"version": "0.0.0", "notes": 10, "words": 20, "runs": 1,
+                "results": [{"name": "full_rebuild", "median_ms": 0.000001,
+                             "min_ms": 0.000001, "per_second": 0.0}]}"#,
+        )
+        .unwrap();
 
         env.cmd()
-            .export_all()
-            .args(["--resolve-links"])
-            .with_output(&output_dir)
+            .args(["bench", "--notes", "10", "--words", "20", "--runs", "1"])
+            .args(["--baseline", baseline.to_str().unwrap()])
             .assert()
-            .success();
+            .failure()
+            .stdout(predicate::str::contains("Baseline"))
+            .stderr(predicate::str::contains(
+                "1 benchmark(s) more than 20% slower than the baseline",
+            ));
+    }
 
-        let source_content =
-            std::fs::read_to_string(output_dir.join("bulk-source.html")).unwrap();
-        assert!(source_content.contains("bulk-target.html"));
+    #[test]
+    fn test_bench_rejects_invalid_baseline() {
+        let env = TestEnv::new();
+        let baseline = env.notes_dir().join("baseline.json");
+        std::fs::write(&baseline, r#"{"data": {"results": 3}}"#).unwrap();
+
+        env.cmd()
+            .args(["bench", "--notes", "5", "--runs", "1"])
+            .args(["--baseline", baseline.to_str().unwrap()])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("is not a bench report"));
     }
 }
@@ -1,6 +1,7 @@
 //! Test fixture utilities for integration tests.
 
-pub mod harness;
+// Not every test binary uses every fixture helper
+#![allow(dead_code)]
 
 use std::path::{Path, PathBuf};
 